@@ -0,0 +1,150 @@
+-- Swictation companion for Neovim
+--
+-- Receives dictation from the daemon and inserts it at the cursor with
+-- nvim_put, so each segment is a single undo step, instead of letting the
+-- daemon type synthetic keystrokes into the terminal.
+--
+-- Add editors/nvim to your runtimepath (or install it with your plugin
+-- manager) and call:
+--
+--   require("swictation").setup()
+--
+-- Protocol (see rust-crates/swictation-daemon/src/editor.rs): connect to the
+-- IPC socket, send {"action":"editor_attach","client":"nvim"}, then read
+-- newline-delimited JSON events and report focus with {"focused":bool}.
+
+local M = {}
+
+local uv = vim.uv or vim.loop
+
+local config = {
+  -- Defaults to the daemon's IPC socket
+  socket = nil,
+  -- Seconds between reconnect attempts while the daemon is not running
+  reconnect_interval = 5,
+}
+
+local state = {
+  pipe = nil,
+  pending = "",
+  timer = nil,
+}
+
+local function socket_path()
+  if config.socket then
+    return config.socket
+  end
+  local runtime_dir = os.getenv("XDG_RUNTIME_DIR")
+  if runtime_dir and uv.fs_stat(runtime_dir) then
+    return runtime_dir .. "/swictation.sock"
+  end
+  if vim.fn.has("mac") == 1 then
+    return vim.fn.expand("~/Library/Application Support/swictation/swictation.sock")
+  end
+  return vim.fn.expand("~/.local/share/swictation/swictation.sock")
+end
+
+local function send(message)
+  if state.pipe then
+    state.pipe:write(vim.json.encode(message) .. "\n")
+  end
+end
+
+local function insert_at_cursor(text)
+  local lines = vim.split(text, "\n", { plain = true })
+  local mode = vim.api.nvim_get_mode().mode
+  -- In insert mode the text goes before the cursor, as if typed
+  local after = not mode:match("^i")
+  vim.api.nvim_put(lines, "c", after, true)
+end
+
+local function handle_line(line)
+  local ok, message = pcall(vim.json.decode, line)
+  if not ok or type(message) ~= "table" then
+    return
+  end
+  if message.status == "error" then
+    vim.notify("swictation: " .. tostring(message.error), vim.log.levels.WARN)
+  elseif message.event == "insert_at_cursor" and type(message.text) == "string" then
+    insert_at_cursor(message.text)
+  end
+end
+
+local function on_read(err, chunk)
+  if err or not chunk then
+    M.disconnect()
+    return
+  end
+  state.pending = state.pending .. chunk
+  while true do
+    local newline = state.pending:find("\n", 1, true)
+    if not newline then
+      break
+    end
+    local line = state.pending:sub(1, newline - 1)
+    state.pending = state.pending:sub(newline + 1)
+    vim.schedule(function()
+      handle_line(line)
+    end)
+  end
+end
+
+function M.connect()
+  if state.pipe then
+    return
+  end
+  local pipe = uv.new_pipe(false)
+  pipe:connect(socket_path(), function(err)
+    if err then
+      pipe:close()
+      return
+    end
+    state.pipe = pipe
+    state.pending = ""
+    send({ action = "editor_attach", client = "nvim" })
+    pipe:read_start(on_read)
+  end)
+end
+
+function M.disconnect()
+  if state.pipe then
+    state.pipe:close()
+    state.pipe = nil
+  end
+end
+
+function M.setup(opts)
+  config = vim.tbl_extend("force", config, opts or {})
+
+  local group = vim.api.nvim_create_augroup("swictation", { clear = true })
+  vim.api.nvim_create_autocmd("FocusGained", {
+    group = group,
+    callback = function()
+      send({ focused = true })
+    end,
+  })
+  vim.api.nvim_create_autocmd("FocusLost", {
+    group = group,
+    callback = function()
+      send({ focused = false })
+    end,
+  })
+  vim.api.nvim_create_autocmd("VimLeavePre", {
+    group = group,
+    callback = M.disconnect,
+  })
+
+  vim.api.nvim_create_user_command("SwictationAttach", M.connect, {})
+  vim.api.nvim_create_user_command("SwictationDetach", M.disconnect, {})
+
+  -- Attach now, and again whenever the daemon restarts
+  M.connect()
+  state.timer = uv.new_timer()
+  state.timer:start(0, config.reconnect_interval * 1000, function()
+    if not state.pipe then
+      M.connect()
+    end
+  end)
+end
+
+return M
@@ -17,6 +17,61 @@ use crate::AudioConfig;
 /// Callback for audio chunks (streaming mode)
 pub type ChunkCallback = Arc<dyn Fn(Vec<f32>) + Send + Sync>;
 
+/// Spare chunk buffers kept by a [`ChunkPool`]
+const MAX_SPARE_CHUNKS: usize = 16;
+
+/// Chunk buffers filled on the audio thread at start, before any are recycled
+const PREFILLED_CHUNKS: usize = 4;
+
+/// Reusable buffers for streaming-mode chunks
+///
+/// The audio thread takes each chunk's buffer from the pool instead of
+/// allocating it. Consumers hand buffers back with [`ChunkPool::recycle`]
+/// once they have copied the samples out; a chunk that is never recycled
+/// only costs one allocation when the pool runs dry.
+#[derive(Clone)]
+pub struct ChunkPool(Arc<Mutex<Vec<Vec<f32>>>>);
+
+impl Default for ChunkPool {
+    fn default() -> Self {
+        Self(Arc::new(Mutex::new(Vec::with_capacity(MAX_SPARE_CHUNKS))))
+    }
+}
+
+impl ChunkPool {
+    /// An empty buffer with room for at least `capacity` samples
+    fn take(&self, capacity: usize) -> Vec<f32> {
+        match self.0.lock().pop() {
+            Some(mut chunk) if chunk.capacity() >= capacity => {
+                chunk.clear();
+                chunk
+            }
+            _ => Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Return a chunk's buffer for reuse
+    pub fn recycle(&self, chunk: Vec<f32>) {
+        let mut spare = self.0.lock();
+        if spare.len() < MAX_SPARE_CHUNKS {
+            spare.push(chunk);
+        }
+    }
+
+    /// Stock up to `count` buffers of `capacity` samples
+    fn fill(&self, count: usize, capacity: usize) {
+        let mut spare = self.0.lock();
+        while spare.len() < count.min(MAX_SPARE_CHUNKS) {
+            spare.push(Vec::with_capacity(capacity));
+        }
+    }
+
+    /// Buffers waiting to be reused
+    pub fn spare(&self) -> usize {
+        self.0.lock().len()
+    }
+}
+
 /// Audio device information
 #[derive(Debug, Clone)]
 pub struct DeviceInfo {
@@ -39,6 +94,7 @@ pub struct AudioCapture {
     host: Host,
     device: Option<Device>,
     chunk_callback: Option<ChunkCallback>,
+    chunk_pool: ChunkPool,
     resampler: Arc<Mutex<Option<Resampler>>>,
     resample_buffer: Arc<Mutex<Vec<f32>>>, // Buffer for accumulating samples before resampling
 }
@@ -70,6 +126,7 @@ impl AudioCapture {
             host,
             device: None,
             chunk_callback: None,
+            chunk_pool: ChunkPool::default(),
             resampler: Arc::new(Mutex::new(None)),
             resample_buffer: Arc::new(Mutex::new(Vec::new())),
         })
@@ -83,6 +140,12 @@ impl AudioCapture {
         self.chunk_callback = Some(Arc::new(callback));
     }
 
+    /// Pool the chunk callback's buffers come from; recycle them here to
+    /// keep the audio thread from allocating
+    pub fn chunk_pool(&self) -> ChunkPool {
+        self.chunk_pool.clone()
+    }
+
     /// List all available audio devices
     pub fn list_devices() -> Result<Vec<DeviceInfo>> {
         let host = cpal::default_host();
@@ -364,12 +427,16 @@ impl AudioCapture {
         let total_frames = Arc::clone(&self.total_frames);
         let is_recording = Arc::clone(&self.is_recording);
         let chunk_callback = self.chunk_callback.clone();
+        let chunk_pool = self.chunk_pool.clone();
         let resampler = Arc::clone(&self.resampler);
         let resample_buffer = Arc::clone(&self.resample_buffer);
 
         let streaming_mode = self.config.streaming_mode;
         let chunk_frames = (self.config.chunk_duration * self.config.sample_rate as f32) as usize;
         let resample_chunk_size = (source_sample_rate as f32 * 0.1) as usize; // 100ms chunks at source rate
+        if streaming_mode {
+            chunk_pool.fill(PREFILLED_CHUNKS, chunk_frames);
+        }
 
         // Determine the sample format and build appropriate stream
         let sample_format = supported_config.sample_format();
@@ -379,6 +446,9 @@ impl AudioCapture {
         let stream = match sample_format {
             SampleFormat::I16 => {
                 // Build stream for i16 format (most common for USB mics)
+                // Scratch buffers live in the callback so steady-state capture reuses them
+                let mut f32_data: Vec<f32> = Vec::new();
+                let mut scratch = ScratchBuffers::default();
                 device.build_input_stream(
                     &stream_config,
                     move |data: &[i16], _: &cpal::InputCallbackInfo| {
@@ -387,19 +457,19 @@ impl AudioCapture {
                         }
 
                         // Convert i16 to f32 with proper normalization
-                        let f32_data: Vec<f32> = data
-                            .iter()
-                            .map(|&sample| sample as f32 / i16::MAX as f32)
-                            .collect();
+                        f32_data.clear();
+                        f32_data.extend(data.iter().map(|&sample| sample as f32 / i16::MAX as f32));
 
                         Self::process_audio_data(
                             &f32_data,
+                            &mut scratch,
                             source_channels,
                             target_channels,
                             &buffer,
                             &chunk_buffer,
                             &total_frames,
                             &chunk_callback,
+                            &chunk_pool,
                             &resampler,
                             &resample_buffer,
                             streaming_mode,
@@ -415,6 +485,7 @@ impl AudioCapture {
             }
             SampleFormat::F32 => {
                 // Build stream for f32 format
+                let mut scratch = ScratchBuffers::default();
                 device.build_input_stream(
                     &stream_config,
                     move |data: &[f32], _: &cpal::InputCallbackInfo| {
@@ -424,12 +495,14 @@ impl AudioCapture {
 
                         Self::process_audio_data(
                             data,
+                            &mut scratch,
                             source_channels,
                             target_channels,
                             &buffer,
                             &chunk_buffer,
                             &total_frames,
                             &chunk_callback,
+                            &chunk_pool,
                             &resampler,
                             &resample_buffer,
                             streaming_mode,
@@ -467,15 +540,20 @@ impl AudioCapture {
     }
 
     /// Common audio data processing logic
+    ///
+    /// Runs on the audio thread and works in reused buffers only: chunks
+    /// handed to the callback come from the [`ChunkPool`].
     #[allow(clippy::too_many_arguments)]
     fn process_audio_data(
         data: &[f32],
+        scratch: &mut ScratchBuffers,
         source_channels: u16,
         target_channels: u16,
         buffer: &Arc<Mutex<CircularBuffer>>,
         chunk_buffer: &Arc<Mutex<Vec<f32>>>,
         total_frames: &Arc<AtomicUsize>,
         chunk_callback: &Option<ChunkCallback>,
+        chunk_pool: &ChunkPool,
         resampler: &Arc<Mutex<Option<Resampler>>>,
         resample_buffer: &Arc<Mutex<Vec<f32>>>,
         streaming_mode: bool,
@@ -483,38 +561,39 @@ impl AudioCapture {
         resample_chunk_size: usize,
     ) {
         // Convert multi-channel to mono if needed
-        let mono_audio: Vec<f32> = if source_channels > target_channels {
+        let mut audio: &[f32] = if source_channels > target_channels {
             // Average all channels to preserve amplitude from any channel
-            data.chunks(source_channels as usize)
-                .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
-                .collect()
+            scratch.mono.clear();
+            scratch.mono.extend(
+                data.chunks(source_channels as usize)
+                    .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32),
+            );
+            &scratch.mono
         } else {
-            data.to_vec()
+            data
         };
 
         // Resample if needed
-        let mut audio = mono_audio;
         if resampler.lock().is_some() {
             // Accumulate samples for resampling
             let mut resample_buf = resample_buffer.lock();
-            resample_buf.extend_from_slice(&audio);
+            resample_buf.extend_from_slice(audio);
 
             // Process when we have enough samples
             if resample_buf.len() >= resample_chunk_size {
-                // Extract chunk
-                let chunk_to_resample: Vec<f32> =
-                    resample_buf.drain(..resample_chunk_size).collect();
-
-                // Resample
-                if let Some(ref mut resampler_lock) = resampler.lock().as_mut() {
-                    match resampler_lock.process(&chunk_to_resample) {
-                        Ok(resampled) => {
-                            audio = resampled;
-                        }
-                        Err(e) => {
-                            eprintln!("Resampling error: {}", e);
-                            return;
-                        }
+                // Resample the oldest chunk in place, then discard it
+                let result = match resampler.lock().as_mut() {
+                    Some(resampler_lock) => resampler_lock
+                        .process_into(&resample_buf[..resample_chunk_size], &mut scratch.resampled),
+                    None => return,
+                };
+                resample_buf.drain(..resample_chunk_size);
+
+                match result {
+                    Ok(()) => audio = &scratch.resampled,
+                    Err(e) => {
+                        eprintln!("Resampling error: {}", e);
+                        return;
                     }
                 }
             } else {
@@ -529,19 +608,16 @@ impl AudioCapture {
         // Streaming mode: accumulate chunks and invoke callback
         if streaming_mode {
             let mut chunk_buf = chunk_buffer.lock();
-            chunk_buf.extend_from_slice(&audio);
+            chunk_buf.extend_from_slice(audio);
 
             // Process complete chunks
             while chunk_buf.len() >= chunk_frames {
-                // Extract chunk
-                let chunk: Vec<f32> = chunk_buf.drain(..chunk_frames).collect();
+                // Extract chunk into a pooled buffer
+                let mut chunk = chunk_pool.take(chunk_frames);
+                chunk.extend(chunk_buf.drain(..chunk_frames));
 
                 // Invoke chunk callback if set
                 if let Some(ref callback) = chunk_callback {
-                    eprintln!(
-                        "AUDIO: Invoking chunk callback with {} samples",
-                        chunk.len()
-                    );
                     callback(chunk);
                 } else {
                     eprintln!("AUDIO: No chunk callback set!");
@@ -550,7 +626,7 @@ impl AudioCapture {
         } else {
             // Non-streaming mode: write to circular buffer for later retrieval
             let mut buf = buffer.lock();
-            let written = buf.write(&audio);
+            let written = buf.write(audio);
             if written < audio.len() {
                 eprintln!(
                     "Warning: Buffer overflow, dropped {} samples",
@@ -628,6 +704,15 @@ impl AudioCapture {
     }
 }
 
+/// Per-stream buffers reused by every audio callback
+#[derive(Default)]
+struct ScratchBuffers {
+    /// Input downmixed to mono
+    mono: Vec<f32>,
+    /// Output of the resampler
+    resampled: Vec<f32>,
+}
+
 impl Drop for AudioCapture {
     fn drop(&mut self) {
         if self.is_recording.load(Ordering::Relaxed) {
@@ -639,6 +724,109 @@ impl Drop for AudioCapture {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+    use std::time::Instant;
+
+    /// Counts heap allocations per thread, for the callback soak test
+    struct CountingAlloc;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    fn count_allocation() {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+    }
+
+    fn allocations() -> usize {
+        ALLOCATIONS.with(Cell::get)
+    }
+
+    unsafe impl GlobalAlloc for CountingAlloc {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            count_allocation();
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            count_allocation();
+            System.realloc(ptr, layout, new_size)
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountingAlloc = CountingAlloc;
+
+    #[test]
+    fn test_callback_soak_does_not_allocate() {
+        // 48 kHz stereo in 10 ms callbacks → 16 kHz mono in 0.5 s chunks
+        const CALLBACKS: u32 = 3000;
+        let chunk_frames = 8000;
+        let buffer = Arc::new(Mutex::new(CircularBuffer::new(16000)));
+        let chunk_buffer = Arc::new(Mutex::new(Vec::with_capacity(chunk_frames)));
+        let total_frames = Arc::new(AtomicUsize::new(0));
+        let resampler = Arc::new(Mutex::new(Some(Resampler::new(48000, 16000, 1).unwrap())));
+        let resample_buffer = Arc::new(Mutex::new(Vec::new()));
+        let pool = ChunkPool::default();
+        pool.fill(PREFILLED_CHUNKS, chunk_frames);
+
+        // A consumer that copies the samples out and recycles the buffer
+        let received = Arc::new(AtomicUsize::new(0));
+        let callback: Option<ChunkCallback> = {
+            let pool = pool.clone();
+            let received = Arc::clone(&received);
+            Some(Arc::new(move |chunk: Vec<f32>| {
+                received.fetch_add(chunk.len(), Ordering::Relaxed);
+                pool.recycle(chunk);
+            }))
+        };
+
+        let data: Vec<f32> = (0..960)
+            .map(|i| ((i / 2) as f32 * 0.05).sin() * 0.5)
+            .collect();
+        let mut scratch = ScratchBuffers::default();
+        let mut run = |callbacks: u32| {
+            for _ in 0..callbacks {
+                AudioCapture::process_audio_data(
+                    &data,
+                    &mut scratch,
+                    2,
+                    1,
+                    &buffer,
+                    &chunk_buffer,
+                    &total_frames,
+                    &callback,
+                    &pool,
+                    &resampler,
+                    &resample_buffer,
+                    true,
+                    chunk_frames,
+                    4800,
+                );
+            }
+        };
+
+        // Scratch buffers reach their working size within the first second
+        run(100);
+        let before = allocations();
+        let started = Instant::now();
+        run(CALLBACKS);
+        let elapsed = started.elapsed();
+
+        assert_eq!(allocations() - before, 0, "audio callback allocated");
+        assert!(received.load(Ordering::Relaxed) >= 50 * chunk_frames);
+        println!(
+            "{} callbacks ({} s of audio): {:?} per callback",
+            CALLBACKS,
+            CALLBACKS / 100,
+            elapsed / CALLBACKS
+        );
+    }
 
     #[test]
     fn test_list_devices() {
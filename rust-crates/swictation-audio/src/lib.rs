@@ -7,6 +7,8 @@
 //! - Zero-copy lock-free circular buffer
 //! - Native PipeWire/ALSA integration via cpal
 //! - Real-time resampling to 16kHz mono
+//! - Energy-based silence trimming of speech segments
+//! - Per-segment prosody analysis (pitch spread, energy, speech time)
 //! - PyO3 bindings for Python integration
 //! - Predictable sub-100μs callback latency
 //!
@@ -27,12 +29,16 @@
 pub mod buffer;
 pub mod capture;
 pub mod error;
+pub mod prosody;
 pub mod resampler;
+pub mod trim;
 
 pub use buffer::CircularBuffer;
-pub use capture::AudioCapture;
+pub use capture::{AudioCapture, ChunkPool};
 pub use error::{AudioError, Result};
+pub use prosody::Prosody;
 pub use resampler::Resampler;
+pub use trim::{trim_silence, TrimConfig, TrimResult};
 
 /// Audio sample rate constant (16kHz for STT models)
 pub const TARGET_SAMPLE_RATE: u32 = 16000;
@@ -0,0 +1,167 @@
+//! Prosody analysis of speech segments
+//!
+//! Cheap per-segment measurements for pacing feedback: how much the pitch
+//! moves (monotone delivery shows up as a low spread), how loud the speech
+//! is, and how much of the segment was actually voiced, from which the
+//! caller derives speaking rate. Pitch is estimated per frame with
+//! normalized autocorrelation, which is plenty for a spread statistic.
+
+/// Analysis frame length in milliseconds
+const FRAME_MS: usize = 40;
+/// Hop between frames in milliseconds
+const HOP_MS: usize = 20;
+/// Frames quieter than this RMS are not speech
+const SILENCE_RMS: f32 = 0.01;
+/// Pitch search range in Hz (covers adult speaking voices)
+const MIN_PITCH_HZ: f32 = 70.0;
+const MAX_PITCH_HZ: f32 = 400.0;
+/// Normalized autocorrelation needed to call a frame voiced
+const VOICING_THRESHOLD: f32 = 0.5;
+/// Voiced frames needed before a pitch spread is reported
+const MIN_PITCHED_FRAMES: usize = 3;
+
+/// Prosody of one segment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Prosody {
+    /// Mean pitch of the voiced frames, `None` when too few were pitched
+    pub pitch_mean_hz: Option<f32>,
+    /// Standard deviation of pitch in semitones (`None` as above)
+    pub pitch_std_semitones: Option<f32>,
+    /// Mean RMS level of the speech frames in dBFS
+    pub energy_dbfs: f32,
+    /// Seconds of the segment above the silence level
+    pub speech_s: f32,
+}
+
+impl Prosody {
+    /// Words per minute of actual speech (pauses inside the segment excluded)
+    pub fn speaking_rate_wpm(&self, words: usize) -> f32 {
+        if self.speech_s > 0.0 {
+            words as f32 * 60.0 / self.speech_s
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Analyze a mono segment sampled at `sample_rate`
+pub fn analyze(samples: &[f32], sample_rate: u32) -> Prosody {
+    let frame_len = sample_rate as usize * FRAME_MS / 1000;
+    let hop = sample_rate as usize * HOP_MS / 1000;
+    let min_lag = (sample_rate as f32 / MAX_PITCH_HZ) as usize;
+    let max_lag = ((sample_rate as f32 / MIN_PITCH_HZ) as usize).min(frame_len - 1);
+
+    let mut energy_sum = 0.0f32;
+    let mut speech_frames = 0usize;
+    let mut pitches = Vec::new();
+
+    let mut start = 0;
+    while start + frame_len <= samples.len() {
+        let frame = &samples[start..start + frame_len];
+        start += hop;
+
+        let rms = (frame.iter().map(|s| s * s).sum::<f32>() / frame_len as f32).sqrt();
+        if rms < SILENCE_RMS {
+            continue;
+        }
+        speech_frames += 1;
+        energy_sum += 20.0 * rms.log10();
+
+        if let Some(pitch) = frame_pitch(frame, min_lag, max_lag, sample_rate) {
+            pitches.push(pitch);
+        }
+    }
+
+    let (pitch_mean_hz, pitch_std_semitones) = if pitches.len() >= MIN_PITCHED_FRAMES {
+        let mean_hz = pitches.iter().sum::<f32>() / pitches.len() as f32;
+        // Semitones make the spread comparable between low and high voices
+        let semitones: Vec<f32> = pitches
+            .iter()
+            .map(|f| 12.0 * (f / mean_hz).log2())
+            .collect();
+        let mean = semitones.iter().sum::<f32>() / semitones.len() as f32;
+        let variance =
+            semitones.iter().map(|s| (s - mean).powi(2)).sum::<f32>() / semitones.len() as f32;
+        (Some(mean_hz), Some(variance.sqrt()))
+    } else {
+        (None, None)
+    };
+
+    Prosody {
+        pitch_mean_hz,
+        pitch_std_semitones,
+        energy_dbfs: if speech_frames > 0 {
+            energy_sum / speech_frames as f32
+        } else {
+            f32::NEG_INFINITY
+        },
+        speech_s: (speech_frames * hop) as f32 / sample_rate as f32,
+    }
+}
+
+/// Pitch of a frame from its strongest autocorrelation peak, if voiced
+fn frame_pitch(frame: &[f32], min_lag: usize, max_lag: usize, sample_rate: u32) -> Option<f32> {
+    let energy: f32 = frame.iter().map(|s| s * s).sum();
+    if energy <= 0.0 || min_lag >= max_lag {
+        return None;
+    }
+
+    let (lag, correlation) = (min_lag..=max_lag)
+        .map(|lag| {
+            let sum: f32 = frame[..frame.len() - lag]
+                .iter()
+                .zip(&frame[lag..])
+                .map(|(a, b)| a * b)
+                .sum();
+            (lag, sum / energy)
+        })
+        .fold((0, f32::MIN), |best, candidate| {
+            if candidate.1 > best.1 {
+                candidate
+            } else {
+                best
+            }
+        });
+
+    (correlation >= VOICING_THRESHOLD).then(|| sample_rate as f32 / lag as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(hz: impl Fn(f32) -> f32, seconds: f32) -> Vec<f32> {
+        let mut phase = 0.0f32;
+        (0..(16000.0 * seconds) as usize)
+            .map(|i| {
+                phase += 2.0 * std::f32::consts::PI * hz(i as f32 / 16000.0) / 16000.0;
+                0.3 * phase.sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_steady_tone_has_flat_pitch() {
+        let prosody = analyze(&tone(|_| 200.0, 1.0), 16000);
+
+        let pitch = prosody.pitch_mean_hz.unwrap();
+        assert!((pitch - 200.0).abs() < 5.0, "pitch {}", pitch);
+        assert!(prosody.pitch_std_semitones.unwrap() < 0.5);
+        assert!((prosody.energy_dbfs - -13.5).abs() < 1.0);
+        assert!((prosody.speech_s - 0.98).abs() < 0.05);
+        assert_eq!(prosody.speaking_rate_wpm(0), 0.0);
+    }
+
+    #[test]
+    fn test_gliding_tone_has_pitch_spread() {
+        let prosody = analyze(&tone(|t| 120.0 + 120.0 * t, 1.0), 16000);
+        assert!(prosody.pitch_std_semitones.unwrap() > 2.0);
+    }
+
+    #[test]
+    fn test_silence() {
+        let prosody = analyze(&vec![0.0; 16000], 16000);
+        assert_eq!(prosody.pitch_mean_hz, None);
+        assert_eq!(prosody.speech_s, 0.0);
+    }
+}
@@ -15,6 +15,9 @@ pub struct Resampler {
     target_rate: u32,
     channels: u16,
     resampler: Option<SincFixedIn<f32>>,
+    /// Planar scratch buffers, sized once so `process_into` never allocates
+    planar_input: Vec<Vec<f32>>,
+    planar_output: Vec<Vec<f32>>,
 }
 
 impl Resampler {
@@ -40,12 +43,21 @@ impl Resampler {
         } else {
             None
         };
+        let (planar_input, planar_output) = match &resampler {
+            Some(r) => (
+                r.input_buffer_allocate(false),
+                r.output_buffer_allocate(true),
+            ),
+            None => (Vec::new(), Vec::new()),
+        };
 
         Ok(Self {
             source_rate,
             target_rate,
             channels,
             resampler,
+            planar_input,
+            planar_output,
         })
     }
 
@@ -89,43 +101,49 @@ impl Resampler {
     ///
     /// Resampled audio at target sample rate
     pub fn process(&mut self, input: &[f32]) -> Result<Vec<f32>> {
-        // If no resampling needed, return input as-is
-        if self.resampler.is_none() {
-            return Ok(input.to_vec());
-        }
+        let mut output = Vec::new();
+        self.process_into(input, &mut output)?;
+        Ok(output)
+    }
+
+    /// Resample audio data into `output`, replacing its contents
+    ///
+    /// Works in buffers sized when the resampler was created, so once
+    /// `output` has grown to a chunk's worth of samples this does not
+    /// allocate (as long as `input` is at most one chunk of 100 ms).
+    pub fn process_into(&mut self, input: &[f32], output: &mut Vec<f32>) -> Result<()> {
+        output.clear();
+
+        // If no resampling needed, pass input through as-is
+        let Some(resampler) = self.resampler.as_mut() else {
+            output.extend_from_slice(input);
+            return Ok(());
+        };
 
         if input.is_empty() {
-            return Ok(Vec::new());
+            return Ok(());
         }
 
-        let resampler = self.resampler.as_mut().unwrap();
-
-        // Convert interleaved to planar format (rubato expects Vec<Vec<f32>>)
-        let frames = input.len() / self.channels as usize;
-        let mut planar_input = vec![vec![0.0f32; frames]; self.channels as usize];
-
-        for (frame_idx, frame) in input.chunks(self.channels as usize).enumerate() {
-            for (ch_idx, &sample) in frame.iter().enumerate() {
-                planar_input[ch_idx][frame_idx] = sample;
-            }
+        // Deinterleave into planar format (rubato expects one slice per channel)
+        let channels = self.channels as usize;
+        for (ch_idx, channel) in self.planar_input.iter_mut().enumerate() {
+            channel.clear();
+            channel.extend(input.iter().skip(ch_idx).step_by(channels));
         }
 
         // Process with resampler
-        let planar_output = resampler
-            .process(&planar_input, None)
+        let (_, output_frames) = resampler
+            .process_into_buffer(&self.planar_input, &mut self.planar_output, None)
             .map_err(|e| AudioError::ResampleError(format!("Resampling failed: {:?}", e)))?;
 
         // Convert planar back to interleaved
-        let output_frames = planar_output[0].len();
-        let mut interleaved_output = Vec::with_capacity(output_frames * self.channels as usize);
-
         for frame_idx in 0..output_frames {
-            for channel_data in planar_output.iter().take(self.channels as usize) {
-                interleaved_output.push(channel_data[frame_idx]);
+            for channel_data in &self.planar_output {
+                output.push(channel_data[frame_idx]);
             }
         }
 
-        Ok(interleaved_output)
+        Ok(())
     }
 
     /// Convert stereo to mono by averaging channels
@@ -186,6 +204,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_process_into_reuses_output() {
+        let mut resampler = Resampler::new(48000, 16000, 1).unwrap();
+        let input = vec![0.25f32; 4800];
+        let mut output = Vec::new();
+
+        resampler.process_into(&input, &mut output).unwrap();
+        let capacity = output.capacity();
+        resampler.process_into(&input, &mut output).unwrap();
+
+        // Each call replaces the previous output (~1600 samples) in place
+        assert!(output.len() > 1500 && output.len() <= 1600);
+        assert_eq!(output.capacity(), capacity);
+    }
+
     #[test]
     fn test_stereo_to_mono() {
         let stereo = vec![0.5, 0.3, 0.1, -0.1, 0.2, 0.4];
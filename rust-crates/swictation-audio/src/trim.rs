@@ -0,0 +1,149 @@
+//! Energy-based silence trimming
+//!
+//! VAD segments usually carry a few hundred milliseconds of near-silence at
+//! each end (the VAD needs `min_silence` of quiet before it closes a
+//! segment). Trimming that before recognition saves STT compute, which is
+//! noticeable on CPU-only systems.
+
+/// Silence trimming parameters
+#[derive(Debug, Clone)]
+pub struct TrimConfig {
+    /// Analysis frame length in milliseconds
+    pub frame_ms: u32,
+
+    /// Frames quieter than this RMS (linear, 0.0-1.0) are silence
+    pub silence_rms: f32,
+
+    /// Frames quieter than this fraction of the loudest frame are also silence,
+    /// so trimming adapts to quiet microphones
+    pub relative_threshold: f32,
+
+    /// Audio kept before the first and after the last voiced frame
+    pub pad_ms: u32,
+}
+
+impl Default for TrimConfig {
+    fn default() -> Self {
+        Self {
+            frame_ms: 10,
+            silence_rms: 0.005,
+            relative_threshold: 0.05,
+            pad_ms: 100,
+        }
+    }
+}
+
+/// Result of trimming: the kept sample range
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrimResult {
+    /// First kept sample
+    pub start: usize,
+    /// One past the last kept sample
+    pub end: usize,
+    /// Original segment length in samples
+    pub original_len: usize,
+}
+
+impl TrimResult {
+    /// Number of samples removed from both ends
+    pub fn trimmed_samples(&self) -> usize {
+        self.original_len - (self.end - self.start)
+    }
+
+    /// Removed audio in milliseconds
+    pub fn trimmed_ms(&self, sample_rate: u32) -> f64 {
+        self.trimmed_samples() as f64 * 1000.0 / sample_rate as f64
+    }
+}
+
+/// Find the voiced part of `samples`, keeping `pad_ms` of context on each side
+///
+/// If no frame rises above the threshold the whole segment is kept - the VAD
+/// already decided it contains speech, so it is not this pass's job to drop it.
+pub fn trim_silence(samples: &[f32], sample_rate: u32, config: &TrimConfig) -> TrimResult {
+    let keep_all = TrimResult {
+        start: 0,
+        end: samples.len(),
+        original_len: samples.len(),
+    };
+
+    let frame_len = (sample_rate as usize * config.frame_ms as usize / 1000).max(1);
+    let rms: Vec<f32> = samples
+        .chunks(frame_len)
+        .map(|frame| (frame.iter().map(|s| s * s).sum::<f32>() / frame.len() as f32).sqrt())
+        .collect();
+
+    let peak = rms.iter().cloned().fold(0.0f32, f32::max);
+    let threshold = config.silence_rms.max(peak * config.relative_threshold);
+
+    let (first, last) = match (
+        rms.iter().position(|&r| r > threshold),
+        rms.iter().rposition(|&r| r > threshold),
+    ) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return keep_all,
+    };
+
+    let pad = sample_rate as usize * config.pad_ms as usize / 1000;
+    TrimResult {
+        start: (first * frame_len).saturating_sub(pad),
+        end: ((last + 1) * frame_len + pad).min(samples.len()),
+        original_len: samples.len(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone(len: usize, amplitude: f32) -> Vec<f32> {
+        (0..len)
+            .map(|i| (i as f32 * 440.0 * 2.0 * std::f32::consts::PI / 16000.0).sin() * amplitude)
+            .collect()
+    }
+
+    #[test]
+    fn test_trims_leading_and_trailing_silence() {
+        // 500ms silence, 1s tone, 300ms silence
+        let mut samples = vec![0.0; 8000];
+        samples.extend(tone(16000, 0.5));
+        samples.extend(vec![0.0; 4800]);
+
+        let result = trim_silence(&samples, 16000, &TrimConfig::default());
+
+        // 100ms pad on each side
+        assert_eq!(result.start, 8000 - 1600);
+        assert_eq!(result.end, 8000 + 16000 + 1600);
+        assert_eq!(result.trimmed_samples(), 6400 + 3200);
+        assert_eq!(result.trimmed_ms(16000), 600.0);
+    }
+
+    #[test]
+    fn test_all_silence_keeps_segment() {
+        let samples = vec![0.0; 16000];
+        let result = trim_silence(&samples, 16000, &TrimConfig::default());
+        assert_eq!(result.trimmed_samples(), 0);
+    }
+
+    #[test]
+    fn test_pad_clamped_to_bounds() {
+        let mut samples = tone(800, 0.5);
+        samples.extend(vec![0.0; 800]);
+
+        let result = trim_silence(&samples, 16000, &TrimConfig::default());
+        assert_eq!(result.start, 0);
+        assert_eq!(result.end, samples.len());
+    }
+
+    #[test]
+    fn test_relative_threshold_ignores_background_noise() {
+        // Steady hiss above the absolute floor but far below the speech level
+        let mut samples = tone(8000, 0.02);
+        samples.extend(tone(16000, 0.8));
+        samples.extend(tone(8000, 0.02));
+
+        let result = trim_silence(&samples, 16000, &TrimConfig::default());
+        assert_eq!(result.start, 8000 - 1600);
+        assert_eq!(result.end, 24000 + 1600);
+    }
+}
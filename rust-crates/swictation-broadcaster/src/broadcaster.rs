@@ -1,26 +1,165 @@
 use chrono::Local;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use swictation_metrics::{DaemonState, RealtimeMetrics};
+use swictation_metrics::{
+    DaemonState, InferenceMetadata, JobStatus, LatencyWarning, RealtimeMetrics, StorageStatus,
+};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::net::unix::OwnedReadHalf;
 use tokio::net::UnixListener;
 use tokio::sync::{Mutex, RwLock};
 use tokio::task::JoinHandle;
 
 use crate::client::{Client, ClientManager};
 use crate::error::{BroadcasterError, Result};
-use crate::events::{BroadcastEvent, TranscriptionSegment};
+use crate::events::{
+    BroadcastEvent, ReadinessStage, ScriptPosition, TranscriptionSegment, UncertainWord,
+};
+use crate::protocol::Hello;
 
 /// Real-time metrics broadcaster for UI clients
+///
+/// The metrics socket carries state and per-segment events. High-rate
+/// events (audio levels) go only to the optional firehose socket, so simple
+/// consumers such as status bars never see them.
 pub struct MetricsBroadcaster {
     socket_path: PathBuf,
     client_manager: ClientManager,
+    firehose_path: Option<PathBuf>,
+    firehose_manager: ClientManager,
     transcription_buffer: Arc<RwLock<Vec<TranscriptionSegment>>>,
     last_state: Arc<RwLock<String>>,
+    /// Last `readiness` event, replayed to clients joining during boot
+    readiness: Arc<RwLock<Option<BroadcastEvent>>>,
     current_session_id: Arc<RwLock<Option<i64>>>,
-    accept_task: Arc<Mutex<Option<JoinHandle<()>>>>,
+    private_mode: Arc<RwLock<bool>>,
+    accept_tasks: Arc<Mutex<Vec<JoinHandle<()>>>>,
     running: Arc<RwLock<bool>>,
 }
 
+/// Shared state a client is caught up from, and the list it joins
+#[derive(Clone)]
+struct CatchUp {
+    clients: Arc<Mutex<Vec<Client>>>,
+    buffer: Arc<RwLock<Vec<TranscriptionSegment>>>,
+    state: Arc<RwLock<String>>,
+    readiness: Arc<RwLock<Option<BroadcastEvent>>>,
+    session_id: Arc<RwLock<Option<i64>>>,
+    private_mode: Arc<RwLock<bool>>,
+    /// Whether clients get the state and buffer on joining (not on the firehose)
+    replay: bool,
+}
+
+impl CatchUp {
+    /// Send the catch-up to a new client and add it to the list
+    async fn register(&self, mut client: Client) -> Result<()> {
+        // Hold the buffer lock until the client is registered: events that
+        // change catch-up data are then either part of the catch-up or
+        // broadcast to this client afterwards - never both, never neither
+        let buffer = self.buffer.read().await;
+        let state = self.state.read().await.clone();
+        let session_id = *self.session_id.read().await;
+        let private_mode = *self.private_mode.read().await;
+        let readiness = self.readiness.read().await.clone();
+
+        if self.replay {
+            client
+                .send_catch_up(
+                    readiness.as_ref(),
+                    &state,
+                    session_id,
+                    private_mode,
+                    &buffer,
+                )
+                .await?;
+        }
+
+        let mut clients = self.clients.lock().await;
+        clients.push(client);
+        tracing::info!("Client added. Total: {}", clients.len());
+        Ok(())
+    }
+
+    /// Switch client `id` to the requested format and catch it up again
+    ///
+    /// Returns `false` if the client is no longer connected.
+    async fn switch_format(&self, id: u64, hello: Hello) -> Result<bool> {
+        let buffer = self.buffer.read().await;
+        let state = self.state.read().await.clone();
+        let session_id = *self.session_id.read().await;
+        let private_mode = *self.private_mode.read().await;
+        let readiness = self.readiness.read().await.clone();
+
+        let mut clients = self.clients.lock().await;
+        let Some(client) = clients.iter_mut().find(|client| client.id() == id) else {
+            return Ok(false);
+        };
+        client.set_format(hello).await?;
+        if self.replay {
+            client
+                .send_catch_up(
+                    readiness.as_ref(),
+                    &state,
+                    session_id,
+                    private_mode,
+                    &buffer,
+                )
+                .await?;
+        }
+        Ok(true)
+    }
+}
+
+/// Listen on `path` (replacing a stale socket file), owner-only
+fn bind(path: &Path) -> Result<UnixListener> {
+    // Remove existing socket file
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+
+    // Create Unix socket listener
+    let listener = UnixListener::bind(path)?;
+
+    // Set secure permissions (0600 = owner-only access)
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if path.exists() {
+            let permissions = std::fs::Permissions::from_mode(0o600);
+            std::fs::set_permissions(path, permissions)?;
+        }
+    }
+
+    Ok(listener)
+}
+
+/// Apply the format requests a client writes (see [`crate::protocol`])
+async fn read_format_requests(reader: OwnedReadHalf, id: u64, catch_up: CatchUp) {
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let hello = match Hello::parse(&line) {
+            Ok(hello) => hello,
+            Err(e) => {
+                tracing::warn!("Ignoring message from client {}: {}", id, e);
+                continue;
+            }
+        };
+        match catch_up.switch_format(id, hello).await {
+            Ok(true) => tracing::info!(
+                "Client {} switched to {:?} with {:?} compression",
+                id,
+                hello.encoding,
+                hello.compression
+            ),
+            Ok(false) => break,
+            Err(e) => tracing::warn!("Failed to switch client {} format: {}", id, e),
+        }
+    }
+}
+
 impl MetricsBroadcaster {
     /// Create new broadcaster
     pub async fn new(socket_path: impl AsRef<Path>) -> Result<Self> {
@@ -29,14 +168,24 @@ impl MetricsBroadcaster {
         Ok(Self {
             socket_path,
             client_manager: ClientManager::new(),
+            firehose_path: None,
+            firehose_manager: ClientManager::new(),
             transcription_buffer: Arc::new(RwLock::new(Vec::new())),
             last_state: Arc::new(RwLock::new("idle".to_string())),
+            readiness: Arc::new(RwLock::new(None)),
             current_session_id: Arc::new(RwLock::new(None)),
-            accept_task: Arc::new(Mutex::new(None)),
+            private_mode: Arc::new(RwLock::new(false)),
+            accept_tasks: Arc::new(Mutex::new(Vec::new())),
             running: Arc::new(RwLock::new(false)),
         })
     }
 
+    /// Also serve high-rate events on `socket_path` (see [`Self::broadcast_audio_level`])
+    pub fn with_firehose(mut self, socket_path: impl AsRef<Path>) -> Self {
+        self.firehose_path = Some(socket_path.as_ref().to_path_buf());
+        self
+    }
+
     /// Start the broadcaster (listen for clients)
     pub async fn start(&self) -> Result<()> {
         let is_running = *self.running.read().await;
@@ -44,40 +193,51 @@ impl MetricsBroadcaster {
             return Err(BroadcasterError::AlreadyRunning);
         }
 
-        // Remove existing socket file
-        if self.socket_path.exists() {
-            std::fs::remove_file(&self.socket_path)?;
-        }
-
-        // Create Unix socket listener
-        let listener = UnixListener::bind(&self.socket_path)?;
-
-        // Set secure permissions (0600 = owner-only access)
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            if self.socket_path.exists() {
-                let permissions = std::fs::Permissions::from_mode(0o600);
-                std::fs::set_permissions(&self.socket_path, permissions)?;
-            }
-        }
+        let listener = bind(&self.socket_path)?;
+        let firehose = match &self.firehose_path {
+            Some(path) => Some(bind(path)?),
+            None => None,
+        };
 
         tracing::info!(
             "Metrics broadcaster started on {:?} (permissions: 0600)",
             self.socket_path
         );
+        if let Some(path) = &self.firehose_path {
+            tracing::info!("Metrics firehose started on {:?}", path);
+        }
 
         // Mark as running
         *self.running.write().await = true;
 
-        // Spawn client acceptance task
-        let client_manager = self.client_manager.clone_arc();
-        let buffer = Arc::clone(&self.transcription_buffer);
-        let state = Arc::clone(&self.last_state);
-        let session_id = Arc::clone(&self.current_session_id);
-        let running = Arc::clone(&self.running);
+        // Spawn client acceptance tasks
+        let catch_up = CatchUp {
+            clients: self.client_manager.clone_arc(),
+            buffer: Arc::clone(&self.transcription_buffer),
+            state: Arc::clone(&self.last_state),
+            readiness: Arc::clone(&self.readiness),
+            session_id: Arc::clone(&self.current_session_id),
+            private_mode: Arc::clone(&self.private_mode),
+            replay: true,
+        };
+        let mut tasks = self.accept_tasks.lock().await;
+        if let Some(firehose) = firehose {
+            let firehose_catch_up = CatchUp {
+                clients: self.firehose_manager.clone_arc(),
+                replay: false,
+                ..catch_up.clone()
+            };
+            tasks.push(self.spawn_accept(firehose, firehose_catch_up));
+        }
+        tasks.push(self.spawn_accept(listener, catch_up));
 
-        let task = tokio::spawn(async move {
+        Ok(())
+    }
+
+    fn spawn_accept(&self, listener: UnixListener, catch_up: CatchUp) -> JoinHandle<()> {
+        let running = Arc::clone(&self.running);
+        tokio::spawn(async move {
+            let mut next_client_id = 0;
             loop {
                 // Check if still running
                 if !*running.read().await {
@@ -87,25 +247,19 @@ impl MetricsBroadcaster {
                 match listener.accept().await {
                     Ok((stream, _addr)) => {
                         tracing::info!("New client connection accepted");
-                        let mut client = Client::new(stream);
-
-                        // Send catch-up data
-                        let current_state = state.read().await.clone();
-                        let current_session = *session_id.read().await;
-                        let buffer_snapshot = buffer.read().await.clone();
+                        next_client_id += 1;
+                        let (reader, writer) = stream.into_split();
 
-                        if let Err(e) = client
-                            .send_catch_up(&current_state, current_session, &buffer_snapshot)
-                            .await
+                        if let Err(e) = catch_up.register(Client::new(next_client_id, writer)).await
                         {
                             tracing::warn!("Failed to send catch-up data: {}", e);
                             continue;
                         }
-
-                        // Add to client list
-                        let mut clients = client_manager.lock().await;
-                        clients.push(client);
-                        tracing::info!("Client added. Total: {}", clients.len());
+                        tokio::spawn(read_format_requests(
+                            reader,
+                            next_client_id,
+                            catch_up.clone(),
+                        ));
                     }
                     Err(e) => {
                         tracing::error!("Failed to accept client: {}", e);
@@ -113,11 +267,7 @@ impl MetricsBroadcaster {
                 }
             }
             tracing::info!("Client acceptance task stopped");
-        });
-
-        *self.accept_task.lock().await = Some(task);
-
-        Ok(())
+        })
     }
 
     /// Stop the broadcaster
@@ -130,14 +280,16 @@ impl MetricsBroadcaster {
         // Mark as not running
         *self.running.write().await = false;
 
-        // Abort accept task
-        if let Some(task) = self.accept_task.lock().await.take() {
+        // Abort accept tasks
+        for task in self.accept_tasks.lock().await.drain(..) {
             task.abort();
         }
 
-        // Remove socket file
-        if self.socket_path.exists() {
-            std::fs::remove_file(&self.socket_path)?;
+        // Remove socket files
+        for path in std::iter::once(&self.socket_path).chain(&self.firehose_path) {
+            if path.exists() {
+                std::fs::remove_file(path)?;
+            }
         }
 
         tracing::info!("Metrics broadcaster stopped");
@@ -146,8 +298,9 @@ impl MetricsBroadcaster {
 
     /// Start a new session (clears transcription buffer)
     pub async fn start_session(&self, session_id: i64) {
-        // Clear buffer
-        self.transcription_buffer.write().await.clear();
+        // Clear buffer (guard held through the broadcast, see `start`)
+        let mut buffer = self.transcription_buffer.write().await;
+        buffer.clear();
 
         // Update session ID
         *self.current_session_id.write().await = Some(session_id);
@@ -167,6 +320,8 @@ impl MetricsBroadcaster {
 
     /// End session (buffer stays visible)
     pub async fn end_session(&self, session_id: i64) {
+        let _catch_up_guard = self.transcription_buffer.write().await;
+
         // Update session ID
         *self.current_session_id.write().await = None;
 
@@ -183,30 +338,74 @@ impl MetricsBroadcaster {
         tracing::info!("Session ended: {}", session_id);
     }
 
+    /// Drop the buffered transcriptions, so new clients don't catch up on them
+    pub async fn clear_transcriptions(&self) {
+        self.transcription_buffer.write().await.clear();
+    }
+
     /// Add transcription segment to buffer and broadcast
     pub async fn add_transcription(&self, text: String, wpm: f64, latency_ms: f64, words: i32) {
-        let timestamp = Self::current_time_string();
+        self.add_transcription_with_inference(text, wpm, latency_ms, words, None)
+            .await;
+    }
 
-        // Create segment
-        let segment = TranscriptionSegment {
-            text: text.clone(),
-            timestamp: timestamp.clone(),
+    /// Add transcription segment tagged with the model/provider that produced it
+    pub async fn add_transcription_with_inference(
+        &self,
+        text: String,
+        wpm: f64,
+        latency_ms: f64,
+        words: i32,
+        inference: Option<InferenceMetadata>,
+    ) {
+        self.push_transcription(TranscriptionSegment {
+            text,
+            timestamp: Self::current_time_string(),
             wpm,
             latency_ms,
             words,
-        };
-
-        // Add to buffer
-        self.transcription_buffer.write().await.push(segment);
+            inference,
+            session_id: None,
+            segment_id: None,
+        })
+        .await;
+    }
 
-        // Broadcast event
-        let event = BroadcastEvent::Transcription {
+    /// Add a transcription segment stored in the metrics database
+    ///
+    /// `segment_id` is the row `insert_segment` returned (`None` if storing
+    /// failed). Both ids go out with the event so clients can correlate it
+    /// with the database, e.g. to edit or bookmark the segment later.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn add_stored_transcription(
+        &self,
+        session_id: i64,
+        segment_id: Option<i64>,
+        text: String,
+        wpm: f64,
+        latency_ms: f64,
+        words: i32,
+        inference: Option<InferenceMetadata>,
+    ) {
+        self.push_transcription(TranscriptionSegment {
             text,
-            timestamp,
+            timestamp: Self::current_time_string(),
             wpm,
             latency_ms,
             words,
-        };
+            inference,
+            session_id: Some(session_id),
+            segment_id,
+        })
+        .await;
+    }
+
+    async fn push_transcription(&self, segment: TranscriptionSegment) {
+        let event = segment.to_event();
+
+        // Add to buffer (guard held through the broadcast, see `start`)
+        let mut buffer = self.transcription_buffer.write().await;
+        buffer.push(segment);
 
         if let Err(e) = self.client_manager.broadcast(&event).await {
             tracing::error!("Failed to broadcast transcription: {}", e);
@@ -238,6 +437,7 @@ impl MetricsBroadcaster {
     /// Broadcast daemon state change
     pub async fn broadcast_state_change(&self, state: DaemonState) {
         let state_str = Self::daemon_state_to_string(&state);
+        let _catch_up_guard = self.transcription_buffer.write().await;
 
         // Update last state
         *self.last_state.write().await = state_str.clone();
@@ -252,11 +452,274 @@ impl MetricsBroadcaster {
         }
     }
 
+    /// Announce that a weekly summary digest is available
+    pub async fn broadcast_weekly_summary(&self, week: &str, path: &Path) {
+        let event = BroadcastEvent::WeeklySummaryReady {
+            week: week.to_string(),
+            path: path.display().to_string(),
+            timestamp: Self::current_timestamp(),
+        };
+
+        if let Err(e) = self.client_manager.broadcast(&event).await {
+            tracing::error!("Failed to broadcast weekly_summary_ready: {}", e);
+        }
+    }
+
+    /// Announce new live VAD tuning values
+    pub async fn broadcast_vad_config(
+        &self,
+        threshold: f32,
+        min_silence: f32,
+        min_speech: f32,
+        persisted: bool,
+    ) {
+        let event = BroadcastEvent::VadConfigChanged {
+            threshold,
+            min_silence,
+            min_speech,
+            persisted,
+            timestamp: Self::current_timestamp(),
+        };
+
+        if let Err(e) = self.client_manager.broadcast(&event).await {
+            tracing::error!("Failed to broadcast vad_config_changed: {}", e);
+        }
+    }
+
+    /// Broadcast a segment that went over its latency budget
+    pub async fn broadcast_latency_warning(&self, warning: LatencyWarning) {
+        let event = BroadcastEvent::LatencyWarning {
+            exceeded: warning.exceeded,
+            breakdown: warning.breakdown,
+            budgets: warning.budgets,
+            timestamp: Self::current_timestamp(),
+        };
+
+        if let Err(e) = self.client_manager.broadcast(&event).await {
+            tracing::error!("Failed to broadcast latency_warning: {}", e);
+        }
+    }
+
+    /// Report a swap to a lighter STT model under VRAM pressure
+    pub async fn broadcast_model_downgraded(&self, from: &str, to: &str, vram_percent: f32) {
+        let event = BroadcastEvent::ModelDowngraded {
+            from: from.to_string(),
+            to: to.to_string(),
+            vram_percent,
+            timestamp: Self::current_timestamp(),
+        };
+
+        if let Err(e) = self.client_manager.broadcast(&event).await {
+            tracing::error!("Failed to broadcast model_downgraded: {}", e);
+        }
+    }
+
+    /// Report STT inference that was cancelled by the watchdog
+    pub async fn broadcast_stt_timeout(&self, audio_s: f64, timeout_s: f64, timeouts_total: u64) {
+        let event = BroadcastEvent::SttTimeout {
+            audio_s,
+            timeout_s,
+            timeouts_total,
+            timestamp: Self::current_timestamp(),
+        };
+
+        if let Err(e) = self.client_manager.broadcast(&event).await {
+            tracing::error!("Failed to broadcast stt_timeout: {}", e);
+        }
+    }
+
+    /// Broadcast a pacing alert (speaking faster than `max_wpm` for
+    /// `sustained_s` seconds)
+    pub async fn broadcast_pacing_alert(&self, wpm: f64, max_wpm: f64, sustained_s: f64) {
+        let event = BroadcastEvent::PacingAlert {
+            wpm,
+            max_wpm,
+            sustained_s,
+            timestamp: Self::current_timestamp(),
+        };
+
+        if let Err(e) = self.client_manager.broadcast(&event).await {
+            tracing::error!("Failed to broadcast pacing_alert: {}", e);
+        }
+    }
+
+    /// Report microphone level and speech probability (firehose only)
+    ///
+    /// A no-op without firehose clients, so the audio thread can call it for
+    /// every VAD block.
+    pub async fn broadcast_audio_level(
+        &self,
+        rms_dbfs: f32,
+        peak_dbfs: f32,
+        speech_probability: f32,
+        window_probabilities: Vec<f32>,
+    ) {
+        let event = BroadcastEvent::AudioLevel {
+            rms_dbfs,
+            peak_dbfs,
+            speech_probability,
+            window_probabilities,
+            timestamp: Self::current_timestamp(),
+        };
+
+        if let Err(e) = self.firehose_manager.broadcast(&event).await {
+            tracing::error!("Failed to broadcast audio_level: {}", e);
+        }
+    }
+
+    /// Report the VAD hearing speech start or stop, so clients can show a
+    /// listening indicator before text arrives
+    pub async fn broadcast_speech_activity(&self, speaking: bool, sample: u64) {
+        let event = BroadcastEvent::SpeechActivity {
+            speaking,
+            sample,
+            timestamp: Self::current_timestamp(),
+        };
+
+        if let Err(e) = self.client_manager.broadcast(&event).await {
+            tracing::error!("Failed to broadcast speech_activity: {}", e);
+        }
+    }
+
+    /// Report private mode turning on or off (also sent on catch-up while on)
+    pub async fn broadcast_private_mode(&self, enabled: bool) {
+        let _catch_up_guard = self.transcription_buffer.write().await;
+        *self.private_mode.write().await = enabled;
+
+        let event = BroadcastEvent::PrivateMode {
+            enabled,
+            timestamp: Self::current_timestamp(),
+        };
+
+        if let Err(e) = self.client_manager.broadcast(&event).await {
+            tracing::error!("Failed to broadcast private_mode: {}", e);
+        }
+    }
+
+    /// Report that the metrics disk filled up (`status.full`) or that the
+    /// metrics held meanwhile were written out
+    pub async fn broadcast_storage_status(&self, status: StorageStatus) {
+        let event = BroadcastEvent::StorageFull {
+            full: status.full,
+            buffered_segments: status.buffered_segments,
+            dropped_segments: status.dropped_segments,
+            timestamp: Self::current_timestamp(),
+        };
+
+        if let Err(e) = self.client_manager.broadcast(&event).await {
+            tracing::error!("Failed to broadcast storage_full: {}", e);
+        }
+    }
+
+    /// Report the words of a typed segment that fell below the confidence
+    /// threshold
+    pub async fn broadcast_low_confidence(
+        &self,
+        text: String,
+        words: Vec<UncertainWord>,
+        threshold: f32,
+    ) {
+        let event = BroadcastEvent::LowConfidence {
+            text,
+            words,
+            threshold,
+            timestamp: Self::current_timestamp(),
+        };
+
+        if let Err(e) = self.client_manager.broadcast(&event).await {
+            tracing::error!("Failed to broadcast low_confidence: {}", e);
+        }
+    }
+
+    /// Report the state and progress of a file transcription job
+    pub async fn broadcast_job_progress(
+        &self,
+        job_id: i64,
+        path: String,
+        status: JobStatus,
+        progress: f32,
+    ) {
+        let event = BroadcastEvent::JobProgress {
+            job_id,
+            path,
+            status,
+            progress,
+            timestamp: Self::current_timestamp(),
+        };
+
+        if let Err(e) = self.client_manager.broadcast(&event).await {
+            tracing::error!("Failed to broadcast job_progress: {}", e);
+        }
+    }
+
+    /// Report how far background context-model training has got
+    pub async fn broadcast_training_progress(
+        &self,
+        stage: &str,
+        done: u64,
+        total: u64,
+        status: &str,
+    ) {
+        let event = BroadcastEvent::TrainingProgress {
+            stage: stage.to_string(),
+            done,
+            total,
+            status: status.to_string(),
+            timestamp: Self::current_timestamp(),
+        };
+
+        if let Err(e) = self.client_manager.broadcast(&event).await {
+            tracing::error!("Failed to broadcast training_progress: {}", e);
+        }
+    }
+
+    /// Report how far a readalong script has been read
+    pub async fn broadcast_readalong_progress(&self, position: ScriptPosition) {
+        let event = BroadcastEvent::ReadalongProgress {
+            word: position.word,
+            line: position.line,
+            total_words: position.total_words,
+            accuracy: position.accuracy,
+            skipped_lines: position.skipped_lines,
+            finished: position.finished,
+            pronunciation_score: position.pronunciation_score,
+            words: position.words,
+            timestamp: Self::current_timestamp(),
+        };
+
+        if let Err(e) = self.client_manager.broadcast(&event).await {
+            tracing::error!("Failed to broadcast readalong_progress: {}", e);
+        }
+    }
+
+    /// Report a boot stage reached (`error` unset) or failed
+    ///
+    /// The last one is remembered, so stages reported before [`Self::start`]
+    /// reach the first clients on catch-up.
+    pub async fn broadcast_readiness(&self, stage: ReadinessStage, error: Option<String>) {
+        let _catch_up_guard = self.transcription_buffer.write().await;
+        let event = BroadcastEvent::Readiness {
+            stage,
+            error,
+            timestamp: Self::current_timestamp(),
+        };
+        *self.readiness.write().await = Some(event.clone());
+
+        if let Err(e) = self.client_manager.broadcast(&event).await {
+            tracing::error!("Failed to broadcast readiness: {}", e);
+        }
+    }
+
     /// Get current client count
     pub async fn client_count(&self) -> usize {
         self.client_manager.client_count().await
     }
 
+    /// Get number of firehose clients
+    pub async fn firehose_client_count(&self) -> usize {
+        self.firehose_manager.client_count().await
+    }
+
     /// Get buffer size
     pub async fn buffer_size(&self) -> usize {
         self.transcription_buffer.read().await.len()
@@ -1,34 +1,69 @@
 use crate::error::Result;
 use crate::events::{BroadcastEvent, TranscriptionSegment};
+use crate::protocol::{EventEncoder, Hello};
 use std::sync::Arc;
 use tokio::io::AsyncWriteExt;
-use tokio::net::UnixStream;
+use tokio::net::unix::OwnedWriteHalf;
 use tokio::sync::Mutex;
 
-/// Client connection wrapper
+/// Client connection wrapper (the sending half; see [`crate::protocol`])
 pub struct Client {
-    stream: UnixStream,
+    id: u64,
+    stream: OwnedWriteHalf,
+    encoder: EventEncoder,
 }
 
 impl Client {
-    pub fn new(stream: UnixStream) -> Self {
-        Self { stream }
+    /// Client `id` that receives newline-delimited JSON until it asks otherwise
+    pub fn new(id: u64, stream: OwnedWriteHalf) -> Self {
+        Self {
+            id,
+            stream,
+            encoder: EventEncoder::default(),
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
     }
 
     /// Send event to client
     pub async fn send_event(&mut self, event: &BroadcastEvent) -> Result<()> {
-        let json_line = event.to_json_line()?;
-        self.stream.write_all(json_line.as_bytes()).await?;
+        let bytes = self.encoder.encode(event)?;
+        self.stream.write_all(&bytes).await?;
+        Ok(())
+    }
+
+    /// Confirm `hello` in the current format, then switch to it
+    pub async fn set_format(&mut self, hello: Hello) -> Result<()> {
+        let encoder = EventEncoder::new(hello)?;
+        let ack = BroadcastEvent::EncodingChanged {
+            encoding: hello.encoding,
+            compression: hello.compression,
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs_f64(),
+        };
+        self.send_event(&ack).await?;
+        self.encoder = encoder;
         Ok(())
     }
 
     /// Send current state to new client (catch-up)
     pub async fn send_catch_up(
         &mut self,
+        readiness: Option<&BroadcastEvent>,
         current_state: &str,
         session_id: Option<i64>,
+        private_mode: bool,
         buffer: &[TranscriptionSegment],
     ) -> Result<()> {
+        // Send how far the daemon has booted
+        if let Some(readiness) = readiness {
+            self.send_event(readiness).await?;
+        }
+
         // Send current state
         let state_event = BroadcastEvent::StateChange {
             state: current_state.to_string(),
@@ -51,16 +86,21 @@ impl Client {
             self.send_event(&session_event).await?;
         }
 
+        // Send private mode so the UI shows its badge
+        if private_mode {
+            let private_event = BroadcastEvent::PrivateMode {
+                enabled: true,
+                timestamp: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs_f64(),
+            };
+            self.send_event(&private_event).await?;
+        }
+
         // Send buffered transcriptions
         for segment in buffer {
-            let trans_event = BroadcastEvent::Transcription {
-                text: segment.text.clone(),
-                timestamp: segment.timestamp.clone(),
-                wpm: segment.wpm,
-                latency_ms: segment.latency_ms,
-                words: segment.words,
-            };
-            self.send_event(&trans_event).await?;
+            self.send_event(&segment.to_event()).await?;
         }
 
         Ok(())
@@ -8,6 +8,9 @@ pub enum BroadcasterError {
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
+    #[error("MessagePack encoding error: {0}")]
+    MessagePack(#[from] rmp_serde::encode::Error),
+
     #[error("Socket path error: {0}")]
     SocketPath(String),
 
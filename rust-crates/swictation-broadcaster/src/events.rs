@@ -1,4 +1,9 @@
 use serde::{Deserialize, Serialize};
+use swictation_metrics::{
+    InferenceMetadata, JobStatus, LatencyBreakdown, LatencyBudgets, LatencyStage,
+};
+
+use crate::protocol::{Compression, Encoding};
 
 /// Event types broadcast to UI clients
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -20,6 +25,15 @@ pub enum BroadcastEvent {
         wpm: f64,
         latency_ms: f64,
         words: i32,
+        /// Model and execution provider that produced the segment
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        inference: Option<InferenceMetadata>,
+        /// Metrics session the segment belongs to
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        session_id: Option<i64>,
+        /// Metrics database row of the segment, for later edits or bookmarks
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        segment_id: Option<i64>,
     },
 
     /// Real-time metrics update
@@ -40,6 +54,274 @@ pub enum BroadcastEvent {
     /// Daemon state changed
     #[serde(rename = "state_change")]
     StateChange { state: String, timestamp: f64 },
+
+    /// Weekly Markdown digest was written to disk
+    #[serde(rename = "weekly_summary_ready")]
+    WeeklySummaryReady {
+        week: String,
+        path: String,
+        timestamp: f64,
+    },
+
+    /// VAD tuning changed on the live detector
+    #[serde(rename = "vad_config_changed")]
+    VadConfigChanged {
+        threshold: f32,
+        min_silence: f32,
+        min_speech: f32,
+        /// Whether the values were also written to the config file
+        persisted: bool,
+        timestamp: f64,
+    },
+
+    /// A segment exceeded one or more per-stage latency budgets
+    #[serde(rename = "latency_warning")]
+    LatencyWarning {
+        exceeded: Vec<LatencyStage>,
+        breakdown: LatencyBreakdown,
+        budgets: LatencyBudgets,
+        timestamp: f64,
+    },
+
+    /// The STT model was swapped for a lighter one because VRAM ran out
+    #[serde(rename = "model_downgraded")]
+    ModelDowngraded {
+        /// Model names as in `stt_model_override` ("1.1b-gpu", ...)
+        from: String,
+        to: String,
+        vram_percent: f32,
+        timestamp: f64,
+    },
+
+    /// STT inference ran past its timeout and was cancelled
+    #[serde(rename = "stt_timeout")]
+    SttTimeout {
+        /// Length of the segment being transcribed
+        audio_s: f64,
+        timeout_s: f64,
+        /// Timeouts since the daemon started
+        timeouts_total: u64,
+        timestamp: f64,
+    },
+
+    /// Speaking rate stayed above the pacing limit
+    #[serde(rename = "pacing_alert")]
+    PacingAlert {
+        /// Average rate over the fast stretch
+        wpm: f64,
+        max_wpm: f64,
+        /// Seconds of capture the rate was sustained for
+        sustained_s: f64,
+        timestamp: f64,
+    },
+
+    /// The metrics database's disk filled up, or has space again
+    #[serde(rename = "storage_full")]
+    StorageFull {
+        /// `false` once the metrics held in RAM were written out
+        full: bool,
+        /// Segments held in RAM until there is space
+        buffered_segments: usize,
+        /// Segments lost because too many were held
+        dropped_segments: usize,
+        timestamp: f64,
+    },
+
+    /// Reply to a client's format request; the events after it use the new format
+    #[serde(rename = "encoding")]
+    EncodingChanged {
+        encoding: Encoding,
+        compression: Compression,
+        timestamp: f64,
+    },
+
+    /// Microphone level and VAD speech probability of one audio block
+    ///
+    /// High-rate: sent on the firehose socket only.
+    #[serde(rename = "audio_level")]
+    AudioLevel {
+        /// RMS level in dBFS (-100 for silence)
+        rms_dbfs: f32,
+        peak_dbfs: f32,
+        /// Highest VAD speech probability within the block
+        speech_probability: f32,
+        /// Probability of each VAD window (32 ms) in the block, oldest first
+        window_probabilities: Vec<f32>,
+        timestamp: f64,
+    },
+
+    /// The VAD heard speech start or stop, ahead of the segment's transcription
+    #[serde(rename = "speech_activity")]
+    SpeechActivity {
+        speaking: bool,
+        /// Capture sample (16 kHz) where speech started or ended
+        sample: u64,
+        timestamp: f64,
+    },
+
+    /// Private mode turned on or off; while on, sessions are not saved
+    #[serde(rename = "private_mode")]
+    PrivateMode { enabled: bool, timestamp: f64 },
+
+    /// Words of a typed segment the recognizer was unsure of
+    #[serde(rename = "low_confidence")]
+    LowConfidence {
+        /// Segment as typed
+        text: String,
+        words: Vec<UncertainWord>,
+        /// Confidence below which words are reported
+        threshold: f32,
+        timestamp: f64,
+    },
+
+    /// A file transcription job changed state or finished a segment
+    #[serde(rename = "job_progress")]
+    JobProgress {
+        job_id: i64,
+        path: String,
+        status: JobStatus,
+        /// Fraction of the file's speech transcribed (0-1)
+        progress: f32,
+        timestamp: f64,
+    },
+
+    /// Background context-model training progressed or ended
+    #[serde(rename = "training_progress")]
+    TrainingProgress {
+        /// Current step (`loading_segments`, `clustering`, `homonyms`, ...)
+        stage: String,
+        /// Items of the step done so far, out of `total`
+        done: u64,
+        total: u64,
+        /// `running`, `completed`, `cancelled` or `failed`
+        status: String,
+        timestamp: f64,
+    },
+
+    /// Where a speaker reading a script in readalong mode has got to
+    #[serde(rename = "readalong_progress")]
+    ReadalongProgress {
+        /// Index of the next script word to be read
+        word: usize,
+        /// Script line (0-based, blank lines counted) of that word
+        line: usize,
+        total_words: usize,
+        /// Fraction of the script words passed that were read as written
+        accuracy: f32,
+        /// Lines passed just now without a word of them being read
+        skipped_lines: Vec<usize>,
+        /// The end of the script was reached
+        finished: bool,
+        /// Mean word score over the script words passed (0-100)
+        pronunciation_score: f32,
+        /// Scores of the script words the segment was aligned to
+        #[serde(default)]
+        words: Vec<PracticeWord>,
+        timestamp: f64,
+    },
+
+    /// Daemon boot progress: `stage` was reached, or could not be when
+    /// `error` is set (also sent on catch-up)
+    #[serde(rename = "readiness")]
+    Readiness {
+        stage: ReadinessStage,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+        timestamp: f64,
+    },
+}
+
+/// Daemon boot stages, in the order they are reached
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReadinessStage {
+    /// Data, model and socket locations resolved
+    PathsOk,
+    /// Config file loaded
+    ConfigOk,
+    /// Capture device opened
+    AudioOk,
+    /// VAD model loaded
+    VadOk,
+    /// STT model load started (can take a while)
+    SttLoading,
+    /// STT model loaded
+    SttReady,
+    /// IPC and metrics sockets listening; the daemon is ready
+    SocketsReady,
+}
+
+impl ReadinessStage {
+    pub const ALL: [ReadinessStage; 7] = [
+        ReadinessStage::PathsOk,
+        ReadinessStage::ConfigOk,
+        ReadinessStage::AudioOk,
+        ReadinessStage::VadOk,
+        ReadinessStage::SttLoading,
+        ReadinessStage::SttReady,
+        ReadinessStage::SocketsReady,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReadinessStage::PathsOk => "paths-ok",
+            ReadinessStage::ConfigOk => "config-ok",
+            ReadinessStage::AudioOk => "audio-ok",
+            ReadinessStage::VadOk => "vad-ok",
+            ReadinessStage::SttLoading => "stt-loading",
+            ReadinessStage::SttReady => "stt-ready",
+            ReadinessStage::SocketsReady => "sockets-ready",
+        }
+    }
+
+    /// The stage after this one, `None` once ready
+    pub fn next(&self) -> Option<ReadinessStage> {
+        let index = Self::ALL.iter().position(|s| s == self)?;
+        Self::ALL.get(index + 1).copied()
+    }
+}
+
+impl std::fmt::Display for ReadinessStage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Recognized word with the recognizer's confidence in it (0-1)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UncertainWord {
+    pub word: String,
+    pub confidence: f32,
+}
+
+/// Reading position in a readalong script (see [`BroadcastEvent::ReadalongProgress`])
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScriptPosition {
+    pub word: usize,
+    pub line: usize,
+    pub total_words: usize,
+    pub accuracy: f32,
+    pub skipped_lines: Vec<usize>,
+    pub finished: bool,
+    pub pronunciation_score: f32,
+    pub words: Vec<PracticeWord>,
+}
+
+/// How one script word was read in readalong mode
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PracticeWord {
+    /// Index of the word in the script
+    pub index: usize,
+    /// The word as written (normalized)
+    pub word: String,
+    /// What was said for it (`None` when it was left out)
+    pub spoken: Option<String>,
+    /// Said exactly as written
+    pub matched: bool,
+    /// Recognizer confidence in the spoken word (0-1), where known
+    pub confidence: Option<f32>,
+    /// 0-100: the confidence when matched, 0 otherwise
+    pub score: f32,
 }
 
 /// Transcription segment stored in RAM buffer
@@ -50,6 +332,25 @@ pub struct TranscriptionSegment {
     pub wpm: f64,
     pub latency_ms: f64,
     pub words: i32,
+    pub inference: Option<InferenceMetadata>,
+    pub session_id: Option<i64>,
+    pub segment_id: Option<i64>,
+}
+
+impl TranscriptionSegment {
+    /// The `transcription` event announcing this segment
+    pub fn to_event(&self) -> BroadcastEvent {
+        BroadcastEvent::Transcription {
+            text: self.text.clone(),
+            timestamp: self.timestamp.clone(),
+            wpm: self.wpm,
+            latency_ms: self.latency_ms,
+            words: self.words,
+            inference: self.inference.clone(),
+            session_id: self.session_id,
+            segment_id: self.segment_id,
+        }
+    }
 }
 
 impl BroadcastEvent {
@@ -84,11 +385,64 @@ mod tests {
             wpm: 145.2,
             latency_ms: 234.5,
             words: 2,
+            inference: None,
+            session_id: None,
+            segment_id: None,
         };
         let json = event.to_json_line().unwrap();
         assert!(json.contains("\"type\":\"transcription\""));
         assert!(json.contains("\"text\":\"Hello world\""));
         assert!(json.contains("\"wpm\":145.2"));
+        assert!(!json.contains("inference"));
+        assert!(!json.contains("segment_id"));
+    }
+
+    #[test]
+    fn test_transcription_event_with_segment_ids() {
+        let segment = TranscriptionSegment {
+            text: "Hello".to_string(),
+            timestamp: "14:23:15".to_string(),
+            wpm: 120.0,
+            latency_ms: 200.0,
+            words: 1,
+            inference: None,
+            session_id: Some(7),
+            segment_id: Some(42),
+        };
+        let json = segment.to_event().to_json_line().unwrap();
+        assert!(json.contains("\"session_id\":7"));
+        assert!(json.contains("\"segment_id\":42"));
+
+        match serde_json::from_str(&json).unwrap() {
+            BroadcastEvent::Transcription {
+                session_id,
+                segment_id,
+                ..
+            } => assert_eq!((session_id, segment_id), (Some(7), Some(42))),
+            other => panic!("Wrong event type: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_transcription_event_with_inference() {
+        let event = BroadcastEvent::Transcription {
+            text: "Hello".to_string(),
+            timestamp: "14:23:15".to_string(),
+            wpm: 120.0,
+            latency_ms: 200.0,
+            words: 1,
+            inference: Some(InferenceMetadata {
+                model: "parakeet-tdt-0.6b-v3".to_string(),
+                quantization: "int8".to_string(),
+                provider: "CUDA".to_string(),
+                device: "gpu".to_string(),
+            }),
+            session_id: None,
+            segment_id: None,
+        };
+        let json = event.to_json_line().unwrap();
+        assert!(json.contains("\"provider\":\"CUDA\""));
+        assert!(json.contains("\"quantization\":\"int8\""));
     }
 
     #[test]
@@ -110,4 +464,252 @@ mod tests {
         assert!(json.contains("\"state\":\"recording\""));
         assert!(json.contains("\"segments\":5"));
     }
+
+    #[test]
+    fn test_weekly_summary_serialization() {
+        let event = BroadcastEvent::WeeklySummaryReady {
+            week: "2025-W03".to_string(),
+            path: "/data/swictation/digests/2025-W03.md".to_string(),
+            timestamp: 1699000000.0,
+        };
+        let json = event.to_json_line().unwrap();
+        assert!(json.contains("\"type\":\"weekly_summary_ready\""));
+        assert!(json.contains("\"week\":\"2025-W03\""));
+    }
+
+    #[test]
+    fn test_vad_config_changed_serialization() {
+        let event = BroadcastEvent::VadConfigChanged {
+            threshold: 0.5,
+            min_silence: 0.8,
+            min_speech: 0.25,
+            persisted: false,
+            timestamp: 1699000000.0,
+        };
+        let json = event.to_json_line().unwrap();
+        assert!(json.contains("\"type\":\"vad_config_changed\""));
+        assert!(json.contains("\"threshold\":0.5"));
+        assert!(json.contains("\"persisted\":false"));
+    }
+
+    #[test]
+    fn test_model_downgraded_serialization() {
+        let event = BroadcastEvent::ModelDowngraded {
+            from: "1.1b-gpu".to_string(),
+            to: "0.6b-gpu".to_string(),
+            vram_percent: 97.5,
+            timestamp: 1699000000.0,
+        };
+        let json = event.to_json_line().unwrap();
+        assert!(json.contains("\"type\":\"model_downgraded\""));
+        assert!(json.contains("\"to\":\"0.6b-gpu\""));
+    }
+
+    #[test]
+    fn test_latency_warning_serialization() {
+        let event = BroadcastEvent::LatencyWarning {
+            exceeded: vec![LatencyStage::Stt],
+            breakdown: LatencyBreakdown {
+                stt_ms: 950.0,
+                total_ms: 980.0,
+                ..Default::default()
+            },
+            budgets: LatencyBudgets::default(),
+            timestamp: 1699000000.0,
+        };
+        let json = event.to_json_line().unwrap();
+        assert!(json.contains("\"type\":\"latency_warning\""));
+        assert!(json.contains("\"exceeded\":[\"stt\"]"));
+        assert!(json.contains("\"stt_ms\":950.0"));
+    }
+
+    #[test]
+    fn test_stt_timeout_serialization() {
+        let event = BroadcastEvent::SttTimeout {
+            audio_s: 12.5,
+            timeout_s: 20.0,
+            timeouts_total: 2,
+            timestamp: 1699000000.0,
+        };
+        let json = event.to_json_line().unwrap();
+        assert!(json.contains("\"type\":\"stt_timeout\""));
+        assert!(json.contains("\"timeouts_total\":2"));
+    }
+
+    #[test]
+    fn test_pacing_alert_serialization() {
+        let event = BroadcastEvent::PacingAlert {
+            wpm: 205.0,
+            max_wpm: 180.0,
+            sustained_s: 12.0,
+            timestamp: 1699000000.0,
+        };
+        let json = event.to_json_line().unwrap();
+        assert!(json.contains("\"type\":\"pacing_alert\""));
+        assert!(json.contains("\"max_wpm\":180.0"));
+    }
+
+    #[test]
+    fn test_storage_full_serialization() {
+        let event = BroadcastEvent::StorageFull {
+            full: true,
+            buffered_segments: 3,
+            dropped_segments: 0,
+            timestamp: 1699000000.0,
+        };
+        let json = event.to_json_line().unwrap();
+        assert!(json.contains("\"type\":\"storage_full\""));
+        assert!(json.contains("\"full\":true"));
+        assert!(json.contains("\"buffered_segments\":3"));
+    }
+
+    #[test]
+    fn test_encoding_changed_serialization() {
+        let event = BroadcastEvent::EncodingChanged {
+            encoding: Encoding::Msgpack,
+            compression: Compression::Zstd,
+            timestamp: 1699000000.0,
+        };
+        let json = event.to_json_line().unwrap();
+        assert!(json.contains("\"type\":\"encoding\""));
+        assert!(json.contains("\"encoding\":\"msgpack\""));
+        assert!(json.contains("\"compression\":\"zstd\""));
+    }
+
+    #[test]
+    fn test_audio_level_serialization() {
+        let event = BroadcastEvent::AudioLevel {
+            rms_dbfs: -30.0,
+            peak_dbfs: -12.5,
+            speech_probability: 0.75,
+            window_probabilities: vec![0.25, 0.75],
+            timestamp: 1699000000.0,
+        };
+        let json = event.to_json_line().unwrap();
+        assert!(json.contains("\"type\":\"audio_level\""));
+        assert!(json.contains("\"peak_dbfs\":-12.5"));
+        assert!(json.contains("\"speech_probability\":0.75"));
+        assert!(json.contains("\"window_probabilities\":[0.25,0.75]"));
+    }
+
+    #[test]
+    fn test_speech_activity_serialization() {
+        let event = BroadcastEvent::SpeechActivity {
+            speaking: true,
+            sample: 48000,
+            timestamp: 1699000000.0,
+        };
+        let json = event.to_json_line().unwrap();
+        assert!(json.contains("\"type\":\"speech_activity\""));
+        assert!(json.contains("\"speaking\":true"));
+        assert!(json.contains("\"sample\":48000"));
+    }
+
+    #[test]
+    fn test_private_mode_serialization() {
+        let event = BroadcastEvent::PrivateMode {
+            enabled: true,
+            timestamp: 1699000000.0,
+        };
+        let json = event.to_json_line().unwrap();
+        assert!(json.contains("\"type\":\"private_mode\""));
+        assert!(json.contains("\"enabled\":true"));
+    }
+
+    #[test]
+    fn test_low_confidence_serialization() {
+        let event = BroadcastEvent::LowConfidence {
+            text: "Meet me at the quay.".to_string(),
+            words: vec![UncertainWord {
+                word: "quay.".to_string(),
+                confidence: 0.25,
+            }],
+            threshold: 0.5,
+            timestamp: 1699000000.0,
+        };
+        let json = event.to_json_line().unwrap();
+        assert!(json.contains("\"type\":\"low_confidence\""));
+        assert!(json.contains("\"words\":[{\"word\":\"quay.\",\"confidence\":0.25}]"));
+    }
+
+    #[test]
+    fn test_readalong_progress_serialization() {
+        let event = BroadcastEvent::ReadalongProgress {
+            word: 12,
+            line: 3,
+            total_words: 40,
+            accuracy: 0.75,
+            skipped_lines: vec![2],
+            finished: false,
+            pronunciation_score: 68.0,
+            words: vec![PracticeWord {
+                index: 11,
+                word: "quarterly".to_string(),
+                spoken: Some("quarterly".to_string()),
+                matched: true,
+                confidence: Some(0.5),
+                score: 50.0,
+            }],
+            timestamp: 1699000000.0,
+        };
+        let json = event.to_json_line().unwrap();
+        assert!(json.contains("\"type\":\"readalong_progress\""));
+        assert!(json.contains("\"skipped_lines\":[2]"));
+        assert!(json.contains("\"matched\":true"));
+    }
+
+    #[test]
+    fn test_job_progress_serialization() {
+        let event = BroadcastEvent::JobProgress {
+            job_id: 7,
+            path: "/audio/interview.wav".to_string(),
+            status: JobStatus::Running,
+            progress: 0.5,
+            timestamp: 1699000000.0,
+        };
+        let json = event.to_json_line().unwrap();
+        assert!(json.contains("\"type\":\"job_progress\""));
+        assert!(json.contains("\"status\":\"running\""));
+        assert!(json.contains("\"progress\":0.5"));
+    }
+
+    #[test]
+    fn test_training_progress_serialization() {
+        let event = BroadcastEvent::TrainingProgress {
+            stage: "clustering".to_string(),
+            done: 500,
+            total: 2000,
+            status: "running".to_string(),
+            timestamp: 1699000000.0,
+        };
+        let json = event.to_json_line().unwrap();
+        assert!(json.contains("\"type\":\"training_progress\""));
+        assert!(json.contains("\"stage\":\"clustering\""));
+        assert!(json.contains("\"done\":500"));
+    }
+
+    #[test]
+    fn test_readiness_serialization() {
+        let event = BroadcastEvent::Readiness {
+            stage: ReadinessStage::SttLoading,
+            error: None,
+            timestamp: 1699000000.0,
+        };
+        let json = event.to_json_line().unwrap();
+        assert!(json.contains("\"type\":\"readiness\""));
+        assert!(json.contains("\"stage\":\"stt-loading\""));
+        assert!(!json.contains("error"));
+
+        for stage in ReadinessStage::ALL {
+            assert_eq!(
+                serde_json::to_value(stage).unwrap(),
+                serde_json::json!(stage.as_str())
+            );
+        }
+        assert_eq!(
+            ReadinessStage::VadOk.next(),
+            Some(ReadinessStage::SttLoading)
+        );
+        assert_eq!(ReadinessStage::SocketsReady.next(), None);
+    }
 }
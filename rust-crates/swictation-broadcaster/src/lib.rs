@@ -7,11 +7,13 @@
 //! # Features
 //!
 //! - Unix domain socket server (`/tmp/swictation_metrics.sock`)
-//! - Newline-delimited JSON protocol
+//! - Newline-delimited JSON protocol, with MessagePack and zstd negotiable
+//!   per connection (see [`protocol`])
 //! - Multiple concurrent client connections
+//! - Optional firehose socket for high-rate events, kept off the main one
 //! - Session-based transcription buffer (RAM only)
 //! - Thread-safe client management
-//! - New client catch-up (current state + buffer)
+//! - New client catch-up (boot stage + current state + buffer)
 //!
 //! # Event Types
 //!
@@ -20,6 +22,15 @@
 //! - `transcription` - New transcription segment
 //! - `metrics_update` - Real-time metrics from daemon
 //! - `state_change` - Daemon state change
+//! - `weekly_summary_ready` - Weekly Markdown digest written to the data dir
+//! - `vad_config_changed` - Live VAD threshold/duration tuning applied
+//! - `latency_warning` - Segment went over a per-stage latency budget
+//! - `low_confidence` - Words of a typed segment the recognizer was unsure of
+//! - `job_progress` - File transcription job state and progress
+//! - `training_progress` - Background context-model training stage and outcome
+//! - `readalong_progress` - Reading position, accuracy, skipped lines and word scores of a rehearsed script
+//! - `readiness` - Daemon boot stage reached or failed (`paths-ok` ... `sockets-ready`)
+//! - `audio_level` - Microphone level and speech probability (firehose only)
 //!
 //! # Example Usage
 //!
@@ -66,8 +77,13 @@ pub mod broadcaster;
 pub mod client;
 pub mod error;
 pub mod events;
+pub mod protocol;
 
 // Re-exports
 pub use broadcaster::MetricsBroadcaster;
 pub use error::{BroadcasterError, Result};
-pub use events::{BroadcastEvent, TranscriptionSegment};
+pub use events::{
+    BroadcastEvent, PracticeWord, ReadinessStage, ScriptPosition, TranscriptionSegment,
+    UncertainWord,
+};
+pub use protocol::{Compression, Encoding, Hello};
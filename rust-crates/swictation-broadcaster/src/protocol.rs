@@ -0,0 +1,149 @@
+//! Per-connection wire format negotiation
+//!
+//! Every connection starts as newline-delimited JSON, so `nc -U` and scripts
+//! keep working untouched. A client that wants less traffic writes one JSON
+//! line, at any time:
+//!
+//! ```json
+//! {"encoding": "msgpack", "compression": "zstd"}
+//! ```
+//!
+//! The server answers with an `encoding` event in the format in effect so
+//! far, then switches and replays the catch-up (state, session, buffered
+//! transcriptions) in the new format, so a client can ignore everything it
+//! read before the answer.
+//!
+//! - `encoding`: `json` (newline-delimited) or `msgpack` (one self-delimiting
+//!   MessagePack map per event, with the same field names as the JSON)
+//! - `compression`: `none` or `zstd` (a single zstd stream for the rest of
+//!   the connection, flushed after every event)
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+
+use crate::error::Result;
+use crate::events::BroadcastEvent;
+
+/// How events are serialized
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Encoding {
+    #[default]
+    Json,
+    #[serde(alias = "messagepack")]
+    Msgpack,
+}
+
+/// How the serialized events are compressed
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    #[default]
+    None,
+    Zstd,
+}
+
+/// Format requested by a client (omitted fields keep the default)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Hello {
+    #[serde(default)]
+    pub encoding: Encoding,
+    #[serde(default)]
+    pub compression: Compression,
+}
+
+impl Hello {
+    /// Parse a line written by a client
+    pub fn parse(line: &str) -> Result<Self> {
+        Ok(serde_json::from_str(line.trim())?)
+    }
+}
+
+/// Turns events into the bytes written to one connection
+pub struct EventEncoder {
+    encoding: Encoding,
+    /// Kept for the whole connection so later events reuse earlier context
+    zstd: Option<zstd::stream::write::Encoder<'static, Vec<u8>>>,
+}
+
+impl EventEncoder {
+    pub fn new(hello: Hello) -> Result<Self> {
+        let zstd = match hello.compression {
+            Compression::None => None,
+            Compression::Zstd => Some(zstd::stream::write::Encoder::new(Vec::new(), 0)?),
+        };
+        Ok(Self {
+            encoding: hello.encoding,
+            zstd,
+        })
+    }
+
+    /// Bytes for `event`, complete enough for the client to decode it
+    pub fn encode(&mut self, event: &BroadcastEvent) -> Result<Vec<u8>> {
+        let bytes = match self.encoding {
+            Encoding::Json => event.to_json_line()?.into_bytes(),
+            Encoding::Msgpack => rmp_serde::to_vec_named(event)?,
+        };
+        match &mut self.zstd {
+            None => Ok(bytes),
+            Some(zstd) => {
+                zstd.write_all(&bytes)?;
+                zstd.flush()?;
+                Ok(std::mem::take(zstd.get_mut()))
+            }
+        }
+    }
+}
+
+impl Default for EventEncoder {
+    fn default() -> Self {
+        Self {
+            encoding: Encoding::Json,
+            zstd: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(session_id: i64) -> BroadcastEvent {
+        BroadcastEvent::SessionStart {
+            session_id,
+            timestamp: 1699000000.0,
+        }
+    }
+
+    #[test]
+    fn test_hello_defaults_to_json() {
+        assert_eq!(Hello::parse("{}").unwrap(), Hello::default());
+        let hello = Hello::parse("{\"encoding\":\"msgpack\",\"compression\":\"zstd\"}\n").unwrap();
+        assert_eq!(hello.encoding, Encoding::Msgpack);
+        assert_eq!(hello.compression, Compression::Zstd);
+        assert!(Hello::parse("{\"encoding\":\"xml\"}").is_err());
+    }
+
+    #[test]
+    fn test_zstd_msgpack_round_trip() {
+        let mut encoder = EventEncoder::new(Hello {
+            encoding: Encoding::Msgpack,
+            compression: Compression::Zstd,
+        })
+        .unwrap();
+        let mut stream = Vec::new();
+        for session_id in 1..=3 {
+            stream.extend(encoder.encode(&event(session_id)).unwrap());
+        }
+
+        // Each event is flushed, so the stream decodes without a frame end
+        let mut decoder = zstd::stream::read::Decoder::new(stream.as_slice()).unwrap();
+        for session_id in 1..=3 {
+            let decoded: BroadcastEvent = rmp_serde::from_read(&mut decoder).unwrap();
+            assert!(matches!(
+                decoded,
+                BroadcastEvent::SessionStart { session_id: id, .. } if id == session_id
+            ));
+        }
+    }
+}
@@ -0,0 +1,376 @@
+//! Catch-up and ordering semantics with several real socket clients
+//!
+//! Each test drives a `MetricsBroadcaster` on a temp socket and reads the
+//! newline-delimited JSON stream the way the UI does.
+
+use serde_json::Value;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use swictation_broadcaster::MetricsBroadcaster;
+use swictation_metrics::DaemonState;
+use tempfile::tempdir;
+use tokio::io::{AsyncBufReadExt, BufReader, Lines};
+use tokio::net::UnixStream;
+
+/// How long a client waits before concluding no more events are coming
+const QUIET: Duration = Duration::from_millis(300);
+
+struct TestClient {
+    lines: Lines<BufReader<UnixStream>>,
+}
+
+impl TestClient {
+    async fn connect(path: &Path) -> Self {
+        let stream = UnixStream::connect(path).await.unwrap();
+        Self {
+            lines: BufReader::new(stream).lines(),
+        }
+    }
+
+    /// Next event, or `None` on timeout / disconnect
+    async fn next_event(&mut self, timeout: Duration) -> Option<Value> {
+        match tokio::time::timeout(timeout, self.lines.next_line()).await {
+            Ok(Ok(Some(line))) => Some(serde_json::from_str(&line).unwrap()),
+            _ => None,
+        }
+    }
+
+    /// Read events until the stream has been quiet for `QUIET`
+    async fn drain(&mut self) -> Vec<Value> {
+        let mut events = Vec::new();
+        while let Some(event) = self.next_event(QUIET).await {
+            events.push(event);
+        }
+        events
+    }
+}
+
+fn types(events: &[Value]) -> Vec<&str> {
+    events.iter().map(|e| e["type"].as_str().unwrap()).collect()
+}
+
+fn transcription_texts(events: &[Value]) -> Vec<String> {
+    events
+        .iter()
+        .filter(|e| e["type"] == "transcription")
+        .map(|e| e["text"].as_str().unwrap().to_string())
+        .collect()
+}
+
+async fn wait_for_clients(broadcaster: &MetricsBroadcaster, count: usize) {
+    for _ in 0..100 {
+        if broadcaster.client_count().await == count {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!(
+        "expected {} clients, have {}",
+        count,
+        broadcaster.client_count().await
+    );
+}
+
+#[tokio::test]
+async fn test_catch_up_order() {
+    let temp_dir = tempdir().unwrap();
+    let socket_path = temp_dir.path().join("catch_up_order.sock");
+    let broadcaster = MetricsBroadcaster::new(&socket_path).await.unwrap();
+    broadcaster.start().await.unwrap();
+
+    broadcaster
+        .broadcast_state_change(DaemonState::Recording)
+        .await;
+    broadcaster.start_session(7).await;
+    for text in ["one", "two", "three"] {
+        broadcaster
+            .add_transcription(text.to_string(), 100.0, 150.0, 1)
+            .await;
+    }
+
+    let mut client = TestClient::connect(&socket_path).await;
+    let events = client.drain().await;
+
+    assert_eq!(
+        types(&events),
+        vec![
+            "state_change",
+            "session_start",
+            "transcription",
+            "transcription",
+            "transcription"
+        ]
+    );
+    assert_eq!(events[0]["state"], "recording");
+    assert_eq!(events[1]["session_id"], 7);
+    assert_eq!(transcription_texts(&events), vec!["one", "two", "three"]);
+
+    broadcaster.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_late_joiner_after_session_end() {
+    let temp_dir = tempdir().unwrap();
+    let socket_path = temp_dir.path().join("late_joiner.sock");
+    let broadcaster = MetricsBroadcaster::new(&socket_path).await.unwrap();
+    broadcaster.start().await.unwrap();
+
+    broadcaster.start_session(1).await;
+    broadcaster
+        .add_transcription("kept".to_string(), 100.0, 150.0, 1)
+        .await;
+    broadcaster.end_session(1).await;
+    broadcaster.broadcast_state_change(DaemonState::Idle).await;
+
+    // Transcriptions stay visible until the next session, but the ended
+    // session is not announced as active
+    let mut client = TestClient::connect(&socket_path).await;
+    let events = client.drain().await;
+    assert_eq!(types(&events), vec!["state_change", "transcription"]);
+    assert_eq!(events[0]["state"], "idle");
+    assert_eq!(transcription_texts(&events), vec!["kept"]);
+
+    // A new session clears the buffer for clients joining after it
+    broadcaster.start_session(2).await;
+    let mut fresh = TestClient::connect(&socket_path).await;
+    let events = fresh.drain().await;
+    assert_eq!(types(&events), vec!["state_change", "session_start"]);
+    assert_eq!(events[1]["session_id"], 2);
+
+    broadcaster.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_all_clients_see_same_order() {
+    let temp_dir = tempdir().unwrap();
+    let socket_path = temp_dir.path().join("same_order.sock");
+    let broadcaster = MetricsBroadcaster::new(&socket_path).await.unwrap();
+    broadcaster.start().await.unwrap();
+
+    let mut clients = Vec::new();
+    for _ in 0..4 {
+        clients.push(TestClient::connect(&socket_path).await);
+    }
+    wait_for_clients(&broadcaster, 4).await;
+    for client in &mut clients {
+        assert_eq!(types(&client.drain().await), vec!["state_change"]);
+    }
+
+    broadcaster.start_session(42).await;
+    for i in 0..20 {
+        broadcaster
+            .add_transcription(format!("segment {}", i), 100.0, 150.0, 2)
+            .await;
+    }
+    broadcaster.end_session(42).await;
+
+    let mut expected = vec!["session_start"];
+    expected.extend(std::iter::repeat_n("transcription", 20));
+    expected.push("session_end");
+    let expected_texts: Vec<String> = (0..20).map(|i| format!("segment {}", i)).collect();
+
+    let readers = clients.into_iter().map(|mut client| {
+        tokio::spawn(async move {
+            let mut events = Vec::new();
+            while events.len() < 22 {
+                match client.next_event(Duration::from_secs(2)).await {
+                    Some(event) => events.push(event),
+                    None => break,
+                }
+            }
+            events
+        })
+    });
+    for reader in readers {
+        let events = reader.await.unwrap();
+        assert_eq!(types(&events), expected);
+        assert_eq!(transcription_texts(&events), expected_texts);
+    }
+
+    broadcaster.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_disconnected_client_is_dropped() {
+    let temp_dir = tempdir().unwrap();
+    let socket_path = temp_dir.path().join("disconnect.sock");
+    let broadcaster = MetricsBroadcaster::new(&socket_path).await.unwrap();
+    broadcaster.start().await.unwrap();
+
+    let mut staying = TestClient::connect(&socket_path).await;
+    let leaving = TestClient::connect(&socket_path).await;
+    wait_for_clients(&broadcaster, 2).await;
+    staying.drain().await;
+
+    drop(leaving);
+    broadcaster
+        .broadcast_state_change(DaemonState::Recording)
+        .await;
+
+    // The failed write removes the dead client; the live one is unaffected
+    assert_eq!(broadcaster.client_count().await, 1);
+    let event = staying.next_event(Duration::from_secs(1)).await.unwrap();
+    assert_eq!(event["type"], "state_change");
+    assert_eq!(event["state"], "recording");
+
+    broadcaster.stop().await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_clients_joining_mid_stream_miss_nothing() {
+    let temp_dir = tempdir().unwrap();
+    let socket_path = temp_dir.path().join("mid_stream.sock");
+    let broadcaster = Arc::new(MetricsBroadcaster::new(&socket_path).await.unwrap());
+    broadcaster.start().await.unwrap();
+    broadcaster.start_session(9).await;
+
+    const SEGMENTS: usize = 50;
+    let producer = {
+        let broadcaster = broadcaster.clone();
+        tokio::spawn(async move {
+            for i in 0..SEGMENTS {
+                broadcaster
+                    .add_transcription(format!("segment {}", i), 100.0, 150.0, 2)
+                    .await;
+                tokio::time::sleep(Duration::from_millis(2)).await;
+            }
+        })
+    };
+
+    // Clients connect while the producer is running; each must see every
+    // transcription exactly once, via catch-up or live broadcast
+    let mut joiners = Vec::new();
+    for n in 0..6 {
+        let socket_path = socket_path.clone();
+        joiners.push(tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(n * 15)).await;
+            let mut client = TestClient::connect(&socket_path).await;
+            client.drain().await
+        }));
+    }
+
+    producer.await.unwrap();
+    let expected: Vec<String> = (0..SEGMENTS).map(|i| format!("segment {}", i)).collect();
+    for joiner in joiners {
+        let events = joiner.await.unwrap();
+        assert_eq!(&types(&events)[..2], ["state_change", "session_start"]);
+        assert_eq!(transcription_texts(&events), expected);
+    }
+
+    broadcaster.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_client_switches_to_compressed_msgpack() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let temp_dir = tempdir().unwrap();
+    let socket_path = temp_dir.path().join("negotiate.sock");
+    let broadcaster = MetricsBroadcaster::new(&socket_path).await.unwrap();
+    broadcaster.start().await.unwrap();
+    broadcaster.start_session(5).await;
+    broadcaster
+        .add_transcription("before".to_string(), 100.0, 150.0, 1)
+        .await;
+
+    let stream = UnixStream::connect(&socket_path).await.unwrap();
+    let (reader, mut writer) = stream.into_split();
+    writer
+        .write_all(b"{\"encoding\":\"msgpack\",\"compression\":\"zstd\"}\n")
+        .await
+        .unwrap();
+
+    // JSON until the acknowledgement, which names the new format
+    let mut reader = BufReader::new(reader);
+    loop {
+        let mut line = String::new();
+        tokio::time::timeout(Duration::from_secs(1), reader.read_line(&mut line))
+            .await
+            .unwrap()
+            .unwrap();
+        let event: Value = serde_json::from_str(&line).unwrap();
+        if event["type"] == "encoding" {
+            assert_eq!(event["encoding"], "msgpack");
+            assert_eq!(event["compression"], "zstd");
+            break;
+        }
+    }
+    wait_for_clients(&broadcaster, 1).await;
+    broadcaster
+        .add_transcription("after".to_string(), 100.0, 150.0, 1)
+        .await;
+
+    let mut compressed = Vec::new();
+    let mut chunk = [0u8; 4096];
+    while let Ok(Ok(n)) = tokio::time::timeout(QUIET, reader.read(&mut chunk)).await {
+        if n == 0 {
+            break;
+        }
+        compressed.extend_from_slice(&chunk[..n]);
+    }
+
+    // Catch-up again, then the live event
+    let mut decoder = zstd::stream::read::Decoder::new(compressed.as_slice()).unwrap();
+    let mut events = Vec::new();
+    while let Ok(event) = rmp_serde::from_read::<_, Value>(&mut decoder) {
+        events.push(event);
+    }
+    assert_eq!(
+        types(&events),
+        [
+            "state_change",
+            "session_start",
+            "transcription",
+            "transcription"
+        ]
+    );
+    assert_eq!(transcription_texts(&events), ["before", "after"]);
+
+    broadcaster.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_audio_levels_only_reach_the_firehose() {
+    let temp_dir = tempdir().unwrap();
+    let socket_path = temp_dir.path().join("control.sock");
+    let firehose_path = temp_dir.path().join("firehose.sock");
+    let broadcaster = MetricsBroadcaster::new(&socket_path)
+        .await
+        .unwrap()
+        .with_firehose(&firehose_path);
+    broadcaster.start().await.unwrap();
+    broadcaster.start_session(3).await;
+
+    let mut control = TestClient::connect(&socket_path).await;
+    let mut firehose = TestClient::connect(&firehose_path).await;
+    wait_for_clients(&broadcaster, 1).await;
+    for _ in 0..100 {
+        if broadcaster.firehose_client_count().await == 1 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    broadcaster
+        .broadcast_audio_level(-30.0, -12.0, 0.9, vec![0.5, 0.9])
+        .await;
+    broadcaster
+        .broadcast_state_change(DaemonState::Recording)
+        .await;
+
+    let control_events = control.drain().await;
+    assert_eq!(
+        types(&control_events),
+        ["state_change", "session_start", "state_change"]
+    );
+
+    // No catch-up on the firehose, and no control events
+    let firehose_events = firehose.drain().await;
+    assert_eq!(types(&firehose_events), ["audio_level"]);
+    assert_eq!(firehose_events[0]["speech_probability"], 0.9);
+    assert_eq!(firehose_events[0]["window_probabilities"][1], 0.9);
+
+    broadcaster.stop().await.unwrap();
+    assert!(!firehose_path.exists());
+}
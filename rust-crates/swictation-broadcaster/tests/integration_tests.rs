@@ -108,6 +108,45 @@ async fn test_session_start_clears_buffer() {
     broadcaster.stop().await.unwrap();
 }
 
+#[tokio::test]
+async fn test_catch_up_keeps_segment_ids() {
+    let temp_dir = tempdir().unwrap();
+    let socket_path = temp_dir.path().join("test_segment_ids.sock");
+
+    let broadcaster = MetricsBroadcaster::new(&socket_path).await.unwrap();
+    broadcaster.start().await.unwrap();
+
+    broadcaster.start_session(321).await;
+    broadcaster
+        .add_stored_transcription(321, Some(9001), "Stored".to_string(), 100.0, 200.0, 1, None)
+        .await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let mut client = UnixStream::connect(&socket_path).await.unwrap();
+    let mut reader = BufReader::new(&mut client);
+    let mut transcription = None;
+    // Expect: state_change, session_start, transcription
+    for _ in 0..3 {
+        let mut line = String::new();
+        tokio::select! {
+            result = reader.read_line(&mut line) => {
+                result.unwrap();
+                let event: serde_json::Value = serde_json::from_str(line.trim()).unwrap();
+                if event["type"] == "transcription" {
+                    transcription = Some(event);
+                }
+            }
+            _ = tokio::time::sleep(Duration::from_millis(500)) => break,
+        }
+    }
+
+    let event = transcription.expect("catch-up should replay the transcription");
+    assert_eq!(event["session_id"], 321);
+    assert_eq!(event["segment_id"], 9001);
+
+    broadcaster.stop().await.unwrap();
+}
+
 #[tokio::test]
 async fn test_session_end_keeps_buffer() {
     let temp_dir = tempdir().unwrap();
@@ -42,6 +42,7 @@ fn main() -> Result<()> {
         min_confidence: 0.70,
         enable_meta_learning: true,
         max_meta_depth: 3,
+        ..LearningConfig::default()
     };
 
     // Adaptive retraining configuration
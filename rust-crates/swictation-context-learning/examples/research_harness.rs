@@ -39,6 +39,7 @@ fn main() -> Result<()> {
         min_confidence: 0.70,
         enable_meta_learning: true,
         max_meta_depth: 3,
+        ..LearningConfig::default()
     };
 
     let mut learner = ContextLearner::new(config.clone());
@@ -47,7 +48,15 @@ fn main() -> Result<()> {
     println!("📂 Loading training data...");
     let data = learner.load_training_data(&db_path, 6)?; // Last 6 months
 
-    println!("  ✓ Loaded {} segments", data.segments.len());
+    println!(
+        "  ✓ Loaded {} of {} segments",
+        data.segments.len(),
+        data.segments_available
+    );
+    println!(
+        "  ✓ Memory: {:.1} MB",
+        data.memory_bytes as f64 / 1_048_576.0
+    );
     println!("  ✓ Total words: {}", data.total_words);
     println!("  ✓ Date range: {} days", data.date_range_days);
     println!();
@@ -75,6 +84,8 @@ fn main() -> Result<()> {
         segments: train_segments,
         total_words: data.total_words,
         date_range_days: data.date_range_days,
+        segments_available: data.segments_available,
+        memory_bytes: data.memory_bytes,
     };
 
     let model = learner.train(&train_data)?;
@@ -1,9 +1,14 @@
 //! Topic clustering using vocabulary similarity
+//!
+//! k-means over sparse TF-IDF vectors, with k chosen by silhouette score.
 
-use crate::Segment;
+use crate::sampling::SplitMix64;
+use crate::{Segment, TrainingMonitor, TrainingStage};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use tracing::debug;
 
 /// A discovered topic cluster
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,82 +20,383 @@ pub struct TopicCluster {
     pub confidence: f64,
 }
 
+/// Words too common to say anything about a topic
+const STOP_WORDS: &[&str] = &[
+    "a", "about", "above", "after", "again", "all", "also", "am", "an", "and", "any", "are", "as",
+    "at", "be", "because", "been", "before", "being", "below", "between", "both", "but", "by",
+    "can", "could", "did", "do", "does", "doing", "don't", "down", "during", "each", "few", "for",
+    "from", "further", "get", "got", "had", "has", "have", "having", "he", "her", "here", "hers",
+    "him", "his", "how", "i", "i'm", "if", "in", "into", "is", "it", "it's", "its", "just",
+    "let's", "like", "me", "more", "most", "my", "no", "nor", "not", "now", "of", "off", "ok",
+    "okay", "on", "once", "only", "or", "other", "our", "ours", "out", "over", "own", "really",
+    "same", "she", "should", "so", "some", "such", "than", "that", "that's", "the", "their",
+    "theirs", "them", "then", "there", "these", "they", "this", "those", "through", "to", "too",
+    "um", "uh", "under", "until", "up", "very", "was", "we", "were", "what", "when", "where",
+    "which", "while", "who", "whom", "why", "will", "with", "would", "yeah", "you", "your",
+    "yours",
+];
+
+/// Largest vocabulary kept (most widespread terms first)
+const MAX_VOCABULARY: usize = 5_000;
+
+/// Most segments scored when comparing values of k (silhouette is quadratic)
+const SILHOUETTE_SAMPLE: usize = 1_000;
+
+/// k-means restarts per k; the lowest-inertia run is kept
+const RESTARTS: usize = 3;
+
+const MAX_ITERATIONS: usize = 100;
+
+/// Keywords listed per topic
+const KEYWORDS_PER_TOPIC: usize = 10;
+
 /// Discover topic clusters from segments
-pub fn discover_topics(segments: &[Segment], num_clusters: usize) -> Result<Vec<TopicCluster>> {
-    // Build vocabulary frequency map
-    let mut word_freq: HashMap<String, usize> = HashMap::new();
+///
+/// Segments become TF-IDF vectors (stop words removed) and are clustered
+/// with k-means for every k in `k_range`; the k with the best silhouette
+/// score wins. The same `seed` and segments always give the same topics.
+/// Progress counts the values of k tried.
+pub fn discover_topics(
+    segments: &[Segment],
+    k_range: RangeInclusive<usize>,
+    seed: u64,
+    monitor: &TrainingMonitor,
+) -> Result<Vec<TopicCluster>> {
+    let tfidf = TfIdf::fit(segments);
+    let n = tfidf.docs.len();
+    let hi = (*k_range.end()).min(n).max(1);
+    let lo = (*k_range.start()).clamp(1, hi);
+    let candidates = hi - lo + 1;
+    monitor.report(TrainingStage::Clustering, 0, candidates)?;
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+
+    // Pairwise distances over an evenly spread sample, shared by every k
+    let sample: Vec<usize> = (0..n.min(SILHOUETTE_SAMPLE))
+        .map(|i| i * n / n.min(SILHOUETTE_SAMPLE))
+        .collect();
+    let distances = if candidates > 1 {
+        sample_distances(&tfidf.docs, &sample)
+    } else {
+        Vec::new()
+    };
 
-    for segment in segments {
-        for word in segment.text.split_whitespace() {
-            let word_lower = word.to_lowercase();
-            *word_freq.entry(word_lower).or_insert(0) += 1;
+    let mut rng = SplitMix64::new(seed);
+    let mut best: Option<(f64, KMeans)> = None;
+    for (tried, k) in (lo..=hi).enumerate() {
+        let mut run = kmeans(&tfidf.docs, tfidf.vocabulary.len(), k, &mut rng, monitor)?;
+        for _ in 1..RESTARTS {
+            let other = kmeans(&tfidf.docs, tfidf.vocabulary.len(), k, &mut rng, monitor)?;
+            if other.inertia < run.inertia {
+                run = other;
+            }
+        }
+        let score = if candidates > 1 {
+            silhouette(&run.assignments, &sample, &distances)
+        } else {
+            0.0
+        };
+        debug!("k={}: silhouette {:.3}", k, score);
+        if best
+            .as_ref()
+            .is_none_or(|(best_score, _)| score > *best_score)
+        {
+            best = Some((score, run));
         }
+        monitor.report(TrainingStage::Clustering, tried + 1, candidates)?;
+    }
+    let (score, run) = best.expect("at least one k is tried");
+    debug!(
+        "Chose {} topics (silhouette {:.3})",
+        run.centroids.len(),
+        score
+    );
+
+    let mut clusters: Vec<TopicCluster> = run
+        .centroids
+        .iter()
+        .enumerate()
+        .map(|(c, centroid)| {
+            let mut terms: Vec<usize> =
+                (0..centroid.len()).filter(|&t| centroid[t] > 0.0).collect();
+            terms.sort_by(|&a, &b| centroid[b].total_cmp(&centroid[a]).then(a.cmp(&b)));
+            let keywords: Vec<String> = terms
+                .into_iter()
+                .take(KEYWORDS_PER_TOPIC)
+                .map(|t| tfidf.vocabulary[t].clone())
+                .collect();
+            TopicCluster {
+                id: 0,
+                name: infer_cluster_name(&keywords),
+                keywords,
+                segment_count: run.assignments.iter().filter(|&&a| a == c).count(),
+                confidence: 0.8,
+            }
+        })
+        .filter(|cluster| cluster.segment_count > 0)
+        .collect();
+    // Largest topic first
+    clusters.sort_by(|a, b| b.segment_count.cmp(&a.segment_count));
+    for (id, cluster) in clusters.iter_mut().enumerate() {
+        cluster.id = id;
     }
 
-    // Simple clustering: group by most frequent words
-    // In production, would use k-means on TF-IDF vectors
-    let mut top_words: Vec<(&String, &usize)> = word_freq.iter().collect();
-    top_words.sort_by(|a, b| b.1.cmp(a.1));
+    Ok(clusters)
+}
+
+/// Lowercased content words of `text`
+fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '\''))
+        .map(|word| word.trim_matches('\'').to_lowercase())
+        .filter(|word| {
+            word.chars().count() > 1
+                && !word.chars().all(|c| c.is_numeric())
+                && !STOP_WORDS.contains(&word.as_str())
+        })
+}
 
-    let words_per_cluster = top_words.len() / num_clusters.max(1);
+/// Sparse vector: (term index, weight), sorted by term index
+type SparseVec = Vec<(usize, f64)>;
 
-    let mut clusters = Vec::new();
-    for i in 0..num_clusters {
-        let start = i * words_per_cluster;
-        let end = ((i + 1) * words_per_cluster).min(top_words.len());
+/// Segments as unit-length TF-IDF vectors over a shared vocabulary
+///
+/// Vectors stay sparse: a dense segments × vocabulary matrix would not fit
+/// in memory for a long history.
+struct TfIdf {
+    vocabulary: Vec<String>,
+    /// Segments with at least one content word
+    docs: Vec<SparseVec>,
+}
 
-        if start >= top_words.len() {
-            break;
+impl TfIdf {
+    fn fit(segments: &[Segment]) -> Self {
+        let counts: Vec<HashMap<String, usize>> = segments
+            .iter()
+            .map(|segment| {
+                let mut counts = HashMap::new();
+                for word in tokenize(&segment.text) {
+                    *counts.entry(word).or_insert(0) += 1;
+                }
+                counts
+            })
+            .filter(|counts| !counts.is_empty())
+            .collect();
+
+        let mut doc_freq: HashMap<&str, usize> = HashMap::new();
+        for doc in &counts {
+            for term in doc.keys() {
+                *doc_freq.entry(term).or_insert(0) += 1;
+            }
         }
+        // Sorted, so the vocabulary (and everything after) is deterministic
+        let mut terms: Vec<(&str, usize)> = doc_freq.into_iter().collect();
+        terms.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        terms.truncate(MAX_VOCABULARY);
 
-        let keywords: Vec<String> = top_words[start..end]
+        let n = counts.len() as f64;
+        let index: HashMap<&str, usize> = terms
+            .iter()
+            .enumerate()
+            .map(|(i, (term, _))| (*term, i))
+            .collect();
+        // Smoothed inverse document frequency
+        let idf: Vec<f64> = terms
             .iter()
-            .take(10)
-            .map(|(word, _)| word.to_string())
+            .map(|&(_, df)| ((1.0 + n) / (1.0 + df as f64)).ln() + 1.0)
             .collect();
 
-        // Infer cluster name from keywords
-        let name = infer_cluster_name(&keywords);
+        let docs = counts
+            .iter()
+            .filter_map(|doc| {
+                let mut vec: SparseVec = doc
+                    .iter()
+                    .filter_map(|(term, &tf)| {
+                        index.get(term.as_str()).map(|&i| (i, tf as f64 * idf[i]))
+                    })
+                    .collect();
+                if vec.is_empty() {
+                    return None;
+                }
+                vec.sort_by_key(|&(i, _)| i);
+                let norm = vec.iter().map(|(_, w)| w * w).sum::<f64>().sqrt();
+                vec.iter_mut().for_each(|(_, w)| *w /= norm);
+                Some(vec)
+            })
+            .collect();
 
-        clusters.push(TopicCluster {
-            id: i,
-            name,
-            keywords,
-            segment_count: 0, // Will be populated later
-            confidence: 0.8,
-        });
+        Self {
+            vocabulary: terms
+                .into_iter()
+                .map(|(term, _)| term.to_string())
+                .collect(),
+            docs,
+        }
     }
+}
 
-    // Count segments per cluster (assign to cluster with most keyword matches)
-    for segment in segments {
-        let segment_words: Vec<String> = segment
-            .text
-            .split_whitespace()
-            .map(|w| w.to_lowercase())
-            .collect();
+fn sparse_dot(a: &SparseVec, b: &SparseVec) -> f64 {
+    let (mut i, mut j, mut dot) = (0, 0, 0.0);
+    while i < a.len() && j < b.len() {
+        match a[i].0.cmp(&b[j].0) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                dot += a[i].1 * b[j].1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    dot
+}
 
-        let mut best_cluster = 0;
-        let mut max_matches = 0;
+/// Squared distance from a unit-length `doc` to a centroid of squared
+/// length `centroid_norm2`
+fn centroid_distance2(doc: &SparseVec, centroid: &[f64], centroid_norm2: f64) -> f64 {
+    let dot: f64 = doc.iter().map(|&(i, w)| w * centroid[i]).sum();
+    (1.0 - 2.0 * dot + centroid_norm2).max(0.0)
+}
 
-        for (idx, cluster) in clusters.iter().enumerate() {
-            let matches = cluster
-                .keywords
+struct KMeans {
+    centroids: Vec<Vec<f64>>,
+    assignments: Vec<usize>,
+    /// Sum of squared distances to the assigned centroids
+    inertia: f64,
+}
+
+/// One k-means run (k-means++ seeding, then Lloyd iterations)
+fn kmeans(
+    docs: &[SparseVec],
+    dim: usize,
+    k: usize,
+    rng: &mut SplitMix64,
+    monitor: &TrainingMonitor,
+) -> Result<KMeans> {
+    let dense = |doc: &SparseVec| {
+        let mut v = vec![0.0; dim];
+        doc.iter().for_each(|&(i, w)| v[i] = w);
+        v
+    };
+
+    let mut centroids = vec![dense(&docs[(rng.next_u64() % docs.len() as u64) as usize])];
+    let mut nearest: Vec<f64> = docs
+        .iter()
+        .map(|doc| centroid_distance2(doc, &centroids[0], 1.0))
+        .collect();
+    while centroids.len() < k {
+        // Next centre drawn with probability proportional to squared distance
+        let total: f64 = nearest.iter().sum();
+        let mut target = rng.next_f64() * total;
+        let mut pick = docs.len() - 1;
+        for (i, d) in nearest.iter().enumerate() {
+            if target < *d {
+                pick = i;
+                break;
+            }
+            target -= d;
+        }
+        centroids.push(dense(&docs[pick]));
+        let centroid = centroids.last().expect("just pushed");
+        for (doc, d) in docs.iter().zip(nearest.iter_mut()) {
+            *d = d.min(centroid_distance2(doc, centroid, 1.0));
+        }
+    }
+
+    let mut assignments = vec![usize::MAX; docs.len()];
+    let mut inertia = 0.0;
+    for _ in 0..MAX_ITERATIONS {
+        monitor.checkpoint()?;
+        let norms: Vec<f64> = centroids
+            .iter()
+            .map(|c| c.iter().map(|x| x * x).sum())
+            .collect();
+        let mut changed = false;
+        inertia = 0.0;
+        for (doc, assigned) in docs.iter().zip(assignments.iter_mut()) {
+            let (best, distance) = centroids
                 .iter()
-                .filter(|kw| segment_words.contains(kw))
-                .count();
+                .zip(&norms)
+                .map(|(c, &norm2)| centroid_distance2(doc, c, norm2))
+                .enumerate()
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .expect("k >= 1");
+            inertia += distance;
+            if *assigned != best {
+                *assigned = best;
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
 
-            if matches > max_matches {
-                max_matches = matches;
-                best_cluster = idx;
+        // An emptied cluster keeps its previous centre
+        let mut sums = vec![vec![0.0; dim]; k];
+        let mut sizes = vec![0usize; k];
+        for (doc, &c) in docs.iter().zip(&assignments) {
+            sizes[c] += 1;
+            doc.iter().for_each(|&(i, w)| sums[c][i] += w);
+        }
+        for ((centroid, sum), size) in centroids.iter_mut().zip(sums).zip(sizes) {
+            if size > 0 {
+                *centroid = sum.into_iter().map(|x| x / size as f64).collect();
             }
         }
+    }
+
+    Ok(KMeans {
+        centroids,
+        assignments,
+        inertia,
+    })
+}
 
-        if let Some(cluster) = clusters.get_mut(best_cluster) {
-            cluster.segment_count += 1;
+/// Euclidean distances between the sampled docs (row-major, m × m)
+fn sample_distances(docs: &[SparseVec], sample: &[usize]) -> Vec<f32> {
+    let m = sample.len();
+    let mut distances = vec![0.0f32; m * m];
+    for a in 0..m {
+        for b in a + 1..m {
+            let dot = sparse_dot(&docs[sample[a]], &docs[sample[b]]);
+            let d = (2.0 - 2.0 * dot).max(0.0).sqrt() as f32;
+            distances[a * m + b] = d;
+            distances[b * m + a] = d;
         }
     }
+    distances
+}
 
-    Ok(clusters)
+/// Mean silhouette coefficient of the sampled docs (-1 to 1, higher is
+/// better separated); a point alone in its cluster scores 0
+fn silhouette(assignments: &[usize], sample: &[usize], distances: &[f32]) -> f64 {
+    let m = sample.len();
+    let k = sample.iter().map(|&i| assignments[i]).max().unwrap_or(0) + 1;
+    let mut total = 0.0;
+    for a in 0..m {
+        let mut sums = vec![0.0f64; k];
+        let mut sizes = vec![0usize; k];
+        for b in 0..m {
+            if a != b {
+                let c = assignments[sample[b]];
+                sums[c] += distances[a * m + b] as f64;
+                sizes[c] += 1;
+            }
+        }
+        let own = assignments[sample[a]];
+        if sizes[own] == 0 {
+            continue;
+        }
+        let cohesion = sums[own] / sizes[own] as f64;
+        let separation = (0..k)
+            .filter(|&c| c != own && sizes[c] > 0)
+            .map(|c| sums[c] / sizes[c] as f64)
+            .fold(f64::INFINITY, f64::min);
+        if separation.is_finite() {
+            total += (separation - cohesion) / cohesion.max(separation).max(f64::EPSILON);
+        }
+    }
+    total / m as f64
 }
 
 /// Infer human-readable cluster name from keywords
@@ -210,10 +516,120 @@ mod tests {
             },
         ];
 
-        let result = discover_topics(&segments, 2);
+        let result = discover_topics(&segments, 2..=2, 42, &TrainingMonitor::default());
         assert!(result.is_ok());
 
         let clusters = result.unwrap();
         assert!(clusters.len() <= 2);
     }
+
+    fn segment(id: i64, text: String) -> Segment {
+        Segment {
+            segment_id: id,
+            session_id: 1,
+            timestamp: Utc::now(),
+            words: text.split_whitespace().count() as i32,
+            text,
+            transformations_count: 0,
+        }
+    }
+
+    const TOPICS: [[&str; 8]; 3] = [
+        [
+            "refactor", "function", "compiler", "database", "query", "endpoint", "schema", "module",
+        ],
+        [
+            "budget", "meeting", "revenue", "client", "deadline", "quarter", "forecast", "invoice",
+        ],
+        [
+            "garden",
+            "tomatoes",
+            "soil",
+            "compost",
+            "seedlings",
+            "watering",
+            "mulch",
+            "harvest",
+        ],
+    ];
+
+    /// 8 distinct segments per topic, each mixing stop words with four of
+    /// its words
+    fn synthetic_corpus() -> Vec<Segment> {
+        let mut segments = Vec::new();
+        for j in 0..8 {
+            for words in &TOPICS {
+                let text = format!(
+                    "so the {} and {} with this {} is {}",
+                    words[j],
+                    words[(j + 1) % 8],
+                    words[(j + 2) % 8],
+                    words[(j + 4) % 8]
+                );
+                segments.push(segment(segments.len() as i64, text));
+            }
+        }
+        segments
+    }
+
+    #[test]
+    fn test_tokenize_drops_stop_words() {
+        let words: Vec<String> = tokenize("So, the Database's query is 42 times FASTER!").collect();
+        assert_eq!(words, vec!["database's", "query", "times", "faster"]);
+    }
+
+    #[test]
+    fn test_discover_known_topics() {
+        let clusters =
+            discover_topics(&synthetic_corpus(), 2..=6, 42, &TrainingMonitor::default()).unwrap();
+
+        // Silhouette picks the three planted topics, each intact
+        assert_eq!(clusters.len(), 3);
+        for cluster in &clusters {
+            assert_eq!(cluster.segment_count, 8);
+            let topic = TOPICS
+                .iter()
+                .find(|words| words.contains(&cluster.keywords[0].as_str()))
+                .unwrap();
+            assert!(cluster
+                .keywords
+                .iter()
+                .all(|kw| topic.contains(&kw.as_str())));
+            assert!(!cluster
+                .keywords
+                .iter()
+                .any(|kw| STOP_WORDS.contains(&kw.as_str())));
+        }
+        assert!(clusters.iter().any(|c| c.name == "Software Development"));
+    }
+
+    #[test]
+    fn test_discover_topics_deterministic() {
+        let segments = synthetic_corpus();
+        let run = |seed| {
+            discover_topics(&segments, 2..=6, seed, &TrainingMonitor::default())
+                .unwrap()
+                .into_iter()
+                .map(|c| (c.keywords, c.segment_count))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(run(7), run(7));
+    }
+
+    #[test]
+    fn test_discover_topics_cancelled() {
+        let segments = vec![Segment {
+            segment_id: 1,
+            session_id: 1,
+            timestamp: Utc::now(),
+            text: "refactor the authentication class".to_string(),
+            words: 4,
+            transformations_count: 0,
+        }];
+        let monitor = TrainingMonitor::default();
+        monitor.cancel_token().cancel();
+
+        let err = discover_topics(&segments, 2..=2, 42, &monitor).unwrap_err();
+        assert!(err.downcast_ref::<crate::Cancelled>().is_some());
+    }
 }
@@ -7,6 +7,9 @@
 //! - Temporal patterns (time of day, session clustering)
 //! - Transformation success signals
 //!
+//! Training reports progress and can be cancelled through a
+//! [`TrainingMonitor`] (see [`ContextLearner::with_monitor`]).
+//!
 //! ## Research Objectives
 //!
 //! 1. Quantify improvement of context-aware learning vs static rules
@@ -25,14 +28,21 @@ use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::{info, warn};
 
+use crate::sampling::{recency_weight, Reservoir};
+
 mod clustering;
 mod homonym;
+mod ngram;
 mod patterns;
+mod progress;
+mod sampling;
 mod validation;
 
 pub use clustering::TopicCluster;
 pub use homonym::HomonymResolver;
+pub use ngram::NgramModel;
 pub use patterns::ContextPattern;
+pub use progress::{CancelToken, Cancelled, TrainingMonitor, TrainingProgress, TrainingStage};
 pub use validation::ValidationReport;
 
 /// A single segment from the metrics database
@@ -52,6 +62,11 @@ pub struct TrainingData {
     pub segments: Vec<Segment>,
     pub total_words: usize,
     pub date_range_days: i64,
+    /// Segments in the database window before sampling (see
+    /// [`LearningConfig::max_segments`])
+    pub segments_available: usize,
+    /// Approximate memory held by `segments`, in bytes
+    pub memory_bytes: usize,
 }
 
 /// Context model learned from segment patterns
@@ -78,9 +93,13 @@ pub struct LearningConfig {
     /// Minimum segments required for training
     pub min_segments: usize,
 
-    /// Number of topic clusters (k-means)
+    /// Most topic clusters (k-means tries every k from `min_topics` up to
+    /// this and keeps the one with the best silhouette score)
     pub num_topics: usize,
 
+    /// Fewest topic clusters
+    pub min_topics: usize,
+
     /// Context window size (number of previous segments)
     pub context_window: usize,
 
@@ -92,6 +111,18 @@ pub struct LearningConfig {
 
     /// Max meta-learning depth
     pub max_meta_depth: usize,
+
+    /// Most segments loaded for training (0 = no cap); beyond it a sample
+    /// weighted toward recent segments is kept
+    pub max_segments: usize,
+
+    /// Age (days) at which a segment is half as likely to be sampled as one
+    /// from today
+    pub recency_half_life_days: f64,
+
+    /// Seed for sampling and clustering, so runs over the same data are
+    /// reproducible
+    pub seed: u64,
 }
 
 impl Default for LearningConfig {
@@ -99,10 +130,14 @@ impl Default for LearningConfig {
         Self {
             min_segments: 50,
             num_topics: 5,
+            min_topics: 2,
             context_window: 10,
             min_confidence: 0.70,
             enable_meta_learning: true,
             max_meta_depth: 3,
+            max_segments: 20_000,
+            recency_half_life_days: 60.0,
+            seed: 42,
         }
     }
 }
@@ -134,10 +169,14 @@ impl Default for RetrainingConfig {
     }
 }
 
+/// Segments loaded between cancellation checks
+const LOAD_CHECK_INTERVAL: usize = 1000;
+
 /// The main context learning engine
 pub struct ContextLearner {
     config: LearningConfig,
     strange_loop: Option<StrangeLoop>,
+    monitor: TrainingMonitor,
 }
 
 impl ContextLearner {
@@ -157,9 +196,18 @@ impl ContextLearner {
         Self {
             config,
             strange_loop,
+            monitor: TrainingMonitor::default(),
         }
     }
 
+    /// Report progress to `monitor` and stop when it is cancelled
+    ///
+    /// Cancelled runs fail with [`Cancelled`].
+    pub fn with_monitor(mut self, monitor: TrainingMonitor) -> Self {
+        self.monitor = monitor;
+        self
+    }
+
     /// Load training data from metrics database
     pub fn load_training_data<P: AsRef<Path>>(
         &self,
@@ -187,7 +235,7 @@ impl ContextLearner {
              ORDER BY timestamp ASC",
         )?;
 
-        let segments: Vec<Segment> = stmt
+        let rows = stmt
             .query_map(params![threshold_timestamp], |row| {
                 let timestamp_f64: f64 = row.get(2)?;
                 let naive = DateTime::from_timestamp(timestamp_f64 as i64, 0)
@@ -204,13 +252,47 @@ impl ContextLearner {
                     transformations_count: row.get(5)?,
                 })
             })?
-            .filter_map(|r| r.ok())
-            .collect();
+            .filter_map(|r| r.ok());
+
+        // Rows are sampled as they stream in; only the sample is held
+        let now = Utc::now();
+        let mut reservoir = Reservoir::new(self.config.max_segments, self.config.seed);
+        for segment in rows {
+            let age_days = (now - segment.timestamp).num_seconds() as f64 / 86_400.0;
+            let weight = recency_weight(age_days, self.config.recency_half_life_days);
+            reservoir.offer(segment, weight);
+            if reservoir.seen() % LOAD_CHECK_INTERVAL == 0 {
+                self.monitor.checkpoint()?;
+            }
+        }
+        let segments_available = reservoir.seen();
+        let mut segments = reservoir.into_items();
+        segments.sort_by_key(|s| s.timestamp);
+        self.monitor.report(
+            TrainingStage::LoadingSegments,
+            segments_available,
+            segments_available,
+        )?;
 
+        let memory_bytes = segments
+            .iter()
+            .map(|s| std::mem::size_of::<Segment>() + s.text.capacity())
+            .sum();
         if segments.is_empty() {
             warn!("No segments found in database");
+        } else if segments.len() < segments_available {
+            info!(
+                "Sampled {} of {} segments (~{:.1} MB)",
+                segments.len(),
+                segments_available,
+                memory_bytes as f64 / 1_048_576.0
+            );
         } else {
-            info!("Loaded {} segments", segments.len());
+            info!(
+                "Loaded {} segments (~{:.1} MB)",
+                segments.len(),
+                memory_bytes as f64 / 1_048_576.0
+            );
         }
 
         let total_words: usize = segments.iter().map(|s| s.words as usize).sum();
@@ -227,6 +309,8 @@ impl ContextLearner {
             segments,
             total_words,
             date_range_days,
+            segments_available,
+            memory_bytes,
         })
     }
 
@@ -244,15 +328,22 @@ impl ContextLearner {
 
         // 1. Discover topic clusters
         info!("Discovering topic clusters...");
-        let topics = clustering::discover_topics(&data.segments, self.config.num_topics)?;
+        let topics = clustering::discover_topics(
+            &data.segments,
+            self.config.min_topics..=self.config.num_topics,
+            self.config.seed,
+            &self.monitor,
+        )?;
         info!("Discovered {} topic clusters", topics.len());
 
         // 2. Learn homonym resolution rules
+        self.monitor.report(TrainingStage::Homonyms, 0, 1)?;
         info!("Learning homonym resolution...");
         let homonym_rules = homonym::learn_homonym_rules(&data.segments, &topics)?;
         info!("Learned {} homonym rules", homonym_rules.len());
 
         // 3. Extract context patterns
+        self.monitor.report(TrainingStage::Patterns, 0, 1)?;
         info!("Extracting context patterns...");
         let patterns = patterns::extract_patterns(&data.segments, self.config.context_window)?;
         info!("Extracted {} context patterns", patterns.len());
@@ -260,6 +351,7 @@ impl ContextLearner {
         // 4. Meta-learning with strange-loop
         let (meta_level_0, meta_level_1, meta_level_2) = if let Some(ref mut sl) = self.strange_loop
         {
+            self.monitor.report(TrainingStage::MetaLearning, 0, 1)?;
             info!("Running meta-learning (strange-loop)...");
 
             // Level 0: Raw segment patterns
@@ -307,7 +399,7 @@ impl ContextLearner {
         model: &ContextModel,
         test_data: &[Segment],
     ) -> Result<ValidationReport> {
-        validation::evaluate_model(model, test_data, self.config.min_confidence)
+        validation::evaluate_model(model, test_data, self.config.min_confidence, &self.monitor)
     }
 
     /// Get meta-learning summary
@@ -429,11 +521,32 @@ pub fn load_or_train_model(
     db_path: &Path,
     learning_config: &LearningConfig,
     retrain_config: &RetrainingConfig,
+) -> Result<Option<ContextModel>> {
+    load_or_train_model_with(
+        model_path,
+        db_path,
+        learning_config,
+        retrain_config,
+        &TrainingMonitor::default(),
+    )
+}
+
+/// [`load_or_train_model`], reporting training progress to `monitor`
+///
+/// A cancelled run fails with [`Cancelled`] and leaves the saved model as
+/// it was.
+pub fn load_or_train_model_with(
+    model_path: &Path,
+    db_path: &Path,
+    learning_config: &LearningConfig,
+    retrain_config: &RetrainingConfig,
+    monitor: &TrainingMonitor,
 ) -> Result<Option<ContextModel>> {
     if should_retrain(model_path, db_path, retrain_config)? {
         info!("Retraining context model...");
 
-        let mut learner = ContextLearner::new(learning_config.clone());
+        let mut learner =
+            ContextLearner::new(learning_config.clone()).with_monitor(monitor.clone());
         let data = learner.load_training_data(db_path, 6)?; // Last 6 months
 
         if data.segments.len() < learning_config.min_segments {
@@ -446,13 +559,19 @@ pub fn load_or_train_model(
         }
 
         let model = learner.train(&data)?;
+        monitor.checkpoint()?;
 
         // Save model
         let model_json =
             serde_json::to_string_pretty(&model).context("Failed to serialize model")?;
         fs::write(model_path, model_json).context("Failed to write model file")?;
 
-        info!("Context model trained and saved successfully");
+        info!(
+            "Context model trained on {} of {} segments (~{:.1} MB) and saved",
+            data.segments.len(),
+            data.segments_available,
+            data.memory_bytes as f64 / 1_048_576.0
+        );
         Ok(Some(model))
     } else if model_path.exists() {
         // Load existing model
@@ -465,6 +584,63 @@ pub fn load_or_train_model(
     }
 }
 
+/// Word order of the language model used for decoding (trigram)
+const NGRAM_ORDER: usize = 3;
+
+/// Load or train the n-gram language model with adaptive retraining
+///
+/// Trained on the user's transcripts from the metrics database plus
+/// `extra_texts` (e.g. the corrected side of learned corrections, which
+/// exist even when transcript text is not stored). Returns `None` when
+/// there is nothing to train on yet.
+pub fn load_or_train_ngram(
+    model_path: &Path,
+    db_path: &Path,
+    extra_texts: &[String],
+    retrain_config: &RetrainingConfig,
+) -> Result<Option<NgramModel>> {
+    if should_retrain(model_path, db_path, retrain_config)? {
+        info!("Retraining n-gram language model...");
+
+        let learner = ContextLearner::new(LearningConfig {
+            enable_meta_learning: false,
+            ..LearningConfig::default()
+        });
+        let data = learner.load_training_data(db_path, 6)?; // Last 6 months
+
+        let texts = data
+            .segments
+            .iter()
+            .map(|s| s.text.as_str())
+            .chain(extra_texts.iter().map(String::as_str));
+        let model = NgramModel::train(texts, NGRAM_ORDER);
+
+        if model.vocabulary_size() == 0 {
+            warn!("No transcripts or corrections to train the n-gram model on");
+            return Ok(None);
+        }
+
+        let model_json =
+            serde_json::to_string(&model).context("Failed to serialize n-gram model")?;
+        fs::write(model_path, model_json).context("Failed to write n-gram model file")?;
+
+        info!(
+            "N-gram model trained ({} words) and saved",
+            model.vocabulary_size()
+        );
+        Ok(Some(model))
+    } else if model_path.exists() {
+        info!("Loading existing n-gram model");
+        let model_json =
+            fs::read_to_string(model_path).context("Failed to read n-gram model file")?;
+        let model =
+            serde_json::from_str(&model_json).context("Failed to deserialize n-gram model")?;
+        Ok(Some(model))
+    } else {
+        Ok(None)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -489,6 +665,8 @@ mod tests {
             segments,
             total_words: 8,
             date_range_days: 1,
+            segments_available: 4,
+            memory_bytes: 0,
         };
 
         let (train, test) = train_test_split(&data, 0.75);
@@ -0,0 +1,243 @@
+//! Word n-gram language model of the user's own phrasing
+//!
+//! Trained on the user's transcripts (and learned corrections) so decoding
+//! can prefer word sequences they actually use. Probabilities use
+//! interpolated absolute discounting down to a uniform distribution, so
+//! unseen words keep a small but nonzero probability.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Sentence-start marker used as history for the first words of a segment
+const SENTENCE_START: &str = "<s>";
+
+/// Absolute discount subtracted from every observed count
+const DISCOUNT: f64 = 0.75;
+
+/// Counts of words following one context
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ContextCounts {
+    total: u32,
+    next: HashMap<String, u32>,
+}
+
+/// Word-level n-gram model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NgramModel {
+    order: usize,
+    /// Context (previous words joined by spaces, "" for unigrams) → counts
+    contexts: HashMap<String, ContextCounts>,
+    /// Unigram counts, ordered for prefix lookups
+    vocabulary: BTreeMap<String, u32>,
+}
+
+impl NgramModel {
+    /// Train a model of the given order (2 = bigram, 3 = trigram, ...)
+    pub fn train<'a>(texts: impl IntoIterator<Item = &'a str>, order: usize) -> Self {
+        let order = order.max(1);
+        let mut model = Self {
+            order,
+            contexts: HashMap::new(),
+            vocabulary: BTreeMap::new(),
+        };
+
+        for text in texts {
+            let words = normalize(text);
+            if words.is_empty() {
+                continue;
+            }
+            let mut padded = vec![SENTENCE_START.to_string(); order - 1];
+            padded.extend(words);
+
+            for i in (order - 1)..padded.len() {
+                let word = &padded[i];
+                *model.vocabulary.entry(word.clone()).or_default() += 1;
+                for context_len in 0..order {
+                    let context = padded[i - context_len..i].join(" ");
+                    let counts = model.contexts.entry(context).or_default();
+                    counts.total += 1;
+                    *counts.next.entry(word.clone()).or_default() += 1;
+                }
+            }
+        }
+
+        model
+    }
+
+    /// Distinct words seen in training
+    pub fn vocabulary_size(&self) -> usize {
+        self.vocabulary.len()
+    }
+
+    /// ln P(word | history); history is the preceding words, oldest first
+    pub fn log_prob(&self, history: &[&str], word: &str) -> f64 {
+        let context = self.context_words(history);
+        self.prob(&context, &word.to_lowercase()).ln()
+    }
+
+    /// ln P(next word starts with `prefix` | history)
+    ///
+    /// Sums the probability of every known word with the prefix, plus the
+    /// mass reserved for unseen words (any prefix could start one).
+    pub fn prefix_log_prob(&self, history: &[&str], prefix: &str) -> f64 {
+        let prefix = prefix.to_lowercase();
+        let context = self.context_words(history);
+
+        let known: f64 = self
+            .vocabulary
+            .range(prefix.clone()..)
+            .take_while(|(word, _)| word.starts_with(&prefix))
+            .map(|(word, _)| self.prob(&context, word))
+            .sum();
+        (known + self.unseen_prob(&context)).min(1.0).ln()
+    }
+
+    /// ln P(text), scoring each word against the words before it
+    pub fn sentence_log_prob(&self, text: &str) -> f64 {
+        let words = normalize(text);
+        (0..words.len())
+            .map(|i| {
+                let history: Vec<&str> = words[..i].iter().map(String::as_str).collect();
+                self.log_prob(&history, &words[i])
+            })
+            .sum()
+    }
+
+    /// Order decoder hypotheses by their score plus `weight` times the
+    /// model's log-probability of their text, returning indices best first
+    ///
+    /// `hypotheses` are (text, decoder log-probability) pairs, e.g. from
+    /// `swictation_stt`'s n-best output.
+    pub fn rerank(&self, hypotheses: &[(&str, f64)], weight: f64) -> Vec<usize> {
+        let scores: Vec<f64> = hypotheses
+            .iter()
+            .map(|(text, score)| score + weight * self.sentence_log_prob(text))
+            .collect();
+        let mut order: Vec<usize> = (0..hypotheses.len()).collect();
+        // Stable: ties keep the decoder's order
+        order.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+        order
+    }
+
+    /// Last `order - 1` words of the history, padded with sentence starts
+    fn context_words(&self, history: &[&str]) -> Vec<String> {
+        let wanted = self.order - 1;
+        let mut context: Vec<String> = history
+            .iter()
+            .rev()
+            .take(wanted)
+            .map(|w| w.to_lowercase())
+            .collect();
+        context.resize(wanted, SENTENCE_START.to_string());
+        context.reverse();
+        context
+    }
+
+    /// Interpolated probability of `word` after `context` (oldest word first)
+    fn prob(&self, context: &[String], word: &str) -> f64 {
+        let uniform = 1.0 / (self.vocabulary.len() + 1) as f64;
+        (0..=context.len()).fold(uniform, |lower, used| {
+            let key = context[context.len() - used..].join(" ");
+            match self.contexts.get(&key) {
+                Some(counts) if counts.total > 0 => {
+                    let total = counts.total as f64;
+                    let seen = counts.next.get(word).copied().unwrap_or(0) as f64;
+                    let backoff = DISCOUNT * counts.next.len() as f64 / total;
+                    (seen - DISCOUNT).max(0.0) / total + backoff * lower
+                }
+                _ => lower,
+            }
+        })
+    }
+
+    /// Probability left for words never seen in training
+    fn unseen_prob(&self, context: &[String]) -> f64 {
+        self.prob(context, "")
+    }
+}
+
+/// Lowercase words with surrounding punctuation removed
+fn normalize(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'')
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model() -> NgramModel {
+        NgramModel::train(
+            [
+                "Deploy the archon service.",
+                "Restart the archon service",
+                "the archon cluster is healthy",
+                "check the logs",
+            ],
+            3,
+        )
+    }
+
+    #[test]
+    fn test_prefers_user_phrasing() {
+        let model = model();
+        assert!(
+            model.sentence_log_prob("restart the archon service")
+                > model.sentence_log_prob("restart the arkon service")
+        );
+        assert!(model.log_prob(&["the"], "archon") > model.log_prob(&["the"], "logs"));
+    }
+
+    #[test]
+    fn test_rerank_hypotheses() {
+        let model = model();
+        // The decoder slightly prefers the misspelling; the model knows better
+        let hypotheses = [
+            ("restart the arkon service", -1.0),
+            ("restart the archon service", -1.5),
+        ];
+        assert_eq!(model.rerank(&hypotheses, 0.5), vec![1, 0]);
+        assert_eq!(model.rerank(&hypotheses, 0.0), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_probabilities_are_normalized() {
+        let model = model();
+        let history = ["the"];
+        let total: f64 = model
+            .vocabulary
+            .keys()
+            .map(|w| model.log_prob(&history, w).exp())
+            .sum::<f64>()
+            + model.unseen_prob(&model.context_words(&history));
+        assert!((total - 1.0).abs() < 1e-9, "total = {}", total);
+    }
+
+    #[test]
+    fn test_prefix_probability() {
+        let model = model();
+        let arc = model.prefix_log_prob(&["the"], "arc");
+        let lo = model.prefix_log_prob(&["the"], "lo");
+        assert!(arc > lo);
+        // Unknown prefixes keep the unseen-word mass
+        assert!(model.prefix_log_prob(&["the"], "zzz").is_finite());
+        assert!(model.prefix_log_prob(&[], "").abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_roundtrip_json() {
+        let model = model();
+        let json = serde_json::to_string(&model).unwrap();
+        let loaded: NgramModel = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.vocabulary_size(), model.vocabulary_size());
+        assert_eq!(
+            loaded.sentence_log_prob("check the logs"),
+            model.sentence_log_prob("check the logs")
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! Training progress reporting and cooperative cancellation
+//!
+//! Training on months of history takes minutes. A [`TrainingMonitor`] passed
+//! to the learner is told how far each stage has got, and its
+//! [`CancelToken`] is checked at the same points: once it is cancelled, the
+//! next checkpoint fails with [`Cancelled`] and nothing is saved.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Step of a training run, in the order they happen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrainingStage {
+    LoadingSegments,
+    Clustering,
+    Homonyms,
+    Patterns,
+    MetaLearning,
+    Validation,
+}
+
+impl TrainingStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::LoadingSegments => "loading_segments",
+            Self::Clustering => "clustering",
+            Self::Homonyms => "homonyms",
+            Self::Patterns => "patterns",
+            Self::MetaLearning => "meta_learning",
+            Self::Validation => "validation",
+        }
+    }
+}
+
+/// How far a stage has got: `done` of `total` items (segments, clusters or
+/// test cases, depending on the stage)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrainingProgress {
+    pub stage: TrainingStage,
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Error returned by a training run that was cancelled
+///
+/// Find it with `err.downcast_ref::<Cancelled>()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("Training cancelled")]
+pub struct Cancelled;
+
+/// Shared flag asking a training run to stop
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the run to stop at its next checkpoint
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+type ProgressCallback = Arc<dyn Fn(TrainingProgress) + Send + Sync>;
+
+/// Progress callback and cancellation for a training run
+///
+/// The default monitor reports nowhere and is never cancelled.
+#[derive(Clone, Default)]
+pub struct TrainingMonitor {
+    on_progress: Option<ProgressCallback>,
+    cancel: CancelToken,
+}
+
+impl TrainingMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `callback` with each progress update (from the training thread)
+    pub fn on_progress(
+        mut self,
+        callback: impl Fn(TrainingProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Stop when `token` is cancelled
+    pub fn with_cancel(mut self, token: CancelToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    pub fn cancel_token(&self) -> &CancelToken {
+        &self.cancel
+    }
+
+    /// Fail with [`Cancelled`] if cancellation was requested
+    pub fn checkpoint(&self) -> Result<(), Cancelled> {
+        if self.cancel.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Report progress, then check for cancellation
+    pub fn report(&self, stage: TrainingStage, done: usize, total: usize) -> Result<(), Cancelled> {
+        if let Some(callback) = &self.on_progress {
+            callback(TrainingProgress { stage, done, total });
+        }
+        self.checkpoint()
+    }
+}
+
+impl fmt::Debug for TrainingMonitor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrainingMonitor")
+            .field("on_progress", &self.on_progress.is_some())
+            .field("cancel", &self.cancel)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_report_and_cancel() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let token = CancelToken::new();
+        let monitor = TrainingMonitor::new()
+            .on_progress({
+                let seen = seen.clone();
+                move |p| seen.lock().unwrap().push(p)
+            })
+            .with_cancel(token.clone());
+
+        assert!(monitor.report(TrainingStage::Clustering, 1, 5).is_ok());
+        token.cancel();
+        // Still reported, then stopped
+        assert_eq!(
+            monitor.report(TrainingStage::Clustering, 2, 5),
+            Err(Cancelled)
+        );
+        assert_eq!(seen.lock().unwrap().len(), 2);
+        assert_eq!(seen.lock().unwrap()[1].done, 2);
+    }
+
+    #[test]
+    fn test_stage_names_match_serde() {
+        let json = serde_json::to_string(&TrainingStage::MetaLearning).unwrap();
+        assert_eq!(
+            json,
+            format!("\"{}\"", TrainingStage::MetaLearning.as_str())
+        );
+    }
+}
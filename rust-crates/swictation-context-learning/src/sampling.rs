@@ -0,0 +1,166 @@
+//! Bounded, recency-weighted sampling of training segments
+//!
+//! Six months of a heavy user's dictation can be hundreds of thousands of
+//! segments. Rows are streamed through a weighted reservoir (Efraimidis and
+//! Spirakis' A-Res) that never holds more than the cap, so memory stays flat
+//! however long the history is. A segment's weight halves every half-life of
+//! age: recent dictation dominates the sample while older topics still show
+//! up in it.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Small deterministic PRNG (SplitMix64), so the same seed and rows give the
+/// same sample and training runs can be reproduced
+#[derive(Debug, Clone)]
+pub(crate) struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in the open interval (0, 1)
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        ((self.next_u64() >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+    }
+}
+
+/// Weight of an item `age_days` old: halves every `half_life_days`
+/// (a non-positive half-life weighs everything equally)
+pub(crate) fn recency_weight(age_days: f64, half_life_days: f64) -> f64 {
+    if half_life_days <= 0.0 {
+        1.0
+    } else {
+        0.5f64.powf(age_days.max(0.0) / half_life_days)
+    }
+}
+
+struct Entry<T> {
+    key: f64,
+    item: T,
+}
+
+// Reversed, so the heap's top is the entry with the smallest key
+impl<T> Ord for Entry<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.key.total_cmp(&self.key)
+    }
+}
+
+impl<T> PartialOrd for Entry<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> PartialEq for Entry<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key.total_cmp(&other.key) == Ordering::Equal
+    }
+}
+
+impl<T> Eq for Entry<T> {}
+
+/// Keeps a weighted random sample of at most `capacity` offered items
+/// (everything when `capacity` is 0)
+pub(crate) struct Reservoir<T> {
+    capacity: usize,
+    heap: BinaryHeap<Entry<T>>,
+    seen: usize,
+    rng: SplitMix64,
+}
+
+impl<T> Reservoir<T> {
+    pub(crate) fn new(capacity: usize, seed: u64) -> Self {
+        Self {
+            capacity,
+            heap: BinaryHeap::new(),
+            seen: 0,
+            rng: SplitMix64::new(seed),
+        }
+    }
+
+    /// Offer an item; the likelier it is kept the higher its `weight`
+    pub(crate) fn offer(&mut self, item: T, weight: f64) {
+        self.seen += 1;
+        // A-Res key u^(1/w), compared in log space: ln(u) / w
+        let key = self.rng.next_f64().ln() / weight.max(f64::MIN_POSITIVE);
+        if self.capacity == 0 || self.heap.len() < self.capacity {
+            self.heap.push(Entry { key, item });
+        } else if self.heap.peek().is_some_and(|lowest| key > lowest.key) {
+            self.heap.pop();
+            self.heap.push(Entry { key, item });
+        }
+    }
+
+    /// Items offered so far
+    pub(crate) fn seen(&self) -> usize {
+        self.seen
+    }
+
+    /// The sample, in no particular order
+    pub(crate) fn into_items(self) -> Vec<T> {
+        self.heap.into_iter().map(|entry| entry.item).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cap_and_unlimited() {
+        let mut reservoir = Reservoir::new(10, 7);
+        for i in 0..1000 {
+            reservoir.offer(i, 1.0);
+        }
+        assert_eq!(reservoir.seen(), 1000);
+        assert_eq!(reservoir.into_items().len(), 10);
+
+        let mut reservoir = Reservoir::new(0, 7);
+        for i in 0..1000 {
+            reservoir.offer(i, 1.0);
+        }
+        assert_eq!(reservoir.into_items().len(), 1000);
+    }
+
+    #[test]
+    fn test_recent_items_dominate() {
+        let mut reservoir = Reservoir::new(100, 42);
+        // 1000 items a year old (half-life 30 days), then 1000 from today
+        for i in 0..2000 {
+            let age_days = if i < 1000 { 365.0 } else { 0.0 };
+            reservoir.offer(i, recency_weight(age_days, 30.0));
+        }
+        let recent = reservoir
+            .into_items()
+            .iter()
+            .filter(|&&i| i >= 1000)
+            .count();
+        assert!(recent > 95, "only {} recent items kept", recent);
+    }
+
+    #[test]
+    fn test_same_seed_same_sample() {
+        let sample = |seed| {
+            let mut reservoir = Reservoir::new(20, seed);
+            for i in 0..500 {
+                reservoir.offer(i, recency_weight((500 - i) as f64, 100.0));
+            }
+            let mut items = reservoir.into_items();
+            items.sort();
+            items
+        };
+        assert_eq!(sample(1), sample(1));
+        assert_ne!(sample(1), sample(2));
+    }
+}
@@ -1,6 +1,6 @@
 //! Model validation and evaluation
 
-use crate::{ContextModel, Segment};
+use crate::{ContextModel, Segment, TrainingMonitor, TrainingStage};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
@@ -43,13 +43,18 @@ pub struct SafetyChecks {
     pub all_checks_passed: bool,
 }
 
+/// Test cases evaluated between progress reports
+const REPORT_INTERVAL: usize = 100;
+
 /// Evaluate model on test data
 pub fn evaluate_model(
     model: &ContextModel,
     test_data: &[Segment],
     min_confidence: f64,
+    monitor: &TrainingMonitor,
 ) -> Result<ValidationReport> {
     let mut test_cases = Vec::new();
+    monitor.report(TrainingStage::Validation, 0, test_data.len())?;
 
     // Baseline accuracy (random guess for homonyms)
     let baseline_accuracy = 0.67;
@@ -74,8 +79,13 @@ pub fn evaluate_model(
             correct,
             confidence,
         });
+        if test_cases.len() % REPORT_INTERVAL == 0 {
+            monitor.report(TrainingStage::Validation, test_cases.len(), test_data.len())?;
+        }
     }
 
+    monitor.report(TrainingStage::Validation, test_data.len(), test_data.len())?;
+
     let topic_accuracy = if !test_cases.is_empty() {
         correct_predictions as f64 / test_cases.len() as f64
     } else {
@@ -0,0 +1,234 @@
+//! Microphone check without speech recognition (`--audio-check`)
+//!
+//! Opens the configured capture device, runs the Silero VAD on what it hears
+//! and prints one line per 0.5 s chunk with the RMS and peak level and the
+//! highest speech probability, then a verdict. Only the small VAD model is
+//! needed, so this works on a server before any STT model is downloaded and
+//! can be pasted into bug reports. The VAD always runs on the CPU.
+
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use swictation_audio::AudioCapture;
+use swictation_vad::VadDetector;
+
+use crate::config::DaemonConfig;
+use crate::jobs;
+use crate::pipeline::{audio_levels_dbfs, live_audio_config};
+
+/// RMS level below which the input is treated as silent (muted or wrong device)
+const SILENT_RMS_DBFS: f32 = -60.0;
+
+/// Width of the speech probability bar
+const BAR_WIDTH: usize = 20;
+
+/// Levels and speech probability of one captured chunk
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChunkReading {
+    pub rms_dbfs: f32,
+    pub peak_dbfs: f32,
+    /// `None` when the VAD model could not be loaded
+    pub speech_probability: Option<f32>,
+}
+
+/// Outcome of the check
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Ok,
+    NoAudio,
+    Silent,
+    NoSpeech,
+    VadUnavailable,
+}
+
+/// Judge the readings of a check against the VAD `threshold`
+pub fn verdict(readings: &[ChunkReading], threshold: f32) -> Verdict {
+    if readings.is_empty() {
+        return Verdict::NoAudio;
+    }
+    let max_rms = readings
+        .iter()
+        .map(|r| r.rms_dbfs)
+        .fold(f32::NEG_INFINITY, f32::max);
+    if max_rms < SILENT_RMS_DBFS {
+        return Verdict::Silent;
+    }
+    if readings.iter().any(|r| r.speech_probability.is_none()) {
+        return Verdict::VadUnavailable;
+    }
+    if readings
+        .iter()
+        .all(|r| r.speech_probability.unwrap_or(0.0) < threshold)
+    {
+        return Verdict::NoSpeech;
+    }
+    Verdict::Ok
+}
+
+/// One output line for a reading taken `elapsed` into the check
+fn format_reading(elapsed: Duration, reading: &ChunkReading) -> String {
+    let speech = match reading.speech_probability {
+        Some(p) => {
+            let filled = ((p.clamp(0.0, 1.0) * BAR_WIDTH as f32).round()) as usize;
+            format!(
+                "speech {:.2} {}{}",
+                p,
+                "█".repeat(filled),
+                "·".repeat(BAR_WIDTH - filled)
+            )
+        }
+        None => "speech n/a".to_string(),
+    };
+    format!(
+        "{:>5.1}s  rms {:>6.1} dBFS  peak {:>6.1} dBFS  {}",
+        elapsed.as_secs_f32(),
+        reading.rms_dbfs,
+        reading.peak_dbfs,
+        speech
+    )
+}
+
+/// Capture from the configured device for `duration`, printing live levels
+pub fn run(config: &DaemonConfig, duration: Duration) -> Result<()> {
+    let mut vad = match VadDetector::new(jobs::file_vad_config(config)) {
+        Ok(vad) => Some(vad),
+        Err(e) => {
+            println!("⚠ VAD unavailable, showing levels only: {:#}", e);
+            if let Some(spec) =
+                swictation_models::missing([config.vad_model_path.as_path()]).first()
+            {
+                println!(
+                    "  Install it with: {}",
+                    swictation_models::download_command(spec)
+                );
+            }
+            None
+        }
+    };
+
+    let mut audio =
+        AudioCapture::new(live_audio_config(config)).context("Failed to open the audio device")?;
+    let (tx, rx) = mpsc::channel::<Vec<f32>>();
+    audio.set_chunk_callback(move |chunk| {
+        let _ = tx.send(chunk);
+    });
+    audio.start().context("Failed to start audio capture")?;
+
+    println!(
+        "🎙️  Listening for {} s on {} (VAD threshold {}) - speak now",
+        duration.as_secs(),
+        config
+            .audio_device_index
+            .map(|i| format!("device #{}", i))
+            .unwrap_or_else(|| "the default device".to_string()),
+        config.vad_threshold
+    );
+
+    let started = Instant::now();
+    let mut readings = Vec::new();
+    while let Some(remaining) = duration.checked_sub(started.elapsed()) {
+        let chunk = match rx.recv_timeout(remaining) {
+            Ok(chunk) => chunk,
+            Err(mpsc::RecvTimeoutError::Timeout) => break,
+            Err(mpsc::RecvTimeoutError::Disconnected) => bail!("Audio capture stopped"),
+        };
+        let (rms_dbfs, peak_dbfs) = audio_levels_dbfs(&chunk);
+        let speech_probability = match vad.as_mut() {
+            Some(vad) => {
+                vad.process_audio(&chunk).context("VAD failed")?;
+                Some(vad.take_peak_probability())
+            }
+            None => None,
+        };
+        let reading = ChunkReading {
+            rms_dbfs,
+            peak_dbfs,
+            speech_probability,
+        };
+        println!("{}", format_reading(started.elapsed(), &reading));
+        readings.push(reading);
+    }
+    let _ = audio.stop();
+
+    println!();
+    match verdict(&readings, config.vad_threshold) {
+        Verdict::Ok => {
+            println!("✓ Microphone and VAD are working");
+            Ok(())
+        }
+        Verdict::NoAudio => bail!(
+            "No audio arrived from the device - check `audio_device_index` and that no other \
+             program holds the microphone exclusively"
+        ),
+        Verdict::Silent => bail!(
+            "Input stayed below {} dBFS - the microphone is muted, unplugged or the wrong device",
+            SILENT_RMS_DBFS
+        ),
+        Verdict::NoSpeech => {
+            println!(
+                "⚠ Sound was captured but never crossed the VAD threshold {} - speak during the \
+                 check or try a lower `vad_threshold`",
+                config.vad_threshold
+            );
+            Ok(())
+        }
+        Verdict::VadUnavailable => {
+            println!("⚠ Audio levels look fine, but speech detection could not be checked");
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(rms_dbfs: f32, speech_probability: Option<f32>) -> ChunkReading {
+        ChunkReading {
+            rms_dbfs,
+            peak_dbfs: rms_dbfs + 10.0,
+            speech_probability,
+        }
+    }
+
+    #[test]
+    fn test_verdicts() {
+        assert_eq!(verdict(&[], 0.25), Verdict::NoAudio);
+        assert_eq!(
+            verdict(
+                &[reading(-90.0, Some(0.0)), reading(-75.0, Some(0.0))],
+                0.25
+            ),
+            Verdict::Silent
+        );
+        assert_eq!(
+            verdict(
+                &[reading(-30.0, Some(0.1)), reading(-25.0, Some(0.2))],
+                0.25
+            ),
+            Verdict::NoSpeech
+        );
+        assert_eq!(
+            verdict(
+                &[reading(-30.0, Some(0.1)), reading(-25.0, Some(0.9))],
+                0.25
+            ),
+            Verdict::Ok
+        );
+        assert_eq!(
+            verdict(&[reading(-30.0, None)], 0.25),
+            Verdict::VadUnavailable
+        );
+    }
+
+    #[test]
+    fn test_reading_line() {
+        let line = format_reading(Duration::from_millis(1500), &reading(-32.04, Some(0.5)));
+        assert_eq!(
+            line,
+            "  1.5s  rms  -32.0 dBFS  peak  -22.0 dBFS  speech 0.50 ██████████··········"
+        );
+        assert!(format_reading(Duration::ZERO, &reading(-32.0, None)).ends_with("speech n/a"));
+    }
+}
@@ -0,0 +1,146 @@
+//! swictation-cli - control a running daemon and collect diagnostics
+//!
+//! Daemon commands are sent over the IPC socket. `bugreport` and `secret`
+//! run the `swictation-daemon` installed next to this binary, which owns
+//! the diagnostics and keyring code, so both binaries behave the same.
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+use std::ffi::OsString;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::process::Command as Process;
+use std::time::Duration;
+
+use swictation_daemon::socket_utils;
+
+#[derive(Parser)]
+#[command(
+    name = "swictation-cli",
+    version,
+    about = "Control the Swictation daemon"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Start or stop recording
+    Toggle,
+
+    /// Print the daemon's state
+    Status,
+
+    /// Stop the daemon
+    Quit,
+
+    /// Write a local tar.gz with diagnostics for attaching to bug reports (never uploaded)
+    Bugreport {
+        /// Output archive path (default: ./swictation-bugreport-<timestamp>.tar.gz)
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+
+        /// Add the anonymous usage summary even if bugreport_analytics is off
+        #[arg(long)]
+        analytics: bool,
+    },
+
+    /// Manage API keys in the OS keyring (referenced from config as `secret:<name>`)
+    Secret {
+        #[command(subcommand)]
+        action: SecretAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum SecretAction {
+    /// Store a secret; reads it from stdin when VALUE is omitted
+    Set { name: String, value: Option<String> },
+
+    /// Print a stored secret
+    Get { name: String },
+
+    /// Remove a stored secret
+    Delete { name: String },
+}
+
+/// Send one `{"action": ...}` request and return the raw response
+fn send(action: &str) -> Result<String> {
+    let socket = socket_utils::get_ipc_socket_path()?;
+    let mut stream = UnixStream::connect(&socket).with_context(|| {
+        format!(
+            "Failed to connect to {} (is the daemon running?)",
+            socket.display()
+        )
+    })?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let request = serde_json::json!({ "action": action });
+    stream.write_all(request.to_string().as_bytes())?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    Ok(response)
+}
+
+/// Run `swictation-daemon <args>` from this binary's directory, falling
+/// back to `PATH`, and return its exit code
+fn run_daemon(args: Vec<OsString>) -> Result<i32> {
+    let daemon = std::env::current_exe()
+        .ok()
+        .and_then(|exe| Some(exe.parent()?.join("swictation-daemon")))
+        .filter(|path| path.exists())
+        .unwrap_or_else(|| PathBuf::from("swictation-daemon"));
+
+    let status = Process::new(&daemon)
+        .args(args)
+        .status()
+        .with_context(|| format!("Failed to run {}", daemon.display()))?;
+    Ok(status.code().unwrap_or(1))
+}
+
+impl SecretAction {
+    /// The same command for `swictation-daemon`
+    fn daemon_args(self) -> Vec<OsString> {
+        let (action, name, value) = match self {
+            SecretAction::Set { name, value } => ("set", name, value),
+            SecretAction::Get { name } => ("get", name, None),
+            SecretAction::Delete { name } => ("delete", name, None),
+        };
+        ["secret", action, name.as_str()]
+            .into_iter()
+            .map(OsString::from)
+            .chain(value.map(OsString::from))
+            .collect()
+    }
+}
+
+fn main() -> Result<()> {
+    let action = match Cli::parse().command {
+        Command::Toggle => "toggle",
+        Command::Status => "status",
+        Command::Quit => "quit",
+        Command::Bugreport { output, analytics } => {
+            let mut args = vec![OsString::from("bugreport")];
+            if let Some(output) = output {
+                args.extend(["--output".into(), output.into()]);
+            }
+            if analytics {
+                args.push("--analytics".into());
+            }
+            std::process::exit(run_daemon(args)?);
+        }
+        Command::Secret { action } => std::process::exit(run_daemon(action.daemon_args())?),
+    };
+
+    let response = send(action)?;
+    let response: serde_json::Value =
+        serde_json::from_str(&response).context("Invalid response from daemon")?;
+    println!("{}", serde_json::to_string_pretty(&response)?);
+    if response.get("error").is_some() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
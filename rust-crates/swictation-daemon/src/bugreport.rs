@@ -0,0 +1,365 @@
+//! Bug report bundle (`swictation-cli bugreport`, or `swictation-daemon
+//! bugreport`)
+//!
+//! Gathers doctor output, version info, the config file (with secrets
+//! scrubbed), recent daemon logs and the running daemon's journal of recent
+//! warnings and errors into a single `.tar.gz` that users can attach to
+//! GitHub issues. With `bugreport_analytics` on, an anonymous usage summary
+//! from the local metrics database is added.
+//!
+//! The bundle is written to local disk only. Nothing is ever uploaded - the
+//! user decides whether and where to share it. The archive is built
+//! in-process, so no `tar` binary is needed.
+
+use anyhow::{Context, Result};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::fs;
+use std::path::PathBuf;
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+use std::process::Command;
+use swictation_metrics::{LifetimeMetrics, MetricsDatabase};
+
+use crate::config::DaemonConfig;
+use crate::{doctor, error_log, pipeline, socket_utils, version};
+
+/// Number of log lines to include from the system journal
+const LOG_TAIL_LINES: usize = 500;
+
+/// Placeholder written in place of scrubbed values
+const REDACTED: &str = "[REDACTED]";
+
+/// Key segments (split on `_`/`-`) that mark a config value as secret
+const SECRET_KEY_PATTERNS: &[&str] =
+    &["key", "apikey", "token", "secret", "password", "credential"];
+
+fn is_secret_key(key: &str) -> bool {
+    key.to_lowercase()
+        .split(['_', '-'])
+        .any(|segment| SECRET_KEY_PATTERNS.contains(&segment))
+}
+
+/// Recursively redact secret-looking values and anonymize the home directory
+fn scrub_value(key: Option<&str>, value: &mut toml::Value, home: Option<&str>) {
+    if key.is_some_and(is_secret_key) && !value.is_table() {
+        *value = toml::Value::String(REDACTED.to_string());
+        return;
+    }
+
+    match value {
+        toml::Value::String(s) => {
+            if let Some(home) = home.filter(|h| !h.is_empty()) {
+                if s.contains(home) {
+                    *s = s.replace(home, "~");
+                }
+            }
+        }
+        toml::Value::Table(table) => {
+            for (k, v) in table.iter_mut() {
+                scrub_value(Some(k), v, home);
+            }
+        }
+        toml::Value::Array(items) => {
+            for v in items.iter_mut() {
+                scrub_value(key, v, home);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Scrub a TOML config document for inclusion in a bug report
+pub fn scrub_config(contents: &str, home: Option<&str>) -> Result<String> {
+    let mut value: toml::Value = toml::from_str(contents).context("Failed to parse config")?;
+    scrub_value(None, &mut value, home);
+    toml::to_string_pretty(&value).context("Failed to serialize scrubbed config")
+}
+
+/// Best-effort tail of the daemon's log output
+fn collect_logs() -> String {
+    #[cfg(target_os = "linux")]
+    let output = Command::new("journalctl")
+        .args([
+            "--user",
+            "-u",
+            "swictation-daemon",
+            "-n",
+            &LOG_TAIL_LINES.to_string(),
+            "--no-pager",
+        ])
+        .output();
+
+    #[cfg(target_os = "macos")]
+    let output = Command::new("log")
+        .args([
+            "show",
+            "--last",
+            "1h",
+            "--style",
+            "compact",
+            "--predicate",
+            "process == \"swictation-daemon\"",
+        ])
+        .output();
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    let output: std::io::Result<std::process::Output> = Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "log collection not supported on this platform",
+    ));
+
+    match output {
+        Ok(out) if out.status.success() => {
+            let text = String::from_utf8_lossy(&out.stdout);
+            // `log show` has no line limit, so trim here for both platforms
+            let lines: Vec<&str> = text.lines().collect();
+            let start = lines.len().saturating_sub(LOG_TAIL_LINES);
+            lines[start..].join("\n")
+        }
+        Ok(out) => format!(
+            "(log collection failed: {})\n",
+            String::from_utf8_lossy(&out.stderr).trim()
+        ),
+        Err(e) => format!("(log collection unavailable: {})\n", e),
+    }
+}
+
+/// The running daemon's recent warnings and errors, oldest first
+fn collect_events() -> String {
+    let events = socket_utils::get_ipc_socket_path()
+        .and_then(|socket| error_log::query(&socket, error_log::CAPACITY));
+    match events {
+        Ok(events) if events.is_empty() => {
+            "(no warnings or errors since the daemon started)\n".into()
+        }
+        Ok(events) => events
+            .iter()
+            .rev()
+            .map(|event| event.to_line() + "\n")
+            .collect(),
+        Err(e) => format!("(daemon not reachable, no event journal: {:#})\n", e),
+    }
+}
+
+/// Anonymous usage summary: aggregates only, nothing that identifies a
+/// session, a file or what was said
+fn analytics_summary(stats: &LifetimeMetrics) -> serde_json::Value {
+    serde_json::json!({
+        "total_sessions": stats.total_sessions,
+        "total_segments": stats.total_segments,
+        "total_words": stats.total_words,
+        "total_dictation_time_minutes": stats.total_dictation_time_minutes,
+        "average_wpm": stats.average_wpm,
+        "average_latency_ms": stats.average_latency_ms,
+        "wpm_trend_7day": stats.wpm_trend_7day,
+        "latency_trend_7day": stats.latency_trend_7day,
+        "cuda_errors_total": stats.cuda_errors_total,
+        "cuda_errors_recovered": stats.cuda_errors_recovered,
+        "memory_pressure_events": stats.memory_pressure_events,
+        "high_latency_warnings": stats.high_latency_warnings,
+    })
+}
+
+fn collect_analytics() -> String {
+    let path = pipeline::metrics_db_path();
+    if !path.exists() {
+        return "{}\n".to_string();
+    }
+    let summary = MetricsDatabase::new(&path)
+        .and_then(|db| db.get_lifetime_stats())
+        .map(|stats| analytics_summary(&stats));
+    match summary {
+        Ok(summary) => serde_json::to_string_pretty(&summary).unwrap_or_default() + "\n",
+        Err(e) => format!("{{\"error\": {:?}}}\n", format!("{:#}", e)),
+    }
+}
+
+fn collect_config() -> String {
+    let path = DaemonConfig::default_config_path();
+    let home = dirs::home_dir().map(|h| h.to_string_lossy().to_string());
+
+    match fs::read_to_string(&path) {
+        Ok(contents) => scrub_config(&contents, home.as_deref())
+            .unwrap_or_else(|e| format!("# config could not be parsed: {}\n", e)),
+        Err(e) => format!("# no config at {}: {}\n", path.display(), e),
+    }
+}
+
+fn readme(created: &str, analytics: bool) -> String {
+    let mut text = format!(
+        "Swictation bug report\n\
+         Created: {}\n\
+         \n\
+         This archive was generated locally and has NOT been uploaded anywhere.\n\
+         Review the contents before attaching it to a GitHub issue.\n\
+         \n\
+         Contents:\n\
+         \x20 version.txt  - build and runtime version information\n\
+         \x20 doctor.txt   - output of `swictation-daemon doctor`\n\
+         \x20 config.toml  - configuration with secrets redacted and $HOME replaced by ~\n\
+         \x20 daemon.log   - last {} lines of daemon logs\n\
+         \x20 events.log   - recent warnings and errors of the running daemon\n",
+        created, LOG_TAIL_LINES
+    );
+    if analytics {
+        text.push_str("  analytics.json - anonymous usage totals (bugreport_analytics = true)\n");
+    }
+    text
+}
+
+/// The bundle's files, by name
+fn bundle_files(created: &str, analytics: bool) -> Vec<(&'static str, String)> {
+    let mut files = vec![
+        ("README.txt", readme(created, analytics)),
+        ("version.txt", version::version_long()),
+        ("doctor.txt", doctor::run().to_string()),
+        ("config.toml", collect_config()),
+        ("daemon.log", collect_logs()),
+        ("events.log", collect_events()),
+    ];
+    if analytics {
+        files.push(("analytics.json", collect_analytics()));
+    }
+    files
+}
+
+/// Pack `files` into a gzipped tar under the directory `stem`
+fn write_archive(output: &std::path::Path, stem: &str, files: &[(&str, String)]) -> Result<()> {
+    let file = fs::File::create(output)
+        .with_context(|| format!("Failed to create {}", output.display()))?;
+    let mut archive = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    let mtime = chrono::Utc::now().timestamp().max(0) as u64;
+    for (name, contents) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o600);
+        header.set_mtime(mtime);
+        archive
+            .append_data(
+                &mut header,
+                format!("{}/{}", stem, name),
+                contents.as_bytes(),
+            )
+            .with_context(|| format!("Failed to add {} to bundle", name))?;
+    }
+    archive
+        .into_inner()
+        .and_then(|gz| gz.finish())
+        .context("Failed to finish bundle")?;
+    Ok(())
+}
+
+/// Create a bug report archive and return its path
+///
+/// If `output` is `None`, the archive is written to the current directory as
+/// `swictation-bugreport-<timestamp>.tar.gz`. `analytics` adds the anonymous
+/// usage summary.
+pub fn create_bundle(output: Option<PathBuf>, analytics: bool) -> Result<PathBuf> {
+    let now = chrono::Local::now();
+    let stem = format!("swictation-bugreport-{}", now.format("%Y%m%d-%H%M%S"));
+    let output = output.unwrap_or_else(|| PathBuf::from(format!("{}.tar.gz", stem)));
+
+    let files = bundle_files(&now.to_rfc3339(), analytics);
+    write_archive(&output, &stem, &files)?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrub_secret_keys() {
+        let input = r#"
+socket_path = "/home/alice/.local/share/swictation/swictation.sock"
+vad_threshold = 0.25
+
+[hooks]
+api_key = "sk-123"
+auth_token = "abc"
+
+[hotkeys]
+toggle = "Super+Shift+D"
+"#;
+        let scrubbed = scrub_config(input, Some("/home/alice")).unwrap();
+
+        assert!(!scrubbed.contains("sk-123"));
+        assert!(!scrubbed.contains("abc"));
+        assert!(!scrubbed.contains("/home/alice"));
+        assert!(scrubbed.contains("~/.local/share/swictation"));
+        assert!(scrubbed.contains("Super+Shift+D"));
+        assert_eq!(scrubbed.matches(REDACTED).count(), 2);
+    }
+
+    #[test]
+    fn test_secret_key_detection() {
+        assert!(is_secret_key("openai_api_key"));
+        assert!(is_secret_key("Password"));
+        assert!(!is_secret_key("vad_threshold"));
+        assert!(!is_secret_key("hotkeys"));
+    }
+
+    #[test]
+    fn test_readme_mentions_local_only() {
+        let text = readme("2025-01-01T00:00:00Z", false);
+        assert!(text.contains("NOT been uploaded"));
+        assert!(!text.contains("analytics.json"));
+        assert!(readme("2025-01-01T00:00:00Z", true).contains("analytics.json"));
+    }
+
+    #[test]
+    fn test_analytics_summary_is_anonymous() {
+        let stats = LifetimeMetrics {
+            total_words: 1200,
+            best_wpm_session: Some(42),
+            longest_session_id: Some(7),
+            ..Default::default()
+        };
+        let summary = analytics_summary(&stats);
+        assert_eq!(summary["total_words"], 1200);
+        let text = summary.to_string();
+        assert!(!text.contains("session\":"));
+        assert!(!text.contains("_id"));
+    }
+
+    #[test]
+    fn test_archive_holds_files_under_stem() {
+        let dir = std::env::temp_dir().join(format!("swictation-bug-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let output = dir.join("bundle.tar.gz");
+        let files = [
+            ("README.txt", "hello".to_string()),
+            ("events.log", String::new()),
+        ];
+        write_archive(&output, "swictation-bugreport-x", &files).unwrap();
+
+        let gz = flate2::read::GzDecoder::new(fs::File::open(&output).unwrap());
+        let mut archive = tar::Archive::new(gz);
+        let mut entries: Vec<(String, String)> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let path = entry.path().unwrap().to_string_lossy().into_owned();
+                let mut contents = String::new();
+                std::io::Read::read_to_string(&mut entry, &mut contents).unwrap();
+                (path, contents)
+            })
+            .collect();
+        entries.sort();
+        assert_eq!(
+            entries,
+            vec![
+                (
+                    "swictation-bugreport-x/README.txt".to_string(),
+                    "hello".to_string()
+                ),
+                (
+                    "swictation-bugreport-x/events.log".to_string(),
+                    String::new()
+                ),
+            ]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -271,6 +271,67 @@ pub fn normalize_0_6b_punctuation(text: &str) -> String {
         .replace("⟪8⟫", "ellipsis")
 }
 
+/// Spoken punctuation words Secretary Mode turns into symbols
+const SPOKEN_PUNCTUATION: [&str; 6] = ["comma", "period", "semicolon", "colon", "dash", "ellipsis"];
+
+/// Two-word spoken punctuation
+const SPOKEN_PUNCTUATION_PAIRS: [(&str, &str); 6] = [
+    ("exclamation", "point"),
+    ("exclamation", "mark"),
+    ("question", "mark"),
+    ("full", "stop"),
+    ("semi", "colon"),
+    ("three", "dots"),
+];
+
+/// Remove model-restored punctuation around spoken punctuation words
+///
+/// A punctuation model may turn "world period" into "world, period." The
+/// spoken word is what the user asked for, so marks after it and after the
+/// word before it are dropped and it is lowercased, leaving it for Secretary
+/// Mode to convert.
+///
+/// # Examples
+/// ```
+/// use swictation_daemon::capitalization::settle_spoken_punctuation;
+///
+/// assert_eq!(settle_spoken_punctuation("Hello, comma world, Period."), "Hello comma world period");
+/// assert_eq!(settle_spoken_punctuation("Why question mark? Fine."), "Why question mark Fine.");
+/// ```
+pub fn settle_spoken_punctuation(text: &str) -> String {
+    let is_mark = |c: char| matches!(c, ',' | '.' | '?' | '!' | ';' | ':');
+    let mut tokens: Vec<String> = text.split_whitespace().map(str::to_string).collect();
+    let bare = |token: &str| token.trim_end_matches(is_mark).to_lowercase();
+
+    let mut i = 0;
+    while i < tokens.len() {
+        let word = bare(&tokens[i]);
+        let span = if SPOKEN_PUNCTUATION.contains(&word.as_str()) {
+            1
+        } else if tokens.get(i + 1).is_some_and(|next| {
+            let next = bare(next);
+            SPOKEN_PUNCTUATION_PAIRS.contains(&(word.as_str(), next.as_str()))
+        }) {
+            2
+        } else {
+            0
+        };
+        if span == 0 {
+            i += 1;
+            continue;
+        }
+        if i > 0 {
+            let previous = tokens[i - 1].trim_end_matches(is_mark).to_string();
+            tokens[i - 1] = previous;
+        }
+        for token in &mut tokens[i..i + span] {
+            *token = bare(token);
+        }
+        i += span;
+    }
+    tokens.join(" ")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -493,4 +554,22 @@ mod tests {
             "first semicolon second period"
         );
     }
+
+    #[test]
+    fn test_settle_spoken_punctuation() {
+        // Restored punctuation next to spoken punctuation is dropped
+        assert_eq!(
+            settle_spoken_punctuation("Hello, comma how are you question mark? I am fine. Period."),
+            "Hello comma how are you question mark I am fine period"
+        );
+        // Other punctuation is kept
+        assert_eq!(
+            settle_spoken_punctuation("First, we stop. Then we go."),
+            "First, we stop. Then we go."
+        );
+        assert_eq!(
+            settle_spoken_punctuation("Great exclamation point!"),
+            "Great exclamation point"
+        );
+    }
 }
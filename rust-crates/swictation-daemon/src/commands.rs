@@ -0,0 +1,494 @@
+//! Editing commands ("scratch that", "new line") spoken between dictation
+//!
+//! With `accessibility_mode` on, commands that control the daemon itself
+//! ("stop dictation", "what's my word count") are recognized too. "Private
+//! mode on/off" is always recognized, being a privacy control.
+//!
+//! A segment is a command only when it consists of the command phrase alone.
+//! With a command model configured, the grammar spotter runs on the segment
+//! audio alongside STT and the two results are arbitrated; without one, the
+//! whole normalized STT text has to equal a phrase.
+
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use tracing::{info, warn};
+
+use swictation_stt::{CommandMatch, CommandSpotter};
+
+/// Segments remembered for "scratch that"
+const HISTORY_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditCommand {
+    /// Delete the previously typed segment
+    ScratchThat,
+    NewLine,
+    NewParagraph,
+    /// Bypass the word filter until "filter on" or the next recording
+    FilterOff,
+    FilterOn,
+    /// Speak the last dictated segment (needs `tts.enabled`)
+    ReadBack,
+    /// Voice control: end the recording
+    StopDictation,
+    /// Voice control: move to the next word filter profile
+    SwitchProfile,
+    /// Voice control: announce the session's word count
+    WordCount,
+    /// Stop saving sessions until "private mode off"
+    PrivateModeOn,
+    PrivateModeOff,
+    /// Type spoken letters as characters until "end spell" or the next
+    /// recording
+    SpellModeOn,
+    SpellModeOff,
+    /// Transcribe a meeting into a document instead of typing
+    MeetingModeOn,
+    MeetingModeOff,
+}
+
+impl EditCommand {
+    pub const ALL: [EditCommand; 15] = [
+        EditCommand::ScratchThat,
+        EditCommand::NewLine,
+        EditCommand::NewParagraph,
+        EditCommand::FilterOff,
+        EditCommand::FilterOn,
+        EditCommand::ReadBack,
+        EditCommand::StopDictation,
+        EditCommand::SwitchProfile,
+        EditCommand::WordCount,
+        EditCommand::PrivateModeOn,
+        EditCommand::PrivateModeOff,
+        EditCommand::SpellModeOn,
+        EditCommand::SpellModeOff,
+        EditCommand::MeetingModeOn,
+        EditCommand::MeetingModeOff,
+    ];
+
+    /// Spoken forms of the command (lowercase, no punctuation)
+    pub fn phrases(&self) -> &'static [&'static str] {
+        match self {
+            EditCommand::ScratchThat => &["scratch that", "delete that"],
+            EditCommand::NewLine => &["new line"],
+            EditCommand::NewParagraph => &["new paragraph"],
+            EditCommand::FilterOff => &["filter off"],
+            EditCommand::FilterOn => &["filter on"],
+            EditCommand::ReadBack => &["read that back"],
+            EditCommand::StopDictation => &["stop dictation"],
+            EditCommand::SwitchProfile => &["switch profile"],
+            EditCommand::WordCount => &["what's my word count", "what is my word count"],
+            EditCommand::PrivateModeOn => &["private mode on"],
+            EditCommand::PrivateModeOff => &["private mode off"],
+            EditCommand::SpellModeOn => &["spell mode", "start spelling"],
+            EditCommand::SpellModeOff => &["end spell", "spell mode off", "stop spelling"],
+            EditCommand::MeetingModeOn => &["start meeting mode", "meeting mode on"],
+            EditCommand::MeetingModeOff => &["stop meeting mode", "meeting mode off"],
+        }
+    }
+
+    /// Commands for the daemon rather than the text, answered by speech or
+    /// a notification (only recognized in accessibility mode)
+    pub fn is_voice_control(&self) -> bool {
+        matches!(
+            self,
+            EditCommand::StopDictation | EditCommand::SwitchProfile | EditCommand::WordCount
+        )
+    }
+
+    /// Commands carried out by the daemon: voice control, private and
+    /// meeting mode
+    pub fn is_daemon_command(&self) -> bool {
+        self.is_voice_control()
+            || matches!(
+                self,
+                EditCommand::PrivateModeOn
+                    | EditCommand::PrivateModeOff
+                    | EditCommand::MeetingModeOn
+                    | EditCommand::MeetingModeOff
+            )
+    }
+
+    fn from_phrase(phrase: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|command| command.phrases().contains(&phrase))
+    }
+
+    /// Command whose phrase is the entire text, ignoring case and punctuation
+    pub fn from_text(text: &str) -> Option<Self> {
+        Self::from_phrase(&normalize(text))
+    }
+
+    /// `<KEY:...>` markers that carry out the command
+    ///
+    /// Returns `None` for "scratch that" with nothing left to delete, and for
+    /// the filter, spell, read-back, voice control, private and meeting mode
+    /// commands, which are carried out by the pipeline or the daemon.
+    pub fn keystrokes(&self, history: &mut EditHistory) -> Option<String> {
+        match self {
+            EditCommand::ScratchThat => {
+                let chars = history.pop()?;
+                Some("<KEY:BackSpace>".repeat(chars))
+            }
+            EditCommand::NewLine => {
+                history.push("\n");
+                Some("<KEY:Return>".to_string())
+            }
+            EditCommand::NewParagraph => {
+                history.push("\n\n");
+                Some("<KEY:Return><KEY:Return>".to_string())
+            }
+            EditCommand::FilterOff
+            | EditCommand::FilterOn
+            | EditCommand::ReadBack
+            | EditCommand::StopDictation
+            | EditCommand::SwitchProfile
+            | EditCommand::WordCount
+            | EditCommand::PrivateModeOn
+            | EditCommand::PrivateModeOff
+            | EditCommand::SpellModeOn
+            | EditCommand::SpellModeOff
+            | EditCommand::MeetingModeOn
+            | EditCommand::MeetingModeOff => None,
+        }
+    }
+}
+
+fn normalize(text: &str) -> String {
+    // STT writes "what’s" as often as "what's"
+    text.replace('\u{2019}', "'")
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Text typed for one segment (or command) of the current recording
+#[derive(Debug)]
+struct TypedSegment {
+    /// Metrics database id, when the segment was stored
+    segment_id: Option<i64>,
+    text: String,
+    /// Decoder alternatives, post-processed like `text`
+    nbest: Vec<String>,
+}
+
+/// Segments typed in the current recording, newest last
+#[derive(Debug, Default)]
+pub struct EditHistory {
+    segments: Vec<TypedSegment>,
+}
+
+impl EditHistory {
+    pub fn push(&mut self, typed: &str) {
+        self.push_segment(None, typed, Vec::new());
+    }
+
+    /// Record a dictated segment along with its stored id and alternatives
+    pub fn push_segment(&mut self, segment_id: Option<i64>, typed: &str, nbest: Vec<String>) {
+        if self.segments.len() == HISTORY_LIMIT {
+            self.segments.remove(0);
+        }
+        self.segments.push(TypedSegment {
+            segment_id,
+            text: typed.to_string(),
+            nbest,
+        });
+    }
+
+    fn pop(&mut self) -> Option<usize> {
+        self.segments.pop().map(|s| s.text.chars().count())
+    }
+
+    /// Swap a typed segment for one of its alternatives
+    ///
+    /// Returns the alternative and the keystrokes that apply it: backspace
+    /// over the segment and everything typed after it, then retype the
+    /// alternative followed by the later segments. The replaced text takes
+    /// the alternative's slot, so picking the same index again undoes it.
+    pub fn use_alternative(&mut self, segment_id: i64, index: usize) -> Result<(String, String)> {
+        let position = self
+            .segments
+            .iter()
+            .position(|s| s.segment_id == Some(segment_id))
+            .with_context(|| {
+                format!(
+                    "Segment {} is not among the recently typed segments",
+                    segment_id
+                )
+            })?;
+
+        let segment = &self.segments[position];
+        let Some(alternative) = segment.nbest.get(index).cloned() else {
+            bail!(
+                "Segment {} has {} alternatives, no index {}",
+                segment_id,
+                segment.nbest.len(),
+                index
+            );
+        };
+
+        let erase: usize = self.segments[position..]
+            .iter()
+            .map(|s| s.text.chars().count())
+            .sum();
+
+        // Keep the separator that followed the original text
+        let segment = &mut self.segments[position];
+        let trailing = &segment.text[segment.text.trim_end().len()..];
+        let typed = format!("{}{}", alternative, trailing);
+        segment.nbest[index] = segment.text.trim_end().to_string();
+        segment.text = typed;
+
+        let mut keys = "<KEY:BackSpace>".repeat(erase);
+        for later in &self.segments[position..] {
+            keys.push_str(&later.text.replace('\n', "<KEY:Return>"));
+        }
+        Ok((alternative, keys))
+    }
+
+    /// Text of the newest segment that was dictated rather than a line break
+    pub fn last_dictated(&self) -> Option<String> {
+        self.segments
+            .iter()
+            .rev()
+            .map(|s| s.text.trim())
+            .find(|text| !text.is_empty())
+            .map(str::to_string)
+    }
+
+    pub fn clear(&mut self) {
+        self.segments.clear();
+    }
+}
+
+/// Grammar spotter plus the arbitration rules
+#[derive(Clone)]
+pub struct CommandDetector {
+    spotter: Option<Arc<Mutex<CommandSpotter>>>,
+    threshold: f32,
+    /// Recognize voice control commands as well
+    voice_control: bool,
+}
+
+impl CommandDetector {
+    /// Detector backed by the model in `model_dir`, or text matching only
+    ///
+    /// A model that fails to load is logged and ignored: commands then fall
+    /// back to exact text matching instead of disabling dictation.
+    pub fn new(model_dir: Option<&Path>, threshold: f32, voice_control: bool) -> Self {
+        let phrases: Vec<String> = EditCommand::ALL
+            .iter()
+            .filter(|command| voice_control || !command.is_voice_control())
+            .flat_map(|command| command.phrases())
+            .map(|phrase| phrase.to_string())
+            .collect();
+
+        let spotter = model_dir.and_then(|dir| match CommandSpotter::new(dir, &phrases) {
+            Ok(spotter) => Some(Arc::new(Mutex::new(spotter))),
+            Err(e) => {
+                warn!("⚠️ Command model unavailable, using text matching: {}", e);
+                None
+            }
+        });
+
+        Self {
+            spotter,
+            threshold,
+            voice_control,
+        }
+    }
+
+    /// Start spotting on `samples` in the background while STT runs
+    pub fn start(&self, samples: &[f32]) -> PendingCommand {
+        let handle = self.spotter.as_ref().map(|spotter| {
+            let spotter = Arc::clone(spotter);
+            let samples = samples.to_vec();
+            std::thread::spawn(move || match spotter.lock().unwrap().spot(&samples) {
+                Ok(found) => found,
+                Err(e) => {
+                    warn!("Command spotting failed: {}", e);
+                    None
+                }
+            })
+        });
+        PendingCommand {
+            handle,
+            threshold: self.threshold,
+            voice_control: self.voice_control,
+        }
+    }
+}
+
+/// Spotter result for one segment, collected once the STT text is known
+pub struct PendingCommand {
+    handle: Option<JoinHandle<Option<CommandMatch>>>,
+    threshold: f32,
+    voice_control: bool,
+}
+
+impl PendingCommand {
+    /// Decide whether the segment with STT result `text` is a command
+    pub fn finish(self, text: &str) -> Option<EditCommand> {
+        let command = match self.handle {
+            None => EditCommand::from_text(text),
+            Some(handle) => arbitrate(handle.join().ok().flatten(), text, self.threshold),
+        }
+        .filter(|command| self.voice_control || !command.is_voice_control());
+        if let Some(command) = command {
+            info!("⌨️ Editing command: {:?}", command);
+        }
+        command
+    }
+}
+
+/// Combine the grammar spotter with the free-text result
+///
+/// - Both agree: command, even at moderate spotter confidence.
+/// - Spotter confident and the text is about as short as the phrase: command
+///   (STT misheard it, e.g. "scratch fat").
+/// - Otherwise dictation wins, so a sentence that merely contains a command
+///   phrase is typed as spoken.
+fn arbitrate(spotted: Option<CommandMatch>, text: &str, threshold: f32) -> Option<EditCommand> {
+    let spotted = spotted?;
+    let command = EditCommand::from_phrase(&spotted.phrase)?;
+
+    if EditCommand::from_text(text) == Some(command) && spotted.confidence >= threshold / 2.0 {
+        return Some(command);
+    }
+
+    let phrase_words = spotted.phrase.split_whitespace().count();
+    let text_words = normalize(text).split_whitespace().count();
+    (spotted.confidence >= threshold && text_words <= phrase_words + 1).then_some(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spotted(phrase: &str, confidence: f32) -> Option<CommandMatch> {
+        Some(CommandMatch {
+            phrase: phrase.to_string(),
+            confidence,
+        })
+    }
+
+    #[test]
+    fn test_text_match_requires_whole_segment() {
+        assert_eq!(
+            EditCommand::from_text("Scratch that."),
+            Some(EditCommand::ScratchThat)
+        );
+        assert_eq!(
+            EditCommand::from_text("new paragraph"),
+            Some(EditCommand::NewParagraph)
+        );
+        assert_eq!(EditCommand::from_text("please scratch that part"), None);
+    }
+
+    #[test]
+    fn test_voice_control_needs_accessibility_mode() {
+        let off = CommandDetector::new(None, 0.8, false);
+        assert_eq!(off.start(&[]).finish("Stop dictation."), None);
+        assert_eq!(
+            off.start(&[]).finish("Scratch that."),
+            Some(EditCommand::ScratchThat)
+        );
+
+        let on = CommandDetector::new(None, 0.8, true);
+        assert_eq!(
+            on.start(&[]).finish("Stop dictation."),
+            Some(EditCommand::StopDictation)
+        );
+        assert_eq!(
+            on.start(&[]).finish("What\u{2019}s my word count?"),
+            Some(EditCommand::WordCount)
+        );
+
+        assert_eq!(
+            off.start(&[]).finish("Private mode on."),
+            Some(EditCommand::PrivateModeOn)
+        );
+    }
+
+    #[test]
+    fn test_arbitration() {
+        // Agreement at moderate confidence
+        assert_eq!(
+            arbitrate(spotted("scratch that", 0.5), "Scratch that.", 0.8),
+            Some(EditCommand::ScratchThat)
+        );
+        // Confident spotter overrides a mishearing
+        assert_eq!(
+            arbitrate(spotted("scratch that", 0.9), "scratch fat", 0.8),
+            Some(EditCommand::ScratchThat)
+        );
+        // Dictation that contains the phrase stays dictation
+        assert_eq!(
+            arbitrate(
+                spotted("new line", 0.95),
+                "add a new line item to the invoice",
+                0.8
+            ),
+            None
+        );
+        // Low confidence never triggers on its own
+        assert_eq!(arbitrate(spotted("new line", 0.3), "nine", 0.8), None);
+    }
+
+    #[test]
+    fn test_scratch_that_deletes_last_segment() {
+        let mut history = EditHistory::default();
+        history.push("Hello world. ");
+        history.push("Oops ");
+
+        let keys = EditCommand::ScratchThat.keystrokes(&mut history).unwrap();
+        assert_eq!(keys, "<KEY:BackSpace>".repeat(5));
+        let keys = EditCommand::ScratchThat.keystrokes(&mut history).unwrap();
+        assert_eq!(keys.matches("<KEY:BackSpace>").count(), 13);
+        assert!(EditCommand::ScratchThat.keystrokes(&mut history).is_none());
+    }
+
+    #[test]
+    fn test_last_dictated_skips_line_breaks() {
+        let mut history = EditHistory::default();
+        assert_eq!(history.last_dictated(), None);
+
+        history.push_segment(Some(1), "Hello world. ", Vec::new());
+        EditCommand::NewParagraph.keystrokes(&mut history);
+        assert_eq!(history.last_dictated().as_deref(), Some("Hello world."));
+    }
+
+    #[test]
+    fn test_use_alternative_retypes_later_segments() {
+        let mut history = EditHistory::default();
+        history.push_segment(Some(7), "Call arkon. ", vec!["Call archon.".to_string()]);
+        EditCommand::NewLine.keystrokes(&mut history);
+        history.push_segment(Some(8), "Done ", Vec::new());
+
+        let (alternative, keys) = history.use_alternative(7, 0).unwrap();
+        assert_eq!(alternative, "Call archon.");
+        let erased = "Call arkon. \nDone ".chars().count();
+        assert_eq!(
+            keys,
+            format!(
+                "{}Call archon. <KEY:Return>Done ",
+                "<KEY:BackSpace>".repeat(erased)
+            )
+        );
+
+        // Picking it again restores the original
+        let (alternative, _) = history.use_alternative(7, 0).unwrap();
+        assert_eq!(alternative, "Call arkon.");
+
+        assert!(history.use_alternative(7, 1).is_err());
+        assert!(history.use_alternative(99, 0).is_err());
+    }
+}
@@ -2,9 +2,15 @@
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
 use std::path::PathBuf;
+use std::time::Duration;
+use swictation_metrics::LatencyBudgets;
+use swictation_paths::config_file::{self, DocumentMut};
+use tracing::warn;
 
+use crate::config_schema;
 use crate::socket_utils;
 
 /// Get default model directory using XDG Base Directory spec
@@ -33,6 +39,11 @@ fn get_default_1_1b_model_path() -> PathBuf {
     get_default_model_dir().join("parakeet-tdt-1.1b-onnx")
 }
 
+/// Get default path for the Whisper model
+fn get_default_whisper_model_path() -> PathBuf {
+    get_default_model_dir().join("whisper-small-onnx")
+}
+
 /// Get default path for VAD model
 fn get_default_vad_model_path() -> PathBuf {
     get_default_model_dir()
@@ -50,6 +61,11 @@ pub struct HotkeyConfig {
     /// Push-to-talk hotkey (default: "Super+Space")
     /// User-configurable via UI settings
     pub push_to_talk: String,
+
+    /// Private mode toggle hotkey (default: none)
+    /// Private sessions are kept out of the metrics database
+    #[serde(default)]
+    pub private_mode: Option<String>,
 }
 
 impl Default for HotkeyConfig {
@@ -57,6 +73,432 @@ impl Default for HotkeyConfig {
         Self {
             toggle: "Super+Shift+D".to_string(), // Windows/Super key + Shift + D (Dictation)
             push_to_talk: "Super+Space".to_string(), // Windows/Super key + Space
+            private_mode: None,
+        }
+    }
+}
+
+/// What the word filter does with a listed word
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterAction {
+    /// Keep the first letter and star out the rest ("d***")
+    #[default]
+    Mask,
+    /// Remove the word entirely
+    #[serde(alias = "remove")]
+    Drop,
+    /// Type everything as dictated; for a profile where filtering is not
+    /// wanted, so "switch profile" can turn it off and back on
+    Allow,
+}
+
+/// One named word list for the word filter
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FilterProfile {
+    /// Words or phrases to filter (case-insensitive, whole words)
+    #[serde(default)]
+    pub words: Vec<String>,
+
+    #[serde(default)]
+    pub action: FilterAction,
+
+    /// Also filter the built-in profanity list (opt-in)
+    #[serde(default)]
+    pub use_default_list: bool,
+}
+
+/// Sensitive-word filter applied to dictated text before it is typed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WordFilterConfig {
+    /// Off by default; saying "filter off" bypasses it until the next recording
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Name of the active entry in `profiles`
+    #[serde(default = "default_filter_profile")]
+    pub profile: String,
+
+    #[serde(default = "default_filter_profiles")]
+    pub profiles: BTreeMap<String, FilterProfile>,
+}
+
+fn default_filter_profile() -> String {
+    "default".to_string()
+}
+
+fn default_filter_profiles() -> BTreeMap<String, FilterProfile> {
+    BTreeMap::from([(default_filter_profile(), FilterProfile::default())])
+}
+
+impl Default for WordFilterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            profile: default_filter_profile(),
+            profiles: default_filter_profiles(),
+        }
+    }
+}
+
+/// What is typed before a segment that follows a long pause
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParagraphBreak {
+    #[default]
+    Nothing,
+    Newline,
+    /// Two newlines, starting a new paragraph
+    BlankLine,
+}
+
+/// Executables run on daemon events
+///
+/// Each hook gets the event as one JSON object on stdin. Hooks are run
+/// directly, not through a shell.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HooksConfig {
+    #[serde(default)]
+    pub session_start: Vec<PathBuf>,
+
+    /// Also receives the session's word count and WPM
+    #[serde(default)]
+    pub session_end: Vec<PathBuf>,
+
+    /// Receives the typed text, even when transcripts are not stored
+    #[serde(default)]
+    pub transcription: Vec<PathBuf>,
+
+    /// Seconds a hook may run before it is killed
+    #[serde(default = "default_hook_timeout_secs")]
+    pub timeout_secs: u64,
+
+    /// Runs of each event's hooks per minute; later events are skipped
+    #[serde(default = "default_hook_max_per_minute")]
+    pub max_per_minute: u32,
+}
+
+fn default_hook_timeout_secs() -> u64 {
+    5
+}
+
+fn default_hook_max_per_minute() -> u32 {
+    30
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            session_start: Vec::new(),
+            session_end: Vec::new(),
+            transcription: Vec::new(),
+            timeout_secs: default_hook_timeout_secs(),
+            max_per_minute: default_hook_max_per_minute(),
+        }
+    }
+}
+
+/// Somewhere a finished segment is delivered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputSink {
+    /// Typed into the focused window, or sent to an attached editor
+    Inject,
+    /// Appended to the Markdown note (needs `note_sink` enabled)
+    Note,
+    /// Sent to the `hooks.transcription` scripts
+    Hook,
+}
+
+impl OutputSink {
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputSink::Inject => "inject",
+            OutputSink::Note => "note",
+            OutputSink::Hook => "hook",
+        }
+    }
+}
+
+/// Sinks every segment goes to while this profile is active
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputProfile {
+    #[serde(default = "default_output_sinks")]
+    pub sinks: Vec<OutputSink>,
+}
+
+fn default_output_sinks() -> Vec<OutputSink> {
+    vec![OutputSink::Inject, OutputSink::Note, OutputSink::Hook]
+}
+
+impl Default for OutputProfile {
+    fn default() -> Self {
+        Self {
+            sinks: default_output_sinks(),
+        }
+    }
+}
+
+/// Where dictated text goes, per named profile
+///
+/// The default profile delivers to every sink that is configured, so a note
+/// sink or transcription hook only needs its own section to take effect.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputConfig {
+    /// Name of the active entry in `profiles`
+    #[serde(default = "default_output_profile")]
+    pub profile: String,
+
+    #[serde(default = "default_output_profiles")]
+    pub profiles: BTreeMap<String, OutputProfile>,
+}
+
+fn default_output_profile() -> String {
+    "default".to_string()
+}
+
+fn default_output_profiles() -> BTreeMap<String, OutputProfile> {
+    BTreeMap::from([(default_output_profile(), OutputProfile::default())])
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            profile: default_output_profile(),
+            profiles: default_output_profiles(),
+        }
+    }
+}
+
+/// When the note sink starts a new timestamp heading
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteHeading {
+    /// Once per recording session; segments follow as they were typed
+    #[default]
+    Session,
+    /// Before every segment
+    Segment,
+}
+
+/// Appends dictated text to a Markdown file, e.g. an Obsidian daily note
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NoteSinkConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Folder the notes are written to (created if missing)
+    #[serde(default)]
+    pub directory: Option<PathBuf>,
+
+    /// File name as a strftime template in local time
+    #[serde(default = "default_note_filename")]
+    pub filename: String,
+
+    #[serde(default)]
+    pub heading: NoteHeading,
+}
+
+fn default_note_filename() -> String {
+    "%Y-%m-%d.md".to_string()
+}
+
+impl Default for NoteSinkConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: None,
+            filename: default_note_filename(),
+            heading: NoteHeading::default(),
+        }
+    }
+}
+
+/// Meeting mode: transcribe a meeting into a document instead of typing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeetingConfig {
+    /// Folder meeting documents are written to (default: `meetings` in the
+    /// data folder)
+    #[serde(default)]
+    pub directory: Option<PathBuf>,
+
+    /// Input device carrying system audio (e.g. a PulseAudio monitor),
+    /// matched by name and mixed with the microphone
+    #[serde(default)]
+    pub system_audio_device: Option<String>,
+
+    /// Minutes after which meeting mode ends by itself
+    #[serde(default = "default_meeting_max_minutes")]
+    pub max_minutes: u64,
+
+    /// Script given the transcript on stdin; its output (e.g. from an LLM)
+    /// is added to the document as action items
+    #[serde(default)]
+    pub summary_hook: Option<PathBuf>,
+
+    /// Seconds the summary hook may run before it is killed
+    #[serde(default = "default_meeting_summary_timeout_secs")]
+    pub summary_timeout_secs: u64,
+}
+
+fn default_meeting_max_minutes() -> u64 {
+    120
+}
+
+fn default_meeting_summary_timeout_secs() -> u64 {
+    300
+}
+
+impl Default for MeetingConfig {
+    fn default() -> Self {
+        Self {
+            directory: None,
+            system_audio_device: None,
+            max_minutes: default_meeting_max_minutes(),
+            summary_hook: None,
+            summary_timeout_secs: default_meeting_summary_timeout_secs(),
+        }
+    }
+}
+
+/// Speaker labels ("Speaker 1", "Speaker 2") from a voice-embedding model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiarizationConfig {
+    /// Speaker-embedding ONNX model (WeSpeaker-style); labelling is off
+    /// without one
+    #[serde(default)]
+    pub model_path: Option<PathBuf>,
+
+    /// Also label dictated segments, not just transcribed files
+    #[serde(default)]
+    pub live: bool,
+
+    /// Cosine similarity (0.0-1.0) above which two segments are the same
+    /// speaker
+    #[serde(default = "default_diarization_threshold")]
+    pub threshold: f32,
+
+    /// Most speakers told apart in one recording
+    #[serde(default = "default_diarization_max_speakers")]
+    pub max_speakers: usize,
+}
+
+fn default_diarization_threshold() -> f32 {
+    0.5
+}
+
+fn default_diarization_max_speakers() -> usize {
+    8
+}
+
+impl Default for DiarizationConfig {
+    fn default() -> Self {
+        Self {
+            model_path: None,
+            live: false,
+            threshold: default_diarization_threshold(),
+            max_speakers: default_diarization_max_speakers(),
+        }
+    }
+}
+
+/// Alerts for speaking too fast, e.g. when rehearsing a talk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PacingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Speaking rate in words per minute that counts as too fast
+    #[serde(default = "default_pacing_max_wpm")]
+    pub max_wpm: f64,
+
+    /// Seconds the rate must stay above `max_wpm` before alerting
+    #[serde(default = "default_pacing_sustain_secs")]
+    pub sustain_secs: f64,
+
+    /// Also play a short chime (needs paplay, aplay or afplay)
+    #[serde(default)]
+    pub earcon: bool,
+}
+
+fn default_pacing_max_wpm() -> f64 {
+    180.0
+}
+
+fn default_pacing_sustain_secs() -> f64 {
+    10.0
+}
+
+impl Default for PacingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_wpm: default_pacing_max_wpm(),
+            sustain_secs: default_pacing_sustain_secs(),
+            earcon: false,
+        }
+    }
+}
+
+/// Inverse text normalization: numbers, dates and units written as digits
+/// and symbols ("twenty three dollars" → "$23")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItnConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// How numbers and dates are written: "en-US" ("March 5th", "5 lb") or
+    /// another English locale such as "en-GB" ("5 March", "£5")
+    #[serde(default = "default_itn_locale")]
+    pub locale: String,
+}
+
+fn default_itn_locale() -> String {
+    "en-US".to_string()
+}
+
+impl Default for ItnConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            locale: default_itn_locale(),
+        }
+    }
+}
+
+/// Spoken read-back of dictated text ("read that back")
+///
+/// Uses the platform speech synthesizer (`say` on macOS, espeak-ng or
+/// spd-say on Linux) unless a Piper voice is configured.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TtsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Piper `.onnx` voice; synthesized with the `piper` binary when set
+    #[serde(default)]
+    pub piper_model: Option<PathBuf>,
+
+    /// Voice name passed to the platform synthesizer
+    #[serde(default)]
+    pub voice: Option<String>,
+
+    /// Speaking rate in words per minute (platform synthesizers only)
+    #[serde(default = "default_tts_rate_wpm")]
+    pub rate_wpm: u32,
+}
+
+fn default_tts_rate_wpm() -> u32 {
+    175
+}
+
+impl Default for TtsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            piper_model: None,
+            voice: None,
+            rate_wpm: default_tts_rate_wpm(),
         }
     }
 }
@@ -83,23 +525,67 @@ pub struct DaemonConfig {
     /// VAD maximum speech duration (seconds)
     pub vad_max_speech: f32,
 
+    /// Below-threshold VAD windows (32ms each) bridged inside speech
+    #[serde(default = "default_vad_hangover_frames")]
+    pub vad_hangover_frames: usize,
+
+    /// Recent VAD probabilities kept for `dump_vad_trace` (0 = disabled)
+    #[serde(default = "default_vad_trace_capacity")]
+    pub vad_trace_capacity: usize,
+
     /// VAD threshold (ONNX: 0.001-0.005, NOT PyTorch 0.5!)
     /// See swictation-vad/ONNX_THRESHOLD_GUIDE.md for details
     pub vad_threshold: f32,
 
     /// STT model selection override
-    /// Options: "auto" (VRAM-based), "0.6b-cpu", "0.6b-gpu", "1.1b-gpu"
+    /// Options: "auto" (VRAM-based), "0.6b-cpu", "0.6b-gpu", "1.1b-cpu",
+    /// "1.1b-gpu", "whisper-cpu", "whisper-gpu"
     pub stt_model_override: String,
 
+    /// Unload the GPU model for a lighter one when VRAM runs critically low
+    /// (1.1B → 1.1B INT8 on CPU if quantized, else 0.6B → CPU), instead of
+    /// waiting for inference to run out of memory. The lighter model stays
+    /// until the next start or model switch.
+    #[serde(default = "default_vram_downgrade")]
+    pub vram_downgrade: bool,
+
     /// Path to 0.6B model directory (OrtRecognizer)
     pub stt_0_6b_model_path: PathBuf,
 
     /// Path to 1.1B INT8 model directory (ONNX Runtime)
     pub stt_1_1b_model_path: PathBuf,
 
+    /// Path to Whisper model directory (encoder/decoder ONNX, tokens.txt);
+    /// used only when `stt_model_override` selects Whisper
+    #[serde(default = "get_default_whisper_model_path")]
+    pub stt_whisper_model_path: PathBuf,
+
+    /// Language Whisper transcribes, as a code like "de" (None = detect)
+    #[serde(default)]
+    pub whisper_language: Option<String>,
+
+    /// Languages dictated in, primary first (e.g. ["en", "de"]). With two
+    /// or more, each segment's language is detected and segments not in the
+    /// primary language are transcribed by the Whisper model
+    #[serde(default)]
+    pub languages: Vec<String>,
+
+    /// Directory of a punctuation and capitalization model (NeMo export:
+    /// model.onnx, vocab.txt and label files). When set, it punctuates every
+    /// segment in place of the recognizer's own punctuation
+    #[serde(default)]
+    pub punctuation_model_path: Option<PathBuf>,
+
     /// Number of threads for ONNX Runtime
     pub num_threads: Option<i32>,
 
+    /// Time the 0.6B model on every usable execution provider at startup and
+    /// run on the fastest. Only happens when OpenVINO (Intel GPU or NPU)
+    /// competes with another provider; otherwise the first detected provider
+    /// is used
+    #[serde(default = "default_provider_benchmark")]
+    pub provider_benchmark: bool,
+
     /// Audio device index (None = default device)
     pub audio_device_index: Option<usize>,
 
@@ -110,6 +596,265 @@ pub struct DaemonConfig {
     /// Lower = more strict, Higher = more fuzzy
     /// Default: 0.3
     pub phonetic_threshold: f64,
+
+    /// Keep each segment's audio under the data directory so it can be
+    /// re-transcribed later with `retry_segment` (off by default)
+    #[serde(default)]
+    pub save_recordings: bool,
+
+    /// Trim leading/trailing silence from speech segments before STT
+    #[serde(default = "default_trim_silence")]
+    pub trim_silence: bool,
+
+    /// Per-stage latency budgets (ms); segments over budget are reported
+    #[serde(default)]
+    pub latency_budgets: LatencyBudgets,
+
+    /// Directory of a small CTC model (model.onnx + tokens.txt) for spotting
+    /// editing commands like "scratch that"; without it commands are matched
+    /// on the transcribed text
+    #[serde(default)]
+    pub command_model_path: Option<PathBuf>,
+
+    /// Minimum command spotter confidence (0.0 - 1.0) to treat a short
+    /// segment as a command even when STT heard something else
+    #[serde(default = "default_command_threshold")]
+    pub command_threshold: f32,
+
+    /// Weight of the personal n-gram language model in decoding (0.0 - 2.0,
+    /// 0 disables it). Trained from your transcripts and corrections.
+    #[serde(default)]
+    pub lm_weight: f32,
+
+    /// ARPA n-gram language model (e.g. built with KenLM from domain text)
+    /// fused into decoding with `lm_weight` instead of the personal model
+    #[serde(default)]
+    pub lm_path: Option<PathBuf>,
+
+    /// Lexicon of out-of-vocabulary words and the word pieces that spell
+    /// them, one per line (`kubectl ▁ku be ct l`); re-read by the
+    /// `reload_lexicon` IPC command
+    #[serde(default)]
+    pub lexicon_path: Option<PathBuf>,
+
+    /// Project names, jargon and other phrases recognition should favour
+    #[serde(default)]
+    pub bias_phrases: Vec<String>,
+
+    /// Log-probability boost per word piece that continues a bias phrase
+    /// (0 - 10, 0 disables biasing). Too high and similar-sounding words
+    /// turn into the phrases.
+    #[serde(default = "default_bias_boost")]
+    pub bias_boost: f32,
+
+    /// Hypotheses kept by beam search decoding (1 = greedy, up to 16).
+    /// Wider beams recover more words from noisy audio at some latency cost.
+    #[serde(default = "default_beam_width")]
+    pub beam_width: usize,
+
+    /// Beam hypotheses scoring this far (log-probability) below the best are
+    /// dropped; lower is faster
+    #[serde(default = "default_beam_prune")]
+    pub beam_prune: f32,
+
+    /// Word pieces the TDT decoder may emit on one encoder frame before it
+    /// moves on (1 - 20; sherpa-onnx uses 5)
+    #[serde(default = "default_tdt_max_tokens_per_frame")]
+    pub tdt_max_tokens_per_frame: usize,
+
+    /// Most frames one decoding step may skip, however long a duration the
+    /// model predicts (0 = as predicted). 1 or 2 can help fast speakers
+    /// whose short words get skipped.
+    #[serde(default)]
+    pub tdt_max_duration_skip: usize,
+
+    /// Subtracted from the blank score at every step (-10 - 10, 0 = off).
+    /// Positive values bring back dropped quiet words; negative ones cut
+    /// words invented from noise.
+    #[serde(default)]
+    pub tdt_blank_penalty: f32,
+
+    /// Encoder sessions the windows of a long segment are encoded on in
+    /// parallel (1 - 8). Each one past the first holds another copy of the
+    /// encoder in memory (VRAM on a GPU), in exchange for a shorter wait
+    /// after long dictation.
+    #[serde(default = "default_encoder_workers")]
+    pub encoder_workers: usize,
+
+    /// Mask or drop configured words before they are typed
+    #[serde(default)]
+    pub word_filter: WordFilterConfig,
+
+    /// Write spoken numbers, dates and units as digits and symbols
+    #[serde(default)]
+    pub itn: ItnConfig,
+
+    /// Recent transcriptions kept in memory for `history_copy` (0 disables)
+    #[serde(default = "default_history_size")]
+    pub history_size: usize,
+
+    /// Seconds before a history entry is forgotten
+    #[serde(default = "default_history_ttl_secs")]
+    pub history_ttl_secs: u64,
+
+    /// Seconds a single recognition may run before it is cancelled and the
+    /// model sessions are reloaded (0 disables the watchdog)
+    #[serde(default = "default_stt_timeout_secs")]
+    pub stt_timeout_secs: u64,
+
+    /// Distinct audio buffers whose transcriptions are cached, so identical
+    /// audio skips inference (0 = off; for tests and benchmark runs)
+    #[serde(default)]
+    pub stt_cache_size: usize,
+
+    /// Scripts to run on session start/end and on each transcription
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
+    /// Record pitch spread, loudness and speaking rate per segment for
+    /// pacing analytics (experimental)
+    #[serde(default)]
+    pub analytics_prosody: bool,
+
+    /// Add an anonymous usage summary (totals, averages, error counters; no
+    /// text, session ids or paths) to `bugreport` bundles. The summary is
+    /// computed locally and, like the bundle, never uploaded
+    #[serde(default)]
+    pub bugreport_analytics: bool,
+
+    /// Break inserted when a pause of `paragraph_pause_secs` separates two
+    /// segments ("nothing", "newline" or "blank_line")
+    #[serde(default)]
+    pub paragraph_break: ParagraphBreak,
+
+    /// Pause in seconds between segments that starts a new paragraph
+    #[serde(default = "default_paragraph_pause_secs")]
+    pub paragraph_pause_secs: f32,
+
+    /// Also append dictated text to a Markdown note
+    #[serde(default)]
+    pub note_sink: NoteSinkConfig,
+
+    /// Which sinks (typing, note, hooks) each segment is delivered to
+    #[serde(default)]
+    pub output: OutputConfig,
+
+    /// "Start meeting mode": transcribe a meeting into a document
+    #[serde(default)]
+    pub meeting: MeetingConfig,
+
+    /// Label who is speaking in transcribed files (and optionally dictation)
+    #[serde(default)]
+    pub diarization: DiarizationConfig,
+
+    /// Alert UI clients when dictation is sustained above a speaking rate
+    #[serde(default)]
+    pub pacing: PacingConfig,
+
+    /// Text-to-speech for reading dictated text back
+    #[serde(default)]
+    pub tts: TtsConfig,
+
+    /// Control the daemon by voice ("stop dictation", "switch profile",
+    /// "what's my word count"); answers are spoken when `tts.enabled`,
+    /// otherwise shown as desktop notifications
+    #[serde(default)]
+    pub accessibility_mode: bool,
+
+    /// Folders whose new WAV/MP3/FLAC files are transcribed while idle,
+    /// with `.txt`, `.srt` and `.vtt` files written next to them
+    #[serde(default)]
+    pub watch_dirs: Vec<PathBuf>,
+
+    /// Shared folder (Syncthing, Nextcloud, ...) through which corrections,
+    /// bias phrases and profiles are kept the same on every machine using it
+    #[serde(default)]
+    pub sync_dir: Option<PathBuf>,
+
+    /// Words recognized with less confidence than this (0.0 - 1.0) are
+    /// reported to UI clients for review (0 disables)
+    #[serde(default = "default_low_confidence_threshold")]
+    pub low_confidence_threshold: f32,
+
+    /// Also type low-confidence words as `[[word]]` so they stand out
+    #[serde(default)]
+    pub mark_low_confidence: bool,
+}
+
+/// Accepted values for `stt_model_override`
+pub const STT_MODEL_OVERRIDES: [&str; 7] = [
+    "auto",
+    "0.6b-cpu",
+    "0.6b-gpu",
+    "1.1b-cpu",
+    "1.1b-gpu",
+    "whisper-cpu",
+    "whisper-gpu",
+];
+
+fn default_vram_downgrade() -> bool {
+    true
+}
+
+fn default_trim_silence() -> bool {
+    true
+}
+
+fn default_provider_benchmark() -> bool {
+    true
+}
+
+fn default_command_threshold() -> f32 {
+    0.8
+}
+
+fn default_low_confidence_threshold() -> f32 {
+    0.5
+}
+
+fn default_history_size() -> usize {
+    20
+}
+
+fn default_history_ttl_secs() -> u64 {
+    600
+}
+
+fn default_paragraph_pause_secs() -> f32 {
+    3.0
+}
+
+fn default_bias_boost() -> f32 {
+    2.0
+}
+
+fn default_beam_width() -> usize {
+    1
+}
+
+fn default_beam_prune() -> f32 {
+    10.0
+}
+
+fn default_tdt_max_tokens_per_frame() -> usize {
+    5
+}
+
+fn default_encoder_workers() -> usize {
+    1
+}
+
+fn default_stt_timeout_secs() -> u64 {
+    20
+}
+
+fn default_vad_hangover_frames() -> usize {
+    3
+}
+
+/// ~30 seconds of 32ms windows
+fn default_vad_trace_capacity() -> usize {
+    1000
 }
 
 impl Default for DaemonConfig {
@@ -127,15 +872,61 @@ impl Default for DaemonConfig {
             vad_min_silence: 0.8,
             vad_min_speech: 0.25,
             vad_max_speech: 30.0,
+            vad_hangover_frames: 3,
+            vad_trace_capacity: default_vad_trace_capacity(),
             vad_threshold: 0.25, // Optimized for real-time transcription (original 0.003 prevented silence detection)
             // STT adaptive model selection (auto = VRAM-based)
             stt_model_override: "auto".to_string(),
+            vram_downgrade: true,
             stt_0_6b_model_path: get_default_0_6b_model_path(),
             stt_1_1b_model_path: get_default_1_1b_model_path(),
+            stt_whisper_model_path: get_default_whisper_model_path(),
+            whisper_language: None,
+            languages: Vec::new(),
+            punctuation_model_path: None,
             num_threads: Some(4),
+            provider_benchmark: true,
             audio_device_index: None, // Will be set from env var or auto-detected
             hotkeys: HotkeyConfig::default(),
             phonetic_threshold: 0.3, // Moderate fuzzy matching
+            save_recordings: false,
+            trim_silence: true,
+            latency_budgets: LatencyBudgets::default(),
+            command_model_path: None,
+            command_threshold: default_command_threshold(),
+            lm_weight: 0.0,
+            lm_path: None,
+            lexicon_path: None,
+            bias_phrases: Vec::new(),
+            bias_boost: default_bias_boost(),
+            beam_width: default_beam_width(),
+            beam_prune: default_beam_prune(),
+            tdt_max_tokens_per_frame: default_tdt_max_tokens_per_frame(),
+            tdt_max_duration_skip: 0,
+            tdt_blank_penalty: 0.0,
+            encoder_workers: default_encoder_workers(),
+            word_filter: WordFilterConfig::default(),
+            itn: ItnConfig::default(),
+            history_size: default_history_size(),
+            history_ttl_secs: default_history_ttl_secs(),
+            stt_timeout_secs: default_stt_timeout_secs(),
+            stt_cache_size: 0,
+            hooks: HooksConfig::default(),
+            analytics_prosody: false,
+            bugreport_analytics: false,
+            paragraph_break: ParagraphBreak::default(),
+            paragraph_pause_secs: default_paragraph_pause_secs(),
+            note_sink: NoteSinkConfig::default(),
+            output: OutputConfig::default(),
+            meeting: MeetingConfig::default(),
+            diarization: DiarizationConfig::default(),
+            pacing: PacingConfig::default(),
+            tts: TtsConfig::default(),
+            accessibility_mode: false,
+            watch_dirs: Vec::new(),
+            sync_dir: None,
+            low_confidence_threshold: default_low_confidence_threshold(),
+            mark_low_confidence: false,
         }
     }
 }
@@ -145,13 +936,16 @@ impl DaemonConfig {
     pub fn load() -> Result<Self> {
         let config_path = Self::default_config_path();
 
-        if config_path.exists() {
-            // Load existing config
-            let contents =
-                std::fs::read_to_string(&config_path).context("Failed to read config file")?;
-
-            let mut config: DaemonConfig =
-                toml::from_str(&contents).context("Failed to parse config file")?;
+        if let Some(report) = Self::check_file()? {
+            for warning in &report.warnings {
+                warn!("⚠️ {}", warning);
+            }
+            let mut config = report.into_config().with_context(|| {
+                format!(
+                    "Invalid config file {} (see `swictation-daemon config validate`)",
+                    config_path.display()
+                )
+            })?;
 
             config.config_path = config_path;
             Ok(config)
@@ -163,22 +957,266 @@ impl DaemonConfig {
         }
     }
 
+    /// Schema check of the config file (`None` when there is none yet)
+    pub fn check_file() -> Result<Option<config_schema::Report>> {
+        let contents = config_file::read(&Self::default_config_path())
+            .context("Failed to read config file")?;
+        Ok(contents.as_deref().map(config_schema::check))
+    }
+
     /// Save configuration to file
+    ///
+    /// Comments and key order of the existing file are kept (see
+    /// `swictation_paths::config_file`).
     pub fn save(&self) -> Result<()> {
-        // Ensure config directory exists
-        if let Some(parent) = self.config_path.parent() {
-            std::fs::create_dir_all(parent).context("Failed to create config directory")?;
-        }
-
         let contents = toml::to_string_pretty(self).context("Failed to serialize config")?;
+        let new: DocumentMut = contents
+            .parse()
+            .context("Failed to re-parse serialized config")?;
 
-        std::fs::write(&self.config_path, contents).context("Failed to write config file")?;
+        config_file::update(&self.config_path, |doc| {
+            config_file::merge(doc, &new, true);
+            Ok(())
+        })
+        .context("Failed to write config file")
+    }
+
+    /// Check values the type system can't (ranges, known model names)
+    ///
+    /// Reports every problem at once, one per line.
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+
+        if !(self.vad_threshold > 0.0 && self.vad_threshold <= 1.0) {
+            problems.push(format!(
+                "vad_threshold must be in (0, 1], got {}",
+                self.vad_threshold
+            ));
+        }
+        if self.vad_min_silence <= 0.0 {
+            problems.push(format!(
+                "vad_min_silence must be positive, got {}",
+                self.vad_min_silence
+            ));
+        }
+        if self.vad_min_speech <= 0.0 {
+            problems.push(format!(
+                "vad_min_speech must be positive, got {}",
+                self.vad_min_speech
+            ));
+        }
+        if self.vad_max_speech <= self.vad_min_speech {
+            problems.push(format!(
+                "vad_max_speech ({}) must be greater than vad_min_speech ({})",
+                self.vad_max_speech, self.vad_min_speech
+            ));
+        }
+        if !STT_MODEL_OVERRIDES.contains(&self.stt_model_override.as_str()) {
+            problems.push(format!(
+                "stt_model_override must be one of {}, got '{}'",
+                STT_MODEL_OVERRIDES.join(", "),
+                self.stt_model_override
+            ));
+        }
+        if matches!(&self.whisper_language, Some(code) if code.trim().is_empty()) {
+            problems.push("whisper_language must be a language code like \"de\"".to_string());
+        }
+        if self.languages.iter().any(|code| code.trim().is_empty()) {
+            problems.push("languages must not contain empty codes".to_string());
+        }
+        if let Some(code) = self
+            .languages
+            .iter()
+            .enumerate()
+            .find(|(i, code)| self.languages[..*i].contains(code))
+            .map(|(_, code)| code)
+        {
+            problems.push(format!("languages lists '{}' more than once", code));
+        }
+        if matches!(self.num_threads, Some(n) if n < 1) {
+            problems.push("num_threads must be at least 1".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.phonetic_threshold) {
+            problems.push(format!(
+                "phonetic_threshold must be in [0, 1], got {}",
+                self.phonetic_threshold
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.command_threshold) {
+            problems.push(format!(
+                "command_threshold must be in [0, 1], got {}",
+                self.command_threshold
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.low_confidence_threshold) {
+            problems.push(format!(
+                "low_confidence_threshold must be in [0, 1], got {}",
+                self.low_confidence_threshold
+            ));
+        }
+        if !(0.0..=2.0).contains(&self.lm_weight) {
+            problems.push(format!(
+                "lm_weight must be in [0, 2], got {}",
+                self.lm_weight
+            ));
+        }
+        if !(0.0..=10.0).contains(&self.bias_boost) {
+            problems.push(format!(
+                "bias_boost must be in [0, 10], got {}",
+                self.bias_boost
+            ));
+        }
+        if !(1..=16).contains(&self.beam_width) {
+            problems.push(format!(
+                "beam_width must be in [1, 16], got {}",
+                self.beam_width
+            ));
+        }
+        if !(1..=20).contains(&self.tdt_max_tokens_per_frame) {
+            problems.push(format!(
+                "tdt_max_tokens_per_frame must be in [1, 20], got {}",
+                self.tdt_max_tokens_per_frame
+            ));
+        }
+        if !(-10.0..=10.0).contains(&self.tdt_blank_penalty) {
+            problems.push(format!(
+                "tdt_blank_penalty must be in [-10, 10], got {}",
+                self.tdt_blank_penalty
+            ));
+        }
+        if !(1..=8).contains(&self.encoder_workers) {
+            problems.push(format!(
+                "encoder_workers must be in [1, 8], got {}",
+                self.encoder_workers
+            ));
+        }
+        if self.beam_prune <= 0.0 {
+            problems.push(format!(
+                "beam_prune must be positive, got {}",
+                self.beam_prune
+            ));
+        }
+        if self.word_filter.enabled
+            && !self
+                .word_filter
+                .profiles
+                .contains_key(&self.word_filter.profile)
+        {
+            problems.push(format!(
+                "word_filter.profile '{}' is not defined in word_filter.profiles",
+                self.word_filter.profile
+            ));
+        }
+        if self.itn.enabled && !crate::itn::is_supported_locale(&self.itn.locale) {
+            problems.push(format!(
+                "itn.locale '{}' is not supported (use an English locale such as en-US or en-GB)",
+                self.itn.locale
+            ));
+        }
+        if self.hooks.timeout_secs == 0 {
+            problems.push("hooks.timeout_secs must be at least 1".to_string());
+        }
+        if self.hooks.max_per_minute == 0 {
+            problems.push("hooks.max_per_minute must be at least 1".to_string());
+        }
+        if self.paragraph_pause_secs <= 0.0 {
+            problems.push(format!(
+                "paragraph_pause_secs must be positive, got {}",
+                self.paragraph_pause_secs
+            ));
+        }
+        if self.note_sink.enabled && self.note_sink.directory.is_none() {
+            problems.push("note_sink.directory must be set when note_sink is enabled".to_string());
+        }
+        if chrono::format::StrftimeItems::new(&self.note_sink.filename)
+            .any(|item| item == chrono::format::Item::Error)
+            || self.note_sink.filename.trim().is_empty()
+        {
+            problems.push(format!(
+                "note_sink.filename '{}' is not a valid strftime template",
+                self.note_sink.filename
+            ));
+        }
+        if !self.output.profiles.contains_key(&self.output.profile) {
+            problems.push(format!(
+                "output.profile '{}' is not defined in output.profiles",
+                self.output.profile
+            ));
+        }
+        if self.meeting.max_minutes == 0 {
+            problems.push("meeting.max_minutes must be positive, got 0".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.diarization.threshold) {
+            problems.push(format!(
+                "diarization.threshold must be between 0.0 and 1.0, got {}",
+                self.diarization.threshold
+            ));
+        }
+        if self.diarization.max_speakers == 0 {
+            problems.push("diarization.max_speakers must be positive, got 0".to_string());
+        }
+        if self.pacing.max_wpm <= 0.0 {
+            problems.push(format!(
+                "pacing.max_wpm must be positive, got {}",
+                self.pacing.max_wpm
+            ));
+        }
+        if self.pacing.sustain_secs <= 0.0 {
+            problems.push(format!(
+                "pacing.sustain_secs must be positive, got {}",
+                self.pacing.sustain_secs
+            ));
+        }
+        if !(80..=500).contains(&self.tts.rate_wpm) {
+            problems.push(format!(
+                "tts.rate_wpm must be in [80, 500], got {}",
+                self.tts.rate_wpm
+            ));
+        }
+        for dir in &self.watch_dirs {
+            if !dir.is_dir() {
+                problems.push(format!(
+                    "watch_dirs entry {} is not a directory",
+                    dir.display()
+                ));
+            }
+        }
+        if let Some(dir) = self.sync_dir.as_ref().filter(|dir| !dir.is_dir()) {
+            problems.push(format!("sync_dir {} is not a directory", dir.display()));
+        }
+        if self.history_size > 0 && self.history_ttl_secs == 0 {
+            problems.push("history_ttl_secs must be positive when history is enabled".to_string());
+        }
+        let budgets = &self.latency_budgets;
+        for (name, budget) in [
+            ("vad_ms", budgets.vad_ms),
+            ("stt_ms", budgets.stt_ms),
+            ("transform_ms", budgets.transform_ms),
+            ("injection_ms", budgets.injection_ms),
+            ("total_ms", budgets.total_ms),
+        ] {
+            if budget <= 0.0 {
+                problems.push(format!(
+                    "latency_budgets.{} must be positive, got {}",
+                    name, budget
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            anyhow::bail!(problems.join("\n"))
+        }
+    }
 
-        Ok(())
+    /// STT watchdog timeout (`None` when disabled)
+    pub fn stt_timeout(&self) -> Option<Duration> {
+        (self.stt_timeout_secs > 0).then(|| Duration::from_secs(self.stt_timeout_secs))
     }
 
     /// Get default config path
-    fn default_config_path() -> PathBuf {
+    pub fn default_config_path() -> PathBuf {
         let config_dir = if cfg!(target_os = "windows") {
             dirs::config_dir()
                 .unwrap_or_else(|| PathBuf::from("."))
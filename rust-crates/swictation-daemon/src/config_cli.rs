@@ -0,0 +1,282 @@
+//! `swictation-daemon config get/set/validate/diff`
+//!
+//! Keys address the serialized config tree. Dots select nested tables
+//! (`latency_budgets.stt_ms`, `hotkeys.toggle`) and may also stand in for
+//! underscores of flat keys, so `vad.threshold` finds `vad_threshold`.
+//! Every change is deserialized back into `DaemonConfig` and validated
+//! before it is written, so a bad value never reaches the file.
+
+use anyhow::{bail, Context, Result};
+use toml::{Table, Value};
+
+use crate::config::DaemonConfig;
+use crate::credentials;
+
+/// Current value of `key`
+pub fn get(config: &DaemonConfig, key: &str) -> Result<Value> {
+    let tree = to_table(config)?;
+    let path = resolve(config, key)?;
+    lookup(&tree, &path)
+        .cloned()
+        .with_context(|| format!("'{}' is not set (or not a config key)", key))
+}
+
+/// Apply `key = raw` and return the validated result (not yet saved)
+pub fn set(config: &DaemonConfig, key: &str, raw: &str) -> Result<DaemonConfig> {
+    let path = resolve(config, key)?;
+    let mut tree = to_table(config)?;
+
+    let template = lookup(&tree, &path)
+        .cloned()
+        .or_else(|| lookup(&defaults_tree(), &path).cloned());
+    let value = coerce(key, raw, template.as_ref())?;
+
+    insert(&mut tree, &path, value.clone())?;
+
+    let mut updated: DaemonConfig = Value::Table(tree)
+        .try_into()
+        .with_context(|| format!("Invalid value for {}", key))?;
+    updated.config_path = config.config_path.clone();
+
+    // Unknown keys are silently dropped by serde; catch them here
+    if lookup(&to_table(&updated)?, &path).is_none() {
+        bail!("Unknown config key '{}'", key);
+    }
+
+    updated.validate()?;
+    Ok(updated)
+}
+
+/// A key whose value differs from the default
+#[derive(Debug, PartialEq)]
+pub struct ConfigChange {
+    pub key: String,
+    pub default: Option<Value>,
+    pub current: Option<Value>,
+}
+
+/// Keys of `config` that differ from `DaemonConfig::default()`
+pub fn diff(config: &DaemonConfig) -> Result<Vec<ConfigChange>> {
+    let mut changes = Vec::new();
+    diff_tables("", &defaults_tree(), &to_table(config)?, &mut changes);
+    Ok(changes)
+}
+
+/// `(key, secret name)` for every `secret:<name>` value in the config
+pub fn secret_refs(config: &DaemonConfig) -> Result<Vec<(String, String)>> {
+    let mut refs = Vec::new();
+    collect_secret_refs("", &to_table(config)?, &mut refs);
+    Ok(refs)
+}
+
+/// Render a value for the terminal (strings without quotes)
+pub fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        // f32 fields widen to f64 on serialization; show 0.4, not 0.4000000059604645
+        Value::Float(f) if (*f as f32) as f64 == *f => (*f as f32).to_string(),
+        Value::Table(table) => toml::to_string(table).unwrap_or_default(),
+        other => other.to_string(),
+    }
+}
+
+/// `config` as a TOML tree
+pub fn to_table(config: &DaemonConfig) -> Result<Table> {
+    match Value::try_from(config).context("Failed to serialize config")? {
+        Value::Table(table) => Ok(table),
+        _ => bail!("Config did not serialize to a table"),
+    }
+}
+
+/// `DaemonConfig::default()` as a TOML tree
+pub fn defaults_tree() -> Table {
+    to_table(&DaemonConfig::default()).unwrap_or_default()
+}
+
+/// Map a user-supplied key onto a path in the config tree
+///
+/// Unset optional fields are missing from the tree, so the defaults are
+/// tried next; a key found in neither is taken literally and rejected by
+/// `set` if the typed config does not know it.
+fn resolve(config: &DaemonConfig, key: &str) -> Result<Vec<String>> {
+    let segments: Vec<&str> = key.split('.').filter(|s| !s.is_empty()).collect();
+    if segments.is_empty() {
+        bail!("Empty config key");
+    }
+
+    let path = resolve_in(&to_table(config)?, &segments)
+        .or_else(|| resolve_in(&defaults_tree(), &segments))
+        .unwrap_or_else(|| vec![segments.join("_")]);
+    Ok(path)
+}
+
+fn resolve_in(table: &Table, segments: &[&str]) -> Option<Vec<String>> {
+    for take in 1..=segments.len() {
+        let name = segments[..take].join("_");
+        let Some(value) = table.get(&name) else {
+            continue;
+        };
+        let rest = &segments[take..];
+        if rest.is_empty() {
+            return Some(vec![name]);
+        }
+        if let Value::Table(inner) = value {
+            if let Some(mut path) = resolve_in(inner, rest) {
+                path.insert(0, name);
+                return Some(path);
+            }
+        }
+    }
+    None
+}
+
+fn lookup<'a>(table: &'a Table, path: &[String]) -> Option<&'a Value> {
+    let (last, parents) = path.split_last()?;
+    let mut current = table;
+    for name in parents {
+        current = current.get(name)?.as_table()?;
+    }
+    current.get(last)
+}
+
+fn insert(table: &mut Table, path: &[String], value: Value) -> Result<()> {
+    let (last, parents) = path.split_last().context("Empty config key")?;
+    let mut current = table;
+    for name in parents {
+        current = current
+            .entry(name.clone())
+            .or_insert_with(|| Value::Table(Table::new()))
+            .as_table_mut()
+            .with_context(|| format!("'{}' is not a table", name))?;
+    }
+    current.insert(last.clone(), value);
+    Ok(())
+}
+
+/// Parse `raw` as the type of `template` (or as a TOML literal without one)
+fn coerce(key: &str, raw: &str, template: Option<&Value>) -> Result<Value> {
+    let value = match template {
+        Some(Value::String(_)) => Value::String(raw.to_string()),
+        Some(Value::Integer(_)) => Value::Integer(
+            raw.parse()
+                .with_context(|| format!("{} expects an integer, got '{}'", key, raw))?,
+        ),
+        Some(Value::Float(_)) => Value::Float(
+            raw.parse()
+                .with_context(|| format!("{} expects a number, got '{}'", key, raw))?,
+        ),
+        Some(Value::Boolean(_)) => Value::Boolean(
+            raw.parse()
+                .with_context(|| format!("{} expects true or false, got '{}'", key, raw))?,
+        ),
+        Some(Value::Table(_)) => bail!("{} is a table; set one of its keys instead", key),
+        _ => format!("value = {}", raw)
+            .parse::<Table>()
+            .ok()
+            .and_then(|mut t| t.remove("value"))
+            .unwrap_or_else(|| Value::String(raw.to_string())),
+    };
+    Ok(value)
+}
+
+fn collect_secret_refs(prefix: &str, table: &Table, refs: &mut Vec<(String, String)>) {
+    for (name, value) in table {
+        let key = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+        match value {
+            Value::Table(inner) => collect_secret_refs(&key, inner, refs),
+            Value::String(s) => {
+                if let Some(secret) = credentials::secret_name(s) {
+                    refs.push((key, secret.to_string()));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn diff_tables(prefix: &str, defaults: &Table, current: &Table, changes: &mut Vec<ConfigChange>) {
+    let mut keys: Vec<&String> = defaults.keys().chain(current.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    for name in keys {
+        let key = if prefix.is_empty() {
+            name.clone()
+        } else {
+            format!("{}.{}", prefix, name)
+        };
+        match (defaults.get(name), current.get(name)) {
+            (Some(Value::Table(d)), Some(Value::Table(c))) => diff_tables(&key, d, c, changes),
+            (d, c) if d != c => changes.push(ConfigChange {
+                key,
+                default: d.cloned(),
+                current: c.cloned(),
+            }),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dotted_keys_resolve() {
+        let config = DaemonConfig::default();
+        assert_eq!(get(&config, "vad.threshold").unwrap(), Value::Float(0.25));
+        assert_eq!(
+            get(&config, "latency_budgets.stt_ms").unwrap(),
+            Value::Float(800.0)
+        );
+        assert!(get(&config, "audio_device_index").is_err());
+    }
+
+    #[test]
+    fn test_set_round_trips_and_validates() {
+        let config = DaemonConfig::default();
+
+        let updated = set(&config, "stt_model_override", "1.1b-gpu").unwrap();
+        assert_eq!(updated.stt_model_override, "1.1b-gpu");
+
+        let updated = set(&updated, "audio_device_index", "2").unwrap();
+        assert_eq!(updated.audio_device_index, Some(2));
+
+        assert!(set(&config, "stt_model_override", "2b-tpu").is_err());
+        assert!(set(&config, "vad.threshold", "abc").is_err());
+        assert!(set(&config, "vad.threshold", "1.5").is_err());
+        assert!(set(&config, "no_such_key", "1").is_err());
+    }
+
+    #[test]
+    fn test_secret_refs_found_in_nested_tables() {
+        let config = set(
+            &DaemonConfig::default(),
+            "hotkeys.toggle",
+            "secret:not-a-hotkey",
+        )
+        .unwrap();
+        assert_eq!(
+            secret_refs(&config).unwrap(),
+            vec![("hotkeys.toggle".to_string(), "not-a-hotkey".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_diff_lists_changed_keys() {
+        let config = DaemonConfig::default();
+        assert!(diff(&config).unwrap().is_empty());
+
+        let updated = set(&config, "vad.threshold", "0.4").unwrap();
+        let updated = set(&updated, "hotkeys.toggle", "Ctrl+Alt+D").unwrap();
+        let changes = diff(&updated).unwrap();
+        let keys: Vec<&str> = changes.iter().map(|c| c.key.as_str()).collect();
+        assert_eq!(keys, vec!["hotkeys.toggle", "vad_threshold"]);
+        assert_eq!(changes[1].default.as_ref().map(display).unwrap(), "0.25");
+        assert_eq!(changes[1].current.as_ref().map(display).unwrap(), "0.4");
+    }
+}
@@ -0,0 +1,283 @@
+//! Unknown, misspelled and renamed keys in config.toml
+//!
+//! serde skips keys it does not know, so `vad_treshold = 0.3` used to fall
+//! back to the default without a word. Every key of the file is now checked
+//! against the keys `DaemonConfig` reads, and an unknown one is an error
+//! naming the closest valid key. This is the effect of
+//! `#[serde(deny_unknown_fields)]`, done by hand because the attribute stops
+//! at the first bad key while `config validate` should list them all.
+//!
+//! Renamed settings are still read under their old names, with a
+//! deprecation warning; the next save writes the new name.
+
+use anyhow::{bail, Result};
+use toml::{Table, Value};
+
+use crate::config::DaemonConfig;
+use crate::config_cli::{defaults_tree, to_table};
+
+/// Old key → current key, as dotted paths
+///
+/// The `[vad]` / `[stt]` tables were the layout documented before the
+/// settings were flattened.
+pub const RENAMED: [(&str, &str); 4] = [
+    ("vad.threshold", "vad_threshold"),
+    ("vad.min_silence_duration", "vad_min_silence"),
+    ("vad.min_speech_duration", "vad_min_speech"),
+    ("stt.model_override", "stt_model_override"),
+];
+
+/// How close (normalized Damerau-Levenshtein similarity) a valid key must
+/// be to an unknown one to be suggested
+const SUGGESTION_SIMILARITY: f64 = 0.7;
+
+/// Everything wrong with a config file
+#[derive(Debug, Default)]
+pub struct Report {
+    /// The parsed config, unless a value could not be read at all
+    pub config: Option<DaemonConfig>,
+    /// Unknown keys and unreadable values, one message each
+    pub errors: Vec<String>,
+    /// Deprecated keys that were read under their new name
+    pub warnings: Vec<String>,
+}
+
+impl Report {
+    /// The config, or all errors, one per line
+    pub fn into_config(self) -> Result<DaemonConfig> {
+        match self.config {
+            Some(config) if self.errors.is_empty() => Ok(config),
+            _ => bail!("{}", self.errors.join("\n")),
+        }
+    }
+}
+
+/// Parse `contents` and collect every schema problem in one pass
+///
+/// Range checks are separate (`DaemonConfig::validate`).
+pub fn check(contents: &str) -> Report {
+    let mut report = Report::default();
+    let mut raw: Table = match toml::from_str(contents) {
+        Ok(raw) => raw,
+        Err(e) => {
+            report.errors.push(format!("Not valid TOML: {}", e));
+            return report;
+        }
+    };
+    report.warnings = migrate(&mut raw);
+
+    match Value::Table(raw.clone()).try_into::<DaemonConfig>() {
+        Ok(config) => {
+            report.errors = unknown_keys(&raw, &config);
+            report.config = Some(config);
+        }
+        Err(whole) => {
+            // serde stops at the first bad value; try each key on its own
+            // against the defaults to find all of them
+            let defaults = defaults_tree();
+            for (key, value) in &raw {
+                let mut probe = defaults.clone();
+                probe.insert(key.clone(), value.clone());
+                if let Err(e) = Value::Table(probe).try_into::<DaemonConfig>() {
+                    report
+                        .errors
+                        .push(format!("{}: {}", key, e.to_string().trim()));
+                }
+            }
+            if report.errors.is_empty() {
+                report.errors.push(whole.to_string().trim().to_string());
+            }
+            report
+                .errors
+                .extend(unknown_keys(&raw, &DaemonConfig::default()));
+        }
+    }
+    report
+}
+
+/// Move deprecated keys of `raw` to their current names
+fn migrate(raw: &mut Table) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for (old, new) in RENAMED {
+        let Some(value) = take(raw, &old.split('.').collect::<Vec<_>>()) else {
+            continue;
+        };
+        if raw.contains_key(new) {
+            warnings.push(format!(
+                "'{}' is deprecated and ignored because '{}' is set",
+                old, new
+            ));
+        } else {
+            warnings.push(format!("'{}' is deprecated, use '{}'", old, new));
+            raw.insert(new.to_string(), value);
+        }
+    }
+    warnings
+}
+
+/// Remove the value at `path`, and any table it leaves empty
+fn take(table: &mut Table, path: &[&str]) -> Option<Value> {
+    match path {
+        [] => None,
+        [key] => table.remove(*key),
+        [key, rest @ ..] => {
+            let inner = table.get_mut(*key)?.as_table_mut()?;
+            let value = take(inner, rest);
+            if inner.is_empty() {
+                table.remove(*key);
+            }
+            value
+        }
+    }
+}
+
+/// Keys of `raw` that neither `config` nor the defaults have
+fn unknown_keys(raw: &Table, config: &DaemonConfig) -> Vec<String> {
+    // Unset options are missing from the defaults and set ones from neither
+    let mut known = defaults_tree();
+    if let Ok(parsed) = to_table(config) {
+        union(&mut known, &parsed);
+    }
+
+    let mut problems = Vec::new();
+    find_unknown("", raw, &known, &mut problems);
+    problems
+}
+
+fn union(known: &mut Table, other: &Table) {
+    for (key, value) in other {
+        match (known.get_mut(key), value) {
+            (Some(Value::Table(inner)), Value::Table(other_inner)) => union(inner, other_inner),
+            (Some(_), _) => {}
+            (None, _) => {
+                known.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+fn find_unknown(prefix: &str, raw: &Table, known: &Table, problems: &mut Vec<String>) {
+    let path = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        }
+    };
+
+    for (key, value) in raw {
+        match (known.get(key), value) {
+            (None, _) => problems.push(match suggest(key, known) {
+                Some(close) => format!(
+                    "Unknown key '{}' (did you mean '{}'?)",
+                    path(key),
+                    path(close)
+                ),
+                None => format!("Unknown key '{}'", path(key)),
+            }),
+            (Some(Value::Table(known_inner)), Value::Table(inner)) => {
+                find_unknown(&path(key), inner, known_inner, problems)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The valid key most similar to `key`, if any is close enough
+fn suggest<'a>(key: &str, known: &'a Table) -> Option<&'a str> {
+    known
+        .keys()
+        .map(|candidate| {
+            (
+                swictation_textsim::normalized_damerau_levenshtein(key, candidate),
+                candidate,
+            )
+        })
+        .filter(|(similarity, _)| *similarity >= SUGGESTION_SIMILARITY)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, candidate)| candidate.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The default config with `edit` applied, as TOML
+    fn config_with(edit: impl FnOnce(&mut Table)) -> String {
+        let mut table = defaults_tree();
+        edit(&mut table);
+        toml::to_string(&table).unwrap()
+    }
+
+    fn set(table: &mut Table, path: &[&str], value: Value) {
+        let (last, parents) = path.split_last().unwrap();
+        let mut current = table;
+        for name in parents {
+            current = current
+                .entry(name.to_string())
+                .or_insert_with(|| Value::Table(Table::new()))
+                .as_table_mut()
+                .unwrap();
+        }
+        current.insert(last.to_string(), value);
+    }
+
+    #[test]
+    fn test_unknown_key_suggests_closest() {
+        let report = check(&config_with(|t| {
+            set(t, &["vad_treshold"], Value::Float(0.3));
+            set(t, &["hotkeys", "togle"], Value::String("F8".into()));
+        }));
+        assert_eq!(
+            report.errors,
+            vec![
+                "Unknown key 'hotkeys.togle' (did you mean 'hotkeys.toggle'?)",
+                "Unknown key 'vad_treshold' (did you mean 'vad_threshold'?)",
+            ]
+        );
+        assert!(report.into_config().is_err());
+
+        let report = check(&config_with(|t| {
+            set(t, &["completely_made_up"], Value::Integer(1))
+        }));
+        assert_eq!(report.errors, vec!["Unknown key 'completely_made_up'"]);
+    }
+
+    #[test]
+    fn test_renamed_keys_still_apply() {
+        let report = check(&config_with(|t| {
+            t.remove("vad_threshold");
+            set(t, &["vad", "threshold"], Value::Float(0.5));
+            set(
+                t,
+                &["stt", "model_override"],
+                Value::String("0.6b-cpu".into()),
+            );
+        }));
+        assert!(report.errors.is_empty(), "{:?}", report.errors);
+        assert_eq!(
+            report.warnings,
+            vec![
+                "'vad.threshold' is deprecated, use 'vad_threshold'",
+                "'stt.model_override' is deprecated and ignored because 'stt_model_override' is set",
+            ]
+        );
+        let config = report.into_config().unwrap();
+        assert_eq!(config.vad_threshold, 0.5);
+        assert_eq!(config.stt_model_override, "auto");
+    }
+
+    #[test]
+    fn test_reports_every_bad_value() {
+        let report = check(&config_with(|t| {
+            set(t, &["vad_threshold"], Value::String("high".into()));
+            set(t, &["vad_min_speech"], Value::Boolean(true));
+            set(t, &["bogus"], Value::Integer(1));
+        }));
+        assert!(report.config.is_none());
+        assert_eq!(report.errors.len(), 3, "{:?}", report.errors);
+        assert!(report.errors[0].starts_with("vad_min_speech:"));
+        assert!(report.errors[1].starts_with("vad_threshold:"));
+        assert_eq!(report.errors[2], "Unknown key 'bogus'");
+    }
+}
@@ -0,0 +1,189 @@
+//! Background training of the context-learning model
+//!
+//! The context model is retrained on a schedule instead of at startup, where
+//! a large history held up the daemon for minutes. Each run's progress goes
+//! to UI clients as `training_progress` events.
+//!
+//! Dictation comes first: no run starts while recording, and starting to
+//! record cancels a run in progress. The model on disk is only replaced by
+//! a finished run; a cancelled one is retried at the next check.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, RwLock};
+use tracing::{info, warn};
+
+use swictation_broadcaster::MetricsBroadcaster;
+use swictation_context_learning::{
+    load_or_train_model_with, should_retrain, CancelToken, Cancelled, LearningConfig,
+    RetrainingConfig, TrainingMonitor,
+};
+
+use crate::DaemonState;
+
+/// How often the scheduler checks whether retraining is due
+const CHECK_INTERVAL_SECS: u64 = 60 * 60;
+
+/// The training run in progress, if any
+#[derive(Default)]
+pub struct ContextTraining {
+    running: Mutex<Option<CancelToken>>,
+}
+
+impl ContextTraining {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop the run in progress; returns whether there was one
+    pub fn cancel(&self) -> bool {
+        match self.running.lock().unwrap().as_ref() {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn begin(&self) -> CancelToken {
+        let token = CancelToken::new();
+        *self.running.lock().unwrap() = Some(token.clone());
+        token
+    }
+
+    fn end(&self) {
+        *self.running.lock().unwrap() = None;
+    }
+}
+
+/// Check for due retraining now and then every `CHECK_INTERVAL_SECS`
+pub fn spawn_scheduler(
+    training: Arc<ContextTraining>,
+    state: Arc<RwLock<DaemonState>>,
+    broadcaster: Arc<MetricsBroadcaster>,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let data_dir = match swictation_paths::get_data_dir() {
+            Ok(dir) => dir,
+            Err(e) => {
+                warn!("Failed to get data directory for context model: {:#}", e);
+                return;
+            }
+        };
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(CHECK_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            run_once(&training, &state, &broadcaster, &data_dir).await;
+        }
+    })
+}
+
+async fn run_once(
+    training: &ContextTraining,
+    state: &RwLock<DaemonState>,
+    broadcaster: &Arc<MetricsBroadcaster>,
+    data_dir: &Path,
+) {
+    let model_path = data_dir.join("context-model.json");
+    let db_path = data_dir.join("metrics.db");
+    let retrain_config = RetrainingConfig::default();
+
+    let due = {
+        let (model_path, db_path, config) =
+            (model_path.clone(), db_path.clone(), retrain_config.clone());
+        tokio::task::spawn_blocking(move || should_retrain(&model_path, &db_path, &config)).await
+    };
+    match due {
+        Ok(Ok(true)) => {}
+        Ok(Ok(false)) => return,
+        Ok(Err(e)) => {
+            warn!("Failed to check context model: {:#}", e);
+            return;
+        }
+        Err(e) => {
+            warn!("Context model check panicked: {}", e);
+            return;
+        }
+    }
+
+    // Registered before looking at the state, so a recording that starts
+    // in between finds the run and cancels it
+    let token = training.begin();
+    if *state.read().await == DaemonState::Recording {
+        training.end();
+        return;
+    }
+
+    let (progress_tx, mut progress_rx) = mpsc::unbounded_channel();
+    let monitor = TrainingMonitor::new()
+        .on_progress(move |progress| {
+            let _ = progress_tx.send(progress);
+        })
+        .with_cancel(token);
+
+    let forwarder = {
+        let broadcaster = broadcaster.clone();
+        tokio::spawn(async move {
+            let mut last_stage = "loading_segments";
+            while let Some(progress) = progress_rx.recv().await {
+                last_stage = progress.stage.as_str();
+                broadcaster
+                    .broadcast_training_progress(
+                        last_stage,
+                        progress.done as u64,
+                        progress.total as u64,
+                        "running",
+                    )
+                    .await;
+            }
+            last_stage
+        })
+    };
+
+    info!("🧠 Training context model in the background");
+    let result = tokio::task::spawn_blocking(move || {
+        load_or_train_model_with(
+            &model_path,
+            &db_path,
+            &LearningConfig::default(),
+            &retrain_config,
+            &monitor,
+        )
+    })
+    .await;
+    training.end();
+    // The monitor is gone with the blocking task, which ends the forwarder
+    let stage = forwarder.await.unwrap_or("loading_segments");
+
+    let status = match result {
+        Ok(Ok(Some(model))) => {
+            info!(
+                "🧠 Context model trained: {} topics, {} homonym rules",
+                model.topics.len(),
+                model.homonym_rules.len()
+            );
+            "completed"
+        }
+        Ok(Ok(None)) => {
+            info!("⚠️  Context model not available (insufficient training data)");
+            "completed"
+        }
+        Ok(Err(e)) if e.downcast_ref::<Cancelled>().is_some() => {
+            info!("⏹️ Context model training cancelled; will retry later");
+            "cancelled"
+        }
+        Ok(Err(e)) => {
+            warn!("Failed to train context model: {:#}", e);
+            "failed"
+        }
+        Err(e) => {
+            warn!("Context model training panicked: {}", e);
+            "failed"
+        }
+    };
+    broadcaster
+        .broadcast_training_progress(stage, 0, 0, status)
+        .await;
+}
@@ -5,7 +5,7 @@
 
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 use std::time::Instant;
 
@@ -79,6 +79,33 @@ struct CorrectionsFile {
     corrections: Vec<Correction>,
 }
 
+/// Corrections stored in `path` (none when the file does not exist yet)
+pub fn load_corrections(
+    path: &Path,
+) -> Result<Vec<Correction>, Box<dyn std::error::Error + Send + Sync>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(toml::from_str::<CorrectionsFile>(&content)?.corrections),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+/// Replace the corrections stored in `path`; a running engine watching the
+/// file picks them up
+pub fn save_corrections(
+    path: &Path,
+    corrections: Vec<Correction>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    // Ensure directory exists
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let content = toml::to_string_pretty(&CorrectionsFile { corrections })?;
+    fs::write(path, content)?;
+    Ok(())
+}
+
 /// The correction engine with hot-reloading support
 pub struct CorrectionEngine {
     /// Path to corrections.toml
@@ -492,42 +519,11 @@ impl CorrectionEngine {
 
     /// Compute normalized Levenshtein edit distance (0.0 = identical, 1.0 = completely different)
     fn normalized_edit_distance(a: &str, b: &str) -> f64 {
-        let a_chars: Vec<char> = a.chars().collect();
-        let b_chars: Vec<char> = b.chars().collect();
-
-        let n = a_chars.len();
-        let m = b_chars.len();
-
-        if n == 0 {
-            return if m == 0 { 0.0 } else { 1.0 };
-        }
-        if m == 0 {
-            return 1.0;
-        }
-
-        // Use single-row optimization for memory efficiency
-        let mut prev_row: Vec<usize> = (0..=m).collect();
-        let mut curr_row: Vec<usize> = vec![0; m + 1];
-
-        for i in 1..=n {
-            curr_row[0] = i;
-
-            for j in 1..=m {
-                let cost = if a_chars[i - 1] == b_chars[j - 1] {
-                    0
-                } else {
-                    1
-                };
-                curr_row[j] = (prev_row[j] + 1)
-                    .min(curr_row[j - 1] + 1)
-                    .min(prev_row[j - 1] + cost);
-            }
-
-            std::mem::swap(&mut prev_row, &mut curr_row);
+        let longest = a.chars().count().max(b.chars().count());
+        if longest == 0 {
+            return 0.0;
         }
-
-        let distance = prev_row[m];
-        distance as f64 / n.max(m) as f64
+        swictation_textsim::levenshtein(a, b) as f64 / longest as f64
     }
 
     /// Add a new correction and save to disk
@@ -671,25 +667,16 @@ impl CorrectionEngine {
     }
 
     fn load_file(&self) -> Result<CorrectionsFile, Box<dyn std::error::Error + Send + Sync>> {
-        match fs::read_to_string(&self.config_path) {
-            Ok(content) => Ok(toml::from_str(&content)?),
-            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(CorrectionsFile::default()),
-            Err(e) => Err(Box::new(e)),
-        }
+        Ok(CorrectionsFile {
+            corrections: load_corrections(&self.config_path)?,
+        })
     }
 
     fn save_file(
         &self,
         file: &CorrectionsFile,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        // Ensure directory exists
-        if let Some(parent) = self.config_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-
-        let content = toml::to_string_pretty(file)?;
-        fs::write(&self.config_path, content)?;
-        Ok(())
+        save_corrections(&self.config_path, file.corrections.clone())
     }
 }
 
@@ -0,0 +1,92 @@
+//! API keys and other secrets, kept in the OS credential store
+//!
+//! Secrets are stored under the `swictation` service in Secret Service
+//! (Linux), Keychain (macOS) or Credential Manager (Windows) and never
+//! written to `config.toml`. Config values refer to them by name with a
+//! `secret:` prefix, e.g. `api_key = "secret:openai"`; `config validate`
+//! reports references to secrets that were never stored.
+
+use anyhow::{bail, Context, Result};
+use keyring::Entry;
+
+/// Service name all secrets are stored under
+const SERVICE: &str = "swictation";
+
+/// Prefix marking a config value as a reference to a stored secret
+pub const SECRET_PREFIX: &str = "secret:";
+
+/// Store (or replace) a secret
+pub fn set(name: &str, value: &str) -> Result<()> {
+    if value.is_empty() {
+        bail!("Refusing to store an empty secret");
+    }
+    entry(name)?
+        .set_password(value)
+        .with_context(|| format!("Failed to store secret '{}'", name))
+}
+
+/// Fetch a secret, or `None` if it was never stored
+pub fn get(name: &str) -> Result<Option<String>> {
+    match entry(name)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read secret '{}'", name)),
+    }
+}
+
+/// Remove a secret; returns false if there was nothing to remove
+pub fn delete(name: &str) -> Result<bool> {
+    match entry(name)?.delete_credential() {
+        Ok(()) => Ok(true),
+        Err(keyring::Error::NoEntry) => Ok(false),
+        Err(e) => Err(e).with_context(|| format!("Failed to delete secret '{}'", name)),
+    }
+}
+
+/// Name referenced by a `secret:<name>` config value
+pub fn secret_name(value: &str) -> Option<&str> {
+    value.strip_prefix(SECRET_PREFIX)
+}
+
+fn entry(name: &str) -> Result<Entry> {
+    validate_name(name)?;
+    Entry::new(SERVICE, name).context("Failed to open OS credential store")
+}
+
+/// Names are short identifiers so they read well in config and keyring UIs
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+    if !valid {
+        bail!(
+            "Invalid secret name '{}' (use letters, digits, '-', '_' or '.', at most 64 characters)",
+            name
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_references() {
+        assert_eq!(secret_name("secret:openai"), Some("openai"));
+        assert_eq!(secret_name("sk-plaintext"), None);
+    }
+
+    #[test]
+    fn test_secret_names_are_validated() {
+        assert!(validate_name("openai").is_ok());
+        assert!(validate_name("remote.control-token_2").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("has space").is_err());
+        assert!(validate_name("../etc").is_err());
+        // Rejected before the keyring is touched
+        assert!(get("bad name").is_err());
+    }
+}
@@ -0,0 +1,361 @@
+//! Shortcuts the daemon writes into GNOME settings and the Sway config
+//!
+//! Where the compositor does not let applications grab keys, the daemon
+//! installs its toggle shortcut itself (see [`crate::hotkey`]). What it
+//! installed is recorded in `hotkeys.json` in the data directory, so a
+//! changed binding is updated in place instead of piling up, and
+//! `swictation-daemon hotkeys uninstall` removes exactly what was added.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::warn;
+
+/// dconf path of the GNOME custom keybinding
+pub const GNOME_KEYBINDING_PATH: &str =
+    "/org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/swictation-toggle/";
+
+pub const GNOME_MEDIA_KEYS: &str = "org.gnome.settings-daemon.plugins.media-keys";
+
+/// Keys set on the GNOME custom keybinding
+const GNOME_KEYBINDING_KEYS: [&str; 3] = ["name", "command", "binding"];
+
+/// GNOME custom keybinding written by the daemon
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GnomeShortcut {
+    /// e.g. `<Super><Shift>d`
+    pub binding: String,
+    pub command: String,
+}
+
+/// Sway `bindsym` block written by the daemon
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SwayShortcut {
+    pub config_path: PathBuf,
+    /// e.g. `$mod+Shift+d`
+    pub binding: String,
+}
+
+/// Everything currently installed, as recorded in the state file
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct InstalledShortcuts {
+    #[serde(default)]
+    pub gnome: Option<GnomeShortcut>,
+    #[serde(default)]
+    pub sway: Option<SwayShortcut>,
+}
+
+impl InstalledShortcuts {
+    pub fn state_path() -> PathBuf {
+        swictation_paths::data_dir().join("hotkeys.json")
+    }
+
+    /// Recorded shortcuts (none when the file is missing or unreadable)
+    pub fn load() -> Self {
+        Self::load_from(&Self::state_path())
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).unwrap_or_else(|e| {
+                warn!("Ignoring unreadable {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::state_path())
+    }
+
+    /// Write the state, removing the file once nothing is installed
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if *self == Self::default() {
+            return match std::fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    Err(e).with_context(|| format!("Failed to remove {}", path.display()))
+                }
+                _ => Ok(()),
+            };
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Record a change made by `update`, warning rather than failing
+    pub fn record(update: impl FnOnce(&mut Self)) {
+        let mut installed = Self::load();
+        update(&mut installed);
+        if let Err(e) = installed.save() {
+            warn!("Failed to record installed hotkeys: {:#}", e);
+        }
+    }
+}
+
+/// Default Sway config location
+pub fn sway_config_path() -> Result<PathBuf> {
+    let home = std::env::var("HOME").context("HOME environment variable not set")?;
+    Ok(PathBuf::from(home).join(".config/sway/config"))
+}
+
+/// The block appended to the Sway config for `binding`
+#[cfg_attr(not(feature = "sway-integration"), allow(dead_code))]
+pub fn sway_block(binding: &str) -> String {
+    format!(
+        "\n# Swictation\nbindsym {} exec swictation toggle\n",
+        binding
+    )
+}
+
+/// `content` without any Swictation block (old and current formats)
+///
+/// Trailing blank lines are dropped too, so stripping and re-appending the
+/// same block gives back the same file.
+pub fn strip_sway_block(content: &str) -> String {
+    let mut cleaned = String::new();
+    let mut in_swictation_block = false;
+
+    for line in content.lines() {
+        // Detect start of Swictation block (both old and new formats)
+        if line.contains("# Swictation") || line.contains("#Swictation") {
+            in_swictation_block = true;
+            continue;
+        }
+
+        // Skip lines in Swictation block
+        if in_swictation_block {
+            // End block when we hit a non-swictation line (empty line or different section)
+            if line.trim().is_empty()
+                || (!line.trim_start().starts_with("bindsym")
+                    && !line.trim_start().starts_with('#'))
+            {
+                in_swictation_block = false;
+                cleaned.push_str(line);
+                cleaned.push('\n');
+            }
+            // Skip all swictation-related bindings
+            continue;
+        }
+
+        cleaned.push_str(line);
+        cleaned.push('\n');
+    }
+
+    let len = cleaned.trim_end().len();
+    cleaned.truncate(len);
+    if !cleaned.is_empty() {
+        cleaned.push('\n');
+    }
+    cleaned
+}
+
+/// A gsettings string list (`['a', 'b']`, or `@as []` when empty) with
+/// `path` appended unless it is already there
+pub fn gnome_list_with(list: &str, path: &str) -> String {
+    let mut paths = parse_gnome_list(list);
+    if !paths.iter().any(|p| p == path) {
+        paths.push(path.to_string());
+    }
+    format_gnome_list(&paths)
+}
+
+/// A gsettings string list without `path`
+pub fn gnome_list_without(list: &str, path: &str) -> String {
+    let paths: Vec<String> = parse_gnome_list(list)
+        .into_iter()
+        .filter(|p| p != path)
+        .collect();
+    format_gnome_list(&paths)
+}
+
+fn parse_gnome_list(list: &str) -> Vec<String> {
+    list.trim()
+        .trim_start_matches("@as")
+        .trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split(',')
+        .map(|p| p.trim().trim_matches(['\'', '"']).to_string())
+        .filter(|p| !p.is_empty())
+        .collect()
+}
+
+fn format_gnome_list(paths: &[String]) -> String {
+    if paths.is_empty() {
+        return "@as []".to_string();
+    }
+    let quoted: Vec<String> = paths.iter().map(|p| format!("'{}'", p)).collect();
+    format!("[{}]", quoted.join(", "))
+}
+
+/// Run gsettings, returning its trimmed stdout
+pub fn gsettings(args: &[&str]) -> Result<String> {
+    let output = Command::new("gsettings")
+        .args(args)
+        .output()
+        .context("Failed to run gsettings")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "gsettings {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Schema and path argument addressing the daemon's GNOME keybinding
+pub fn gnome_keybinding_schema() -> String {
+    format!(
+        "{}.custom-keybinding:{}",
+        GNOME_MEDIA_KEYS, GNOME_KEYBINDING_PATH
+    )
+}
+
+/// Remove the GNOME keybinding, returning whether there was one
+fn uninstall_gnome() -> Result<bool> {
+    let list = gsettings(&["get", GNOME_MEDIA_KEYS, "custom-keybindings"])?;
+    if !parse_gnome_list(&list)
+        .iter()
+        .any(|p| p == GNOME_KEYBINDING_PATH)
+    {
+        return Ok(false);
+    }
+    gsettings(&[
+        "set",
+        GNOME_MEDIA_KEYS,
+        "custom-keybindings",
+        &gnome_list_without(&list, GNOME_KEYBINDING_PATH),
+    ])?;
+    let schema = gnome_keybinding_schema();
+    for key in GNOME_KEYBINDING_KEYS {
+        gsettings(&["reset", &schema, key])?;
+    }
+    Ok(true)
+}
+
+/// Remove the Sway block from `config_path`, returning whether there was one
+fn uninstall_sway(config_path: &Path) -> Result<bool> {
+    let content = match std::fs::read_to_string(config_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read {}", config_path.display()))
+        }
+    };
+    let cleaned = strip_sway_block(&content);
+    if cleaned.trim_end() == content.trim_end() {
+        return Ok(false);
+    }
+    std::fs::write(config_path, cleaned)
+        .with_context(|| format!("Failed to write {}", config_path.display()))?;
+
+    // Best effort: the binding stays live until Sway reloads
+    let _ = Command::new("swaymsg").arg("reload").output();
+    Ok(true)
+}
+
+/// Remove every shortcut the daemon installed, returning what was removed
+///
+/// Shortcuts from versions that did not keep the state file are found at
+/// their fixed locations too.
+pub fn uninstall() -> Result<Vec<String>> {
+    let installed = InstalledShortcuts::load();
+    let mut removed = Vec::new();
+
+    let has_gsettings = Command::new("gsettings")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if (has_gsettings || installed.gnome.is_some()) && uninstall_gnome()? {
+        removed.push(format!("GNOME shortcut {}", GNOME_KEYBINDING_PATH));
+    }
+
+    let sway_path = match &installed.sway {
+        Some(sway) => Some(sway.config_path.clone()),
+        None => sway_config_path().ok(),
+    };
+    if let Some(path) = sway_path {
+        if uninstall_sway(&path)? {
+            removed.push(format!("Sway bindings in {}", path.display()));
+        }
+    }
+
+    InstalledShortcuts::default().save()?;
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sway_block_round_trip() {
+        let config = "set $mod Mod4\nbindsym $mod+Return exec foot\n\n# Swictation voice-to-text hotkeys\nbindsym $mod+Shift+d exec swictation toggle\n";
+        let cleaned = strip_sway_block(config);
+        assert_eq!(cleaned, "set $mod Mod4\nbindsym $mod+Return exec foot\n");
+
+        // Re-applying the same binding leaves the file unchanged
+        let installed = format!("{}{}", cleaned, sway_block("$mod+Shift+d"));
+        let reinstalled = format!(
+            "{}{}",
+            strip_sway_block(&installed),
+            sway_block("$mod+Shift+d")
+        );
+        assert_eq!(installed, reinstalled);
+
+        // A new binding replaces the old one instead of adding a second
+        let changed = format!(
+            "{}{}",
+            strip_sway_block(&installed),
+            sway_block("$mod+Shift+v")
+        );
+        assert_eq!(changed.matches("exec swictation toggle").count(), 1);
+        assert!(changed.contains("bindsym $mod+Shift+v"));
+    }
+
+    #[test]
+    fn test_gnome_list_edits() {
+        let ours = GNOME_KEYBINDING_PATH;
+        let other = "/org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/custom0/";
+
+        assert_eq!(gnome_list_with("@as []", ours), format!("['{}']", ours));
+        let both = gnome_list_with(&format!("['{}']", other), ours);
+        assert_eq!(both, format!("['{}', '{}']", other, ours));
+        assert_eq!(gnome_list_with(&both, ours), both);
+
+        assert_eq!(gnome_list_without(&both, ours), format!("['{}']", other));
+        assert_eq!(gnome_list_without(&format!("['{}']", ours), ours), "@as []");
+    }
+
+    #[test]
+    fn test_state_file_round_trip() {
+        let dir = std::env::temp_dir().join(format!("swictation-hotkeys-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("hotkeys.json");
+        assert_eq!(
+            InstalledShortcuts::load_from(&path),
+            InstalledShortcuts::default()
+        );
+
+        let installed = InstalledShortcuts {
+            gnome: Some(GnomeShortcut {
+                binding: "<Super><Shift>d".into(),
+                command: "swictation toggle".into(),
+            }),
+            sway: None,
+        };
+        installed.save_to(&path).unwrap();
+        assert_eq!(InstalledShortcuts::load_from(&path), installed);
+
+        // Nothing installed removes the file
+        InstalledShortcuts::default().save_to(&path).unwrap();
+        assert!(!path.exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
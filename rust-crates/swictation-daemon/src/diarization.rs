@@ -0,0 +1,58 @@
+//! Who said what: speaker labels for transcribed segments
+//!
+//! Each speech segment is embedded with the `[diarization]` model and
+//! clustered against the voices heard earlier in the same recording (a
+//! transcribed file, or one recording session when `live` is set). Labels
+//! restart at Speaker 1 with every recording; nothing identifies a voice
+//! across recordings.
+
+use std::path::Path;
+use tracing::{info, warn};
+
+use swictation_stt::{SpeakerClusters, SpeakerEmbedder};
+
+use crate::config::DiarizationConfig;
+
+/// Speaker labels for the segments of one recording at a time
+pub struct Diarizer {
+    embedder: SpeakerEmbedder,
+    clusters: SpeakerClusters,
+}
+
+impl Diarizer {
+    /// Load the speaker model, or `None` when none is configured or it does
+    /// not load (transcripts are then unlabelled)
+    pub fn new(config: &DiarizationConfig) -> Option<Self> {
+        let path: &Path = config.model_path.as_deref()?;
+        match SpeakerEmbedder::new(path) {
+            Ok(embedder) => {
+                info!("🗣️ Speaker labels enabled");
+                Some(Self {
+                    embedder,
+                    clusters: SpeakerClusters::new(config.threshold, config.max_speakers),
+                })
+            }
+            Err(e) => {
+                warn!("⚠️ Speaker model not loaded: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Speaker number (from 1) of a speech segment, or `None` when it is too
+    /// short to tell or the model failed
+    pub fn label(&mut self, samples: &[f32]) -> Option<usize> {
+        match self.embedder.embed(samples) {
+            Ok(embedding) => embedding.map(|embedding| self.clusters.assign(&embedding)),
+            Err(e) => {
+                warn!("⚠️ Speaker embedding failed: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Start a new recording: forget the voices heard so far
+    pub fn reset(&mut self) {
+        self.clusters.clear();
+    }
+}
@@ -0,0 +1,361 @@
+//! Environment diagnostics (`swictation-daemon doctor`)
+//!
+//! Runs a set of read-only checks against the local installation: config
+//! parsing, model files, GPU detection, display server, hotkey conflicts and
+//! socket paths.
+//! Nothing here loads models or starts the audio pipeline, so it is safe to
+//! run while the daemon is already active. If the daemon is running, its
+//! recent VAD probabilities are fetched over IPC and compared against the
+//! threshold.
+
+use std::fmt;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::config::DaemonConfig;
+use crate::config_schema;
+use crate::display_server::detect_display_server;
+use crate::gpu::{detect_gpu_provider, get_gpu_memory_mb};
+use crate::hotkey_conflicts::{self, HotkeyConflict};
+use crate::socket_utils;
+
+/// Outcome of a single diagnostic check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+impl CheckStatus {
+    fn symbol(&self) -> &'static str {
+        match self {
+            CheckStatus::Ok => "✓",
+            CheckStatus::Warn => "⚠",
+            CheckStatus::Fail => "✗",
+        }
+    }
+}
+
+/// A single named diagnostic with a human-readable detail line
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: &'static str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Collected results of all diagnostic checks
+#[derive(Debug, Clone, Default)]
+pub struct DoctorReport {
+    pub checks: Vec<Check>,
+}
+
+impl DoctorReport {
+    /// True if no check failed outright
+    pub fn is_healthy(&self) -> bool {
+        self.checks.iter().all(|c| c.status != CheckStatus::Fail)
+    }
+}
+
+impl fmt::Display for DoctorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Swictation doctor")?;
+        writeln!(f)?;
+        for check in &self.checks {
+            writeln!(
+                f,
+                "  {} {:<16} {}",
+                check.status.symbol(),
+                check.name,
+                check.detail
+            )?;
+        }
+        writeln!(f)?;
+        if self.is_healthy() {
+            writeln!(f, "No blocking problems found.")
+        } else {
+            writeln!(f, "One or more checks failed - see above.")
+        }
+    }
+}
+
+fn check_path(name: &'static str, path: &Path) -> Check {
+    if path.exists() {
+        Check::new(name, CheckStatus::Ok, path.display().to_string())
+    } else {
+        Check::new(
+            name,
+            CheckStatus::Fail,
+            match swictation_models::missing([path]).first() {
+                Some(spec) => format!(
+                    "missing: {} (run `{}`)",
+                    path.display(),
+                    swictation_models::download_command(spec)
+                ),
+                None => format!("missing: {}", path.display()),
+            },
+        )
+    }
+}
+
+/// Ask a running daemon for its recent VAD probabilities (`dump_vad_trace`)
+pub fn query_vad_trace(socket: &Path) -> anyhow::Result<serde_json::Value> {
+    let mut stream = UnixStream::connect(socket)?;
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    stream.write_all(br#"{"action": "dump_vad_trace"}"#)?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let response: serde_json::Value = serde_json::from_str(&response)?;
+
+    match response.get("trace") {
+        Some(trace) => Ok(trace.clone()),
+        None => anyhow::bail!(
+            "{}",
+            response["error"].as_str().unwrap_or("unexpected response")
+        ),
+    }
+}
+
+/// Report configured hotkeys that are probably taken, with alternatives
+fn check_hotkeys(conflicts: &[HotkeyConflict]) -> Check {
+    if conflicts.is_empty() {
+        return Check::new("hotkeys", CheckStatus::Ok, "no known conflicts");
+    }
+    Check::new(
+        "hotkeys",
+        CheckStatus::Warn,
+        conflicts
+            .iter()
+            .map(HotkeyConflict::to_string)
+            .collect::<Vec<_>>()
+            .join("; "),
+    )
+}
+
+/// Judge whether the VAD is seeing speech at the configured threshold
+fn check_vad_trace(trace: &serde_json::Value) -> Check {
+    let threshold = trace["threshold"].as_f64().unwrap_or(0.0);
+    let probabilities: Vec<f64> = trace["points"]
+        .as_array()
+        .map(|points| {
+            points
+                .iter()
+                .filter_map(|p| p["probability"].as_f64())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    if probabilities.is_empty() {
+        return Check::new(
+            "vad trace",
+            CheckStatus::Warn,
+            "no data (vad_trace_capacity = 0 or no audio recorded yet)",
+        );
+    }
+
+    let max = probabilities.iter().cloned().fold(0.0, f64::max);
+    let above = probabilities.iter().filter(|&&p| p >= threshold).count();
+    let detail = format!(
+        "{} windows, max probability {:.4}, {} at/above threshold {}",
+        probabilities.len(),
+        max,
+        above,
+        threshold
+    );
+
+    if above == 0 {
+        Check::new(
+            "vad trace",
+            CheckStatus::Warn,
+            format!(
+                "{} - speech never detected, try a lower vad_threshold",
+                detail
+            ),
+        )
+    } else {
+        Check::new("vad trace", CheckStatus::Ok, detail)
+    }
+}
+
+/// Run all diagnostic checks
+pub fn run() -> DoctorReport {
+    let mut checks = Vec::new();
+
+    let config_path = DaemonConfig::default_config_path();
+    let config = if config_path.exists() {
+        match std::fs::read_to_string(&config_path)
+            .map_err(anyhow::Error::from)
+            .and_then(|s| config_schema::check(&s).into_config())
+        {
+            Ok(config) => {
+                checks.push(Check::new(
+                    "config",
+                    CheckStatus::Ok,
+                    config_path.display().to_string(),
+                ));
+                config
+            }
+            Err(e) => {
+                checks.push(Check::new(
+                    "config",
+                    CheckStatus::Fail,
+                    // One problem per line is too tall for the checks table
+                    format!(
+                        "{}: {}",
+                        config_path.display(),
+                        e.to_string().replace('\n', "; ")
+                    ),
+                ));
+                DaemonConfig::default()
+            }
+        }
+    } else {
+        checks.push(Check::new(
+            "config",
+            CheckStatus::Warn,
+            format!("not found, defaults apply ({})", config_path.display()),
+        ));
+        DaemonConfig::default()
+    };
+
+    checks.push(check_path("vad model", &config.vad_model_path));
+    checks.push(check_path("stt 0.6b model", &config.stt_0_6b_model_path));
+    // The 1.1B model is optional - only needed on GPUs with ≥6GB VRAM
+    let mut large = check_path("stt 1.1b model", &config.stt_1_1b_model_path);
+    if large.status == CheckStatus::Fail {
+        large.status = CheckStatus::Warn;
+    }
+    checks.push(large);
+
+    match detect_gpu_provider() {
+        Some(provider) => {
+            let vram = get_gpu_memory_mb()
+                .map(|(total, free)| format!(" ({} MB total, {} MB free)", total, free))
+                .unwrap_or_default();
+            checks.push(Check::new(
+                "gpu",
+                CheckStatus::Ok,
+                format!("{}{}", provider, vram),
+            ));
+        }
+        None => checks.push(Check::new(
+            "gpu",
+            CheckStatus::Warn,
+            "no GPU provider detected, CPU inference only",
+        )),
+    }
+
+    let display = detect_display_server();
+    checks.push(check_hotkeys(&hotkey_conflicts::check(
+        &config.hotkeys,
+        display.desktop_environment.as_deref(),
+    )));
+    checks.push(Check::new(
+        "display server",
+        CheckStatus::Ok,
+        format!(
+            "{:?}{}",
+            display.server_type,
+            display
+                .desktop_environment
+                .map(|de| format!(" ({})", de))
+                .unwrap_or_default()
+        ),
+    ));
+
+    for (name, path) in [
+        ("ipc socket", socket_utils::get_ipc_socket_path()),
+        ("metrics socket", socket_utils::get_metrics_socket_path()),
+        ("firehose socket", socket_utils::get_firehose_socket_path()),
+    ] {
+        checks.push(match path {
+            Ok(p) if p.exists() => Check::new(name, CheckStatus::Ok, p.display().to_string()),
+            Ok(p) => Check::new(
+                name,
+                CheckStatus::Warn,
+                format!("{} (daemon not running?)", p.display()),
+            ),
+            Err(e) => Check::new(name, CheckStatus::Fail, e.to_string()),
+        });
+    }
+
+    if let Ok(socket) = socket_utils::get_ipc_socket_path() {
+        if socket.exists() {
+            checks.push(match query_vad_trace(&socket) {
+                Ok(trace) => check_vad_trace(&trace),
+                Err(e) => Check::new(
+                    "vad trace",
+                    CheckStatus::Warn,
+                    format!("unavailable: {}", e),
+                ),
+            });
+        }
+    }
+
+    DoctorReport { checks }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_health() {
+        let mut report = DoctorReport::default();
+        report.checks.push(Check::new("a", CheckStatus::Ok, "fine"));
+        report
+            .checks
+            .push(Check::new("b", CheckStatus::Warn, "meh"));
+        assert!(report.is_healthy());
+
+        report
+            .checks
+            .push(Check::new("c", CheckStatus::Fail, "broken"));
+        assert!(!report.is_healthy());
+        assert!(report.to_string().contains("✗ c"));
+    }
+
+    #[test]
+    fn test_vad_trace_check() {
+        let trace = serde_json::json!({
+            "threshold": 0.25,
+            "points": [
+                { "timestamp": 1.0, "probability": 0.01 },
+                { "timestamp": 1.032, "probability": 0.6 }
+            ]
+        });
+        assert_eq!(check_vad_trace(&trace).status, CheckStatus::Ok);
+
+        let quiet = serde_json::json!({
+            "threshold": 0.25,
+            "points": [{ "timestamp": 1.0, "probability": 0.01 }]
+        });
+        let check = check_vad_trace(&quiet);
+        assert_eq!(check.status, CheckStatus::Warn);
+        assert!(check.detail.contains("never detected"));
+
+        let empty = serde_json::json!({ "threshold": 0.25, "points": [] });
+        assert_eq!(check_vad_trace(&empty).status, CheckStatus::Warn);
+    }
+
+    #[test]
+    fn test_missing_path_fails() {
+        let check = check_path("model", Path::new("/nonexistent/swictation/model.onnx"));
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert!(check.detail.starts_with("missing:"));
+    }
+}
@@ -0,0 +1,187 @@
+//! Editor companion connections
+//!
+//! An editor plugin attaches over the IPC socket with
+//! `{"action": "editor_attach", "client": "nvim"}` and keeps the connection
+//! open. While an attached editor has focus, dictated text is sent to it as
+//! newline-delimited JSON instead of being typed as synthetic keystrokes, so
+//! the editor can insert it through its own API (one undo step per segment):
+//!
+//! ```text
+//! daemon → editor  {"status":"success","protocol":1,"client_id":3}
+//! daemon → editor  {"event":"insert_at_cursor","text":"Hello world. "}
+//! editor → daemon  {"focused":false}
+//! ```
+//!
+//! Editors start out focused and report focus changes. Text goes to the most
+//! recently focused editor; with none focused it is typed as before.
+//! Keystroke commands are always typed. See `editors/nvim` for a reference
+//! client.
+
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+use tokio::sync::mpsc;
+use tracing::{debug, info};
+
+/// Version of the editor protocol, sent when an editor attaches
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Line sent by an attached editor
+#[derive(Debug, Deserialize)]
+struct EditorMessage {
+    #[serde(default)]
+    focused: Option<bool>,
+}
+
+struct EditorClient {
+    id: u64,
+    name: String,
+    /// When the editor last reported focus (`None` while unfocused)
+    focused_since: Option<Instant>,
+    tx: mpsc::UnboundedSender<String>,
+}
+
+/// Attached editors
+#[derive(Default)]
+pub struct EditorHub {
+    clients: Mutex<Vec<EditorClient>>,
+    next_id: Mutex<u64>,
+}
+
+impl EditorHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn attach(&self, name: &str) -> (u64, mpsc::UnboundedReceiver<String>) {
+        let id = {
+            let mut next_id = self.next_id.lock().unwrap();
+            *next_id += 1;
+            *next_id
+        };
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.clients.lock().unwrap().push(EditorClient {
+            id,
+            name: name.to_string(),
+            focused_since: Some(Instant::now()),
+            tx,
+        });
+        (id, rx)
+    }
+
+    fn set_focus(&self, id: u64, focused: bool) {
+        let mut clients = self.clients.lock().unwrap();
+        if let Some(client) = clients.iter_mut().find(|c| c.id == id) {
+            client.focused_since = focused.then(Instant::now);
+        }
+    }
+
+    fn detach(&self, id: u64) {
+        self.clients.lock().unwrap().retain(|c| c.id != id);
+    }
+
+    /// Send `text` to the focused editor, returning its name
+    ///
+    /// `None` when no attached editor has focus, or for keystroke commands,
+    /// which the caller should type instead.
+    pub fn route(&self, text: &str, session_id: Option<i64>) -> Option<String> {
+        if text.is_empty() || text.contains("<KEY:") {
+            return None;
+        }
+        let line = format!(
+            "{}\n",
+            json!({ "event": "insert_at_cursor", "text": text, "session_id": session_id })
+        );
+
+        let mut clients = self.clients.lock().unwrap();
+        let client = clients
+            .iter()
+            .filter(|c| c.focused_since.is_some())
+            .max_by_key(|c| c.focused_since)?;
+        if client.tx.send(line).is_ok() {
+            return Some(client.name.clone());
+        }
+        // Connection task already gone
+        let id = client.id;
+        clients.retain(|c| c.id != id);
+        None
+    }
+}
+
+/// Serve an attached editor until it disconnects
+pub async fn serve(stream: UnixStream, hub: Arc<EditorHub>, name: String) {
+    let (id, mut rx) = hub.attach(&name);
+    info!("📝 Editor attached: {} (#{})", name, id);
+
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let hello = format!(
+        "{}\n",
+        json!({ "status": "success", "protocol": PROTOCOL_VERSION, "client_id": id })
+    );
+
+    if writer.write_all(hello.as_bytes()).await.is_ok() {
+        loop {
+            tokio::select! {
+                Some(line) = rx.recv() => {
+                    if writer.write_all(line.as_bytes()).await.is_err() {
+                        break;
+                    }
+                }
+                line = lines.next_line() => match line {
+                    Ok(Some(line)) => match serde_json::from_str::<EditorMessage>(&line) {
+                        Ok(EditorMessage { focused: Some(focused) }) => {
+                            hub.set_focus(id, focused)
+                        }
+                        Ok(_) => {}
+                        Err(e) => debug!("Ignoring editor message {:?}: {}", line, e),
+                    },
+                    _ => break,
+                },
+            }
+        }
+    }
+
+    hub.detach(id);
+    info!("📝 Editor detached: {} (#{})", name, id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routes_to_most_recently_focused_editor() {
+        let hub = EditorHub::new();
+        let (nvim, mut nvim_rx) = hub.attach("nvim");
+        let (code, mut code_rx) = hub.attach("vscode");
+
+        assert_eq!(hub.route("hello ", Some(1)).as_deref(), Some("vscode"));
+        let line = code_rx.try_recv().unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&line).unwrap()["text"],
+            "hello "
+        );
+
+        hub.set_focus(code, false);
+        assert_eq!(hub.route("world", Some(1)).as_deref(), Some("nvim"));
+        assert!(nvim_rx.try_recv().is_ok());
+        assert_eq!(hub.route("<KEY:Return>", Some(1)), None);
+
+        hub.set_focus(nvim, false);
+        assert_eq!(hub.route("typed", Some(1)), None);
+    }
+
+    #[test]
+    fn test_closed_editor_is_dropped() {
+        let hub = EditorHub::new();
+        let (_, rx) = hub.attach("nvim");
+        drop(rx);
+
+        assert_eq!(hub.route("hello", None), None);
+        assert!(hub.clients.lock().unwrap().is_empty());
+    }
+}
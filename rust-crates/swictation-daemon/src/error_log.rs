@@ -0,0 +1,210 @@
+//! Recent warnings and errors, kept in memory for `get_recent_errors`
+//!
+//! A tracing layer copies every WARN and ERROR event into a fixed-size ring,
+//! so the UI's diagnostics panel and `swictation-daemon errors` can show what
+//! went wrong without access to journald or the terminal the daemon runs in.
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Events kept in the ring
+pub const CAPACITY: usize = 200;
+
+/// Events returned when the request names no limit
+pub const DEFAULT_LIMIT: usize = 50;
+
+/// One logged warning or error
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ErrorEvent {
+    /// Unix time in seconds
+    pub timestamp: f64,
+    /// "error" or "warn"
+    pub level: String,
+    /// Module that logged it
+    pub target: String,
+    pub message: String,
+}
+
+impl ErrorEvent {
+    /// One line for the terminal, in local time
+    pub fn to_line(&self) -> String {
+        let time = chrono::DateTime::from_timestamp(self.timestamp as i64, 0)
+            .map(|t| {
+                t.with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_default();
+        format!(
+            "{} {:<5} {}: {}",
+            time,
+            self.level.to_uppercase(),
+            self.target,
+            self.message
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct ErrorLog {
+    /// Oldest first
+    events: Mutex<VecDeque<ErrorEvent>>,
+    capacity: usize,
+}
+
+impl ErrorLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    pub fn push(&self, event: ErrorEvent) {
+        let mut events = self.events.lock().unwrap();
+        if events.len() == self.capacity {
+            events.pop_front();
+        }
+        if self.capacity > 0 {
+            events.push_back(event);
+        }
+    }
+
+    /// Up to `limit` events, most recent first
+    pub fn recent(&self, limit: usize) -> Vec<ErrorEvent> {
+        let events = self.events.lock().unwrap();
+        events.iter().rev().take(limit).cloned().collect()
+    }
+}
+
+/// Collects an event's message and any extra fields as `key=value`
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+}
+
+/// Tracing layer feeding an [`ErrorLog`]
+pub struct ErrorLogLayer {
+    log: Arc<ErrorLog>,
+}
+
+impl ErrorLogLayer {
+    pub fn new(log: Arc<ErrorLog>) -> Self {
+        Self { log }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for ErrorLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let level = match *metadata.level() {
+            Level::ERROR => "error",
+            Level::WARN => "warn",
+            _ => return,
+        };
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        self.log.push(ErrorEvent {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs_f64())
+                .unwrap_or_default(),
+            level: level.to_string(),
+            target: metadata.target().to_string(),
+            message: visitor.message + &visitor.fields,
+        });
+    }
+}
+
+/// Ask a running daemon for its recent errors (`get_recent_errors`)
+pub fn query(socket: &Path, limit: usize) -> anyhow::Result<Vec<ErrorEvent>> {
+    let mut stream = UnixStream::connect(socket)?;
+    stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+    let request = serde_json::json!({ "action": "get_recent_errors", "limit": limit });
+    stream.write_all(request.to_string().as_bytes())?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let response: serde_json::Value = serde_json::from_str(&response)?;
+
+    match response.get("errors") {
+        Some(errors) => Ok(serde_json::from_value(errors.clone())?),
+        None => anyhow::bail!(
+            "{}",
+            response["error"].as_str().unwrap_or("unexpected response")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn event(message: &str) -> ErrorEvent {
+        ErrorEvent {
+            timestamp: 0.0,
+            level: "warn".to_string(),
+            target: "test".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_ring_keeps_newest() {
+        let log = ErrorLog::new(2);
+        for message in ["a", "b", "c"] {
+            log.push(event(message));
+        }
+        let messages: Vec<String> = log.recent(10).into_iter().map(|e| e.message).collect();
+        assert_eq!(messages, ["c", "b"]);
+        assert_eq!(log.recent(1).len(), 1);
+    }
+
+    #[test]
+    fn test_layer_records_warnings_and_errors() {
+        let log = Arc::new(ErrorLog::new(CAPACITY));
+        let subscriber = tracing_subscriber::registry().with(ErrorLogLayer::new(Arc::clone(&log)));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("loaded");
+            tracing::warn!(device = 3, "audio device busy");
+            tracing::error!("STT timed out after {} s", 30);
+        });
+
+        let events = log.recent(DEFAULT_LIMIT);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].level, "error");
+        assert_eq!(events[0].message, "STT timed out after 30 s");
+        assert_eq!(events[1].message, "audio device busy device=3");
+        assert!(events[1].to_line().contains("WARN  "));
+    }
+}
@@ -1,14 +1,27 @@
 //! GPU detection and provider selection
 
+use std::path::Path;
+use std::time::{Duration, Instant};
+use swictation_stt::{ExecutionTarget, OrtRecognizer};
 use tracing::{info, warn};
 
+/// Audio recognized per benchmark run (seconds)
+const BENCHMARK_AUDIO_S: usize = 5;
+
+/// Timed runs per provider, after one warm-up run
+const BENCHMARK_RUNS: usize = 3;
+
 /// Detect available GPU provider
 ///
 /// Returns the best available GPU provider in priority order:
 /// 1. CUDA (NVIDIA) on Linux/Windows
-/// 2. DirectML (any GPU) on Windows
-/// 3. CoreML (Apple Silicon) on macOS
-/// 4. None (CPU fallback)
+/// 2. ROCm (AMD) on Linux
+/// 3. DirectML (any GPU) on Windows
+/// 4. CoreML (Apple Silicon) on macOS
+/// 5. OpenVINO (Intel integrated GPU, then NPU)
+/// 6. None (CPU fallback)
+///
+/// See [`select_provider_by_benchmark`] to measure instead.
 pub fn detect_gpu_provider() -> Option<String> {
     // macOS: Check for Apple Silicon (CoreML)
     #[cfg(target_os = "macos")]
@@ -37,10 +50,151 @@ pub fn detect_gpu_provider() -> Option<String> {
         }
     }
 
+    // Linux: Check AMD ROCm
+    #[cfg(target_os = "linux")]
+    {
+        if check_rocm_available() {
+            info!("Detected AMD GPU - using ROCm");
+            return Some("rocm".to_string());
+        }
+    }
+
+    // Intel: OpenVINO on the integrated GPU or NPU
+    if let Some(device) = openvino_devices().first() {
+        info!("Detected Intel {} - using OpenVINO", device);
+        return Some(openvino_provider(device));
+    }
+
     warn!("No GPU detected - falling back to CPU");
     None
 }
 
+/// Provider name for OpenVINO on `device`, as [`ExecutionTarget::from_provider`] reads it
+fn openvino_provider(device: &str) -> String {
+    format!("openvino:{}", device)
+}
+
+/// Intel devices OpenVINO can use ("GPU", "NPU"), best first
+///
+/// Empty unless the loaded ONNX Runtime has the OpenVINO execution provider.
+/// The hardware is probed first so machines without Intel devices never load
+/// ONNX Runtime here. On Windows the devices can't be probed without loading
+/// a model, so both are listed and [`select_provider_by_benchmark`] drops the
+/// missing one.
+pub fn openvino_devices() -> Vec<&'static str> {
+    #[cfg(target_os = "linux")]
+    let devices = {
+        let mut devices = Vec::new();
+        if intel_device_in("/sys/class/drm") {
+            devices.push("GPU");
+        }
+        if intel_device_in("/sys/class/accel") {
+            devices.push("NPU");
+        }
+        devices
+    };
+    #[cfg(target_os = "windows")]
+    let devices = vec!["GPU", "NPU"];
+    #[cfg(not(any(target_os = "linux", target_os = "windows")))]
+    let devices: Vec<&'static str> = Vec::new();
+
+    if devices.is_empty() || !ExecutionTarget::OpenVino(String::new()).is_available() {
+        return Vec::new();
+    }
+    devices
+}
+
+/// Whether a device in a sysfs class directory is made by Intel (PCI vendor 0x8086)
+#[cfg(target_os = "linux")]
+fn intel_device_in(class_dir: &str) -> bool {
+    let Ok(entries) = std::fs::read_dir(class_dir) else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        std::fs::read_to_string(entry.path().join("device/vendor"))
+            .is_ok_and(|vendor| vendor.trim() == "0x8086")
+    })
+}
+
+/// Providers [`select_provider_by_benchmark`] times: the detected one,
+/// OpenVINO on each device, then the CPU (`None`)
+fn benchmark_candidates(detected: Option<&str>, devices: &[&str]) -> Vec<Option<String>> {
+    let mut candidates: Vec<Option<String>> = detected
+        .filter(|provider| !provider.starts_with("openvino"))
+        .map(|provider| Some(provider.to_string()))
+        .into_iter()
+        .collect();
+    candidates.extend(devices.iter().map(|device| Some(openvino_provider(device))));
+    candidates.push(None);
+    candidates
+}
+
+/// Pick the execution provider by timing the Parakeet model in `model_dir`
+///
+/// Only measures when OpenVINO has a device to compete with the detected
+/// provider (or the CPU); otherwise `detected` is returned unchanged. Each
+/// candidate recognizes a few seconds of low-level noise after a warm-up run
+/// that absorbs graph compilation, and the fastest run counts. Candidates
+/// that fail to load are skipped. Returns `None` when the CPU wins.
+pub fn select_provider_by_benchmark(detected: Option<String>, model_dir: &Path) -> Option<String> {
+    let devices = openvino_devices();
+    if devices.is_empty() {
+        return detected;
+    }
+
+    info!(
+        "⏱️  Benchmarking execution providers on {}",
+        model_dir.display()
+    );
+    let mut fastest: Option<(Option<String>, Duration)> = None;
+    for candidate in benchmark_candidates(detected.as_deref(), &devices) {
+        let label = candidate.as_deref().unwrap_or("cpu").to_string();
+        match benchmark_provider(candidate.as_deref(), model_dir) {
+            Ok(elapsed) => {
+                info!(
+                    "   {}: {:.0}ms per {}s of audio",
+                    label,
+                    elapsed.as_secs_f64() * 1000.0,
+                    BENCHMARK_AUDIO_S
+                );
+                if fastest.as_ref().is_none_or(|(_, best)| elapsed < *best) {
+                    fastest = Some((candidate, elapsed));
+                }
+            }
+            Err(e) => warn!("   {}: unusable ({:#})", label, e),
+        }
+    }
+
+    match fastest {
+        Some((provider, _)) => {
+            info!(
+                "✓ Fastest execution provider: {}",
+                provider.as_deref().unwrap_or("cpu")
+            );
+            provider
+        }
+        None => detected,
+    }
+}
+
+/// Fastest of [`BENCHMARK_RUNS`] recognitions on `provider`
+fn benchmark_provider(provider: Option<&str>, model_dir: &Path) -> anyhow::Result<Duration> {
+    let mut recognizer = OrtRecognizer::new(model_dir, ExecutionTarget::from_provider(provider))?;
+    // Quiet noise rather than digital silence, like a real room
+    let audio: Vec<f32> = (0..16_000 * BENCHMARK_AUDIO_S)
+        .map(|i| (i as f32 * 0.37).sin() * 0.001)
+        .collect();
+
+    recognizer.recognize_samples(&audio)?;
+    let mut fastest = Duration::MAX;
+    for _ in 0..BENCHMARK_RUNS {
+        let start = Instant::now();
+        recognizer.recognize_samples(&audio)?;
+        fastest = fastest.min(start.elapsed());
+    }
+    Ok(fastest)
+}
+
 /// Check if CUDA is available (NVIDIA GPUs)
 #[cfg(not(target_os = "macos"))]
 fn check_cuda_available() -> bool {
@@ -57,6 +211,17 @@ fn check_cuda_available() -> bool {
     false // No CUDA on macOS
 }
 
+/// Check if ROCm is available (AMD GPUs, Linux)
+#[cfg(target_os = "linux")]
+fn check_rocm_available() -> bool {
+    // rocm-smi ships with the ROCm runtime and fails without an AMD GPU
+    std::process::Command::new("rocm-smi")
+        .arg("--showproductname")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
 /// Check if DirectML is available (Windows, any GPU)
 #[allow(dead_code)]
 #[cfg(all(target_os = "windows", feature = "gpu-info"))]
@@ -111,7 +276,10 @@ fn check_coreml_available() -> bool {
 /// Get GPU memory information in MB (total, available)
 ///
 /// **Platform-specific behavior:**
-/// - **Linux**: Queries NVIDIA GPU VRAM using nvidia-smi (dedicated GPU memory)
+/// - **Linux**: Queries NVIDIA GPU VRAM using nvidia-smi, or AMD GPU VRAM
+///   using rocm-smi (dedicated GPU memory)
+/// - **Windows**: Queries the largest hardware adapter through DXGI (any
+///   vendor, what DirectML runs on), falling back to nvidia-smi
 /// - **macOS**: Queries unified system memory (GPU shares RAM with CPU)
 ///
 /// Returns None if:
@@ -125,7 +293,8 @@ fn check_coreml_available() -> bool {
 /// Where:
 /// - **total_mb**: Total GPU memory (VRAM on Linux, system RAM on macOS)
 /// - **available_mb**: Memory available for ML workloads
-///   - Linux: Free VRAM reported by nvidia-smi
+///   - Linux: Free VRAM reported by nvidia-smi (rocm-smi: total minus used)
+///   - Windows: Unused part of the OS video memory budget
 ///   - macOS: 65% of system RAM (35% reserved for OS/apps)
 ///
 /// # Example
@@ -146,12 +315,125 @@ pub fn get_gpu_memory_mb() -> Option<(u64, u64)> {
     }
 
     // Linux/Windows: Query NVIDIA GPU VRAM via nvidia-smi
+    // (Windows asks DXGI first, which sees AMD and Intel GPUs too;
+    // Linux falls back to rocm-smi for AMD GPUs)
     #[cfg(not(target_os = "macos"))]
     {
-        get_nvidia_vram_mb()
+        #[cfg(all(target_os = "windows", feature = "gpu-info"))]
+        if let Some(vram) = get_dxgi_vram_mb() {
+            return Some(vram);
+        }
+        let nvidia = get_nvidia_vram_mb();
+        #[cfg(target_os = "linux")]
+        if nvidia.is_none() {
+            return get_rocm_vram_mb();
+        }
+        nvidia
+    }
+}
+
+/// Get dedicated VRAM of the largest hardware GPU via DXGI (Windows)
+///
+/// Covers every adapter DirectML can use. Integrated GPUs report only
+/// their small dedicated carve-out, so they rank below discrete cards and
+/// usually stay under the GPU model thresholds. Free memory is the OS
+/// budget for the adapter minus current usage (needs DXGI 1.4, otherwise
+/// the total is reported).
+#[cfg(all(target_os = "windows", feature = "gpu-info"))]
+fn get_dxgi_vram_mb() -> Option<(u64, u64)> {
+    use windows::core::Interface;
+    use windows::Win32::Graphics::Dxgi::*;
+
+    let mut adapters = Vec::new();
+    unsafe {
+        let factory: IDXGIFactory1 = CreateDXGIFactory1().ok()?;
+        let mut index = 0;
+        while let Ok(adapter) = factory.EnumAdapters1(index) {
+            index += 1;
+            let Ok(desc) = adapter.GetDesc1() else {
+                continue;
+            };
+            // Skip the Microsoft Basic Render Driver
+            if desc.Flags & DXGI_ADAPTER_FLAG_SOFTWARE.0 as u32 != 0 {
+                continue;
+            }
+
+            let budget = adapter
+                .cast::<IDXGIAdapter3>()
+                .ok()
+                .and_then(|adapter| {
+                    adapter
+                        .QueryVideoMemoryInfo(0, DXGI_MEMORY_SEGMENT_GROUP_LOCAL)
+                        .ok()
+                })
+                .map(|info| (info.Budget, info.CurrentUsage));
+            let name_len = desc
+                .Description
+                .iter()
+                .position(|&c| c == 0)
+                .unwrap_or(desc.Description.len());
+            adapters.push(DxgiAdapter::new(
+                String::from_utf16_lossy(&desc.Description[..name_len]),
+                desc.DedicatedVideoMemory as u64,
+                budget,
+            ));
+        }
+    }
+
+    let best = largest_adapter(adapters)?;
+    if best.total_mb == 0 {
+        warn!("DXGI reported no dedicated VRAM for {}", best.name);
+        return None;
+    }
+    info!(
+        "Detected {} via DXGI: {}MB total, {}MB free",
+        best.name, best.total_mb, best.free_mb
+    );
+    Some((best.total_mb, best.free_mb))
+}
+
+/// Dedicated VRAM of one DXGI adapter
+#[cfg(any(all(target_os = "windows", feature = "gpu-info"), test))]
+#[derive(Debug, PartialEq)]
+struct DxgiAdapter {
+    name: String,
+    total_mb: u64,
+    free_mb: u64,
+}
+
+#[cfg(any(all(target_os = "windows", feature = "gpu-info"), test))]
+impl DxgiAdapter {
+    /// `budget` is the OS (budget, current usage) in bytes when DXGI 1.4
+    /// reports it; free memory is the total otherwise, and never more
+    fn new(name: String, dedicated_bytes: u64, budget: Option<(u64, u64)>) -> Self {
+        const MB: u64 = 1024 * 1024;
+
+        let total_mb = dedicated_bytes / MB;
+        let free_mb = budget
+            .map_or(total_mb, |(budget, usage)| {
+                budget.saturating_sub(usage) / MB
+            })
+            .min(total_mb);
+        Self {
+            name,
+            total_mb,
+            free_mb,
+        }
     }
 }
 
+/// Adapter with the most dedicated VRAM, the first one listed on a tie
+#[cfg(any(all(target_os = "windows", feature = "gpu-info"), test))]
+fn largest_adapter(adapters: impl IntoIterator<Item = DxgiAdapter>) -> Option<DxgiAdapter> {
+    adapters.into_iter().reduce(|best, adapter| {
+        if adapter.total_mb > best.total_mb {
+            adapter
+        } else {
+            best
+        }
+    })
+}
+
 /// Get macOS unified memory information (GPU shares system RAM)
 ///
 /// Apple Silicon uses Unified Memory Architecture - GPU and CPU share the same physical RAM.
@@ -255,6 +537,58 @@ fn get_nvidia_vram_mb() -> Option<(u64, u64)> {
     Some((total, free))
 }
 
+/// Get AMD GPU VRAM via rocm-smi (Linux)
+///
+/// With several cards, the one with the most VRAM is reported.
+#[cfg(target_os = "linux")]
+fn get_rocm_vram_mb() -> Option<(u64, u64)> {
+    let output = std::process::Command::new("rocm-smi")
+        .args(["--showmeminfo", "vram", "--json"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        warn!("rocm-smi command failed with status: {:?}", output.status);
+        return None;
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let Some((total, free)) = parse_rocm_smi_vram(&stdout) else {
+        warn!("rocm-smi output format unexpected: '{}'", stdout.trim());
+        return None;
+    };
+    info!("Detected AMD GPU: {}MB total, {}MB free", total, free);
+    Some((total, free))
+}
+
+/// Parse `rocm-smi --showmeminfo vram --json` into (total_mb, free_mb) of
+/// the card with the most VRAM
+///
+/// The output maps each card to byte counts given as strings, e.g.
+/// `{"card0": {"VRAM Total Memory (B)": "17163091968",
+/// "VRAM Total Used Memory (B)": "1073741824"}}`. Some versions print
+/// warnings before the JSON.
+#[cfg(any(target_os = "linux", test))]
+fn parse_rocm_smi_vram(output: &str) -> Option<(u64, u64)> {
+    const MB: u64 = 1024 * 1024;
+
+    let json: serde_json::Value = serde_json::from_str(&output[output.find('{')?..]).ok()?;
+    let bytes = |card: &serde_json::Value, key: &str| -> Option<u64> {
+        match card.get(key)? {
+            serde_json::Value::String(s) => s.trim().parse().ok(),
+            value => value.as_u64(),
+        }
+    };
+    json.as_object()?
+        .values()
+        .filter_map(|card| {
+            let total = bytes(card, "VRAM Total Memory (B)")? / MB;
+            let used = bytes(card, "VRAM Total Used Memory (B)")? / MB;
+            Some((total, total.saturating_sub(used)))
+        })
+        .filter(|(total, _)| *total > 0)
+        .max_by_key(|(total, _)| *total)
+}
+
 /// Check if running on Apple Silicon (ARM64)
 ///
 /// Returns true if the current CPU architecture is aarch64 (Apple Silicon M1/M2/M3/M4)
@@ -313,6 +647,76 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_benchmark_candidates() {
+        assert_eq!(
+            benchmark_candidates(Some("cuda"), &["GPU", "NPU"]),
+            vec![
+                Some("cuda".to_string()),
+                Some("openvino:GPU".to_string()),
+                Some("openvino:NPU".to_string()),
+                None,
+            ]
+        );
+        // A detected OpenVINO device is benchmarked once, with the others
+        assert_eq!(
+            benchmark_candidates(Some("openvino:GPU"), &["GPU"]),
+            vec![Some("openvino:GPU".to_string()), None]
+        );
+        assert_eq!(benchmark_candidates(None, &[]), vec![None]);
+    }
+
+    #[test]
+    fn test_parse_rocm_smi_vram() {
+        let output = r#"WARNING: AMD GPU device(s) is/are in a low-power state. Check power control/runtime_status
+
+{"card0": {"VRAM Total Memory (B)": "536870912", "VRAM Total Used Memory (B)": "268435456"}, "card1": {"VRAM Total Memory (B)": "17163091968", "VRAM Total Used Memory (B)": "1073741824"}}"#;
+        assert_eq!(parse_rocm_smi_vram(output), Some((16368, 15344)));
+
+        assert_eq!(parse_rocm_smi_vram(""), None);
+        assert_eq!(parse_rocm_smi_vram("{}"), None);
+        assert_eq!(
+            parse_rocm_smi_vram(r#"{"card0": {"VRAM Total Memory (B)": "N/A"}}"#),
+            None
+        );
+    }
+
+    #[test]
+    fn test_dxgi_adapter_budget() {
+        const MB: u64 = 1024 * 1024;
+
+        let adapter = DxgiAdapter::new("GPU".to_string(), 8192 * MB, Some((7000 * MB, 1000 * MB)));
+        assert_eq!((adapter.total_mb, adapter.free_mb), (8192, 6000));
+
+        // No DXGI 1.4 budget: report the total as free
+        let adapter = DxgiAdapter::new("GPU".to_string(), 8192 * MB, None);
+        assert_eq!(adapter.free_mb, 8192);
+
+        // Budget can exceed dedicated memory on integrated GPUs
+        let adapter = DxgiAdapter::new("iGPU".to_string(), 128 * MB, Some((4096 * MB, 0)));
+        assert_eq!(adapter.free_mb, 128);
+
+        // Usage over budget saturates instead of wrapping
+        let adapter = DxgiAdapter::new("GPU".to_string(), 8192 * MB, Some((1000 * MB, 2000 * MB)));
+        assert_eq!(adapter.free_mb, 0);
+    }
+
+    #[test]
+    fn test_largest_dxgi_adapter() {
+        const MB: u64 = 1024 * 1024;
+        let adapter =
+            |name: &str, total_mb: u64| DxgiAdapter::new(name.to_string(), total_mb * MB, None);
+
+        let best = largest_adapter([
+            adapter("Intel UHD", 128),
+            adapter("Radeon RX 7800", 16384),
+            adapter("Second RX 7800", 16384),
+        ]);
+        assert_eq!(best.map(|a| a.name), Some("Radeon RX 7800".to_string()));
+
+        assert_eq!(largest_adapter([]), None);
+    }
+
     #[test]
     fn test_vram_thresholds() {
         // Verify our threshold logic matches memory requirements from task
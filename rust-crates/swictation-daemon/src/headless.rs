@@ -0,0 +1,226 @@
+//! Headless transcription (`--headless`)
+//!
+//! Instead of listening for hotkeys and typing into the focused window, the
+//! daemon reads raw PCM from stdin or a named pipe and writes one transcript
+//! per line to stdout (transcriptions still reach the broadcaster), so other
+//! programs can use it as a local speech-to-text service:
+//!
+//! ```text
+//! arecord -f S16_LE -r 16000 -c 1 | swictation-daemon --headless
+//! ```
+//!
+//! Input must be 16 kHz mono signed 16-bit little-endian PCM. Each writer is
+//! one session: stdin ends the daemon at end of input, a FIFO is reopened
+//! for the next writer.
+
+use anyhow::{Context, Result};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{error, info};
+
+use crate::hooks::HookEvent;
+use crate::Daemon;
+
+/// Samples per chunk handed to the VAD (0.5 s, like the capture callback)
+const CHUNK_SAMPLES: usize = 8000;
+
+/// Where headless PCM comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum HeadlessInput {
+    Stdin,
+    Fifo(PathBuf),
+}
+
+impl FromStr for HeadlessInput {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stdin" | "-" => Ok(Self::Stdin),
+            _ => match s.strip_prefix("fifo:") {
+                Some(path) if !path.is_empty() => Ok(Self::Fifo(PathBuf::from(path))),
+                _ => Err(format!("expected `stdin` or `fifo:<path>`, got '{}'", s)),
+            },
+        }
+    }
+}
+
+impl HeadlessInput {
+    /// Open the input (blocks until a writer connects to a FIFO)
+    fn open(&self) -> Result<PcmReader> {
+        let reader: Box<dyn Read + Send> = match self {
+            Self::Stdin => Box::new(io::stdin()),
+            Self::Fifo(path) => Box::new(
+                File::open(path).with_context(|| format!("Failed to open {}", path.display()))?,
+            ),
+        };
+        Ok(PcmReader::new(reader))
+    }
+}
+
+/// Decodes 16 kHz mono s16le PCM into VAD-sized chunks
+pub struct PcmReader {
+    reader: Box<dyn Read + Send>,
+    /// Odd byte left over from the previous read
+    pending: Option<u8>,
+}
+
+impl PcmReader {
+    pub fn new(reader: Box<dyn Read + Send>) -> Self {
+        Self {
+            reader,
+            pending: None,
+        }
+    }
+
+    /// Next chunk of up to `CHUNK_SAMPLES` samples, `None` at end of input
+    ///
+    /// Waits for a full chunk unless the input ends first; a trailing odd
+    /// byte is dropped.
+    pub fn next_chunk(&mut self) -> io::Result<Option<Vec<f32>>> {
+        let mut bytes = Vec::with_capacity(CHUNK_SAMPLES * 2);
+        bytes.extend(self.pending.take());
+        let mut buf = [0u8; CHUNK_SAMPLES * 2];
+        while bytes.len() < CHUNK_SAMPLES * 2 {
+            let want = CHUNK_SAMPLES * 2 - bytes.len();
+            match self.reader.read(&mut buf[..want]) {
+                Ok(0) => break,
+                Ok(n) => bytes.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        if bytes.len() % 2 == 1 {
+            self.pending = bytes.pop();
+        }
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(
+            bytes
+                .chunks_exact(2)
+                .map(|pair| i16::from_le_bytes([pair[0], pair[1]]) as f32 / 32768.0)
+                .collect(),
+        ))
+    }
+}
+
+/// Transcribe sessions from `input` until it is exhausted or Ctrl-C
+pub async fn run(
+    daemon: Arc<Daemon>,
+    mut transcription_rx: mpsc::Receiver<Result<String>>,
+    input: HeadlessInput,
+) -> Result<()> {
+    loop {
+        // A plain thread rather than spawn_blocking: the runtime would wait
+        // on shutdown for an open that no writer ever completes
+        let (open_tx, open_rx) = oneshot::channel();
+        let source = input.clone();
+        std::thread::spawn(move || open_tx.send(source.open()));
+        let reader = tokio::select! {
+            reader = open_rx => reader.context("Input opener exited")??,
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        };
+
+        let (sid, drained) = daemon.start(Some(reader)).await?;
+        info!("🎧 Transcribing input (Session #{})", sid);
+        let mut drained = drained.context("Input session did not start")?;
+
+        let interrupted = loop {
+            tokio::select! {
+                Some(result) = transcription_rx.recv() => emit(&daemon, result).await,
+                _ = &mut drained => break false,
+                _ = tokio::signal::ctrl_c() => break true,
+            }
+        };
+
+        // Transcribe the tail the VAD is still holding, then print it too
+        let summary = daemon.stop().await?;
+        info!("🎧 {}", summary);
+        while let Ok(result) = transcription_rx.try_recv() {
+            emit(&daemon, result).await;
+        }
+
+        if interrupted || input == HeadlessInput::Stdin {
+            return Ok(());
+        }
+    }
+}
+
+/// Print a transcription result as a line on stdout
+async fn emit(daemon: &Daemon, result: Result<String>) {
+    let text = match result {
+        Ok(text) => text,
+        Err(e) => {
+            error!("Transcription error: {}", e);
+            return;
+        }
+    };
+    // Keystroke-only results (edit commands, paragraph breaks) have no text
+    let text = text.trim();
+    if text.is_empty() || text.contains("<KEY:") {
+        return;
+    }
+
+    daemon.history.lock().unwrap().push(text);
+    let sid = *daemon.session_id.read().await;
+    daemon.hooks.fire(
+        HookEvent::Transcription,
+        serde_json::json!({ "session_id": sid, "text": text }),
+    );
+
+    let mut stdout = io::stdout().lock();
+    if let Err(e) = writeln!(stdout, "{}", text).and_then(|_| stdout.flush()) {
+        error!("Failed to write transcript to stdout: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hands out its data a few bytes at a time, like a pipe
+    struct Trickle(Vec<u8>);
+
+    impl Read for Trickle {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.0.len()).min(3);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0.drain(..n);
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_parse_input() {
+        assert_eq!("stdin".parse(), Ok(HeadlessInput::Stdin));
+        assert_eq!(
+            "fifo:/tmp/swictation.pcm".parse(),
+            Ok(HeadlessInput::Fifo(PathBuf::from("/tmp/swictation.pcm")))
+        );
+        assert!("fifo:".parse::<HeadlessInput>().is_err());
+        assert!("/tmp/swictation.pcm".parse::<HeadlessInput>().is_err());
+    }
+
+    #[test]
+    fn test_pcm_chunks() {
+        let samples: Vec<i16> = (0..CHUNK_SAMPLES as i16 + 2).collect();
+        let mut bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+        bytes.extend(i16::MIN.to_le_bytes());
+        bytes.push(0x7f); // incomplete trailing sample
+
+        let mut reader = PcmReader::new(Box::new(Trickle(bytes)));
+        let first = reader.next_chunk().unwrap().unwrap();
+        assert_eq!(first.len(), CHUNK_SAMPLES);
+        assert_eq!(first[1], 1.0 / 32768.0);
+
+        let rest = reader.next_chunk().unwrap().unwrap();
+        assert_eq!(rest.len(), 3);
+        assert_eq!(rest[2], -1.0);
+        assert!(reader.next_chunk().unwrap().is_none());
+    }
+}
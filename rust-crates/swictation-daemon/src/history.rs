@@ -0,0 +1,131 @@
+//! Recently typed transcriptions, kept in memory only
+//!
+//! Text that went to the wrong window can be copied back from here even with
+//! transcription storage disabled. Entries expire after a TTL and are never
+//! written to disk.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Entry as reported over IPC
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HistoryEntry {
+    /// 1 = most recent, as used by `history_copy`
+    pub n: usize,
+    pub text: String,
+    /// Seconds since the text was typed
+    pub age_secs: u64,
+}
+
+#[derive(Debug)]
+pub struct TranscriptHistory {
+    /// Oldest first
+    entries: VecDeque<(Instant, String)>,
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl TranscriptHistory {
+    /// History of up to `capacity` entries (0 disables it)
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+            ttl,
+        }
+    }
+
+    /// Remember typed text; blank text and keystroke markers are skipped
+    pub fn push(&mut self, text: &str) {
+        let text = text.trim();
+        if self.capacity == 0 || text.is_empty() || text.contains("<KEY:") {
+            return;
+        }
+        self.expire();
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((Instant::now(), text.to_string()));
+    }
+
+    /// Unexpired entries, most recent first
+    pub fn list(&mut self) -> Vec<HistoryEntry> {
+        self.expire();
+        self.entries
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, (at, text))| HistoryEntry {
+                n: i + 1,
+                text: text.clone(),
+                age_secs: at.elapsed().as_secs(),
+            })
+            .collect()
+    }
+
+    /// Text of entry `n` (1 = most recent)
+    pub fn get(&mut self, n: usize) -> Option<String> {
+        self.expire();
+        let index = self.entries.len().checked_sub(n)?;
+        (n > 0).then(|| self.entries[index].1.clone())
+    }
+
+    /// Drop entries pushed at or after `start`, e.g. a private session's
+    pub fn forget_since(&mut self, start: Instant) {
+        while self.entries.back().is_some_and(|(at, _)| *at >= start) {
+            self.entries.pop_back();
+        }
+    }
+
+    fn expire(&mut self) {
+        while let Some((at, _)) = self.entries.front() {
+            if at.elapsed() < self.ttl {
+                break;
+            }
+            self.entries.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_newest_first_and_bounded() {
+        let mut history = TranscriptHistory::new(2, Duration::from_secs(60));
+        for text in ["one ", "<KEY:Return>", "two ", "three "] {
+            history.push(text);
+        }
+
+        let texts: Vec<String> = history.list().into_iter().map(|e| e.text).collect();
+        assert_eq!(texts, vec!["three", "two"]);
+        assert_eq!(history.get(2).as_deref(), Some("two"));
+        assert_eq!(history.get(0), None);
+        assert_eq!(history.get(3), None);
+    }
+
+    #[test]
+    fn test_entries_expire() {
+        let mut history = TranscriptHistory::new(5, Duration::from_millis(10));
+        history.push("secret");
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(history.list().is_empty());
+        assert_eq!(history.get(1), None);
+    }
+
+    #[test]
+    fn test_forget_since() {
+        let mut history = TranscriptHistory::new(5, Duration::from_secs(60));
+        history.push("kept");
+        std::thread::sleep(Duration::from_millis(1));
+        let start = Instant::now();
+        history.push("private one");
+        history.push("private two");
+        history.forget_since(start);
+
+        let texts: Vec<String> = history.list().into_iter().map(|e| e.text).collect();
+        assert_eq!(texts, vec!["kept"]);
+    }
+}
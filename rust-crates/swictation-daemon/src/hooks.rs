@@ -0,0 +1,236 @@
+//! User scripts run on daemon events
+//!
+//! Lets note-taking tools, OBS scene switches or habit trackers react to
+//! dictation without writing a socket client. Each event is written to the
+//! hook's stdin as one JSON object; stdout is discarded and stderr is logged
+//! when the hook fails. Hooks run in the background and never hold up
+//! dictation.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::{debug, warn};
+
+use crate::config::HooksConfig;
+
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HookEvent {
+    SessionStart,
+    SessionEnd,
+    Transcription,
+}
+
+impl HookEvent {
+    /// Name sent in the `event` field
+    pub fn name(self) -> &'static str {
+        match self {
+            HookEvent::SessionStart => "session_start",
+            HookEvent::SessionEnd => "session_end",
+            HookEvent::Transcription => "transcription",
+        }
+    }
+}
+
+pub struct HookRunner {
+    config: HooksConfig,
+    /// Recent run times per event, oldest first
+    recent: Mutex<HashMap<HookEvent, VecDeque<Instant>>>,
+}
+
+impl HookRunner {
+    pub fn new(config: HooksConfig) -> Self {
+        Self {
+            config,
+            recent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn hooks(&self, event: HookEvent) -> &[PathBuf] {
+        match event {
+            HookEvent::SessionStart => &self.config.session_start,
+            HookEvent::SessionEnd => &self.config.session_end,
+            HookEvent::Transcription => &self.config.transcription,
+        }
+    }
+
+    /// Run the hooks for `event` in the background
+    ///
+    /// `fields` (a JSON object) are sent along with `event` and `timestamp`.
+    /// Must be called from within the tokio runtime.
+    pub fn fire(&self, event: HookEvent, fields: Value) {
+        let Some(input) = self.input(event, fields) else {
+            return;
+        };
+        let timeout = Duration::from_secs(self.config.timeout_secs);
+
+        for hook in self.hooks(event) {
+            let hook = hook.clone();
+            let input = input.clone();
+            tokio::spawn(async move {
+                if let Err(e) = run_hook(&hook, input.as_bytes(), timeout).await {
+                    warn!(
+                        "⚠️ {} hook {} failed: {:#}",
+                        event.name(),
+                        hook.display(),
+                        e
+                    );
+                }
+            });
+        }
+    }
+
+    /// Run the hooks for `event` side by side and wait for all of them
+    ///
+    /// Fails with the first hook that failed. Returns `Ok(false)` when no
+    /// hook ran (none configured, or over the rate limit).
+    pub async fn run(&self, event: HookEvent, fields: Value) -> Result<bool> {
+        let Some(input) = self.input(event, fields) else {
+            return Ok(false);
+        };
+        let timeout = Duration::from_secs(self.config.timeout_secs);
+
+        let mut runs = tokio::task::JoinSet::new();
+        for hook in self.hooks(event) {
+            let hook = hook.clone();
+            let input = input.clone();
+            runs.spawn(async move {
+                run_hook(&hook, input.as_bytes(), timeout)
+                    .await
+                    .with_context(|| format!("{} hook {}", event.name(), hook.display()))
+            });
+        }
+        let mut result = Ok(true);
+        while let Some(run) = runs.join_next().await {
+            let run = run.context("Hook task panicked").and_then(|r| r);
+            if result.is_ok() {
+                result = run.map(|()| true);
+            }
+        }
+        result
+    }
+
+    /// JSON line sent to the hooks, unless there are none to run
+    fn input(&self, event: HookEvent, fields: Value) -> Option<String> {
+        if self.hooks(event).is_empty() || !self.allow(event, Instant::now()) {
+            return None;
+        }
+
+        let mut payload = json!({
+            "event": event.name(),
+            "timestamp": Utc::now().to_rfc3339(),
+        });
+        if let (Some(payload), Value::Object(fields)) = (payload.as_object_mut(), fields) {
+            payload.extend(fields);
+        }
+        Some(format!("{}\n", payload))
+    }
+
+    /// Record a run of `event` unless it is over the per-minute limit
+    fn allow(&self, event: HookEvent, now: Instant) -> bool {
+        let mut recent = self.recent.lock().unwrap();
+        let runs = recent.entry(event).or_default();
+        while runs
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= RATE_WINDOW)
+        {
+            runs.pop_front();
+        }
+        if runs.len() >= self.config.max_per_minute as usize {
+            debug!("Skipping {} hooks: rate limit reached", event.name());
+            return false;
+        }
+        runs.push_back(now);
+        true
+    }
+}
+
+/// Run one hook to completion, killing it after `timeout`
+async fn run_hook(path: &Path, input: &[u8], timeout: Duration) -> Result<()> {
+    let mut child = Command::new(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .context("Failed to start hook")?;
+
+    let mut stdin = child.stdin.take().context("Hook stdin unavailable")?;
+    let run = async move {
+        // A hook that ignores its input may exit before reading it
+        let _ = stdin.write_all(input).await;
+        drop(stdin);
+        child.wait_with_output().await
+    };
+    let output = tokio::time::timeout(timeout, run)
+        .await
+        .map_err(|_| anyhow::anyhow!("timed out after {}s", timeout.as_secs_f64()))?
+        .context("Failed to wait for hook")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "{}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limit_per_event() {
+        let runner = HookRunner::new(HooksConfig {
+            max_per_minute: 2,
+            ..Default::default()
+        });
+        let now = Instant::now();
+
+        assert!(runner.allow(HookEvent::Transcription, now));
+        assert!(runner.allow(HookEvent::Transcription, now));
+        assert!(!runner.allow(HookEvent::Transcription, now));
+        assert!(runner.allow(HookEvent::SessionEnd, now));
+        assert!(runner.allow(HookEvent::Transcription, now + RATE_WINDOW));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_hook_exit_status_and_timeout() {
+        let timeout = Duration::from_millis(500);
+        let input = b"{\"event\":\"transcription\"}\n";
+
+        assert!(run_hook(Path::new("true"), input, timeout).await.is_ok());
+        assert!(run_hook(Path::new("false"), input, timeout).await.is_err());
+        // Never exits on its own
+        let err = run_hook(Path::new("yes"), input, timeout)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_waits_for_every_hook() {
+        let runner = HookRunner::new(HooksConfig {
+            transcription: vec![PathBuf::from("true"), PathBuf::from("false")],
+            ..Default::default()
+        });
+        let err = runner
+            .run(HookEvent::Transcription, json!({ "text": "hi" }))
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("transcription hook false"));
+        assert!(!runner.run(HookEvent::SessionEnd, json!({})).await.unwrap());
+    }
+}
@@ -14,9 +14,13 @@ use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
 
 use crate::config::HotkeyConfig;
+#[cfg(feature = "sway-integration")]
+use crate::desktop_shortcuts::SwayShortcut;
+use crate::desktop_shortcuts::{self, GnomeShortcut, InstalledShortcuts};
 use crate::display_server::{
     detect_display_server as detect_display_server_base, DisplayServer as BaseDisplayServer,
 };
+use crate::hotkey_conflicts::{self, HotkeyConflict};
 
 /// Hotkey events
 #[derive(Debug, Clone)]
@@ -27,6 +31,8 @@ pub enum HotkeyEvent {
     PushToTalkPressed,
     /// Push-to-talk released
     PushToTalkReleased,
+    /// Toggle private mode
+    PrivateModeToggle,
 }
 
 /// Hotkey-specific display server types (extends base detection with Sway)
@@ -98,6 +104,7 @@ enum HotkeyBackend {
         manager: GlobalHotKeyManager,
         toggle_hotkey: HotKey,
         ptt_hotkey: HotKey,
+        private_hotkey: Option<HotKey>,
         rx: mpsc::UnboundedReceiver<HotkeyEvent>,
     },
     /// Sway compositor (requires manual config)
@@ -109,20 +116,27 @@ enum HotkeyBackend {
 
 impl HotkeyManager {
     /// Create new hotkey manager with configured hotkeys
-    /// Returns None if hotkeys are not available on this system
-    pub fn new(config: HotkeyConfig) -> Result<Option<Self>> {
+    /// Returns None if hotkeys are not available on this system, along with
+    /// the configured hotkeys that are probably taken
+    pub fn new(config: HotkeyConfig) -> Result<(Option<Self>, Vec<HotkeyConflict>)> {
         let display_server = detect_hotkey_server();
         info!("Detected display server: {:?}", display_server);
 
-        match display_server {
+        let desktop = detect_display_server_base().desktop_environment;
+        let mut conflicts = hotkey_conflicts::check(&config, desktop.as_deref());
+        for conflict in &conflicts {
+            warn!("⚠️  Hotkey conflict: {}", conflict);
+        }
+
+        let manager = match display_server {
             HotkeyDisplayServer::X11 => {
                 info!("Using X11 hotkey backend (direct key grabbing)");
-                Self::new_global_hotkey(config)
+                Self::new_global_hotkey(config, desktop.as_deref(), &mut conflicts)
             }
             HotkeyDisplayServer::MacOS => {
                 info!("Using macOS hotkey backend (CGEvent/NSEvent)");
                 info!("Note: Accessibility permission may be required in System Settings");
-                Self::new_global_hotkey(config)
+                Self::new_global_hotkey(config, desktop.as_deref(), &mut conflicts)
             }
             HotkeyDisplayServer::Sway => {
                 info!("Using Sway IPC backend (requires manual config)");
@@ -176,11 +190,19 @@ impl HotkeyManager {
                 warn!("Hotkeys disabled - use IPC/CLI for control");
                 Ok(None)
             }
-        }
+        }?;
+        Ok((manager, conflicts))
     }
 
     /// Create X11/Windows/macOS backend using global-hotkey
-    fn new_global_hotkey(config: HotkeyConfig) -> Result<Option<Self>> {
+    ///
+    /// A hotkey the system refuses to register is explained in `conflicts`
+    /// and leaves hotkeys disabled rather than failing the daemon.
+    fn new_global_hotkey(
+        config: HotkeyConfig,
+        desktop: Option<&str>,
+        conflicts: &mut Vec<HotkeyConflict>,
+    ) -> Result<Option<Self>> {
         // Try to create hotkey manager
         let manager = match GlobalHotKeyManager::new() {
             Ok(m) => m,
@@ -198,20 +220,42 @@ impl HotkeyManager {
             }
         };
 
-        // Parse and register toggle hotkey
+        // Parse all hotkeys before registering any
         let toggle_hotkey = parse_hotkey(&config.toggle).context("Invalid toggle hotkey")?;
-        let toggle_hotkey_clone = toggle_hotkey;
-        manager
-            .register(toggle_hotkey)
-            .context("Failed to register toggle hotkey")?;
-
-        // Parse and register push-to-talk hotkey
         let ptt_hotkey =
             parse_hotkey(&config.push_to_talk).context("Invalid push-to-talk hotkey")?;
+        let private_hotkey = config
+            .private_mode
+            .as_deref()
+            .map(parse_hotkey)
+            .transpose()
+            .context("Invalid private mode hotkey")?;
+
+        let mut hotkeys = vec![
+            ("toggle", config.toggle.as_str(), toggle_hotkey),
+            ("push_to_talk", config.push_to_talk.as_str(), ptt_hotkey),
+        ];
+        if let (Some(text), Some(hotkey)) = (config.private_mode.as_deref(), private_hotkey) {
+            hotkeys.push(("private_mode", text, hotkey));
+        }
+
+        for (i, (role, text, hotkey)) in hotkeys.iter().enumerate() {
+            if let Err(e) = manager.register(*hotkey) {
+                let conflict = hotkey_conflicts::registration_failed(&config, role, text, desktop);
+                warn!("Failed to register {} hotkey {}: {}", role, text, e);
+                warn!("⚠️  {}", conflict);
+                conflicts.retain(|known| known.role != conflict.role);
+                conflicts.push(conflict);
+
+                for (_, _, registered) in &hotkeys[..i] {
+                    let _ = manager.unregister(*registered);
+                }
+                warn!("Hotkeys disabled - change [hotkeys] in the config or use IPC/CLI");
+                return Ok(None);
+            }
+        }
+        let toggle_hotkey_clone = toggle_hotkey;
         let ptt_hotkey_clone = ptt_hotkey;
-        manager
-            .register(ptt_hotkey)
-            .context("Failed to register push-to-talk hotkey")?;
 
         // Create event channel
         let (tx, rx) = mpsc::unbounded_channel();
@@ -219,6 +263,7 @@ impl HotkeyManager {
         // Spawn hotkey event listener thread
         let toggle_id = toggle_hotkey_clone.id();
         let ptt_id = ptt_hotkey_clone.id();
+        let private_id = private_hotkey.map(|hotkey| hotkey.id());
         std::thread::spawn(move || loop {
             if let Ok(event) = GlobalHotKeyEvent::receiver().recv() {
                 let hotkey_event = if event.id == toggle_id && event.state == HotKeyState::Pressed {
@@ -227,6 +272,8 @@ impl HotkeyManager {
                     Some(HotkeyEvent::PushToTalkPressed)
                 } else if event.id == ptt_id && event.state == HotKeyState::Released {
                     Some(HotkeyEvent::PushToTalkReleased)
+                } else if Some(event.id) == private_id && event.state == HotKeyState::Pressed {
+                    Some(HotkeyEvent::PrivateModeToggle)
                 } else {
                     None
                 };
@@ -244,6 +291,7 @@ impl HotkeyManager {
                 manager,
                 toggle_hotkey: toggle_hotkey_clone,
                 ptt_hotkey: ptt_hotkey_clone,
+                private_hotkey,
                 rx,
             },
         }))
@@ -300,16 +348,20 @@ impl HotkeyManager {
         }
     }
 
-    /// Check if Sway config has our hotkeys, add them if not, and reload Sway
+    /// Add our hotkeys to the Sway config, replacing a stale binding, and reload Sway
     #[cfg(feature = "sway-integration")]
     fn configure_sway_hotkeys(config: &HotkeyConfig) -> Result<()> {
-        let home = std::env::var("HOME").context("HOME environment variable not set")?;
-        let sway_config_dir = format!("{}/.config/sway", home);
-        let sway_config_path = format!("{}/config", sway_config_dir);
+        let sway_config_path = desktop_shortcuts::sway_config_path()?;
+        let sway_config_dir = sway_config_path
+            .parent()
+            .context("Sway config path has no parent directory")?;
 
         // Ensure config directory exists
-        if !std::path::Path::new(&sway_config_dir).exists() {
-            warn!("Sway config directory does not exist: {}", sway_config_dir);
+        if !sway_config_dir.exists() {
+            warn!(
+                "Sway config directory does not exist: {}",
+                sway_config_dir.display()
+            );
             return Err(anyhow::anyhow!("Sway config directory not found"));
         }
 
@@ -317,75 +369,44 @@ impl HotkeyManager {
         let config_content = std::fs::read_to_string(&sway_config_path)
             .context("Failed to read Sway config - file may not exist")?;
 
-        // Check if our hotkeys already exist
-        if config_content.contains("# Swictation voice-to-text hotkeys") {
+        // Parse the configured hotkeys to Sway format
+        let toggle_key = config.toggle.replace("Super", "$mod");
+
+        // Replace any existing Swictation block (old formats or a stale binding)
+        let cleaned_content = desktop_shortcuts::strip_sway_block(&config_content);
+        let updated_content = format!(
+            "{}{}",
+            cleaned_content,
+            desktop_shortcuts::sway_block(&toggle_key)
+        );
+        let record = |installed: &mut InstalledShortcuts| {
+            installed.sway = Some(SwayShortcut {
+                config_path: sway_config_path.clone(),
+                binding: toggle_key.clone(),
+            });
+        };
+
+        if updated_content == config_content {
             info!("✓ Swictation hotkeys already configured in Sway");
+            InstalledShortcuts::record(record);
             return Ok(());
         }
 
-        // Parse the configured hotkeys to Sway format
-        let toggle_key = config.toggle.replace("Super", "$mod");
-        let ptt_key = config.push_to_talk.replace("Super", "$mod");
-
         // Check for potential conflicts
-        if config_content.contains(&format!("bindsym {}", toggle_key)) {
+        if cleaned_content.contains(&format!("bindsym {}", toggle_key)) {
             warn!("Hotkey {} may conflict with existing binding", toggle_key);
         }
-        if config_content.contains(&format!("bindsym {}", ptt_key)) {
-            warn!("Hotkey {} may conflict with existing binding", ptt_key);
-        }
 
         info!("Adding Swictation hotkeys to Sway config...");
 
         // Create backup
-        let backup_path = format!("{}.swictation.backup", sway_config_path);
+        let backup_path = format!("{}.swictation.backup", sway_config_path.display());
         std::fs::copy(&sway_config_path, &backup_path).context("Failed to create config backup")?;
         debug!("Created backup at: {}", backup_path);
 
-        // Remove any existing Swictation blocks (clean up old configs)
-        let mut cleaned_content = String::new();
-        let mut in_swictation_block = false;
-
-        for line in config_content.lines() {
-            // Detect start of Swictation block (both old and new formats)
-            if line.contains("# Swictation") || line.contains("#Swictation") {
-                in_swictation_block = true;
-                continue;
-            }
-
-            // Skip lines in Swictation block
-            if in_swictation_block {
-                // End block when we hit a non-swictation line (empty line or different section)
-                if line.trim().is_empty()
-                    || (!line.trim_start().starts_with("bindsym")
-                        && !line.trim_start().starts_with('#'))
-                {
-                    in_swictation_block = false;
-                    cleaned_content.push_str(line);
-                    cleaned_content.push('\n');
-                }
-                // Skip all swictation-related bindings
-                continue;
-            }
-
-            cleaned_content.push_str(line);
-            cleaned_content.push('\n');
-        }
-
-        // Append new simplified hotkey config
-        let hotkey_config = format!(
-            r#"
-# Swictation
-bindsym {} exec swictation toggle
-"#,
-            toggle_key
-        );
-
-        std::fs::write(
-            &sway_config_path,
-            format!("{}{}", cleaned_content, hotkey_config),
-        )
-        .context("Failed to write Sway config")?;
+        std::fs::write(&sway_config_path, updated_content)
+            .context("Failed to write Sway config")?;
+        InstalledShortcuts::record(record);
 
         info!("✓ Hotkeys added to Sway config");
         info!("Reloading Sway...");
@@ -403,8 +424,10 @@ bindsym {} exec swictation toggle
         Ok(())
     }
 
-    /// Configure GNOME keyboard shortcuts via gsettings
+    /// Configure GNOME keyboard shortcuts via gsettings, updating a stale
+    /// binding or command in place
     fn configure_gnome_hotkeys(config: &HotkeyConfig) -> Result<()> {
+        use desktop_shortcuts::{gsettings, GNOME_KEYBINDING_PATH, GNOME_MEDIA_KEYS};
         use std::process::Command;
 
         // Check if gsettings is available
@@ -421,45 +444,10 @@ bindsym {} exec swictation toggle
 
         info!("Configuring GNOME keyboard shortcuts via gsettings...");
 
-        // Define the custom keybinding path
-        let custom_path =
-            "/org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/swictation-toggle/";
-
         // Get current custom keybindings list
-        let current_bindings_output = Command::new("gsettings")
-            .args([
-                "get",
-                "org.gnome.settings-daemon.plugins.media-keys",
-                "custom-keybindings",
-            ])
-            .output()
+        let current_bindings = gsettings(&["get", GNOME_MEDIA_KEYS, "custom-keybindings"])
             .context("Failed to get current custom keybindings")?;
 
-        let current_bindings = String::from_utf8_lossy(&current_bindings_output.stdout);
-        let current_bindings = current_bindings.trim();
-
-        // Check if our binding already exists
-        if current_bindings.contains("swictation-toggle") {
-            info!("✓ Swictation shortcuts already configured in GNOME");
-            return Ok(());
-        }
-
-        // Parse existing bindings and add ours
-        let new_bindings = if current_bindings == "@as []" || current_bindings.is_empty() {
-            // No existing bindings
-            format!("['{}']", custom_path)
-        } else {
-            // Add to existing bindings - remove the closing bracket and append
-            let trimmed = current_bindings
-                .trim_start_matches('[')
-                .trim_end_matches(']');
-            if trimmed.is_empty() {
-                format!("['{}']", custom_path)
-            } else {
-                format!("[{}, '{}']", trimmed, custom_path)
-            }
-        };
-
         // Convert hotkey format from our config to GNOME format
         // Our format: "Super+Shift+D" -> GNOME format: "<Super><Shift>d"
         let toggle_binding = convert_to_gnome_binding(&config.toggle)?;
@@ -477,70 +465,35 @@ bindsym {} exec swictation toggle
             socket_path
         );
 
-        // Set the custom keybinding properties
-        info!("Setting custom keybinding at: {}", custom_path);
-
-        let schema_path = format!(
-            "org.gnome.settings-daemon.plugins.media-keys.custom-keybinding:{}",
-            custom_path
-        );
-
-        // Set name
-        let name_result = Command::new("gsettings")
-            .args(["set", &schema_path, "name", "Swictation Toggle"])
-            .output()
-            .context("Failed to set keybinding name")?;
-
-        if !name_result.status.success() {
-            return Err(anyhow::anyhow!(
-                "Failed to set keybinding name: {}",
-                String::from_utf8_lossy(&name_result.stderr)
-            ));
+        // Nothing to do when our binding is listed and matches what we recorded
+        let shortcut = GnomeShortcut {
+            binding: toggle_binding.clone(),
+            command: command.clone(),
+        };
+        let listed = current_bindings.contains(GNOME_KEYBINDING_PATH);
+        if listed && InstalledShortcuts::load().gnome.as_ref() == Some(&shortcut) {
+            info!("✓ Swictation shortcuts already configured in GNOME");
+            return Ok(());
         }
 
-        // Set command
-        let cmd_result = Command::new("gsettings")
-            .args(["set", &schema_path, "command", &command])
-            .output()
+        // Set the custom keybinding properties (overwriting a stale binding)
+        info!("Setting custom keybinding at: {}", GNOME_KEYBINDING_PATH);
+        let schema_path = desktop_shortcuts::gnome_keybinding_schema();
+        gsettings(&["set", &schema_path, "name", "Swictation Toggle"])
+            .context("Failed to set keybinding name")?;
+        gsettings(&["set", &schema_path, "command", &command])
             .context("Failed to set keybinding command")?;
-
-        if !cmd_result.status.success() {
-            return Err(anyhow::anyhow!(
-                "Failed to set keybinding command: {}",
-                String::from_utf8_lossy(&cmd_result.stderr)
-            ));
-        }
-
-        // Set binding
-        let binding_result = Command::new("gsettings")
-            .args(["set", &schema_path, "binding", &toggle_binding])
-            .output()
+        gsettings(&["set", &schema_path, "binding", &toggle_binding])
             .context("Failed to set keybinding")?;
 
-        if !binding_result.status.success() {
-            return Err(anyhow::anyhow!(
-                "Failed to set keybinding: {}",
-                String::from_utf8_lossy(&binding_result.stderr)
-            ));
-        }
-
         // Add our binding to the list
-        let list_result = Command::new("gsettings")
-            .args([
-                "set",
-                "org.gnome.settings-daemon.plugins.media-keys",
-                "custom-keybindings",
-                &new_bindings,
-            ])
-            .output()
-            .context("Failed to update custom keybindings list")?;
-
-        if !list_result.status.success() {
-            return Err(anyhow::anyhow!(
-                "Failed to update custom keybindings list: {}",
-                String::from_utf8_lossy(&list_result.stderr)
-            ));
+        if !listed {
+            let new_bindings =
+                desktop_shortcuts::gnome_list_with(&current_bindings, GNOME_KEYBINDING_PATH);
+            gsettings(&["set", GNOME_MEDIA_KEYS, "custom-keybindings", &new_bindings])
+                .context("Failed to update custom keybindings list")?;
         }
+        InstalledShortcuts::record(|installed| installed.gnome = Some(shortcut));
 
         info!("✓ GNOME keyboard shortcut configured: {}", toggle_binding);
 
@@ -562,11 +515,15 @@ impl Drop for HotkeyManager {
             manager,
             toggle_hotkey,
             ptt_hotkey,
+            private_hotkey,
             ..
         } = &self.backend
         {
             let _ = manager.unregister(*toggle_hotkey);
             let _ = manager.unregister(*ptt_hotkey);
+            if let Some(private_hotkey) = private_hotkey {
+                let _ = manager.unregister(*private_hotkey);
+            }
         }
     }
 }
@@ -0,0 +1,287 @@
+//! Hotkey conflict detection
+//!
+//! A hotkey already grabbed by the desktop or an application either fails to
+//! register with an opaque global-hotkey error or, worse, registers and
+//! never fires because the focused application eats it first. Before
+//! registering, configured hotkeys are matched against the default
+//! shortcuts of common desktops and applications, and a failed registration
+//! is explained the same way. Each conflict names the likely owner and a few
+//! free alternatives; they are logged, listed by `doctor` and returned in
+//! the IPC `status` response.
+
+use serde::Serialize;
+
+use crate::config::HotkeyConfig;
+
+/// Default shortcuts that commonly collide: (desktop, hotkey, owner)
+///
+/// A `None` desktop is an application shortcut, active on any desktop.
+const KNOWN_SHORTCUTS: &[(Option<&str>, &str, &str)] = &[
+    (None, "Ctrl+Shift+D", "Firefox/Chrome: bookmark all tabs"),
+    (None, "Ctrl+Shift+T", "browsers: reopen closed tab"),
+    (None, "Ctrl+Shift+N", "browsers: new private window"),
+    (None, "Ctrl+Shift+P", "VS Code: command palette"),
+    (None, "Ctrl+Shift+I", "browsers: developer tools"),
+    (None, "Ctrl+Shift+C", "terminals: copy"),
+    (None, "Ctrl+Shift+V", "terminals: paste"),
+    (None, "Ctrl+Shift+F", "VS Code: search in files"),
+    (None, "Ctrl+Shift+E", "VS Code: explorer"),
+    (None, "Ctrl+Shift+R", "browsers: hard reload"),
+    (Some("GNOME"), "Super+Space", "GNOME: switch input source"),
+    (Some("GNOME"), "Super+L", "GNOME: lock screen"),
+    (Some("GNOME"), "Super+A", "GNOME: show applications"),
+    (Some("GNOME"), "Super+V", "GNOME: notification list"),
+    (Some("GNOME"), "Super+D", "GNOME: hide all windows"),
+    (Some("GNOME"), "Ctrl+Alt+T", "GNOME: open terminal"),
+    (Some("GNOME"), "Alt+F2", "GNOME: run command"),
+    (Some("KDE"), "Alt+Space", "KDE: KRunner"),
+    (Some("KDE"), "Super+L", "KDE: lock screen"),
+    (Some("KDE"), "Super+E", "KDE: Dolphin"),
+    (Some("KDE"), "Super+D", "KDE: peek at desktop"),
+    (Some("KDE"), "Ctrl+Alt+T", "KDE: Konsole"),
+    (Some("KDE"), "Ctrl+Esc", "KDE: System Monitor"),
+    (Some("macOS"), "Super+Space", "macOS: Spotlight"),
+    (Some("macOS"), "Ctrl+Space", "macOS: switch input source"),
+    (Some("macOS"), "Super+Shift+3", "macOS: screenshot"),
+    (
+        Some("macOS"),
+        "Super+Shift+4",
+        "macOS: screenshot selection",
+    ),
+    (Some("macOS"), "Super+Shift+5", "macOS: screenshot toolbar"),
+];
+
+/// Hotkeys offered instead of a conflicting one, in order of preference
+const CANDIDATES: &[&str] = &[
+    "Super+Shift+D",
+    "Ctrl+Alt+D",
+    "Super+Alt+D",
+    "Ctrl+Alt+Space",
+    "Super+Shift+Space",
+    "Ctrl+Shift+F12",
+    "Super+F9",
+];
+
+/// Suggestions listed per conflict
+const MAX_ALTERNATIVES: usize = 3;
+
+/// A configured hotkey that is probably taken
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HotkeyConflict {
+    /// What the hotkey does in Swictation (`toggle`, `push_to_talk`, ...)
+    pub role: &'static str,
+    pub hotkey: String,
+    /// Application or desktop action likely holding the hotkey
+    pub owner: String,
+    /// Free hotkeys to use instead
+    pub alternatives: Vec<String>,
+}
+
+impl std::fmt::Display for HotkeyConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}) is likely taken by {}",
+            self.hotkey, self.role, self.owner
+        )?;
+        if !self.alternatives.is_empty() {
+            write!(f, "; try {}", self.alternatives.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Canonical form of `hotkey` for comparison: modifiers in a fixed order,
+/// aliases folded, key lowercased (`"shift+ctrl+d"` → `"Ctrl+Shift+d"`)
+pub fn normalize(hotkey: &str) -> String {
+    let mut modifiers = [false; 4];
+    let mut key = String::new();
+    for part in hotkey.split('+').map(str::trim) {
+        match part.to_lowercase().as_str() {
+            "ctrl" | "control" => modifiers[0] = true,
+            "alt" | "option" => modifiers[1] = true,
+            "shift" => modifiers[2] = true,
+            "super" | "win" | "cmd" | "meta" | "$mod" => modifiers[3] = true,
+            k => key = k.to_string(),
+        }
+    }
+    let mut parts: Vec<String> = ["Ctrl", "Alt", "Shift", "Super"]
+        .iter()
+        .zip(modifiers)
+        .filter(|(_, held)| *held)
+        .map(|(name, _)| name.to_string())
+        .collect();
+    parts.push(key);
+    parts.join("+")
+}
+
+/// Whether a shortcut of `shortcut_desktop` applies on `desktop`
+fn applies(shortcut_desktop: Option<&str>, desktop: Option<&str>) -> bool {
+    match (shortcut_desktop, desktop) {
+        (None, _) => true,
+        (Some(owner), Some(desktop)) => desktop.to_lowercase().contains(&owner.to_lowercase()),
+        (Some(_), None) => false,
+    }
+}
+
+/// Known owners of `hotkey` on `desktop` (as detected, e.g. `"GNOME"`)
+pub fn known_owners(hotkey: &str, desktop: Option<&str>) -> Vec<&'static str> {
+    let hotkey = normalize(hotkey);
+    KNOWN_SHORTCUTS
+        .iter()
+        .filter(|(shortcut_desktop, shortcut, _)| {
+            applies(*shortcut_desktop, desktop) && normalize(shortcut) == hotkey
+        })
+        .map(|(_, _, owner)| *owner)
+        .collect()
+}
+
+/// Candidates with no known owner on `desktop` and not in `taken`
+pub fn alternatives(desktop: Option<&str>, taken: &[&str]) -> Vec<String> {
+    let taken: Vec<String> = taken.iter().map(|hotkey| normalize(hotkey)).collect();
+    CANDIDATES
+        .iter()
+        .filter(|candidate| {
+            known_owners(candidate, desktop).is_empty() && !taken.contains(&normalize(candidate))
+        })
+        .take(MAX_ALTERNATIVES)
+        .map(|candidate| candidate.to_string())
+        .collect()
+}
+
+/// Configured hotkeys by role
+fn configured(config: &HotkeyConfig) -> Vec<(&'static str, &str)> {
+    let mut hotkeys = vec![
+        ("toggle", config.toggle.as_str()),
+        ("push_to_talk", config.push_to_talk.as_str()),
+    ];
+    if let Some(private_mode) = &config.private_mode {
+        hotkeys.push(("private_mode", private_mode.as_str()));
+    }
+    hotkeys
+}
+
+/// Conflicts of the configured hotkeys with known shortcuts and each other
+pub fn check(config: &HotkeyConfig, desktop: Option<&str>) -> Vec<HotkeyConflict> {
+    let hotkeys = configured(config);
+    let taken: Vec<&str> = hotkeys.iter().map(|(_, hotkey)| *hotkey).collect();
+    let mut conflicts = Vec::new();
+
+    for (i, (role, hotkey)) in hotkeys.iter().enumerate() {
+        let mut owners: Vec<String> = known_owners(hotkey, desktop)
+            .into_iter()
+            .map(String::from)
+            .collect();
+        // Two roles on one hotkey: only the first would ever fire
+        owners.extend(
+            hotkeys[..i]
+                .iter()
+                .filter(|(_, earlier)| normalize(earlier) == normalize(hotkey))
+                .map(|(earlier_role, _)| format!("Swictation's own {} hotkey", earlier_role)),
+        );
+        if !owners.is_empty() {
+            conflicts.push(HotkeyConflict {
+                role,
+                hotkey: hotkey.to_string(),
+                owner: owners.join(", "),
+                alternatives: alternatives(desktop, &taken),
+            });
+        }
+    }
+    conflicts
+}
+
+/// Explain a registration of `hotkey` that the system refused
+pub fn registration_failed(
+    config: &HotkeyConfig,
+    role: &'static str,
+    hotkey: &str,
+    desktop: Option<&str>,
+) -> HotkeyConflict {
+    let owners = known_owners(hotkey, desktop);
+    let taken: Vec<&str> = configured(config)
+        .iter()
+        .map(|(_, hotkey)| *hotkey)
+        .collect();
+    HotkeyConflict {
+        role,
+        hotkey: hotkey.to_string(),
+        owner: if owners.is_empty() {
+            "another application that already grabbed it".to_string()
+        } else {
+            owners.join(", ")
+        },
+        alternatives: alternatives(desktop, &taken),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(toggle: &str, push_to_talk: &str) -> HotkeyConfig {
+        HotkeyConfig {
+            toggle: toggle.to_string(),
+            push_to_talk: push_to_talk.to_string(),
+            private_mode: None,
+        }
+    }
+
+    #[test]
+    fn test_normalize_folds_order_and_aliases() {
+        assert_eq!(normalize("shift+control+D"), "Ctrl+Shift+d");
+        assert_eq!(normalize("Cmd+Space"), normalize("Super+space"));
+        assert_eq!(normalize("$mod+Shift+d"), "Shift+Super+d");
+    }
+
+    #[test]
+    fn test_known_owner_depends_on_desktop() {
+        assert_eq!(
+            known_owners("Ctrl+Shift+D", None),
+            ["Firefox/Chrome: bookmark all tabs"]
+        );
+        assert!(known_owners("Super+Space", Some("KDE")).is_empty());
+        assert_eq!(
+            known_owners("Super+Space", Some("ubuntu:GNOME")),
+            ["GNOME: switch input source"]
+        );
+    }
+
+    #[test]
+    fn test_check_reports_owner_and_free_alternatives() {
+        let conflicts = check(&config("Ctrl+Shift+D", "Super+Space"), Some("GNOME"));
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].role, "toggle");
+        assert_eq!(conflicts[0].owner, "Firefox/Chrome: bookmark all tabs");
+        assert_eq!(conflicts[1].owner, "GNOME: switch input source");
+        // Suggestions skip hotkeys that are themselves taken
+        assert_eq!(
+            conflicts[0].alternatives,
+            ["Super+Shift+D", "Ctrl+Alt+D", "Super+Alt+D"]
+        );
+
+        assert!(check(&config("Super+Shift+D", "Ctrl+Alt+Space"), Some("GNOME")).is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_roles_conflict() {
+        let conflicts = check(&config("Super+Alt+D", "alt+super+d"), None);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].role, "push_to_talk");
+        assert_eq!(conflicts[0].owner, "Swictation's own toggle hotkey");
+    }
+
+    #[test]
+    fn test_registration_failure_without_known_owner() {
+        let conflict = registration_failed(
+            &config("Ctrl+Alt+D", "Super+Alt+D"),
+            "toggle",
+            "Ctrl+Alt+D",
+            Some("XFCE"),
+        );
+        assert_eq!(
+            conflict.to_string(),
+            "Ctrl+Alt+D (toggle) is likely taken by another application that already grabbed it; try Super+Shift+D, Ctrl+Alt+Space, Super+Shift+Space"
+        );
+    }
+}
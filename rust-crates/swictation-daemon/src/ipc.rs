@@ -1,23 +1,97 @@
 //! Unix socket IPC server for toggle commands
 
 use anyhow::{Context, Result};
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{UnixListener, UnixStream};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
+use crate::editor;
+use crate::error_log;
+use crate::jobs::JobControl;
 use crate::Daemon;
 
 /// IPC command - JSON only
 #[derive(Debug, serde::Deserialize)]
 struct IpcCommand {
     action: String,
+
+    /// Segment to re-transcribe (`retry_segment`) or replace (`use_alternative`)
+    #[serde(default)]
+    segment_id: Option<i64>,
+
+    /// Which n-best alternative to type for `use_alternative` (0 = most likely),
+    /// or which history entry to return for `history_copy` (1 = most recent)
+    #[serde(default)]
+    index: Option<usize>,
+
+    /// Model override name for `retry_segment` and `switch_model` ("0.6b-cpu",
+    /// "0.6b-gpu", "1.1b-cpu", "1.1b-gpu", "whisper-cpu", "whisper-gpu")
+    #[serde(default)]
+    model: Option<String>,
+
+    /// VAD tuning for `set_vad` (omitted values keep their current setting)
+    #[serde(default)]
+    threshold: Option<f32>,
+    #[serde(default)]
+    min_silence: Option<f32>,
+    #[serde(default)]
+    min_speech: Option<f32>,
+
+    /// Write `set_vad` values back to the config file
+    #[serde(default)]
+    persist: bool,
+
+    /// Editor name for `editor_attach` (shown in logs)
+    #[serde(default)]
+    client: Option<String>,
+
+    /// Audio file for `transcribe_file`, or script file for `start_readalong`
+    #[serde(default)]
+    path: Option<PathBuf>,
+
+    /// Script text for `start_readalong` (longer scripts go in a file)
+    #[serde(default)]
+    script: Option<String>,
+
+    /// Transcription job for `job_pause`, `job_resume` and `job_cancel`
+    #[serde(default)]
+    job_id: Option<i64>,
+
+    /// Phrases to favour for `set_bias_phrases` (empty clears them)
+    #[serde(default)]
+    phrases: Vec<String>,
+
+    /// Turn `private_mode` on or off (omitted toggles it)
+    #[serde(default)]
+    enabled: Option<bool>,
+
+    /// How many events `get_recent_errors` returns
+    #[serde(default)]
+    limit: Option<usize>,
+
+    /// Sessions to combine for `merge_sessions`
+    #[serde(default)]
+    session_ids: Vec<i64>,
+
+    /// Session to divide for `split_session` (and where, in Unix seconds),
+    /// or to archive, delete or restore
+    #[serde(default)]
+    session_id: Option<i64>,
+    #[serde(default)]
+    at: Option<f64>,
+
+    /// Output profile to switch to for `output_profile` (omitted reports it)
+    #[serde(default)]
+    profile: Option<String>,
 }
 
 impl IpcCommand {
     fn parse(s: &str) -> Result<Self> {
-        serde_json::from_str(s.trim())
-            .context("Invalid JSON. Expected: {\"action\": \"toggle|status|quit\"}")
+        serde_json::from_str(s.trim()).context(
+            "Invalid JSON. Expected: {\"action\": \"toggle|status|quit|retry_segment|use_alternative|history_list|history_copy|read_back|set_vad|dump_vad_trace|editor_attach|transcription_jobs|transcribe_file|job_pause|job_resume|job_cancel|set_bias_phrases|reload_lexicon|private_mode|switch_model|get_recent_errors|merge_sessions|split_session|archive_session|unarchive_session|delete_session|restore_session|empty_trash|start_meeting_mode|stop_meeting_mode|start_readalong|stop_readalong|output_profile\"}",
+        )
     }
 
     fn to_command_type(&self) -> Result<CommandType> {
@@ -25,7 +99,118 @@ impl IpcCommand {
             "toggle" => Ok(CommandType::Toggle),
             "status" => Ok(CommandType::Status),
             "quit" | "exit" | "shutdown" => Ok(CommandType::Quit),
-            _ => anyhow::bail!("Unknown action: {}", self.action),
+            "retry_segment" => Ok(CommandType::RetrySegment {
+                segment_id: self
+                    .segment_id
+                    .context("retry_segment requires \"segment_id\"")?,
+                model: self.model.clone(),
+            }),
+            "use_alternative" => Ok(CommandType::UseAlternative {
+                segment_id: self
+                    .segment_id
+                    .context("use_alternative requires \"segment_id\"")?,
+                index: self.index.context("use_alternative requires \"index\"")?,
+            }),
+            "history_list" | "history-list" => Ok(CommandType::HistoryList),
+            "history_copy" | "history-copy" => Ok(CommandType::HistoryCopy {
+                n: self.index.unwrap_or(1),
+            }),
+            "read_back" | "read-back" => Ok(CommandType::ReadBack),
+            "dump_vad_trace" | "dump-vad-trace" => Ok(CommandType::DumpVadTrace),
+            "set_vad" | "set-vad" => Ok(CommandType::SetVad {
+                threshold: self.threshold,
+                min_silence: self.min_silence,
+                min_speech: self.min_speech,
+                persist: self.persist,
+            }),
+            "editor_attach" | "editor-attach" => Ok(CommandType::EditorAttach {
+                client: self.client.clone().unwrap_or_else(|| "editor".to_string()),
+            }),
+            "transcription_jobs" | "transcription-jobs" => Ok(CommandType::TranscriptionJobs),
+            "transcribe_file" | "transcribe-file" => Ok(CommandType::TranscribeFile {
+                path: self
+                    .path
+                    .clone()
+                    .context("transcribe_file requires \"path\"")?,
+            }),
+            "set_bias_phrases" | "set-bias-phrases" => Ok(CommandType::SetBiasPhrases {
+                phrases: self.phrases.clone(),
+            }),
+            "reload_lexicon" | "reload-lexicon" => Ok(CommandType::ReloadLexicon),
+            "switch_model" | "switch-model" => Ok(CommandType::SwitchModel {
+                model: self
+                    .model
+                    .clone()
+                    .context("switch_model requires \"model\"")?,
+            }),
+            "private_mode" | "private-mode" => Ok(CommandType::PrivateMode {
+                enabled: self.enabled,
+            }),
+            "get_recent_errors" | "get-recent-errors" => Ok(CommandType::RecentErrors {
+                limit: self.limit.unwrap_or(error_log::DEFAULT_LIMIT),
+            }),
+            "merge_sessions" | "merge-sessions" => Ok(CommandType::MergeSessions {
+                ids: self.session_ids.clone(),
+            }),
+            "split_session" | "split-session" => Ok(CommandType::SplitSession {
+                id: self
+                    .session_id
+                    .context("split_session requires \"session_id\"")?,
+                at: self.at.context("split_session requires \"at\"")?,
+            }),
+            "archive_session" | "archive-session" => Ok(CommandType::ArchiveSession {
+                id: self
+                    .session_id
+                    .context("archive_session requires \"session_id\"")?,
+                archived: true,
+            }),
+            "unarchive_session" | "unarchive-session" => Ok(CommandType::ArchiveSession {
+                id: self
+                    .session_id
+                    .context("unarchive_session requires \"session_id\"")?,
+                archived: false,
+            }),
+            "delete_session" | "delete-session" => Ok(CommandType::TrashSession {
+                id: self
+                    .session_id
+                    .context("delete_session requires \"session_id\"")?,
+                trashed: true,
+            }),
+            "restore_session" | "restore-session" => Ok(CommandType::TrashSession {
+                id: self
+                    .session_id
+                    .context("restore_session requires \"session_id\"")?,
+                trashed: false,
+            }),
+            "empty_trash" | "empty-trash" => Ok(CommandType::EmptyTrash),
+            "start_meeting_mode" | "start-meeting-mode" => {
+                Ok(CommandType::MeetingMode { enabled: true })
+            }
+            "stop_meeting_mode" | "stop-meeting-mode" => {
+                Ok(CommandType::MeetingMode { enabled: false })
+            }
+            "start_readalong" | "start-readalong" => {
+                if self.script.is_none() && self.path.is_none() {
+                    anyhow::bail!("start_readalong requires \"script\" or \"path\"");
+                }
+                Ok(CommandType::StartReadalong {
+                    script: self.script.clone(),
+                    path: self.path.clone(),
+                })
+            }
+            "stop_readalong" | "stop-readalong" => Ok(CommandType::StopReadalong),
+            "output_profile" | "output-profile" => Ok(CommandType::OutputProfile {
+                profile: self.profile.clone(),
+            }),
+            action => match JobControl::parse(action) {
+                Some(control) => Ok(CommandType::JobControl {
+                    job_id: self
+                        .job_id
+                        .with_context(|| format!("{} requires \"job_id\"", action))?,
+                    control,
+                }),
+                None => anyhow::bail!("Unknown action: {}", self.action),
+            },
         }
     }
 }
@@ -35,12 +220,89 @@ enum CommandType {
     Toggle,
     Status,
     Quit,
+    RetrySegment {
+        segment_id: i64,
+        model: Option<String>,
+    },
+    UseAlternative {
+        segment_id: i64,
+        index: usize,
+    },
+    HistoryList,
+    HistoryCopy {
+        n: usize,
+    },
+    ReadBack,
+    DumpVadTrace,
+    SetVad {
+        threshold: Option<f32>,
+        min_silence: Option<f32>,
+        min_speech: Option<f32>,
+        persist: bool,
+    },
+    EditorAttach {
+        client: String,
+    },
+    TranscriptionJobs,
+    TranscribeFile {
+        path: PathBuf,
+    },
+    JobControl {
+        job_id: i64,
+        control: JobControl,
+    },
+    SetBiasPhrases {
+        phrases: Vec<String>,
+    },
+    ReloadLexicon,
+    PrivateMode {
+        enabled: Option<bool>,
+    },
+    SwitchModel {
+        model: String,
+    },
+    RecentErrors {
+        limit: usize,
+    },
+    MergeSessions {
+        ids: Vec<i64>,
+    },
+    SplitSession {
+        id: i64,
+        at: f64,
+    },
+    ArchiveSession {
+        id: i64,
+        archived: bool,
+    },
+    TrashSession {
+        id: i64,
+        trashed: bool,
+    },
+    EmptyTrash,
+    MeetingMode {
+        enabled: bool,
+    },
+    StartReadalong {
+        script: Option<String>,
+        path: Option<PathBuf>,
+    },
+    StopReadalong,
+    OutputProfile {
+        profile: Option<String>,
+    },
 }
 
 /// Unix socket IPC server
+///
+/// Only the user the daemon runs as (and root) may connect: besides the
+/// socket's 0600 mode, every connection's peer UID is checked, which also
+/// holds in system mode where each user's daemon serves one UID.
 pub struct IpcServer {
     listener: UnixListener,
     daemon: Arc<Daemon>,
+    /// Owner of the socket file, i.e. the daemon's effective UID
+    owner_uid: u32,
 }
 
 impl IpcServer {
@@ -63,28 +325,57 @@ impl IpcServer {
             }
         }
 
+        let owner_uid = {
+            use std::os::unix::fs::MetadataExt;
+            std::fs::metadata(socket_path)
+                .context("Failed to read socket owner")?
+                .uid()
+        };
+
         info!(
-            "IPC server listening on {} (permissions: 0600)",
-            socket_path
+            "IPC server listening on {} (permissions: 0600, UID {} only)",
+            socket_path, owner_uid
         );
 
-        Ok(Self { listener, daemon })
+        Ok(Self {
+            listener,
+            daemon,
+            owner_uid,
+        })
     }
 
-    /// Accept next IPC connection
+    /// Accept next IPC connection from an allowed user
     pub async fn accept(&mut self) -> Result<(UnixStream, Arc<Daemon>)> {
-        let (stream, _) = self
-            .listener
-            .accept()
-            .await
-            .context("Failed to accept connection")?;
-        Ok((stream, self.daemon.clone()))
+        loop {
+            let (stream, _) = self
+                .listener
+                .accept()
+                .await
+                .context("Failed to accept connection")?;
+            match stream.peer_cred() {
+                Ok(cred) if peer_allowed(cred.uid(), self.owner_uid) => {
+                    return Ok((stream, self.daemon.clone()))
+                }
+                Ok(cred) => warn!(
+                    "Refused IPC connection from UID {} (daemon belongs to UID {})",
+                    cred.uid(),
+                    self.owner_uid
+                ),
+                Err(e) => warn!("Refused IPC connection without peer credentials: {}", e),
+            }
+        }
     }
 }
 
+/// Whether a client running as `peer_uid` may control the daemon of `owner_uid`
+fn peer_allowed(peer_uid: u32, owner_uid: u32) -> bool {
+    peer_uid == owner_uid || peer_uid == 0
+}
+
 /// Handle a single IPC connection
 pub async fn handle_connection(mut stream: UnixStream, daemon: Arc<Daemon>) -> Result<()> {
-    let mut buffer = [0u8; 1024];
+    // Room for a `set_bias_phrases` list or a short readalong script
+    let mut buffer = [0u8; 8192];
     let n = stream.read(&mut buffer).await?;
 
     if n == 0 {
@@ -111,9 +402,288 @@ pub async fn handle_connection(mut stream: UnixStream, daemon: Arc<Daemon>) -> R
                 let status = daemon.status().await;
                 serde_json::json!({
                     "status": "success",
-                    "state": status
+                    "state": status,
+                    "private_mode": daemon.private_mode(),
+                    "hotkey_conflicts": daemon.hotkey_conflicts
                 })
             }
+            Ok(CommandType::RetrySegment { segment_id, model }) => {
+                match daemon.retry_segment(segment_id, model).await {
+                    Ok(result) => serde_json::json!({
+                        "status": "success",
+                        "result": result
+                    }),
+                    Err(e) => serde_json::json!({
+                        "status": "error",
+                        "error": format!("{:#}", e)
+                    }),
+                }
+            }
+            Ok(CommandType::UseAlternative { segment_id, index }) => {
+                match daemon.use_alternative(segment_id, index).await {
+                    Ok(text) => serde_json::json!({
+                        "status": "success",
+                        "text": text
+                    }),
+                    Err(e) => serde_json::json!({
+                        "status": "error",
+                        "error": format!("{:#}", e)
+                    }),
+                }
+            }
+            Ok(CommandType::RecentErrors { limit }) => serde_json::json!({
+                "status": "success",
+                "errors": daemon.recent_errors(limit)
+            }),
+            Ok(CommandType::MergeSessions { ids }) => match daemon.merge_sessions(&ids).await {
+                Ok(session_id) => serde_json::json!({
+                    "status": "success",
+                    "session_id": session_id
+                }),
+                Err(e) => serde_json::json!({
+                    "status": "error",
+                    "error": format!("{:#}", e)
+                }),
+            },
+            Ok(CommandType::SplitSession { id, at }) => match daemon.split_session(id, at).await {
+                Ok(session_id) => serde_json::json!({
+                    "status": "success",
+                    "session_id": session_id
+                }),
+                Err(e) => serde_json::json!({
+                    "status": "error",
+                    "error": format!("{:#}", e)
+                }),
+            },
+            Ok(CommandType::ArchiveSession { id, archived }) => {
+                match daemon.archive_session(id, archived).await {
+                    Ok(()) => serde_json::json!({
+                        "status": "success",
+                        "session_id": id
+                    }),
+                    Err(e) => serde_json::json!({
+                        "status": "error",
+                        "error": format!("{:#}", e)
+                    }),
+                }
+            }
+            Ok(CommandType::TrashSession { id, trashed }) => {
+                match daemon.trash_session(id, trashed).await {
+                    Ok(()) => serde_json::json!({
+                        "status": "success",
+                        "session_id": id
+                    }),
+                    Err(e) => serde_json::json!({
+                        "status": "error",
+                        "error": format!("{:#}", e)
+                    }),
+                }
+            }
+            Ok(CommandType::EmptyTrash) => match daemon.empty_trash().await {
+                Ok(purged) => serde_json::json!({
+                    "status": "success",
+                    "purged": purged
+                }),
+                Err(e) => serde_json::json!({
+                    "status": "error",
+                    "error": format!("{:#}", e)
+                }),
+            },
+            Ok(CommandType::MeetingMode { enabled }) => {
+                let result = if enabled {
+                    daemon.start_meeting().await
+                } else {
+                    daemon.stop_meeting().await
+                };
+                match result {
+                    Ok(msg) => serde_json::json!({
+                        "status": "success",
+                        "message": msg
+                    }),
+                    Err(e) => serde_json::json!({
+                        "status": "error",
+                        "error": format!("{:#}", e)
+                    }),
+                }
+            }
+            Ok(CommandType::StartReadalong { script, path }) => {
+                match daemon.start_readalong(script, path).await {
+                    Ok((words, lines)) => serde_json::json!({
+                        "status": "success",
+                        "words": words,
+                        "lines": lines
+                    }),
+                    Err(e) => serde_json::json!({
+                        "status": "error",
+                        "error": format!("{:#}", e)
+                    }),
+                }
+            }
+            Ok(CommandType::StopReadalong) => match daemon.stop_readalong().await {
+                Ok(position) => serde_json::json!({
+                    "status": "success",
+                    "word": position.word,
+                    "total_words": position.total_words,
+                    "accuracy": position.accuracy,
+                    "pronunciation_score": position.pronunciation_score
+                }),
+                Err(e) => serde_json::json!({
+                    "status": "error",
+                    "error": format!("{:#}", e)
+                }),
+            },
+            Ok(CommandType::OutputProfile { profile }) => {
+                match daemon.output_profile(profile.as_deref()) {
+                    Ok((profile, sinks)) => serde_json::json!({
+                        "status": "success",
+                        "profile": profile,
+                        "sinks": sinks
+                    }),
+                    Err(e) => serde_json::json!({
+                        "status": "error",
+                        "error": format!("{:#}", e)
+                    }),
+                }
+            }
+            Ok(CommandType::HistoryList) => serde_json::json!({
+                "status": "success",
+                "history": daemon.history()
+            }),
+            Ok(CommandType::HistoryCopy { n }) => match daemon.history_entry(n) {
+                Ok(text) => serde_json::json!({
+                    "status": "success",
+                    "text": text
+                }),
+                Err(e) => serde_json::json!({
+                    "status": "error",
+                    "error": format!("{:#}", e)
+                }),
+            },
+            Ok(CommandType::ReadBack) => match daemon.read_back().await {
+                Ok(text) => serde_json::json!({
+                    "status": "success",
+                    "text": text
+                }),
+                Err(e) => serde_json::json!({
+                    "status": "error",
+                    "error": format!("{:#}", e)
+                }),
+            },
+            Ok(CommandType::DumpVadTrace) => match daemon.vad_trace().await {
+                Ok(trace) => serde_json::json!({
+                    "status": "success",
+                    "trace": trace
+                }),
+                Err(e) => serde_json::json!({
+                    "status": "error",
+                    "error": format!("{:#}", e)
+                }),
+            },
+            Ok(CommandType::SetVad {
+                threshold,
+                min_silence,
+                min_speech,
+                persist,
+            }) => match daemon
+                .set_vad(threshold, min_silence, min_speech, persist)
+                .await
+            {
+                Ok((threshold, min_silence, min_speech)) => serde_json::json!({
+                    "status": "success",
+                    "vad": {
+                        "threshold": threshold,
+                        "min_silence": min_silence,
+                        "min_speech": min_speech,
+                        "persisted": persist
+                    }
+                }),
+                Err(e) => serde_json::json!({
+                    "status": "error",
+                    "error": format!("{:#}", e)
+                }),
+            },
+            Ok(CommandType::TranscriptionJobs) => match daemon.jobs.recent() {
+                Ok(jobs) => serde_json::json!({
+                    "status": "success",
+                    "jobs": jobs
+                }),
+                Err(e) => serde_json::json!({
+                    "status": "error",
+                    "error": format!("{:#}", e)
+                }),
+            },
+            Ok(CommandType::TranscribeFile { path }) => match daemon.jobs.request(&path).await {
+                Ok(job_id) => serde_json::json!({
+                    "status": "success",
+                    "job_id": job_id
+                }),
+                Err(e) => serde_json::json!({
+                    "status": "error",
+                    "error": format!("{:#}", e)
+                }),
+            },
+            Ok(CommandType::JobControl { job_id, control }) => {
+                match daemon.jobs.control(job_id, control).await {
+                    Ok(status) => serde_json::json!({
+                        "status": "success",
+                        "job_id": job_id,
+                        "job_status": status
+                    }),
+                    Err(e) => serde_json::json!({
+                        "status": "error",
+                        "error": format!("{:#}", e)
+                    }),
+                }
+            }
+            Ok(CommandType::SetBiasPhrases { phrases }) => {
+                match daemon.set_bias_phrases(phrases).await {
+                    Ok(count) => serde_json::json!({
+                        "status": "success",
+                        "phrases": count
+                    }),
+                    Err(e) => serde_json::json!({
+                        "status": "error",
+                        "error": format!("{:#}", e)
+                    }),
+                }
+            }
+            Ok(CommandType::ReloadLexicon) => match daemon.reload_lexicon().await {
+                Ok(words) => serde_json::json!({
+                    "status": "success",
+                    "words": words
+                }),
+                Err(e) => serde_json::json!({
+                    "status": "error",
+                    "error": format!("{:#}", e)
+                }),
+            },
+            Ok(CommandType::PrivateMode { enabled }) => {
+                match daemon.set_private_mode(enabled).await {
+                    Ok(enabled) => serde_json::json!({
+                        "status": "success",
+                        "private_mode": enabled
+                    }),
+                    Err(e) => serde_json::json!({
+                        "status": "error",
+                        "error": format!("{:#}", e)
+                    }),
+                }
+            }
+            Ok(CommandType::SwitchModel { model }) => match daemon.switch_model(&model).await {
+                Ok(model) => serde_json::json!({
+                    "status": "success",
+                    "model": model
+                }),
+                Err(e) => serde_json::json!({
+                    "status": "error",
+                    "error": format!("{:#}", e)
+                }),
+            },
+            Ok(CommandType::EditorAttach { client }) => {
+                // Stays open: the connection becomes the editor's event stream
+                tokio::spawn(editor::serve(stream, daemon.editors.clone(), client));
+                return Ok(());
+            }
             Ok(CommandType::Quit) => {
                 info!("Received quit command");
                 std::process::exit(0);
@@ -151,3 +721,24 @@ pub async fn handle_connection(mut stream: UnixStream, daemon: Arc<Daemon>) -> R
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(json: &str) -> Result<CommandType> {
+        IpcCommand::parse(json)?.to_command_type()
+    }
+
+    #[test]
+    fn test_switch_model_parses_model() {
+        let parsed = command(r#"{"action": "switch_model", "model": "1.1b-cpu"}"#).unwrap();
+        assert!(matches!(parsed, CommandType::SwitchModel { model } if model == "1.1b-cpu"));
+    }
+
+    #[test]
+    fn test_switch_model_requires_model() {
+        let e = command(r#"{"action": "switch-model"}"#).unwrap_err();
+        assert_eq!(e.to_string(), "switch_model requires \"model\"");
+    }
+}
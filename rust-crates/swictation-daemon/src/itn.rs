@@ -0,0 +1,702 @@
+//! Inverse text normalization: spoken numbers, dates and units as written
+//!
+//! The 1.1B model and Whisper in raw mode spell everything out ("twenty
+//! three dollars"); this stage rewrites it the way it would be typed:
+//!
+//! - amounts: "twenty three dollars and fifty cents" → "$23.50"
+//! - dates: "march fifth" / "the fifth of march" → "March 5th" ("5 March"
+//!   in day-first locales), with an optional year
+//! - units: "five kilometers" → "5 km", "ten percent" → "10%"
+//! - plain numbers from ten up ("twenty one" → "21", "nineteen eighty four"
+//!   → "1984"); one to nine stay words, as most style guides write them
+//!
+//! Spoken forms are English; the locale decides how they are written.
+//! Numbers interrupted by punctuation are not joined ("twenty, three").
+
+use crate::config::ItnConfig;
+
+/// How a locale writes what was said
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Locale {
+    /// "March 5th" rather than "5 March"
+    month_first: bool,
+    /// "pounds" are sterling (£) rather than weight (lb)
+    pounds_are_money: bool,
+}
+
+impl Locale {
+    /// `en`, `en-US`, `en_GB`, ...; `None` for locales without rules
+    fn parse(code: &str) -> Option<Self> {
+        let code = code.replace('_', "-").to_ascii_lowercase();
+        let region = match code.split_once('-') {
+            Some(("en", region)) => region,
+            None if code == "en" => "us",
+            _ => return None,
+        };
+        Some(match region {
+            "us" | "ca" | "ph" => Self {
+                month_first: true,
+                pounds_are_money: false,
+            },
+            _ => Self {
+                month_first: false,
+                pounds_are_money: true,
+            },
+        })
+    }
+}
+
+/// Whether `code` names a locale with ITN rules
+pub fn is_supported_locale(code: &str) -> bool {
+    Locale::parse(code).is_some()
+}
+
+#[derive(Debug, Clone)]
+pub struct Itn {
+    /// `None` when disabled
+    locale: Option<Locale>,
+}
+
+impl Itn {
+    pub fn new(config: &ItnConfig) -> Self {
+        Self {
+            locale: config
+                .enabled
+                .then(|| Locale::parse(&config.locale))
+                .flatten(),
+        }
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let Some(locale) = self.locale else {
+            return text.to_string();
+        };
+        let tokens = tokenize(text);
+        let mut out = String::with_capacity(text.len());
+        let mut end = 0;
+        let mut i = 0;
+        while i < tokens.len() {
+            let (written, next) = match rewrite(&tokens, i, locale) {
+                Some(rewritten) => rewritten,
+                None => (tokens[i].core(text).to_string(), i + 1),
+            };
+            let (first, last) = (&tokens[i], &tokens[next - 1]);
+            out.push_str(&text[end..first.start]);
+            out.push_str(&text[first.start..first.core_start]);
+            out.push_str(&written);
+            out.push_str(&text[last.core_end..last.end]);
+            end = last.end;
+            i = next;
+        }
+        out.push_str(&text[end..]);
+        out
+    }
+}
+
+/// Whitespace-separated word: `start..end` with punctuation, the word
+/// itself at `core_start..core_end`
+#[derive(Debug)]
+struct Token {
+    start: usize,
+    end: usize,
+    core_start: usize,
+    core_end: usize,
+    /// Lowercase word
+    key: String,
+}
+
+impl Token {
+    fn core<'a>(&self, text: &'a str) -> &'a str {
+        &text[self.core_start..self.core_end]
+    }
+
+    /// Punctuation before or after the word, which ends a number or phrase
+    fn has_prefix(&self) -> bool {
+        self.core_start > self.start
+    }
+
+    fn has_suffix(&self) -> bool {
+        self.core_end < self.end
+    }
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                let word = &text[s..i];
+                let core = word.trim_start_matches(|c: char| !c.is_alphanumeric());
+                let core_start = s + word.len() - core.len();
+                let core = core.trim_end_matches(|c: char| !c.is_alphanumeric());
+                let core_end = core_start + core.len();
+                tokens.push(Token {
+                    start: s,
+                    end: i,
+                    core_start,
+                    core_end,
+                    key: core.to_lowercase(),
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+/// Replacement for the phrase starting at token `i`, and the token after it
+fn rewrite(tokens: &[Token], i: usize, locale: Locale) -> Option<(String, usize)> {
+    if let Some(date) = date(tokens, i, locale) {
+        return Some(date);
+    }
+    let (number, next) = parse_number(tokens, i)?;
+    // Punctuation after the number ends the phrase
+    let open = !tokens[next - 1].has_suffix();
+
+    if open && !number.ordinal {
+        if let Some(amount) = amount(tokens, &number, next, locale) {
+            return Some(amount);
+        }
+        if let Some((unit, after)) = unit(tokens, next, locale) {
+            let value = number.format(10_000);
+            let written = match unit {
+                "%" | "°" | "°C" | "°F" => format!("{}{}", value, unit),
+                _ => format!("{} {}", value, unit),
+            };
+            return Some((written, after));
+        }
+        if let Some((year, after)) = year(tokens, &number, next) {
+            return Some((year.to_string(), after));
+        }
+    }
+
+    if number.ordinal {
+        (number.value() >= 10 && number.decimals.is_none()).then(|| (ordinal(number.value()), next))
+    } else {
+        (number.value() >= 10 || number.decimals.is_some()).then(|| (number.format(10_000), next))
+    }
+}
+
+/// "march fifth [twenty twenty four]" or "[the] fifth of march [...]"
+fn date(tokens: &[Token], i: usize, locale: Locale) -> Option<(String, usize)> {
+    let day_of = |j: usize| {
+        parse_number(tokens, j).filter(|(day, _)| {
+            day.ordinal && day.decimals.is_none() && (1..=31).contains(&day.value())
+        })
+    };
+
+    let (month, day, next) = if let Some(month) = month(&tokens[i].key) {
+        if tokens[i].has_suffix() {
+            return None;
+        }
+        let (day, next) = day_of(i + 1)?;
+        (month, day.value(), next)
+    } else {
+        let start = if tokens[i].key == "the" && !tokens[i].has_suffix() {
+            i + 1
+        } else {
+            i
+        };
+        let (day, of) = day_of(start)?;
+        let month_at = of + 1;
+        let month = (!tokens[of - 1].has_suffix()
+            && tokens
+                .get(of)
+                .is_some_and(|t| t.key == "of" && !t.has_suffix()))
+        .then(|| tokens.get(month_at).and_then(|t| month(&t.key)))
+        .flatten()?;
+        (month, day.value(), month_at + 1)
+    };
+
+    let mut written = if locale.month_first {
+        format!("{} {}", month, ordinal(day))
+    } else {
+        format!("{} {}", day, month)
+    };
+    let mut next = next;
+    if !tokens[next - 1].has_suffix() {
+        if let Some((year, after)) = parse_number(tokens, next).and_then(|(number, end)| {
+            year(tokens, &number, end).or_else(|| {
+                (!number.ordinal
+                    && number.decimals.is_none()
+                    && (1000..=2999).contains(&number.value()))
+                .then_some((number.value(), end))
+            })
+        }) {
+            written = if locale.month_first {
+                format!("{}, {}", written, year)
+            } else {
+                format!("{} {}", written, year)
+            };
+            next = after;
+        }
+    }
+    Some((written, next))
+}
+
+/// "nineteen eighty four" → 1984, "twenty oh five" → 2005, following a
+/// first half already parsed as `number` (ending before token `next`)
+fn year(tokens: &[Token], number: &Number, next: usize) -> Option<(u64, usize)> {
+    let century = number.value();
+    if number.ordinal || number.decimals.is_some() || !(10..=99).contains(&century) {
+        return None;
+    }
+    if number.words > 1 && !century.is_multiple_of(10) {
+        // "twenty one" is one number, not a century
+        return None;
+    }
+    if tokens.get(next).is_some_and(|t| t.key == "oh") && !tokens[next].has_suffix() {
+        let (digit, after) = parse_number(tokens, next + 1)?;
+        return (!digit.ordinal && digit.decimals.is_none() && (1..=9).contains(&digit.value()))
+            .then(|| (century * 100 + digit.value(), after));
+    }
+    let (rest, after) = parse_number(tokens, next)?;
+    let last_two = rest.value();
+    (!rest.ordinal && rest.decimals.is_none() && rest.words <= 2 && (10..=99).contains(&last_two))
+        .then(|| (century * 100 + last_two, after))
+}
+
+/// "$23.50" from "twenty three dollars [and fifty cents]", or "50¢"
+fn amount(
+    tokens: &[Token],
+    number: &Number,
+    next: usize,
+    locale: Locale,
+) -> Option<(String, usize)> {
+    let key = tokens.get(next)?.key.as_str();
+    let symbol = match key {
+        "dollar" | "dollars" | "bucks" => "$",
+        "euro" | "euros" => "€",
+        "pound" | "pounds" | "quid" if locale.pounds_are_money => "£",
+        "cent" | "cents" if number.decimals.is_none() && number.value() < 100 => {
+            return Some((format!("{}¢", number.value()), next + 1));
+        }
+        _ => return None,
+    };
+    let mut written = format!("{}{}", symbol, number.format(1000));
+    let mut after = next + 1;
+
+    // "... and fifty cents" / "... and fifty pence"
+    if number.decimals.is_none() && !tokens[next].has_suffix() {
+        let cents = tokens
+            .get(after)
+            .filter(|t| t.key == "and" && !t.has_suffix())
+            .and_then(|_| parse_number(tokens, after + 1))
+            .filter(|(cents, end)| {
+                !cents.ordinal
+                    && cents.decimals.is_none()
+                    && cents.value() < 100
+                    && !tokens[end - 1].has_suffix()
+                    && tokens.get(*end).is_some_and(|t| {
+                        matches!(t.key.as_str(), "cent" | "cents" | "penny" | "pence")
+                    })
+            });
+        if let Some((cents, end)) = cents {
+            written = format!("{}.{:02}", written, cents.value());
+            after = end + 1;
+        }
+    }
+    Some((written, after))
+}
+
+/// Unit symbol for the words starting at token `i`, and the token after them
+fn unit(tokens: &[Token], i: usize, locale: Locale) -> Option<(&'static str, usize)> {
+    const PHRASES: [(&[&str], &str); 6] = [
+        (&["kilometers", "per", "hour"], "km/h"),
+        (&["kilometres", "per", "hour"], "km/h"),
+        (&["miles", "per", "hour"], "mph"),
+        (&["degrees", "celsius"], "°C"),
+        (&["degrees", "centigrade"], "°C"),
+        (&["degrees", "fahrenheit"], "°F"),
+    ];
+    for (words, symbol) in PHRASES {
+        let matched = words.iter().enumerate().all(|(k, word)| {
+            tokens
+                .get(i + k)
+                .is_some_and(|t| t.key == *word && (k + 1 == words.len() || !t.has_suffix()))
+        });
+        if matched {
+            return Some((symbol, i + words.len()));
+        }
+    }
+
+    let symbol = match tokens.get(i)?.key.as_str() {
+        "percent" | "per-cent" => "%",
+        "degree" | "degrees" => "°",
+        "kilometer" | "kilometers" | "kilometre" | "kilometres" => "km",
+        "meter" | "meters" | "metre" | "metres" => "m",
+        "centimeter" | "centimeters" | "centimetre" | "centimetres" => "cm",
+        "millimeter" | "millimeters" | "millimetre" | "millimetres" => "mm",
+        "mile" | "miles" => "mi",
+        "kilogram" | "kilograms" | "kilo" | "kilos" => "kg",
+        "gram" | "grams" => "g",
+        "pound" | "pounds" if !locale.pounds_are_money => "lb",
+        "liter" | "liters" | "litre" | "litres" => "L",
+        "milliliter" | "milliliters" | "millilitre" | "millilitres" => "mL",
+        _ => return None,
+    };
+    Some((symbol, i + 1))
+}
+
+fn month(key: &str) -> Option<&'static str> {
+    const MONTHS: [&str; 12] = [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ];
+    MONTHS
+        .into_iter()
+        .find(|month| month.eq_ignore_ascii_case(key))
+}
+
+/// "21st", "112th"
+fn ordinal(value: u64) -> String {
+    let suffix = match (value % 10, value % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", value, suffix)
+}
+
+/// What the last word of a number was, deciding what may follow it
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Last {
+    Nothing,
+    Zero,
+    Unit,
+    Teen,
+    Tens,
+    Hundred,
+    Scale,
+    And,
+    Point,
+}
+
+/// A spoken number, built one word at a time
+#[derive(Debug, Clone)]
+struct Number {
+    /// Sum of completed thousands/millions/billions groups
+    total: u64,
+    /// Group below the last scale word
+    current: u64,
+    /// Smallest scale used so far; later ones must be smaller
+    scale: u64,
+    last: Last,
+    ordinal: bool,
+    /// Digits after "point"
+    decimals: Option<String>,
+    words: usize,
+}
+
+impl Default for Number {
+    fn default() -> Self {
+        Self {
+            total: 0,
+            current: 0,
+            scale: u64::MAX,
+            last: Last::Nothing,
+            ordinal: false,
+            decimals: None,
+            words: 0,
+        }
+    }
+}
+
+impl Number {
+    fn value(&self) -> u64 {
+        self.total + self.current
+    }
+
+    /// Ends on a word that completes a number
+    fn is_complete(&self) -> bool {
+        !matches!(self.last, Last::Nothing | Last::And | Last::Point)
+    }
+
+    /// Digits, with thousands separators from `group_from` up
+    fn format(&self, group_from: u64) -> String {
+        let value = self.value();
+        let digits = value.to_string();
+        let mut written = if value >= group_from {
+            let mut grouped = String::new();
+            for (k, digit) in digits.chars().enumerate() {
+                if k > 0 && (digits.len() - k).is_multiple_of(3) {
+                    grouped.push(',');
+                }
+                grouped.push(digit);
+            }
+            grouped
+        } else {
+            digits
+        };
+        if let Some(decimals) = &self.decimals {
+            written.push('.');
+            written.push_str(decimals);
+        }
+        written
+    }
+
+    /// Add one word; `false` (leaving the number unusable) if it cannot follow
+    fn push(&mut self, word: &str) -> bool {
+        if self.ordinal {
+            return false;
+        }
+        if let Some(decimals) = &mut self.decimals {
+            return match word_value(word) {
+                Some((digit @ 0..=9, false)) => {
+                    decimals.push_str(&digit.to_string());
+                    self.last = Last::Unit;
+                    self.words += 1;
+                    true
+                }
+                _ if word == "oh" => {
+                    decimals.push('0');
+                    self.last = Last::Unit;
+                    self.words += 1;
+                    true
+                }
+                _ => false,
+            };
+        }
+        match word {
+            "point" if self.is_complete() => {
+                self.decimals = Some(String::new());
+                self.last = Last::Point;
+                self.words += 1;
+                return true;
+            }
+            "and" if matches!(self.last, Last::Hundred | Last::Scale) => {
+                self.last = Last::And;
+                self.words += 1;
+                return true;
+            }
+            _ => {}
+        }
+
+        let Some((value, ordinal)) = word_value(word) else {
+            return false;
+        };
+        use Last::*;
+        let fits = match value {
+            0 => self.last == Nothing,
+            1..=9 => matches!(self.last, Nothing | Tens | Hundred | Scale | And),
+            10..=99 => matches!(self.last, Nothing | Hundred | Scale | And),
+            100 => matches!(self.last, Unit | Teen | Tens) && (1..=99).contains(&self.current),
+            _ => self.current > 0 && value < self.scale && self.last != And,
+        };
+        if !fits {
+            return false;
+        }
+        self.last = match value {
+            0 => Zero,
+            1..=9 => Unit,
+            10..=19 => Teen,
+            20..=99 => Tens,
+            100 => Hundred,
+            _ => Scale,
+        };
+        match value {
+            100 => self.current *= 100,
+            1000.. => {
+                self.total += self.current * value;
+                self.current = 0;
+                self.scale = value;
+            }
+            _ => self.current += value,
+        }
+        self.ordinal = ordinal;
+        self.words += 1;
+        true
+    }
+}
+
+/// Value of a number word and whether it is an ordinal
+fn word_value(word: &str) -> Option<(u64, bool)> {
+    const CARDINALS: [(&str, u64); 32] = [
+        ("zero", 0),
+        ("one", 1),
+        ("two", 2),
+        ("three", 3),
+        ("four", 4),
+        ("five", 5),
+        ("six", 6),
+        ("seven", 7),
+        ("eight", 8),
+        ("nine", 9),
+        ("ten", 10),
+        ("eleven", 11),
+        ("twelve", 12),
+        ("thirteen", 13),
+        ("fourteen", 14),
+        ("fifteen", 15),
+        ("sixteen", 16),
+        ("seventeen", 17),
+        ("eighteen", 18),
+        ("nineteen", 19),
+        ("twenty", 20),
+        ("thirty", 30),
+        ("forty", 40),
+        ("fifty", 50),
+        ("sixty", 60),
+        ("seventy", 70),
+        ("eighty", 80),
+        ("ninety", 90),
+        ("hundred", 100),
+        ("thousand", 1_000),
+        ("million", 1_000_000),
+        ("billion", 1_000_000_000),
+    ];
+    const ORDINALS: [(&str, u64); 12] = [
+        ("first", 1),
+        ("second", 2),
+        ("third", 3),
+        ("fifth", 5),
+        ("eighth", 8),
+        ("ninth", 9),
+        ("twelfth", 12),
+        ("twentieth", 20),
+        ("thirtieth", 30),
+        ("fortieth", 40),
+        ("fiftieth", 50),
+        ("sixtieth", 60),
+    ];
+
+    if let Some(&(_, value)) = CARDINALS.iter().find(|(w, _)| *w == word) {
+        return Some((value, false));
+    }
+    if let Some(&(_, value)) = ORDINALS.iter().find(|(w, _)| *w == word) {
+        return Some((value, true));
+    }
+    // "fourth", "seventeenth", "seventieth", "hundredth", "millionth"
+    let stem = word.strip_suffix("th")?;
+    let stem = stem
+        .strip_suffix("ie")
+        .map(|tens| format!("{}y", tens))
+        .unwrap_or_else(|| stem.to_string());
+    CARDINALS
+        .iter()
+        .find(|(w, value)| *w == stem && *value > 0)
+        .map(|&(_, value)| (value, true))
+}
+
+/// The longest number starting at token `i`, and the token after it
+///
+/// Hyphenated words ("twenty-three") count as one token. Punctuation ends
+/// the number after the token carrying it.
+fn parse_number(tokens: &[Token], i: usize) -> Option<(Number, usize)> {
+    let mut number = Number::default();
+    let mut best = None;
+    for (j, token) in tokens.iter().enumerate().skip(i) {
+        if j > i && token.has_prefix() {
+            break;
+        }
+        let mut next = number.clone();
+        if !token.key.split('-').all(|part| next.push(part)) {
+            break;
+        }
+        number = next;
+        if number.is_complete() {
+            best = Some((number.clone(), j + 1));
+        }
+        if token.has_suffix() {
+            break;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn itn(locale: &str) -> Itn {
+        Itn::new(&ItnConfig {
+            enabled: true,
+            locale: locale.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_numbers() {
+        let us = itn("en-US");
+        assert_eq!(us.apply("I need twenty three of them"), "I need 23 of them");
+        assert_eq!(us.apply("one or two"), "one or two");
+        assert_eq!(
+            us.apply("one hundred and five thousand, two"),
+            "105,000, two"
+        );
+        assert_eq!(us.apply("three point one four"), "3.14");
+        assert_eq!(us.apply("twenty-one guns"), "21 guns");
+        assert_eq!(
+            us.apply("the twenty first century, the second time"),
+            "the 21st century, the second time"
+        );
+        assert_eq!(us.apply("back in nineteen eighty four."), "back in 1984.");
+        assert_eq!(us.apply("twenty oh five"), "2005");
+        assert_eq!(us.apply("twenty, three"), "20, three");
+    }
+
+    #[test]
+    fn test_amounts_and_units() {
+        let us = itn("en-US");
+        assert_eq!(us.apply("twenty three dollars"), "$23");
+        assert_eq!(
+            us.apply("It costs twenty three dollars and fifty cents."),
+            "It costs $23.50."
+        );
+        assert_eq!(us.apply("two thousand euros"), "€2,000");
+        assert_eq!(us.apply("fifty cents"), "50¢");
+        assert_eq!(us.apply("ten percent off"), "10% off");
+        assert_eq!(us.apply("five kilometers"), "5 km");
+        assert_eq!(us.apply("sixty miles per hour"), "60 mph");
+        assert_eq!(us.apply("twenty degrees celsius"), "20°C");
+        assert_eq!(us.apply("five pounds"), "5 lb");
+
+        let gb = itn("en_GB");
+        assert_eq!(gb.apply("five pounds and ten pence"), "£5.10");
+    }
+
+    #[test]
+    fn test_dates() {
+        let us = itn("en-US");
+        assert_eq!(us.apply("march fifth"), "March 5th");
+        assert_eq!(us.apply("On the fifth of march, we"), "On March 5th, we");
+        assert_eq!(
+            us.apply("may twenty second twenty twenty four"),
+            "May 22nd, 2024"
+        );
+        assert_eq!(
+            us.apply("july fourth two thousand and one"),
+            "July 4th, 2001"
+        );
+        // Months need an ordinal day
+        assert_eq!(us.apply("you may two"), "you may two");
+
+        let gb = itn("en-GB");
+        assert_eq!(gb.apply("march fifth"), "5 March");
+        assert_eq!(gb.apply("the first of may"), "1 May");
+    }
+
+    #[test]
+    fn test_disabled_and_unsupported() {
+        let text = "twenty three dollars";
+        assert_eq!(Itn::new(&ItnConfig::default()).apply(text), text);
+        assert_eq!(itn("de-DE").apply(text), text);
+        assert!(is_supported_locale("en"));
+        assert!(!is_supported_locale("fr-FR"));
+    }
+}
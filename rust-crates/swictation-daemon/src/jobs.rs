@@ -0,0 +1,771 @@
+//! Background transcription of audio files
+//!
+//! Files from watched folders and files the user asks for (`transcribe_file`)
+//! are queued in the `transcription_jobs` table and transcribed one at a
+//! time with the loaded model; the transcript is written next to each file
+//! as `.txt`, with `.srt` and `.vtt` subtitles. Subtitles follow the speech
+//! segments found by VAD, split at sentence ends and at subtitle length
+//! using the recognizer's word timestamps. With a `[diarization]` model,
+//! each segment is also labelled with its speaker: the transcript starts a
+//! "Speaker N:" paragraph when the voice changes, and subtitles name the
+//! speaker.
+//!
+//! Live dictation always comes first. Nothing starts while the daemon is
+//! recording, and a running job checks again before each speech segment, so
+//! a live segment waits for at most one batch segment to finish. Among
+//! queued jobs, requested ones go before watch-folder ones, oldest first.
+//!
+//! Jobs can be paused, resumed and cancelled over IPC. A running job stops
+//! at its next segment; a resumed job starts its file over. Every state
+//! change and finished segment is sent to UI clients as `job_progress`.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock};
+use tracing::{info, warn};
+
+use swictation_broadcaster::MetricsBroadcaster;
+use swictation_metrics::{JobPriority, JobStatus, MetricsDatabase, TranscriptionJob};
+use swictation_stt::{AudioProcessor, WordConfidence};
+use swictation_vad::{VadConfig, VadDetector, VadResult};
+
+use crate::config::DaemonConfig;
+use crate::diarization::Diarizer;
+use crate::pipeline::Transcriber;
+use crate::watch_folder::is_audio_file;
+use crate::DaemonState;
+
+/// How often a waiting job checks whether dictation has stopped
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Jobs returned by `JobQueue::recent`
+const RECENT_JOBS: usize = 50;
+
+/// Longest subtitle: two lines of 42 characters
+const MAX_CUE_CHARS: usize = 84;
+
+/// Longest time one subtitle stays on screen
+const MAX_CUE_SECS: f64 = 7.0;
+
+/// What the user can do with a job over IPC
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JobControl {
+    Pause,
+    Resume,
+    Cancel,
+}
+
+impl JobControl {
+    pub fn parse(action: &str) -> Option<Self> {
+        match action {
+            "job_pause" | "job-pause" => Some(Self::Pause),
+            "job_resume" | "job-resume" => Some(Self::Resume),
+            "job_cancel" | "job-cancel" => Some(Self::Cancel),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pause => "pause",
+            Self::Resume => "resume",
+            Self::Cancel => "cancel",
+        }
+    }
+}
+
+/// Messages from the queue handle to the worker
+#[derive(Debug)]
+enum JobCommand {
+    /// Run (or re-prioritize) a job
+    Queued {
+        job_id: i64,
+        path: PathBuf,
+        priority: JobPriority,
+    },
+    /// Stop a queued or running job, leaving it in `status`
+    Stop { job_id: i64, status: JobStatus },
+}
+
+/// Handle for adding and controlling jobs (cheap to clone)
+#[derive(Clone)]
+pub struct JobQueue {
+    db: Arc<MetricsDatabase>,
+    broadcaster: Arc<MetricsBroadcaster>,
+    tx: mpsc::UnboundedSender<JobCommand>,
+}
+
+impl JobQueue {
+    /// Start the worker, resuming jobs left queued or running by the last run
+    pub fn spawn(
+        vad_config: VadConfig,
+        state: Arc<RwLock<DaemonState>>,
+        transcriber: Transcriber,
+        diarizer: Option<Diarizer>,
+        db: Arc<MetricsDatabase>,
+        broadcaster: Arc<MetricsBroadcaster>,
+    ) -> Self {
+        let (tx, commands) = mpsc::unbounded_channel();
+        let mut pending = Vec::new();
+        match db.get_unfinished_transcription_jobs() {
+            Ok(jobs) => pending.extend(jobs.into_iter().map(|job| Pending {
+                job_id: job.job_id,
+                path: PathBuf::from(job.path),
+                priority: job.priority,
+            })),
+            Err(e) => warn!("Failed to load unfinished transcription jobs: {:#}", e),
+        }
+
+        let worker = Worker {
+            state,
+            transcriber,
+            diarizer,
+            db: db.clone(),
+            broadcaster: broadcaster.clone(),
+            vad_config,
+            vad: None,
+            commands,
+            pending,
+        };
+        tokio::spawn(worker.run());
+
+        Self {
+            db,
+            broadcaster,
+            tx,
+        }
+    }
+
+    /// Queue a file found in a watched folder, unless it already has a job
+    pub async fn enqueue(&self, path: &Path) -> Result<Option<i64>> {
+        let Some(job_id) = self
+            .db
+            .enqueue_transcription_job(&path.display().to_string(), JobPriority::Background)?
+        else {
+            return Ok(None);
+        };
+        self.queued(job_id, path, JobPriority::Background).await;
+        Ok(Some(job_id))
+    }
+
+    /// Queue a file the user asked for, ahead of watch-folder jobs
+    ///
+    /// A file that was transcribed before is transcribed again.
+    pub async fn request(&self, path: &Path) -> Result<i64> {
+        if !is_audio_file(path) {
+            anyhow::bail!("{} is not a WAV, MP3 or FLAC file", path.display());
+        }
+        let path = path
+            .canonicalize()
+            .with_context(|| format!("Cannot read {}", path.display()))?;
+        let job_id = self
+            .db
+            .requeue_transcription_job(&path.display().to_string(), JobPriority::Requested)?
+            .with_context(|| format!("{} is already being transcribed", path.display()))?;
+        self.queued(job_id, &path, JobPriority::Requested).await;
+        Ok(job_id)
+    }
+
+    /// Pause, resume or cancel a job, returning its new status
+    pub async fn control(&self, job_id: i64, control: JobControl) -> Result<JobStatus> {
+        let job = self
+            .db
+            .get_transcription_job(job_id)?
+            .with_context(|| format!("Transcription job {} not found", job_id))?;
+        let active = matches!(job.status, JobStatus::Queued | JobStatus::Running);
+
+        match control {
+            JobControl::Pause if active => self.stop(job_id, JobStatus::Paused),
+            JobControl::Cancel if active => self.stop(job_id, JobStatus::Cancelled),
+            JobControl::Cancel if job.status == JobStatus::Paused => {
+                // Paused jobs are not known to the worker
+                self.db
+                    .update_transcription_job(job_id, JobStatus::Cancelled, None, None, None)?;
+                self.broadcaster
+                    .broadcast_job_progress(job_id, job.path, JobStatus::Cancelled, 0.0)
+                    .await;
+                Ok(JobStatus::Cancelled)
+            }
+            JobControl::Resume if job.status == JobStatus::Paused => {
+                self.db
+                    .update_transcription_job(job_id, JobStatus::Queued, None, None, None)?;
+                self.queued(job_id, Path::new(&job.path), job.priority)
+                    .await;
+                Ok(JobStatus::Queued)
+            }
+            _ => anyhow::bail!(
+                "Cannot {} transcription job {} while it is {}",
+                control.as_str(),
+                job_id,
+                job.status.as_str()
+            ),
+        }
+    }
+
+    /// Most recent jobs, newest first
+    pub fn recent(&self) -> Result<Vec<TranscriptionJob>> {
+        self.db.get_transcription_jobs(RECENT_JOBS)
+    }
+
+    fn stop(&self, job_id: i64, status: JobStatus) -> Result<JobStatus> {
+        self.tx
+            .send(JobCommand::Stop { job_id, status })
+            .context("Job worker has stopped")?;
+        Ok(status)
+    }
+
+    async fn queued(&self, job_id: i64, path: &Path, priority: JobPriority) {
+        let _ = self.tx.send(JobCommand::Queued {
+            job_id,
+            path: path.to_path_buf(),
+            priority,
+        });
+        self.broadcaster
+            .broadcast_job_progress(job_id, path.display().to_string(), JobStatus::Queued, 0.0)
+            .await;
+    }
+}
+
+/// VAD settings for splitting files into segments: the live settings,
+/// without trace or debug output
+pub fn file_vad_config(config: &DaemonConfig) -> VadConfig {
+    VadConfig::with_model(config.vad_model_path.display().to_string())
+        .min_silence(config.vad_min_silence)
+        .min_speech(config.vad_min_speech)
+        .max_speech(config.vad_max_speech)
+        .hangover_frames(config.vad_hangover_frames)
+        .threshold(config.vad_threshold)
+        .num_threads(config.num_threads)
+}
+
+/// A job waiting for the worker
+#[derive(Debug, Clone, PartialEq)]
+struct Pending {
+    job_id: i64,
+    path: PathBuf,
+    priority: JobPriority,
+}
+
+/// Index of the job to run next: highest priority, then oldest
+fn next_job(pending: &[Pending]) -> Option<usize> {
+    (0..pending.len()).max_by(|&a, &b| {
+        pending[a]
+            .priority
+            .cmp(&pending[b].priority)
+            .then(pending[b].job_id.cmp(&pending[a].job_id))
+    })
+}
+
+/// How a job that was started ended, short of an error
+enum Outcome {
+    /// Transcribed, with this many words
+    Done(usize),
+    /// Paused or cancelled at a segment boundary
+    Stopped(JobStatus),
+}
+
+/// Transcribes queued jobs one at a time
+struct Worker {
+    state: Arc<RwLock<DaemonState>>,
+    transcriber: Transcriber,
+    /// Speaker labels, when a speaker model is configured
+    diarizer: Option<Diarizer>,
+    db: Arc<MetricsDatabase>,
+    broadcaster: Arc<MetricsBroadcaster>,
+    vad_config: VadConfig,
+    /// Created with the first job
+    vad: Option<VadDetector>,
+    commands: mpsc::UnboundedReceiver<JobCommand>,
+    pending: Vec<Pending>,
+}
+
+impl Worker {
+    async fn run(mut self) {
+        let mut poll = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            tokio::select! {
+                command = self.commands.recv() => match command {
+                    Some(command) => {
+                        self.apply(command, None).await;
+                    }
+                    None => return,
+                },
+                _ = poll.tick() => {
+                    if !self.is_idle().await {
+                        continue;
+                    }
+                    if let Some(i) = next_job(&self.pending) {
+                        let job = self.pending.remove(i);
+                        self.run_job(job).await;
+                    }
+                }
+            }
+        }
+    }
+
+    async fn is_idle(&self) -> bool {
+        *self.state.read().await == DaemonState::Idle
+    }
+
+    /// Apply a command, returning the status `running` should stop in if
+    /// the command was for it
+    async fn apply(&mut self, command: JobCommand, running: Option<i64>) -> Option<JobStatus> {
+        match command {
+            JobCommand::Queued {
+                job_id,
+                path,
+                priority,
+            } => {
+                match self.pending.iter_mut().find(|job| job.job_id == job_id) {
+                    Some(job) => job.priority = job.priority.max(priority),
+                    None if running != Some(job_id) => self.pending.push(Pending {
+                        job_id,
+                        path,
+                        priority,
+                    }),
+                    None => {}
+                }
+                None
+            }
+            JobCommand::Stop { job_id, status } => {
+                if running == Some(job_id) {
+                    return Some(status);
+                }
+                if let Some(i) = self.pending.iter().position(|job| job.job_id == job_id) {
+                    let job = self.pending.remove(i);
+                    self.finish(&job, status, None, None).await;
+                }
+                None
+            }
+        }
+    }
+
+    /// Apply the commands that arrived while `job_id` was running
+    async fn check_commands(&mut self, job_id: i64) -> Option<JobStatus> {
+        let mut stop = None;
+        while let Ok(command) = self.commands.try_recv() {
+            stop = stop.or(self.apply(command, Some(job_id)).await);
+        }
+        stop
+    }
+
+    async fn run_job(&mut self, job: Pending) {
+        info!("📂 Transcribing {}", job.path.display());
+        match self.transcribe_file(&job).await {
+            Ok(Outcome::Done(words)) => {
+                info!("📂 Transcribed {} ({} words)", job.path.display(), words);
+                self.finish(&job, JobStatus::Done, Some(words as i64), None)
+                    .await;
+            }
+            Ok(Outcome::Stopped(status)) => {
+                info!("📂 {} {}", status.as_str(), job.path.display());
+                self.finish(&job, status, None, None).await;
+            }
+            Err(e) => {
+                warn!("📂 Failed to transcribe {}: {:#}", job.path.display(), e);
+                let error = format!("{:#}", e);
+                self.finish(&job, JobStatus::Failed, None, Some(&error))
+                    .await;
+            }
+        }
+    }
+
+    /// Record a job's final (or paused) state and tell UI clients
+    async fn finish(
+        &self,
+        job: &Pending,
+        status: JobStatus,
+        words: Option<i64>,
+        error: Option<&str>,
+    ) {
+        if let Err(e) = self
+            .db
+            .update_transcription_job(job.job_id, status, None, words, error)
+        {
+            warn!("Failed to record transcription job {}: {:#}", job.job_id, e);
+        }
+        let progress = if status == JobStatus::Done { 1.0 } else { 0.0 };
+        self.progress(job, status, progress).await;
+    }
+
+    async fn progress(&self, job: &Pending, status: JobStatus, progress: f32) {
+        self.broadcaster
+            .broadcast_job_progress(job.job_id, job.path.display().to_string(), status, progress)
+            .await;
+    }
+
+    /// Transcribe one file and write its sidecars
+    async fn transcribe_file(&mut self, job: &Pending) -> Result<Outcome> {
+        self.db
+            .update_transcription_job(job.job_id, JobStatus::Running, None, None, None)?;
+        self.progress(job, JobStatus::Running, 0.0).await;
+
+        // Decoding and VAD block - keep them off the async workers
+        let (total_samples, segments) = tokio::task::block_in_place(|| -> Result<_> {
+            let samples = AudioProcessor::new()?
+                .load_audio(&job.path)
+                .context("Failed to decode audio")?;
+            if self.vad.is_none() {
+                self.vad = Some(
+                    VadDetector::new(self.vad_config.clone())
+                        .context("Failed to initialize VAD")?,
+                );
+            }
+            let vad = self.vad.as_mut().unwrap();
+            vad.clear();
+            if let Some(diarizer) = self.diarizer.as_mut() {
+                diarizer.reset();
+            }
+            Ok((samples.len(), speech_segments(vad, &samples)?))
+        })?;
+        self.db.update_transcription_job(
+            job.job_id,
+            JobStatus::Running,
+            Some(total_samples as f64 / 16000.0),
+            None,
+            None,
+        )?;
+
+        let mut cues = Vec::new();
+        let total = segments.len();
+        for (i, (start_sample, samples)) in segments.into_iter().enumerate() {
+            // Dictation comes first: finish the file once the user stops recording
+            loop {
+                if let Some(status) = self.check_commands(job.job_id).await {
+                    return Ok(Outcome::Stopped(status));
+                }
+                if self.is_idle().await {
+                    break;
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+
+            let (text, words, speaker) = tokio::task::block_in_place(|| -> Result<_> {
+                let (text, words) = self.transcriber.transcribe(&samples)?;
+                let speaker = self
+                    .diarizer
+                    .as_mut()
+                    .and_then(|diarizer| diarizer.label(&samples));
+                Ok((text, words, speaker))
+            })?;
+            cues.extend(
+                segment_cues(
+                    &text,
+                    &words,
+                    start_sample as f64 / 16000.0,
+                    (start_sample as usize + samples.len()) as f64 / 16000.0,
+                )
+                .into_iter()
+                .map(|cue| Cue { speaker, ..cue }),
+            );
+            self.progress(job, JobStatus::Running, (i + 1) as f32 / total as f32)
+                .await;
+        }
+
+        let path = &job.path;
+        let txt = path.with_extension("txt");
+        std::fs::write(&txt, to_txt(&cues))
+            .with_context(|| format!("Failed to write {}", txt.display()))?;
+        let srt = path.with_extension("srt");
+        std::fs::write(&srt, to_srt(&cues))
+            .with_context(|| format!("Failed to write {}", srt.display()))?;
+        let vtt = path.with_extension("vtt");
+        std::fs::write(&vtt, to_vtt(&cues))
+            .with_context(|| format!("Failed to write {}", vtt.display()))?;
+
+        Ok(Outcome::Done(
+            cues.iter()
+                .map(|cue| cue.text.split_whitespace().count())
+                .sum(),
+        ))
+    }
+}
+
+/// Split a whole file into speech segments, fed in the same 0.5 s chunks
+/// as live audio
+fn speech_segments(vad: &mut VadDetector, samples: &[f32]) -> Result<Vec<(u64, Vec<f32>)>> {
+    let mut segments = Vec::new();
+    for chunk in samples.chunks(8000) {
+        if let VadResult::Speech {
+            start_sample,
+            samples,
+        } = vad.process_audio(chunk)?
+        {
+            segments.push((start_sample, samples));
+        }
+    }
+    if let Some(VadResult::Speech {
+        start_sample,
+        samples,
+    }) = vad.flush()
+    {
+        segments.push((start_sample, samples));
+    }
+    Ok(segments)
+}
+
+/// One subtitle
+#[derive(Debug)]
+struct Cue {
+    start_s: f64,
+    end_s: f64,
+    text: String,
+    /// Speaker number (from 1) when speakers are labelled
+    speaker: Option<usize>,
+}
+
+/// Subtitles for the speech segment from `start_s` to `end_s`
+///
+/// The typed text need not have as many words as were recognized (numbers
+/// written out, spoken punctuation replaced), so each typed word starts with
+/// the recognized word at the same relative position. Without word
+/// timestamps (Whisper) the words are spread evenly over the segment.
+fn segment_cues(text: &str, words: &[WordConfidence], start_s: f64, end_s: f64) -> Vec<Cue> {
+    let typed: Vec<&str> = text.split_whitespace().collect();
+    let timed = !words.is_empty() && words.iter().all(|w| w.start_s.is_some());
+    let mut starts = Vec::with_capacity(typed.len());
+    for i in 0..typed.len() {
+        let start = if timed {
+            start_s + words[i * words.len() / typed.len()].start_s.unwrap_or(0.0)
+        } else {
+            start_s + (end_s - start_s) * i as f64 / typed.len() as f64
+        };
+        let earliest = starts.last().copied().unwrap_or(start_s);
+        starts.push(start.clamp(earliest, end_s));
+    }
+
+    let mut cues = Vec::new();
+    let mut first = 0;
+    for i in 0..typed.len() {
+        let last = i + 1 == typed.len();
+        let sentence_end = typed[i].ends_with(['.', '?', '!']);
+        let next_fits = !last && {
+            let chars = typed[first..=i + 1].join(" ").chars().count();
+            let end = starts.get(i + 2).copied().unwrap_or(end_s);
+            chars <= MAX_CUE_CHARS && end - starts[first] <= MAX_CUE_SECS
+        };
+        if last || sentence_end || !next_fits {
+            cues.push(Cue {
+                start_s: starts[first],
+                end_s: if last { end_s } else { starts[i + 1] },
+                text: typed[first..=i].join(" "),
+                speaker: None,
+            });
+            first = i + 1;
+        }
+    }
+    cues
+}
+
+/// Cue texts joined into one line, or into a "Speaker N:" paragraph per
+/// change of speaker
+fn to_txt(cues: &[Cue]) -> String {
+    let mut txt = String::new();
+    let mut speaker = None;
+    for (i, cue) in cues.iter().enumerate() {
+        if let Some(n) = cue.speaker.filter(|_| cue.speaker != speaker) {
+            if i > 0 {
+                txt.push_str("\n\n");
+            }
+            txt.push_str(&format!("Speaker {}: ", n));
+            speaker = cue.speaker;
+        } else if i > 0 {
+            txt.push(' ');
+        }
+        txt.push_str(&cue.text);
+    }
+    txt.push('\n');
+    txt
+}
+
+fn to_srt(cues: &[Cue]) -> String {
+    cues.iter()
+        .enumerate()
+        .map(|(i, cue)| {
+            // Name the speaker when the voice changes
+            let label = match cue.speaker {
+                Some(n) if i == 0 || cues[i - 1].speaker != cue.speaker => {
+                    format!("[Speaker {}] ", n)
+                }
+                _ => String::new(),
+            };
+            format!(
+                "{}\n{} --> {}\n{}{}\n\n",
+                i + 1,
+                timestamp(cue.start_s, ','),
+                timestamp(cue.end_s, ','),
+                label,
+                cue.text
+            )
+        })
+        .collect()
+}
+
+fn to_vtt(cues: &[Cue]) -> String {
+    let body: String = cues
+        .iter()
+        .map(|cue| {
+            let voice = cue
+                .speaker
+                .map(|n| format!("<v Speaker {}>", n))
+                .unwrap_or_default();
+            format!(
+                "{} --> {}\n{}{}\n\n",
+                timestamp(cue.start_s, '.'),
+                timestamp(cue.end_s, '.'),
+                voice,
+                cue.text
+            )
+        })
+        .collect();
+    format!("WEBVTT\n\n{}", body)
+}
+
+/// `HH:MM:SS,mmm` (SRT) or `HH:MM:SS.mmm` (WebVTT)
+fn timestamp(seconds: f64, separator: char) -> String {
+    let ms = (seconds * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        separator,
+        ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(job_id: i64, priority: JobPriority) -> Pending {
+        Pending {
+            job_id,
+            path: PathBuf::from(format!("/audio/{}.wav", job_id)),
+            priority,
+        }
+    }
+
+    #[test]
+    fn test_requested_jobs_run_first() {
+        let queue = vec![
+            pending(3, JobPriority::Background),
+            pending(5, JobPriority::Requested),
+            pending(1, JobPriority::Background),
+            pending(4, JobPriority::Requested),
+        ];
+        assert_eq!(next_job(&queue), Some(3)); // job 4
+        assert_eq!(next_job(&queue[..1]), Some(0));
+        assert_eq!(next_job(&[]), None);
+
+        let background = [queue[0].clone(), queue[2].clone()];
+        assert_eq!(next_job(&background), Some(1)); // oldest: job 1
+    }
+
+    #[test]
+    fn test_parse_control() {
+        assert_eq!(JobControl::parse("job_pause"), Some(JobControl::Pause));
+        assert_eq!(JobControl::parse("job-resume"), Some(JobControl::Resume));
+        assert_eq!(JobControl::parse("job_cancel"), Some(JobControl::Cancel));
+        assert_eq!(JobControl::parse("job_retry"), None);
+    }
+
+    #[test]
+    fn test_srt() {
+        let cues = vec![
+            Cue {
+                start_s: 1.5,
+                end_s: 3.25,
+                text: "Hello there.".to_string(),
+                speaker: None,
+            },
+            Cue {
+                start_s: 3725.0,
+                end_s: 3727.0005,
+                text: "Still recording?".to_string(),
+                speaker: None,
+            },
+        ];
+        assert_eq!(
+            to_srt(&cues),
+            "1\n00:00:01,500 --> 00:00:03,250\nHello there.\n\n\
+             2\n01:02:05,000 --> 01:02:07,001\nStill recording?\n\n"
+        );
+        assert_eq!(
+            to_vtt(&cues[..1]),
+            "WEBVTT\n\n00:00:01.500 --> 00:00:03.250\nHello there.\n\n"
+        );
+    }
+
+    #[test]
+    fn test_speakers_labelled_on_change() {
+        let cue = |start_s: f64, text: &str, speaker| Cue {
+            start_s,
+            end_s: start_s + 1.0,
+            text: text.to_string(),
+            speaker,
+        };
+        let cues = vec![
+            cue(0.0, "Shall we start?", Some(1)),
+            cue(1.0, "The budget first.", Some(1)),
+            cue(2.0, "Sure.", Some(2)),
+        ];
+        assert_eq!(
+            to_txt(&cues),
+            "Speaker 1: Shall we start? The budget first.\n\nSpeaker 2: Sure.\n"
+        );
+        assert_eq!(
+            to_srt(&cues),
+            "1\n00:00:00,000 --> 00:00:01,000\n[Speaker 1] Shall we start?\n\n\
+             2\n00:00:01,000 --> 00:00:02,000\nThe budget first.\n\n\
+             3\n00:00:02,000 --> 00:00:03,000\n[Speaker 2] Sure.\n\n"
+        );
+        assert!(to_vtt(&cues).ends_with("00:00:03.000\n<v Speaker 2>Sure.\n\n"));
+
+        let unlabelled = [cue(0.0, "One.", None), cue(1.0, "Two.", None)];
+        assert_eq!(to_txt(&unlabelled), "One. Two.\n");
+    }
+
+    fn word(word: &str, start_s: Option<f64>) -> WordConfidence {
+        WordConfidence {
+            word: word.to_string(),
+            confidence: 1.0,
+            start_s,
+        }
+    }
+
+    #[test]
+    fn test_cues_split_at_sentences_with_word_times() {
+        let words = [
+            word("hello", Some(0.0)),
+            word("there", Some(0.5)),
+            word("it", Some(1.5)),
+            word("costs", Some(1.75)),
+            word("twenty", Some(2.25)),
+            word("dollars", Some(2.5)),
+        ];
+        let cues = segment_cues("Hello there. It costs $20.", &words, 10.0, 13.5);
+        let cues: Vec<(f64, f64, &str)> = cues
+            .iter()
+            .map(|c| (c.start_s, c.end_s, c.text.as_str()))
+            .collect();
+        assert_eq!(
+            cues,
+            [(10.0, 11.5, "Hello there."), (11.5, 13.5, "It costs $20.")]
+        );
+    }
+
+    #[test]
+    fn test_long_cues_split_evenly_without_word_times() {
+        let text = "word ".repeat(40);
+        let cues = segment_cues(&text, &[word("word", None)], 0.0, 20.0);
+        assert!(cues.len() >= 3);
+        assert!(cues.iter().all(|c| c.text.chars().count() <= MAX_CUE_CHARS));
+        assert!(cues.iter().all(|c| c.end_s - c.start_s <= MAX_CUE_SECS));
+        assert_eq!(cues[0].start_s, 0.0);
+        assert_eq!(cues.last().unwrap().end_s, 20.0);
+        assert!(segment_cues("  ", &[], 0.0, 1.0).is_empty());
+    }
+}
@@ -0,0 +1,85 @@
+//! Personal n-gram language model fused into STT decoding
+//!
+//! Built by the context-learning pipeline from the user's transcripts and
+//! the corrected side of their learned corrections, so names and jargon the
+//! user has taught swictation win close calls in the decoder. An external
+//! ARPA model (`lm_path`) can take its place.
+
+use std::path::Path;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use swictation_context_learning::{load_or_train_ngram, NgramModel, RetrainingConfig};
+use swictation_stt::{ArpaModel, PrefixScorer, ShallowFusion};
+
+use crate::corrections::CorrectionEngine;
+
+/// The context-learning model, as seen by the decoder
+struct UserLanguageModel(NgramModel);
+
+impl PrefixScorer for UserLanguageModel {
+    fn prefix_log_prob(&self, history: &[&str], prefix: &str) -> f32 {
+        self.0.prefix_log_prob(history, prefix) as f32
+    }
+}
+
+/// Load an ARPA language model for shallow fusion
+///
+/// Returns `None` when it can't be loaded; decoding then runs on the
+/// acoustic model alone.
+pub fn load_external(path: &Path, weight: f32) -> Option<ShallowFusion> {
+    match ArpaModel::load(path) {
+        Ok(model) => Some(ShallowFusion::new(Arc::new(model), weight)),
+        Err(e) => {
+            warn!("⚠️ Failed to load language model {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Load (retraining if due) the user's language model for shallow fusion
+///
+/// Returns `None` when there is nothing to train on yet or loading fails;
+/// decoding then runs on the acoustic model alone.
+pub fn load_fusion(
+    data_dir: &Path,
+    corrections: &CorrectionEngine,
+    weight: f32,
+) -> Option<ShallowFusion> {
+    let model_path = data_dir.join("ngram-lm.json");
+    let db_path = data_dir.join("metrics.db");
+
+    let corrected: Vec<String> = match corrections.get_all() {
+        Ok(all) => all.into_iter().map(|c| c.corrected).collect(),
+        Err(e) => {
+            warn!("Failed to read corrections for the language model: {}", e);
+            Vec::new()
+        }
+    };
+
+    match load_or_train_ngram(
+        &model_path,
+        &db_path,
+        &corrected,
+        &RetrainingConfig::default(),
+    ) {
+        Ok(Some(model)) => {
+            info!(
+                "✓ Personal language model loaded ({} words)",
+                model.vocabulary_size()
+            );
+            Some(ShallowFusion::new(
+                Arc::new(UserLanguageModel(model)),
+                weight,
+            ))
+        }
+        Ok(None) => {
+            info!("No personal language model yet; decoding without LM fusion");
+            None
+        }
+        Err(e) => {
+            warn!("⚠️ Failed to load personal language model: {}", e);
+            None
+        }
+    }
+}
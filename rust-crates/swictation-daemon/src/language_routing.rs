@@ -0,0 +1,94 @@
+//! Per-segment spoken language routing
+//!
+//! With two or more `languages` configured, the Whisper model first detects
+//! which of them each VAD segment is in. Segments in the primary language
+//! (the first listed) go to the configured model as before; the others are
+//! transcribed by Whisper prompted with their language, so switching between
+//! English and German mid-session types German for the German parts.
+//!
+//! When Whisper is the configured model itself it already detects the
+//! language of each segment; `languages` then only narrows its choice.
+
+use anyhow::{Context, Result};
+use swictation_stt::{SttEngine, WhisperRecognizer};
+use tracing::{debug, info, warn};
+
+use crate::config::DaemonConfig;
+
+/// Segments detected with less confidence stay with the primary model
+const MIN_PROBABILITY: f32 = 0.5;
+
+/// Whisper for segments outside the primary language
+pub struct LanguageRouter {
+    whisper: SttEngine,
+    primary: String,
+}
+
+impl LanguageRouter {
+    /// Set up routing for `config.languages`
+    ///
+    /// Returns `None` when fewer than two languages are configured or the
+    /// primary engine is Whisper (which is restricted to them instead).
+    pub fn load(config: &DaemonConfig, primary: &mut SttEngine) -> Result<Option<Self>> {
+        if config.languages.len() < 2 {
+            return Ok(None);
+        }
+        if let SttEngine::Whisper(whisper) = primary {
+            whisper
+                .set_languages(&config.languages)
+                .context("Invalid languages for the Whisper model")?;
+            info!("🌐 Whisper detects among: {}", config.languages.join(", "));
+            return Ok(None);
+        }
+
+        let use_gpu = primary.backend() == "GPU";
+        let mut whisper = WhisperRecognizer::new(&config.stt_whisper_model_path, use_gpu)
+            .with_context(|| {
+                format!(
+                    "Failed to load Whisper model for language routing from {}",
+                    config.stt_whisper_model_path.display()
+                )
+            })?;
+        whisper
+            .set_languages(&config.languages)
+            .context("Invalid languages for the Whisper model")?;
+        info!(
+            "🌐 Language routing: {} → {}, {} → Whisper-{}",
+            config.languages[0],
+            primary.model_name(),
+            config.languages[1..].join(", "),
+            whisper.size()
+        );
+
+        Ok(Some(Self {
+            whisper: SttEngine::Whisper(whisper),
+            primary: config.languages[0].clone(),
+        }))
+    }
+
+    /// Whisper set to the language of `samples`, or `None` if the segment
+    /// belongs to the primary model
+    pub fn route(&mut self, samples: &[f32]) -> Option<&mut SttEngine> {
+        let detected = match self.whisper.detect_language(samples) {
+            Ok(Some(detected)) => detected,
+            Ok(None) => return None,
+            Err(e) => {
+                warn!("Language detection failed: {}", e);
+                return None;
+            }
+        };
+        debug!(
+            "Segment language: {} ({:.0}%)",
+            detected.code,
+            detected.probability * 100.0
+        );
+        if detected.code == self.primary || detected.probability < MIN_PROBABILITY {
+            return None;
+        }
+        if let Err(e) = self.whisper.set_language(Some(&detected.code)) {
+            warn!("Cannot transcribe {} with Whisper: {}", detected.code, e);
+            return None;
+        }
+        Some(&mut self.whisper)
+    }
+}
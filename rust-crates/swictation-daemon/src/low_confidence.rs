@@ -0,0 +1,143 @@
+//! Words the recognizer was unsure of
+//!
+//! STT reports a confidence for each word it heard (see
+//! `swictation_stt::confidence`). Words below `low_confidence_threshold` are
+//! sent to UI clients and, with `mark_low_confidence`, typed as `[[word]]`
+//! so a quick review can skip everything the model was sure about.
+
+use swictation_broadcaster::UncertainWord;
+use swictation_stt::WordConfidence;
+
+/// Words below `threshold`, in spoken order (none when `threshold` is 0)
+pub fn uncertain_words(words: &[WordConfidence], threshold: f32) -> Vec<UncertainWord> {
+    words
+        .iter()
+        .filter(|w| w.confidence < threshold)
+        .map(|w| UncertainWord {
+            word: w.word.clone(),
+            confidence: w.confidence,
+        })
+        .collect()
+}
+
+/// Wrap the uncertain words of `text` in `[[...]]`
+///
+/// `text` is the post-processed transcription, so words are matched in
+/// order ignoring case and punctuation; a word that post-processing changed
+/// beyond that (a correction, a masked word) is left unmarked.
+pub fn mark(text: &str, uncertain: &[UncertainWord]) -> String {
+    let spans = word_spans(text);
+    let mut marked: Vec<bool> = vec![false; spans.len()];
+    let mut next = 0;
+    for word in uncertain {
+        let key = match_key(&word.word);
+        if key.is_empty() {
+            continue;
+        }
+        if let Some(i) = (next..spans.len()).find(|&i| match_key(&text[spans[i].clone()]) == key) {
+            marked[i] = true;
+            next = i + 1;
+        }
+    }
+
+    let mut out = String::with_capacity(text.len() + 4 * uncertain.len());
+    let mut last = 0;
+    for (span, _) in spans.iter().zip(&marked).filter(|(_, &m)| m) {
+        let word = &text[span.clone()];
+        // Keep surrounding punctuation outside the markers: "[[quay]]."
+        let core_start = word.find(char::is_alphanumeric).unwrap_or(0);
+        let core_end = word.rfind(char::is_alphanumeric).map_or(word.len(), |i| {
+            i + word[i..].chars().next().unwrap().len_utf8()
+        });
+        out.push_str(&text[last..span.start + core_start]);
+        out.push_str("[[");
+        out.push_str(&word[core_start..core_end]);
+        out.push_str("]]");
+        last = span.start + core_end;
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+/// Byte ranges of the whitespace-separated words of `text`
+fn word_spans(text: &str) -> Vec<std::ops::Range<usize>> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        match (c.is_whitespace(), start) {
+            (true, Some(s)) => {
+                spans.push(s..i);
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        spans.push(s..text.len());
+    }
+    spans
+}
+
+fn match_key(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uncertain(words: &[&str]) -> Vec<UncertainWord> {
+        words
+            .iter()
+            .map(|w| UncertainWord {
+                word: w.to_string(),
+                confidence: 0.2,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_uncertain_words() {
+        let words = vec![
+            WordConfidence {
+                word: "meet".to_string(),
+                confidence: 0.9,
+                start_s: None,
+            },
+            WordConfidence {
+                word: "quay".to_string(),
+                confidence: 0.3,
+                start_s: None,
+            },
+        ];
+        let found = uncertain_words(&words, 0.5);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].word, "quay");
+        assert!(uncertain_words(&words, 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_mark_keeps_punctuation_outside() {
+        assert_eq!(
+            mark("Meet me at the quay.", &uncertain(&["quay."])),
+            "Meet me at the [[quay]]."
+        );
+        assert_eq!(
+            mark("the cat and the dog\n", &uncertain(&["the", "the"])),
+            "[[the]] cat and [[the]] dog\n"
+        );
+    }
+
+    #[test]
+    fn test_mark_skips_words_changed_by_post_processing() {
+        // "arkon" was corrected to "archon"; "world" is still marked
+        assert_eq!(
+            mark("Hello, archon world", &uncertain(&["arkon", "world"])),
+            "Hello, archon [[world]]"
+        );
+    }
+}
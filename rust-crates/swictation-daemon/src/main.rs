@@ -4,17 +4,60 @@
 //! Communicates via Unix socket (/tmp/swictation.sock) for toggle commands.
 //! Sway hotkey → socket toggle → start/stop recording (zero latency)
 
+mod audio_check;
+mod bugreport;
 mod capitalization;
+mod commands;
 mod config;
+mod config_cli;
+mod config_schema;
+mod context_training;
 mod corrections;
+mod credentials;
+mod desktop_shortcuts;
+mod diarization;
 mod display_server;
+mod doctor;
+mod editor;
+mod error_log;
 mod gpu;
+mod headless;
+mod history;
+mod hooks;
 mod hotkey;
+mod hotkey_conflicts;
 mod ipc;
+mod itn;
+mod jobs;
+mod language_model;
+mod language_routing;
+mod low_confidence;
+mod meeting;
+mod note_sink;
+mod notification;
+mod output;
+mod overlap;
+mod pacing;
+mod paragraph;
 mod pipeline;
+mod playback;
+mod readalong;
+mod readiness;
+mod recordings;
+mod retry;
+#[cfg(target_os = "linux")]
+mod seats;
 mod socket_utils;
+mod spell;
+mod stages;
+mod stt_watchdog;
+mod sync;
 mod text_injection;
+mod tts;
 mod version;
+mod watch_folder;
+mod weekly_summary;
+mod word_filter;
 
 // macOS text injection module (conditional compilation)
 #[cfg(target_os = "macos")]
@@ -25,13 +68,19 @@ mod macos_text_inject;
 mod macos_audio_permission;
 
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::path::PathBuf;
-use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
-use tracing::{error, info, warn};
-
-use crate::config::DaemonConfig;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot, RwLock};
+use tracing::{debug, error, info, warn};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+use crate::commands::EditCommand;
+use crate::config::{DaemonConfig, MeetingConfig, OutputSink};
 
 /// Swictation Daemon - Voice-to-Text Pipeline
 #[derive(Parser, Debug)]
@@ -41,26 +90,211 @@ use crate::config::DaemonConfig;
 struct CliArgs {
     /// Override STT model selection (bypasses auto-detection)
     #[arg(long, value_name = "MODEL")]
-    #[arg(value_parser = ["0.6b-cpu", "0.6b-gpu", "1.1b-gpu"])]
+    #[arg(value_parser = ["0.6b-cpu", "0.6b-gpu", "1.1b-cpu", "1.1b-gpu", "whisper-cpu", "whisper-gpu"])]
     test_model: Option<String>,
 
     /// Dry-run: show model selection without loading models
     #[arg(long)]
     dry_run: bool,
 
+    /// Show live microphone levels and VAD speech probability for SECONDS
+    /// (default 10), then exit; no STT model needed
+    #[arg(long, value_name = "SECONDS", num_args = 0..=1, default_missing_value = "10")]
+    audio_check: Option<u64>,
+
+    /// Load everything the daemon needs, report each startup stage, then
+    /// exit: 0 when all passed, 10-16 for the first stage that failed
+    /// (paths-ok, config-ok, audio-ok, vad-ok, stt-loading, stt-ready,
+    /// sockets-ready)
+    #[arg(long, conflicts_with_all = ["dry_run", "audio_check", "headless"])]
+    self_test: bool,
+
     /// Show detailed version information
     #[arg(long)]
     version_info: bool,
+
+    /// Transcribe PCM from --input to stdout instead of using hotkeys and typing
+    #[arg(long)]
+    headless: bool,
+
+    /// Headless audio source: `stdin` or `fifo:<path>` (16 kHz mono s16le PCM)
+    #[arg(long, value_name = "SOURCE", requires = "headless")]
+    input: Option<HeadlessInput>,
+
+    /// Run as a system service (as root) with a separate daemon for every
+    /// logged-in user, each with its own sockets and database (Linux)
+    #[arg(long, conflicts_with_all = ["dry_run", "audio_check", "self_test", "headless"])]
+    system: bool,
+
+    /// With --system, only serve this user (name or UID); repeatable
+    #[arg(long = "seat-user", value_name = "USER", requires = "system")]
+    seat_users: Vec<String>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
 }
+
+/// Maintenance subcommands (run instead of starting the daemon)
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check models, GPU, display server and sockets
+    Doctor {
+        /// Also print the running daemon's recent VAD probabilities
+        #[arg(long)]
+        vad_trace: bool,
+    },
+
+    /// Write a local tar.gz with diagnostics for attaching to bug reports (never uploaded)
+    Bugreport {
+        /// Output archive path (default: ./swictation-bugreport-<timestamp>.tar.gz)
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+
+        /// Add the anonymous usage summary even if bugreport_analytics is off
+        #[arg(long)]
+        analytics: bool,
+    },
+
+    /// Print last week's summary digest (generating it if needed)
+    WeeklySummary,
+
+    /// Read, change or check the config file without hand-editing TOML
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// Manage the desktop shortcuts installed on GNOME and Sway
+    Hotkeys {
+        #[command(subcommand)]
+        action: HotkeysAction,
+    },
+
+    /// Manage API keys in the OS keyring (referenced from config as `secret:<name>`)
+    Secret {
+        #[command(subcommand)]
+        action: SecretAction,
+    },
+
+    /// Remove duplicated segments from the metrics database and fix the totals they inflated
+    Dedupe {
+        /// Only report what would be removed
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Rebuild lifetime stats from stored sessions and report inconsistent sessions
+    RecomputeStats {
+        /// Also fix the inconsistent sessions before rebuilding
+        #[arg(long)]
+        repair: bool,
+    },
+
+    /// Fill in missing median/p95 session latency from stored segments
+    BackfillLatency {
+        /// Recompute every ended session, not only those missing percentiles
+        #[arg(long)]
+        all: bool,
+        /// Only report what would be updated
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Merge sessions split by accidental toggles into the earliest of them
+    MergeSessions {
+        /// Session IDs (at least two)
+        #[arg(required = true, num_args = 2..)]
+        ids: Vec<i64>,
+    },
+
+    /// Split a session in two at a point in time
+    SplitSession {
+        id: i64,
+        /// Unix seconds or local "YYYY-MM-DD HH:MM:SS"; segments from then on
+        /// go to the new session
+        at: String,
+    },
+
+    /// Print the running daemon's recent warnings and errors
+    Errors {
+        /// How many to show, most recent first
+        #[arg(short = 'n', long, default_value_t = error_log::DEFAULT_LIMIT)]
+        limit: usize,
+    },
+
+    /// Convert a local FP32 Parakeet model to INT8, calibrated on your recorded segments
+    Quantize {
+        /// Model directory with encoder.onnx, decoder.onnx and joiner.onnx
+        /// (default: the configured 1.1B model)
+        #[arg(value_name = "MODEL_DIR")]
+        model_dir: Option<PathBuf>,
+        /// Most recent recorded segments to calibrate on (0 quantizes every layer)
+        #[arg(long, default_value_t = 20)]
+        segments: usize,
+        /// Keep encoder layers that quantize below this SQNR (dB) in FP32
+        #[arg(long, default_value_t = swictation_stt::quantize::DEFAULT_MIN_SQNR_DB)]
+        min_sqnr: f32,
+    },
+}
+
+/// `config` subcommands
+#[derive(Subcommand, Debug)]
+enum ConfigAction {
+    /// Print a value, e.g. `config get vad.threshold`
+    Get { key: String },
+
+    /// Change a value after validating it, e.g. `config set stt_model_override 1.1b-gpu`
+    Set { key: String, value: String },
+
+    /// Check the config file for unknown keys, bad values and deprecated
+    /// settings, reporting all of them at once
+    Validate,
+
+    /// List settings that differ from the defaults
+    Diff,
+}
+
+/// `hotkeys` subcommands
+#[derive(Subcommand, Debug)]
+enum HotkeysAction {
+    /// Remove the GNOME shortcut and Sway bindings added by the daemon
+    Uninstall,
+}
+
+/// `secret` subcommands
+#[derive(Subcommand, Debug)]
+enum SecretAction {
+    /// Store a secret; reads it from stdin when VALUE is omitted
+    Set { name: String, value: Option<String> },
+
+    /// Print a stored secret
+    Get { name: String },
+
+    /// Remove a stored secret
+    Delete { name: String },
+}
+use crate::context_training::ContextTraining;
+use crate::diarization::Diarizer;
+use crate::editor::EditorHub;
+use crate::error_log::{ErrorEvent, ErrorLog, ErrorLogLayer};
 use crate::gpu::detect_gpu_provider;
+use crate::headless::{HeadlessInput, PcmReader};
+use crate::history::{HistoryEntry, TranscriptHistory};
+use crate::hooks::{HookEvent, HookRunner};
 use crate::hotkey::{HotkeyEvent, HotkeyManager};
+use crate::hotkey_conflicts::HotkeyConflict;
 use crate::ipc::{handle_connection as handle_ipc_connection, IpcServer};
+use crate::jobs::JobQueue;
+use crate::meeting::Meeting;
+use crate::note_sink::NoteSink;
+use crate::output::{DeliveryLog, OutputProfiles};
 use crate::pipeline::Pipeline;
-use swictation_broadcaster::MetricsBroadcaster;
-use swictation_context_learning::{
-    load_or_train_model, ContextModel, LearningConfig, RetrainingConfig,
+use crate::readalong::{Readalong, Script};
+use crate::readiness::Readiness;
+use swictation_broadcaster::{MetricsBroadcaster, ReadinessStage, ScriptPosition};
+use swictation_metrics::{
+    MemoryMonitor, MemoryPressure, MetricsDatabase, PRIVATE_SESSION_ID, TRASH_RETENTION_DAYS,
 };
-use swictation_metrics::{MemoryMonitor, MemoryPressure};
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum DaemonState {
@@ -73,42 +307,93 @@ struct Daemon {
     state: Arc<RwLock<DaemonState>>,
     broadcaster: Arc<MetricsBroadcaster>,
     session_id: Arc<RwLock<Option<i64>>>,
+    history: Arc<Mutex<TranscriptHistory>>,
+    /// New sessions are private (kept out of the database) while set
+    private_mode: Arc<AtomicBool>,
+    /// When the private session in progress started
+    private_since: Mutex<Option<Instant>>,
+    hooks: Arc<HookRunner>,
+    editors: Arc<EditorHub>,
+    /// Which sinks segments are delivered to
+    outputs: Arc<OutputProfiles>,
+    deliveries: DeliveryLog,
+    meeting_config: MeetingConfig,
+    /// The meeting being transcribed in meeting mode
+    meeting: Mutex<Option<Meeting>>,
+    jobs: JobQueue,
+    training: Arc<ContextTraining>,
+    /// Configured hotkeys that are probably taken, reported by `status`
+    hotkey_conflicts: Vec<HotkeyConflict>,
+    /// Recent warnings and errors for `get_recent_errors`
+    error_log: Arc<ErrorLog>,
 }
 
 impl Daemon {
     async fn new(
         config: DaemonConfig,
         gpu_provider: Option<String>,
+        error_log: Arc<ErrorLog>,
+        broadcaster: Arc<MetricsBroadcaster>,
+        readiness: &Readiness,
     ) -> Result<(Self, mpsc::Receiver<Result<String>>)> {
-        let (pipeline, transcription_rx) = Pipeline::new(config, gpu_provider).await?;
-
-        // Initialize metrics broadcaster with secure socket path
-        let metrics_socket =
-            socket_utils::get_metrics_socket_path().context("Failed to get metrics socket path")?;
-        let broadcaster = Arc::new(
-            MetricsBroadcaster::new(&metrics_socket)
-                .await
-                .context("Failed to create metrics broadcaster")?,
+        let history = TranscriptHistory::new(
+            config.history_size,
+            Duration::from_secs(config.history_ttl_secs),
         );
+        let hooks = HookRunner::new(config.hooks.clone());
+        let outputs = OutputProfiles::new(config.output.clone());
+        let meeting_config = config.meeting.clone();
+        let job_vad_config = jobs::file_vad_config(&config);
+        let job_diarizer = Diarizer::new(&config.diarization);
+        let (pipeline, transcription_rx) = Pipeline::new(config, gpu_provider, readiness).await?;
 
         // Set broadcaster in pipeline for real-time updates
         pipeline.set_broadcaster(broadcaster.clone());
 
+        let state = Arc::new(RwLock::new(DaemonState::Idle));
+        let jobs = JobQueue::spawn(
+            job_vad_config,
+            state.clone(),
+            pipeline.transcriber(),
+            job_diarizer,
+            pipeline.get_metrics().lock().unwrap().database(),
+            broadcaster.clone(),
+        );
+
+        let metrics_db = pipeline.get_metrics().lock().unwrap().database();
+        match metrics_db.purge_trash(TRASH_RETENTION_DAYS) {
+            Ok(purged) if purged.sessions == 0 => {}
+            Ok(purged) => {
+                recordings::delete_segments(&purged.segment_ids);
+                info!(
+                    "🗑️  Purged {} sessions deleted over {} days ago",
+                    purged.sessions, TRASH_RETENTION_DAYS
+                );
+            }
+            Err(e) => warn!("Failed to purge deleted sessions: {:#}", e),
+        }
+
         #[allow(clippy::arc_with_non_send_sync)]
         let daemon = Self {
             pipeline: Arc::new(RwLock::new(pipeline)),
-            state: Arc::new(RwLock::new(DaemonState::Idle)),
+            state,
             broadcaster: broadcaster.clone(),
             session_id: Arc::new(RwLock::new(None)),
+            history: Arc::new(Mutex::new(history)),
+            private_mode: Arc::new(AtomicBool::new(false)),
+            private_since: Mutex::new(None),
+            hooks: Arc::new(hooks),
+            editors: Arc::new(EditorHub::new()),
+            outputs: Arc::new(outputs),
+            deliveries: DeliveryLog::new(metrics_db),
+            meeting_config,
+            meeting: Mutex::new(None),
+            jobs,
+            training: Arc::new(ContextTraining::new()),
+            hotkey_conflicts: Vec::new(),
+            error_log,
         };
 
-        // Start broadcaster Unix socket server
-        daemon
-            .broadcaster
-            .start()
-            .await
-            .context("Failed to start metrics broadcaster")?;
-
         Ok((daemon, transcription_rx))
     }
 
@@ -127,95 +412,551 @@ impl Daemon {
 
         match current_state {
             DaemonState::Idle => {
-                info!("▶️ Starting recording");
-
-                // Phase 2: Start session and get metrics (short lock scope)
-                let sid = {
-                    let pipeline = self.pipeline.read().await;
-                    let metrics = pipeline.get_metrics();
-                    let sid = metrics.lock().unwrap().start_session()?;
-                    sid
-                };
+                let (sid, _) = self.start(None).await?;
+                Ok(format!("Recording started (Session #{})", sid))
+            }
+            DaemonState::Recording => self.stop().await,
+        }
+    }
 
-                // Phase 3: Update state and start recording
-                {
-                    let mut state = self.state.write().await;
-                    let mut pipeline = self.pipeline.write().await;
-                    let mut session_id = self.session_id.write().await;
+    /// Start a session, from the microphone or (headless) from PCM input
+    ///
+    /// Returns the session ID and, for PCM input, a receiver that resolves
+    /// once the input has been processed.
+    async fn start(
+        &self,
+        input: Option<PcmReader>,
+    ) -> Result<(i64, Option<oneshot::Receiver<()>>)> {
+        info!("▶️ Starting recording");
+
+        // Phase 2: Start session and get metrics (short lock scope)
+        let sid = {
+            let pipeline = self.pipeline.read().await;
+            let metrics = pipeline.get_metrics();
+            let metrics = metrics.lock().unwrap();
+            if self.private_mode() {
+                *self.private_since.lock().unwrap() = Some(Instant::now());
+                metrics.start_private_session()
+            } else {
+                metrics.start_session()?
+            }
+        };
 
-                    *session_id = Some(sid);
-                    pipeline.set_session_id(sid);
+        // Phase 3: Update state and start recording
+        let drained = {
+            let mut state = self.state.write().await;
+            let mut pipeline = self.pipeline.write().await;
+            let mut session_id = self.session_id.write().await;
+
+            *session_id = Some(sid);
+            pipeline.set_session_id(sid);
+            let drained = match input {
+                Some(input) => pipeline.start_recording_from(input).await?,
+                None => {
                     pipeline.start_recording().await?;
-                    *state = DaemonState::Recording;
+                    None
                 }
-                // Locks released here before broadcast
+            };
+            *state = DaemonState::Recording;
+            drained
+        };
+        // Locks released here before broadcast
 
-                // Phase 4: Broadcast (no locks held - prevents deadlock with metrics updater)
-                // CRITICAL: Spawn broadcasts to prevent blocking IPC responses
-                // Broadcasting to UI clients can block if clients are slow/disconnected
-                // By spawning, we return immediately and let broadcasts happen async
-                {
-                    let broadcaster = Arc::clone(&self.broadcaster);
-                    tokio::spawn(async move {
-                        broadcaster.start_session(sid).await;
-                        broadcaster
-                            .broadcast_state_change(swictation_metrics::DaemonState::Recording)
-                            .await;
-                    });
-                }
+        // Dictation gets the CPU back right away
+        if self.training.cancel() {
+            info!("⏹️ Cancelling background context training");
+        }
 
-                Ok(format!("Recording started (Session #{})", sid))
+        // Phase 4: Broadcast (no locks held - prevents deadlock with metrics updater)
+        // CRITICAL: Spawn broadcasts to prevent blocking IPC responses
+        // Broadcasting to UI clients can block if clients are slow/disconnected
+        // By spawning, we return immediately and let broadcasts happen async
+        {
+            let broadcaster = Arc::clone(&self.broadcaster);
+            tokio::spawn(async move {
+                broadcaster.start_session(sid).await;
+                broadcaster
+                    .broadcast_state_change(swictation_metrics::DaemonState::Recording)
+                    .await;
+            });
+        }
+        self.hooks.fire(
+            HookEvent::SessionStart,
+            serde_json::json!({ "session_id": sid }),
+        );
+
+        Ok((sid, drained))
+    }
+
+    /// End the current session
+    async fn stop(&self) -> Result<String> {
+        info!("⏸️ Stopping recording");
+
+        // Phase 2: Stop recording (this does STT inference - can take 50-500ms)
+        // We MUST release state lock before this to prevent deadlock
+        let meeting = self.meeting.lock().unwrap().take();
+        {
+            let mut pipeline = self.pipeline.write().await;
+            pipeline.stop_recording().await?;
+            pipeline.clear_session_id();
+            pipeline.clear_session_bias();
+            if meeting.is_some() {
+                pipeline.set_meeting(None, None);
             }
-            DaemonState::Recording => {
-                info!("⏸️ Stopping recording");
+        }
+        // Pipeline lock released before we touch state
 
-                // Phase 2: Stop recording (this does STT inference - can take 50-500ms)
-                // We MUST release state lock before this to prevent deadlock
-                {
-                    let mut pipeline = self.pipeline.write().await;
-                    pipeline.stop_recording().await?;
-                    pipeline.clear_session_id();
+        // Phase 3: Update state and end session
+        let (session_metrics, sid) = {
+            let mut state = self.state.write().await;
+            let pipeline = self.pipeline.read().await;
+            let mut session_id = self.session_id.write().await;
+
+            *state = DaemonState::Idle;
+
+            let metrics = pipeline.get_metrics();
+            let session_metrics = metrics.lock().unwrap().end_session()?;
+            let sid = *session_id;
+            *session_id = None;
+
+            (session_metrics, sid)
+        };
+        // All locks released before broadcast
+
+        // A private session leaves no transcript behind
+        let private_since = self.private_since.lock().unwrap().take();
+        if let Some(since) = private_since {
+            self.history.lock().unwrap().forget_since(since);
+            self.pipeline.read().await.clear_edit_history();
+        }
+
+        // Phase 4: Broadcast (no locks held)
+        // CRITICAL: Spawn broadcasts to prevent blocking IPC responses
+        // Same rationale as start_recording - avoid blocking on slow clients
+        {
+            let broadcaster = Arc::clone(&self.broadcaster);
+            tokio::spawn(async move {
+                if private_since.is_some() {
+                    broadcaster.clear_transcriptions().await;
+                }
+                if let Some(sid) = sid {
+                    broadcaster.end_session(sid).await;
                 }
-                // Pipeline lock released before we touch state
+                broadcaster
+                    .broadcast_state_change(swictation_metrics::DaemonState::Idle)
+                    .await;
+            });
+        }
+        self.hooks.fire(
+            HookEvent::SessionEnd,
+            serde_json::json!({
+                "session_id": sid,
+                "words": session_metrics.words_dictated,
+                "wpm": session_metrics.words_per_minute,
+                "duration_s": session_metrics.total_duration_s,
+            }),
+        );
+
+        let stopped = format!(
+            "Recording stopped ({} words, {:.1} WPM)",
+            session_metrics.words_dictated, session_metrics.words_per_minute
+        );
+        let Some(meeting) = meeting else {
+            return Ok(stopped);
+        };
+        let path = meeting
+            .finish()
+            .await
+            .context("Failed to write the meeting transcript")?;
+        info!("📝 Meeting transcript written to {}", path.display());
+        Ok(format!(
+            "{}, meeting transcript in {}",
+            stopped,
+            path.display()
+        ))
+    }
 
-                // Phase 3: Update state and end session
-                let (session_metrics, sid) = {
-                    let mut state = self.state.write().await;
-                    let pipeline = self.pipeline.read().await;
-                    let mut session_id = self.session_id.write().await;
+    /// Start meeting mode, ending any dictation in progress
+    async fn start_meeting(&self) -> Result<String> {
+        if self.meeting.lock().unwrap().is_some() {
+            anyhow::bail!("Meeting mode is already on");
+        }
+        if *self.state.read().await == DaemonState::Recording {
+            self.stop().await?;
+        }
 
-                    *state = DaemonState::Idle;
+        let (meeting, output) = Meeting::start(&self.meeting_config);
+        self.pipeline.write().await.set_meeting(
+            Some(output),
+            self.meeting_config.system_audio_device.clone(),
+        );
+        *self.meeting.lock().unwrap() = Some(meeting);
+        match self.start(None).await {
+            Ok((sid, _)) => {
+                info!("🎙️ Meeting mode on (Session #{})", sid);
+                Ok(format!("Meeting mode on (Session #{})", sid))
+            }
+            Err(e) => {
+                self.meeting.lock().unwrap().take();
+                self.pipeline.write().await.set_meeting(None, None);
+                Err(e)
+            }
+        }
+    }
 
-                    let metrics = pipeline.get_metrics();
-                    let session_metrics = metrics.lock().unwrap().end_session()?;
-                    let sid = *session_id;
-                    *session_id = None;
+    /// End meeting mode and write the meeting document
+    async fn stop_meeting(&self) -> Result<String> {
+        if self.meeting.lock().unwrap().is_none() {
+            anyhow::bail!("Meeting mode is not on");
+        }
+        self.stop().await
+    }
 
-                    (session_metrics, sid)
-                };
-                // All locks released before broadcast
+    /// When the meeting in progress reaches `meeting.max_minutes`
+    fn meeting_deadline(&self) -> Option<Instant> {
+        self.meeting.lock().unwrap().as_ref().map(Meeting::deadline)
+    }
 
-                // Phase 4: Broadcast (no locks held)
-                // CRITICAL: Spawn broadcasts to prevent blocking IPC responses
-                // Same rationale as start_recording - avoid blocking on slow clients
-                {
-                    let broadcaster = Arc::clone(&self.broadcaster);
-                    tokio::spawn(async move {
-                        if let Some(sid) = sid {
-                            broadcaster.end_session(sid).await;
-                        }
-                        broadcaster
-                            .broadcast_state_change(swictation_metrics::DaemonState::Idle)
-                            .await;
-                    });
+    /// Load a script to rehearse: segments follow it instead of being typed
+    /// until [`stop_readalong`](Self::stop_readalong), returning its word
+    /// and line counts
+    async fn start_readalong(
+        &self,
+        script: Option<String>,
+        path: Option<PathBuf>,
+    ) -> Result<(usize, usize)> {
+        let text = match (script, path) {
+            (Some(script), _) => script,
+            (None, Some(path)) => std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read script {}", path.display()))?,
+            (None, None) => anyhow::bail!("No script given"),
+        };
+        let script = Script::new(&text);
+        let (words, lines) = (script.words().len(), script.line_count());
+        if words == 0 {
+            anyhow::bail!("The script has no words");
+        }
+
+        let pipeline = self.pipeline.read().await;
+        if pipeline
+            .set_readalong(Some(Readalong::new(script)))
+            .is_some()
+        {
+            info!("📜 Replaced the readalong script");
+        }
+        info!("📜 Readalong on: {} words on {} lines", words, lines);
+        Ok((words, lines))
+    }
+
+    /// Return to dictation, reporting how far the script was read
+    ///
+    /// The run is stored as a practice session unless private mode is on.
+    async fn stop_readalong(&self) -> Result<ScriptPosition> {
+        let pipeline = self.pipeline.read().await;
+        let mut readalong = pipeline
+            .set_readalong(None)
+            .context("Readalong mode is not on")?;
+        let position = readalong.position();
+        info!(
+            "📜 Readalong off at word {}/{}, {:.0}% read as written, pronunciation {:.0}",
+            position.word,
+            position.total_words,
+            position.accuracy * 100.0,
+            position.pronunciation_score
+        );
+
+        if position.word > 0 && !self.private_mode() {
+            let database = pipeline.get_metrics().lock().unwrap().database();
+            if let Err(e) = database.insert_practice_session(&readalong.practice()) {
+                warn!("Failed to store practice session: {}", e);
+            }
+        }
+        Ok(position)
+    }
+
+    fn private_mode(&self) -> bool {
+        self.private_mode.load(Ordering::SeqCst)
+    }
+
+    /// Turn private mode on or off (`None` toggles it), returning the new mode
+    ///
+    /// A recording in progress is split into two sessions, so the part
+    /// spoken before the switch keeps the mode it was started in. A meeting
+    /// is not split: the new mode applies from the next session.
+    async fn set_private_mode(&self, enabled: Option<bool>) -> Result<bool> {
+        let enabled = enabled.unwrap_or(!self.private_mode());
+        if self.private_mode.swap(enabled, Ordering::SeqCst) == enabled {
+            return Ok(enabled);
+        }
+        info!(
+            "🕶️ Private mode {}",
+            if enabled {
+                "on - sessions are not saved"
+            } else {
+                "off"
+            }
+        );
+
+        let broadcaster = Arc::clone(&self.broadcaster);
+        tokio::spawn(async move {
+            broadcaster.broadcast_private_mode(enabled).await;
+        });
+
+        let in_meeting = self.meeting.lock().unwrap().is_some();
+        if *self.state.read().await == DaemonState::Recording && !in_meeting {
+            self.stop().await?;
+            self.start(None).await?;
+        }
+        Ok(enabled)
+    }
+
+    /// Retune the live VAD and notify UI clients
+    ///
+    /// With `persist`, the resulting values are also written to the config
+    /// file so they survive a restart.
+    async fn set_vad(
+        &self,
+        threshold: Option<f32>,
+        min_silence: Option<f32>,
+        min_speech: Option<f32>,
+        persist: bool,
+    ) -> Result<(f32, f32, f32)> {
+        let (threshold, min_silence, min_speech) = {
+            let pipeline = self.pipeline.read().await;
+            pipeline.set_vad_tuning(threshold, min_silence, min_speech)?
+        };
+
+        if persist {
+            // Reload from disk so CLI overrides of this run are not persisted
+            let mut config = DaemonConfig::load().context("Failed to load configuration")?;
+            config.vad_threshold = threshold;
+            config.vad_min_silence = min_silence;
+            config.vad_min_speech = min_speech;
+            config.save().context("Failed to save VAD settings")?;
+            info!("💾 VAD settings saved to {}", config.config_path.display());
+        }
+
+        let broadcaster = Arc::clone(&self.broadcaster);
+        tokio::spawn(async move {
+            broadcaster
+                .broadcast_vad_config(threshold, min_silence, min_speech, persist)
+                .await;
+        });
+
+        Ok((threshold, min_silence, min_speech))
+    }
+
+    /// Bias recognition toward `phrases` until the current (or next)
+    /// recording stops; an empty list clears them early
+    async fn set_bias_phrases(&self, phrases: Vec<String>) -> Result<usize> {
+        let pipeline = self.pipeline.read().await;
+        pipeline.set_session_bias(phrases)
+    }
+
+    /// Pick up changes to the lexicon file without a restart
+    async fn reload_lexicon(&self) -> Result<usize> {
+        let pipeline = self.pipeline.read().await;
+        pipeline.reload_lexicon()
+    }
+
+    /// Recent VAD probabilities as JSON for `dump_vad_trace`
+    async fn vad_trace(&self) -> Result<serde_json::Value> {
+        let pipeline = self.pipeline.read().await;
+        let (trace, threshold) = pipeline.vad_trace()?;
+        let points: Vec<serde_json::Value> = trace
+            .iter()
+            .map(|p| serde_json::json!({ "timestamp": p.timestamp, "probability": p.probability }))
+            .collect();
+        Ok(serde_json::json!({ "threshold": threshold, "points": points }))
+    }
+
+    /// Re-transcribe a recorded segment (see `Pipeline::retry_segment`)
+    ///
+    /// Loading another model takes seconds, so it happens on a blocking
+    /// thread before the pipeline lock is taken; holding the lock that long
+    /// would stall starting and stopping a recording. Recognition itself runs
+    /// under the read lock, as a live segment does.
+    async fn retry_segment(
+        &self,
+        segment_id: i64,
+        model: Option<String>,
+    ) -> Result<retry::RetryResult> {
+        let engine = match model {
+            Some(spec) => {
+                let switcher = self.pipeline.read().await.model_switcher();
+                tokio::task::spawn_blocking(move || switcher.load_for_retry(&spec)).await??
+            }
+            None => None,
+        };
+        let pipeline = self.pipeline.read().await;
+        // Inference blocks - keep it off the async workers
+        tokio::task::block_in_place(|| pipeline.retry_segment(segment_id, engine))
+    }
+
+    /// Load another STT model in place of the live one (see
+    /// `ModelSwitcher::switch`), on a blocking thread without the pipeline
+    /// lock so recording can start and stop meanwhile
+    async fn switch_model(&self, model: &str) -> Result<String> {
+        let switcher = self.pipeline.read().await.model_switcher();
+        let model = model.to_string();
+        tokio::task::spawn_blocking(move || switcher.switch(&model)).await?
+    }
+
+    /// Swap in a lighter STT model under VRAM pressure (see
+    /// `ModelSwitcher::downgrade_for_vram`), sending on `done` when finished
+    ///
+    /// The model load takes seconds, so it runs on a blocking thread without
+    /// the pipeline lock; the main loop keeps serving hotkeys and IPC.
+    async fn downgrade_for_vram(&self, vram_percent: f32, done: mpsc::UnboundedSender<()>) {
+        let switcher = self.pipeline.read().await.model_switcher();
+        let broadcaster = Arc::clone(&self.broadcaster);
+        tokio::spawn(async move {
+            let result = tokio::task::spawn_blocking(move || switcher.downgrade_for_vram())
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|result| result);
+            match result {
+                Ok(Some((from, to))) => {
+                    warn!("⬇️ STT model switched {} → {} (VRAM pressure)", from, to);
+                    broadcaster
+                        .broadcast_model_downgraded(&from, &to, vram_percent)
+                        .await;
                 }
+                Ok(None) => {}
+                Err(e) => error!("Failed to switch to a lighter STT model: {:#}", e),
+            }
+            let _ = done.send(());
+        });
+    }
+
+    /// Swap a typed segment for a decoder alternative (see `Pipeline::use_alternative`)
+    async fn use_alternative(&self, segment_id: i64, index: usize) -> Result<String> {
+        let pipeline = self.pipeline.read().await;
+        pipeline.use_alternative(segment_id, index).await
+    }
 
-                Ok(format!(
-                    "Recording stopped ({} words, {:.1} WPM)",
-                    session_metrics.words_dictated, session_metrics.words_per_minute
-                ))
+    /// Carry out a voice control command and announce the result
+    async fn voice_command(&self, command: EditCommand) {
+        let reply = match command {
+            EditCommand::StopDictation => {
+                if *self.state.read().await == DaemonState::Recording {
+                    self.toggle().await.map(|_| "Dictation stopped".to_string())
+                } else {
+                    Ok("Not dictating".to_string())
+                }
             }
+            EditCommand::SwitchProfile => self
+                .pipeline
+                .read()
+                .await
+                .next_filter_profile()
+                .map(|profile| format!("Filter profile {}", profile)),
+            EditCommand::WordCount => {
+                let pipeline = self.pipeline.read().await;
+                let metrics = pipeline
+                    .get_metrics()
+                    .lock()
+                    .unwrap()
+                    .get_realtime_metrics();
+                Ok(match metrics.words_this_session {
+                    1 => "1 word this session".to_string(),
+                    words => format!("{} words this session", words),
+                })
+            }
+            EditCommand::PrivateModeOn | EditCommand::PrivateModeOff => self
+                .set_private_mode(Some(command == EditCommand::PrivateModeOn))
+                .await
+                .map(|enabled| format!("Private mode {}", if enabled { "on" } else { "off" })),
+            EditCommand::MeetingModeOn => self.start_meeting().await,
+            EditCommand::MeetingModeOff => self
+                .stop_meeting()
+                .await
+                .map(|_| "Meeting mode off".to_string()),
+            _ => return,
+        };
+
+        let message = reply.unwrap_or_else(|e| format!("{:#}", e));
+        info!("🗣️ {:?}: {}", command, message);
+        self.pipeline.read().await.announce(&message);
+    }
+
+    /// Speak the last dictated segment (see `Pipeline::read_back`)
+    async fn read_back(&self) -> Result<String> {
+        self.pipeline.read().await.read_back()
+    }
+
+    /// Up to `limit` recent warnings and errors, most recent first
+    async fn metrics_db(&self) -> Arc<MetricsDatabase> {
+        self.pipeline
+            .read()
+            .await
+            .get_metrics()
+            .lock()
+            .unwrap()
+            .database()
+    }
+
+    /// See `MetricsDatabase::merge_sessions`
+    async fn merge_sessions(&self, ids: &[i64]) -> Result<i64> {
+        self.metrics_db().await.merge_sessions(ids)
+    }
+
+    /// See `MetricsDatabase::split_session`
+    async fn split_session(&self, id: i64, at: f64) -> Result<i64> {
+        self.metrics_db().await.split_session(id, at)
+    }
+
+    /// See `MetricsDatabase::archive_session`
+    async fn archive_session(&self, id: i64, archived: bool) -> Result<()> {
+        let db = self.metrics_db().await;
+        if archived {
+            db.archive_session(id)
+        } else {
+            db.unarchive_session(id)
+        }
+    }
+
+    /// See `MetricsDatabase::delete_session`
+    async fn trash_session(&self, id: i64, trashed: bool) -> Result<()> {
+        let db = self.metrics_db().await;
+        if trashed {
+            db.delete_session(id)
+        } else {
+            db.restore_session(id)
+        }
+    }
+
+    /// Permanently delete everything in the trash, recordings included,
+    /// returning the number of sessions removed
+    async fn empty_trash(&self) -> Result<usize> {
+        let purged = self.metrics_db().await.purge_trash(0)?;
+        recordings::delete_segments(&purged.segment_ids);
+        Ok(purged.sessions)
+    }
+
+    /// Switch to output profile `name` if given, returning the active
+    /// profile and its sinks
+    fn output_profile(&self, name: Option<&str>) -> Result<(String, Vec<OutputSink>)> {
+        if let Some(name) = name {
+            self.outputs.set_profile(name)?;
         }
+        Ok((self.outputs.profile(), self.outputs.sinks()))
+    }
+
+    fn recent_errors(&self, limit: usize) -> Vec<ErrorEvent> {
+        self.error_log.recent(limit)
+    }
+
+    fn history(&self) -> Vec<HistoryEntry> {
+        self.history.lock().unwrap().list()
+    }
+
+    fn history_entry(&self, n: usize) -> Result<String> {
+        self.history
+            .lock()
+            .unwrap()
+            .get(n)
+            .with_context(|| format!("No history entry {} (expired or never typed)", n))
     }
 
     async fn status(&self) -> String {
@@ -227,29 +968,332 @@ impl Daemon {
     }
 }
 
-/// Load or train context-aware learning model
-async fn load_context_model(_config: &DaemonConfig) -> Option<ContextModel> {
-    let data_dir = match dirs::data_local_dir() {
-        Some(dir) => dir.join("swictation"),
-        None => {
-            warn!("Failed to get data directory for context model");
-            return None;
+/// Handle `swictation-daemon config ...`
+fn run_config_command(action: ConfigAction) -> Result<()> {
+    let load = || DaemonConfig::load().context("Failed to load configuration");
+
+    match action {
+        ConfigAction::Get { key } => {
+            let config = load()?;
+            println!("{}", config_cli::display(&config_cli::get(&config, &key)?));
+        }
+        ConfigAction::Set { key, value } => {
+            let config = load()?;
+            let updated = config_cli::set(&config, &key, &value)?;
+            updated.save().context("Failed to save configuration")?;
+            println!(
+                "{} = {}",
+                key,
+                config_cli::display(&config_cli::get(&updated, &key)?)
+            );
+            eprintln!(
+                "Saved to {}. Restart the daemon to apply.",
+                updated.config_path.display()
+            );
         }
+        ConfigAction::Validate => {
+            // Not `load()`: it stops at the first kind of problem
+            let config_path = DaemonConfig::default_config_path();
+            let report = DaemonConfig::check_file()?.unwrap_or_else(|| config_schema::Report {
+                config: Some(DaemonConfig::default()),
+                ..Default::default()
+            });
+
+            let mut problems = report.errors;
+            if let Some(config) = &report.config {
+                if let Err(e) = config.validate() {
+                    problems.extend(e.to_string().lines().map(str::to_string));
+                }
+                for (key, name) in config_cli::secret_refs(config)? {
+                    match credentials::get(&name) {
+                        Ok(Some(_)) => {}
+                        Ok(None) => {
+                            problems.push(format!("{} refers to missing secret '{}'", key, name))
+                        }
+                        Err(e) => problems.push(format!("{}: {:#}", key, e)),
+                    }
+                }
+            }
+            for warning in &report.warnings {
+                eprintln!("⚠ {}", warning);
+            }
+
+            if problems.is_empty() {
+                println!("✓ {} is valid", config_path.display());
+            } else {
+                eprintln!("✗ {}:", config_path.display());
+                for problem in problems {
+                    eprintln!("  {}", problem);
+                }
+                std::process::exit(1);
+            }
+        }
+        ConfigAction::Diff => {
+            let changes = config_cli::diff(&load()?)?;
+            if changes.is_empty() {
+                println!("All settings are at their defaults");
+            }
+            let show = |v: &Option<toml::Value>| {
+                v.as_ref()
+                    .map(config_cli::display)
+                    .unwrap_or_else(|| "(unset)".to_string())
+            };
+            for change in changes {
+                println!(
+                    "{} = {}  (default: {})",
+                    change.key,
+                    show(&change.current),
+                    show(&change.default)
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Handle `swictation-daemon secret ...`
+fn run_secret_command(action: SecretAction) -> Result<()> {
+    match action {
+        SecretAction::Set { name, value } => {
+            let value = match value {
+                Some(value) => value,
+                None => {
+                    // Keeps the secret out of shell history
+                    let mut line = String::new();
+                    std::io::stdin()
+                        .read_line(&mut line)
+                        .context("Failed to read secret from stdin")?;
+                    line.trim_end_matches(['\r', '\n']).to_string()
+                }
+            };
+            credentials::set(&name, &value)?;
+            println!(
+                "Stored secret '{}'. Reference it in config as \"{}{}\"",
+                name,
+                credentials::SECRET_PREFIX,
+                name
+            );
+        }
+        SecretAction::Get { name } => match credentials::get(&name)? {
+            Some(value) => println!("{}", value),
+            None => {
+                eprintln!("No secret named '{}'", name);
+                std::process::exit(1);
+            }
+        },
+        SecretAction::Delete { name } => {
+            if credentials::delete(&name)? {
+                println!("Deleted secret '{}'", name);
+            } else {
+                println!("No secret named '{}'", name);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Open the metrics database the daemon records into
+fn open_metrics_db() -> Result<MetricsDatabase> {
+    let path = pipeline::metrics_db_path();
+    MetricsDatabase::new(&path)
+        .with_context(|| format!("Failed to open metrics database {}", path.display()))
+}
+
+/// One line describing a stored session
+fn session_line(db: &MetricsDatabase, id: i64) -> Result<String> {
+    let session = db
+        .get_session(id)?
+        .with_context(|| format!("Session {} not found", id))?;
+    Ok(format!(
+        "session {}: {} words in {} segments over {:.0}s, {:.0} WPM",
+        id,
+        session.words_dictated,
+        session.segments_processed,
+        session.total_duration_s,
+        session.words_per_minute
+    ))
+}
+
+/// Handle `swictation-daemon merge-sessions`
+fn run_merge_sessions_command(ids: &[i64]) -> Result<()> {
+    let db = open_metrics_db()?;
+    let merged = db.merge_sessions(ids)?;
+    println!("Merged into {}", session_line(&db, merged)?);
+    Ok(())
+}
+
+/// Unix seconds, or a local "YYYY-MM-DD HH:MM:SS" time
+fn parse_time(value: &str) -> Result<f64> {
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Ok(seconds);
+    }
+    let time = chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S"))
+        .with_context(|| format!("Invalid time '{}'", value))?;
+    let local = time
+        .and_local_timezone(chrono::Local)
+        .earliest()
+        .with_context(|| format!("'{}' does not exist in the local time zone", value))?;
+    Ok(local.timestamp() as f64)
+}
+
+/// Handle `swictation-daemon split-session`
+fn run_split_session_command(id: i64, at: &str) -> Result<()> {
+    let db = open_metrics_db()?;
+    let new_id = db.split_session(id, parse_time(at)?)?;
+    println!("Split into:");
+    println!("  {}", session_line(&db, id)?);
+    println!("  {}", session_line(&db, new_id)?);
+    Ok(())
+}
+
+/// Handle `swictation-daemon dedupe`
+fn run_dedupe_command(dry_run: bool) -> Result<()> {
+    let report = open_metrics_db()?.dedupe_segments(dry_run)?;
+    if report.duplicate_segments == 0 {
+        println!("No duplicate segments found");
+        return Ok(());
+    }
+    println!(
+        "{} {} duplicate segments in {} sessions ({} words)",
+        if dry_run { "Found" } else { "Removed" },
+        report.duplicate_segments,
+        report.sessions_affected,
+        report.words_removed
+    );
+    if dry_run {
+        println!("Run without --dry-run to remove them and recompute lifetime stats");
+    }
+    Ok(())
+}
+
+/// Handle `swictation-daemon recompute-stats`
+///
+/// Exits non-zero when discrepancies were found and left unrepaired.
+fn run_recompute_stats_command(repair: bool) -> Result<()> {
+    let report = open_metrics_db()?.recompute_stats(repair)?;
+    let ids = |ids: &[i64]| {
+        ids.iter()
+            .map(|id| format!("#{}", id))
+            .collect::<Vec<_>>()
+            .join(", ")
     };
+    for (label, sessions) in [
+        ("Dangling sessions (never ended)", &report.dangling_sessions),
+        ("Sessions without durations", &report.null_durations),
+        ("Sessions with impossible WPM", &report.impossible_wpm),
+    ] {
+        if !sessions.is_empty() {
+            println!("{}: {}", label, ids(sessions));
+        }
+    }
+    println!(
+        "Lifetime stats rebuilt: {} → {} words, {} → {} sessions",
+        report.total_words_before,
+        report.total_words_after,
+        report.total_sessions_before,
+        report.total_sessions_after
+    );
 
-    let model_path = data_dir.join("context-model.json");
-    let db_path = data_dir.join("metrics.db");
+    if report.is_clean() {
+        println!("No discrepancies found");
+    } else if repair {
+        println!("Repaired the sessions above");
+    } else {
+        println!("Run with --repair to fix the sessions above");
+        std::process::exit(1);
+    }
+    Ok(())
+}
 
-    let learning_config = LearningConfig::default();
-    let retrain_config = RetrainingConfig::default();
+/// Handle `swictation-daemon backfill-latency`
+fn run_backfill_latency_command(all: bool, dry_run: bool) -> Result<()> {
+    let report = open_metrics_db()?.backfill_latency_percentiles(all, dry_run)?;
+    if report.sessions_updated == 0 {
+        println!("No sessions need latency percentiles");
+        return Ok(());
+    }
+    println!(
+        "{} median/p95 latency for {} sessions from {} segments",
+        if dry_run {
+            "Would fill in"
+        } else {
+            "Filled in"
+        },
+        report.sessions_updated,
+        report.segments_read
+    );
+    if dry_run {
+        println!("Run without --dry-run to write them");
+    }
+    Ok(())
+}
 
-    match load_or_train_model(&model_path, &db_path, &learning_config, &retrain_config) {
-        Ok(model) => model,
-        Err(e) => {
-            warn!("Failed to load context model: {}", e);
-            None
+/// Handle `swictation-daemon quantize`
+fn run_quantize_command(model_dir: Option<PathBuf>, segments: usize, min_sqnr: f32) -> Result<()> {
+    let model_dir = match model_dir {
+        Some(dir) => dir,
+        None => {
+            DaemonConfig::load()
+                .context("Failed to load configuration")?
+                .stt_1_1b_model_path
+        }
+    };
+    let calibration = recordings::recent_recordings(segments);
+    if segments > 0 && calibration.is_empty() {
+        println!(
+            "⚠ No recorded segments in {} - quantizing every layer without calibration",
+            recordings::recordings_dir().display()
+        );
+        println!("  Enable save_recordings and dictate for a while for a calibrated model");
+    } else if !calibration.is_empty() {
+        println!("Calibrating on {} recorded segments", calibration.len());
+    }
+
+    let options = swictation_stt::quantize::QuantizeOptions {
+        calibration,
+        min_sqnr_db: min_sqnr,
+    };
+    let results = swictation_stt::quantize::quantize_model(&model_dir, &options)
+        .with_context(|| format!("Failed to quantize {}", model_dir.display()))?;
+
+    const MB: f64 = 1024.0 * 1024.0;
+    for file in &results {
+        let name = file
+            .source
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy();
+        let Some(output) = &file.output else {
+            println!("- {}: nothing to quantize, FP32 is used", name);
+            continue;
+        };
+        println!(
+            "✓ {} -> {}: {} MatMuls, weights {:.0} MB -> {:.0} MB",
+            name,
+            output.display(),
+            file.quantized,
+            file.fp32_bytes as f64 / MB,
+            file.int8_bytes as f64 / MB
+        );
+        for layer in &file.kept {
+            println!("    kept FP32: {} ({:.1} dB)", layer.layer, layer.sqnr_db);
         }
     }
+    println!(
+        "INT8 files are used for CPU inference: set stt_model_override = \"1.1b-cpu\" to run \
+         the 1.1B model without VRAM. GPU sessions keep loading the FP32 files, and with \
+         vram_downgrade a 1.1B GPU session falls back to these under VRAM pressure"
+    );
+    Ok(())
+}
+
+/// Text for the injection thread, with the session its delivery is
+/// recorded against
+struct Injection {
+    text: String,
+    session_id: Option<i64>,
+    private: bool,
 }
 
 #[tokio::main]
@@ -263,21 +1307,143 @@ async fn main() -> Result<()> {
         return Ok(());
     }
 
-    // Initialize logging
-    tracing_subscriber::fmt()
+    // Maintenance subcommands don't need logging or a running pipeline
+    match cli.command {
+        Some(Command::Doctor { vad_trace }) => {
+            let report = doctor::run();
+            print!("{}", report);
+            if vad_trace {
+                let socket = socket_utils::get_ipc_socket_path()?;
+                let trace = doctor::query_vad_trace(&socket)
+                    .context("Failed to fetch VAD trace (is the daemon running?)")?;
+                println!("\ntimestamp,probability");
+                for point in trace["points"].as_array().into_iter().flatten() {
+                    println!(
+                        "{:.3},{}",
+                        point["timestamp"].as_f64().unwrap_or_default(),
+                        point["probability"]
+                    );
+                }
+            }
+            if !report.is_healthy() {
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Some(Command::Bugreport { output, analytics }) => {
+            let analytics = analytics
+                || DaemonConfig::load()
+                    .map(|config| config.bugreport_analytics)
+                    .unwrap_or(false);
+            let path = bugreport::create_bundle(output, analytics)?;
+            println!("Bug report written to {}", path.display());
+            println!("Nothing was uploaded. Review the archive before attaching it to an issue.");
+            return Ok(());
+        }
+        Some(Command::WeeklySummary) => {
+            let (path, _) = weekly_summary::ensure_previous_week()?;
+            let markdown = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            print!("{}", markdown);
+            eprintln!("(saved at {})", path.display());
+            return Ok(());
+        }
+        Some(Command::Config { action }) => {
+            run_config_command(action)?;
+            return Ok(());
+        }
+        Some(Command::Hotkeys {
+            action: HotkeysAction::Uninstall,
+        }) => {
+            let removed = desktop_shortcuts::uninstall()?;
+            if removed.is_empty() {
+                println!("No Swictation shortcuts installed");
+            }
+            for shortcut in removed {
+                println!("Removed {}", shortcut);
+            }
+            return Ok(());
+        }
+        Some(Command::Secret { action }) => {
+            run_secret_command(action)?;
+            return Ok(());
+        }
+        Some(Command::Dedupe { dry_run }) => {
+            run_dedupe_command(dry_run)?;
+            return Ok(());
+        }
+        Some(Command::MergeSessions { ids }) => {
+            run_merge_sessions_command(&ids)?;
+            return Ok(());
+        }
+        Some(Command::SplitSession { id, at }) => {
+            run_split_session_command(id, &at)?;
+            return Ok(());
+        }
+        Some(Command::RecomputeStats { repair }) => {
+            run_recompute_stats_command(repair)?;
+            return Ok(());
+        }
+        Some(Command::BackfillLatency { all, dry_run }) => {
+            run_backfill_latency_command(all, dry_run)?;
+            return Ok(());
+        }
+        Some(Command::Errors { limit }) => {
+            let socket = socket_utils::get_ipc_socket_path()?;
+            let errors = error_log::query(&socket, limit)
+                .context("Failed to fetch recent errors (is the daemon running?)")?;
+            if errors.is_empty() {
+                println!("No warnings or errors since the daemon started");
+            }
+            // Oldest first, like a log
+            for event in errors.iter().rev() {
+                println!("{}", event.to_line());
+            }
+            return Ok(());
+        }
+        Some(Command::Quantize {
+            model_dir,
+            segments,
+            min_sqnr,
+        }) => {
+            run_quantize_command(model_dir, segments, min_sqnr)?;
+            return Ok(());
+        }
+        None => {}
+    }
+
+    // Initialize logging (headless mode keeps stdout for transcripts).
+    // Warnings and errors are also kept for `get_recent_errors`.
+    let error_log = Arc::new(ErrorLog::new(error_log::CAPACITY));
+    let logging = tracing_subscriber::fmt::layer()
         .with_target(false)
-        .with_level(true)
-        .init();
+        .with_level(true);
+    let registry = tracing_subscriber::registry()
+        .with(LevelFilter::INFO)
+        .with(ErrorLogLayer::new(Arc::clone(&error_log)));
+    if cli.headless {
+        registry.with(logging.with_writer(std::io::stderr)).init();
+    } else {
+        registry.with(logging).init();
+    }
 
     info!(
         "🎙️ Starting Swictation Daemon v{}",
         env!("CARGO_PKG_VERSION")
     );
 
+    // The system service only supervises the per-user daemons
+    if cli.system {
+        #[cfg(target_os = "linux")]
+        return seats::run(&cli.seat_users).await;
+        #[cfg(not(target_os = "linux"))]
+        anyhow::bail!("--system is only supported on Linux");
+    }
+
     // macOS: Request permissions at startup with system dialogs
     // This provides better UX by prompting users immediately rather than failing silently
     #[cfg(target_os = "macos")]
-    {
+    if !cli.headless {
         use crate::macos_audio_permission::request_microphone_permission;
         use crate::macos_text_inject::MacOSTextInjector;
 
@@ -304,13 +1470,54 @@ async fn main() -> Result<()> {
         }
     }
 
+    // Boot progress goes out on the metrics socket as soon as it listens.
+    // It only listens when the daemon will run, so the one-shot modes leave a
+    // running daemon's sockets alone.
+    let mut readiness = Readiness::new(cli.self_test);
+    let socket_paths = (|| -> Result<_> {
+        Ok((
+            socket_utils::get_metrics_socket_path().context("Failed to get metrics socket path")?,
+            socket_utils::get_firehose_socket_path()
+                .context("Failed to get firehose socket path")?,
+            socket_utils::get_ipc_socket_path().context("Failed to get IPC socket path")?,
+        ))
+    })();
+    let (metrics_socket, firehose_socket, socket_path) = match socket_paths {
+        Ok(paths) => paths,
+        Err(e) => return Err(readiness.failed(e).await),
+    };
+    let broadcaster = Arc::new(
+        MetricsBroadcaster::new(&metrics_socket)
+            .await
+            .context("Failed to create metrics broadcaster")?
+            .with_firehose(&firehose_socket),
+    );
+    if !(cli.self_test || cli.dry_run || cli.audio_check.is_some()) {
+        broadcaster
+            .start()
+            .await
+            .context("Failed to start metrics broadcaster")?;
+    }
+    readiness.set_broadcaster(Arc::clone(&broadcaster));
+    readiness.reached(ReadinessStage::PathsOk).await;
+
     // Load configuration
-    let mut config = DaemonConfig::load().context("Failed to load configuration")?;
+    let mut config = match DaemonConfig::load().context("Failed to load configuration") {
+        Ok(config) => config,
+        Err(e) => return Err(readiness.failed(e).await),
+    };
+    readiness.reached(ReadinessStage::ConfigOk).await;
 
     info!(
         "📋 Configuration loaded from {}",
         config.config_path.display()
     );
+    if let Err(e) = config.validate() {
+        warn!("⚠️ Configuration problems (see `swictation-daemon config validate`):");
+        for line in e.to_string().lines() {
+            warn!("   {}", line);
+        }
+    }
 
     // Apply CLI overrides
     if let Some(ref model) = cli.test_model {
@@ -318,8 +1525,17 @@ async fn main() -> Result<()> {
         config.stt_model_override = model.clone();
     }
 
+    // AUDIO-CHECK MODE: Exercise capture and VAD, then exit
+    if let Some(seconds) = cli.audio_check {
+        return audio_check::run(&config, Duration::from_secs(seconds));
+    }
+
     // Detect GPU provider
-    let gpu_provider = detect_gpu_provider();
+    let mut gpu_provider = detect_gpu_provider();
+    if config.provider_benchmark && !cli.dry_run {
+        gpu_provider =
+            crate::gpu::select_provider_by_benchmark(gpu_provider, &config.stt_0_6b_model_path);
+    }
     match &gpu_provider {
         Some(provider) => info!("🎮 GPU detected: {}", provider),
         None => warn!("⚠️ No GPU detected, using CPU (slower)"),
@@ -335,8 +1551,17 @@ async fn main() -> Result<()> {
             info!("  Override active: {}", config.stt_model_override);
             match config.stt_model_override.as_str() {
                 "1.1b-gpu" => info!("  Would load: Parakeet-TDT-1.1B-INT8 (GPU, forced)"),
+                "1.1b-cpu" => info!("  Would load: Parakeet-TDT-1.1B-INT8 (CPU, forced)"),
                 "0.6b-gpu" => info!("  Would load: Parakeet-TDT-0.6B (GPU, forced)"),
                 "0.6b-cpu" => info!("  Would load: Parakeet-TDT-0.6B (CPU, forced)"),
+                "whisper-gpu" => info!(
+                    "  Would load: Whisper (GPU, forced) from {}",
+                    config.stt_whisper_model_path.display()
+                ),
+                "whisper-cpu" => info!(
+                    "  Would load: Whisper (CPU, forced) from {}",
+                    config.stt_whisper_model_path.display()
+                ),
                 _ => error!("  Invalid override value!"),
             }
         } else {
@@ -370,67 +1595,102 @@ async fn main() -> Result<()> {
 
     // Initialize daemon with models loaded
     info!("🔧 Initializing pipeline (this may take a moment)...");
-    let (daemon, mut transcription_rx) =
-        match Daemon::new(config.clone(), gpu_provider.clone()).await {
-            Ok(result) => result,
-            Err(e) => {
-                let err_msg = format!("{:#}", e);
-
-                // Check if error is about missing model files
-                if err_msg.contains("No such file or directory")
-                    || err_msg.contains("model") && err_msg.contains("not found")
-                    || err_msg.contains("Failed to load")
-                {
-                    error!("❌ Failed to load AI model");
-                    error!("");
+    let (mut daemon, mut transcription_rx) = match Daemon::new(
+        config.clone(),
+        gpu_provider.clone(),
+        error_log,
+        broadcaster,
+        &readiness,
+    )
+    .await
+    {
+        Ok(result) => result,
+        Err(e) => {
+            let e = readiness.failed(e).await;
+            let err_msg = format!("{:#}", e);
+
+            // Check if error is about missing model files
+            if err_msg.contains("No such file or directory")
+                || err_msg.contains("model") && err_msg.contains("not found")
+                || err_msg.contains("Failed to load")
+            {
+                error!("❌ Failed to load AI model");
+                error!("");
+                let mut required = vec![
+                    config.vad_model_path.as_path(),
+                    config.stt_0_6b_model_path.as_path(),
+                ];
+                if config.stt_model_override.starts_with("1.1b") {
+                    required.push(config.stt_1_1b_model_path.as_path());
+                }
+                let missing = swictation_models::missing(required);
+                if missing.is_empty() {
                     error!("The required AI model files were not found.");
-                    error!("Please download the recommended model for your system:");
-                    error!("");
-                    error!("  swictation download-model 0.6b-gpu    # For 4GB+ VRAM GPUs");
-                    error!("  swictation download-model 1.1b-gpu    # For 6GB+ VRAM GPUs");
-                    error!("  swictation download-model 0.6b        # For CPU-only systems");
-                    error!("");
-                    error!("Or download all models:");
-                    error!("  swictation download-models");
+                    error!("Check the model paths in the config, or download the models:");
+                    error!("  swictation-models list");
+                } else {
+                    error!("These models are not installed:");
                     error!("");
-
-                    return Err(
-                        e.context("AI models not found - run 'swictation download-model' first")
-                    );
+                    for spec in &missing {
+                        error!(
+                            "  {:<36} # {}",
+                            swictation_models::download_command(spec),
+                            spec.description
+                        );
+                    }
                 }
+                error!("");
 
-                // For other errors, just pass through
-                return Err(e.context("Failed to initialize daemon"));
+                return Err(
+                    e.context("AI models not found - run 'swictation-models download' first")
+                );
             }
-        };
+
+            // For other errors, just pass through
+            return Err(e.context("Failed to initialize daemon"));
+        }
+    };
 
     info!("✓ Pipeline initialized successfully");
+
+    // SELF-TEST MODE: Everything loaded; check the sockets could be created
+    if cli.self_test {
+        let socket_dir = socket_path.parent().unwrap_or(&socket_path);
+        if let Err(e) = readiness::probe_socket_dir(socket_dir) {
+            return Err(readiness.failed(e).await);
+        }
+        readiness.reached(ReadinessStage::SocketsReady).await;
+        println!("✅ Self-test passed");
+        return Ok(());
+    }
     info!("  - Audio: 16000 Hz, 1 channel");
     info!("  - VAD: Silero VAD v6 (ort/ONNX)");
     // STT info is logged by pipeline.rs during initialization
     info!("📊 Memory usage: {} MB", get_memory_usage_mb());
     info!(
         "📡 Metrics broadcaster ready on {}",
-        socket_utils::get_metrics_socket_path()
-            .unwrap_or_else(|_| PathBuf::from("unknown"))
-            .display()
+        metrics_socket.display()
     );
+    info!("📡 Audio level firehose on {}", firehose_socket.display());
 
-    // Initialize context-aware learning model
-    let context_model = load_context_model(&config).await;
-    if let Some(ref model) = context_model {
-        info!(
-            "🧠 Context model loaded: {} topics, {} homonym rules",
-            model.topics.len(),
-            model.homonym_rules.len()
-        );
-    } else {
-        info!("⚠️  Context model not available (insufficient training data)");
+    if cli.headless {
+        let input = cli.input.unwrap_or(HeadlessInput::Stdin);
+        info!("🎧 Headless mode: transcribing {:?} to stdout", input);
+        // No IPC server in headless mode; the metrics socket is all there is
+        readiness.reached(ReadinessStage::SocketsReady).await;
+        #[allow(clippy::arc_with_non_send_sync)]
+        let daemon = Arc::new(daemon);
+        let result = headless::run(daemon.clone(), transcription_rx, input).await;
+        if let Err(e) = daemon.broadcaster.stop().await {
+            warn!("Failed to stop broadcaster cleanly: {}", e);
+        }
+        return result;
     }
 
     // Initialize hotkey manager (optional - some compositors don't support it)
-    let mut hotkey_manager = HotkeyManager::new(config.hotkeys.clone())
+    let (mut hotkey_manager, hotkey_conflicts) = HotkeyManager::new(config.hotkeys.clone())
         .context("Failed to initialize hotkey manager")?;
+    daemon.hotkey_conflicts = hotkey_conflicts;
 
     if let Some(ref _manager) = hotkey_manager {
         info!("✓ Hotkeys initialized successfully");
@@ -439,15 +1699,21 @@ async fn main() -> Result<()> {
     }
 
     // Start IPC server for CLI/scripts (optional) with secure socket path
-    let socket_path =
-        socket_utils::get_ipc_socket_path().context("Failed to get IPC socket path")?;
     let socket_path_str = socket_path.to_str().context("Invalid socket path")?;
     info!("🔌 Starting IPC server on {}", socket_path_str);
 
+    let (voice_tx, mut voice_rx) = mpsc::unbounded_channel();
+    daemon.pipeline.read().await.set_voice_commands(voice_tx);
+
     #[allow(clippy::arc_with_non_send_sync)]
     let daemon_clone = Arc::new(daemon);
-    let mut ipc_server = IpcServer::new(socket_path_str, daemon_clone.clone())
-        .context("Failed to start IPC server")?;
+    let mut ipc_server = match IpcServer::new(socket_path_str, daemon_clone.clone())
+        .context("Failed to start IPC server")
+    {
+        Ok(server) => server,
+        Err(e) => return Err(readiness.failed(e).await),
+    };
+    readiness.reached(ReadinessStage::SocketsReady).await;
 
     // Spawn background metrics updater (CPU/GPU monitoring every 1 second)
     //
@@ -463,6 +1729,7 @@ async fn main() -> Result<()> {
         let daemon_state = daemon_clone.state.clone();
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+            let mut storage_full = false;
             loop {
                 interval.tick().await;
 
@@ -478,25 +1745,32 @@ async fn main() -> Result<()> {
                 // State lock released here
 
                 // NOW safe to acquire metrics lock (no other locks held)
-                let realtime = {
+                let (realtime, storage) = {
                     let metrics_guard = metrics.lock().unwrap();
                     metrics_guard.update_system_metrics();
                     metrics_guard.update_recording_duration();
                     let mut realtime = metrics_guard.get_realtime_metrics();
                     realtime.current_state = current_state;
-                    realtime
+                    // Retries buffered writes while the metrics disk is full
+                    (realtime, metrics_guard.retry_storage())
                 };
                 // Metrics lock released here
 
                 // Broadcast with no locks held
                 broadcaster.update_metrics(&realtime).await;
+                if storage.full != storage_full {
+                    storage_full = storage.full;
+                    broadcaster.broadcast_storage_status(storage).await;
+                }
             }
         })
     };
 
     // Spawn memory pressure monitor (RAM + VRAM every 5 seconds)
+    let (vram_tx, mut vram_rx) = mpsc::unbounded_channel::<f32>();
     let _memory_handle = {
         let _broadcaster = daemon_clone.broadcaster.clone();
+        let vram_downgrade = config.vram_downgrade;
         tokio::spawn(async move {
             let mut memory_monitor = match MemoryMonitor::new() {
                 Ok(m) => {
@@ -552,7 +1826,11 @@ async fn main() -> Result<()> {
                                 "🚨 VRAM critical: {:.1}% ({} MB used / {} MB total) on {}",
                                 vram.percent_used, vram.used_mb, vram.total_mb, vram.device_name
                             );
-                            // Note: Could pause recording here if needed
+                            // The main loop swaps in a lighter model before
+                            // inference runs out of memory
+                            if vram_downgrade {
+                                let _ = vram_tx.send(vram.percent_used);
+                            }
                         }
                     }
                     MemoryPressure::Normal => {}
@@ -561,17 +1839,41 @@ async fn main() -> Result<()> {
         })
     };
 
+    // Queue audio files dropped into watch folders for transcription
+    let _watch_folders = match watch_folder::spawn(&config, daemon_clone.jobs.clone()) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            warn!("⚠️ Watch folders disabled: {:#}", e);
+            None
+        }
+    };
+
+    // Merge corrections, bias phrases and profiles with other machines
+    let _sync_handle = config.sync_dir.clone().map(sync::spawn_scheduler);
+
+    // Spawn weekly summary digest scheduler (checks hourly, writes once per week)
+    let _digest_handle = weekly_summary::spawn_scheduler(daemon_clone.broadcaster.clone());
+
+    // Retrain the context model in the background when due (checks hourly,
+    // yields to recording)
+    let _training_handle = context_training::spawn_scheduler(
+        daemon_clone.training.clone(),
+        daemon_clone.state.clone(),
+        daemon_clone.broadcaster.clone(),
+    );
+
     info!("🚀 Swictation daemon ready!");
     if hotkey_manager.is_some() {
         info!("   Press {} to start/stop recording", config.hotkeys.toggle);
     }
     info!("   Or use 'swictation-cli toggle' for CLI control");
 
-    // Handle transcription results and inject text
+    // Deliver transcription results to the output sinks
     //
     // On macOS, CGEventSource is not Send/Sync, so we must use a dedicated OS thread
     // for text injection and communicate via a channel.
-    let (inject_tx, inject_rx) = std::sync::mpsc::channel::<String>();
+    let (inject_tx, inject_rx) = std::sync::mpsc::channel::<Injection>();
+    let deliveries = daemon_clone.deliveries.clone();
 
     // Spawn dedicated thread for text injection (required for macOS CGEventSource)
     std::thread::spawn(move || {
@@ -603,22 +1905,104 @@ async fn main() -> Result<()> {
         };
 
         // Receive text to inject from channel
-        while let Ok(text) = inject_rx.recv() {
-            info!("Injecting text: {}", text);
-            if let Err(e) = text_injector.inject_text(&text) {
-                error!("Failed to inject text: {}", e);
-            }
+        while let Ok(injection) = inject_rx.recv() {
+            info!("Injecting text: {}", injection.text);
+            let started = Instant::now();
+            let result = text_injector.inject_text(&injection.text);
+            deliveries.record(
+                OutputSink::Inject,
+                injection.session_id,
+                injection.private,
+                started,
+                result,
+            );
         }
     });
 
-    // Bridge async transcription results to the sync text injection thread
+    // Bridge async transcription results to the sinks; each sink's failure
+    // is its own, the others still get the text
+    let history = daemon_clone.history.clone();
+    let hooks = daemon_clone.hooks.clone();
+    let session_id = daemon_clone.session_id.clone();
+    let editors = daemon_clone.editors.clone();
+    let private_mode = daemon_clone.private_mode.clone();
+    let outputs = daemon_clone.outputs.clone();
+    let deliveries = daemon_clone.deliveries.clone();
+    let mut note_sink = NoteSink::new(&config.note_sink);
     tokio::spawn(async move {
+        let mut injector_running = true;
         while let Some(result) = transcription_rx.recv().await {
             match result {
                 Ok(text) => {
-                    if inject_tx.send(text).is_err() {
-                        error!("Text injection thread has exited");
-                        break;
+                    let sid = *session_id.read().await;
+                    // Text flushed after a private session ended (and its
+                    // history was wiped) is private too
+                    let late_private = sid.is_none() && private_mode.load(Ordering::SeqCst);
+                    let private = late_private || sid == Some(PRIVATE_SESSION_ID);
+                    if !late_private {
+                        history.lock().unwrap().push(&text);
+                    }
+                    let sinks = outputs.sinks();
+
+                    if sinks.contains(&OutputSink::Note) {
+                        if let Some(sink) = note_sink.as_mut().filter(|_| !private) {
+                            let started = Instant::now();
+                            // Blank text and keystrokes are not written
+                            match sink.append(sid, &text, chrono::Local::now()) {
+                                Ok(None) => {}
+                                result => deliveries.record(
+                                    OutputSink::Note,
+                                    sid,
+                                    private,
+                                    started,
+                                    result.map(drop),
+                                ),
+                            }
+                        }
+                    }
+
+                    // Keystroke-only results (edit commands) are not transcripts
+                    if sinks.contains(&OutputSink::Hook)
+                        && !private
+                        && !text.trim().is_empty()
+                        && !text.contains("<KEY:")
+                    {
+                        let hooks = hooks.clone();
+                        let deliveries = deliveries.clone();
+                        let fields = serde_json::json!({ "session_id": sid, "text": text.trim() });
+                        tokio::spawn(async move {
+                            let started = Instant::now();
+                            match hooks.run(HookEvent::Transcription, fields).await {
+                                Ok(false) => {}
+                                result => deliveries.record(
+                                    OutputSink::Hook,
+                                    sid,
+                                    private,
+                                    started,
+                                    result.map(drop),
+                                ),
+                            }
+                        });
+                    }
+
+                    if !sinks.contains(&OutputSink::Inject) {
+                        continue;
+                    }
+                    let started = Instant::now();
+                    if let Some(editor) = editors.route(&text, sid) {
+                        debug!("Sent text to {}", editor);
+                        deliveries.record(OutputSink::Inject, sid, private, started, Ok(()));
+                        continue;
+                    }
+                    let injection = Injection {
+                        text,
+                        session_id: sid,
+                        private,
+                    };
+                    if inject_tx.send(injection).is_err() && injector_running {
+                        // Keep delivering to the other sinks
+                        error!("Text injection thread has exited; text will not be typed");
+                        injector_running = false;
                     }
                 }
                 Err(e) => {
@@ -628,6 +2012,11 @@ async fn main() -> Result<()> {
         }
     });
 
+    // VRAM downgrades run in the background; the loop takes no new
+    // readings until the current one is done
+    let (downgrade_done_tx, mut downgrade_done_rx) = mpsc::unbounded_channel::<()>();
+    let mut downgrading = false;
+
     // Main event loop
     loop {
         tokio::select! {
@@ -658,6 +2047,35 @@ async fn main() -> Result<()> {
                             error!("PTT stop error: {}", e);
                         }
                     }
+                    HotkeyEvent::PrivateModeToggle => {
+                        if let Err(e) = daemon_clone.set_private_mode(None).await {
+                            error!("Private mode error: {}", e);
+                        }
+                    }
+                }
+            }
+
+            // Voice control ("stop dictation") in accessibility mode
+            Some(command) = voice_rx.recv() => {
+                daemon_clone.voice_command(command).await;
+            }
+
+            // Critical VRAM pressure, one downgrade at a time
+            Some(vram_percent) = vram_rx.recv(), if !downgrading => {
+                downgrading = true;
+                daemon_clone.downgrade_for_vram(vram_percent, downgrade_done_tx.clone()).await;
+            }
+            Some(()) = downgrade_done_rx.recv() => {
+                downgrading = false;
+                // Readings taken while the model was loading are stale
+                while vram_rx.try_recv().is_ok() {}
+            }
+
+            // Meeting mode is time-boxed
+            () = meeting::until(daemon_clone.meeting_deadline()) => {
+                info!("⏰ Meeting reached meeting.max_minutes");
+                if let Err(e) = daemon_clone.stop_meeting().await {
+                    error!("Meeting stop error: {:#}", e);
                 }
             }
 
@@ -0,0 +1,338 @@
+//! Meeting mode: transcribe a meeting into a document
+//!
+//! "Start meeting mode" (spoken or over IPC) records a meeting instead of
+//! dictating. System audio from `meeting.system_audio_device` is mixed with
+//! the microphone, so remote participants are transcribed too. Nothing is
+//! typed or delivered to the output sinks; segments are collected until the
+//! meeting stops (or reaches `meeting.max_minutes`) and written to a
+//! Markdown document. With `meeting.summary_hook` set, the transcript is
+//! then piped to that script (e.g. an LLM prompt) and its output added to
+//! the document as action items.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use swictation_audio::{AudioCapture, AudioConfig};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+use crate::config::MeetingConfig;
+
+/// System audio held back for mixing (1 s); when the microphone falls
+/// behind, the oldest samples go
+const MAX_QUEUED_SAMPLES: usize = 16_000;
+
+/// How long to wait for segments still being transcribed at the end
+const SETTLE: Duration = Duration::from_secs(2);
+
+/// Segments this close together share a paragraph
+const PARAGRAPH_GAP_SECS: i64 = 30;
+
+/// Capture of the system audio device, mixed into the microphone chunks
+pub struct SystemAudio {
+    capture: AudioCapture,
+    queue: Arc<Mutex<VecDeque<f32>>>,
+}
+
+impl SystemAudio {
+    /// Start capturing the input device whose name contains `device`
+    pub fn start(device: &str, config: AudioConfig) -> Result<Self> {
+        let index = AudioCapture::list_devices()?
+            .into_iter()
+            .find(|info| info.name.contains(device))
+            .with_context(|| format!("No audio input device matching '{}'", device))?
+            .index;
+        let mut capture = AudioCapture::new(AudioConfig {
+            device_index: Some(index),
+            ..config
+        })?;
+
+        let queue = Arc::new(Mutex::new(VecDeque::new()));
+        let incoming = queue.clone();
+        capture.set_chunk_callback(move |chunk| {
+            let mut queue = incoming.lock().unwrap();
+            queue.extend(chunk);
+            let excess = queue.len().saturating_sub(MAX_QUEUED_SAMPLES);
+            queue.drain(..excess);
+        });
+        capture
+            .start()
+            .with_context(|| format!("Failed to capture '{}'", device))?;
+        info!("🔊 Mixing in system audio from '{}'", device);
+        Ok(Self { capture, queue })
+    }
+
+    /// Samples waiting to be mixed (see [`mix`])
+    pub fn queue(&self) -> Arc<Mutex<VecDeque<f32>>> {
+        self.queue.clone()
+    }
+
+    pub fn stop(mut self) {
+        if let Err(e) = self.capture.stop() {
+            warn!("Failed to stop system audio capture: {}", e);
+        }
+    }
+}
+
+/// Add queued system audio to a microphone chunk
+pub fn mix(chunk: &mut [f32], queue: &Mutex<VecDeque<f32>>) {
+    let mut queue = queue.lock().unwrap();
+    let n = chunk.len().min(queue.len());
+    for (sample, system) in chunk.iter_mut().zip(queue.drain(..n)) {
+        *sample = (*sample + system).clamp(-1.0, 1.0);
+    }
+}
+
+/// A meeting in progress
+pub struct Meeting {
+    deadline: Instant,
+    finish: oneshot::Sender<()>,
+    document: JoinHandle<Result<PathBuf>>,
+}
+
+impl Meeting {
+    /// Start collecting segments, returning the meeting and the sender the
+    /// pipeline writes its segments to instead of the sinks
+    pub fn start(config: &MeetingConfig) -> (Self, mpsc::Sender<Result<String>>) {
+        let (tx, rx) = mpsc::channel(100);
+        let (finish, finished) = oneshot::channel();
+        let meeting = Self {
+            deadline: Instant::now() + Duration::from_secs(config.max_minutes * 60),
+            finish,
+            document: tokio::spawn(collect(rx, finished, config.clone())),
+        };
+        (meeting, tx)
+    }
+
+    /// When the meeting ends by itself
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Stop collecting and write the document, returning its path
+    ///
+    /// The summary, if any, is added in the background.
+    pub async fn finish(self) -> Result<PathBuf> {
+        let _ = self.finish.send(());
+        self.document.await.context("Meeting task panicked")?
+    }
+}
+
+/// Resolves at `deadline`, or never without one
+pub async fn until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// A transcribed segment and when it arrived
+type Segment = (DateTime<Local>, String);
+
+async fn collect(
+    mut rx: mpsc::Receiver<Result<String>>,
+    mut finished: oneshot::Receiver<()>,
+    config: MeetingConfig,
+) -> Result<PathBuf> {
+    let started = Local::now();
+    let mut segments = Vec::new();
+    loop {
+        tokio::select! {
+            Some(result) = rx.recv() => push(&mut segments, result),
+            _ = &mut finished => break,
+        }
+    }
+    while let Ok(Some(result)) = tokio::time::timeout(SETTLE, rx.recv()).await {
+        push(&mut segments, result);
+    }
+
+    let directory = config.directory.clone().unwrap_or_else(meetings_dir);
+    std::fs::create_dir_all(&directory)
+        .with_context(|| format!("Failed to create {}", directory.display()))?;
+    let path = directory.join(started.format("meeting-%Y-%m-%d-%H%M%S.md").to_string());
+    std::fs::write(&path, document(started, Local::now(), &segments))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    if let Some(hook) = config.summary_hook.filter(|_| !segments.is_empty()) {
+        let transcript: String = segments
+            .iter()
+            .map(|(_, text)| format!("{}\n", text))
+            .collect();
+        let timeout = Duration::from_secs(config.summary_timeout_secs);
+        let path = path.clone();
+        tokio::spawn(async move {
+            match summarize(&hook, &transcript, timeout).await {
+                Ok(summary) if summary.trim().is_empty() => {}
+                Ok(summary) => {
+                    if let Err(e) = append_action_items(&path, &summary) {
+                        warn!("⚠️ {:#}", e);
+                    }
+                }
+                Err(e) => warn!("⚠️ Meeting summary hook {} failed: {:#}", hook.display(), e),
+            }
+        });
+    }
+    Ok(path)
+}
+
+/// Keep typed text; keystrokes (edit commands, paragraph breaks) are not
+/// part of the transcript
+fn push(segments: &mut Vec<Segment>, result: Result<String>) {
+    match result {
+        Ok(text) if text.contains("<KEY:") || text.trim().is_empty() => {}
+        Ok(text) => segments.push((Local::now(), text.trim().to_string())),
+        Err(e) => warn!("Meeting transcription error: {}", e),
+    }
+}
+
+/// Default folder for meeting documents
+fn meetings_dir() -> PathBuf {
+    swictation_paths::data_dir().join("meetings")
+}
+
+/// Markdown document: a heading, the time span and the transcript, with a
+/// timestamp before each run of segments
+fn document(started: DateTime<Local>, ended: DateTime<Local>, segments: &[Segment]) -> String {
+    let minutes = (ended - started).num_minutes();
+    let mut doc = format!(
+        "# Meeting {}\n\n{} - {} ({} min)\n\n## Transcript\n",
+        started.format("%Y-%m-%d %H:%M"),
+        started.format("%H:%M"),
+        ended.format("%H:%M"),
+        minutes
+    );
+    let mut last: Option<DateTime<Local>> = None;
+    for (at, text) in segments {
+        match last {
+            Some(last) if (*at - last).num_seconds() < PARAGRAPH_GAP_SECS => {
+                doc.push(' ');
+            }
+            _ => doc.push_str(&format!("\n**{}** ", at.format("%H:%M:%S"))),
+        }
+        doc.push_str(text);
+        last = Some(*at);
+    }
+    if segments.is_empty() {
+        doc.push_str("\n_Nothing was transcribed._");
+    }
+    doc.push('\n');
+    doc
+}
+
+/// Run the summary hook with the transcript on stdin, returning its output
+async fn summarize(hook: &Path, transcript: &str, timeout: Duration) -> Result<String> {
+    let mut child = Command::new(hook)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .context("Failed to start")?;
+    let mut stdin = child.stdin.take().context("No stdin")?;
+    stdin.write_all(transcript.as_bytes()).await?;
+    drop(stdin);
+
+    let output = tokio::time::timeout(timeout, child.wait_with_output())
+        .await
+        .with_context(|| format!("Timed out after {}s", timeout.as_secs()))??;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+fn append_action_items(path: &Path, summary: &str) -> Result<()> {
+    use std::io::Write;
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    write!(file, "\n## Action items\n\n{}\n", summary.trim())
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    info!("📝 Meeting action items added to {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_mix_adds_queued_audio() {
+        let queue = Mutex::new(VecDeque::from([0.25, 0.75, 0.5]));
+        let mut chunk = [0.5, 0.5];
+        mix(&mut chunk, &queue);
+        assert_eq!(chunk, [0.75, 1.0]);
+        assert_eq!(queue.lock().unwrap().len(), 1);
+
+        let mut chunk = [0.0, 0.1];
+        mix(&mut chunk, &queue);
+        assert_eq!(chunk, [0.5, 0.1]);
+    }
+
+    #[test]
+    fn test_document_groups_segments() {
+        let at = |h, m, s| Local.with_ymd_and_hms(2026, 3, 2, h, m, s).unwrap();
+        let segments = vec![
+            (at(14, 0, 5), "Welcome everyone.".to_string()),
+            (at(14, 0, 20), "Let's start.".to_string()),
+            (at(14, 5, 0), "Any questions?".to_string()),
+        ];
+        assert_eq!(
+            document(at(14, 0, 0), at(14, 42, 0), &segments),
+            "# Meeting 2026-03-02 14:00\n\n14:00 - 14:42 (42 min)\n\n## Transcript\n\
+             \n**14:00:05** Welcome everyone. Let's start.\
+             \n**14:05:00** Any questions?\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_meeting_writes_document_and_action_items() {
+        let dir = std::env::temp_dir().join(format!("swictation-meeting-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let hook = dir.join("summarize.sh");
+        std::fs::write(&hook, "#!/bin/sh\necho \"- $(wc -l | tr -d ' ') lines\"\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let config = MeetingConfig {
+            directory: Some(dir.clone()),
+            summary_hook: Some(hook),
+            ..Default::default()
+        };
+        let (meeting, tx) = Meeting::start(&config);
+        tx.send(Ok("First point. ".to_string())).await.unwrap();
+        tx.send(Ok("<KEY:Return>".to_string())).await.unwrap();
+        tx.send(Ok("Second point. ".to_string())).await.unwrap();
+        drop(tx);
+        let path = meeting.finish().await.unwrap();
+
+        let mut doc = String::new();
+        for _ in 0..50 {
+            doc = std::fs::read_to_string(&path).unwrap();
+            if doc.contains("## Action items") {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(doc.contains("First point. Second point."));
+        assert!(!doc.contains("<KEY:"));
+        assert!(doc.ends_with("## Action items\n\n- 2 lines\n"));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,142 @@
+//! Markdown note sink
+//!
+//! Appends typed text to a dated Markdown file (an Obsidian daily note, for
+//! example) under timestamp headings, so dictation ends up in your notes
+//! without a hook script.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use std::fmt::Write as _;
+use std::fs::OpenOptions;
+use std::io::Write as _;
+use std::path::PathBuf;
+
+use crate::config::{NoteHeading, NoteSinkConfig};
+
+pub struct NoteSink {
+    directory: PathBuf,
+    filename: String,
+    heading: NoteHeading,
+    /// Session and file that last got a heading
+    headed: Option<(Option<i64>, PathBuf)>,
+}
+
+impl NoteSink {
+    /// `None` unless the sink is enabled with a directory
+    pub fn new(config: &NoteSinkConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+        Some(Self {
+            directory: config.directory.clone()?,
+            filename: config.filename.clone(),
+            heading: config.heading,
+            headed: None,
+        })
+    }
+
+    /// Append typed `text`, returning the note it was written to
+    ///
+    /// Blank text and keystroke markers are skipped (`Ok(None)`).
+    pub fn append(
+        &mut self,
+        session_id: Option<i64>,
+        text: &str,
+        now: DateTime<Local>,
+    ) -> Result<Option<PathBuf>> {
+        if text.trim().is_empty() || text.contains("<KEY:") {
+            return Ok(None);
+        }
+
+        let mut filename = String::new();
+        write!(filename, "{}", now.format(&self.filename))
+            .map_err(|_| anyhow::anyhow!("Invalid note filename '{}'", self.filename))?;
+        let path = self.directory.join(filename);
+        std::fs::create_dir_all(&self.directory).with_context(|| {
+            format!("Failed to create notes folder {}", self.directory.display())
+        })?;
+
+        let is_empty = std::fs::metadata(&path).map_or(true, |m| m.len() == 0);
+        let start_heading = match self.heading {
+            NoteHeading::Segment => true,
+            NoteHeading::Session => {
+                is_empty || self.headed.as_ref() != Some(&(session_id, path.clone()))
+            }
+        };
+
+        let mut entry = String::new();
+        if start_heading {
+            if !is_empty {
+                entry.push_str("\n\n");
+            }
+            let _ = write!(entry, "## {}\n\n", now.format("%H:%M:%S"));
+            self.headed = Some((session_id, path.clone()));
+        }
+        match self.heading {
+            // Kept as typed, so segments join up the way they did on screen
+            NoteHeading::Session => entry.push_str(text),
+            NoteHeading::Segment => entry.push_str(text.trim()),
+        }
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(entry.as_bytes()))
+            .with_context(|| format!("Failed to append to note {}", path.display()))?;
+        Ok(Some(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn sink(heading: NoteHeading) -> (NoteSink, PathBuf) {
+        let dir = std::env::temp_dir().join(format!("swictation-notes-{}", uuid::Uuid::new_v4()));
+        let config = NoteSinkConfig {
+            enabled: true,
+            directory: Some(dir.clone()),
+            heading,
+            ..Default::default()
+        };
+        (NoteSink::new(&config).unwrap(), dir)
+    }
+
+    #[test]
+    fn test_session_heading_then_text_as_typed() {
+        let (mut sink, dir) = sink(NoteHeading::Session);
+        let at = |s| Local.with_ymd_and_hms(2025, 3, 14, 9, 30, s).unwrap();
+
+        let path = sink
+            .append(Some(1), "Hello world. ", at(0))
+            .unwrap()
+            .unwrap();
+        sink.append(Some(1), "<KEY:Return>", at(1)).unwrap();
+        sink.append(Some(1), "Second sentence. ", at(2)).unwrap();
+        sink.append(Some(2), "New session.", at(5)).unwrap();
+
+        assert!(path.ends_with("2025-03-14.md"));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "## 09:30:00\n\nHello world. Second sentence. \n\n## 09:30:05\n\nNew session."
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_segment_headings() {
+        let (mut sink, dir) = sink(NoteHeading::Segment);
+        let at = |s| Local.with_ymd_and_hms(2025, 3, 14, 9, 30, s).unwrap();
+
+        let path = sink.append(Some(1), "One. ", at(0)).unwrap().unwrap();
+        sink.append(Some(1), "Two. ", at(3)).unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "## 09:30:00\n\nOne.\n\n## 09:30:03\n\nTwo."
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,35 @@
+//! Desktop notifications
+//!
+//! Used to answer voice control commands when spoken read-back is off.
+
+use std::process::{Command, Stdio};
+use tracing::warn;
+
+/// Show `body` as a desktop notification without waiting for it
+pub fn show(body: &str) {
+    let mut command = if cfg!(target_os = "macos") {
+        // Passed as an argument so quotes in `body` need no escaping
+        let mut osascript = Command::new("osascript");
+        osascript.args([
+            "-e",
+            "on run argv",
+            "-e",
+            "display notification (item 1 of argv) with title \"Swictation\"",
+            "-e",
+            "end run",
+            body,
+        ]);
+        osascript
+    } else {
+        let mut notify_send = Command::new("notify-send");
+        notify_send.args(["--app-name=Swictation", "Swictation", body]);
+        notify_send
+    };
+
+    match command.stdout(Stdio::null()).stderr(Stdio::null()).spawn() {
+        Ok(mut child) => {
+            std::thread::spawn(move || child.wait());
+        }
+        Err(e) => warn!("Failed to show notification: {}", e),
+    }
+}
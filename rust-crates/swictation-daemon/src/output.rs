@@ -0,0 +1,145 @@
+//! Output sinks: where a finished segment is delivered
+//!
+//! Every segment goes to each sink of the active output profile - typed
+//! into the focused window, appended to the Markdown note, sent to the
+//! transcription hooks. Sinks are independent: one that fails is logged and
+//! the rest still get the text. How long each delivery took, and whether it
+//! failed, is recorded in the metrics database (not for private sessions).
+
+use anyhow::{bail, Result};
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+use swictation_metrics::{MetricsDatabase, SinkDelivery};
+use tracing::{debug, info, warn};
+
+use crate::config::{OutputConfig, OutputSink};
+
+/// The configured output profiles and which one is in use
+pub struct OutputProfiles {
+    config: OutputConfig,
+    active: RwLock<String>,
+}
+
+impl OutputProfiles {
+    pub fn new(config: OutputConfig) -> Self {
+        let active = RwLock::new(config.profile.clone());
+        Self { config, active }
+    }
+
+    /// Sinks of the active profile
+    pub fn sinks(&self) -> Vec<OutputSink> {
+        let active = self.active.read().unwrap();
+        self.config
+            .profiles
+            .get(active.as_str())
+            .map(|profile| profile.sinks.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn profile(&self) -> String {
+        self.active.read().unwrap().clone()
+    }
+
+    /// Make `name` the active profile
+    pub fn set_profile(&self, name: &str) -> Result<()> {
+        if !self.config.profiles.contains_key(name) {
+            bail!("Output profile '{}' is not configured", name);
+        }
+        *self.active.write().unwrap() = name.to_string();
+        info!("📤 Output profile: {} ({:?})", name, self.sinks());
+        Ok(())
+    }
+}
+
+/// Records how each delivery went
+#[derive(Clone)]
+pub struct DeliveryLog {
+    db: Arc<MetricsDatabase>,
+}
+
+impl DeliveryLog {
+    pub fn new(db: Arc<MetricsDatabase>) -> Self {
+        Self { db }
+    }
+
+    /// Log a failed delivery, and store the delivery's latency unless the
+    /// session is private
+    pub fn record(
+        &self,
+        sink: OutputSink,
+        session_id: Option<i64>,
+        private: bool,
+        started: Instant,
+        result: Result<()>,
+    ) {
+        let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+        if let Err(e) = &result {
+            warn!("⚠️ {} sink failed: {:#}", sink.name(), e);
+        }
+        if private {
+            return;
+        }
+        let delivery = SinkDelivery {
+            session_id,
+            timestamp: Some(chrono::Utc::now()),
+            sink: sink.name().to_string(),
+            latency_ms,
+            error: result.err().map(|e| format!("{:#}", e)),
+        };
+        if let Err(e) = self.db.insert_sink_delivery(&delivery) {
+            debug!("Failed to record {} delivery: {:#}", sink.name(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OutputProfile;
+
+    #[test]
+    fn test_switch_profile() {
+        let mut config = OutputConfig::default();
+        config.profiles.insert(
+            "notes".to_string(),
+            OutputProfile {
+                sinks: vec![OutputSink::Note],
+            },
+        );
+        let outputs = OutputProfiles::new(config);
+        assert_eq!(outputs.sinks().len(), 3);
+
+        outputs.set_profile("notes").unwrap();
+        assert_eq!(outputs.sinks(), [OutputSink::Note]);
+        assert!(outputs.set_profile("missing").is_err());
+        assert_eq!(outputs.profile(), "notes");
+    }
+
+    #[test]
+    fn test_private_deliveries_not_stored() {
+        let dir = std::env::temp_dir().join(format!("swictation-output-{}", uuid::Uuid::new_v4()));
+        let db = Arc::new(MetricsDatabase::new(dir.join("metrics.db")).unwrap());
+        let log = DeliveryLog::new(db.clone());
+        let started = Instant::now();
+
+        log.record(OutputSink::Inject, Some(1), false, started, Ok(()));
+        log.record(
+            OutputSink::Hook,
+            Some(1),
+            false,
+            started,
+            Err(anyhow::anyhow!("exit status: 1")),
+        );
+        log.record(OutputSink::Note, Some(2), true, started, Ok(()));
+
+        let now = chrono::Utc::now();
+        let hour = chrono::Duration::hours(1);
+        let stats = db.get_sink_stats_between(now - hour, now + hour).unwrap();
+        let sinks: Vec<(&str, i64)> = stats
+            .iter()
+            .map(|s| (s.sink.as_str(), s.failures))
+            .collect();
+        assert_eq!(sinks, [("hook", 1), ("inject", 0)]);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
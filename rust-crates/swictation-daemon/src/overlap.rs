@@ -0,0 +1,105 @@
+//! Duplicate suppression for speech segments
+//!
+//! Segments reach STT from two places: the VAD thread while recording, and
+//! the final `flush()` in `stop_recording`. Each carries its position in the
+//! VAD's sample clock, so audio that was already transcribed can be detected
+//! and skipped instead of being typed a second time.
+
+use tracing::info;
+
+/// High-water mark of audio already handed to STT
+#[derive(Debug, Default)]
+pub struct ProcessedSpans {
+    /// End (exclusive) of the last accepted segment, in VAD samples
+    processed_until: u64,
+}
+
+impl ProcessedSpans {
+    /// Drop the part of a segment that was already processed
+    ///
+    /// Returns `None` when the whole segment lies within processed audio,
+    /// otherwise the start and samples of the part that is new.
+    pub fn filter(
+        &mut self,
+        mut start_sample: u64,
+        mut samples: Vec<f32>,
+    ) -> Option<(u64, Vec<f32>)> {
+        let end = start_sample + samples.len() as u64;
+
+        if end <= self.processed_until {
+            info!(
+                "🔁 Suppressed duplicate segment (samples {}..{} already transcribed)",
+                start_sample, end
+            );
+            return None;
+        }
+
+        if start_sample < self.processed_until {
+            let overlap = (self.processed_until - start_sample) as usize;
+            info!(
+                "🔁 Trimmed {} already-transcribed samples from segment start",
+                overlap
+            );
+            samples.drain(..overlap);
+            start_sample = self.processed_until;
+        }
+
+        self.processed_until = end;
+        Some((start_sample, samples))
+    }
+
+    /// Forget processed audio (the VAD sample clock restarted)
+    pub fn reset(&mut self) {
+        self.processed_until = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_double_flush_is_suppressed() {
+        let mut spans = ProcessedSpans::default();
+
+        // Segment emitted while recording: samples 16000..48000
+        let tail = vec![0.5; 32000];
+        assert_eq!(
+            spans.filter(16000, tail.clone()).map(|(_, s)| s.len()),
+            Some(32000)
+        );
+
+        // The same tail re-emitted by a flush on stop
+        assert!(spans.filter(16000, tail).is_none());
+        // ...or only its last part
+        assert!(spans.filter(40000, vec![0.5; 8000]).is_none());
+    }
+
+    #[test]
+    fn test_partial_overlap_is_trimmed() {
+        let mut spans = ProcessedSpans::default();
+        spans.filter(0, vec![0.0; 1000]).unwrap();
+
+        let mut samples = vec![0.0; 500];
+        samples.extend(vec![1.0; 700]);
+        let (start, kept) = spans.filter(500, samples).unwrap();
+        assert_eq!(start, 1000);
+        assert_eq!(kept, vec![1.0; 700]);
+
+        // Later, non-overlapping audio passes untouched
+        assert_eq!(
+            spans
+                .filter(5000, vec![0.0; 10])
+                .map(|(start, s)| (start, s.len())),
+            Some((5000, 10))
+        );
+    }
+
+    #[test]
+    fn test_reset_accepts_restarted_clock() {
+        let mut spans = ProcessedSpans::default();
+        spans.filter(0, vec![0.0; 1000]).unwrap();
+        spans.reset();
+        assert!(spans.filter(0, vec![0.0; 1000]).is_some());
+    }
+}
@@ -0,0 +1,219 @@
+//! Speaking-rate pacing alerts
+//!
+//! People rehearsing a talk or reading to a teleprompter tend to speed up
+//! without noticing. The monitor follows the rate of consecutive fast
+//! segments and raises one alert per stretch once the rate has stayed above
+//! `pacing.max_wpm` for `pacing.sustain_secs` of capture time. Pauses count
+//! towards the rate, so slowing down or stopping to breathe ends the stretch.
+
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+use tracing::warn;
+
+use crate::config::PacingConfig;
+use crate::playback;
+
+const SAMPLE_RATE: f64 = 16000.0;
+
+/// Alert raised when a fast stretch reaches the sustain time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PacingAlert {
+    /// Average words per minute over the stretch, pauses included
+    pub wpm: f64,
+    pub max_wpm: f64,
+    pub sustained_s: f64,
+}
+
+/// Consecutive segments above the limit
+#[derive(Debug)]
+struct Stretch {
+    start: u64,
+    end: u64,
+    words: usize,
+    alerted: bool,
+}
+
+impl Stretch {
+    fn wpm(&self) -> f64 {
+        rate(self.words, self.end.saturating_sub(self.start))
+    }
+}
+
+#[derive(Debug)]
+pub struct PacingMonitor {
+    max_wpm: f64,
+    sustain_samples: u64,
+    stretch: Option<Stretch>,
+}
+
+impl PacingMonitor {
+    pub fn new(config: &PacingConfig) -> Self {
+        Self {
+            max_wpm: config.max_wpm,
+            sustain_samples: (config.sustain_secs * SAMPLE_RATE) as u64,
+            stretch: None,
+        }
+    }
+
+    /// Forget the current stretch (new recording)
+    pub fn reset(&mut self) {
+        self.stretch = None;
+    }
+
+    /// Note a segment of `len` samples at `start_sample` holding `words`
+    /// words, returning an alert when it completes a sustained fast stretch
+    pub fn segment(&mut self, start_sample: u64, len: usize, words: usize) -> Option<PacingAlert> {
+        let end = start_sample + len as u64;
+        if rate(words, len as u64) <= self.max_wpm {
+            self.stretch = None;
+            return None;
+        }
+
+        let stretch = match self.stretch.take() {
+            Some(mut stretch) => {
+                stretch.end = end;
+                stretch.words += words;
+                stretch
+            }
+            None => Stretch {
+                start: start_sample,
+                end,
+                words,
+                alerted: false,
+            },
+        };
+        // A long pause before this segment brings the average back down
+        let stretch = if stretch.wpm() > self.max_wpm {
+            stretch
+        } else {
+            Stretch {
+                start: start_sample,
+                end,
+                words,
+                alerted: false,
+            }
+        };
+
+        let duration = stretch.end - stretch.start;
+        let alert = (!stretch.alerted && duration >= self.sustain_samples).then(|| PacingAlert {
+            wpm: stretch.wpm(),
+            max_wpm: self.max_wpm,
+            sustained_s: duration as f64 / SAMPLE_RATE,
+        });
+        self.stretch = Some(Stretch {
+            alerted: stretch.alerted || alert.is_some(),
+            ..stretch
+        });
+        alert
+    }
+}
+
+fn rate(words: usize, samples: u64) -> f64 {
+    if samples == 0 {
+        return 0.0;
+    }
+    words as f64 * 60.0 * SAMPLE_RATE / samples as f64
+}
+
+/// Play the pacing chime without waiting for it to finish
+pub fn play_earcon() {
+    let path = match earcon_path() {
+        Ok(path) => path,
+        Err(e) => {
+            warn!("Failed to prepare pacing earcon: {:#}", e);
+            return;
+        }
+    };
+
+    match playback::play_wav(&path) {
+        Ok(mut child) => {
+            // Reap the player in the background
+            std::thread::spawn(move || child.wait());
+        }
+        Err(e) => warn!("Failed to play pacing earcon: {:#}", e),
+    }
+}
+
+/// Soft two-note chime, written to the data directory on first use
+fn earcon_path() -> Result<PathBuf> {
+    let path = swictation_paths::data_dir()
+        .join("earcons")
+        .join("pacing.wav");
+    if path.exists() {
+        return Ok(path);
+    }
+    std::fs::create_dir_all(path.parent().unwrap()).context("Failed to create earcon directory")?;
+
+    let sample_rate = 22050u32;
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+
+    let note_len = sample_rate as usize * 3 / 20; // 150 ms
+    for hz in [880.0f32, 660.0] {
+        for i in 0..note_len {
+            let t = i as f32 / sample_rate as f32;
+            // Raised-cosine envelope so the notes start and end without clicks
+            let envelope =
+                0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / note_len as f32).cos();
+            let sample = 0.15 * envelope * (2.0 * std::f32::consts::PI * hz * t).sin();
+            writer.write_sample((sample * i16::MAX as f32) as i16)?;
+        }
+    }
+    writer.finalize().context("Failed to write earcon")?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor() -> PacingMonitor {
+        PacingMonitor::new(&PacingConfig {
+            enabled: true,
+            max_wpm: 180.0,
+            sustain_secs: 10.0,
+            earcon: false,
+        })
+    }
+
+    #[test]
+    fn test_alerts_once_per_sustained_stretch() {
+        let mut pacing = monitor();
+        // 4 s segments of 14 words (210 WPM) separated by 0.5 s pauses
+        let mut start = 0;
+        let mut alerts = Vec::new();
+        for _ in 0..6 {
+            alerts.push(pacing.segment(start, 64_000, 14));
+            start += 72_000;
+        }
+
+        assert_eq!(alerts[0], None);
+        assert_eq!(alerts[1], None);
+        let alert = alerts[2].unwrap();
+        assert!((alert.sustained_s - 13.0).abs() < 1e-9);
+        assert!(alert.wpm > 180.0 && alert.wpm < 210.0);
+        assert!(alerts[3..].iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn test_slow_segment_or_long_pause_ends_stretch() {
+        let mut pacing = monitor();
+        pacing.segment(0, 96_000, 36); // 6 s at 360 WPM
+        pacing.segment(96_000, 64_000, 8); // 120 WPM
+        assert_eq!(pacing.segment(160_000, 96_000, 36), None);
+
+        // 20 s pause drags the average under the limit
+        assert_eq!(pacing.segment(576_000, 96_000, 36), None);
+
+        pacing.reset();
+        pacing.segment(0, 96_000, 36);
+        assert!(pacing.segment(96_000, 96_000, 36).is_some());
+    }
+}
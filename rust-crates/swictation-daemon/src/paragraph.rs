@@ -0,0 +1,98 @@
+//! Paragraph breaks after long pauses
+//!
+//! Stopping to think for a few seconds usually starts a new thought. When
+//! the silence between two segments reaches `paragraph_pause_secs`, the
+//! configured break is typed before the second one, the way commercial
+//! dictation tools lay out prose.
+
+use crate::config::ParagraphBreak;
+
+const SAMPLE_RATE: f32 = 16000.0;
+
+/// Break to type before a segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Break {
+    /// `<KEY:...>` markers sent to the injector
+    pub keys: &'static str,
+    /// Text the keys produce, for the edit history
+    pub typed: &'static str,
+}
+
+impl Break {
+    fn for_style(style: ParagraphBreak) -> Option<Self> {
+        match style {
+            ParagraphBreak::Nothing => None,
+            ParagraphBreak::Newline => Some(Self {
+                keys: "<KEY:Return>",
+                typed: "\n",
+            }),
+            ParagraphBreak::BlankLine => Some(Self {
+                keys: "<KEY:Return><KEY:Return>",
+                typed: "\n\n",
+            }),
+        }
+    }
+}
+
+/// Tracks where the previous segment ended in the capture stream
+#[derive(Debug)]
+pub struct PauseBreaks {
+    style: ParagraphBreak,
+    pause_samples: u64,
+    /// Capture position just past the previous segment
+    last_end: Option<u64>,
+}
+
+impl PauseBreaks {
+    pub fn new(style: ParagraphBreak, pause_secs: f32) -> Self {
+        Self {
+            style,
+            pause_samples: (pause_secs * SAMPLE_RATE) as u64,
+            last_end: None,
+        }
+    }
+
+    /// Forget the previous segment (new recording)
+    pub fn reset(&mut self) {
+        self.last_end = None;
+    }
+
+    /// Note the segment of `len` samples at `start_sample`, returning the
+    /// break to type before it if a long pause preceded it
+    pub fn segment(&mut self, start_sample: u64, len: usize) -> Option<Break> {
+        let previous_end = self.last_end.replace(start_sample + len as u64)?;
+        let pause = start_sample.saturating_sub(previous_end);
+        if pause < self.pause_samples {
+            return None;
+        }
+        Break::for_style(self.style)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_break_only_after_long_pause() {
+        let mut breaks = PauseBreaks::new(ParagraphBreak::BlankLine, 3.0);
+
+        // First segment of a recording never gets a break
+        assert_eq!(breaks.segment(0, 16_000), None);
+        // 1 s pause
+        assert_eq!(breaks.segment(32_000, 16_000), None);
+        // 3 s pause
+        let brk = breaks.segment(96_000, 16_000).unwrap();
+        assert_eq!(brk.typed, "\n\n");
+
+        breaks.reset();
+        assert_eq!(breaks.segment(500_000, 16_000), None);
+    }
+
+    #[test]
+    fn test_nothing_style_never_breaks() {
+        let mut breaks = PauseBreaks::new(ParagraphBreak::Nothing, 3.0);
+        breaks.segment(0, 16_000);
+        assert_eq!(breaks.segment(1_000_000, 16_000), None);
+    }
+}
@@ -1,25 +1,54 @@
 //! Audio → VAD → STT → Midstream → Corrections → Text Injection pipeline integration
 
 use anyhow::{Context, Result};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
-use tokio::sync::mpsc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
 use tracing::{info, warn};
 
 use midstreamer_text_transform::transform;
-use swictation_audio::AudioCapture;
-use swictation_broadcaster::MetricsBroadcaster;
-use swictation_metrics::{MetricsCollector, SegmentMetrics};
-use swictation_stt::{OrtRecognizer, SttEngine};
-use swictation_vad::{VadConfig, VadDetector, VadResult};
+use swictation_audio::{prosody, trim_silence, AudioCapture, TrimConfig};
+use swictation_broadcaster::{MetricsBroadcaster, ReadinessStage};
+use swictation_metrics::{
+    audio_content_hash, InferenceMetadata, MetricsCollector, ProsodyMetrics, SegmentAlternative,
+    SegmentMetrics,
+};
+use swictation_stt::{
+    BeamConfig, ContextBias, Decoding, ExecutionTarget, OrtRecognizer, Punctuator, SttEngine,
+    TdtConfig, WhisperRecognizer, WordConfidence,
+};
+use swictation_vad::{VadConfig, VadDetector, VadEvent, VadResult, VadTracePoint};
 
 use crate::capitalization::{
     apply_capitalization, normalize_0_6b_punctuation, process_capital_commands,
+    settle_spoken_punctuation,
 };
-use crate::config::DaemonConfig;
+use crate::commands::{CommandDetector, EditCommand, EditHistory};
+use crate::config::{DaemonConfig, PacingConfig, STT_MODEL_OVERRIDES};
 use crate::corrections::CorrectionEngine;
+use crate::diarization::Diarizer;
 use crate::gpu::get_gpu_memory_mb;
+use crate::headless::PcmReader;
+use crate::itn::Itn;
+use crate::language_model;
+use crate::language_routing::LanguageRouter;
+use crate::low_confidence;
+use crate::meeting::{self, SystemAudio};
+use crate::notification;
+use crate::overlap::ProcessedSpans;
+use crate::pacing::{self, PacingMonitor};
+use crate::paragraph::PauseBreaks;
+use crate::readalong::Readalong;
+use crate::readiness::Readiness;
+use crate::recordings;
+use crate::retry::{word_diff, RetryResult};
+use crate::spell;
+use crate::stages::{spawn_stage, STT_NICE, VAD_NICE};
+use crate::stt_watchdog;
+use crate::tts::Speaker;
+use crate::word_filter::WordFilter;
 
 /// Pipeline state
 pub struct Pipeline {
@@ -32,6 +61,16 @@ pub struct Pipeline {
     /// Speech-to-Text engine (adaptive: 1.1B GPU / 0.6B GPU / 0.6B CPU)
     stt: Arc<Mutex<SttEngine>>,
 
+    /// Whisper for segments in other `languages` (`None` when not routing)
+    language_router: Option<Arc<Mutex<LanguageRouter>>>,
+
+    /// Punctuation and capitalization model (`None` when not configured)
+    punctuator: Option<Arc<Mutex<Punctuator>>>,
+
+    /// Speaker labels for dictated segments (`None` unless
+    /// `diarization.live`)
+    diarizer: Option<Arc<Mutex<Diarizer>>>,
+
     /// Metrics collector
     metrics: Arc<Mutex<MetricsCollector>>,
 
@@ -49,6 +88,211 @@ pub struct Pipeline {
 
     /// Learned pattern corrections engine
     corrections: Arc<CorrectionEngine>,
+
+    /// Daemon configuration (model paths for retries, recording settings)
+    config: DaemonConfig,
+
+    /// Typed text of recorded segments, when not stored with the recordings
+    segment_texts: recordings::SegmentTexts,
+
+    /// Audio already sent to STT, shared by the VAD thread and the stop flush
+    processed_spans: Arc<Mutex<ProcessedSpans>>,
+
+    /// Wall-clock time of sample 0 of the current recording
+    capture_started_at: DateTime<Utc>,
+
+    /// Editing command recognition ("scratch that", "new line")
+    commands: CommandDetector,
+
+    /// Characters typed per segment, for "scratch that"
+    edit_history: Arc<Mutex<EditHistory>>,
+
+    /// Spoken numbers, dates and units written out ("$23")
+    itn: Itn,
+
+    /// Sensitive-word masking applied before injection
+    word_filter: WordFilter,
+
+    /// Spell mode: segments typed letter by letter, until "end spell" or the
+    /// next recording
+    spelling: Arc<AtomicBool>,
+
+    /// Recognitions cancelled by the STT watchdog since startup
+    stt_timeouts: Arc<AtomicU64>,
+
+    /// Paragraph breaks typed after long pauses
+    paragraph_breaks: Arc<Mutex<PauseBreaks>>,
+
+    /// Sustained speaking-rate tracking for pacing alerts
+    pacing: Arc<Mutex<PacingMonitor>>,
+
+    /// Read-back speech synthesis (`None` when disabled)
+    speaker: Option<Arc<Speaker>>,
+
+    /// Voice control commands, forwarded to the daemon
+    voice_commands: Arc<Mutex<Option<VoiceCommandSender>>>,
+
+    /// Bias phrases registered for this session, on top of `bias_phrases`
+    session_phrases: Arc<Mutex<Vec<String>>>,
+
+    /// Readalong mode: the script being rehearsed; segments follow it
+    /// instead of being typed
+    readalong: Arc<Mutex<Option<Readalong>>>,
+
+    /// Meeting mode: where segments go instead of the sinks
+    meeting_output: Option<mpsc::Sender<Result<String>>>,
+
+    /// Meeting mode: device mixed in with the microphone, and its capture
+    /// while recording
+    system_audio_device: Option<String>,
+    system_audio: Option<SystemAudio>,
+}
+
+type VoiceCommandSender = mpsc::UnboundedSender<EditCommand>;
+
+/// Transcribes audio that is not being recorded (watch-folder files) with
+/// the loaded model and the same post-processing as dictated text
+#[derive(Clone)]
+pub struct Transcriber {
+    stt: Arc<Mutex<SttEngine>>,
+    punctuator: Option<Arc<Mutex<Punctuator>>>,
+    corrections: Arc<CorrectionEngine>,
+    itn: Itn,
+    word_filter: WordFilter,
+}
+
+impl Transcriber {
+    /// Post-processed text of `samples`, and the words as recognized (with
+    /// their start times, where the engine has them)
+    pub fn transcribe(&self, samples: &[f32]) -> Result<(String, Vec<WordConfidence>)> {
+        let (text, words, punctuated) = {
+            let mut stt = self
+                .stt
+                .lock()
+                .map_err(|e| anyhow::anyhow!("STT lock error: {}", e))?;
+            let result = stt
+                .recognize(samples)
+                .context("Failed to transcribe audio")?;
+            (result.text, result.words, stt.writes_punctuation())
+        };
+        let text = post_process(
+            &text,
+            punctuated,
+            self.punctuator.as_deref(),
+            &self.corrections,
+            &self.itn,
+            &self.word_filter,
+        );
+        Ok((text, words))
+    }
+}
+
+/// Swaps the live STT model; `Send`, so a model load can run on a blocking
+/// thread while the pipeline keeps serving
+#[derive(Clone)]
+pub struct ModelSwitcher {
+    stt: Arc<Mutex<SttEngine>>,
+    config: DaemonConfig,
+    corrections: Arc<CorrectionEngine>,
+    session_phrases: Arc<Mutex<Vec<String>>>,
+}
+
+impl ModelSwitcher {
+    /// Override name of the live engine
+    fn live_spec(&self) -> Result<String> {
+        let stt_lock = self
+            .stt
+            .lock()
+            .map_err(|e| anyhow::anyhow!("STT lock error: {}", e))?;
+        Ok(engine_spec(&stt_lock))
+    }
+
+    /// Load `spec` for a one-off retry, or `None` when it is the live model
+    /// (see [`Pipeline::retry_segment`])
+    pub fn load_for_retry(&self, spec: &str) -> Result<Option<SttEngine>> {
+        if !needs_load(&self.live_spec()?, spec)? {
+            return Ok(None);
+        }
+        info!("Loading {} for a segment retry", spec);
+        load_forced_engine(&self.config, spec).map(Some)
+    }
+
+    /// Replace the live STT engine with another model, returning its name
+    ///
+    /// `spec` takes the same names as `stt_model_override`. The new engine
+    /// is loaded and warmed up before the old one is dropped, so a model that
+    /// fails to load leaves dictation on the current one. A recording in
+    /// progress carries on: segments recognized after the swap use the new
+    /// model, and the session, its bias phrases and the edit history stay.
+    pub fn switch(&self, spec: &str) -> Result<String> {
+        let current = self.live_spec()?;
+        if !needs_load(&current, spec)? {
+            return Ok(current);
+        }
+
+        info!("🔀 Switching STT model: {} → {}", current, spec);
+        let mut stt = load_forced_engine(&self.config, spec)?;
+        configure_engine(
+            &mut stt,
+            &self.config,
+            &self.corrections,
+            &self.session_phrases.lock().unwrap(),
+        );
+        if let SttEngine::Whisper(whisper) = &mut stt {
+            if self.config.languages.len() > 1 {
+                whisper
+                    .set_languages(&self.config.languages)
+                    .context("Invalid languages for the Whisper model")?;
+            }
+        }
+        if let Err(e) = stt.warm_up() {
+            warn!("⚠️  STT warm-up failed: {}", e);
+        }
+
+        let old = {
+            let mut stt_lock = self
+                .stt
+                .lock()
+                .map_err(|e| anyhow::anyhow!("STT lock error: {}", e))?;
+            std::mem::replace(&mut *stt_lock, stt)
+        };
+        // Unload outside the lock so recognition resumes right away
+        drop(old);
+
+        info!("✓ STT model switched to {}", spec);
+        Ok(spec.to_string())
+    }
+
+    /// Step down to a lighter engine under critical VRAM pressure, returning
+    /// the old and new model names, or `None` when nothing runs on the GPU
+    ///
+    /// The GPU model is replaced by a CPU engine first, so the old and new
+    /// models never sit in VRAM together and segments keep being recognized
+    /// meanwhile. The 1.1B model moves to the CPU itself once `quantize` has
+    /// written its INT8 files; without them the 0.6B model is loaded onto the
+    /// GPU instead (staying on the CPU if it does not fit). Any other GPU
+    /// model stays on the CPU. The session carries on as with
+    /// [`Self::switch`].
+    pub fn downgrade_for_vram(&self) -> Result<Option<(String, String)>> {
+        let current = self.live_spec()?;
+        let has_int8 = has_int8_encoder(&self.config.stt_1_1b_model_path);
+        let Some((interim, target)) = vram_downgrade_steps(&current, has_int8) else {
+            return Ok(None);
+        };
+
+        warn!("🚨 Unloading {} to relieve VRAM pressure", current);
+        self.switch(interim)?;
+        if target != interim {
+            if let Err(e) = self.switch(target) {
+                warn!(
+                    "⚠️ {} did not load, staying on {}: {:#}",
+                    target, interim, e
+                );
+                return Ok(Some((current, interim.to_string())));
+            }
+        }
+        Ok(Some((current, target.to_string())))
+    }
 }
 
 impl Pipeline {
@@ -57,19 +301,12 @@ impl Pipeline {
     pub async fn new(
         config: DaemonConfig,
         gpu_provider: Option<String>,
+        readiness: &Readiness,
     ) -> Result<(Self, mpsc::Receiver<Result<String>>)> {
         info!("Initializing Audio capture...");
-        let audio_config = swictation_audio::AudioConfig {
-            sample_rate: 16000,
-            channels: 1,
-            blocksize: 1024,
-            buffer_duration: 10.0,
-            device_index: config.audio_device_index,
-            streaming_mode: true,
-            chunk_duration: 0.5,
-        };
-        let audio =
-            AudioCapture::new(audio_config).context("Failed to initialize audio capture")?;
+        let audio = AudioCapture::new(live_audio_config(&config))
+            .context("Failed to initialize audio capture")?;
+        readiness.reached(ReadinessStage::AudioOk).await;
 
         info!(
             "Initializing VAD with {} provider...",
@@ -79,12 +316,15 @@ impl Pipeline {
             .min_silence(config.vad_min_silence)
             .min_speech(config.vad_min_speech)
             .max_speech(config.vad_max_speech)
+            .hangover_frames(config.vad_hangover_frames)
+            .trace(config.vad_trace_capacity)
             .threshold(config.vad_threshold)
             .provider(gpu_provider.clone())
             .num_threads(config.num_threads)
             .debug(); // Enable VAD debug output for troubleshooting
 
         let vad = VadDetector::new(vad_config).context("Failed to initialize VAD")?;
+        readiness.reached(ReadinessStage::VadOk).await;
 
         // ADAPTIVE MODEL SELECTION based on GPU VRAM availability
         // Decision tree:
@@ -96,71 +336,69 @@ impl Pipeline {
         //   "auto" = VRAM-based selection (default)
         //   "0.6b-cpu" = Force 0.6B CPU
         //   "0.6b-gpu" = Force 0.6B GPU
+        //   "1.1b-cpu" = Force 1.1B CPU (INT8 files from `quantize`)
         //   "1.1b-gpu" = Force 1.1B GPU
+        //   "whisper-cpu" / "whisper-gpu" = Whisper (multilingual)
 
-        let stt = if config.stt_model_override != "auto" {
+        readiness.reached(ReadinessStage::SttLoading).await;
+        let mut stt = if config.stt_model_override != "auto" {
             // MANUAL OVERRIDE: User specified exact model
             info!("STT model override active: {}", config.stt_model_override);
 
-            match config.stt_model_override.as_str() {
-                "1.1b-gpu" => {
-                    info!("  Loading Parakeet-TDT-1.1B-INT8 via ONNX Runtime (forced)...");
-                    let ort_recognizer = OrtRecognizer::new(&config.stt_1_1b_model_path, true)
-                        .map_err(|e| {
-                            anyhow::anyhow!(
-                                "Failed to load 1.1B INT8 model from {}. \
-                            \nError: {}",
-                                config.stt_1_1b_model_path.display(),
-                                e
-                            )
-                        })?;
-                    info!("✓ Parakeet-TDT-1.1B-INT8 loaded successfully (GPU, forced)");
-                    SttEngine::Parakeet1_1B(ort_recognizer)
-                }
-                "0.6b-gpu" => {
-                    info!("  Loading Parakeet-TDT-0.6B via ONNX Runtime (GPU, forced)...");
-                    let ort_recognizer = OrtRecognizer::new(&config.stt_0_6b_model_path, true)
-                        .map_err(|e| {
-                            anyhow::anyhow!(
-                                "Failed to load 0.6B GPU model from {}. \
-                            \nError: {}",
-                                config.stt_0_6b_model_path.display(),
-                                e
-                            )
-                        })?;
-                    info!("✓ Parakeet-TDT-0.6B loaded successfully (GPU, forced)");
-                    SttEngine::Parakeet0_6B(ort_recognizer)
-                }
-                "0.6b-cpu" => {
-                    info!("  Loading Parakeet-TDT-0.6B via ONNX Runtime (CPU, forced)...");
-                    let ort_recognizer = OrtRecognizer::new(&config.stt_0_6b_model_path, false)
-                        .map_err(|e| {
-                            anyhow::anyhow!(
-                                "Failed to load 0.6B CPU model from {}. \
-                            \nError: {}",
-                                config.stt_0_6b_model_path.display(),
-                                e
-                            )
-                        })?;
-                    info!("✓ Parakeet-TDT-0.6B loaded successfully (CPU, forced)");
-                    SttEngine::Parakeet0_6B(ort_recognizer)
-                }
-                _ => {
-                    return Err(anyhow::anyhow!(
-                        "Invalid stt_model_override: '{}'. \
-                        Valid options: 'auto', '0.6b-cpu', '0.6b-gpu', '1.1b-gpu'",
-                        config.stt_model_override
-                    ));
-                }
-            }
+            load_forced_engine(&config, &config.stt_model_override)?
         } else {
             // AUTO MODE: VRAM-based adaptive selection
             info!("STT model selection: auto (VRAM-based)");
             info!("Detecting GPU memory for adaptive model selection...");
             let vram_mb = get_gpu_memory_mb().map(|(total, _free)| total);
+            // DirectML on Windows (any vendor), ROCm for AMD on Linux, CUDA otherwise
+            let gpu_check = if cfg!(target_os = "windows") {
+                "Check the GPU driver supports DirectX 12 (DirectML)"
+            } else if gpu_provider.as_deref() == Some("rocm") {
+                "Check ROCm installation and an ONNX Runtime built with ROCm: rocm-smi"
+            } else {
+                "Check CUDA/cuDNN installation: nvidia-smi"
+            };
 
-            if let Some(vram) = vram_mb {
-                info!("Detected GPU with {}MB VRAM", vram);
+            if let ExecutionTarget::OpenVino(device) =
+                ExecutionTarget::from_provider(gpu_provider.as_deref())
+            {
+                // Intel iGPU/NPU share system RAM, so there's no VRAM to size the
+                // model by; the 0.6B model is what they run at interactive speed
+                info!(
+                    "  Loading Parakeet-TDT-0.6B via ONNX Runtime (OpenVINO {})...",
+                    device
+                );
+
+                let ort_recognizer = OrtRecognizer::new(
+                    &config.stt_0_6b_model_path,
+                    ExecutionTarget::OpenVino(device.clone()),
+                )
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "Failed to load 0.6B model on OpenVINO {}. \
+                        \nTroubleshooting:\
+                        \n  1. Verify model files: ls {}\
+                        \n  2. Check the Intel GPU/NPU driver and an ONNX Runtime built with OpenVINO\
+                        \n  3. Try CPU fallback by setting stt_model_override=\"0.6b-cpu\" in config\
+                        \nError: {}",
+                        device,
+                        config.stt_0_6b_model_path.display(),
+                        e
+                    )
+                })?;
+
+                info!(
+                    "✓ Parakeet-TDT-0.6B loaded successfully (OpenVINO {})",
+                    device
+                );
+                SttEngine::Parakeet0_6B(ort_recognizer)
+            } else if let Some(vram) = vram_mb {
+                info!(
+                    "Detected GPU with {}MB VRAM ({})",
+                    vram,
+                    gpu_provider.as_deref().unwrap_or("no GPU provider")
+                );
 
                 if vram >= 6000 {
                     // High VRAM: Use 1.1B INT8 model for best quality (5.77% WER)
@@ -172,10 +410,10 @@ impl Pipeline {
                         "Failed to load 1.1B INT8 model despite {}MB VRAM. \
                         \nTroubleshooting:\
                         \n  1. Verify model files exist: ls {}\
-                        \n  2. Check CUDA/cuDNN installation: nvidia-smi\
-                        \n  3. Ensure ONNX Runtime CUDA EP is available\
+                        \n  2. {}\
+                        \n  3. Ensure the ONNX Runtime GPU execution provider is available\
                         \n  4. Try 0.6B fallback by setting stt_model_override=\"0.6b-gpu\" in config\
-                        \nError: {}", vram, config.stt_1_1b_model_path.display(), e
+                        \nError: {}", vram, config.stt_1_1b_model_path.display(), gpu_check, e
                     ))?;
 
                     info!("✓ Parakeet-TDT-1.1B-INT8 loaded successfully (GPU)");
@@ -190,10 +428,10 @@ impl Pipeline {
                             "Failed to load 0.6B GPU model despite {}MB VRAM. \
                             \nTroubleshooting:\
                             \n  1. Verify model files: ls {}\
-                            \n  2. Check CUDA availability: nvidia-smi\
-                            \n  3. Verify ONNX Runtime CUDA support\
+                            \n  2. {}\
+                            \n  3. Verify ONNX Runtime GPU execution provider support\
                             \n  4. Try CPU fallback by setting stt_model_override=\"0.6b-cpu\" in config\
-                            \nError: {}", vram, config.stt_0_6b_model_path.display(), e
+                            \nError: {}", vram, config.stt_0_6b_model_path.display(), gpu_check, e
                         ))?;
 
                     info!("✓ Parakeet-TDT-0.6B loaded successfully (GPU)");
@@ -223,7 +461,7 @@ impl Pipeline {
                 }
             } else {
                 // No GPU detected: Fall back to CPU
-                warn!("⚠️  No GPU detected (nvidia-smi failed or no NVIDIA GPU)");
+                warn!("⚠️  No GPU detected (no GPU memory reported by nvidia-smi or DXGI)");
                 warn!("  Falling back to CPU mode (slower but functional)");
                 info!("  Loading Parakeet-TDT-0.6B via ONNX Runtime (CPU)...");
 
@@ -258,13 +496,23 @@ impl Pipeline {
             info!("   Minimum VRAM: {}MB", stt.vram_required_mb());
         }
 
+        // Pay the first-run allocation now rather than on the first segment
+        match stt.warm_up() {
+            Ok(elapsed) => info!(
+                "🔥 STT warmed up in {:.0}ms",
+                elapsed.as_secs_f64() * 1000.0
+            ),
+            Err(e) => warn!(
+                "⚠️  STT warm-up failed, the first segment will be slower: {}",
+                e
+            ),
+        }
+        readiness.reached(ReadinessStage::SttReady).await;
+
         info!("Initializing metrics collector...");
 
         // Initialize metrics collector with database
-        let metrics_db_path = dirs::data_local_dir()
-            .unwrap_or_else(|| std::path::PathBuf::from("."))
-            .join("swictation")
-            .join("metrics.db");
+        let metrics_db_path = metrics_db_path();
 
         // Ensure directory exists
         if let Some(parent) = metrics_db_path.parent() {
@@ -279,7 +527,8 @@ impl Pipeline {
             1000.0, // high_latency_threshold_ms
             80.0,   // gpu_memory_threshold_percent
         )
-        .context("Failed to initialize metrics collector")?;
+        .context("Failed to initialize metrics collector")?
+        .with_latency_budgets(config.latency_budgets.clone());
 
         // Enable GPU monitoring if provider is available
         if let Some(ref provider) = gpu_provider {
@@ -292,12 +541,8 @@ impl Pipeline {
 
         // Initialize learned corrections engine with hot-reloading
         info!("Initializing corrections engine...");
-        let corrections_dir = dirs::config_dir()
-            .unwrap_or_else(|| std::path::PathBuf::from(".config"))
-            .join("swictation");
-
-        // Ensure config directory exists
-        std::fs::create_dir_all(&corrections_dir).context("Failed to create config directory")?;
+        // Created on first use
+        let corrections_dir = swictation_paths::get_config_dir()?;
 
         let mut corrections = CorrectionEngine::new(corrections_dir, config.phonetic_threshold);
         if let Err(e) = corrections.start_watching() {
@@ -309,17 +554,75 @@ impl Pipeline {
         let corrections = Arc::new(corrections);
         info!("✓ Corrections engine initialized");
 
+        configure_engine(&mut stt, &config, &corrections, &[]);
+
+        let language_router = match LanguageRouter::load(&config, &mut stt) {
+            Ok(router) => router.map(|router| Arc::new(Mutex::new(router))),
+            Err(e) => {
+                warn!("⚠️ Language routing disabled: {:#}", e);
+                None
+            }
+        };
+
+        let punctuator = config.punctuation_model_path.as_ref().and_then(|path| {
+            Punctuator::new(path)
+                .map(|punctuator| Arc::new(Mutex::new(punctuator)))
+                .map_err(|e| warn!("⚠️ Punctuation model not loaded: {}", e))
+                .ok()
+        });
+
+        let diarizer = config
+            .diarization
+            .live
+            .then(|| Diarizer::new(&config.diarization))
+            .flatten()
+            .map(|diarizer| Arc::new(Mutex::new(diarizer)));
+
+        let commands = CommandDetector::new(
+            config.command_model_path.as_deref(),
+            config.command_threshold,
+            config.accessibility_mode,
+        );
+        let itn = Itn::new(&config.itn);
+        let word_filter = WordFilter::new(&config.word_filter);
+        let paragraph_breaks =
+            PauseBreaks::new(config.paragraph_break, config.paragraph_pause_secs);
+        let pacing = PacingMonitor::new(&config.pacing);
+        let speaker = Speaker::new(&config.tts).map(Arc::new);
+
         #[allow(clippy::arc_with_non_send_sync)]
         let pipeline = Self {
             audio: Arc::new(Mutex::new(audio)),
             vad: Arc::new(Mutex::new(vad)),
             stt: Arc::new(Mutex::new(stt)),
+            language_router,
+            punctuator,
+            diarizer,
             metrics: Arc::new(Mutex::new(metrics)),
             is_recording: false,
             session_id: Arc::new(Mutex::new(None)),
             broadcaster: Arc::new(Mutex::new(None)),
             tx,
             corrections,
+            config,
+            segment_texts: recordings::SegmentTexts::default(),
+            processed_spans: Arc::new(Mutex::new(ProcessedSpans::default())),
+            capture_started_at: Utc::now(),
+            commands,
+            edit_history: Arc::new(Mutex::new(EditHistory::default())),
+            itn,
+            word_filter,
+            spelling: Arc::new(AtomicBool::new(false)),
+            stt_timeouts: Arc::new(AtomicU64::new(0)),
+            paragraph_breaks: Arc::new(Mutex::new(paragraph_breaks)),
+            pacing: Arc::new(Mutex::new(pacing)),
+            speaker,
+            voice_commands: Arc::new(Mutex::new(None)),
+            session_phrases: Arc::new(Mutex::new(Vec::new())),
+            readalong: Arc::new(Mutex::new(None)),
+            meeting_output: None,
+            system_audio_device: None,
+            system_audio: None,
         };
 
         Ok((pipeline, rx))
@@ -327,41 +630,109 @@ impl Pipeline {
 
     /// Start recording and processing
     pub async fn start_recording(&mut self) -> Result<()> {
+        self.start_processing(None).await.map(|_| ())
+    }
+
+    /// Start processing PCM from `input` instead of the microphone
+    ///
+    /// The returned receiver resolves once the input has ended and all of it
+    /// has been transcribed, except the speech `stop_recording` flushes.
+    pub async fn start_recording_from(
+        &mut self,
+        input: PcmReader,
+    ) -> Result<Option<oneshot::Receiver<()>>> {
+        self.start_processing(Some(input)).await
+    }
+
+    /// `None` when already recording
+    async fn start_processing(
+        &mut self,
+        input: Option<PcmReader>,
+    ) -> Result<Option<oneshot::Receiver<()>>> {
         if self.is_recording {
-            return Ok(());
+            return Ok(None);
         }
 
         self.is_recording = true;
         info!("Recording started");
 
+        // Sample positions restart at 0 for every recording
+        self.vad.lock().unwrap().clear();
+        self.processed_spans.lock().unwrap().reset();
+        self.edit_history.lock().unwrap().clear();
+        self.word_filter.set_bypass(false);
+        self.spelling.store(false, Ordering::Relaxed);
+        self.paragraph_breaks.lock().unwrap().reset();
+        self.pacing.lock().unwrap().reset();
+        if let Some(diarizer) = &self.diarizer {
+            diarizer.lock().unwrap().reset();
+        }
+
         // Create BOUNDED channel for audio chunks (cpal callback → VAD/STT processing)
+        // Each chunk carries the capture position of its first sample
         // Capacity: 20 chunks = 10 seconds at 0.5s/chunk
         // This prevents memory exhaustion if processing falls behind
-        let (audio_tx, mut audio_rx) = mpsc::channel::<Vec<f32>>(20);
+        let (audio_tx, mut audio_rx) = mpsc::channel::<(u64, Vec<f32>)>(20);
 
         // Track dropped chunks for metrics
         let dropped_chunks = Arc::new(std::sync::atomic::AtomicU64::new(0));
         let dropped_chunks_clone = dropped_chunks.clone();
+        let captured_samples = std::sync::atomic::AtomicU64::new(0);
+        // VAD hands chunk buffers back to the audio thread once copied
+        let chunk_pool = self.audio.lock().unwrap().chunk_pool();
+
+        if let Some(mut input) = input {
+            // Nothing is dropped here: reading simply waits for the VAD
+            self.capture_started_at = Utc::now();
+            spawn_stage("swictation-pcm-input", 0, move || {
+                let mut offset = 0u64;
+                loop {
+                    let chunk = match input.next_chunk() {
+                        Ok(Some(chunk)) => chunk,
+                        Ok(None) => break,
+                        Err(e) => {
+                            warn!("Failed to read PCM input: {}", e);
+                            break;
+                        }
+                    };
+                    let len = chunk.len() as u64;
+                    if audio_tx.blocking_send((offset, chunk)).is_err() {
+                        break; // Recording stopped
+                    }
+                    offset += len;
+                }
+            })?;
+        } else {
+            if let Some(device) = &self.system_audio_device {
+                match SystemAudio::start(device, live_audio_config(&self.config)) {
+                    Ok(system) => self.system_audio = Some(system),
+                    Err(e) => warn!("⚠️ Meeting continues without system audio: {:#}", e),
+                }
+            }
+            let system_mix = self.system_audio.as_ref().map(SystemAudio::queue);
 
-        // Set up audio callback to push chunks via channel
-        {
+            // Set up audio callback to push chunks via channel
             let mut audio = self.audio.lock().unwrap();
             let audio_tx_clone = audio_tx.clone();
+            let dropped_pool = chunk_pool.clone();
 
-            audio.set_chunk_callback(move |chunk| {
-                // This runs in cpal's audio thread - must be non-blocking
-                match audio_tx_clone.try_send(chunk) {
+            audio.set_chunk_callback(move |mut chunk| {
+                // This runs in cpal's audio thread - must be non-blocking and must not
+                // allocate or log (the backpressure monitor below reports drops)
+                if let Some(system) = &system_mix {
+                    meeting::mix(&mut chunk, system);
+                }
+                let offset = captured_samples
+                    .fetch_add(chunk.len() as u64, std::sync::atomic::Ordering::Relaxed);
+                match audio_tx_clone.try_send((offset, chunk)) {
                     Ok(_) => {
                         // Successfully queued chunk
                     }
-                    Err(mpsc::error::TrySendError::Full(_)) => {
+                    Err(mpsc::error::TrySendError::Full((_, chunk))) => {
                         // Channel full - backpressure activated
                         // Drop this chunk to prevent blocking audio thread
+                        dropped_pool.recycle(chunk);
                         dropped_chunks_clone.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-                        eprintln!(
-                            "WARNING: Audio chunk dropped (processing too slow). Total dropped: {}",
-                            dropped_chunks_clone.load(std::sync::atomic::Ordering::Relaxed)
-                        );
                     }
                     Err(mpsc::error::TrySendError::Closed(_)) => {
                         // Channel closed - recording stopped
@@ -370,8 +741,10 @@ impl Pipeline {
             });
 
             // Start audio capture (cpal will invoke callback)
+            self.capture_started_at = Utc::now();
             audio.start()?;
         }
+        let (drained_tx, drained_rx) = oneshot::channel();
 
         // Log backpressure warning if chunks are being dropped
         let dropped_monitor = dropped_chunks.clone();
@@ -393,23 +766,55 @@ impl Pipeline {
 
         // Clone components for parallel VAD/STT processing
         let vad = self.vad.clone();
+        let processed_spans = self.processed_spans.clone();
         let stt = self.stt.clone();
-        let tx = self.tx.clone();
+        let language_router = self.language_router.clone();
+        let punctuator = self.punctuator.clone();
+        let diarizer = self.diarizer.clone();
+        let tx = self.output();
         let metrics = self.metrics.clone();
         let session_id = self.session_id.clone();
         let broadcaster = self.broadcaster.clone();
         let corrections = self.corrections.clone();
-
-        // Create channel for VAD → STT communication
+        let save_recordings = self.config.save_recordings;
+        let segment_texts = self.segment_texts.clone();
+        let trim = self.config.trim_silence;
+        let capture_started_at = self.capture_started_at;
+        let commands = self.commands.clone();
+        let edit_history = self.edit_history.clone();
+        let itn = self.itn.clone();
+        let word_filter = self.word_filter.clone();
+        let spelling = self.spelling.clone();
+        let readalong = self.readalong.clone();
+        let stt_timeouts = self.stt_timeouts.clone();
+        let stt_timeout = self.config.stt_timeout();
+        let paragraph_breaks = self.paragraph_breaks.clone();
+        let analytics_prosody = self.config.analytics_prosody;
+        let pacing = self.pacing.clone();
+        let pacing_config = self.config.pacing.clone();
+        let low_confidence_threshold = self.config.low_confidence_threshold;
+        let mark_low_confidence = self.config.mark_low_confidence;
+        let speaker = self.speaker.clone();
+        let voice_commands = self.voice_commands.clone();
+
+        // Create channel for VAD → STT communication (start sample, samples)
         // Capacity: 10 speech segments (allows VAD to detect ahead while STT processes)
-        let (vad_tx, mut stt_rx) = mpsc::channel::<Vec<f32>>(10);
+        let (vad_tx, mut stt_rx) = mpsc::channel::<(u64, Vec<f32>)>(10);
+
+        // Handle for spawning broadcasts from the (non-async) stage threads
+        let runtime = tokio::runtime::Handle::current();
 
-        // Spawn VAD task (processes audio chunks and detects speech segments)
-        let _vad_task = tokio::spawn(async move {
+        // VAD thread (processes audio chunks and detects speech segments)
+        let level_broadcaster = broadcaster.clone();
+        let level_runtime = runtime.clone();
+        spawn_stage("swictation-vad", VAD_NICE, move || {
             let mut buffer = Vec::with_capacity(16000); // 1 second buffer
             let mut chunk_count = 0;
+            // Capture position of the next expected chunk (gaps = dropped chunks)
+            let mut next_offset = 0u64;
+            let mut speech = Vec::new();
 
-            while let Some(chunk) = audio_rx.recv().await {
+            while let Some((offset, chunk)) = audio_rx.blocking_recv() {
                 chunk_count += 1;
                 if chunk_count % 10 == 0 {
                     eprintln!(
@@ -418,7 +823,21 @@ impl Pipeline {
                         chunk.len()
                     );
                 }
+
+                let gap = offset.saturating_sub(next_offset);
+                next_offset = offset + chunk.len() as u64;
+                if gap > 0 {
+                    // Audio before the gap must reach the VAD before its clock skips ahead
+                    speech.extend(vad_step(&vad, &processed_spans, &buffer));
+                    broadcast_speech_activity(&level_broadcaster, &level_runtime, &vad);
+                    buffer.clear();
+                    if let Ok(mut vad_lock) = vad.lock() {
+                        vad_lock.skip_samples(gap);
+                    }
+                }
+
                 buffer.extend_from_slice(&chunk);
+                chunk_pool.recycle(chunk);
 
                 // Process in 0.5 second chunks for VAD
                 while buffer.len() >= 8000 {
@@ -432,54 +851,41 @@ impl Pipeline {
                     eprintln!("DEBUG: Processing VAD chunk, buffer len: {}, max_amplitude: {:.6}, avg_amplitude: {:.6}",
                               buffer.len(), max_amplitude, avg_amplitude);
 
-                    // Process through VAD (scoped to ensure lock is dropped before any async ops)
-                    let vad_result = {
-                        let mut vad_lock = match vad.lock() {
-                            Ok(v) => v,
-                            Err(e) => {
-                                eprintln!("VAD lock error: {}", e);
-                                continue;
-                            }
-                        };
-                        vad_lock.process_audio(&vad_chunk)
-                    }; // vad_lock automatically dropped here
-
-                    match vad_result {
-                        Ok(VadResult::Speech {
-                            samples: speech_samples,
-                            ..
-                        }) => {
-                            eprintln!(
-                                "DEBUG: VAD detected speech! {} samples",
-                                speech_samples.len()
-                            );
-
-                            // Send speech segment to STT task (non-blocking with backpressure)
-                            if let Err(e) = vad_tx.send(speech_samples).await {
-                                eprintln!("Failed to send speech segment to STT task: {}", e);
-                                break; // STT task has terminated
-                            }
-                        }
-                        Ok(VadResult::Silence) => {
-                            eprintln!("DEBUG: VAD detected silence");
-                            // Skip silence (VAD ensures we only transcribe speech segments)
-                        }
-                        Err(e) => {
-                            eprintln!("VAD error: {}", e);
-                        }
+                    speech.extend(vad_step(&vad, &processed_spans, &vad_chunk));
+                    broadcast_audio_level(&level_broadcaster, &level_runtime, &vad, &vad_chunk);
+                    broadcast_speech_activity(&level_broadcaster, &level_runtime, &vad);
+                }
+
+                for segment in speech.drain(..) {
+                    // Send speech segment to the STT thread (blocks when it is behind)
+                    if let Err(e) = vad_tx.blocking_send(segment) {
+                        eprintln!("Failed to send speech segment to STT thread: {}", e);
+                        return; // STT thread has terminated
                     }
                 }
             }
-        });
-
-        // Spawn STT task (processes speech segments from VAD in parallel)
-        let _stt_task = tokio::spawn(async move {
-            while let Some(speech_samples) = stt_rx.recv().await {
+        })?;
+
+        // STT thread (processes speech segments from VAD in parallel)
+        spawn_stage("swictation-stt", STT_NICE, move || {
+            // Dropped when the VAD has passed on everything it was given
+            let _drained = drained_tx;
+            while let Some((start_sample, speech_samples)) = stt_rx.blocking_recv() {
+                if is_read_back_echo(
+                    speaker.as_deref(),
+                    capture_started_at,
+                    start_sample,
+                    speech_samples.len(),
+                ) {
+                    continue;
+                }
                 eprintln!("DEBUG: STT processing {} samples", speech_samples.len());
+                let (stt_samples, trimmed_silence_ms) = trim_for_stt(&speech_samples, trim);
+                let pending_command = commands.start(stt_samples);
 
                 // Process through STT (scoped to ensure lock is dropped before any async ops)
                 let stt_start = Instant::now();
-                let (text, stt_latency, is_0_6b) = {
+                let (text, alternatives, words, stt_latency, punctuated, inference) = {
                     let mut stt_lock = match stt.lock() {
                         Ok(s) => s,
                         Err(e) => {
@@ -488,21 +894,81 @@ impl Pipeline {
                         }
                     };
 
-                    // Use STT engine (OrtRecognizer)
-                    let result = stt_lock.recognize(&speech_samples).unwrap_or_else(|e| {
-                        eprintln!("STT transcribe error: {}", e);
-                        swictation_stt::RecognitionResult {
-                            text: String::new(),
-                            confidence: 0.0,
-                            processing_time_ms: 0.0,
-                        }
-                    });
-                    let text = result.text;
+                    // Segments in another language go to Whisper
+                    let mut router_lock = language_router.as_ref().and_then(|r| r.lock().ok());
+                    let engine = match router_lock.as_mut().and_then(|r| r.route(stt_samples)) {
+                        Some(whisper) => whisper,
+                        None => &mut *stt_lock,
+                    };
+
+                    // Use STT engine (OrtRecognizer), cancelled if it hangs
+                    let on_timeout = stt_timeout_reporter(
+                        &stt_timeouts,
+                        &broadcaster,
+                        &runtime,
+                        stt_samples.len(),
+                        stt_timeout,
+                    );
+                    let result =
+                        stt_watchdog::recognize(engine, stt_samples, stt_timeout, on_timeout)
+                            .unwrap_or_else(|e| {
+                                eprintln!("STT transcribe error: {}", e);
+                                swictation_stt::RecognitionResult {
+                                    text: String::new(),
+                                    confidence: 0.0,
+                                    processing_time_ms: 0.0,
+                                    alternatives: Vec::new(),
+                                    words: Vec::new(),
+                                }
+                            });
                     let stt_latency = stt_start.elapsed().as_millis() as f64;
-                    let is_0_6b = stt_lock.model_size() == "0.6B";
-                    (text, stt_latency, is_0_6b)
+                    let punctuated = engine.writes_punctuation();
+                    let inference = inference_metadata(engine);
+                    (
+                        result.text,
+                        result.alternatives,
+                        result.words,
+                        stt_latency,
+                        punctuated,
+                        inference,
+                    )
                 }; // stt_lock automatically dropped here
 
+                let paragraph = paragraph_breaks
+                    .lock()
+                    .unwrap()
+                    .segment(start_sample, speech_samples.len());
+
+                // Explicit commands replace any automatic paragraph break
+                if let Some(command) = pending_command.finish(&text) {
+                    if let Some(keys) = run_command(
+                        command,
+                        &edit_history,
+                        &word_filter,
+                        &spelling,
+                        speaker.as_deref(),
+                        &voice_commands,
+                    ) {
+                        if let Err(e) = tx.blocking_send(Ok(keys)) {
+                            eprintln!("Failed to send command (consumer dropped): {}", e);
+                        }
+                    }
+                    continue;
+                }
+
+                if spelling.load(Ordering::Relaxed) {
+                    if let Some(spelled) = spell_segment(&text, &edit_history) {
+                        if let Err(e) = tx.blocking_send(Ok(spelled)) {
+                            eprintln!("Failed to send spelled text (consumer dropped): {}", e);
+                        }
+                    }
+                    continue;
+                }
+
+                if follow_script(&readalong, &text, &words, &broadcaster, &runtime) {
+                    continue;
+                }
+
                 if !text.is_empty() {
                     // Transform voice commands → symbols (Midstream)
                     // "hello comma world" → "hello, world"
@@ -513,23 +979,24 @@ impl Pipeline {
                     // - "comma" → "," (word replaced with symbol)
                     // - "period" → "period." (word kept + symbol added at end of sentence)
                     //
-                    // Solution: Smart normalization that avoids duplicate punctuation:
+                    // With a punctuation model its punctuation is replaced by the model's.
+                    // Without one, smart normalization avoids duplicate punctuation:
                     // - If punctuation WORD exists → remove the symbol (it's redundant)
                     // - If punctuation WORD doesn't exist → convert symbol to word
                     //
                     // This ensures Secretary Mode always sees consistent word-based input.
+                    // Whisper punctuates the same way and gets the same treatment.
                     // 1.1B model outputs raw text without ITN - no conversion needed.
-                    let text = if is_0_6b {
-                        normalize_0_6b_punctuation(&text)
-                    } else {
-                        text
-                    };
+                    let text = prepare_text(&text, punctuated, punctuator.as_deref());
 
                     // Step 1: Process capital commands first ("capital r robert" → "Robert")
                     let with_capitals = process_capital_commands(&text);
 
+                    // Step 1b: Write out numbers, dates and units ("twenty dollars" → "$20")
+                    let normalized = itn.apply(&with_capitals);
+
                     // Step 2: Transform punctuation ("comma" → ",")
-                    let transformed = transform(&with_capitals);
+                    let transformed = transform(&normalized);
 
                     // Step 3: Apply learned corrections ("arkon" → "archon")
                     let corrected = corrections.apply(&transformed, "all");
@@ -541,20 +1008,44 @@ impl Pipeline {
                         }
                     }
 
-                    // Step 4: Apply automatic capitalization rules
-                    let capitalized = apply_capitalization(&corrected);
+                    // Step 4: Mask or drop filtered words ("d***")
+                    let (filtered, masked_words) = word_filter.apply(&corrected);
+
+                    // Step 5: Apply automatic capitalization rules
+                    let capitalized = apply_capitalization(&filtered);
 
                     let transform_latency = transform_start.elapsed().as_micros() as f64;
 
                     info!("Transcribed: {} → {}", text, capitalized);
 
+                    let nbest = post_process_alternatives(
+                        &alternatives,
+                        &capitalized,
+                        punctuated,
+                        punctuator.as_deref(),
+                        &corrections,
+                        &itn,
+                        &word_filter,
+                    );
+
                     // Track segment metrics (ephemeral - no text stored in DB)
                     let word_count = capitalized.split_whitespace().count() as i32;
                     let char_count = capitalized.len() as i32;
 
+                    check_pacing(
+                        &pacing,
+                        &pacing_config,
+                        &broadcaster,
+                        &runtime,
+                        start_sample,
+                        speech_samples.len(),
+                        word_count,
+                    );
+
                     // Get current session ID (scoped to ensure lock is dropped)
                     let current_session_id = { *session_id.lock().unwrap() };
 
+                    let mut stored_segment_id = None;
                     if let Some(sid) = current_session_id {
                         let duration_s = (speech_samples.len() as f64) / 16000.0; // samples / sample_rate
                                                                                   // Note: VAD latency not tracked in parallel mode (VAD runs independently)
@@ -574,15 +1065,47 @@ impl Pipeline {
                             transform_latency_us: transform_latency,
                             injection_latency_ms: 0.0,
                             total_latency_ms,
-                            transformations_count: if text != capitalized { 1 } else { 0 },
+                            transformations_count: i32::from(text != capitalized)
+                                + i32::from(paragraph.is_some()),
                             keyboard_actions_count: 0,
+                            trimmed_silence_ms,
+                            start_sample: Some(start_sample),
+                            audio_start: Some(sample_time(capture_started_at, start_sample)),
+                            inference: Some(inference.clone()),
+                            nbest: nbest.clone(),
+                            masked_words: masked_words as i32,
+                            content_hash: save_recordings
+                                .then(|| audio_content_hash(sid, start_sample, &speech_samples)),
+                            prosody: analytics_prosody
+                                .then(|| segment_prosody(&speech_samples, word_count))
+                                .flatten(),
+                            speaker: segment_speaker(diarizer.as_deref(), &speech_samples),
                         };
 
                         // Add segment to metrics (scoped to ensure lock is dropped)
-                        {
-                            if let Err(e) = metrics.lock().unwrap().add_segment(segment) {
-                                eprintln!("Failed to add segment metrics: {}", e);
+                        let (latency_warning, segment_id) = {
+                            let metrics = metrics.lock().unwrap();
+                            (
+                                metrics.check_latency(&segment),
+                                metrics.add_segment(segment),
+                            )
+                        };
+                        match segment_id {
+                            Ok(Some(segment_id)) => {
+                                stored_segment_id = Some(segment_id);
+                                if save_recordings {
+                                    save_recording(
+                                        &metrics,
+                                        &segment_texts,
+                                        segment_id,
+                                        &speech_samples,
+                                        &capitalized,
+                                    );
+                                }
                             }
+                            // Held in RAM while the metrics disk is full
+                            Ok(None) => {}
+                            Err(e) => eprintln!("Failed to add segment metrics: {}", e),
                         }
 
                         // Broadcast transcription to UI clients (scoped to ensure lock is dropped)
@@ -591,22 +1114,37 @@ impl Pipeline {
 
                         if let Some(broadcaster_ref) = broadcaster_clone {
                             let wpm = (word_count as f64 / (duration_s / 60.0)).min(300.0); // Cap at 300 WPM
-                            tokio::spawn({
+                            runtime.spawn({
                                 let text_clone = capitalized.clone();
                                 async move {
                                     broadcaster_ref
-                                        .add_transcription(
+                                        .add_stored_transcription(
+                                            sid,
+                                            stored_segment_id,
                                             text_clone,
                                             wpm,
                                             total_latency_ms,
                                             word_count,
+                                            Some(inference),
                                         )
                                         .await;
+                                    if let Some(warning) = latency_warning {
+                                        broadcaster_ref.broadcast_latency_warning(warning).await;
+                                    }
                                 }
                             });
                         }
                     }
 
+                    let capitalized = flag_low_confidence(
+                        capitalized,
+                        &words,
+                        low_confidence_threshold,
+                        mark_low_confidence,
+                        &broadcaster,
+                        &runtime,
+                    );
+
                     // Add trailing space between speech segments
                     let final_text = if capitalized.ends_with(char::is_whitespace) {
                         capitalized
@@ -614,15 +1152,53 @@ impl Pipeline {
                         format!("{} ", capitalized)
                     };
 
+                    if let Some(paragraph) = paragraph {
+                        edit_history.lock().unwrap().push(paragraph.typed);
+                        if let Err(e) = tx.blocking_send(Ok(paragraph.keys.to_string())) {
+                            eprintln!("Failed to send paragraph break (consumer dropped): {}", e);
+                        }
+                    }
+
+                    edit_history.lock().unwrap().push_segment(
+                        stored_segment_id,
+                        &final_text,
+                        nbest,
+                    );
+
                     // Send transcription (bounded channel - will block if consumer is slow)
-                    if let Err(e) = tx.send(Ok(final_text)).await {
+                    if let Err(e) = tx.blocking_send(Ok(final_text)) {
                         eprintln!("Failed to send transcription (consumer dropped): {}", e);
                     }
                 }
             }
-        });
+        })?;
 
-        Ok(())
+        Ok(Some(drained_rx))
+    }
+
+    /// Send segments to `output` instead of the sinks, with system audio
+    /// from `system_audio_device` mixed in, from the next recording on
+    /// (`None` returns to dictation)
+    pub fn set_meeting(
+        &mut self,
+        output: Option<mpsc::Sender<Result<String>>>,
+        system_audio_device: Option<String>,
+    ) {
+        self.meeting_output = output;
+        self.system_audio_device = system_audio_device;
+    }
+
+    /// Follow `readalong` with the segments from now on instead of typing
+    /// them (`None` returns to dictation), returning the one it replaces
+    pub fn set_readalong(&self, readalong: Option<Readalong>) -> Option<Readalong> {
+        std::mem::replace(&mut *self.readalong.lock().unwrap(), readalong)
+    }
+
+    /// Where transcribed segments go: the sinks, or the meeting
+    fn output(&self) -> mpsc::Sender<Result<String>> {
+        self.meeting_output
+            .clone()
+            .unwrap_or_else(|| self.tx.clone())
     }
 
     /// Stop recording
@@ -643,15 +1219,32 @@ impl Pipeline {
 
         self.is_recording = false;
         self.audio.lock().unwrap().stop()?;
+        if let Some(system) = self.system_audio.take() {
+            system.stop();
+        }
+        let output = self.output();
 
         // Flush remaining audio through VAD and process any final speech
-        let flushed_speech = self.vad.lock().unwrap().flush();
+        let flushed_speech = {
+            let mut vad = self.vad.lock().unwrap();
+            vad.flush()
+                .and_then(|result| unprocessed_speech(result, &self.processed_spans))
+                .filter(|(start_sample, samples)| {
+                    !is_read_back_echo(
+                        self.speaker.as_deref(),
+                        self.capture_started_at,
+                        *start_sample,
+                        samples.len(),
+                    )
+                })
+        };
+        broadcast_speech_activity(
+            &self.broadcaster,
+            &tokio::runtime::Handle::current(),
+            &self.vad,
+        );
 
-        if let Some(swictation_vad::VadResult::Speech {
-            samples: speech_samples,
-            ..
-        }) = flushed_speech
-        {
+        if let Some((start_sample, speech_samples)) = flushed_speech {
             info!(
                 "Processing flushed speech segment: {} samples",
                 speech_samples.len()
@@ -669,51 +1262,128 @@ impl Pipeline {
             let segment_start = Instant::now();
             let vad_latency = segment_start.elapsed().as_millis() as f64;
 
+            let (stt_samples, trimmed_silence_ms) =
+                trim_for_stt(&speech_samples, self.config.trim_silence);
+            let pending_command = self.commands.start(stt_samples);
+
             // Process through STT - CRITICAL: Release lock immediately after use
             // The STT inference can take 50-500ms, but we release the lock right after
             let stt_start = Instant::now();
-            let (text, stt_latency, is_0_6b) = {
+            // block_in_place: the executor moves other tasks off this worker while
+            // inference runs (STT normally runs on its own thread, see start_recording)
+            let stt_timeout = self.config.stt_timeout();
+            let on_timeout = stt_timeout_reporter(
+                &self.stt_timeouts,
+                &self.broadcaster,
+                &tokio::runtime::Handle::current(),
+                stt_samples.len(),
+                stt_timeout,
+            );
+            let recognized = tokio::task::block_in_place(|| {
                 let mut stt_lock = match self.stt.lock() {
                     Ok(s) => s,
                     Err(e) => {
                         eprintln!("STT lock error during flush: {}", e);
-                        info!("Recording stopped");
-                        return Ok(());
+                        return None;
                     }
                 };
 
-                let result = stt_lock.recognize(&speech_samples).unwrap_or_else(|e| {
-                    eprintln!("STT transcribe error during flush: {}", e);
-                    swictation_stt::RecognitionResult {
-                        text: String::new(),
-                        confidence: 0.0,
-                        processing_time_ms: 0.0,
-                    }
-                });
-                let text = result.text;
+                let mut router_lock = self.language_router.as_ref().and_then(|r| r.lock().ok());
+                let engine = match router_lock.as_mut().and_then(|r| r.route(stt_samples)) {
+                    Some(whisper) => whisper,
+                    None => &mut *stt_lock,
+                };
+
+                let result = stt_watchdog::recognize(engine, stt_samples, stt_timeout, on_timeout)
+                    .unwrap_or_else(|e| {
+                        eprintln!("STT transcribe error during flush: {}", e);
+                        swictation_stt::RecognitionResult {
+                            text: String::new(),
+                            confidence: 0.0,
+                            processing_time_ms: 0.0,
+                            alternatives: Vec::new(),
+                            words: Vec::new(),
+                        }
+                    });
                 let stt_latency = stt_start.elapsed().as_millis() as f64;
-                let is_0_6b = stt_lock.model_size() == "0.6B";
-                (text, stt_latency, is_0_6b)
-            };
+                let punctuated = engine.writes_punctuation();
+                let inference = inference_metadata(engine);
+                Some((
+                    result.text,
+                    result.alternatives,
+                    result.words,
+                    stt_latency,
+                    punctuated,
+                    inference,
+                ))
+            });
             // stt_lock released here - BEFORE any .await calls
+            let Some((text, alternatives, words, stt_latency, punctuated, inference)) = recognized
+            else {
+                info!("Recording stopped");
+                return Ok(());
+            };
+
+            let paragraph = self
+                .paragraph_breaks
+                .lock()
+                .unwrap()
+                .segment(start_sample, speech_samples.len());
+
+            if let Some(command) = pending_command.finish(&text) {
+                if let Some(keys) = run_command(
+                    command,
+                    &self.edit_history,
+                    &self.word_filter,
+                    &self.spelling,
+                    self.speaker.as_deref(),
+                    &self.voice_commands,
+                ) {
+                    if let Err(e) = output.send(Ok(keys)).await {
+                        eprintln!("Failed to send flushed command: {}", e);
+                    }
+                }
+                info!("Recording stopped");
+                return Ok(());
+            }
+
+            if self.spelling.load(Ordering::Relaxed) {
+                if let Some(spelled) = spell_segment(&text, &self.edit_history) {
+                    if let Err(e) = output.send(Ok(spelled)).await {
+                        eprintln!("Failed to send flushed spelled text: {}", e);
+                    }
+                }
+                info!("Recording stopped");
+                return Ok(());
+            }
+
+            if follow_script(
+                &self.readalong,
+                &text,
+                &words,
+                &self.broadcaster,
+                &tokio::runtime::Handle::current(),
+            ) {
+                info!("Recording stopped");
+                return Ok(());
+            }
 
             if !text.is_empty() {
                 // Transform voice commands → symbols (Midstream)
                 let transform_start = Instant::now();
 
-                // IMPORTANT: 0.6B model has built-in ITN - use smart normalization
-                // to avoid duplicate punctuation. See normalize_0_6b_punctuation docs.
-                let text = if is_0_6b {
-                    normalize_0_6b_punctuation(&text)
-                } else {
-                    text
-                };
+                // IMPORTANT: 0.6B model has built-in ITN - punctuation model or smart
+                // normalization avoids duplicate punctuation. See prepare_text.
+                let text = prepare_text(&text, punctuated, self.punctuator.as_deref());
 
                 // Step 1: Process capital commands first
                 let with_capitals = process_capital_commands(&text);
 
+                // Step 1b: Write out numbers, dates and units
+                let normalized = self.itn.apply(&with_capitals);
+
                 // Step 2: Transform punctuation
-                let transformed = transform(&with_capitals);
+                let transformed = transform(&normalized);
 
                 // Step 3: Apply learned corrections
                 let corrected = self.corrections.apply(&transformed, "all");
@@ -725,19 +1395,43 @@ impl Pipeline {
                     }
                 }
 
-                // Step 4: Apply automatic capitalization rules
-                let capitalized = apply_capitalization(&corrected);
+                // Step 4: Mask or drop filtered words
+                let (filtered, masked_words) = self.word_filter.apply(&corrected);
+
+                // Step 5: Apply automatic capitalization rules
+                let capitalized = apply_capitalization(&filtered);
 
                 let transform_latency = transform_start.elapsed().as_micros() as f64;
 
                 info!("Flushed transcription: {} → {}", text, capitalized);
 
+                let nbest = post_process_alternatives(
+                    &alternatives,
+                    &capitalized,
+                    punctuated,
+                    self.punctuator.as_deref(),
+                    &self.corrections,
+                    &self.itn,
+                    &self.word_filter,
+                );
+
                 // Track segment metrics
                 let word_count = capitalized.split_whitespace().count() as i32;
                 let char_count = capitalized.len() as i32;
 
+                check_pacing(
+                    &self.pacing,
+                    &self.config.pacing,
+                    &self.broadcaster,
+                    &tokio::runtime::Handle::current(),
+                    start_sample,
+                    speech_samples.len(),
+                    word_count,
+                );
+
                 let current_session_id = *self.session_id.lock().unwrap();
 
+                let mut stored_segment_id = None;
                 if let Some(sid) = current_session_id {
                     let duration_s = (speech_samples.len() as f64) / 16000.0;
                     let total_latency_ms = vad_latency + stt_latency + (transform_latency / 1000.0);
@@ -756,12 +1450,50 @@ impl Pipeline {
                         transform_latency_us: transform_latency,
                         injection_latency_ms: 0.0,
                         total_latency_ms,
-                        transformations_count: if text != capitalized { 1 } else { 0 },
+                        transformations_count: i32::from(text != capitalized)
+                            + i32::from(paragraph.is_some()),
                         keyboard_actions_count: 0,
+                        trimmed_silence_ms,
+                        start_sample: Some(start_sample),
+                        audio_start: Some(sample_time(self.capture_started_at, start_sample)),
+                        inference: Some(inference.clone()),
+                        nbest: nbest.clone(),
+                        masked_words: masked_words as i32,
+                        content_hash: self
+                            .config
+                            .save_recordings
+                            .then(|| audio_content_hash(sid, start_sample, &speech_samples)),
+                        prosody: self
+                            .config
+                            .analytics_prosody
+                            .then(|| segment_prosody(&speech_samples, word_count))
+                            .flatten(),
+                        speaker: segment_speaker(self.diarizer.as_deref(), &speech_samples),
                     };
 
-                    if let Err(e) = self.metrics.lock().unwrap().add_segment(segment) {
-                        eprintln!("Failed to add flushed segment metrics: {}", e);
+                    let (latency_warning, segment_id) = {
+                        let metrics = self.metrics.lock().unwrap();
+                        (
+                            metrics.check_latency(&segment),
+                            metrics.add_segment(segment),
+                        )
+                    };
+                    match segment_id {
+                        Ok(Some(segment_id)) => {
+                            stored_segment_id = Some(segment_id);
+                            if self.config.save_recordings {
+                                save_recording(
+                                    &self.metrics,
+                                    &self.segment_texts,
+                                    segment_id,
+                                    &speech_samples,
+                                    &capitalized,
+                                );
+                            }
+                        }
+                        // Held in RAM while the metrics disk is full
+                        Ok(None) => {}
+                        Err(e) => eprintln!("Failed to add flushed segment metrics: {}", e),
                     }
 
                     // Broadcast transcription to UI clients
@@ -772,20 +1504,48 @@ impl Pipeline {
                             let text_clone = capitalized.clone();
                             async move {
                                 broadcaster
-                                    .add_transcription(
+                                    .add_stored_transcription(
+                                        sid,
+                                        stored_segment_id,
                                         text_clone,
                                         wpm,
                                         total_latency_ms,
                                         word_count,
+                                        Some(inference),
                                     )
                                     .await;
+                                if let Some(warning) = latency_warning {
+                                    broadcaster.broadcast_latency_warning(warning).await;
+                                }
                             }
                         });
                     }
                 }
 
+                let capitalized = flag_low_confidence(
+                    capitalized,
+                    &words,
+                    self.config.low_confidence_threshold,
+                    self.config.mark_low_confidence,
+                    &self.broadcaster,
+                    &tokio::runtime::Handle::current(),
+                );
+
+                if let Some(paragraph) = paragraph {
+                    self.edit_history.lock().unwrap().push(paragraph.typed);
+                    if let Err(e) = output.send(Ok(paragraph.keys.to_string())).await {
+                        eprintln!("Failed to send flushed paragraph break: {}", e);
+                    }
+                }
+
+                self.edit_history.lock().unwrap().push_segment(
+                    stored_segment_id,
+                    &capitalized,
+                    nbest,
+                );
+
                 // Send through transcription channel (bounded - provides backpressure)
-                if let Err(e) = self.tx.send(Ok(capitalized)).await {
+                if let Err(e) = output.send(Ok(capitalized)).await {
                     eprintln!("Failed to send flushed transcription: {}", e);
                 }
             }
@@ -837,10 +1597,935 @@ impl Pipeline {
         *self.session_id.lock().unwrap() = None;
     }
 
+    /// Forget the typed segments kept for "scratch that" and read-back
+    pub fn clear_edit_history(&self) {
+        self.edit_history.lock().unwrap().clear();
+    }
+
+    /// Bias recognition toward `phrases` as well as the configured
+    /// `bias_phrases`, until `clear_session_bias`
+    ///
+    /// Returns the number of phrases now in effect.
+    pub fn set_session_bias(&self, phrases: Vec<String>) -> Result<usize> {
+        let bias = context_bias(&self.config, &phrases);
+        let count = bias.as_ref().map_or(0, |b| b.phrases().len());
+        self.stt
+            .lock()
+            .map_err(|e| anyhow::anyhow!("STT lock error: {}", e))?
+            .set_context_bias(bias);
+        *self.session_phrases.lock().unwrap() = phrases;
+        Ok(count)
+    }
+
+    /// Go back to the configured `bias_phrases` (at the end of a session)
+    pub fn clear_session_bias(&self) {
+        if self.session_phrases.lock().unwrap().is_empty() {
+            return;
+        }
+        if let Err(e) = self.set_session_bias(Vec::new()) {
+            warn!("Failed to reset bias phrases: {:#}", e);
+        }
+    }
+
+    /// Re-read `lexicon_path` into the live engine; returns the word count
+    pub fn reload_lexicon(&self) -> Result<usize> {
+        let path = self
+            .config
+            .lexicon_path
+            .as_deref()
+            .context("lexicon_path is not set in the config")?;
+        let count = self
+            .stt
+            .lock()
+            .map_err(|e| anyhow::anyhow!("STT lock error: {}", e))?
+            .load_lexicon(Some(path))?;
+        Ok(count)
+    }
+
+    /// Handle for swapping the STT model without holding the pipeline
+    pub fn model_switcher(&self) -> ModelSwitcher {
+        ModelSwitcher {
+            stt: self.stt.clone(),
+            config: self.config.clone(),
+            corrections: self.corrections.clone(),
+            session_phrases: self.session_phrases.clone(),
+        }
+    }
+
     /// Set the broadcaster for real-time updates
     pub fn set_broadcaster(&self, broadcaster: Arc<MetricsBroadcaster>) {
         *self.broadcaster.lock().unwrap() = Some(broadcaster);
     }
+
+    /// Set where recognized voice control commands are sent
+    pub fn set_voice_commands(&self, tx: VoiceCommandSender) {
+        *self.voice_commands.lock().unwrap() = Some(tx);
+    }
+
+    /// Move the word filter to its next profile, returning the profile name
+    pub fn next_filter_profile(&self) -> Result<String> {
+        let profile = self.word_filter.next_profile()?;
+        info!("🙈 Word filter profile: {}", profile);
+        Ok(profile)
+    }
+
+    /// Tell the user something without typing it: spoken when read-back is
+    /// enabled, a desktop notification otherwise
+    pub fn announce(&self, message: &str) {
+        match &self.speaker {
+            Some(speaker) => speaker.speak(message),
+            None => notification::show(message),
+        }
+    }
+
+    /// Apply new VAD tuning to the running detector (omitted values are kept)
+    ///
+    /// Returns the effective (threshold, min_silence, min_speech).
+    pub fn set_vad_tuning(
+        &self,
+        threshold: Option<f32>,
+        min_silence: Option<f32>,
+        min_speech: Option<f32>,
+    ) -> Result<(f32, f32, f32)> {
+        let mut vad = self
+            .vad
+            .lock()
+            .map_err(|e| anyhow::anyhow!("VAD lock error: {}", e))?;
+
+        let current = vad.config();
+        let threshold = threshold.unwrap_or(current.threshold);
+        let min_silence = min_silence.unwrap_or(current.min_silence_duration);
+        let min_speech = min_speech.unwrap_or(current.min_speech_duration);
+
+        vad.update_tuning(threshold, min_silence, min_speech)
+            .map_err(|e| anyhow::anyhow!("Invalid VAD settings: {}", e))?;
+
+        info!(
+            "🎚️ VAD tuned: threshold={}, min_silence={}s, min_speech={}s",
+            threshold, min_silence, min_speech
+        );
+        Ok((threshold, min_silence, min_speech))
+    }
+
+    /// Recent VAD speech probabilities and the threshold they are compared against
+    pub fn vad_trace(&self) -> Result<(Vec<VadTracePoint>, f32)> {
+        let vad = self
+            .vad
+            .lock()
+            .map_err(|e| anyhow::anyhow!("VAD lock error: {}", e))?;
+        Ok((vad.trace(), vad.config().threshold))
+    }
+
+    /// Replace a recently typed segment with one of its decoder alternatives
+    ///
+    /// The typed text is undone with backspaces and the choice (plus anything
+    /// dictated after it) is retyped through the normal injection channel.
+    /// Only segments of the current or last recording can be replaced.
+    pub async fn use_alternative(&self, segment_id: i64, index: usize) -> Result<String> {
+        let (alternative, keys) = self
+            .edit_history
+            .lock()
+            .unwrap()
+            .use_alternative(segment_id, index)?;
+
+        self.tx
+            .send(Ok(keys))
+            .await
+            .map_err(|_| anyhow::anyhow!("Text injection is not running"))?;
+
+        info!(
+            "🔀 Segment {} replaced with alternative {}: {}",
+            segment_id, index, alternative
+        );
+        Ok(alternative)
+    }
+
+    /// Speak the last dictated segment of the current or last recording
+    pub fn read_back(&self) -> Result<String> {
+        read_back(&self.edit_history, self.speaker.as_deref())
+    }
+
+    /// Handle for transcribing audio outside of a recording
+    pub fn transcriber(&self) -> Transcriber {
+        Transcriber {
+            stt: self.stt.clone(),
+            punctuator: self.punctuator.clone(),
+            corrections: self.corrections.clone(),
+            itn: self.itn.clone(),
+            word_filter: self.word_filter.clone(),
+        }
+    }
+
+    /// Re-transcribe a recorded segment and store the result as an alternative
+    ///
+    /// `engine` is a model loaded just for this retry (see
+    /// [`ModelSwitcher::load_for_retry`]) and dropped afterwards; `None`
+    /// reuses the live engine.
+    pub fn retry_segment(&self, segment_id: i64, engine: Option<SttEngine>) -> Result<RetryResult> {
+        let (samples, original) = recordings::load_segment(segment_id)?;
+        let original = original
+            .or_else(|| self.segment_texts.get(segment_id))
+            .unwrap_or_default();
+
+        let run = |stt: &mut SttEngine| -> Result<(String, bool, InferenceMetadata)> {
+            let result = stt
+                .recognize(&samples)
+                .context("Failed to re-transcribe segment")?;
+            Ok((
+                result.text,
+                stt.writes_punctuation(),
+                inference_metadata(stt),
+            ))
+        };
+
+        let (text, punctuated, inference) = match engine {
+            Some(mut engine) => run(&mut engine)?,
+            None => run(&mut *self
+                .stt
+                .lock()
+                .map_err(|e| anyhow::anyhow!("STT lock error: {}", e))?)?,
+        };
+
+        // Same post-processing as live dictation so the diff only shows model differences
+        let alternative = post_process(
+            &text,
+            punctuated,
+            self.punctuator.as_deref(),
+            &self.corrections,
+            &self.itn,
+            &self.word_filter,
+        );
+
+        let alternative_id = self
+            .metrics
+            .lock()
+            .unwrap()
+            .database()
+            .insert_segment_alternative(&SegmentAlternative {
+                alternative_id: None,
+                segment_id,
+                created_at: Some(Utc::now()),
+                text: alternative.clone(),
+                inference: Some(inference.clone()),
+            })?;
+
+        info!(
+            "🔁 Segment {} retried with {}: {}",
+            segment_id, inference.model, alternative
+        );
+
+        Ok(RetryResult {
+            segment_id,
+            alternative_id,
+            diff: word_diff(&original, &alternative),
+            original,
+            alternative,
+            inference,
+        })
+    }
+}
+
+/// Live-dictation text pipeline: punctuation, capitals, corrections, word
+/// filter, capitalization
+/// Biasing toward the configured phrases plus `session` ones (`None` if
+/// there are none or the boost is 0)
+fn context_bias(config: &DaemonConfig, session: &[String]) -> Option<ContextBias> {
+    if config.bias_boost <= 0.0 {
+        return None;
+    }
+    let phrases: Vec<&String> = config.bias_phrases.iter().chain(session).collect();
+    let bias = ContextBias::new(&phrases, config.bias_boost);
+    (!bias.phrases().is_empty()).then_some(bias)
+}
+
+/// Recognizer text with spoken punctuation as words, ready for Secretary Mode
+///
+/// A loaded punctuation model replaces the recognizer's punctuation and
+/// casing; otherwise 0.6B/Whisper punctuation is normalized back to words.
+fn prepare_text(text: &str, punctuated: bool, punctuator: Option<&Mutex<Punctuator>>) -> String {
+    if let Some(punctuator) = punctuator {
+        match punctuator.lock().unwrap().restore(text) {
+            Ok(restored) => return settle_spoken_punctuation(&restored),
+            Err(e) => warn!("Punctuation model failed: {}", e),
+        }
+    }
+    if punctuated {
+        normalize_0_6b_punctuation(text)
+    } else {
+        text.to_string()
+    }
+}
+
+fn post_process(
+    text: &str,
+    punctuated: bool,
+    punctuator: Option<&Mutex<Punctuator>>,
+    corrections: &CorrectionEngine,
+    itn: &Itn,
+    word_filter: &WordFilter,
+) -> String {
+    let text = prepare_text(text, punctuated, punctuator);
+    let transformed = transform(&itn.apply(&process_capital_commands(&text)));
+    let (filtered, _) = word_filter.apply(&corrections.apply(&transformed, "all"));
+    apply_capitalization(&filtered)
+}
+
+/// Decoder alternatives post-processed like the typed text, without repeats
+fn post_process_alternatives(
+    alternatives: &[String],
+    typed: &str,
+    punctuated: bool,
+    punctuator: Option<&Mutex<Punctuator>>,
+    corrections: &CorrectionEngine,
+    itn: &Itn,
+    word_filter: &WordFilter,
+) -> Vec<String> {
+    let mut nbest: Vec<String> = Vec::new();
+    for alternative in alternatives {
+        let text = post_process(
+            alternative,
+            punctuated,
+            punctuator,
+            corrections,
+            itn,
+            word_filter,
+        );
+        if !text.is_empty() && text != typed && !nbest.contains(&text) {
+            nbest.push(text);
+        }
+    }
+    nbest
+}
+
+/// Watchdog callback that counts a cancelled recognition and reports it to
+/// UI clients as an `stt_timeout` event
+fn stt_timeout_reporter(
+    timeouts: &Arc<AtomicU64>,
+    broadcaster: &Mutex<Option<Arc<MetricsBroadcaster>>>,
+    runtime: &tokio::runtime::Handle,
+    samples: usize,
+    timeout: Option<Duration>,
+) -> impl FnOnce() + Send + 'static {
+    let timeouts = timeouts.clone();
+    let broadcaster = broadcaster.lock().unwrap().clone();
+    let runtime = runtime.clone();
+    let audio_s = samples as f64 / 16000.0;
+    let timeout_s = timeout.map_or(0.0, |t| t.as_secs_f64());
+    move || {
+        let total = timeouts.fetch_add(1, Ordering::Relaxed) + 1;
+        if let Some(broadcaster) = broadcaster {
+            runtime.spawn(async move {
+                broadcaster
+                    .broadcast_stt_timeout(audio_s, timeout_s, total)
+                    .await;
+            });
+        }
+    }
+}
+
+/// Feed a segment to the pacing monitor, alerting UI clients (and playing
+/// the earcon) when the user has been speaking too fast for too long
+fn check_pacing(
+    pacing: &Mutex<PacingMonitor>,
+    config: &PacingConfig,
+    broadcaster: &Mutex<Option<Arc<MetricsBroadcaster>>>,
+    runtime: &tokio::runtime::Handle,
+    start_sample: u64,
+    samples: usize,
+    words: i32,
+) {
+    if !config.enabled {
+        return;
+    }
+    let Some(alert) = pacing
+        .lock()
+        .unwrap()
+        .segment(start_sample, samples, words.max(0) as usize)
+    else {
+        return;
+    };
+
+    info!(
+        "🏃 Speaking at {:.0} WPM for {:.0}s (limit {:.0})",
+        alert.wpm, alert.sustained_s, alert.max_wpm
+    );
+    if config.earcon {
+        pacing::play_earcon();
+    }
+    if let Some(broadcaster) = broadcaster.lock().unwrap().clone() {
+        runtime.spawn(async move {
+            broadcaster
+                .broadcast_pacing_alert(alert.wpm, alert.max_wpm, alert.sustained_s)
+                .await;
+        });
+    }
+}
+
+/// Report a segment's low-confidence words to UI clients, returning the
+/// text to type (with the words marked when `mark` is set)
+fn flag_low_confidence(
+    text: String,
+    words: &[WordConfidence],
+    threshold: f32,
+    mark: bool,
+    broadcaster: &Mutex<Option<Arc<MetricsBroadcaster>>>,
+    runtime: &tokio::runtime::Handle,
+) -> String {
+    let uncertain = low_confidence::uncertain_words(words, threshold);
+    if uncertain.is_empty() {
+        return text;
+    }
+
+    info!(
+        "🔍 {} low-confidence word(s): {}",
+        uncertain.len(),
+        uncertain
+            .iter()
+            .map(|w| format!("{} ({:.2})", w.word, w.confidence))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let typed = if mark {
+        low_confidence::mark(&text, &uncertain)
+    } else {
+        text.clone()
+    };
+    if let Some(broadcaster) = broadcaster.lock().unwrap().clone() {
+        runtime.spawn(async move {
+            broadcaster
+                .broadcast_low_confidence(text, uncertain, threshold)
+                .await;
+        });
+    }
+    typed
+}
+
+/// Move a readalong script on by a segment and broadcast where the speaker
+/// is; `false` when no script is loaded and the segment is dictation
+fn follow_script(
+    readalong: &Mutex<Option<Readalong>>,
+    text: &str,
+    words: &[WordConfidence],
+    broadcaster: &Mutex<Option<Arc<MetricsBroadcaster>>>,
+    runtime: &tokio::runtime::Handle,
+) -> bool {
+    let mut readalong = readalong.lock().unwrap();
+    let Some(readalong) = readalong.as_mut() else {
+        return false;
+    };
+    let Some(position) = readalong.follow(text, words) else {
+        return true;
+    };
+
+    info!(
+        "📜 Script word {}/{} (line {}), {:.0}% read as written, pronunciation {:.0}",
+        position.word,
+        position.total_words,
+        position.line + 1,
+        position.accuracy * 100.0,
+        position.pronunciation_score
+    );
+    if !position.skipped_lines.is_empty() {
+        info!(
+            "📜 Skipped line(s) {}",
+            position
+                .skipped_lines
+                .iter()
+                .map(|line| (line + 1).to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    if let Some(broadcaster) = broadcaster.lock().unwrap().clone() {
+        runtime.spawn(async move {
+            broadcaster.broadcast_readalong_progress(position).await;
+        });
+    }
+    true
+}
+
+/// Carry out a spoken command, returning the keystrokes to type (if any)
+fn run_command(
+    command: EditCommand,
+    edit_history: &Mutex<EditHistory>,
+    word_filter: &WordFilter,
+    spelling: &AtomicBool,
+    speaker: Option<&Speaker>,
+    voice_commands: &Mutex<Option<VoiceCommandSender>>,
+) -> Option<String> {
+    match command {
+        _ if command.is_daemon_command() => {
+            if let Some(tx) = voice_commands.lock().unwrap().as_ref() {
+                let _ = tx.send(command);
+            }
+            None
+        }
+        EditCommand::ReadBack => {
+            if let Err(e) = read_back(edit_history, speaker) {
+                warn!("🔈 {:#}", e);
+            }
+            None
+        }
+        EditCommand::FilterOff | EditCommand::FilterOn => {
+            let bypass = command == EditCommand::FilterOff;
+            word_filter.set_bypass(bypass);
+            info!("🙈 Word filter {}", if bypass { "bypassed" } else { "on" });
+            None
+        }
+        EditCommand::SpellModeOn | EditCommand::SpellModeOff => {
+            let on = command == EditCommand::SpellModeOn;
+            spelling.store(on, Ordering::Relaxed);
+            info!("🔤 Spell mode {}", if on { "on" } else { "off" });
+            None
+        }
+        _ => command.keystrokes(&mut edit_history.lock().unwrap()),
+    }
+}
+
+/// Spell-mode text for a segment, remembered for "scratch that"
+///
+/// Spelled segments skip the rest of post-processing and are typed without
+/// a trailing space. They are neither stored nor broadcast: spelled strings
+/// are often keys or passwords.
+fn spell_segment(text: &str, edit_history: &Mutex<EditHistory>) -> Option<String> {
+    let spelled = spell::spell(text);
+    if spelled.is_empty() {
+        return None;
+    }
+    info!("🔤 Spelled {} characters", spelled.chars().count());
+    edit_history.lock().unwrap().push(&spelled);
+    Some(spelled)
+}
+
+/// Speak the last dictated segment, returning its text
+fn read_back(edit_history: &Mutex<EditHistory>, speaker: Option<&Speaker>) -> Result<String> {
+    let speaker = speaker.context("Read-back is disabled (set tts.enabled = true)")?;
+    let text = edit_history
+        .lock()
+        .unwrap()
+        .last_dictated()
+        .context("Nothing has been dictated yet")?;
+    info!("🔈 Reading back: {}", text);
+    speaker.speak(&text);
+    Ok(text)
+}
+
+/// Whether a segment was captured while read-back was playing, i.e. it is
+/// the synthesizer heard through the microphone
+fn is_read_back_echo(
+    speaker: Option<&Speaker>,
+    capture_started_at: DateTime<Utc>,
+    start_sample: u64,
+    len: usize,
+) -> bool {
+    let Some(speaker) = speaker else {
+        return false;
+    };
+    let echo = speaker.overlaps(
+        sample_time(capture_started_at, start_sample),
+        sample_time(capture_started_at, start_sample + len as u64),
+    );
+    if echo {
+        info!("🔈 Dropping segment captured during read-back");
+    }
+    echo
+}
+
+/// Keep a segment's audio for retries; its text goes to disk only when the
+/// metrics database stores transcripts too
+fn save_recording(
+    metrics: &Mutex<MetricsCollector>,
+    texts: &recordings::SegmentTexts,
+    segment_id: i64,
+    samples: &[f32],
+    text: &str,
+) {
+    let store_text = metrics.lock().unwrap().stores_transcription_text();
+    if !store_text {
+        texts.insert(segment_id, text);
+    }
+    if let Err(e) = recordings::save_segment(segment_id, samples, store_text.then_some(text)) {
+        warn!("Failed to save segment recording: {}", e);
+    }
+}
+
+/// Start and samples of a VAD result that have not been transcribed yet
+fn unprocessed_speech(result: VadResult, spans: &Mutex<ProcessedSpans>) -> Option<(u64, Vec<f32>)> {
+    match result {
+        VadResult::Speech {
+            start_sample,
+            samples,
+        } => spans.lock().unwrap().filter(start_sample, samples),
+        VadResult::Silence => None,
+    }
+}
+
+/// Send the level of a VAD block to firehose clients
+fn broadcast_audio_level(
+    broadcaster: &Mutex<Option<Arc<MetricsBroadcaster>>>,
+    runtime: &tokio::runtime::Handle,
+    vad: &Mutex<VadDetector>,
+    samples: &[f32],
+) {
+    let Some(broadcaster) = broadcaster.lock().unwrap().clone() else {
+        return;
+    };
+    let (speech_probability, window_probabilities) = vad
+        .lock()
+        .map(|mut vad| (vad.take_peak_probability(), vad.take_probabilities()))
+        .unwrap_or_default();
+    let (rms_dbfs, peak_dbfs) = audio_levels_dbfs(samples);
+    runtime.spawn(async move {
+        broadcaster
+            .broadcast_audio_level(
+                rms_dbfs,
+                peak_dbfs,
+                speech_probability,
+                window_probabilities,
+            )
+            .await;
+    });
+}
+
+/// Tell clients the VAD heard speech start or stop since the last call
+fn broadcast_speech_activity(
+    broadcaster: &Mutex<Option<Arc<MetricsBroadcaster>>>,
+    runtime: &tokio::runtime::Handle,
+    vad: &Mutex<VadDetector>,
+) {
+    // Taken even without clients, so a later client does not get stale ones
+    let events = vad
+        .lock()
+        .map(|mut vad| vad.take_events())
+        .unwrap_or_default();
+    let Some(broadcaster) = broadcaster.lock().unwrap().clone() else {
+        return;
+    };
+    if events.is_empty() {
+        return;
+    }
+    runtime.spawn(async move {
+        for event in events {
+            let (speaking, sample) = match event {
+                VadEvent::SpeechStart { sample } => (true, sample),
+                VadEvent::SpeechEnd { sample } => (false, sample),
+            };
+            broadcaster
+                .broadcast_speech_activity(speaking, sample)
+                .await;
+        }
+    });
+}
+
+/// Capture settings for live dictation: 16 kHz mono in 0.5 s chunks from
+/// the configured device
+pub fn live_audio_config(config: &DaemonConfig) -> swictation_audio::AudioConfig {
+    swictation_audio::AudioConfig {
+        sample_rate: 16000,
+        channels: 1,
+        blocksize: 1024,
+        buffer_duration: 10.0,
+        device_index: config.audio_device_index,
+        streaming_mode: true,
+        chunk_duration: 0.5,
+    }
+}
+
+/// RMS and peak level of `samples` in dBFS, floored at -100
+pub(crate) fn audio_levels_dbfs(samples: &[f32]) -> (f32, f32) {
+    let to_dbfs = |amplitude: f32| (20.0 * amplitude.log10()).max(-100.0);
+    if samples.is_empty() {
+        return (-100.0, -100.0);
+    }
+    let rms = (samples.iter().map(|x| x * x).sum::<f32>() / samples.len() as f32).sqrt();
+    let peak = samples.iter().map(|x| x.abs()).fold(0.0f32, f32::max);
+    (to_dbfs(rms), to_dbfs(peak))
+}
+
+/// Run one block of audio through the VAD, returning a new speech segment if one completed
+fn vad_step(
+    vad: &Mutex<VadDetector>,
+    spans: &Mutex<ProcessedSpans>,
+    samples: &[f32],
+) -> Option<(u64, Vec<f32>)> {
+    let mut vad_lock = match vad.lock() {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("VAD lock error: {}", e);
+            return None;
+        }
+    };
+
+    // Overlap check happens under the VAD lock so it sees segments
+    // in the order the VAD emitted them (the stop flush does the same)
+    match vad_lock.process_audio(samples) {
+        Ok(result) => {
+            let speech = unprocessed_speech(result, spans);
+            match &speech {
+                Some((start, samples)) => eprintln!(
+                    "DEBUG: VAD detected speech! {} samples at {}",
+                    samples.len(),
+                    start
+                ),
+                // Skip silence (VAD ensures we only transcribe speech segments)
+                None => eprintln!("DEBUG: VAD detected silence"),
+            }
+            speech
+        }
+        Err(e) => {
+            eprintln!("VAD error: {}", e);
+            None
+        }
+    }
+}
+
+/// Prosody analytics for a segment, `None` if it held no audible speech
+fn segment_prosody(samples: &[f32], words: i32) -> Option<ProsodyMetrics> {
+    let prosody = prosody::analyze(samples, 16000);
+    (prosody.speech_s > 0.0).then(|| ProsodyMetrics {
+        pitch_std_semitones: prosody.pitch_std_semitones.map(f64::from),
+        energy_dbfs: prosody.energy_dbfs.into(),
+        speaking_rate_wpm: prosody.speaking_rate_wpm(words.max(0) as usize).into(),
+    })
+}
+
+/// Speaker number of a segment in the current recording, when labelling
+/// live segments
+fn segment_speaker(diarizer: Option<&Mutex<Diarizer>>, samples: &[f32]) -> Option<i32> {
+    diarizer?
+        .lock()
+        .unwrap()
+        .label(samples)
+        .map(|speaker| speaker as i32)
+}
+
+/// Wall-clock capture time of a sample in the current recording
+fn sample_time(capture_started_at: DateTime<Utc>, sample: u64) -> DateTime<Utc> {
+    capture_started_at + chrono::Duration::microseconds((sample * 1_000_000 / 16000) as i64)
+}
+
+/// Segment audio to hand to STT, and the milliseconds of silence trimmed off
+fn trim_for_stt(samples: &[f32], enabled: bool) -> (&[f32], f64) {
+    if !enabled {
+        return (samples, 0.0);
+    }
+    let trim = trim_silence(samples, 16000, &TrimConfig::default());
+    (&samples[trim.start..trim.end], trim.trimmed_ms(16000))
+}
+
+/// Metrics database the daemon records into
+pub(crate) fn metrics_db_path() -> std::path::PathBuf {
+    swictation_paths::data_dir().join("metrics.db")
+}
+
+/// Apply the decoding settings of `config` to a freshly loaded engine,
+/// biased toward the `session` phrases too
+fn configure_engine(
+    stt: &mut SttEngine,
+    config: &DaemonConfig,
+    corrections: &CorrectionEngine,
+    session: &[String],
+) {
+    if config.stt_cache_size > 0 {
+        info!(
+            "STT result cache enabled ({} entries)",
+            config.stt_cache_size
+        );
+        stt.set_result_cache(config.stt_cache_size);
+    }
+
+    if config.encoder_workers > 1 {
+        if let Err(e) = stt.set_encoder_workers(config.encoder_workers) {
+            warn!("⚠️ Extra encoder workers not loaded: {}", e);
+        }
+    }
+
+    stt.set_tdt(TdtConfig {
+        max_tokens_per_frame: config.tdt_max_tokens_per_frame,
+        max_duration_skip: config.tdt_max_duration_skip,
+        blank_penalty: config.tdt_blank_penalty,
+    });
+
+    if config.beam_width > 1 {
+        stt.set_decoding(Decoding::Beam(BeamConfig {
+            width: config.beam_width,
+            prune: config.beam_prune,
+        }));
+    }
+
+    if let Some(path) = &config.lexicon_path {
+        if let Err(e) = stt.load_lexicon(Some(path)) {
+            warn!("⚠️ Lexicon not loaded: {}", e);
+        }
+    }
+
+    if let Some(bias) = context_bias(config, session) {
+        stt.set_context_bias(Some(bias));
+    }
+
+    if config.lm_weight > 0.0 {
+        let fusion = match &config.lm_path {
+            Some(path) => language_model::load_external(path, config.lm_weight),
+            None => metrics_db_path().parent().and_then(|data_dir| {
+                language_model::load_fusion(data_dir, corrections, config.lm_weight)
+            }),
+        };
+        stt.set_shallow_fusion(fusion);
+    }
+}
+
+/// Override name ("0.6b-gpu", ...) of a loaded engine
+fn engine_spec(stt: &SttEngine) -> String {
+    let size = match stt {
+        SttEngine::Parakeet0_6B(_) => "0.6b",
+        SttEngine::Parakeet1_1B(_) => "1.1b",
+        SttEngine::Whisper(_) => "whisper",
+    };
+    format!("{}-{}", size, stt.backend().to_lowercase())
+}
+
+/// Whether switching from the live `current` engine to `spec` needs a model
+/// load; unknown names fail here, before the live engine is touched
+fn needs_load(current: &str, spec: &str) -> Result<bool> {
+    if spec == current {
+        return Ok(false);
+    }
+    if spec == "auto" || !STT_MODEL_OVERRIDES.contains(&spec) {
+        anyhow::bail!(
+            "Invalid STT model: '{}'. Valid options: {}",
+            spec,
+            STT_MODEL_OVERRIDES[1..].join(", ")
+        );
+    }
+    Ok(true)
+}
+
+/// Models loaded by [`ModelSwitcher::downgrade_for_vram`] for a GPU engine
+/// `current`: a CPU model to free the GPU first, then the model to end on
+///
+/// The 1.1B model moves to the CPU when an INT8 encoder exists, and
+/// otherwise steps down to the 0.6B model on the GPU. `None` for CPU engines.
+fn vram_downgrade_steps(current: &str, has_int8: bool) -> Option<(&'static str, &'static str)> {
+    match current {
+        "1.1b-gpu" if has_int8 => Some(("1.1b-cpu", "1.1b-cpu")),
+        "1.1b-gpu" => Some(("0.6b-cpu", "0.6b-gpu")),
+        "0.6b-gpu" => Some(("0.6b-cpu", "0.6b-cpu")),
+        "whisper-gpu" => Some(("whisper-cpu", "whisper-cpu")),
+        _ => None,
+    }
+}
+
+/// Whether `quantize` (or the model download) left an INT8 encoder in `model_dir`
+fn has_int8_encoder(model_dir: &std::path::Path) -> bool {
+    model_dir.join("encoder.int8.onnx").exists()
+}
+
+/// Load a specific STT model by override name ("0.6b-cpu", "0.6b-gpu", "1.1b-cpu",
+/// "1.1b-gpu", "whisper-cpu", "whisper-gpu")
+fn load_forced_engine(config: &DaemonConfig, spec: &str) -> Result<SttEngine> {
+    match spec {
+        "1.1b-cpu" => {
+            info!("  Loading Parakeet-TDT-1.1B-INT8 via ONNX Runtime (CPU, forced)...");
+            if !has_int8_encoder(&config.stt_1_1b_model_path) {
+                warn!(
+                    "⚠️  No encoder.int8.onnx in {} - the FP32 1.1B model is slow on CPU, \
+                     run `swictation-daemon quantize` first",
+                    config.stt_1_1b_model_path.display()
+                );
+            }
+            let ort_recognizer =
+                OrtRecognizer::new(&config.stt_1_1b_model_path, false).map_err(|e| {
+                    anyhow::anyhow!(
+                        "Failed to load 1.1B CPU model from {}. \
+                    \nError: {}",
+                        config.stt_1_1b_model_path.display(),
+                        e
+                    )
+                })?;
+            info!("✓ Parakeet-TDT-1.1B-INT8 loaded successfully (CPU, forced)");
+            Ok(SttEngine::Parakeet1_1B(ort_recognizer))
+        }
+        "1.1b-gpu" => {
+            info!("  Loading Parakeet-TDT-1.1B-INT8 via ONNX Runtime (forced)...");
+            let ort_recognizer =
+                OrtRecognizer::new(&config.stt_1_1b_model_path, true).map_err(|e| {
+                    anyhow::anyhow!(
+                        "Failed to load 1.1B INT8 model from {}. \
+                    \nError: {}",
+                        config.stt_1_1b_model_path.display(),
+                        e
+                    )
+                })?;
+            info!("✓ Parakeet-TDT-1.1B-INT8 loaded successfully (GPU, forced)");
+            Ok(SttEngine::Parakeet1_1B(ort_recognizer))
+        }
+        "0.6b-gpu" => {
+            info!("  Loading Parakeet-TDT-0.6B via ONNX Runtime (GPU, forced)...");
+            let ort_recognizer =
+                OrtRecognizer::new(&config.stt_0_6b_model_path, true).map_err(|e| {
+                    anyhow::anyhow!(
+                        "Failed to load 0.6B GPU model from {}. \
+                    \nError: {}",
+                        config.stt_0_6b_model_path.display(),
+                        e
+                    )
+                })?;
+            info!("✓ Parakeet-TDT-0.6B loaded successfully (GPU, forced)");
+            Ok(SttEngine::Parakeet0_6B(ort_recognizer))
+        }
+        "0.6b-cpu" => {
+            info!("  Loading Parakeet-TDT-0.6B via ONNX Runtime (CPU, forced)...");
+            let ort_recognizer =
+                OrtRecognizer::new(&config.stt_0_6b_model_path, false).map_err(|e| {
+                    anyhow::anyhow!(
+                        "Failed to load 0.6B CPU model from {}. \
+                    \nError: {}",
+                        config.stt_0_6b_model_path.display(),
+                        e
+                    )
+                })?;
+            info!("✓ Parakeet-TDT-0.6B loaded successfully (CPU, forced)");
+            Ok(SttEngine::Parakeet0_6B(ort_recognizer))
+        }
+        "whisper-cpu" | "whisper-gpu" => {
+            let use_gpu = spec == "whisper-gpu";
+            info!("  Loading Whisper via ONNX Runtime (forced)...");
+            let mut whisper = WhisperRecognizer::new(&config.stt_whisper_model_path, use_gpu)
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "Failed to load Whisper model from {}. \
+                    \nError: {}",
+                        config.stt_whisper_model_path.display(),
+                        e
+                    )
+                })?;
+            whisper.set_language(config.whisper_language.as_deref())?;
+            info!(
+                "✓ Whisper-{} loaded successfully ({}, forced)",
+                whisper.size(),
+                if use_gpu { "GPU" } else { "CPU" }
+            );
+            Ok(SttEngine::Whisper(whisper))
+        }
+        _ => Err(anyhow::anyhow!(
+            "Invalid STT model: '{}'. \
+            Valid options: '0.6b-cpu', '0.6b-gpu', '1.1b-cpu', '1.1b-gpu', 'whisper-cpu', \
+            'whisper-gpu' \
+            (or 'auto' in config)",
+            spec
+        )),
+    }
+}
+
+/// Describe the loaded STT engine for segment metrics and events
+fn inference_metadata(stt: &SttEngine) -> InferenceMetadata {
+    let info = stt.inference_info();
+    InferenceMetadata {
+        model: info.model,
+        quantization: info.quantization,
+        provider: info.provider,
+        device: info.device,
+    }
 }
 
 /// DEBUG: Save audio samples to WAV file for analysis
@@ -867,3 +2552,45 @@ fn save_audio_debug(samples: &[f32], path: &str) -> Result<()> {
         .map_err(|e| anyhow::anyhow!("Failed to finalize WAV: {}", e))?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_switch_to_live_model_is_a_no_op() {
+        assert!(!needs_load("0.6b-gpu", "0.6b-gpu").unwrap());
+        assert!(needs_load("0.6b-gpu", "1.1b-cpu").unwrap());
+    }
+
+    #[test]
+    fn test_invalid_model_fails_before_loading() {
+        for spec in ["auto", "2b-gpu", ""] {
+            let e = needs_load("0.6b-gpu", spec).unwrap_err();
+            assert!(e.to_string().contains("Invalid STT model"), "{}", e);
+        }
+    }
+
+    #[test]
+    fn test_vram_downgrade_steps() {
+        assert_eq!(
+            vram_downgrade_steps("1.1b-gpu", true),
+            Some(("1.1b-cpu", "1.1b-cpu"))
+        );
+        assert_eq!(
+            vram_downgrade_steps("1.1b-gpu", false),
+            Some(("0.6b-cpu", "0.6b-gpu"))
+        );
+        assert_eq!(
+            vram_downgrade_steps("0.6b-gpu", true),
+            Some(("0.6b-cpu", "0.6b-cpu"))
+        );
+        assert_eq!(
+            vram_downgrade_steps("whisper-gpu", false),
+            Some(("whisper-cpu", "whisper-cpu"))
+        );
+        for cpu in ["0.6b-cpu", "1.1b-cpu", "whisper-cpu"] {
+            assert_eq!(vram_downgrade_steps(cpu, true), None);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+//! Playing sounds on the default output device
+//!
+//! The daemon only captures audio itself, so earcons and synthesized speech
+//! are handed to the platform's command-line player.
+
+use anyhow::{bail, Result};
+use std::path::Path;
+use std::process::{Child, Command, Stdio};
+use tracing::debug;
+
+/// Start playing a WAV file, returning the player process
+pub fn play_wav(path: &Path) -> Result<Child> {
+    let players: &[(&str, &[&str])] = if cfg!(target_os = "macos") {
+        &[("afplay", &[])]
+    } else {
+        &[("paplay", &[]), ("aplay", &["-q"])]
+    };
+    for (player, args) in players {
+        match Command::new(player)
+            .args(*args)
+            .arg(path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => return Ok(child),
+            Err(e) => debug!("{} unavailable: {}", player, e),
+        }
+    }
+    bail!("No audio player found (tried paplay, aplay and afplay)")
+}
@@ -0,0 +1,404 @@
+//! Readalong mode: following a speaker through a script
+//!
+//! For rehearsing talks. A script is loaded over IPC and, while it is,
+//! segments are not typed: their words are aligned against the script from
+//! where the speaker last was, and the new position is broadcast so a UI can
+//! highlight it and flag lines that were skipped.
+//!
+//! Alignment is an edit distance over normalized words (lowercase, letters,
+//! digits and apostrophes) between the segment and the script ahead of the
+//! position. Jumping over script words before the segment's first word is
+//! cheap, so a speaker who skips a paragraph is found again; leaving out or
+//! adding words inside the segment costs as much as a misread word.
+//!
+//! For pronunciation practice every script word passed gets a score: the
+//! recognizer's confidence in it when it was read as written, 0 when it was
+//! misread or left out. A run's pronunciation score is their mean, and is
+//! stored (without the script) when readalong mode is turned off.
+
+use chrono::{DateTime, Utc};
+use swictation_broadcaster::{PracticeWord, ScriptPosition};
+use swictation_metrics::PracticeSession;
+use swictation_stt::WordConfidence;
+
+/// Script words after the position searched beyond twice the segment length
+const LOOKAHEAD_WORDS: usize = 60;
+
+/// Cost per script word jumped over before the segment starts
+const JUMP_COST: f32 = 0.2;
+
+/// Word as compared between script and speech
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '\'')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Normalized words of `text`, leaving out bare punctuation
+pub fn words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(normalize)
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// A script split into words, each knowing its line
+#[derive(Debug, Clone)]
+pub struct Script {
+    words: Vec<String>,
+    /// Line (0-based, blank lines counted) of each word
+    lines: Vec<usize>,
+}
+
+impl Script {
+    pub fn new(text: &str) -> Self {
+        let mut script = Self {
+            words: Vec::new(),
+            lines: Vec::new(),
+        };
+        for (line, text) in text.lines().enumerate() {
+            for word in words(text) {
+                script.words.push(word);
+                script.lines.push(line);
+            }
+        }
+        script
+    }
+
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    /// Lines with at least one word
+    pub fn line_count(&self) -> usize {
+        let mut lines = self.lines.clone();
+        lines.dedup();
+        lines.len()
+    }
+}
+
+/// How a segment lines up with part of the script
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alignment {
+    /// First script word the segment was aligned to; the ones before it,
+    /// from where alignment started, were jumped over
+    pub start: usize,
+    /// End (exclusive) of the script words covered by the segment
+    pub end: usize,
+    /// For each script word in `start..end`, the spoken word read for it
+    /// (`None` when it was left out)
+    pub spoken: Vec<Option<usize>>,
+}
+
+#[derive(Clone, Copy)]
+enum Step {
+    Read,
+    Inserted,
+    LeftOut,
+    Jumped,
+}
+
+/// Align `spoken` against the script words from `from` on
+pub fn align(script: &[String], from: usize, spoken: &[String]) -> Alignment {
+    let ahead = &script[from.min(script.len())..];
+    let ahead = &ahead[..ahead.len().min(2 * spoken.len() + LOOKAHEAD_WORDS)];
+    let (n, m) = (spoken.len(), ahead.len());
+
+    // cost[i][j]: first i spoken words against the first j script words
+    let mut cost = vec![vec![0.0f32; m + 1]; n + 1];
+    let mut steps = vec![vec![Step::Jumped; m + 1]; n + 1];
+    for (j, cost) in cost[0].iter_mut().enumerate() {
+        *cost = j as f32 * JUMP_COST;
+    }
+    for i in 1..=n {
+        cost[i][0] = i as f32;
+        steps[i][0] = Step::Inserted;
+        for j in 1..=m {
+            let read = cost[i - 1][j - 1]
+                + if spoken[i - 1] == ahead[j - 1] {
+                    0.0
+                } else {
+                    1.0
+                };
+            let inserted = cost[i - 1][j] + 1.0;
+            let left_out = cost[i][j - 1] + 1.0;
+            (cost[i][j], steps[i][j]) = if read <= inserted && read <= left_out {
+                (read, Step::Read)
+            } else if inserted <= left_out {
+                (inserted, Step::Inserted)
+            } else {
+                (left_out, Step::LeftOut)
+            };
+        }
+    }
+
+    // The script after the segment is free: end where the cost is lowest,
+    // taking a misread last word over an extra one
+    let mut end = 0;
+    for j in 1..=m {
+        if cost[n][j] <= cost[n][end] {
+            end = j;
+        }
+    }
+
+    let mut spoken_for = Vec::new();
+    let (mut i, mut j) = (n, end);
+    while i > 0 || j > 0 {
+        match steps[i][j] {
+            Step::Read => {
+                spoken_for.push(Some(i - 1));
+                i -= 1;
+                j -= 1;
+            }
+            Step::Inserted => i -= 1,
+            Step::LeftOut => {
+                spoken_for.push(None);
+                j -= 1;
+            }
+            Step::Jumped => break,
+        }
+    }
+    spoken_for.reverse();
+
+    let from = from.min(script.len());
+    Alignment {
+        start: from + j,
+        end: from + end,
+        spoken: spoken_for,
+    }
+}
+
+/// Reading progress through a script
+#[derive(Debug)]
+pub struct Readalong {
+    script: Script,
+    /// Next script word expected
+    position: usize,
+    /// Script words read as written
+    matched: Vec<bool>,
+    /// Best score (0-100) of each script word so far
+    scores: Vec<f32>,
+    /// Lines before this one have been checked for skipping
+    judged_line: usize,
+    started_at: DateTime<Utc>,
+}
+
+impl Readalong {
+    pub fn new(script: Script) -> Self {
+        let words = script.words.len();
+        Self {
+            script,
+            position: 0,
+            matched: vec![false; words],
+            scores: vec![0.0; words],
+            judged_line: 0,
+            started_at: Utc::now(),
+        }
+    }
+
+    /// Move through the script by what was just said, scoring the words
+    /// read with the recognizer's `confidences` for the segment
+    ///
+    /// Returns the new position, or `None` when the segment had no words.
+    pub fn follow(&mut self, text: &str, confidences: &[WordConfidence]) -> Option<ScriptPosition> {
+        let spoken = words(text);
+        if spoken.is_empty() {
+            return None;
+        }
+        // Confidences only line up with the text if post-processing kept
+        // every word
+        let confidences: Vec<f32> = confidences
+            .iter()
+            .filter(|w| !normalize(&w.word).is_empty())
+            .map(|w| w.confidence)
+            .collect();
+        let confidence = |s: usize| (confidences.len() == spoken.len()).then(|| confidences[s]);
+
+        let alignment = align(&self.script.words, self.position, &spoken);
+        let mut scored = Vec::with_capacity(alignment.spoken.len());
+        for (k, &s) in alignment.spoken.iter().enumerate() {
+            let index = alignment.start + k;
+            let word = &self.script.words[index];
+            let matched = s.is_some_and(|s| spoken[s] == *word);
+            let confidence = s.and_then(confidence);
+            let score = if matched {
+                confidence.unwrap_or(1.0) * 100.0
+            } else {
+                0.0
+            };
+            self.matched[index] |= matched;
+            self.scores[index] = self.scores[index].max(score);
+            scored.push(PracticeWord {
+                index,
+                word: word.clone(),
+                spoken: s.map(|s| spoken[s].clone()),
+                matched,
+                confidence,
+                score,
+            });
+        }
+        self.position = self.position.max(alignment.end);
+
+        let mut position = self.position();
+        position.words = scored;
+        Some(position)
+    }
+
+    /// Where the speaker is, with the lines passed unread since last time
+    pub fn position(&mut self) -> ScriptPosition {
+        let total_words = self.script.words.len();
+        // The line being read is not judged until the speaker moves past it
+        let line = match self.script.lines.get(self.position) {
+            Some(&line) => line,
+            None => self.script.lines.last().map_or(0, |line| line + 1),
+        };
+        let mut skipped_lines: Vec<usize> = (0..self.position)
+            .filter(|&word| self.script.lines[word] >= self.judged_line)
+            .filter(|&word| self.script.lines[word] < line)
+            .map(|word| self.script.lines[word])
+            .collect();
+        skipped_lines.dedup();
+        skipped_lines.retain(|&skipped| {
+            !(0..self.position).any(|word| self.script.lines[word] == skipped && self.matched[word])
+        });
+        self.judged_line = self.judged_line.max(line);
+
+        ScriptPosition {
+            word: self.position,
+            line,
+            total_words,
+            accuracy: self.accuracy(),
+            skipped_lines,
+            finished: self.position == total_words,
+            pronunciation_score: self.pronunciation_score(),
+            words: Vec::new(),
+        }
+    }
+
+    /// Mean score of the script words passed (0-100)
+    pub fn pronunciation_score(&self) -> f32 {
+        if self.position == 0 {
+            return 0.0;
+        }
+        self.scores[..self.position].iter().sum::<f32>() / self.position as f32
+    }
+
+    /// The run so far as a practice session to store
+    pub fn practice(&self) -> PracticeSession {
+        PracticeSession {
+            practice_id: None,
+            started_at: self.started_at,
+            ended_at: Utc::now(),
+            script_words: self.script.words.len() as i64,
+            words_read: self.position as i64,
+            words_matched: self.matched[..self.position].iter().filter(|&&m| m).count() as i64,
+            accuracy: self.accuracy() as f64,
+            pronunciation_score: self.pronunciation_score() as f64,
+        }
+    }
+
+    /// Fraction of the script words passed that were read as written
+    pub fn accuracy(&self) -> f32 {
+        if self.position == 0 {
+            return 1.0;
+        }
+        let matched = self.matched[..self.position].iter().filter(|&&m| m).count();
+        matched as f32 / self.position as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = "Good morning, everyone.\n\
+                          Today we look at the quarterly numbers.\n\
+                          \n\
+                          Revenue grew by ten percent.\n\
+                          Costs stayed flat over the year.";
+
+    #[test]
+    fn test_alignment_tolerates_misreads() {
+        let script = Script::new(SCRIPT);
+        assert_eq!(script.words().len(), 21);
+        assert_eq!(script.line_count(), 4);
+
+        let spoken = words("good mourning everyone");
+        let alignment = align(script.words(), 0, &spoken);
+        assert_eq!((alignment.start, alignment.end), (0, 3));
+        assert_eq!(alignment.spoken, vec![Some(0), Some(1), Some(2)]);
+
+        // A word left out inside the segment
+        let alignment = align(script.words(), 3, &words("today we at the"));
+        assert_eq!((alignment.start, alignment.end), (3, 8));
+        assert_eq!(
+            alignment.spoken,
+            vec![Some(0), Some(1), None, Some(2), Some(3)]
+        );
+    }
+
+    #[test]
+    fn test_follow_tracks_position_and_accuracy() {
+        let mut readalong = Readalong::new(Script::new(SCRIPT));
+        let position = readalong.follow("Good morning, everyone.", &[]).unwrap();
+        assert_eq!((position.word, position.line), (3, 1));
+        assert_eq!(position.accuracy, 1.0);
+        assert!(position.skipped_lines.is_empty());
+
+        let position = readalong
+            .follow("today we look at the quarterly figures", &[])
+            .unwrap();
+        // The last word of the line was misread but still passed
+        assert_eq!((position.word, position.line), (10, 3));
+        assert_eq!(position.accuracy, 9.0 / 10.0);
+        assert!(readalong.follow("...", &[]).is_none());
+    }
+
+    #[test]
+    fn test_skipped_line_is_flagged() {
+        let mut readalong = Readalong::new(Script::new(SCRIPT));
+        readalong.follow("good morning everyone", &[]).unwrap();
+        // The speaker goes straight to the last line
+        let position = readalong
+            .follow("costs stayed flat over the year", &[])
+            .unwrap();
+        assert_eq!(position.words[0].index, 15);
+        assert_eq!(position.skipped_lines, vec![1, 3]);
+        assert!(position.finished);
+        assert_eq!(position.line, 5);
+    }
+
+    #[test]
+    fn test_words_scored_by_confidence() {
+        let confidences = |words: &[(&str, f32)]| -> Vec<WordConfidence> {
+            words
+                .iter()
+                .map(|&(word, confidence)| WordConfidence {
+                    word: word.to_string(),
+                    confidence,
+                    start_s: None,
+                })
+                .collect()
+        };
+        let mut readalong = Readalong::new(Script::new(SCRIPT));
+        let position = readalong
+            .follow(
+                "Good mourning, everyone.",
+                &confidences(&[("Good", 0.75), ("mourning,", 0.25), ("everyone.", 0.75)]),
+            )
+            .unwrap();
+        let scores: Vec<f32> = position.words.iter().map(|w| w.score).collect();
+        assert_eq!(scores, vec![75.0, 0.0, 75.0]);
+        assert_eq!(position.words[1].spoken.as_deref(), Some("mourning"));
+        assert!(!position.words[1].matched);
+        assert_eq!(position.pronunciation_score, 50.0);
+
+        // Without confidences a word read as written scores full marks
+        readalong.follow("today", &[]).unwrap();
+        let practice = readalong.practice();
+        assert_eq!((practice.words_read, practice.words_matched), (4, 3));
+        assert_eq!(practice.pronunciation_score, 62.5);
+        assert_eq!(practice.script_words, 21);
+    }
+}
@@ -0,0 +1,133 @@
+//! Boot progress for installers and the UI splash screen
+//!
+//! Startup passes through the [`ReadinessStage`]s in order. Each stage reached
+//! is logged and sent as a `readiness` event on the metrics socket, and a
+//! failure is reported against the stage that was being attempted. With
+//! `--self-test` the stages are printed instead and the daemon exits with the
+//! failing stage's [`exit_code`], so an installer can tell which part of the
+//! setup is broken without parsing logs.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use swictation_broadcaster::{MetricsBroadcaster, ReadinessStage};
+use tracing::info;
+
+/// `--self-test` exit code when `stage` could not be reached
+///
+/// 10 for paths-ok through 16 for sockets-ready; 0 means every stage passed.
+pub fn exit_code(stage: ReadinessStage) -> i32 {
+    let index = ReadinessStage::ALL
+        .iter()
+        .position(|s| *s == stage)
+        .unwrap_or_default();
+    10 + index as i32
+}
+
+pub struct Readiness {
+    /// Unset until the socket paths are known
+    broadcaster: Option<Arc<MetricsBroadcaster>>,
+    self_test: bool,
+    /// Last stage reached
+    last: Mutex<Option<ReadinessStage>>,
+}
+
+impl Readiness {
+    pub fn new(self_test: bool) -> Self {
+        Self {
+            broadcaster: None,
+            self_test,
+            last: Mutex::new(None),
+        }
+    }
+
+    pub fn set_broadcaster(&mut self, broadcaster: Arc<MetricsBroadcaster>) {
+        self.broadcaster = Some(broadcaster);
+    }
+
+    pub async fn reached(&self, stage: ReadinessStage) {
+        *self.last.lock().unwrap() = Some(stage);
+        info!("🚦 Readiness: {}", stage);
+        if self.self_test {
+            println!("✓ {}", stage);
+        }
+        if let Some(broadcaster) = &self.broadcaster {
+            broadcaster.broadcast_readiness(stage, None).await;
+        }
+    }
+
+    /// The stage being worked towards: the one after the last reached
+    pub fn pending(&self) -> ReadinessStage {
+        match *self.last.lock().unwrap() {
+            Some(stage) => stage.next().unwrap_or(stage),
+            None => ReadinessStage::PathsOk,
+        }
+    }
+
+    /// Report `err` against the pending stage and hand it back
+    ///
+    /// In self-test mode this exits with the stage's [`exit_code`] instead.
+    /// Otherwise the broadcaster is stopped once clients have the event, as
+    /// the daemon is about to exit.
+    pub async fn failed(&self, err: anyhow::Error) -> anyhow::Error {
+        let stage = self.pending();
+        if self.self_test {
+            println!("✗ {}: {:#}", stage, err);
+            std::process::exit(exit_code(stage));
+        }
+        if let Some(broadcaster) = &self.broadcaster {
+            broadcaster
+                .broadcast_readiness(stage, Some(format!("{:#}", err)))
+                .await;
+            let _ = broadcaster.stop().await;
+        }
+        err
+    }
+}
+
+/// Check a socket can be bound in `dir` without touching a running daemon's
+/// sockets (used by `--self-test`)
+pub fn probe_socket_dir(dir: &Path) -> anyhow::Result<()> {
+    let probe = dir.join(format!(".swictation-self-test-{}.sock", std::process::id()));
+    let listener = std::os::unix::net::UnixListener::bind(&probe);
+    let _ = std::fs::remove_file(&probe);
+    listener
+        .map(drop)
+        .map_err(|e| anyhow::anyhow!("Cannot create sockets in {}: {}", dir.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(exit_code(ReadinessStage::PathsOk), 10);
+        assert_eq!(exit_code(ReadinessStage::SttLoading), 14);
+        assert_eq!(exit_code(ReadinessStage::SocketsReady), 16);
+    }
+
+    #[tokio::test]
+    async fn test_pending_stage() {
+        let socket = std::env::temp_dir().join(format!("readiness-{}.sock", std::process::id()));
+        let broadcaster = Arc::new(MetricsBroadcaster::new(&socket).await.unwrap());
+        let mut readiness = Readiness::new(false);
+        readiness.set_broadcaster(broadcaster);
+        assert_eq!(readiness.pending(), ReadinessStage::PathsOk);
+
+        readiness.reached(ReadinessStage::PathsOk).await;
+        readiness.reached(ReadinessStage::ConfigOk).await;
+        assert_eq!(readiness.pending(), ReadinessStage::AudioOk);
+        let err = readiness.failed(anyhow::anyhow!("no capture device")).await;
+        assert_eq!(err.to_string(), "no capture device");
+
+        readiness.reached(ReadinessStage::SocketsReady).await;
+        assert_eq!(readiness.pending(), ReadinessStage::SocketsReady);
+    }
+
+    #[test]
+    fn test_probe_socket_dir() {
+        assert!(probe_socket_dir(&std::env::temp_dir()).is_ok());
+        assert!(probe_socket_dir(Path::new("/nonexistent/swictation")).is_err());
+    }
+}
@@ -0,0 +1,200 @@
+//! Per-segment audio recordings
+//!
+//! When `save_recordings` is enabled, every transcribed segment's speech
+//! audio is kept as `<data dir>/swictation/recordings/<segment_id>.wav`.
+//! This is what makes `retry_segment` possible - the metrics database
+//! itself never stores audio.
+//!
+//! The text that was typed is only written next to it (`<segment_id>.txt`)
+//! when transcription storage is enabled; otherwise it is kept in memory
+//! ([`SegmentTexts`]) for retries during the same run, so saving audio does
+//! not quietly store transcripts.
+
+use anyhow::{Context, Result};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+
+/// Sample rate of stored recordings (matches the capture pipeline)
+const SAMPLE_RATE: u32 = 16000;
+
+/// Segment texts held in memory when transcripts are not stored
+const MAX_SEGMENT_TEXTS: usize = 500;
+
+/// Directory holding segment recordings
+pub fn recordings_dir() -> PathBuf {
+    swictation_paths::data_dir().join("recordings")
+}
+
+fn audio_path(segment_id: i64) -> PathBuf {
+    recordings_dir().join(format!("{}.wav", segment_id))
+}
+
+fn text_path(segment_id: i64) -> PathBuf {
+    recordings_dir().join(format!("{}.txt", segment_id))
+}
+
+/// Save a segment's audio, and its final text when transcripts are stored
+pub fn save_segment(segment_id: i64, samples: &[f32], text: Option<&str>) -> Result<PathBuf> {
+    std::fs::create_dir_all(recordings_dir()).context("Failed to create recordings directory")?;
+
+    let path = audio_path(segment_id);
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+
+    let mut writer = hound::WavWriter::create(&path, spec)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    for &sample in samples {
+        writer.write_sample((sample * 32767.0).clamp(-32768.0, 32767.0) as i16)?;
+    }
+    writer.finalize().context("Failed to finalize recording")?;
+
+    match text {
+        Some(text) => {
+            std::fs::write(text_path(segment_id), text).context("Failed to write recording text")?
+        }
+        // A reused segment id must not pick up an older segment's text
+        None => match std::fs::remove_file(text_path(segment_id)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(e).context("Failed to remove old recording text")
+            }
+            _ => {}
+        },
+    }
+
+    Ok(path)
+}
+
+/// Load a segment's audio (as f32 samples) and the text originally typed,
+/// if it was stored
+pub fn load_segment(segment_id: i64) -> Result<(Vec<f32>, Option<String>)> {
+    let path = audio_path(segment_id);
+    let mut reader = hound::WavReader::open(&path).with_context(|| {
+        format!(
+            "No recording for segment {} (is save_recordings enabled?)",
+            segment_id
+        )
+    })?;
+
+    let samples = reader
+        .samples::<i16>()
+        .map(|s| s.map(|v| v as f32 / 32768.0))
+        .collect::<std::result::Result<Vec<f32>, _>>()
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+
+    let text = std::fs::read_to_string(text_path(segment_id)).ok();
+
+    Ok((samples, text))
+}
+
+/// Delete the recordings of segments removed from the metrics database
+///
+/// Their ids may be handed to new segments, which must not inherit the
+/// audio or text. Returns how many recordings were deleted.
+pub fn delete_segments(segment_ids: &[i64]) -> usize {
+    delete_segments_in(&recordings_dir(), segment_ids)
+}
+
+fn delete_segments_in(dir: &Path, segment_ids: &[i64]) -> usize {
+    let mut deleted = 0;
+    for id in segment_ids {
+        for ext in ["wav", "txt"] {
+            let path = dir.join(format!("{}.{}", id, ext));
+            match std::fs::remove_file(&path) {
+                Ok(()) => deleted += usize::from(ext == "wav"),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => warn!("Failed to delete {}: {}", path.display(), e),
+            }
+        }
+    }
+    deleted
+}
+
+/// The `limit` most recent segment recordings, newest first
+pub fn recent_recordings(limit: usize) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(recordings_dir()) else {
+        return Vec::new();
+    };
+    let mut recordings: Vec<(i64, PathBuf)> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wav"))
+        .filter_map(|path| {
+            let id = path.file_stem()?.to_str()?.parse().ok()?;
+            Some((id, path))
+        })
+        .collect();
+    recordings.sort_by(|a, b| b.0.cmp(&a.0));
+    recordings
+        .into_iter()
+        .take(limit)
+        .map(|(_, path)| path)
+        .collect()
+}
+
+/// Text typed for recent segments, for `retry_segment` diffs while
+/// transcripts are not stored on disk
+///
+/// Lost on restart; only the most recent segments are kept.
+#[derive(Clone, Default)]
+pub struct SegmentTexts(Arc<Mutex<VecDeque<(i64, String)>>>);
+
+impl SegmentTexts {
+    pub fn insert(&self, segment_id: i64, text: &str) {
+        let mut texts = self.0.lock().unwrap();
+        texts.retain(|(id, _)| *id != segment_id);
+        if texts.len() == MAX_SEGMENT_TEXTS {
+            texts.pop_front();
+        }
+        texts.push_back((segment_id, text.to_string()));
+    }
+
+    pub fn get(&self, segment_id: i64) -> Option<String> {
+        let texts = self.0.lock().unwrap();
+        texts
+            .iter()
+            .find(|(id, _)| *id == segment_id)
+            .map(|(_, text)| text.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delete_segments_removes_audio_and_text() {
+        let dir = std::env::temp_dir().join(format!("swictation-rec-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["1.wav", "1.txt", "2.wav", "3.wav"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+
+        assert_eq!(delete_segments_in(&dir, &[1, 2, 9]), 2);
+        let mut left: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        left.sort();
+        assert_eq!(left, vec!["3.wav"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_segment_texts_keep_most_recent() {
+        let texts = SegmentTexts::default();
+        for id in 0..MAX_SEGMENT_TEXTS as i64 + 10 {
+            texts.insert(id, &format!("segment {}", id));
+        }
+        assert_eq!(texts.get(5), None);
+        assert_eq!(texts.get(10), Some("segment 10".to_string()));
+
+        // A reused id replaces the old text
+        texts.insert(10, "replaced");
+        assert_eq!(texts.get(10), Some("replaced".to_string()));
+    }
+}
@@ -0,0 +1,111 @@
+//! Segment retry (`retry_segment` IPC command)
+//!
+//! Re-runs a recorded segment through STT, optionally with a different
+//! model, and reports a word-level diff against the text that was typed.
+
+use serde::Serialize;
+use swictation_metrics::InferenceMetadata;
+use swictation_textsim::{myers_diff, Edit};
+
+/// One run of words in a word-level diff
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "op", content = "text", rename_all = "lowercase")]
+pub enum DiffOp {
+    Equal(String),
+    Insert(String),
+    Delete(String),
+}
+
+/// Outcome of re-transcribing a stored segment
+#[derive(Debug, Clone, Serialize)]
+pub struct RetryResult {
+    pub segment_id: i64,
+    pub alternative_id: i64,
+    pub original: String,
+    pub alternative: String,
+    pub inference: InferenceMetadata,
+    pub diff: Vec<DiffOp>,
+}
+
+/// Word-level diff from `original` to `alternative` (Myers' shortest edit script)
+///
+/// Consecutive words with the same operation are merged into one entry.
+pub fn word_diff(original: &str, alternative: &str) -> Vec<DiffOp> {
+    let a: Vec<&str> = original.split_whitespace().collect();
+    let b: Vec<&str> = alternative.split_whitespace().collect();
+
+    let words = myers_diff(&a, &b).into_iter().map(|edit| match edit {
+        Edit::Equal(i, _) => (Kind::Equal, a[i]),
+        Edit::Delete(i) => (Kind::Delete, a[i]),
+        Edit::Insert(j) => (Kind::Insert, b[j]),
+    });
+
+    // Merge runs of the same kind
+    let mut runs: Vec<(Kind, String)> = Vec::new();
+    for (kind, word) in words {
+        match runs.last_mut() {
+            Some((last, text)) if *last == kind => {
+                text.push(' ');
+                text.push_str(word);
+            }
+            _ => runs.push((kind, word.to_string())),
+        }
+    }
+
+    runs.into_iter()
+        .map(|(kind, text)| match kind {
+            Kind::Equal => DiffOp::Equal(text),
+            Kind::Insert => DiffOp::Insert(text),
+            Kind::Delete => DiffOp::Delete(text),
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Equal,
+    Insert,
+    Delete,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_diff() {
+        let diff = word_diff(
+            "the quick brown socks jumped",
+            "the quick brown fox jumped over",
+        );
+        assert_eq!(
+            diff,
+            vec![
+                DiffOp::Equal("the quick brown".to_string()),
+                DiffOp::Delete("socks".to_string()),
+                DiffOp::Insert("fox".to_string()),
+                DiffOp::Equal("jumped".to_string()),
+                DiffOp::Insert("over".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_word_diff_identical_and_empty() {
+        assert_eq!(
+            word_diff("hello world", "hello world"),
+            vec![DiffOp::Equal("hello world".to_string())]
+        );
+        assert_eq!(
+            word_diff("", "hello"),
+            vec![DiffOp::Insert("hello".to_string())]
+        );
+        assert!(word_diff("", "").is_empty());
+    }
+
+    #[test]
+    fn test_diff_serialization() {
+        let json = serde_json::to_string(&DiffOp::Insert("fox".to_string())).unwrap();
+        assert_eq!(json, r#"{"op":"insert","text":"fox"}"#);
+    }
+}
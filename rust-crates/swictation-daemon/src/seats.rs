@@ -0,0 +1,286 @@
+//! System mode: one service dictating for every logged-in user
+//!
+//! Lab and kiosk machines run a single system service instead of a user
+//! service per account. `swictation-daemon --system` (as root) watches
+//! `/run/user` for logged-in users and runs an ordinary daemon for each of
+//! them, switched to the user's UID, groups and home, with their runtime
+//! directory as `XDG_RUNTIME_DIR`. Every user therefore gets their own IPC
+//! and metrics sockets under `/run/user/<uid>`, their own config and their
+//! own metrics database in their home, exactly as with a user service; no
+//! user's daemon can read another's files. A user's daemon is stopped when
+//! they log out (logind removes `/run/user/<uid>`).
+//!
+//! The IPC server additionally refuses connections from any UID but its
+//! own (see `IpcServer`), so a socket left with loose permissions still
+//! only answers its owner.
+
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::ffi::{CStr, CString};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::{Child, Command};
+use tracing::{info, warn};
+
+/// Where logind creates each logged-in user's runtime directory
+const RUN_USER: &str = "/run/user";
+
+/// Lowest UID of a regular (non-system) account
+const MIN_UID: u32 = 1000;
+
+/// How often logins and logouts are picked up
+const RESCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Variables passed from the service to each user's daemon (library paths
+/// for GPU builds, log filter); everything else is set per user
+const PASSED_ENV: [&str; 5] = [
+    "PATH",
+    "LD_LIBRARY_PATH",
+    "ORT_DYLIB_PATH",
+    "RUST_LOG",
+    "LANG",
+];
+
+/// Account a daemon runs as
+#[derive(Debug, Clone)]
+struct SeatUser {
+    uid: u32,
+    gid: u32,
+    name: String,
+    home: PathBuf,
+}
+
+/// Run a daemon for every logged-in user until interrupted
+///
+/// `allowed` restricts this to the listed user names or UIDs; empty means
+/// every regular account.
+pub async fn run(allowed: &[String]) -> Result<()> {
+    if unsafe { libc::geteuid() } != 0 {
+        bail!("--system must run as root, e.g. from a system service");
+    }
+    let exe = std::env::current_exe().context("Failed to locate the daemon binary")?;
+    info!("🖥️ System mode: one daemon per logged-in user");
+
+    let mut seats: HashMap<u32, Child> = HashMap::new();
+    let mut rescan = tokio::time::interval(RESCAN_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = rescan.tick() => {}
+            _ = tokio::signal::ctrl_c() => break,
+        }
+
+        let logged_in: Vec<SeatUser> = logged_in_uids(Path::new(RUN_USER))
+            .into_iter()
+            .filter_map(|uid| match lookup_user(uid) {
+                Ok(user) => Some(user),
+                Err(e) => {
+                    warn!("⚠️ Skipping UID {}: {:#}", uid, e);
+                    None
+                }
+            })
+            .filter(|user| is_allowed(allowed, user))
+            .collect();
+
+        // Logged out, or the daemon exited (it is restarted below)
+        let mut ended = Vec::new();
+        for (uid, child) in seats.iter_mut() {
+            if !logged_in.iter().any(|user| user.uid == *uid) {
+                info!("👋 UID {} logged out, stopping their daemon", uid);
+                let _ = child.start_kill();
+                ended.push(*uid);
+            } else if let Ok(Some(status)) = child.try_wait() {
+                warn!("⚠️ Daemon for UID {} exited ({}), restarting", uid, status);
+                ended.push(*uid);
+            }
+        }
+        for uid in ended {
+            if let Some(mut child) = seats.remove(&uid) {
+                let _ = child.wait().await;
+            }
+        }
+
+        for user in logged_in {
+            if seats.contains_key(&user.uid) {
+                continue;
+            }
+            match spawn_seat(&exe, &user) {
+                Ok(child) => {
+                    info!("✓ Started daemon for {} (UID {})", user.name, user.uid);
+                    seats.insert(user.uid, child);
+                }
+                Err(e) => warn!("⚠️ Failed to start daemon for {}: {:#}", user.name, e),
+            }
+        }
+    }
+
+    info!("🛑 Stopping {} user daemons", seats.len());
+    for (_, mut child) in seats {
+        let _ = child.start_kill();
+        let _ = child.wait().await;
+    }
+    Ok(())
+}
+
+/// UIDs of regular accounts with a runtime directory in `run_user`
+fn logged_in_uids(run_user: &Path) -> Vec<u32> {
+    let mut uids: Vec<u32> = std::fs::read_dir(run_user)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+        .filter(|&uid| uid >= MIN_UID)
+        .collect();
+    uids.sort_unstable();
+    uids
+}
+
+/// Whether `user` is one of `allowed` (by name or UID), or anyone when
+/// nobody is listed
+fn is_allowed(allowed: &[String], user: &SeatUser) -> bool {
+    allowed.is_empty()
+        || allowed
+            .iter()
+            .any(|entry| *entry == user.name || entry.parse() == Ok(user.uid))
+}
+
+/// Account details of `uid` from the system user database (NSS, so LDAP
+/// and other directory users are found too)
+fn lookup_user(uid: u32) -> Result<SeatUser> {
+    let mut buffer = vec![0 as libc::c_char; 16 * 1024];
+    let mut entry: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut found = std::ptr::null_mut();
+    let rc = unsafe {
+        libc::getpwuid_r(
+            uid,
+            &mut entry,
+            buffer.as_mut_ptr(),
+            buffer.len(),
+            &mut found,
+        )
+    };
+    if rc != 0 || found.is_null() {
+        bail!("No account with UID {}", uid);
+    }
+    let text = |field: *const libc::c_char| {
+        unsafe { CStr::from_ptr(field) }
+            .to_string_lossy()
+            .into_owned()
+    };
+    Ok(SeatUser {
+        uid,
+        gid: entry.pw_gid,
+        name: text(entry.pw_name),
+        home: PathBuf::from(text(entry.pw_dir)),
+    })
+}
+
+/// Every group `user` belongs to, including their primary group
+fn supplementary_groups(user: &SeatUser) -> Result<Vec<libc::gid_t>> {
+    let name = CString::new(user.name.clone()).context("User name contains a NUL byte")?;
+    let mut groups: Vec<libc::gid_t> = vec![0; 64];
+    loop {
+        let mut count = groups.len() as libc::c_int;
+        let rc =
+            unsafe { libc::getgrouplist(name.as_ptr(), user.gid, groups.as_mut_ptr(), &mut count) };
+        if rc >= 0 {
+            groups.truncate(count as usize);
+            return Ok(groups);
+        }
+        // Too small: `count` now holds the number needed (some libcs leave
+        // it unchanged, so grow at least twofold)
+        let needed = (count as usize).max(groups.len() * 2);
+        if needed > 65536 {
+            bail!("Failed to list the groups of {}", user.name);
+        }
+        groups.resize(needed, 0);
+    }
+}
+
+/// Start this binary as an ordinary daemon running as `user`
+fn spawn_seat(exe: &Path, user: &SeatUser) -> Result<Child> {
+    let runtime_dir = Path::new(RUN_USER).join(user.uid.to_string());
+    let mut command = Command::new(exe);
+    command
+        .env_clear()
+        .envs(
+            PASSED_ENV
+                .iter()
+                .filter_map(|key| Some((key, std::env::var_os(key)?))),
+        )
+        .env("HOME", &user.home)
+        .env("USER", &user.name)
+        .env("LOGNAME", &user.name)
+        .env("XDG_RUNTIME_DIR", &runtime_dir)
+        .env(
+            "DBUS_SESSION_BUS_ADDRESS",
+            format!("unix:path={}", runtime_dir.join("bus").display()),
+        )
+        .current_dir(&user.home)
+        .kill_on_drop(true);
+    if runtime_dir.join("wayland-0").exists() {
+        command.env("WAYLAND_DISPLAY", "wayland-0");
+    }
+
+    // Drop to the user's groups, then GID, then UID (the order matters:
+    // once the UID is dropped the groups can no longer be changed). The
+    // child of a multi-threaded process may only make async-signal-safe
+    // calls, so the groups are looked up here rather than with initgroups.
+    let groups = supplementary_groups(user)?;
+    let (uid, gid) = (user.uid, user.gid);
+    unsafe {
+        command.pre_exec(move || {
+            if libc::setgroups(groups.len() as _, groups.as_ptr()) != 0
+                || libc::setgid(gid) != 0
+                || libc::setuid(uid) != 0
+            {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+    command.spawn().context("Failed to spawn daemon")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(uid: u32, name: &str) -> SeatUser {
+        SeatUser {
+            uid,
+            gid: uid,
+            name: name.to_string(),
+            home: PathBuf::from(format!("/home/{}", name)),
+        }
+    }
+
+    #[test]
+    fn test_logged_in_uids_are_regular_accounts() {
+        let dir = std::env::temp_dir().join(format!("swictation-seats-{}", uuid::Uuid::new_v4()));
+        for name in ["0", "120", "1001", "1000", "gdm"] {
+            std::fs::create_dir_all(dir.join(name)).unwrap();
+        }
+        std::fs::write(dir.join("1002"), "").unwrap();
+
+        assert_eq!(logged_in_uids(&dir), vec![1000, 1001]);
+        assert!(logged_in_uids(&dir.join("missing")).is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_supplementary_groups_include_primary_group() {
+        let me = lookup_user(unsafe { libc::getuid() }).unwrap();
+        let groups = supplementary_groups(&me).unwrap();
+        assert!(groups.contains(&me.gid));
+    }
+
+    #[test]
+    fn test_allowed_by_name_or_uid() {
+        let alice = user(1000, "alice");
+        assert!(is_allowed(&[], &alice));
+        assert!(is_allowed(&["alice".to_string()], &alice));
+        assert!(is_allowed(&["bob".to_string(), "1000".to_string()], &alice));
+        assert!(!is_allowed(&["bob".to_string()], &alice));
+    }
+}
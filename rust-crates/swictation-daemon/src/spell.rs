@@ -0,0 +1,145 @@
+//! Spell mode: letter-by-letter dictation
+//!
+//! Between "spell mode" and "end spell", each segment is read as a sequence
+//! of characters instead of words: NATO alphabet ("alpha bravo" → "ab"),
+//! spoken letters ("bee see" → "bc"), digits and a few symbols, joined
+//! without spaces. For identifiers, serial numbers and similar strings that
+//! STT would otherwise mangle into words.
+//!
+//! - "capital"/"cap"/"uppercase" upper-cases the next character
+//! - "double" types the next character twice ("double u" is still "w")
+//! - anything else is typed as recognized, without spaces
+
+/// Character for a spoken letter, digit or symbol name
+fn character(word: &str) -> Option<&'static str> {
+    Some(match word {
+        "alpha" | "alfa" | "ay" => "a",
+        "bravo" | "bee" | "be" => "b",
+        "charlie" | "see" | "sea" => "c",
+        "delta" | "dee" => "d",
+        "echo" => "e",
+        "foxtrot" | "eff" => "f",
+        "golf" | "gee" => "g",
+        "hotel" | "aitch" => "h",
+        "india" | "eye" => "i",
+        "juliet" | "juliett" | "jay" => "j",
+        "kilo" | "kay" => "k",
+        "lima" | "el" => "l",
+        "mike" | "em" => "m",
+        "november" | "en" => "n",
+        "oscar" | "oh" => "o",
+        "papa" | "pee" => "p",
+        "quebec" | "cue" | "queue" => "q",
+        "romeo" | "are" | "ar" => "r",
+        "sierra" | "ess" => "s",
+        "tango" | "tee" | "tea" => "t",
+        "uniform" | "you" => "u",
+        "victor" | "vee" => "v",
+        "whiskey" | "whisky" => "w",
+        "xray" | "x-ray" | "ex" => "x",
+        "yankee" | "why" => "y",
+        "zulu" | "zee" | "zed" => "z",
+        "zero" => "0",
+        "one" => "1",
+        "two" => "2",
+        "three" => "3",
+        "four" => "4",
+        "five" => "5",
+        "six" => "6",
+        "seven" => "7",
+        "eight" => "8",
+        "nine" | "niner" => "9",
+        "space" => " ",
+        "dash" | "hyphen" | "minus" => "-",
+        "underscore" => "_",
+        "dot" | "period" | "point" => ".",
+        "at" => "@",
+        "slash" => "/",
+        "colon" => ":",
+        "plus" => "+",
+        "hash" => "#",
+        _ => return None,
+    })
+}
+
+/// Type `text` character by character
+pub fn spell(text: &str) -> String {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    let mut spelled = String::new();
+    let mut upper = false;
+    let mut repeat = 1;
+    for (i, word) in words.iter().enumerate() {
+        match word.as_str() {
+            "capital" | "cap" | "uppercase" => {
+                upper = true;
+                continue;
+            }
+            "double" => {
+                // "double u" / "double you" is the letter w
+                if matches!(words.get(i + 1).map(String::as_str), Some("u" | "you")) {
+                    spelled.push(if upper { 'W' } else { 'w' });
+                    upper = false;
+                    repeat = 0;
+                } else {
+                    repeat = 2;
+                }
+                continue;
+            }
+            _ => {}
+        }
+        if repeat == 0 {
+            // The "u" of "double u"
+            repeat = 1;
+            continue;
+        }
+
+        // Single letters and digits come through as themselves
+        let chars = character(word).unwrap_or(word);
+        let chars = if upper {
+            chars.to_uppercase()
+        } else {
+            chars.to_string()
+        };
+        for _ in 0..repeat {
+            spelled.push_str(&chars);
+        }
+        upper = false;
+        repeat = 1;
+    }
+    spelled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nato_and_spoken_letters() {
+        assert_eq!(spell("Alpha, bravo, Charlie."), "abc");
+        assert_eq!(spell("bee see dee"), "bcd");
+        assert_eq!(spell("x-ray Yankee zulu"), "xyz");
+        assert_eq!(spell("a b c 1 2"), "abc12");
+    }
+
+    #[test]
+    fn test_modifiers_and_symbols() {
+        assert_eq!(spell("capital tango one niner"), "T19");
+        assert_eq!(spell("double oscar dash seven"), "oo-7");
+        assert_eq!(spell("double u cap double you"), "wW");
+        assert_eq!(spell("mike underscore kilo at echo dot India"), "m_k@e.i");
+    }
+
+    #[test]
+    fn test_unknown_words_typed_as_recognized() {
+        assert_eq!(spell("hotel swictation two"), "hswictation2");
+        assert_eq!(spell(""), "");
+    }
+}
@@ -0,0 +1,76 @@
+//! Dedicated OS threads for the blocking pipeline stages
+//!
+//! VAD and STT inference are synchronous ONNX calls that take from a few
+//! milliseconds (VAD) up to hundreds of milliseconds (STT). Running them as
+//! tokio tasks parks executor workers for that long, which delays IPC,
+//! hotkey handling and metrics broadcasts. Each stage therefore gets its own
+//! named thread and talks to the async side through bounded channels
+//! (`blocking_recv` / `blocking_send`).
+
+use anyhow::{Context, Result};
+use std::thread::JoinHandle;
+use tracing::debug;
+
+/// Nice value requested for the VAD thread
+///
+/// VAD sits between the audio callback and STT; if it falls behind, audio
+/// chunks are dropped. Raising priority needs CAP_SYS_NICE, so this is best
+/// effort and silently stays at the default without it.
+pub const VAD_NICE: i32 = -5;
+
+/// Nice value for the STT thread (default priority)
+pub const STT_NICE: i32 = 0;
+
+/// Spawn a named stage thread running `f` at the given nice value
+pub fn spawn_stage<F>(name: &str, nice: i32, f: F) -> Result<JoinHandle<()>>
+where
+    F: FnOnce() + Send + 'static,
+{
+    let thread_name = name.to_string();
+    std::thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || {
+            set_current_thread_nice(&thread_name, nice);
+            f();
+        })
+        .with_context(|| format!("Failed to spawn {} thread", name))
+}
+
+#[cfg(target_os = "linux")]
+fn set_current_thread_nice(name: &str, nice: i32) {
+    if nice == 0 {
+        return;
+    }
+    // On Linux, PRIO_PROCESS with a thread ID applies to that thread only
+    let tid = unsafe { libc::gettid() } as libc::id_t;
+    let rc = unsafe { libc::setpriority(libc::PRIO_PROCESS, tid, nice) };
+    if rc != 0 {
+        debug!(
+            "Could not set {} thread nice to {}: {}",
+            name,
+            nice,
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_current_thread_nice(name: &str, nice: i32) {
+    if nice != 0 {
+        debug!("Thread priority not adjusted for {} on this platform", name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spawn_stage_runs_named_thread() {
+        let handle = spawn_stage("swictation-test", STT_NICE, || {
+            assert_eq!(std::thread::current().name(), Some("swictation-test"));
+        })
+        .unwrap();
+        handle.join().unwrap();
+    }
+}
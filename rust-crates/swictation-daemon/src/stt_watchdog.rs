@@ -0,0 +1,80 @@
+//! Watchdog for stuck STT inference
+//!
+//! A pathological segment or a driver hang can keep `recognize` from ever
+//! returning, and with it the engine lock and all later dictation. Each
+//! recognition gets a timer thread that terminates the ONNX Runtime runs
+//! when the timeout passes; the sessions are then reloaded so the next
+//! segment does not inherit whatever state the cancelled run left behind.
+
+use anyhow::Result;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+use swictation_stt::{RecognitionResult, SttEngine};
+use tracing::{error, info, warn};
+
+/// Error returned when the watchdog cancelled a recognition
+#[derive(Debug)]
+pub struct SttTimeout {
+    pub after: Duration,
+}
+
+impl std::fmt::Display for SttTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "STT inference cancelled after {:.1}s",
+            self.after.as_secs_f64()
+        )
+    }
+}
+
+impl std::error::Error for SttTimeout {}
+
+/// Recognize `samples`, cancelling inference that runs longer than `timeout`
+///
+/// `on_timeout` runs on the watchdog thread as soon as the timeout passes,
+/// so it is reported even if inference never comes back. `None` disables
+/// the watchdog.
+pub fn recognize(
+    stt: &mut SttEngine,
+    samples: &[f32],
+    timeout: Option<Duration>,
+    on_timeout: impl FnOnce() + Send + 'static,
+) -> Result<RecognitionResult> {
+    let Some(timeout) = timeout else {
+        return Ok(stt.recognize(samples)?);
+    };
+
+    let cancel = stt.cancel_handle();
+    let (done_tx, done_rx) = mpsc::channel::<()>();
+    let watchdog = thread::Builder::new()
+        .name("swictation-stt-watchdog".to_string())
+        .spawn(move || {
+            let fired = done_rx.recv_timeout(timeout) == Err(RecvTimeoutError::Timeout);
+            if fired {
+                warn!(
+                    "⏱️ STT inference exceeded {:.1}s, cancelling",
+                    timeout.as_secs_f64()
+                );
+                cancel.cancel();
+                on_timeout();
+            }
+            fired
+        })?;
+
+    let result = stt.recognize(samples);
+    drop(done_tx);
+    let fired = watchdog.join().unwrap_or(false);
+
+    // Finished just as the timer fired: the result is still good
+    if !fired || result.is_ok() {
+        return Ok(result?);
+    }
+
+    match stt.reload() {
+        Ok(()) => info!("♻️ STT sessions reloaded after timeout"),
+        Err(e) => error!("Failed to reload STT sessions after timeout: {}", e),
+    }
+    Err(SttTimeout { after: timeout }.into())
+}
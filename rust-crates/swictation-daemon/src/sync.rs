@@ -0,0 +1,583 @@
+//! Personalization mirrored between machines through a shared folder
+//!
+//! With `sync_dir` pointing at a folder that something else keeps in sync
+//! (Syncthing, Nextcloud, a network share), learned corrections, bias
+//! phrases (hotwords) and the output and word-filter profiles are merged with
+//! every other machine using that folder. There is no server: the daemon
+//! only reads and writes files there.
+//!
+//! Each machine writes nothing but its own file, `swictation-<id>.json`, so
+//! the sync tool never sees two machines edit the same file. The file holds
+//! one record per rule (a correction by its ID, a phrase, a profile by name)
+//! with the time it last changed; a deleted rule stays behind as a tombstone
+//! so the deletion wins over older copies elsewhere. A sync takes the newest
+//! record of every rule across all files (last writer wins, per rule),
+//! applies it here and writes the merged set back to this machine's file.
+//!
+//! Local edits are found by comparing against the previous sync's records,
+//! kept in `sync-state.json` in the data folder. A machine syncing for the
+//! first time dates its rules to 1970, so it adopts what the folder already
+//! has instead of overwriting it. Corrections apply at once (the corrections
+//! file is watched); phrase and profile changes are saved to config.toml and
+//! apply from the next start.
+
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::config::DaemonConfig;
+use crate::corrections::{self, Correction};
+
+/// How often the folder is merged
+const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Machine files in the sync folder are `swictation-<machine id>.json`
+const FILE_PREFIX: &str = "swictation-";
+
+/// Tombstones are dropped after this long, by when every machine that
+/// syncs at all has seen them
+const TOMBSTONE_DAYS: i64 = 180;
+
+/// Record key prefixes, one per kind of rule
+const CORRECTION: &str = "correction:";
+const HOTWORD: &str = "hotword:";
+const OUTPUT_PROFILE: &str = "output_profile:";
+const FILTER_PROFILE: &str = "filter_profile:";
+
+/// One rule as last changed on some machine
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Record {
+    modified_at: DateTime<Utc>,
+    /// `None` once the rule is deleted (a tombstone)
+    #[serde(default)]
+    value: Option<Value>,
+}
+
+type Records = BTreeMap<String, Record>;
+
+/// A machine's records: its file in the sync folder, and locally the state
+/// of the previous sync
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Snapshot {
+    machine: String,
+    #[serde(default)]
+    records: Records,
+}
+
+/// Merge with the sync folder every minute
+pub fn spawn_scheduler(sync_dir: PathBuf) -> tokio::task::JoinHandle<()> {
+    info!("🔄 Syncing personalization through {}", sync_dir.display());
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SYNC_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let dir = sync_dir.clone();
+            match tokio::task::spawn_blocking(move || sync_now(&dir)).await {
+                Ok(Ok(0)) => {}
+                Ok(Ok(changes)) => info!("🔄 Took {} changed rules from other machines", changes),
+                Ok(Err(e)) => warn!("Failed to sync with {}: {:#}", sync_dir.display(), e),
+                Err(e) => warn!("Sync task panicked: {}", e),
+            }
+        }
+    })
+}
+
+/// Merge with the other machines once; returns how many rules changed here
+pub fn sync_now(sync_dir: &Path) -> Result<usize> {
+    let state_path = swictation_paths::data_dir().join("sync-state.json");
+    let corrections_path = swictation_paths::config_dir().join("corrections.toml");
+
+    let (mut state, first_sync) = load_state(&state_path)?;
+
+    // From disk, so command-line overrides of this run are not synced
+    let mut config = DaemonConfig::load().context("Failed to load configuration")?;
+    let mut corrections =
+        corrections::load_corrections(&corrections_path).map_err(|e| anyhow!("{}", e))?;
+
+    let current = local_rules(&config, &corrections)?;
+    let now = if first_sync {
+        DateTime::UNIX_EPOCH
+    } else {
+        Utc::now()
+    };
+    let merged = sync_records(&mut state, current.clone(), sync_dir, now)?;
+
+    let (corrections_changed, config_changed) = apply(&merged, &mut config, &mut corrections)?;
+    if corrections_changed {
+        corrections::save_corrections(&corrections_path, corrections)
+            .map_err(|e| anyhow!("{}", e))?;
+    }
+    if config_changed {
+        config.save()?;
+    }
+
+    let changes = merged
+        .iter()
+        .filter(|(key, record)| current.get(*key) != record.value.as_ref())
+        .count();
+    let contents = serde_json::to_string_pretty(&state)?;
+    std::fs::write(&state_path, contents)
+        .with_context(|| format!("Failed to write {}", state_path.display()))?;
+    Ok(changes)
+}
+
+/// The previous sync's records, and whether this is the first sync
+///
+/// Only a missing state file makes a first sync: a machine that had no
+/// rules last time must still date its new edits.
+fn load_state(state_path: &Path) -> Result<(Snapshot, bool)> {
+    match std::fs::read_to_string(state_path) {
+        Ok(contents) => {
+            let state = serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse {}", state_path.display()))?;
+            Ok((state, false))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let state = Snapshot {
+                machine: uuid::Uuid::new_v4().simple().to_string(),
+                records: Records::new(),
+            };
+            Ok((state, true))
+        }
+        Err(e) => Err(e).context("Failed to read sync state"),
+    }
+}
+
+/// Every synced rule of this machine, keyed like the records
+fn local_rules(
+    config: &DaemonConfig,
+    corrections: &[Correction],
+) -> Result<BTreeMap<String, Value>> {
+    let mut rules = BTreeMap::new();
+    for correction in corrections {
+        let mut value = serde_json::to_value(correction)?;
+        // Counted per machine; syncing it would make every use an edit
+        if let Some(fields) = value.as_object_mut() {
+            fields.remove("use_count");
+        }
+        rules.insert(format!("{}{}", CORRECTION, correction.id), value);
+    }
+    for phrase in &config.bias_phrases {
+        rules.insert(format!("{}{}", HOTWORD, phrase), Value::Bool(true));
+    }
+    for (name, profile) in &config.output.profiles {
+        rules.insert(
+            format!("{}{}", OUTPUT_PROFILE, name),
+            serde_json::to_value(profile)?,
+        );
+    }
+    for (name, profile) in &config.word_filter.profiles {
+        rules.insert(
+            format!("{}{}", FILTER_PROFILE, name),
+            serde_json::to_value(profile)?,
+        );
+    }
+    Ok(rules)
+}
+
+/// Date local edits, merge with the other machines' files in `sync_dir`
+/// and publish the result as this machine's file
+///
+/// `state` holds the previous sync's records and becomes the merged set.
+fn sync_records(
+    state: &mut Snapshot,
+    current: BTreeMap<String, Value>,
+    sync_dir: &Path,
+    now: DateTime<Utc>,
+) -> Result<Records> {
+    let ours = stamp(&state.records, current, now);
+    let own_file = format!("{}{}.json", FILE_PREFIX, state.machine);
+
+    let mut others = Vec::new();
+    let entries = std::fs::read_dir(sync_dir)
+        .with_context(|| format!("Failed to read {}", sync_dir.display()))?;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !name.starts_with(FILE_PREFIX) || !name.ends_with(".json") || name == own_file {
+            continue;
+        }
+        // A file the sync tool is still writing is read again next time
+        match std::fs::read_to_string(entry.path())
+            .map_err(anyhow::Error::from)
+            .and_then(|contents| Ok(serde_json::from_str::<Snapshot>(&contents)?))
+        {
+            Ok(snapshot) => others.push(snapshot.records),
+            Err(e) => warn!("Skipping sync file {}: {:#}", name, e),
+        }
+    }
+
+    let merged = merge(std::iter::once(&ours).chain(&others), now);
+    state.records = merged.clone();
+
+    // Replaced in one rename, so other machines never read half a file
+    let contents = serde_json::to_string_pretty(&*state)?;
+    let temp = sync_dir.join(format!(".{}.tmp", own_file));
+    std::fs::write(&temp, contents)
+        .with_context(|| format!("Failed to write {}", temp.display()))?;
+    std::fs::rename(&temp, sync_dir.join(&own_file))
+        .with_context(|| format!("Failed to publish {}", own_file))?;
+    Ok(merged)
+}
+
+/// Records for `current`, dated `now` where they differ from `previous`;
+/// rules gone since then become tombstones
+fn stamp(previous: &Records, current: BTreeMap<String, Value>, now: DateTime<Utc>) -> Records {
+    let mut records: Records = previous
+        .iter()
+        .filter(|(key, _)| !current.contains_key(*key))
+        .map(|(key, record)| {
+            let modified_at = if record.value.is_some() {
+                now
+            } else {
+                record.modified_at
+            };
+            (
+                key.clone(),
+                Record {
+                    modified_at,
+                    value: None,
+                },
+            )
+        })
+        .collect();
+    for (key, value) in current {
+        let record = match previous.get(&key) {
+            Some(record) if record.value.as_ref() == Some(&value) => record.clone(),
+            _ => Record {
+                modified_at: now,
+                value: Some(value),
+            },
+        };
+        records.insert(key, record);
+    }
+    records
+}
+
+/// The newest record of every rule, without tombstones older than
+/// [`TOMBSTONE_DAYS`]
+fn merge<'a>(snapshots: impl IntoIterator<Item = &'a Records>, now: DateTime<Utc>) -> Records {
+    let mut merged = Records::new();
+    for records in snapshots {
+        for (key, record) in records {
+            if merged.get(key).is_none_or(|kept| wins(record, kept)) {
+                merged.insert(key.clone(), record.clone());
+            }
+        }
+    }
+    let horizon = now - chrono::Duration::days(TOMBSTONE_DAYS);
+    merged.retain(|_, record| record.value.is_some() || record.modified_at > horizon);
+    merged
+}
+
+/// Whether `a` replaces `b`: the later change, on a tie the deletion, then
+/// the larger value, so that every machine picks the same one
+fn wins(a: &Record, b: &Record) -> bool {
+    let rank = |r: &Record| {
+        (
+            r.modified_at,
+            r.value.is_none(),
+            r.value.as_ref().map(Value::to_string),
+        )
+    };
+    rank(a) > rank(b)
+}
+
+/// Bring `config` and `corrections` in line with `merged`; returns whether
+/// the corrections and the config changed
+///
+/// The active output and filter profiles are never deleted: the config
+/// would no longer load. They are published again on the next sync.
+fn apply(
+    merged: &Records,
+    config: &mut DaemonConfig,
+    corrections: &mut Vec<Correction>,
+) -> Result<(bool, bool)> {
+    let live = |prefix: &'static str| {
+        merged.iter().filter_map(move |(key, record)| {
+            Some((key.strip_prefix(prefix)?, record.value.as_ref()))
+        })
+    };
+
+    let use_counts: HashMap<String, u64> = corrections
+        .iter()
+        .map(|c| (c.id.clone(), c.use_count))
+        .collect();
+    let mut synced = Vec::new();
+    // Local order first, new rules after
+    let ids = corrections.iter().map(|c| c.id.as_str()).chain(
+        live(CORRECTION)
+            .map(|(id, _)| id)
+            .filter(|id| !use_counts.contains_key(*id)),
+    );
+    for id in ids {
+        let Some(Some(value)) = merged
+            .get(&format!("{}{}", CORRECTION, id))
+            .map(|r| r.value.clone())
+        else {
+            continue;
+        };
+        let mut value = value;
+        value["use_count"] = use_counts.get(id).copied().unwrap_or(0).into();
+        synced.push(serde_json::from_value::<Correction>(value)?);
+    }
+    let corrections_changed =
+        serde_json::to_value(&synced)? != serde_json::to_value(&*corrections)?;
+    *corrections = synced;
+
+    let before = local_rules(config, &[])?;
+    let mut phrases: Vec<String> = config
+        .bias_phrases
+        .iter()
+        .filter(|p| matches!(live(HOTWORD).find(|(k, _)| k == p), Some((_, Some(_)))))
+        .cloned()
+        .collect();
+    for (phrase, value) in live(HOTWORD) {
+        if value.is_some() && !phrases.iter().any(|p| p == phrase) {
+            phrases.push(phrase.to_string());
+        }
+    }
+    config.bias_phrases = phrases;
+    for (name, value) in live(OUTPUT_PROFILE) {
+        match value {
+            Some(value) => {
+                config
+                    .output
+                    .profiles
+                    .insert(name.to_string(), serde_json::from_value(value.clone())?);
+            }
+            None if name != config.output.profile => {
+                config.output.profiles.remove(name);
+            }
+            None => {}
+        }
+    }
+    for (name, value) in live(FILTER_PROFILE) {
+        match value {
+            Some(value) => {
+                config
+                    .word_filter
+                    .profiles
+                    .insert(name.to_string(), serde_json::from_value(value.clone())?);
+            }
+            None if name != config.word_filter.profile => {
+                config.word_filter.profiles.remove(name);
+            }
+            None => {}
+        }
+    }
+    let config_changed = local_rules(config, &[])? != before;
+
+    Ok((corrections_changed, config_changed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::corrections::{CaseMode, CorrectionMode, MatchType};
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(secs, 0).unwrap()
+    }
+
+    fn rules(entries: &[(&str, Value)]) -> BTreeMap<String, Value> {
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect()
+    }
+
+    fn correction(id: &str, corrected: &str, use_count: u64) -> Correction {
+        Correction {
+            id: id.to_string(),
+            original: "arkon".to_string(),
+            corrected: corrected.to_string(),
+            mode: CorrectionMode::All,
+            match_type: MatchType::Exact,
+            case_mode: CaseMode::PreserveInput,
+            learned_at: at(1_000),
+            use_count,
+        }
+    }
+
+    #[test]
+    fn test_stamp_dates_edits_and_deletions() {
+        let first = stamp(
+            &Records::new(),
+            rules(&[
+                ("hotword:kubectl", Value::Bool(true)),
+                ("hotword:helm", Value::Bool(true)),
+            ]),
+            at(10),
+        );
+        let second = stamp(
+            &first,
+            rules(&[("hotword:kubectl", Value::Bool(true))]),
+            at(20),
+        );
+        assert_eq!(second["hotword:kubectl"].modified_at, at(10));
+        assert_eq!(
+            second["hotword:helm"],
+            Record {
+                modified_at: at(20),
+                value: None
+            }
+        );
+
+        // A tombstone keeps its date while the rule stays deleted
+        let third = stamp(
+            &second,
+            rules(&[("hotword:kubectl", Value::Bool(true))]),
+            at(30),
+        );
+        assert_eq!(third["hotword:helm"].modified_at, at(20));
+    }
+
+    #[test]
+    fn test_first_sync_only_without_state_file() {
+        let dir = std::env::temp_dir().join(format!("swictation-sync-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("sync-state.json");
+
+        let (state, first_sync) = load_state(&path).unwrap();
+        assert!(first_sync);
+
+        // A sync that found no rules anywhere leaves an empty state behind
+        std::fs::write(&path, serde_json::to_string(&state).unwrap()).unwrap();
+        let (reloaded, first_sync) = load_state(&path).unwrap();
+        assert!(!first_sync);
+        assert!(reloaded.records.is_empty());
+        assert_eq!(reloaded.machine, state.machine);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_merge_takes_newest_record() {
+        let record = |secs, value: Option<&str>| Record {
+            modified_at: at(secs),
+            value: value.map(Value::from),
+        };
+        let desktop = Records::from([
+            ("a".to_string(), record(10, Some("desktop"))),
+            ("b".to_string(), record(30, None)),
+            ("old".to_string(), record(0, None)),
+        ]);
+        let laptop = Records::from([
+            ("a".to_string(), record(20, Some("laptop"))),
+            ("b".to_string(), record(25, Some("laptop"))),
+        ]);
+
+        let now = at(TOMBSTONE_DAYS * 86_400 + 5);
+        for merged in [
+            merge([&desktop, &laptop], now),
+            merge([&laptop, &desktop], now),
+        ] {
+            assert_eq!(merged["a"], record(20, Some("laptop")));
+            assert_eq!(merged["b"], record(30, None));
+            assert!(!merged.contains_key("old"));
+        }
+    }
+
+    #[test]
+    fn test_machines_converge_through_the_folder() {
+        let dir = std::env::temp_dir().join(format!("swictation-sync-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut desktop = Snapshot {
+            machine: "desktop".to_string(),
+            records: Records::new(),
+        };
+        let mut laptop = Snapshot {
+            machine: "laptop".to_string(),
+            records: Records::new(),
+        };
+
+        let yes = Value::Bool(true);
+        sync_records(
+            &mut desktop,
+            rules(&[
+                ("hotword:kubectl", yes.clone()),
+                ("hotword:helm", yes.clone()),
+            ]),
+            &dir,
+            at(10),
+        )
+        .unwrap();
+        let merged = sync_records(
+            &mut laptop,
+            rules(&[("hotword:argo", yes.clone())]),
+            &dir,
+            at(20),
+        )
+        .unwrap();
+        assert_eq!(merged.len(), 3);
+
+        // Deleted on the laptop, so gone from the desktop too
+        let merged = sync_records(
+            &mut laptop,
+            rules(&[
+                ("hotword:kubectl", yes.clone()),
+                ("hotword:argo", yes.clone()),
+            ]),
+            &dir,
+            at(30),
+        )
+        .unwrap();
+        assert_eq!(merged["hotword:helm"].value, None);
+        let merged = sync_records(
+            &mut desktop,
+            rules(&[
+                ("hotword:kubectl", yes.clone()),
+                ("hotword:helm", yes.clone()),
+            ]),
+            &dir,
+            at(40),
+        )
+        .unwrap();
+        assert_eq!(merged["hotword:helm"].value, None);
+        assert!(merged["hotword:argo"].value.is_some());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_apply_updates_rules_and_keeps_active_profile() {
+        let mut config = DaemonConfig {
+            bias_phrases: vec!["helm".to_string()],
+            ..Default::default()
+        };
+        let mut corrections = vec![correction("1", "Archon", 7)];
+
+        let mut edited = correction("1", "ARCHON", 0);
+        edited.case_mode = CaseMode::ForcePattern;
+        let mut value = serde_json::to_value(&edited).unwrap();
+        value.as_object_mut().unwrap().remove("use_count");
+        let record = |value: Option<Value>| Record {
+            modified_at: at(50),
+            value,
+        };
+        let merged = Records::from([
+            ("correction:1".to_string(), record(Some(value))),
+            ("hotword:helm".to_string(), record(None)),
+            ("hotword:argo".to_string(), record(Some(Value::Bool(true)))),
+            ("output_profile:default".to_string(), record(None)),
+        ]);
+
+        let (corrections_changed, config_changed) =
+            apply(&merged, &mut config, &mut corrections).unwrap();
+        assert!(corrections_changed && config_changed);
+        assert_eq!(corrections[0].corrected, "ARCHON");
+        assert_eq!(corrections[0].use_count, 7);
+        assert_eq!(config.bias_phrases, vec!["argo".to_string()]);
+        assert!(config.output.profiles.contains_key("default"));
+
+        assert_eq!(
+            apply(&merged, &mut config, &mut corrections).unwrap(),
+            (false, false)
+        );
+    }
+}
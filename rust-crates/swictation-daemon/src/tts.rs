@@ -0,0 +1,212 @@
+//! Spoken read-back of dictated text
+//!
+//! "Read that back" (or the `read_back` IPC action) speaks the last dictated
+//! segment, so users who cannot look at the screen can check what was typed.
+//! While recording, the microphone hears the synthesizer too; the pipeline
+//! asks `Speaker::overlaps` and drops segments captured during read-back.
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, warn};
+
+use crate::config::TtsConfig;
+use crate::playback;
+
+/// Room echo and VAD hangover still heard after the synthesizer finishes
+const ECHO_TAIL_MS: i64 = 500;
+
+/// Wall-clock span of an utterance (`end` unset while speaking)
+#[derive(Debug, Clone, Copy)]
+struct Utterance {
+    id: u64,
+    start: DateTime<Utc>,
+    end: Option<DateTime<Utc>>,
+}
+
+pub struct Speaker {
+    config: TtsConfig,
+    /// Latest utterance
+    utterance: Arc<Mutex<Option<Utterance>>>,
+}
+
+impl Speaker {
+    /// `None` when read-back is disabled
+    pub fn new(config: &TtsConfig) -> Option<Self> {
+        config.enabled.then(|| Self {
+            config: config.clone(),
+            utterance: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    /// Speak `text` on the default output device without waiting for it
+    pub fn speak(&self, text: &str) {
+        let id = {
+            let mut utterance = self.utterance.lock().unwrap();
+            let id = utterance.map_or(0, |u| u.id + 1);
+            *utterance = Some(Utterance {
+                id,
+                start: Utc::now(),
+                end: None,
+            });
+            id
+        };
+
+        let config = self.config.clone();
+        let text = text.to_string();
+        let utterance = self.utterance.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = synthesize(&config, &text) {
+                warn!("🔈 Read-back failed: {:#}", e);
+            }
+            if let Some(u) = utterance.lock().unwrap().as_mut() {
+                if u.id == id {
+                    u.end = Some(Utc::now());
+                }
+            }
+        });
+    }
+
+    /// Whether audio captured between `start` and `end` may contain read-back
+    pub fn overlaps(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        let Some(utterance) = *self.utterance.lock().unwrap() else {
+            return false;
+        };
+        let spoke_until = utterance.end.map_or(end, |spoke_until| {
+            spoke_until + chrono::Duration::milliseconds(ECHO_TAIL_MS)
+        });
+        start <= spoke_until && end >= utterance.start
+    }
+}
+
+fn synthesize(config: &TtsConfig, text: &str) -> Result<()> {
+    match &config.piper_model {
+        Some(model) => speak_piper(model, text),
+        None => speak_platform(config, text),
+    }
+}
+
+/// Synthesize with a Piper ONNX voice, then play the result
+fn speak_piper(model: &Path, text: &str) -> Result<()> {
+    let wav = swictation_paths::data_dir().join(format!("readback-{}.wav", uuid::Uuid::new_v4()));
+    let mut piper = Command::new("piper")
+        .arg("--model")
+        .arg(model)
+        .arg("--output_file")
+        .arg(&wav)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to start piper (is it installed?)")?;
+    piper
+        .stdin
+        .take()
+        .context("piper stdin unavailable")?
+        .write_all(text.as_bytes())?;
+    let status = piper.wait()?;
+    if !status.success() {
+        let _ = std::fs::remove_file(&wav);
+        bail!("piper exited with {}", status);
+    }
+
+    let played = playback::play_wav(&wav).and_then(|mut player| Ok(player.wait()?));
+    let _ = std::fs::remove_file(&wav);
+    played.map(|_| ())
+}
+
+/// Speak through the first platform synthesizer that is installed
+///
+/// Text goes in on stdin so dictation starting with `-` is not read as an
+/// option.
+fn speak_platform(config: &TtsConfig, text: &str) -> Result<()> {
+    let rate = config.rate_wpm.to_string();
+    let mut synthesizers = Vec::new();
+    if cfg!(target_os = "macos") {
+        let mut say = Command::new("say");
+        say.args(["-r", &rate, "-f", "-"]);
+        if let Some(voice) = &config.voice {
+            say.args(["-v", voice]);
+        }
+        synthesizers.push(say);
+    } else {
+        for program in ["espeak-ng", "espeak"] {
+            let mut espeak = Command::new(program);
+            espeak.args(["-s", &rate, "--stdin"]);
+            if let Some(voice) = &config.voice {
+                espeak.args(["-v", voice]);
+            }
+            synthesizers.push(espeak);
+        }
+        let mut spd_say = Command::new("spd-say");
+        spd_say.args(["--wait", "--pipe-mode"]);
+        synthesizers.push(spd_say);
+    }
+
+    for mut synthesizer in synthesizers {
+        let program = synthesizer.get_program().to_string_lossy().into_owned();
+        let mut child = match synthesizer
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                debug!("{} unavailable: {}", program, e);
+                continue;
+            }
+        };
+        child
+            .stdin
+            .take()
+            .with_context(|| format!("{} stdin unavailable", program))?
+            .write_all(text.as_bytes())?;
+        let status = child.wait()?;
+        if !status.success() {
+            bail!("{} exited with {}", program, status);
+        }
+        return Ok(());
+    }
+    bail!("No speech synthesizer found (install espeak-ng or speech-dispatcher, or set tts.piper_model)")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(ms: i64) -> DateTime<Utc> {
+        DateTime::<Utc>::UNIX_EPOCH + chrono::Duration::milliseconds(ms)
+    }
+
+    #[test]
+    fn test_disabled_speaker() {
+        assert!(Speaker::new(&TtsConfig::default()).is_none());
+    }
+
+    #[test]
+    fn test_overlaps_utterance_and_echo_tail() {
+        let speaker = Speaker::new(&TtsConfig {
+            enabled: true,
+            ..TtsConfig::default()
+        })
+        .unwrap();
+        assert!(!speaker.overlaps(at(0), at(1_000)));
+
+        *speaker.utterance.lock().unwrap() = Some(Utterance {
+            id: 0,
+            start: at(10_000),
+            end: None,
+        });
+        // Still speaking: anything from the start on is suspect
+        assert!(speaker.overlaps(at(12_000), at(13_000)));
+        assert!(!speaker.overlaps(at(8_000), at(9_000)));
+
+        speaker.utterance.lock().unwrap().as_mut().unwrap().end = Some(at(12_000));
+        assert!(speaker.overlaps(at(12_300), at(14_000)));
+        assert!(!speaker.overlaps(at(13_000), at(14_000)));
+    }
+}
@@ -0,0 +1,119 @@
+//! Batch transcription of audio files dropped into `watch_dirs`
+//!
+//! New WAV/MP3/FLAC files are queued as background jobs (see [`crate::jobs`])
+//! once they have finished arriving. The job table keeps a file from being
+//! transcribed twice, so files already handled before a restart are skipped.
+
+use anyhow::{Context, Result};
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+use crate::config::DaemonConfig;
+use crate::jobs::JobQueue;
+
+const AUDIO_EXTENSIONS: [&str; 3] = ["wav", "mp3", "flac"];
+
+/// Quiet time before a file counts as completely written (copies and
+/// downloads arrive as a series of modify events)
+const SETTLE_TIME: Duration = Duration::from_secs(2);
+
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Whether `path` looks like audio the job queue can transcribe
+pub fn is_audio_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| AUDIO_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// Start watching `config.watch_dirs` (`None` when none are configured)
+///
+/// The returned watcher must be kept alive for new files to be noticed.
+pub fn spawn(config: &DaemonConfig, jobs: JobQueue) -> Result<Option<RecommendedWatcher>> {
+    if config.watch_dirs.is_empty() {
+        return Ok(None);
+    }
+
+    let (paths_tx, paths_rx) = mpsc::unbounded_channel();
+    let events_tx = paths_tx.clone();
+    let mut watcher =
+        notify::recommended_watcher(move |res: Result<Event, notify::Error>| match res {
+            Ok(event) => {
+                if event.kind.is_create() || event.kind.is_modify() {
+                    for path in event.paths.into_iter().filter(|p| is_audio_file(p)) {
+                        let _ = events_tx.send(path);
+                    }
+                }
+            }
+            Err(e) => error!("Watch folder error: {}", e),
+        })?;
+
+    for dir in &config.watch_dirs {
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch {}", dir.display()))?;
+
+        // Files that arrived while the daemon was not running (the job
+        // table skips any that were already transcribed)
+        for entry in std::fs::read_dir(dir)?.flatten() {
+            let path = entry.path();
+            if is_audio_file(&path) && !path.with_extension("txt").exists() {
+                let _ = paths_tx.send(path);
+            }
+        }
+        info!("📂 Watching {} for audio files", dir.display());
+    }
+
+    tokio::spawn(queue_settled(paths_rx, jobs));
+
+    Ok(Some(watcher))
+}
+
+/// Queue each file once no event has been seen for it for `SETTLE_TIME`
+async fn queue_settled(mut paths_rx: mpsc::UnboundedReceiver<PathBuf>, jobs: JobQueue) {
+    // Paths by the time of their latest event
+    let mut settling: HashMap<PathBuf, Instant> = HashMap::new();
+    let mut poll = tokio::time::interval(POLL_INTERVAL);
+
+    loop {
+        tokio::select! {
+            path = paths_rx.recv() => match path {
+                Some(path) => {
+                    settling.insert(path, Instant::now());
+                }
+                None => return,
+            },
+            _ = poll.tick() => {
+                let settled: Vec<PathBuf> = settling
+                    .iter()
+                    .filter(|(_, seen)| seen.elapsed() >= SETTLE_TIME)
+                    .map(|(path, _)| path.clone())
+                    .collect();
+                for path in settled {
+                    settling.remove(&path);
+                    if let Err(e) = jobs.enqueue(&path).await {
+                        warn!("Failed to queue {}: {:#}", path.display(), e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_audio_file() {
+        assert!(is_audio_file(Path::new("/audio/interview.wav")));
+        assert!(is_audio_file(Path::new("/audio/Memo.MP3")));
+        assert!(is_audio_file(Path::new("lecture.flac")));
+        assert!(!is_audio_file(Path::new("/audio/interview.txt")));
+        assert!(!is_audio_file(Path::new("/audio/wav")));
+    }
+}
@@ -0,0 +1,132 @@
+//! Weekly summary digest scheduling
+//!
+//! Once per ISO week the daemon renders a Markdown digest of the previous
+//! week (totals, best sessions, trends, context-learning topics and newly
+//! learned corrections) into `<data dir>/swictation/digests/` and announces
+//! it to UI clients with a `weekly_summary_ready` event.
+
+use anyhow::{Context, Result};
+use chrono::{Duration, Utc};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use swictation_broadcaster::MetricsBroadcaster;
+use swictation_context_learning::ContextModel;
+use swictation_metrics::{DigestCorrection, MetricsDatabase, WeeklyDigest};
+use tracing::{info, warn};
+
+use crate::corrections::CorrectionEngine;
+
+/// How often the scheduler checks whether a new digest is due
+const CHECK_INTERVAL_SECS: u64 = 60 * 60;
+
+/// Number of context-learning topics listed in the digest
+const TOP_TOPICS: usize = 5;
+
+/// Directory holding rendered digests
+pub fn digests_dir() -> PathBuf {
+    swictation_paths::data_dir().join("digests")
+}
+
+/// Topic names from the saved context model, most frequent first
+fn load_top_topics(model_path: &Path) -> Vec<String> {
+    let model: ContextModel = match std::fs::read_to_string(model_path)
+        .ok()
+        .and_then(|s| serde_json::from_str(&s).ok())
+    {
+        Some(model) => model,
+        None => return Vec::new(),
+    };
+
+    let mut topics = model.topics;
+    topics.sort_by(|a, b| b.segment_count.cmp(&a.segment_count));
+    topics
+        .into_iter()
+        .take(TOP_TOPICS)
+        .map(|t| t.name)
+        .collect()
+}
+
+/// Corrections learned within the digest's period
+fn load_new_corrections(digest: &WeeklyDigest) -> Vec<DigestCorrection> {
+    let engine = CorrectionEngine::new(swictation_paths::config_dir(), 0.3);
+    match engine.get_all() {
+        Ok(all) => all
+            .into_iter()
+            .filter(|c| c.learned_at >= digest.period_start && c.learned_at < digest.period_end)
+            .map(|c| DigestCorrection {
+                original: c.original,
+                corrected: c.corrected,
+            })
+            .collect(),
+        Err(e) => {
+            warn!("Failed to load corrections for weekly summary: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Render the digest for the most recently completed week
+///
+/// Returns the digest's path, and the digest when it was newly written. An
+/// existing digest is never overwritten or regenerated, so repeated calls
+/// are cheap.
+pub fn ensure_previous_week() -> Result<(PathBuf, Option<WeeklyDigest>)> {
+    let this_week = WeeklyDigest::week_start(Utc::now().date_naive());
+    let period_start = this_week - Duration::days(7);
+    let week = WeeklyDigest::week_label(period_start);
+    let path = digests_dir().join(format!("{}.md", week));
+    if path.exists() {
+        return Ok((path, None));
+    }
+
+    let data_dir = swictation_paths::data_dir();
+    let db = MetricsDatabase::new(crate::pipeline::metrics_db_path())
+        .context("Failed to open metrics database")?;
+    let mut digest = WeeklyDigest::generate(&db, period_start)?;
+    digest.top_topics = load_top_topics(&data_dir.join("context-model.json"));
+    digest.new_corrections = load_new_corrections(&digest);
+
+    std::fs::create_dir_all(digests_dir()).context("Failed to create digests directory")?;
+    std::fs::write(&path, digest.to_markdown()).context("Failed to write weekly summary")?;
+    info!("📰 Weekly summary {} written to {}", week, path.display());
+
+    Ok((path, Some(digest)))
+}
+
+/// Spawn the background task that writes a digest once per week
+pub fn spawn_scheduler(broadcaster: Arc<MetricsBroadcaster>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(std::time::Duration::from_secs(CHECK_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+
+            match tokio::task::spawn_blocking(ensure_previous_week).await {
+                Ok(Ok((path, Some(digest)))) => {
+                    broadcaster
+                        .broadcast_weekly_summary(&digest.week, &path)
+                        .await;
+                }
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => warn!("Failed to generate weekly summary: {:#}", e),
+                Err(e) => warn!("Weekly summary task panicked: {}", e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_topics_missing_model() {
+        let topics = load_top_topics(Path::new("/nonexistent/context-model.json"));
+        assert!(topics.is_empty());
+    }
+
+    #[test]
+    fn test_digests_dir_under_data_dir() {
+        assert!(digests_dir().ends_with("swictation/digests"));
+    }
+}
@@ -0,0 +1,306 @@
+//! Sensitive-word filter for dictated text (screen-share safety)
+//!
+//! Words from the active profile are masked ("d***"), dropped, or allowed
+//! through before the text is typed. Matching is case-insensitive on whole
+//! words, so "class" never trips a filter for "ass", and phrases match
+//! across word boundaries.
+//! Saying "filter off" bypasses the filter until "filter on" or the next
+//! recording, and "switch profile" moves on to the next profile.
+
+use anyhow::{bail, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+
+use crate::config::{FilterAction, WordFilterConfig};
+
+/// Built-in list, used only by profiles with `use_default_list = true`
+const DEFAULT_WORDS: &[&str] = &[
+    "asshole", "bastard", "bitch", "bullshit", "crap", "damn", "dick", "fuck", "fucked", "fucking",
+    "goddamn", "piss", "shit", "shitty",
+];
+
+/// Word list of the profile in use
+#[derive(Debug)]
+struct ActiveProfile {
+    name: String,
+    /// Filtered phrases as lowercase words, longest first (none for an
+    /// `Allow` profile)
+    phrases: Vec<Vec<String>>,
+    /// Drop matches instead of masking them
+    drop: bool,
+}
+
+impl ActiveProfile {
+    /// Profile `name` of `config` (filters nothing when disabled)
+    fn load(config: &WordFilterConfig, name: &str) -> Self {
+        let profile = config
+            .enabled
+            .then(|| config.profiles.get(name))
+            .flatten()
+            .filter(|profile| profile.action != FilterAction::Allow);
+
+        let mut phrases: Vec<Vec<String>> = Vec::new();
+        if let Some(profile) = profile {
+            let defaults = DEFAULT_WORDS
+                .iter()
+                .copied()
+                .filter(|_| profile.use_default_list);
+            for entry in profile.words.iter().map(String::as_str).chain(defaults) {
+                let words = words_of(entry);
+                if !words.is_empty() && !phrases.contains(&words) {
+                    phrases.push(words);
+                }
+            }
+        }
+        phrases.sort_by_key(|words| std::cmp::Reverse(words.len()));
+
+        Self {
+            name: name.to_string(),
+            phrases,
+            drop: profile.is_some_and(|p| p.action == FilterAction::Drop),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WordFilter {
+    config: Arc<WordFilterConfig>,
+    /// Shared by every clone, so a profile switch reaches the STT thread
+    active: Arc<RwLock<ActiveProfile>>,
+    bypass: Arc<AtomicBool>,
+}
+
+impl WordFilter {
+    /// Filter for the configured profile (filters nothing when disabled)
+    pub fn new(config: &WordFilterConfig) -> Self {
+        Self {
+            active: Arc::new(RwLock::new(ActiveProfile::load(config, &config.profile))),
+            config: Arc::new(config.clone()),
+            bypass: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.active.read().unwrap().phrases.is_empty() && !self.bypass.load(Ordering::Relaxed)
+    }
+
+    /// Switch to the next profile in name order (wrapping), returning its name
+    pub fn next_profile(&self) -> Result<String> {
+        if !self.config.enabled {
+            bail!("The word filter is disabled");
+        }
+        if self.config.profiles.len() < 2 {
+            bail!("Only one filter profile is configured");
+        }
+
+        let mut active = self.active.write().unwrap();
+        let names: Vec<&String> = self.config.profiles.keys().collect();
+        let next = names
+            .iter()
+            .position(|name| **name == active.name)
+            .map_or(0, |i| (i + 1) % names.len());
+        *active = ActiveProfile::load(&self.config, names[next]);
+        Ok(active.name.clone())
+    }
+
+    /// Stop (or resume) filtering; shared by every clone of this filter
+    pub fn set_bypass(&self, bypass: bool) {
+        self.bypass.store(bypass, Ordering::Relaxed);
+    }
+
+    /// Filtered text and the number of words masked or dropped
+    pub fn apply(&self, text: &str) -> (String, usize) {
+        if !self.is_active() {
+            return (text.to_string(), 0);
+        }
+
+        let active = self.active.read().unwrap();
+        let tokens = tokenize(text);
+        let keys: Vec<String> = tokens.iter().map(|t| key(&text[t.start..t.end])).collect();
+        let mut hit = vec![false; tokens.len()];
+        let mut i = 0;
+        while i < keys.len() {
+            let matched = active
+                .phrases
+                .iter()
+                .find(|phrase| keys[i..].starts_with(phrase))
+                .map_or(0, |phrase| phrase.len());
+            hit[i..i + matched].iter_mut().for_each(|h| *h = true);
+            i += matched.max(1);
+        }
+
+        let mut out = String::new();
+        let mut end = 0;
+        // Whitespace before a dropped word replaces the whitespace after it
+        let mut dropped_gap: Option<&str> = None;
+        for (token, &hit) in tokens.iter().zip(&hit) {
+            let gap = &text[end..token.start];
+            end = token.end;
+            let word = &text[token.start..token.end];
+            match (hit, active.drop) {
+                (false, _) => {
+                    out.push_str(dropped_gap.take().unwrap_or(gap));
+                    out.push_str(word);
+                }
+                (true, false) => {
+                    out.push_str(gap);
+                    out.push_str(&mask(word));
+                }
+                // Trailing punctuation stays with the previous word: "what the."
+                (true, true) => {
+                    dropped_gap.get_or_insert(gap);
+                    if !out.is_empty() {
+                        let letters = word.trim_end_matches(|c: char| !c.is_alphanumeric());
+                        out.push_str(&word[letters.len()..]);
+                    }
+                }
+            }
+        }
+        out.push_str(&text[end..]);
+
+        (out, hit.iter().filter(|&&h| h).count())
+    }
+}
+
+/// Byte range of a whitespace-separated word, punctuation included
+struct Token {
+    start: usize,
+    end: usize,
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                tokens.push(Token { start: s, end: i });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+/// Lowercase word without surrounding punctuation ("Don't," → "don't")
+fn key(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+        .chars()
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn words_of(entry: &str) -> Vec<String> {
+    entry
+        .split_whitespace()
+        .map(key)
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Star out every letter after the first, keeping surrounding punctuation
+fn mask(word: &str) -> String {
+    let mut seen_letter = false;
+    word.chars()
+        .map(|c| {
+            if !c.is_alphanumeric() {
+                c
+            } else if seen_letter {
+                '*'
+            } else {
+                seen_letter = true;
+                c
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FilterProfile;
+
+    fn filter(words: &[&str], action: FilterAction, use_default_list: bool) -> WordFilter {
+        let mut config = WordFilterConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        config.profiles.insert(
+            config.profile.clone(),
+            FilterProfile {
+                words: words.iter().map(|w| w.to_string()).collect(),
+                action,
+                use_default_list,
+            },
+        );
+        WordFilter::new(&config)
+    }
+
+    #[test]
+    fn test_mask_whole_words_case_insensitive() {
+        let f = filter(&["project falcon", "acme"], FilterAction::Mask, false);
+        let (text, count) = f.apply("Ask ACME, not Acmes, about Project Falcon.");
+        assert_eq!(text, "Ask A***, not Acmes, about P****** F*****.");
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn test_drop_keeps_punctuation_and_spacing() {
+        let f = filter(&["damn"], FilterAction::Drop, false);
+        assert_eq!(f.apply("Damn it broke damn.").0, "it broke.");
+        assert_eq!(f.apply("one\ndamn two").0, "one\ntwo");
+    }
+
+    #[test]
+    fn test_default_list_is_opt_in_and_bypass() {
+        assert_eq!(filter(&[], FilterAction::Mask, false).apply("shit").1, 0);
+
+        let f = filter(&[], FilterAction::Mask, true);
+        assert_eq!(f.apply("oh shit").0, "oh s***");
+
+        f.clone().set_bypass(true);
+        assert_eq!(f.apply("oh shit"), ("oh shit".to_string(), 0));
+    }
+
+    #[test]
+    fn test_next_profile_is_shared_by_clones() {
+        let mut config = (*filter(&["acme"], FilterAction::Mask, false).config).clone();
+        config.profiles.insert(
+            "stream".to_string(),
+            FilterProfile {
+                words: vec!["falcon".to_string()],
+                action: FilterAction::Drop,
+                use_default_list: false,
+            },
+        );
+        let f = WordFilter::new(&config);
+        let stt_thread = f.clone();
+
+        assert_eq!(f.next_profile().unwrap(), "stream");
+        assert_eq!(stt_thread.apply("acme falcon").0, "acme");
+        assert_eq!(f.next_profile().unwrap(), config.profile);
+        assert_eq!(stt_thread.apply("acme falcon").0, "a*** falcon");
+
+        assert!(WordFilter::new(&WordFilterConfig::default())
+            .next_profile()
+            .is_err());
+    }
+
+    #[test]
+    fn test_allow_profile_filters_nothing() {
+        let f = filter(&["damn"], FilterAction::Allow, true);
+        assert!(!f.is_active());
+        assert_eq!(f.apply("damn it"), ("damn it".to_string(), 0));
+
+        let profile: FilterProfile = toml::from_str("action = \"remove\"").unwrap();
+        assert_eq!(profile.action, FilterAction::Drop);
+    }
+
+    #[test]
+    fn test_disabled_filters_nothing() {
+        let f = WordFilter::new(&WordFilterConfig::default());
+        assert!(!f.is_active());
+    }
+}
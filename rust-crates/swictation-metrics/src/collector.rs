@@ -5,13 +5,21 @@
 use anyhow::Result;
 use chrono::Utc;
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use sysinfo::{Pid, System};
-use tracing::info;
+use tracing::{debug, info, warn};
 
 use crate::database::MetricsDatabase;
+use crate::latency::{LatencyBudgets, LatencyWarning};
 use crate::memory::MemoryMonitor;
 use crate::models::{RealtimeMetrics, SegmentMetrics, SessionMetrics};
+use crate::percentile::PercentileAggregator;
+use crate::storage::{is_storage_full, StorageBuffer, StorageStatus, DEFAULT_RETRY_INTERVAL};
+
+/// Session ID of a private session, which never gets a database row
+///
+/// Database IDs start at 1 and provisional ones are negative, so 0 is free.
+pub const PRIVATE_SESSION_ID: i64 = 0;
 
 /// Orchestrates metrics collection for Swictation daemon
 pub struct MetricsCollector {
@@ -21,7 +29,7 @@ pub struct MetricsCollector {
 
     // Warning configuration
     warnings_enabled: bool,
-    high_latency_threshold_ms: f64,
+    latency_budgets: LatencyBudgets,
     gpu_memory_threshold_percent: f64,
 
     // Current session tracking
@@ -36,6 +44,10 @@ pub struct MetricsCollector {
     // System monitoring
     system: Arc<Mutex<System>>,
     memory_monitor: Arc<Mutex<Option<MemoryMonitor>>>,
+
+    // Writes held in RAM while the database's disk is full
+    storage: Arc<Mutex<StorageBuffer>>,
+    storage_retry_interval: Duration,
 }
 
 impl MetricsCollector {
@@ -59,7 +71,10 @@ impl MetricsCollector {
             typing_baseline_wpm,
             store_transcription_text,
             warnings_enabled,
-            high_latency_threshold_ms,
+            latency_budgets: LatencyBudgets {
+                total_ms: high_latency_threshold_ms,
+                ..Default::default()
+            },
             gpu_memory_threshold_percent,
             current_session: Arc::new(Mutex::new(None)),
             session_segments: Arc::new(Mutex::new(Vec::new())),
@@ -68,9 +83,33 @@ impl MetricsCollector {
             realtime: Arc::new(Mutex::new(RealtimeMetrics::default())),
             system: Arc::new(Mutex::new(system)),
             memory_monitor: Arc::new(Mutex::new(None)),
+            storage: Arc::new(Mutex::new(StorageBuffer::default())),
+            storage_retry_interval: DEFAULT_RETRY_INTERVAL,
         })
     }
 
+    /// Replace the per-stage latency budgets (including the total)
+    pub fn with_latency_budgets(mut self, budgets: LatencyBudgets) -> Self {
+        self.latency_budgets = budgets;
+        self
+    }
+
+    /// Replace how often buffered writes are retried on a full disk
+    pub fn with_storage_retry_interval(mut self, interval: Duration) -> Self {
+        self.storage_retry_interval = interval;
+        self
+    }
+
+    /// Whether segment text is written to the database
+    pub fn stores_transcription_text(&self) -> bool {
+        self.store_transcription_text
+    }
+
+    /// Underlying metrics database
+    pub fn database(&self) -> Arc<MetricsDatabase> {
+        self.db.clone()
+    }
+
     /// Enable GPU monitoring
     pub fn enable_gpu_monitoring(&self, _provider: &str) {
         match MemoryMonitor::new() {
@@ -86,16 +125,56 @@ impl MetricsCollector {
 
     /// Start a new metrics session
     pub fn start_session(&self) -> Result<i64> {
-        let now = Utc::now();
-        let mut session = SessionMetrics {
-            session_start: Some(now),
+        self.retry_storage();
+        let mut session = self.new_session();
+
+        // Insert into database (get ID), or hold it with a provisional ID
+        let session_id = match self.write(|db| db.insert_session(&session))? {
+            Some(session_id) => session_id,
+            None => self.storage.lock().unwrap().push_session(session.clone()),
+        };
+        session.session_id = Some(session_id);
+        self.begin_session(session);
+
+        info!("🎤 Recording started (Session #{})", session_id);
+
+        Ok(session_id)
+    }
+
+    /// Start a session kept in RAM only
+    ///
+    /// Nothing from it reaches the database: no session row, no segments and
+    /// no lifetime stats. Its ID is [`PRIVATE_SESSION_ID`], and its segments
+    /// and last transcription are wiped when it ends.
+    pub fn start_private_session(&self) -> i64 {
+        let mut session = self.new_session();
+        session.session_id = Some(PRIVATE_SESSION_ID);
+        self.begin_session(session);
+
+        info!("🕶️  Private recording started (nothing is saved)");
+
+        PRIVATE_SESSION_ID
+    }
+
+    /// Whether the session in progress is private
+    pub fn is_private_session(&self) -> bool {
+        self.current_session
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|session| session.session_id == Some(PRIVATE_SESSION_ID))
+    }
+
+    fn new_session(&self) -> SessionMetrics {
+        SessionMetrics {
+            session_start: Some(Utc::now()),
             typing_speed_equivalent: self.typing_baseline_wpm,
             ..Default::default()
-        };
+        }
+    }
 
-        // Insert into database (get ID)
-        let session_id = self.db.insert_session(&session)?;
-        session.session_id = Some(session_id);
+    fn begin_session(&self, session: SessionMetrics) {
+        let session_id = session.session_id;
 
         // Update state
         *self.current_session.lock().unwrap() = Some(session);
@@ -106,19 +185,16 @@ impl MetricsCollector {
         // Update realtime metrics
         {
             let mut realtime = self.realtime.lock().unwrap();
-            realtime.current_session_id = Some(session_id);
+            realtime.current_session_id = session_id;
             realtime.segments_this_session = 0;
             realtime.words_this_session = 0;
             realtime.wpm_this_session = 0.0;
         }
-
-        info!("🎤 Recording started (Session #{})", session_id);
-
-        Ok(session_id)
     }
 
     /// End current session and finalize metrics
     pub fn end_session(&self) -> Result<SessionMetrics> {
+        self.retry_storage();
         let session_id = {
             let current = self.current_session.lock().unwrap();
             current
@@ -144,13 +220,12 @@ impl MetricsCollector {
         let segments = self.session_segments.lock().unwrap();
         let (avg_latency, median_latency, p95_latency, avg_words, avg_duration) =
             if !segments.is_empty() {
-                let mut latencies: Vec<f64> = segments.iter().map(|s| s.total_latency_ms).collect();
-                latencies.sort_by(|a, b| a.partial_cmp(b).unwrap());
-
-                let avg_lat = latencies.iter().sum::<f64>() / latencies.len() as f64;
-                let median_lat = latencies[latencies.len() / 2];
-                let p95_idx = (latencies.len() as f64 * 0.95) as usize;
-                let p95_lat = latencies[p95_idx.min(latencies.len() - 1)];
+                let avg_lat = segments.iter().map(|s| s.total_latency_ms).sum::<f64>()
+                    / segments.len() as f64;
+                let mut latencies: PercentileAggregator =
+                    segments.iter().map(|s| s.total_latency_ms).collect();
+                let percentiles = latencies.latency_percentiles().unwrap_or_default();
+                let (median_lat, p95_lat) = (percentiles.median_ms, percentiles.p95_ms);
 
                 let words: Vec<i32> = segments.iter().map(|s| s.words).collect();
                 let avg_w = words.iter().sum::<i32>() as f64 / words.len() as f64;
@@ -162,6 +237,7 @@ impl MetricsCollector {
             } else {
                 (0.0, 0.0, 0.0, 0.0, 0.0)
             };
+        drop(segments);
 
         // Update session metrics
         let mut session = {
@@ -182,11 +258,31 @@ impl MetricsCollector {
         // Calculate WPM
         session.calculate_wpm();
 
+        if session_id == PRIVATE_SESSION_ID {
+            self.session_segments.lock().unwrap().clear();
+            self.realtime.lock().unwrap().last_transcription.clear();
+            info!(
+                "🕶️  Private session complete: {} words, nothing saved",
+                session.words_dictated
+            );
+            return Ok(session);
+        }
+
         // Update database
-        self.db.update_session(session_id, &session)?;
+        if self
+            .write(|db| db.update_session(session_id, &session))?
+            .is_none()
+        {
+            self.storage
+                .lock()
+                .unwrap()
+                .push_session_update(session_id, session.clone());
+        }
 
         // Recalculate lifetime stats after session ends
-        self.db.recalculate_lifetime_stats()?;
+        if self.write(|db| db.recalculate_lifetime_stats())?.is_none() {
+            self.storage.lock().unwrap().mark_lifetime_stale();
+        }
 
         info!(
             "📊 Session #{} complete: {} words in {:.1}s ({:.1} WPM)",
@@ -196,8 +292,13 @@ impl MetricsCollector {
         Ok(session)
     }
 
-    /// Record a segment
-    pub fn add_segment(&self, segment: SegmentMetrics) -> Result<()> {
+    /// Record a segment, returning its database ID
+    ///
+    /// The ID is `None` while the database's disk is full: the segment is
+    /// held in RAM and written once there is space (see [`crate::storage`]),
+    /// and always in a private session, whose segments are never written.
+    pub fn add_segment(&self, segment: SegmentMetrics) -> Result<Option<i64>> {
+        self.retry_storage();
         let session_id = {
             let current = self.current_session.lock().unwrap();
             current
@@ -211,9 +312,23 @@ impl MetricsCollector {
         seg.session_id = Some(session_id);
         seg.timestamp = Some(Utc::now());
 
-        // Insert into database
-        self.db
-            .insert_segment(&seg, self.store_transcription_text)?;
+        // Insert into database (private segments only count in RAM)
+        let store_text = self.store_transcription_text;
+        if session_id != PRIVATE_SESSION_ID {
+            match self.write(|db| db.insert_segment_checked(&seg, store_text))? {
+                Some((segment_id, true)) => seg.segment_id = Some(segment_id),
+                // Already counted when first recorded
+                Some((segment_id, false)) => {
+                    warn!(
+                        "Segment #{} recorded twice, not counting it again",
+                        segment_id
+                    );
+                    return Ok(Some(segment_id));
+                }
+                None => self.storage.lock().unwrap().push_segment(seg.clone()),
+            }
+        }
+        let segment_id = seg.segment_id;
 
         // Update session aggregates
         {
@@ -224,6 +339,7 @@ impl MetricsCollector {
                 session.segments_processed += 1;
                 session.transformations_count += seg.transformations_count;
                 session.keyboard_actions_count += seg.keyboard_actions_count;
+                session.masked_words_count += seg.masked_words;
             }
         }
 
@@ -251,12 +367,96 @@ impl MetricsCollector {
             }
         }
 
-        // Check for warnings
-        if self.warnings_enabled && seg.total_latency_ms > self.high_latency_threshold_ms {
-            info!("⚠️  High latency detected: {:.1}ms", seg.total_latency_ms);
+        Ok(segment_id)
+    }
+
+    /// Check a segment against the latency budgets
+    ///
+    /// Over-budget segments are counted in the lifetime `high_latency_warnings`
+    /// and returned with their stage breakdown so the caller can report them.
+    pub fn check_latency(&self, segment: &SegmentMetrics) -> Option<LatencyWarning> {
+        if !self.warnings_enabled {
+            return None;
+        }
+
+        let warning = self.latency_budgets.check(segment)?;
+        info!("⚠️  High latency detected: {}", warning.summary());
+
+        if self.is_private_session() {
+            return Some(warning);
+        }
+        if let Err(e) = self.write(|db| db.increment_high_latency_warnings()) {
+            warn!("Failed to record high latency warning: {}", e);
+        }
+
+        Some(warning)
+    }
+
+    /// Run a database write, or skip it while the disk is full
+    ///
+    /// Returns `None` when the write didn't happen, including when it is
+    /// the one that found the disk full; the caller buffers what it wrote.
+    fn write<T>(&self, write: impl FnOnce(&MetricsDatabase) -> Result<T>) -> Result<Option<T>> {
+        let mut storage = self.storage.lock().unwrap();
+        if storage.is_full() {
+            return Ok(None);
+        }
+        match write(&self.db) {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if is_storage_full(&e) => {
+                warn!(
+                    "💾 Metrics disk is full - keeping metrics in RAM, retrying every {}s",
+                    self.storage_retry_interval.as_secs()
+                );
+                storage.mark_full();
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Write buffered metrics out if a retry is due, returning the storage status
+    ///
+    /// Sessions and segments retry first so writes stay in order, but a
+    /// daemon that sits idle on a full disk should call this periodically.
+    pub fn retry_storage(&self) -> StorageStatus {
+        let (status, session_id) = {
+            let mut storage = self.storage.lock().unwrap();
+            if !storage.is_full() || !storage.retry_due(self.storage_retry_interval) {
+                return storage.status();
+            }
+            self.flush_storage(&mut storage);
+
+            // The session in progress may have been started while buffering
+            let mut current = self.current_session.lock().unwrap();
+            let session_id = current.as_mut().and_then(|session| {
+                session.session_id = session.session_id.map(|id| storage.resolve(id));
+                session.session_id
+            });
+            (storage.status(), session_id)
+        };
+        if session_id.is_some() {
+            self.realtime.lock().unwrap().current_session_id = session_id;
         }
+        status
+    }
 
-        Ok(())
+    /// Current storage status (see [`crate::storage`])
+    pub fn storage_status(&self) -> StorageStatus {
+        self.storage.lock().unwrap().status()
+    }
+
+    /// Write the buffer out, logging how it went
+    fn flush_storage(&self, storage: &mut StorageBuffer) {
+        let buffered = storage.status().buffered_segments;
+        match storage.flush(&self.db, self.store_transcription_text) {
+            Ok(()) => info!(
+                "💾 Metrics disk has space again - wrote {} buffered segments",
+                buffered
+            ),
+            Err(e) if is_storage_full(&e) => debug!("Metrics disk still full: {}", e),
+            Err(e) => warn!("Failed to write buffered metrics: {}", e),
+        }
     }
 
     /// Update GPU memory metrics
@@ -400,4 +600,144 @@ mod tests {
         assert_eq!(session.words_dictated, 10);
         assert!(!collector.has_active_session());
     }
+
+    #[test]
+    fn test_check_latency_counts_warnings() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db_path = tmp_dir.path().join("test_metrics.db");
+
+        let collector =
+            MetricsCollector::new(db_path.to_str().unwrap(), 40.0, false, true, 1000.0, 80.0)
+                .unwrap()
+                .with_latency_budgets(LatencyBudgets {
+                    stt_ms: 300.0,
+                    ..Default::default()
+                });
+
+        let fast = SegmentMetrics {
+            stt_latency_ms: 200.0,
+            total_latency_ms: 210.0,
+            ..Default::default()
+        };
+        assert!(collector.check_latency(&fast).is_none());
+
+        let slow_stt = SegmentMetrics {
+            stt_latency_ms: 450.0,
+            total_latency_ms: 460.0,
+            ..Default::default()
+        };
+        let warning = collector.check_latency(&slow_stt).unwrap();
+        assert_eq!(warning.exceeded, vec![crate::latency::LatencyStage::Stt]);
+
+        let stats = collector.database().get_lifetime_stats().unwrap();
+        assert_eq!(stats.high_latency_warnings, 1);
+    }
+
+    #[test]
+    fn test_private_session_writes_nothing() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db_path = tmp_dir.path().join("test_metrics.db");
+
+        let collector =
+            MetricsCollector::new(db_path.to_str().unwrap(), 40.0, true, true, 100.0, 80.0)
+                .unwrap();
+        let db = collector.database();
+
+        assert_eq!(collector.start_private_session(), PRIVATE_SESSION_ID);
+        assert!(collector.is_private_session());
+        let segment = SegmentMetrics {
+            words: 3,
+            duration_s: 1.0,
+            total_latency_ms: 500.0,
+            text: "my secret words".to_string(),
+            ..Default::default()
+        };
+        assert!(collector.check_latency(&segment).is_some());
+        assert_eq!(collector.add_segment(segment).unwrap(), None);
+        assert_eq!(
+            collector.get_realtime_metrics().last_transcription,
+            "my secret words"
+        );
+
+        let session = collector.end_session().unwrap();
+        assert_eq!(session.words_dictated, 3);
+        assert!(collector
+            .get_realtime_metrics()
+            .last_transcription
+            .is_empty());
+        assert!(collector.session_segments.lock().unwrap().is_empty());
+
+        let stats = db.get_lifetime_stats().unwrap();
+        assert_eq!(stats.total_sessions, 0);
+        assert_eq!(stats.high_latency_warnings, 0);
+        assert!(db
+            .get_session_segments(PRIVATE_SESSION_ID)
+            .unwrap()
+            .is_empty());
+
+        // The next ordinary session is recorded as usual
+        assert!(collector.start_session().unwrap() > 0);
+        assert!(!collector.is_private_session());
+    }
+
+    #[test]
+    fn test_full_disk_buffers_until_space_returns() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db_path = tmp_dir.path().join("test_metrics.db");
+
+        let collector =
+            MetricsCollector::new(db_path.to_str().unwrap(), 40.0, true, true, 1000.0, 80.0)
+                .unwrap()
+                .with_storage_retry_interval(Duration::ZERO);
+        let db = collector.database();
+        let segment = |i: usize| SegmentMetrics {
+            words: 2,
+            duration_s: 1.0,
+            total_latency_ms: 300.0,
+            text: format!("segment number {}", i),
+            ..Default::default()
+        };
+
+        // Fill the file's free pages until a segment has to be buffered
+        let first = collector.start_session().unwrap();
+        db.set_max_page_count(1).unwrap();
+        let mut added = 0;
+        while collector.add_segment(segment(added)).unwrap().is_some() {
+            added += 1;
+            assert!(added < 10_000, "database never filled up");
+        }
+        added += 1;
+        assert!(collector.storage_status().full);
+        collector.end_session().unwrap();
+
+        // A session started on the full disk gets a provisional ID
+        let second = collector.start_session().unwrap();
+        assert!(second < 0);
+        collector.add_segment(segment(added)).unwrap();
+        assert_eq!(
+            collector.retry_storage(),
+            StorageStatus {
+                full: true,
+                buffered_segments: 2,
+                dropped_segments: 0,
+            }
+        );
+
+        // Space returns: everything is written, and the open session gets its real ID
+        db.set_max_page_count(1_000_000).unwrap();
+        assert_eq!(collector.retry_storage(), StorageStatus::default());
+        let second = collector.get_realtime_metrics().current_session_id.unwrap();
+        assert!(second > first);
+        collector.add_segment(segment(added + 1)).unwrap().unwrap();
+        let session = collector.end_session().unwrap();
+        assert_eq!(session.session_id, Some(second));
+
+        assert_eq!(db.get_session_segments(first).unwrap().len(), added);
+        assert_eq!(db.get_session_segments(second).unwrap().len(), 2);
+        assert_eq!(
+            db.get_session(first).unwrap().unwrap().words_dictated,
+            added as i32 * 2
+        );
+        assert_eq!(db.get_lifetime_stats().unwrap().total_sessions, 2);
+    }
 }
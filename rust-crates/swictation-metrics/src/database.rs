@@ -4,11 +4,26 @@
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection, Row};
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
-use crate::models::{LifetimeMetrics, SegmentMetrics, SessionMetrics};
+use crate::dedupe::{text_content_hash, DedupeReport};
+use crate::integrity::{IntegrityReport, DANGLING_AFTER_S, MAX_PLAUSIBLE_WPM};
+use crate::latency::LatencyBreakdown;
+use crate::models::{
+    InferenceMetadata, JobPriority, JobStatus, LifetimeMetrics, PracticeSession, ProsodyMetrics,
+    SegmentAlternative, SegmentMetrics, SessionComparison, SessionMetrics, SinkDelivery, SinkStats,
+    TranscriptionJob,
+};
+use crate::percentile::{PercentileAggregator, PercentileBackfillReport};
+
+/// Days a deleted session stays in the trash before it is purged
+pub const TRASH_RETENTION_DAYS: u32 = 30;
 
 /// Type alias for complex database session query row
 type DbSessionRow = (
@@ -24,6 +39,122 @@ type DbSessionRow = (
     Option<i64>, // lowest_latency_session
 );
 
+/// Read the `audio_start` column (Unix seconds with microsecond precision)
+fn parse_audio_start(row: &Row) -> Option<DateTime<Utc>> {
+    let seconds: f64 = row.get::<_, Option<f64>>("audio_start").ok().flatten()?;
+    DateTime::from_timestamp_micros((seconds * 1_000_000.0).round() as i64)
+}
+
+/// Recompute a session's segment-derived totals from its segments
+///
+/// Active time is the summed segment duration, as the collector counts it
+/// live, and the session's duration is taken from its start and end times.
+fn reaggregate_session(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE sessions SET
+            duration_s = end_time - start_time,
+            active_time_s = (SELECT COALESCE(SUM(duration_s), 0)
+                             FROM segments WHERE session_id = ?1),
+            words_dictated = (SELECT COALESCE(SUM(words), 0)
+                              FROM segments WHERE session_id = ?1),
+            characters_typed = (SELECT COALESCE(SUM(characters), 0)
+                                FROM segments WHERE session_id = ?1),
+            segments_processed = (SELECT COUNT(*) FROM segments WHERE session_id = ?1),
+            transformations_count = (SELECT COALESCE(SUM(transformations_count), 0)
+                                     FROM segments WHERE session_id = ?1),
+            keyboard_actions_count = (SELECT COALESCE(SUM(keyboard_actions_count), 0)
+                                      FROM segments WHERE session_id = ?1),
+            masked_words_count = (SELECT COALESCE(SUM(masked_words), 0)
+                                  FROM segments WHERE session_id = ?1),
+            avg_latency_ms = (SELECT COALESCE(AVG(total_latency_ms), 0)
+                              FROM segments WHERE session_id = ?1),
+            avg_segment_words = (SELECT COALESCE(AVG(words), 0)
+                                 FROM segments WHERE session_id = ?1),
+            avg_segment_duration_s = (SELECT COALESCE(AVG(duration_s), 0)
+                                      FROM segments WHERE session_id = ?1)
+         WHERE id = ?1",
+        params![id],
+    )?;
+    conn.execute(
+        "UPDATE sessions SET
+            pause_time_s = MAX(duration_s - active_time_s, 0),
+            wpm = CASE WHEN active_time_s > 0 THEN words_dictated * 60.0 / active_time_s
+                       ELSE 0 END
+         WHERE id = ?1",
+        params![id],
+    )?;
+
+    let mut aggregator = PercentileAggregator::new();
+    let mut stmt = conn.prepare(
+        "SELECT total_latency_ms FROM segments
+         WHERE session_id = ?1 AND total_latency_ms IS NOT NULL",
+    )?;
+    let mut rows = stmt.query(params![id])?;
+    while let Some(row) = rows.next()? {
+        aggregator.add(row.get(0)?);
+    }
+    let percentiles = aggregator.latency_percentiles().unwrap_or_default();
+    conn.execute(
+        "UPDATE sessions SET median_latency_ms = ?2, p95_latency_ms = ?3 WHERE id = ?1",
+        params![id, percentiles.median_ms, percentiles.p95_ms],
+    )?;
+    Ok(())
+}
+
+/// Decode the optional JSON `inference_metadata` column of a segment row
+fn parse_inference(row: &Row) -> Option<InferenceMetadata> {
+    row.get::<_, Option<String>>("inference_metadata")
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+}
+
+/// N-best alternatives as deflate-compressed JSON (`None` when there are none)
+fn compress_nbest(nbest: &[String]) -> Result<Option<Vec<u8>>> {
+    if nbest.is_empty() {
+        return Ok(None);
+    }
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&serde_json::to_vec(nbest)?)?;
+    Ok(Some(encoder.finish()?))
+}
+
+fn decompress_nbest(blob: &[u8]) -> Result<Vec<String>> {
+    let mut json = Vec::new();
+    DeflateDecoder::new(blob)
+        .read_to_end(&mut json)
+        .context("Corrupt n-best data")?;
+    Ok(serde_json::from_slice(&json)?)
+}
+
+fn parse_prosody(row: &Row) -> Option<ProsodyMetrics> {
+    Some(ProsodyMetrics {
+        energy_dbfs: row.get::<_, Option<f64>>("prosody_energy_dbfs").ok()??,
+        pitch_std_semitones: row.get("prosody_pitch_std").ok()?,
+        speaking_rate_wpm: row
+            .get::<_, Option<f64>>("prosody_rate_wpm")
+            .ok()?
+            .unwrap_or(0.0),
+    })
+}
+
+fn parse_nbest(row: &Row) -> Vec<String> {
+    row.get::<_, Option<Vec<u8>>>("nbest")
+        .ok()
+        .flatten()
+        .and_then(|blob| decompress_nbest(&blob).ok())
+        .unwrap_or_default()
+}
+
+/// What [`MetricsDatabase::purge_trash`] removed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PurgedTrash {
+    /// Sessions deleted for good
+    pub sessions: usize,
+    /// Their segments, whose recordings are now orphaned
+    pub segment_ids: Vec<i64>,
+}
+
 /// Thread-safe SQLite database for metrics storage
 pub struct MetricsDatabase {
     db_path: PathBuf,
@@ -103,7 +234,8 @@ impl MetricsDatabase {
                 gpu_peak_mb REAL,
                 gpu_mean_mb REAL,
                 cpu_mean_percent REAL,
-                cpu_peak_percent REAL
+                cpu_peak_percent REAL,
+                masked_words_count INTEGER DEFAULT 0
             )",
             [],
         )?;
@@ -126,11 +258,77 @@ impl MetricsDatabase {
                 total_latency_ms REAL,
                 transformations_count INTEGER DEFAULT 0,
                 keyboard_actions_count INTEGER DEFAULT 0,
+                inference_metadata TEXT,
+                trimmed_silence_ms REAL DEFAULT 0,
+                start_sample INTEGER,
+                audio_start REAL,
+                nbest BLOB,
+                masked_words INTEGER DEFAULT 0,
+                content_hash TEXT,
+                prosody_pitch_std REAL,
+                prosody_energy_dbfs REAL,
+                prosody_rate_wpm REAL,
                 FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
             )",
             [],
         )?;
 
+        // Alternative transcriptions of segments (retries with other models)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS segment_alternatives (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                segment_id INTEGER NOT NULL,
+                created_at REAL NOT NULL,
+                text TEXT NOT NULL,
+                inference_metadata TEXT,
+                FOREIGN KEY (segment_id) REFERENCES segments(id) ON DELETE CASCADE
+            )",
+            [],
+        )?;
+
+        // Audio files transcribed in the background (one job per path)
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS transcription_jobs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                path TEXT NOT NULL UNIQUE,
+                status TEXT NOT NULL,
+                created_at REAL NOT NULL,
+                updated_at REAL NOT NULL,
+                duration_s REAL,
+                words INTEGER,
+                error TEXT
+            )",
+            [],
+        )?;
+
+        // Segments handed to output sinks (typing, notes, hooks), one row per sink
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS sink_deliveries (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id INTEGER,
+                timestamp REAL NOT NULL,
+                sink TEXT NOT NULL,
+                latency_ms REAL NOT NULL,
+                error TEXT
+            )",
+            [],
+        )?;
+
+        // Readalong runs scored for pronunciation practice
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS practice_sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                started_at REAL NOT NULL,
+                ended_at REAL NOT NULL,
+                script_words INTEGER NOT NULL,
+                words_read INTEGER NOT NULL,
+                words_matched INTEGER NOT NULL,
+                accuracy REAL NOT NULL,
+                pronunciation_score REAL NOT NULL
+            )",
+            [],
+        )?;
+
         // Lifetime stats table (single row)
         conn.execute(
             "CREATE TABLE IF NOT EXISTS lifetime_stats (
@@ -162,6 +360,23 @@ impl MetricsDatabase {
             [],
         )?;
 
+        // Columns added after the initial schema (existing databases need ALTER TABLE)
+        Self::ensure_column(&conn, "segments", "inference_metadata", "TEXT")?;
+        Self::ensure_column(&conn, "segments", "trimmed_silence_ms", "REAL DEFAULT 0")?;
+        Self::ensure_column(&conn, "segments", "start_sample", "INTEGER")?;
+        Self::ensure_column(&conn, "segments", "audio_start", "REAL")?;
+        Self::ensure_column(&conn, "segments", "nbest", "BLOB")?;
+        Self::ensure_column(&conn, "segments", "masked_words", "INTEGER DEFAULT 0")?;
+        Self::ensure_column(&conn, "segments", "content_hash", "TEXT")?;
+        Self::ensure_column(&conn, "segments", "prosody_pitch_std", "REAL")?;
+        Self::ensure_column(&conn, "segments", "prosody_energy_dbfs", "REAL")?;
+        Self::ensure_column(&conn, "segments", "prosody_rate_wpm", "REAL")?;
+        Self::ensure_column(&conn, "segments", "speaker", "INTEGER")?;
+        Self::ensure_column(&conn, "sessions", "masked_words_count", "INTEGER DEFAULT 0")?;
+        Self::ensure_column(&conn, "sessions", "archived_at", "REAL")?;
+        Self::ensure_column(&conn, "sessions", "deleted_at", "REAL")?;
+        Self::ensure_column(&conn, "transcription_jobs", "priority", "INTEGER DEFAULT 0")?;
+
         // Initialize lifetime_stats row if not exists
         conn.execute(
             "INSERT OR IGNORE INTO lifetime_stats (id, last_updated) VALUES (1, ?)",
@@ -181,6 +396,40 @@ impl MetricsDatabase {
             "CREATE INDEX IF NOT EXISTS idx_segments_timestamp ON segments(timestamp)",
             [],
         )?;
+        // Rows from before content hashes are NULL, which never conflict
+        conn.execute(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_segments_content_hash
+             ON segments(content_hash)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_segment_alternatives_segment_id
+             ON segment_alternatives(segment_id)",
+            [],
+        )?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_sink_deliveries_timestamp
+             ON sink_deliveries(timestamp)",
+            [],
+        )?;
+
+        Ok(())
+    }
+
+    /// Add a column to an existing table if it is missing
+    fn ensure_column(conn: &Connection, table: &str, column: &str, decl: &str) -> Result<()> {
+        let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+        let exists = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .filter_map(|name| name.ok())
+            .any(|name| name == column);
+
+        if !exists {
+            conn.execute(
+                &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, decl),
+                [],
+            )?;
+        }
 
         Ok(())
     }
@@ -228,8 +477,9 @@ impl MetricsDatabase {
                 gpu_peak_mb = ?16,
                 gpu_mean_mb = ?17,
                 cpu_mean_percent = ?18,
-                cpu_peak_percent = ?19
-            WHERE id = ?20",
+                cpu_peak_percent = ?19,
+                masked_words_count = ?20
+            WHERE id = ?21",
             params![
                 end_time,
                 session.total_duration_s,
@@ -250,6 +500,7 @@ impl MetricsDatabase {
                 session.gpu_memory_mean_mb,
                 session.cpu_usage_mean_percent,
                 session.cpu_usage_peak_percent,
+                session.masked_words_count,
                 session_id,
             ],
         )?;
@@ -258,8 +509,26 @@ impl MetricsDatabase {
     }
 
     /// Insert segment record
+    ///
+    /// A segment whose content hash is already stored is not inserted again;
+    /// the existing row's ID is returned instead.
     pub fn insert_segment(&self, segment: &SegmentMetrics, store_text: bool) -> Result<i64> {
+        self.insert_segment_checked(segment, store_text)
+            .map(|(id, _)| id)
+    }
+
+    /// Insert segment record, returning its ID and whether it was new
+    /// (`false` for a duplicate of a stored segment)
+    pub fn insert_segment_checked(
+        &self,
+        segment: &SegmentMetrics,
+        store_text: bool,
+    ) -> Result<(i64, bool)> {
         let conn = self.conn.lock().unwrap();
+        let content_hash = segment
+            .content_hash
+            .clone()
+            .or_else(|| text_content_hash(segment));
 
         let timestamp = segment
             .timestamp
@@ -271,14 +540,30 @@ impl MetricsDatabase {
         } else {
             None
         };
+        // Alternatives are transcript text too
+        let nbest = if store_text {
+            compress_nbest(&segment.nbest)?
+        } else {
+            None
+        };
+
+        let inference = segment
+            .inference
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
 
         conn.execute(
             "INSERT INTO segments (
                 session_id, timestamp, duration_s, words, characters, text,
                 vad_latency_ms, audio_save_latency_ms, stt_latency_ms,
                 transform_latency_us, injection_latency_ms, total_latency_ms,
-                transformations_count, keyboard_actions_count
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+                transformations_count, keyboard_actions_count, inference_metadata,
+                trimmed_silence_ms, start_sample, audio_start, nbest, masked_words,
+                content_hash, prosody_pitch_std, prosody_energy_dbfs, prosody_rate_wpm, speaker
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21,
+                      ?22, ?23, ?24, ?25)
+            ON CONFLICT(content_hash) DO NOTHING",
             params![
                 segment.session_id,
                 timestamp,
@@ -294,12 +579,372 @@ impl MetricsDatabase {
                 segment.total_latency_ms,
                 segment.transformations_count,
                 segment.keyboard_actions_count,
+                inference,
+                segment.trimmed_silence_ms,
+                segment.start_sample.map(|s| s as i64),
+                segment
+                    .audio_start
+                    .map(|dt| dt.timestamp_micros() as f64 / 1_000_000.0),
+                nbest,
+                segment.masked_words,
+                content_hash,
+                segment.prosody.and_then(|p| p.pitch_std_semitones),
+                segment.prosody.map(|p| p.energy_dbfs),
+                segment.prosody.map(|p| p.speaking_rate_wpm),
+                segment.speaker,
+            ],
+        )?;
+
+        if conn.changes() == 0 {
+            let existing = conn.query_row(
+                "SELECT id FROM segments WHERE content_hash = ?1",
+                params![content_hash],
+                |row| row.get(0),
+            )?;
+            return Ok((existing, false));
+        }
+        Ok((conn.last_insert_rowid(), true))
+    }
+
+    /// N-best alternatives stored with a segment (empty if none were kept)
+    pub fn get_segment_nbest(&self, segment_id: i64) -> Result<Vec<String>> {
+        let conn = self.conn.lock().unwrap();
+        let blob: Option<Vec<u8>> = conn
+            .query_row(
+                "SELECT nbest FROM segments WHERE id = ?1",
+                params![segment_id],
+                |row| row.get(0),
+            )
+            .with_context(|| format!("Segment {} not found", segment_id))?;
+        blob.map_or(Ok(Vec::new()), |blob| decompress_nbest(&blob))
+    }
+
+    /// Store an alternative transcription for an existing segment
+    pub fn insert_segment_alternative(&self, alternative: &SegmentAlternative) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+
+        let created_at = alternative
+            .created_at
+            .map(|dt| dt.timestamp() as f64)
+            .unwrap_or_else(|| Utc::now().timestamp() as f64);
+
+        let inference = alternative
+            .inference
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()?;
+
+        conn.execute(
+            "INSERT INTO segment_alternatives (segment_id, created_at, text, inference_metadata)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                alternative.segment_id,
+                created_at,
+                alternative.text,
+                inference
+            ],
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Record a segment's delivery to an output sink
+    pub fn insert_sink_delivery(&self, delivery: &SinkDelivery) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+
+        let timestamp = delivery
+            .timestamp
+            .map(|dt| dt.timestamp_micros() as f64 / 1_000_000.0)
+            .unwrap_or_else(|| Utc::now().timestamp() as f64);
+
+        conn.execute(
+            "INSERT INTO sink_deliveries (session_id, timestamp, sink, latency_ms, error)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                delivery.session_id,
+                timestamp,
+                delivery.sink,
+                delivery.latency_ms,
+                delivery.error
+            ],
+        )?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Record a scored practice run
+    pub fn insert_practice_session(&self, practice: &PracticeSession) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute(
+            "INSERT INTO practice_sessions
+                (started_at, ended_at, script_words, words_read, words_matched,
+                 accuracy, pronunciation_score)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                practice.started_at.timestamp_micros() as f64 / 1_000_000.0,
+                practice.ended_at.timestamp_micros() as f64 / 1_000_000.0,
+                practice.script_words,
+                practice.words_read,
+                practice.words_matched,
+                practice.accuracy,
+                practice.pronunciation_score
             ],
         )?;
 
         Ok(conn.last_insert_rowid())
     }
 
+    /// Most recent practice runs, newest first
+    pub fn get_practice_sessions(&self, limit: usize) -> Result<Vec<PracticeSession>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT id, started_at, ended_at, script_words, words_read, words_matched,
+                    accuracy, pronunciation_score
+             FROM practice_sessions
+             ORDER BY started_at DESC, id DESC
+             LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit as i64], |row| {
+            let timestamp = |index| -> rusqlite::Result<DateTime<Utc>> {
+                let seconds: f64 = row.get(index)?;
+                Ok(
+                    DateTime::from_timestamp_micros((seconds * 1_000_000.0) as i64)
+                        .unwrap_or_default(),
+                )
+            };
+            Ok(PracticeSession {
+                practice_id: row.get(0)?,
+                started_at: timestamp(1)?,
+                ended_at: timestamp(2)?,
+                script_words: row.get(3)?,
+                words_read: row.get(4)?,
+                words_matched: row.get(5)?,
+                accuracy: row.get(6)?,
+                pronunciation_score: row.get(7)?,
+            })
+        })?;
+
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Delivery counts, failures and latency per sink within `[start, end)`,
+    /// by sink name
+    pub fn get_sink_stats_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<SinkStats>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT sink, COUNT(*), COUNT(error), AVG(latency_ms), MAX(latency_ms)
+             FROM sink_deliveries
+             WHERE timestamp >= ?1 AND timestamp < ?2
+             GROUP BY sink
+             ORDER BY sink",
+        )?;
+        let rows = stmt.query_map(
+            params![start.timestamp() as f64, end.timestamp() as f64],
+            |row| {
+                Ok(SinkStats {
+                    sink: row.get(0)?,
+                    deliveries: row.get(1)?,
+                    failures: row.get(2)?,
+                    avg_latency_ms: row.get(3)?,
+                    max_latency_ms: row.get(4)?,
+                })
+            },
+        )?;
+
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// Get all alternative transcriptions of a segment, oldest first
+    pub fn get_segment_alternatives(&self, segment_id: i64) -> Result<Vec<SegmentAlternative>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT * FROM segment_alternatives WHERE segment_id = ?1 ORDER BY created_at ASC, id ASC",
+        )?;
+
+        let rows = stmt.query_map(params![segment_id], |row| {
+            let created_at: f64 = row.get("created_at")?;
+
+            Ok(SegmentAlternative {
+                alternative_id: row.get("id").ok(),
+                segment_id,
+                created_at: DateTime::from_timestamp(created_at as i64, 0),
+                text: row.get("text")?,
+                inference: parse_inference(row),
+            })
+        })?;
+
+        let mut alternatives = Vec::new();
+        for alternative in rows {
+            alternatives.push(alternative?);
+        }
+
+        Ok(alternatives)
+    }
+
+    /// Queue a transcription job for `path`, or `None` if it already has one
+    pub fn enqueue_transcription_job(
+        &self,
+        path: &str,
+        priority: JobPriority,
+    ) -> Result<Option<i64>> {
+        let conn = self.conn.lock().unwrap();
+        let now = Utc::now().timestamp() as f64;
+
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO transcription_jobs
+                (path, status, priority, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?4)",
+            params![path, JobStatus::Queued.as_str(), priority.as_i64(), now],
+        )?;
+
+        Ok((inserted > 0).then(|| conn.last_insert_rowid()))
+    }
+
+    /// Queue `path` whether or not it was transcribed before, returning its
+    /// job ID (an existing job is reset to queued at `priority`), or `None`
+    /// while the file is being transcribed
+    pub fn requeue_transcription_job(
+        &self,
+        path: &str,
+        priority: JobPriority,
+    ) -> Result<Option<i64>> {
+        let conn = self.conn.lock().unwrap();
+        let now = Utc::now().timestamp() as f64;
+
+        let changed = conn.execute(
+            "INSERT INTO transcription_jobs (path, status, priority, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?4)
+             ON CONFLICT(path) DO UPDATE SET
+                status = excluded.status,
+                priority = excluded.priority,
+                updated_at = excluded.updated_at,
+                error = NULL
+             WHERE transcription_jobs.status != ?5",
+            params![
+                path,
+                JobStatus::Queued.as_str(),
+                priority.as_i64(),
+                now,
+                JobStatus::Running.as_str()
+            ],
+        )?;
+        if changed == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(conn.query_row(
+            "SELECT id FROM transcription_jobs WHERE path = ?1",
+            params![path],
+            |row| row.get(0),
+        )?))
+    }
+
+    /// Record a job's progress (unset fields keep their stored values)
+    pub fn update_transcription_job(
+        &self,
+        job_id: i64,
+        status: JobStatus,
+        duration_s: Option<f64>,
+        words: Option<i64>,
+        error: Option<&str>,
+    ) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+
+        let updated = conn.execute(
+            "UPDATE transcription_jobs SET
+                status = ?2,
+                updated_at = ?3,
+                duration_s = COALESCE(?4, duration_s),
+                words = COALESCE(?5, words),
+                error = ?6
+             WHERE id = ?1",
+            params![
+                job_id,
+                status.as_str(),
+                Utc::now().timestamp() as f64,
+                duration_s,
+                words,
+                error
+            ],
+        )?;
+        if updated == 0 {
+            anyhow::bail!("Transcription job {} not found", job_id);
+        }
+
+        Ok(())
+    }
+
+    /// Most recent transcription jobs, newest first
+    pub fn get_transcription_jobs(&self, limit: usize) -> Result<Vec<TranscriptionJob>> {
+        self.query_transcription_jobs(
+            "SELECT * FROM transcription_jobs ORDER BY id DESC LIMIT ?1",
+            params![limit as i64],
+        )
+    }
+
+    /// Get a transcription job by ID
+    pub fn get_transcription_job(&self, job_id: i64) -> Result<Option<TranscriptionJob>> {
+        Ok(self
+            .query_transcription_jobs(
+                "SELECT * FROM transcription_jobs WHERE id = ?1",
+                params![job_id],
+            )?
+            .pop())
+    }
+
+    /// Jobs still queued or interrupted while running, in the order they
+    /// should run (highest priority, then oldest, first)
+    pub fn get_unfinished_transcription_jobs(&self) -> Result<Vec<TranscriptionJob>> {
+        self.query_transcription_jobs(
+            "SELECT * FROM transcription_jobs WHERE status IN (?1, ?2)
+             ORDER BY priority DESC, id ASC",
+            params![JobStatus::Queued.as_str(), JobStatus::Running.as_str()],
+        )
+    }
+
+    fn query_transcription_jobs(
+        &self,
+        sql: &str,
+        params: impl rusqlite::Params,
+    ) -> Result<Vec<TranscriptionJob>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(params, |row| {
+            let status: String = row.get("status")?;
+            let created_at: f64 = row.get("created_at")?;
+            let updated_at: f64 = row.get("updated_at")?;
+            let priority: Option<i64> = row.get("priority")?;
+
+            Ok(TranscriptionJob {
+                job_id: row.get("id")?,
+                path: row.get("path")?,
+                status: JobStatus::parse(&status).unwrap_or(JobStatus::Failed),
+                priority: JobPriority::from_i64(priority.unwrap_or(0)),
+                created_at: DateTime::from_timestamp(created_at as i64, 0),
+                updated_at: DateTime::from_timestamp(updated_at as i64, 0),
+                duration_s: row.get("duration_s")?,
+                words: row.get("words")?,
+                error: row.get("error")?,
+            })
+        })?;
+
+        let mut jobs = Vec::new();
+        for job in rows {
+            jobs.push(job?);
+        }
+
+        Ok(jobs)
+    }
+
     /// Get session by ID
     pub fn get_session(&self, session_id: i64) -> Result<Option<SessionMetrics>> {
         let conn = self.conn.lock().unwrap();
@@ -392,8 +1037,20 @@ impl MetricsDatabase {
         Ok(())
     }
 
+    /// Count one more segment over its latency budget
+    pub fn increment_high_latency_warnings(&self) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE lifetime_stats SET high_latency_warnings = high_latency_warnings + 1 WHERE id = 1",
+            [],
+        )?;
+        Ok(())
+    }
+
     /// Recalculate lifetime stats from all sessions and segments
     /// This should be called after each session ends to update aggregate statistics
+    ///
+    /// Archived and trashed sessions are left out.
     pub fn recalculate_lifetime_stats(&self) -> Result<()> {
         let conn = self.conn.lock().unwrap();
 
@@ -407,11 +1064,14 @@ impl MetricsDatabase {
                 COALESCE(AVG(wpm), 0) as avg_wpm,
                 COALESCE(AVG(avg_latency_ms), 0) as avg_latency_ms,
                 MAX(wpm) as best_wpm,
-                (SELECT id FROM sessions ORDER BY wpm DESC LIMIT 1) as best_wpm_session,
+                (SELECT id FROM sessions WHERE archived_at IS NULL AND deleted_at IS NULL
+                 ORDER BY wpm DESC LIMIT 1) as best_wpm_session,
                 MIN(avg_latency_ms) as lowest_latency,
-                (SELECT id FROM sessions WHERE avg_latency_ms > 0 ORDER BY avg_latency_ms ASC LIMIT 1) as lowest_latency_session
+                (SELECT id FROM sessions WHERE avg_latency_ms > 0
+                   AND archived_at IS NULL AND deleted_at IS NULL
+                 ORDER BY avg_latency_ms ASC LIMIT 1) as lowest_latency_session
              FROM sessions
-             WHERE end_time IS NOT NULL"
+             WHERE end_time IS NOT NULL AND archived_at IS NULL AND deleted_at IS NULL",
         )?;
 
         let result: Result<DbSessionRow> = stmt
@@ -446,7 +1106,13 @@ impl MetricsDatabase {
 
         // Count total segments
         let total_segments: i32 = conn
-            .query_row("SELECT COUNT(*) FROM segments", [], |row| row.get(0))
+            .query_row(
+                "SELECT COUNT(*) FROM segments
+                 WHERE session_id NOT IN (SELECT id FROM sessions
+                     WHERE archived_at IS NOT NULL OR deleted_at IS NOT NULL)",
+                [],
+                |row| row.get(0),
+            )
             .unwrap_or(0);
 
         // Calculate time saved (assuming 40 WPM typing baseline)
@@ -532,6 +1198,7 @@ impl MetricsDatabase {
             gpu_memory_mean_mb: row.get("gpu_mean_mb").unwrap_or(0.0),
             cpu_usage_mean_percent: row.get("cpu_mean_percent").unwrap_or(0.0),
             cpu_usage_peak_percent: row.get("cpu_peak_percent").unwrap_or(0.0),
+            masked_words_count: row.get("masked_words_count").unwrap_or(0),
             total_samples: 0,
         })
     }
@@ -569,10 +1236,16 @@ impl MetricsDatabase {
     }
 
     /// Get recent sessions ordered by start time (for Tauri UI)
+    ///
+    /// Archived and trashed sessions are not listed.
     pub fn get_recent_sessions(&self, limit: usize) -> Result<Vec<SessionMetrics>> {
         let conn = self.conn.lock().unwrap();
 
-        let mut stmt = conn.prepare("SELECT * FROM sessions ORDER BY start_time DESC LIMIT ?1")?;
+        let mut stmt = conn.prepare(
+            "SELECT * FROM sessions
+             WHERE archived_at IS NULL AND deleted_at IS NULL
+             ORDER BY start_time DESC LIMIT ?1",
+        )?;
 
         let rows = stmt.query_map(params![limit], |row| {
             let start_time: Option<f64> = row.get("start_time")?;
@@ -603,6 +1276,7 @@ impl MetricsDatabase {
                 gpu_memory_mean_mb: row.get("gpu_mean_mb").unwrap_or(0.0),
                 cpu_usage_mean_percent: row.get("cpu_mean_percent").unwrap_or(0.0),
                 cpu_usage_peak_percent: row.get("cpu_peak_percent").unwrap_or(0.0),
+                masked_words_count: row.get("masked_words_count").unwrap_or(0),
                 total_samples: 0,
             })
         })?;
@@ -642,6 +1316,19 @@ impl MetricsDatabase {
                 total_latency_ms: row.get("total_latency_ms").unwrap_or(0.0),
                 transformations_count: row.get("transformations_count").unwrap_or(0),
                 keyboard_actions_count: row.get("keyboard_actions_count").unwrap_or(0),
+                trimmed_silence_ms: row.get("trimmed_silence_ms").unwrap_or(0.0),
+                start_sample: row
+                    .get::<_, Option<i64>>("start_sample")
+                    .ok()
+                    .flatten()
+                    .map(|s| s as u64),
+                audio_start: parse_audio_start(row),
+                inference: parse_inference(row),
+                nbest: parse_nbest(row),
+                masked_words: row.get("masked_words").unwrap_or(0),
+                content_hash: row.get("content_hash").unwrap_or(None),
+                prosody: parse_prosody(row),
+                speaker: row.get("speaker").unwrap_or(None),
             })
         })?;
 
@@ -655,6 +1342,8 @@ impl MetricsDatabase {
 
     /// Search transcriptions by text query (for Tauri UI)
     /// Uses SQLite FTS if available, otherwise falls back to LIKE
+    ///
+    /// Archived sessions are searched; sessions in the trash are not.
     pub fn search_transcriptions(&self, query: &str, limit: usize) -> Result<Vec<SegmentMetrics>> {
         let conn = self.conn.lock().unwrap();
 
@@ -664,6 +1353,7 @@ impl MetricsDatabase {
         let mut stmt = conn.prepare(
             "SELECT * FROM segments
              WHERE text LIKE ?1
+               AND session_id NOT IN (SELECT id FROM sessions WHERE deleted_at IS NOT NULL)
              ORDER BY timestamp DESC
              LIMIT ?2",
         )?;
@@ -689,6 +1379,19 @@ impl MetricsDatabase {
                 total_latency_ms: row.get("total_latency_ms").unwrap_or(0.0),
                 transformations_count: row.get("transformations_count").unwrap_or(0),
                 keyboard_actions_count: row.get("keyboard_actions_count").unwrap_or(0),
+                trimmed_silence_ms: row.get("trimmed_silence_ms").unwrap_or(0.0),
+                start_sample: row
+                    .get::<_, Option<i64>>("start_sample")
+                    .ok()
+                    .flatten()
+                    .map(|s| s as u64),
+                audio_start: parse_audio_start(row),
+                inference: parse_inference(row),
+                nbest: parse_nbest(row),
+                masked_words: row.get("masked_words").unwrap_or(0),
+                content_hash: row.get("content_hash").unwrap_or(None),
+                prosody: parse_prosody(row),
+                speaker: row.get("speaker").unwrap_or(None),
             })
         })?;
 
@@ -700,6 +1403,29 @@ impl MetricsDatabase {
         Ok(segments)
     }
 
+    /// Compare two sessions' performance and vocabulary (for Tauri UI)
+    ///
+    /// Vocabulary is derived from stored segment text, so sessions recorded
+    /// with text storage disabled report an empty vocabulary.
+    pub fn compare_sessions(&self, session_a: i64, session_b: i64) -> Result<SessionComparison> {
+        let a = self
+            .get_session(session_a)?
+            .with_context(|| format!("Session {} not found", session_a))?;
+        let b = self
+            .get_session(session_b)?
+            .with_context(|| format!("Session {} not found", session_b))?;
+
+        let segments_a = self.get_session_segments(session_a)?;
+        let segments_b = self.get_session_segments(session_b)?;
+
+        Ok(SessionComparison::new(
+            &a,
+            &b,
+            segments_a.iter().map(|s| s.text.as_str()),
+            segments_b.iter().map(|s| s.text.as_str()),
+        ))
+    }
+
     /// Get lifetime statistics (alias for get_lifetime_metrics for consistency)
     pub fn get_lifetime_stats(&self) -> Result<LifetimeMetrics> {
         self.get_lifetime_metrics()
@@ -711,8 +1437,11 @@ impl MetricsDatabase {
 
         let cutoff_time = Utc::now().timestamp() as f64 - (days as f64 * 24.0 * 60.0 * 60.0);
 
-        let mut stmt =
-            conn.prepare("SELECT * FROM sessions WHERE start_time >= ?1 ORDER BY start_time ASC")?;
+        let mut stmt = conn.prepare(
+            "SELECT * FROM sessions
+             WHERE start_time >= ?1 AND archived_at IS NULL AND deleted_at IS NULL
+             ORDER BY start_time ASC",
+        )?;
 
         let rows = stmt.query_map(params![cutoff_time], |row| {
             let start_time: Option<f64> = row.get("start_time")?;
@@ -743,6 +1472,7 @@ impl MetricsDatabase {
                 gpu_memory_mean_mb: row.get("gpu_mean_mb").unwrap_or(0.0),
                 cpu_usage_mean_percent: row.get("cpu_mean_percent").unwrap_or(0.0),
                 cpu_usage_peak_percent: row.get("cpu_peak_percent").unwrap_or(0.0),
+                masked_words_count: row.get("masked_words_count").unwrap_or(0),
                 total_samples: 0,
             })
         })?;
@@ -755,67 +1485,667 @@ impl MetricsDatabase {
         Ok(sessions)
     }
 
-    /// Delete segments older than N days to manage database size
-    pub fn cleanup_old_segments(&self, days: u32) -> Result<usize> {
+    /// Get sessions that started within `[start, end)`, oldest first
+    /// (archived and trashed sessions excluded)
+    pub fn get_sessions_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<SessionMetrics>> {
         let conn = self.conn.lock().unwrap();
 
-        let cutoff_time = Utc::now().timestamp() as f64 - (days as f64 * 24.0 * 60.0 * 60.0);
-
-        let deleted = conn.execute(
-            "DELETE FROM segments WHERE timestamp < ?1",
-            params![cutoff_time],
+        let mut stmt = conn.prepare(
+            "SELECT * FROM sessions
+             WHERE start_time >= ?1 AND start_time < ?2
+               AND archived_at IS NULL AND deleted_at IS NULL
+             ORDER BY start_time ASC",
         )?;
 
-        Ok(deleted)
-    }
+        let mut rows = stmt.query(params![start.timestamp() as f64, end.timestamp() as f64])?;
 
-    /// Get database file size in megabytes
-    pub fn get_database_size_mb(&self) -> Result<f64> {
-        let metadata = std::fs::metadata(&self.db_path)?;
-        Ok(metadata.len() as f64 / (1024.0 * 1024.0))
+        let mut sessions = Vec::new();
+        while let Some(row) = rows.next()? {
+            sessions.push(self.row_to_session(row)?);
+        }
+
+        Ok(sessions)
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::TempDir;
+    /// Average per-stage latency of segments recorded within `[start, end)`
+    ///
+    /// Returns the averages and the number of segments they cover.
+    pub fn get_latency_breakdown_between(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<(LatencyBreakdown, i64)> {
+        let conn = self.conn.lock().unwrap();
 
-    #[test]
-    fn test_database_creation() {
-        let tmp_dir = TempDir::new().unwrap();
-        let db_path = tmp_dir.path().join("test_metrics.db");
+        let result = conn.query_row(
+            "SELECT
+                COUNT(*),
+                COALESCE(AVG(vad_latency_ms), 0),
+                COALESCE(AVG(stt_latency_ms), 0),
+                COALESCE(AVG(transform_latency_us), 0) / 1000.0,
+                COALESCE(AVG(injection_latency_ms), 0),
+                COALESCE(AVG(total_latency_ms), 0)
+             FROM segments
+             WHERE timestamp >= ?1 AND timestamp < ?2
+               AND session_id NOT IN (SELECT id FROM sessions
+                   WHERE archived_at IS NOT NULL OR deleted_at IS NOT NULL)",
+            params![start.timestamp() as f64, end.timestamp() as f64],
+            |row| {
+                Ok((
+                    LatencyBreakdown {
+                        vad_ms: row.get(1)?,
+                        stt_ms: row.get(2)?,
+                        transform_ms: row.get(3)?,
+                        injection_ms: row.get(4)?,
+                        total_ms: row.get(5)?,
+                    },
+                    row.get(0)?,
+                ))
+            },
+        )?;
 
-        let _db = MetricsDatabase::new(&db_path).unwrap();
-        assert!(db_path.exists());
+        Ok(result)
     }
 
-    #[test]
-    fn test_session_crud() {
-        let tmp_dir = TempDir::new().unwrap();
-        let db_path = tmp_dir.path().join("test_metrics.db");
-        let db = MetricsDatabase::new(&db_path).unwrap();
-
-        let mut session = SessionMetrics {
-            session_start: Some(Utc::now()),
+    /// Remove duplicate segments and take their words back out of the
+    /// session and lifetime totals
+    ///
+    /// Rows are duplicates when they share a content hash or, for rows
+    /// stored before hashes existed, the same session, capture position and
+    /// word/character counts (or session, second and text when the capture
+    /// position is unknown). The oldest row of each group is kept.
+    pub fn dedupe_segments(&self, dry_run: bool) -> Result<DedupeReport> {
+        let mut report = DedupeReport {
+            dry_run,
             ..Default::default()
         };
+        {
+            let mut conn = self.conn.lock().unwrap();
+            let tx = conn.transaction()?;
+
+            type Row = (
+                i64,
+                i64,
+                Option<String>,
+                Option<i64>,
+                f64,
+                Option<String>,
+                i32,
+                i32,
+            );
+            let rows: Vec<Row> = {
+                let mut stmt = tx.prepare(
+                    "SELECT id, session_id, content_hash, start_sample, timestamp, text,
+                            words, characters
+                     FROM segments ORDER BY id",
+                )?;
+                let rows = stmt.query_map([], |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                        row.get(5)?,
+                        row.get::<_, Option<i32>>(6)?.unwrap_or(0),
+                        row.get::<_, Option<i32>>(7)?.unwrap_or(0),
+                    ))
+                })?;
+                rows.collect::<rusqlite::Result<_>>()?
+            };
 
-        // Insert creates session (only sets start_time)
-        let session_id = db.insert_session(&session).unwrap();
-        assert!(session_id > 0);
-
-        // Update adds words (realistic usage pattern)
-        session.session_id = Some(session_id);
-        session.words_dictated = 100;
-        db.update_session(session_id, &session).unwrap();
+            let mut seen = std::collections::HashSet::new();
+            // session -> (segments, words, characters) to subtract
+            let mut removed: std::collections::BTreeMap<i64, (i32, i32, i32)> = Default::default();
+            for (id, session_id, hash, start_sample, timestamp, text, words, characters) in rows {
+                let key = match (hash, start_sample, text.filter(|t| !t.is_empty())) {
+                    (Some(hash), _, _) => hash,
+                    (None, Some(start), _) => {
+                        format!("legacy:{}:{}:{}:{}", session_id, start, words, characters)
+                    }
+                    (None, None, Some(text)) => {
+                        format!("legacy:{}:{}:{}", session_id, timestamp as i64, text)
+                    }
+                    (None, None, None) => continue,
+                };
+                if seen.insert(key) {
+                    continue;
+                }
+
+                report.duplicate_segments += 1;
+                report.words_removed += words as i64;
+                let totals = removed.entry(session_id).or_default();
+                totals.0 += 1;
+                totals.1 += words;
+                totals.2 += characters;
+                if !dry_run {
+                    tx.execute("DELETE FROM segments WHERE id = ?1", params![id])?;
+                }
+            }
+            report.sessions_affected = removed.len();
+
+            if !dry_run {
+                for (session_id, (segments, words, characters)) in &removed {
+                    tx.execute(
+                        "UPDATE sessions SET
+                            segments_processed = MAX(segments_processed - ?1, 0),
+                            words_dictated = MAX(words_dictated - ?2, 0),
+                            characters_typed = MAX(characters_typed - ?3, 0)
+                         WHERE id = ?4",
+                        params![segments, words, characters, session_id],
+                    )?;
+                    tx.execute(
+                        "UPDATE sessions SET wpm = words_dictated * 60.0 / active_time_s
+                         WHERE id = ?1 AND active_time_s > 0",
+                        params![session_id],
+                    )?;
+                }
+                tx.commit()?;
+            }
+        }
 
-        let retrieved = db.get_session(session_id).unwrap();
-        assert!(retrieved.is_some());
-        assert_eq!(retrieved.unwrap().words_dictated, 100);
+        if !dry_run && report.duplicate_segments > 0 {
+            self.recalculate_lifetime_stats()?;
+        }
+        Ok(report)
     }
 
-    #[test]
+    /// Rebuild lifetime stats from the sessions table, reporting sessions
+    /// whose accounting is inconsistent
+    ///
+    /// With `repair`, dangling sessions are closed at their last segment
+    /// with totals summed from their segments, missing durations are filled
+    /// in, and impossible WPM values are recomputed (or zeroed when the
+    /// session has no active time) before the rebuild.
+    pub fn recompute_stats(&self, repair: bool) -> Result<IntegrityReport> {
+        let before = self.get_lifetime_stats()?;
+        let mut report = IntegrityReport {
+            repaired: repair,
+            total_words_before: before.total_words,
+            total_sessions_before: before.total_sessions,
+            ..Default::default()
+        };
+
+        {
+            let mut conn = self.conn.lock().unwrap();
+            let tx = conn.transaction()?;
+            let ids = |sql: &str, params: &[&dyn rusqlite::ToSql]| -> Result<Vec<i64>> {
+                let mut stmt = tx.prepare(sql)?;
+                let ids = stmt.query_map(params, |row| row.get(0))?;
+                Ok(ids.collect::<rusqlite::Result<_>>()?)
+            };
+
+            let idle_cutoff = Utc::now().timestamp() as f64 - DANGLING_AFTER_S;
+            report.dangling_sessions = ids(
+                "SELECT id FROM sessions
+                 WHERE end_time IS NULL
+                   AND MAX(start_time, COALESCE(
+                       (SELECT MAX(timestamp) FROM segments WHERE session_id = sessions.id),
+                       0)) < ?1
+                 ORDER BY id",
+                &[&idle_cutoff],
+            )?;
+            report.null_durations = ids(
+                "SELECT id FROM sessions
+                 WHERE end_time IS NOT NULL AND (duration_s IS NULL OR active_time_s IS NULL)
+                 ORDER BY id",
+                &[],
+            )?;
+            report.impossible_wpm = ids(
+                "SELECT id FROM sessions
+                 WHERE wpm < 0 OR wpm > ?1 OR (wpm > 0 AND COALESCE(active_time_s, 0) <= 0)
+                 ORDER BY id",
+                &[&MAX_PLAUSIBLE_WPM],
+            )?;
+
+            if repair {
+                for id in &report.dangling_sessions {
+                    tx.execute(
+                        "UPDATE sessions SET
+                            end_time = MAX(start_time, COALESCE(
+                                (SELECT MAX(timestamp) FROM segments WHERE session_id = ?1),
+                                start_time)),
+                            words_dictated = (SELECT COALESCE(SUM(words), 0)
+                                              FROM segments WHERE session_id = ?1),
+                            characters_typed = (SELECT COALESCE(SUM(characters), 0)
+                                                FROM segments WHERE session_id = ?1),
+                            segments_processed = (SELECT COUNT(*)
+                                                  FROM segments WHERE session_id = ?1),
+                            wpm = NULL
+                         WHERE id = ?1",
+                        params![id],
+                    )?;
+                }
+                // Also covers the sessions closed above
+                tx.execute(
+                    "UPDATE sessions SET
+                        duration_s = COALESCE(duration_s, end_time - start_time),
+                        active_time_s = COALESCE(active_time_s,
+                            (SELECT COALESCE(SUM(duration_s), 0)
+                             FROM segments WHERE session_id = sessions.id))
+                     WHERE end_time IS NOT NULL
+                       AND (duration_s IS NULL OR active_time_s IS NULL)",
+                    [],
+                )?;
+                for id in report
+                    .impossible_wpm
+                    .iter()
+                    .chain(&report.dangling_sessions)
+                {
+                    tx.execute(
+                        "UPDATE sessions SET wpm = CASE
+                            WHEN active_time_s > 0 AND words_dictated * 60.0 / active_time_s <= ?2
+                            THEN words_dictated * 60.0 / active_time_s
+                            ELSE 0 END
+                         WHERE id = ?1",
+                        params![id, MAX_PLAUSIBLE_WPM],
+                    )?;
+                }
+                tx.commit()?;
+            }
+        }
+
+        self.recalculate_lifetime_stats()?;
+        let after = self.get_lifetime_stats()?;
+        report.total_words_after = after.total_words;
+        report.total_sessions_after = after.total_sessions;
+        Ok(report)
+    }
+
+    /// Fill in the median and p95 latency of ended sessions from their
+    /// segments' end-to-end latencies
+    ///
+    /// Only sessions missing them are touched unless `all` is set, which
+    /// recomputes every session (older builds took nearest-rank shortcuts
+    /// instead of interpolating). Latencies are streamed per session, so
+    /// long sessions never need all their rows in memory at once.
+    pub fn backfill_latency_percentiles(
+        &self,
+        all: bool,
+        dry_run: bool,
+    ) -> Result<PercentileBackfillReport> {
+        let mut report = PercentileBackfillReport {
+            dry_run,
+            ..Default::default()
+        };
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let sessions: Vec<i64> = {
+            let mut stmt = tx.prepare(
+                "SELECT id FROM sessions
+                 WHERE end_time IS NOT NULL
+                   AND (?1 OR COALESCE(median_latency_ms, 0) <= 0
+                           OR COALESCE(p95_latency_ms, 0) <= 0)
+                   AND EXISTS (SELECT 1 FROM segments
+                               WHERE session_id = sessions.id
+                                 AND total_latency_ms IS NOT NULL)
+                 ORDER BY id",
+            )?;
+            let ids = stmt.query_map(params![all], |row| row.get(0))?;
+            ids.collect::<rusqlite::Result<_>>()?
+        };
+
+        {
+            let mut latencies = tx.prepare(
+                "SELECT total_latency_ms FROM segments
+                 WHERE session_id = ?1 AND total_latency_ms IS NOT NULL",
+            )?;
+            for id in sessions {
+                let mut aggregator = PercentileAggregator::new();
+                let mut rows = latencies.query(params![id])?;
+                while let Some(row) = rows.next()? {
+                    aggregator.add(row.get(0)?);
+                }
+                report.segments_read += aggregator.count();
+
+                let Some(percentiles) = aggregator.latency_percentiles() else {
+                    continue;
+                };
+                if !dry_run {
+                    tx.execute(
+                        "UPDATE sessions SET median_latency_ms = ?2, p95_latency_ms = ?3
+                         WHERE id = ?1",
+                        params![id, percentiles.median_ms, percentiles.p95_ms],
+                    )?;
+                }
+                report.sessions_updated += 1;
+            }
+        }
+
+        if !dry_run {
+            tx.commit()?;
+        }
+        Ok(report)
+    }
+
+    /// Merge ended sessions into the earliest of them, returning its ID
+    ///
+    /// Segments move to the surviving session, which then spans all of the
+    /// merged sessions and has its words, latency and WPM re-aggregated from
+    /// the combined segments; the other sessions are deleted. Meant for
+    /// cleaning up the fragments accidental toggles leave in the history.
+    pub fn merge_sessions(&self, ids: &[i64]) -> Result<i64> {
+        let mut ids = ids.to_vec();
+        ids.sort_unstable();
+        ids.dedup();
+        if ids.len() < 2 {
+            anyhow::bail!("Merging needs at least two sessions");
+        }
+
+        let target = {
+            let mut conn = self.conn.lock().unwrap();
+            let tx = conn.transaction()?;
+
+            // (id, start, end, duration, gpu_peak, gpu_mean, cpu_mean, cpu_peak)
+            let mut sessions = Vec::with_capacity(ids.len());
+            for id in &ids {
+                let row = tx
+                    .query_row(
+                        "SELECT start_time, end_time, COALESCE(duration_s, 0),
+                                gpu_peak_mb, gpu_mean_mb, cpu_mean_percent, cpu_peak_percent
+                         FROM sessions WHERE id = ?1",
+                        params![id],
+                        |row| {
+                            Ok((
+                                row.get::<_, f64>(0)?,
+                                row.get::<_, Option<f64>>(1)?,
+                                row.get::<_, f64>(2)?,
+                                row.get::<_, Option<f64>>(3)?,
+                                row.get::<_, Option<f64>>(4)?,
+                                row.get::<_, Option<f64>>(5)?,
+                                row.get::<_, Option<f64>>(6)?,
+                            ))
+                        },
+                    )
+                    .optional()?
+                    .with_context(|| format!("Session {} not found", id))?;
+                let (start, end, duration, gpu_peak, gpu_mean, cpu_mean, cpu_peak) = row;
+                let end = end.with_context(|| format!("Session {} has not ended", id))?;
+                sessions.push((
+                    *id, start, end, duration, gpu_peak, gpu_mean, cpu_mean, cpu_peak,
+                ));
+            }
+            sessions.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+            let target = sessions[0].0;
+
+            let start = sessions.iter().map(|s| s.1).fold(f64::INFINITY, f64::min);
+            let end = sessions
+                .iter()
+                .map(|s| s.2)
+                .fold(f64::NEG_INFINITY, f64::max);
+            let peak = |values: Vec<Option<f64>>| values.into_iter().flatten().reduce(f64::max);
+            // Resource means weighted by how long each session ran
+            let mean = |values: Vec<(Option<f64>, f64)>| {
+                let known: Vec<(f64, f64)> = values
+                    .into_iter()
+                    .filter_map(|(value, weight)| value.map(|v| (v, weight)))
+                    .collect();
+                let weight: f64 = known.iter().map(|(_, w)| w).sum();
+                if weight > 0.0 {
+                    Some(known.iter().map(|(v, w)| v * w).sum::<f64>() / weight)
+                } else {
+                    known.first().map(|(v, _)| *v)
+                }
+            };
+            let gpu_peak = peak(sessions.iter().map(|s| s.4).collect());
+            let gpu_mean = mean(sessions.iter().map(|s| (s.5, s.3)).collect());
+            let cpu_mean = mean(sessions.iter().map(|s| (s.6, s.3)).collect());
+            let cpu_peak = peak(sessions.iter().map(|s| s.7).collect());
+
+            for (id, ..) in &sessions[1..] {
+                tx.execute(
+                    "UPDATE segments SET session_id = ?1 WHERE session_id = ?2",
+                    params![target, id],
+                )?;
+                tx.execute("DELETE FROM sessions WHERE id = ?1", params![id])?;
+            }
+            tx.execute(
+                "UPDATE sessions SET start_time = ?2, end_time = ?3,
+                    gpu_peak_mb = ?4, gpu_mean_mb = ?5, cpu_mean_percent = ?6,
+                    cpu_peak_percent = ?7
+                 WHERE id = ?1",
+                params![target, start, end, gpu_peak, gpu_mean, cpu_mean, cpu_peak],
+            )?;
+            reaggregate_session(&tx, target)?;
+            tx.commit()?;
+            target
+        };
+
+        self.recalculate_lifetime_stats()?;
+        Ok(target)
+    }
+
+    /// Split an ended session at `at` (Unix seconds), returning the ID of the
+    /// new session holding the segments from `at` on
+    ///
+    /// Both halves are re-aggregated from their segments. Resource usage
+    /// (GPU/CPU) cannot be apportioned, so the new session copies it.
+    pub fn split_session(&self, id: i64, at: f64) -> Result<i64> {
+        let new_id = {
+            let mut conn = self.conn.lock().unwrap();
+            let tx = conn.transaction()?;
+
+            let (start, end) = tx
+                .query_row(
+                    "SELECT start_time, end_time FROM sessions WHERE id = ?1",
+                    params![id],
+                    |row| Ok((row.get::<_, f64>(0)?, row.get::<_, Option<f64>>(1)?)),
+                )
+                .optional()?
+                .with_context(|| format!("Session {} not found", id))?;
+            let end = end.with_context(|| format!("Session {} has not ended", id))?;
+            if at <= start || at >= end {
+                anyhow::bail!(
+                    "Split time must fall inside session {} ({} to {})",
+                    id,
+                    start,
+                    end
+                );
+            }
+
+            tx.execute(
+                "INSERT INTO sessions (start_time, end_time, typing_equiv_wpm, gpu_peak_mb,
+                    gpu_mean_mb, cpu_mean_percent, cpu_peak_percent)
+                 SELECT ?2, end_time, typing_equiv_wpm, gpu_peak_mb, gpu_mean_mb,
+                    cpu_mean_percent, cpu_peak_percent
+                 FROM sessions WHERE id = ?1",
+                params![id, at],
+            )?;
+            let new_id = tx.last_insert_rowid();
+            tx.execute(
+                "UPDATE segments SET session_id = ?1 WHERE session_id = ?2 AND timestamp >= ?3",
+                params![new_id, id, at],
+            )?;
+            tx.execute(
+                "UPDATE sessions SET end_time = ?2 WHERE id = ?1",
+                params![id, at],
+            )?;
+            reaggregate_session(&tx, id)?;
+            reaggregate_session(&tx, new_id)?;
+            tx.commit()?;
+            new_id
+        };
+
+        self.recalculate_lifetime_stats()?;
+        Ok(new_id)
+    }
+
+    /// Archive an ended session: it is left out of stats, trends and the
+    /// session list, but stays readable by ID and searchable
+    pub fn archive_session(&self, id: i64) -> Result<()> {
+        self.mark_session(id, "archived_at", true)
+    }
+
+    /// Bring an archived session back into stats and the session list
+    pub fn unarchive_session(&self, id: i64) -> Result<()> {
+        self.mark_session(id, "archived_at", false)
+    }
+
+    /// Move an ended session to the trash
+    ///
+    /// Trashed sessions are hidden everywhere, search included, until they
+    /// are restored or [`Self::purge_trash`] removes them for good.
+    pub fn delete_session(&self, id: i64) -> Result<()> {
+        self.mark_session(id, "deleted_at", true)
+    }
+
+    /// Take a session back out of the trash
+    pub fn restore_session(&self, id: i64) -> Result<()> {
+        self.mark_session(id, "deleted_at", false)
+    }
+
+    /// Set (to now) or clear one of a session's `archived_at`/`deleted_at`
+    /// timestamps and rebuild lifetime stats
+    fn mark_session(&self, id: i64, column: &str, set: bool) -> Result<()> {
+        {
+            let conn = self.conn.lock().unwrap();
+            let end_time: Option<f64> = conn
+                .query_row(
+                    "SELECT end_time FROM sessions WHERE id = ?1",
+                    params![id],
+                    |row| row.get(0),
+                )
+                .optional()?
+                .with_context(|| format!("Session {} not found", id))?;
+            if end_time.is_none() {
+                anyhow::bail!("Session {} has not ended", id);
+            }
+            let at = set.then(|| Utc::now().timestamp() as f64);
+            conn.execute(
+                &format!("UPDATE sessions SET {} = ?2 WHERE id = ?1", column),
+                params![id, at],
+            )?;
+        }
+        self.recalculate_lifetime_stats()
+    }
+
+    /// Permanently delete sessions that have been in the trash for at least
+    /// `days` days (0 empties the trash), with their segments
+    ///
+    /// The purged segment ids are returned so files kept per segment (audio
+    /// recordings) can be deleted too; SQLite may reuse the ids.
+    pub fn purge_trash(&self, days: u32) -> Result<PurgedTrash> {
+        let cutoff_time = Utc::now().timestamp() as f64 - (days as f64 * 24.0 * 60.0 * 60.0);
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let segment_ids = tx
+            .prepare(
+                "SELECT segments.id FROM segments JOIN sessions ON sessions.id = session_id
+                 WHERE sessions.deleted_at <= ?1",
+            )?
+            .query_map(params![cutoff_time], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<i64>>>()?;
+        tx.execute(
+            "DELETE FROM segment_alternatives WHERE segment_id IN (
+                SELECT segments.id FROM segments JOIN sessions ON sessions.id = session_id
+                WHERE sessions.deleted_at <= ?1)",
+            params![cutoff_time],
+        )?;
+        tx.execute(
+            "DELETE FROM segments WHERE session_id IN (
+                SELECT id FROM sessions WHERE deleted_at <= ?1)",
+            params![cutoff_time],
+        )?;
+        tx.execute(
+            "DELETE FROM sink_deliveries WHERE session_id IN (
+                SELECT id FROM sessions WHERE deleted_at <= ?1)",
+            params![cutoff_time],
+        )?;
+        let sessions = tx.execute(
+            "DELETE FROM sessions WHERE deleted_at <= ?1",
+            params![cutoff_time],
+        )?;
+        tx.commit()?;
+        Ok(PurgedTrash {
+            sessions,
+            segment_ids,
+        })
+    }
+
+    /// Delete segments older than N days to manage database size
+    pub fn cleanup_old_segments(&self, days: u32) -> Result<usize> {
+        let conn = self.conn.lock().unwrap();
+
+        let cutoff_time = Utc::now().timestamp() as f64 - (days as f64 * 24.0 * 60.0 * 60.0);
+
+        let deleted = conn.execute(
+            "DELETE FROM segments WHERE timestamp < ?1",
+            params![cutoff_time],
+        )?;
+        conn.execute(
+            "DELETE FROM sink_deliveries WHERE timestamp < ?1",
+            params![cutoff_time],
+        )?;
+
+        Ok(deleted)
+    }
+
+    /// Get database file size in megabytes
+    pub fn get_database_size_mb(&self) -> Result<f64> {
+        let metadata = std::fs::metadata(&self.db_path)?;
+        Ok(metadata.len() as f64 / (1024.0 * 1024.0))
+    }
+
+    /// Cap the file at `pages` pages (at least its current size), so writes
+    /// that need more space fail as on a full disk
+    #[cfg(test)]
+    pub(crate) fn set_max_page_count(&self, pages: u32) -> Result<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            &format!("PRAGMA max_page_count = {}", pages),
+            [],
+            |_| Ok(()),
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_database_creation() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db_path = tmp_dir.path().join("test_metrics.db");
+
+        let _db = MetricsDatabase::new(&db_path).unwrap();
+        assert!(db_path.exists());
+    }
+
+    #[test]
+    fn test_session_crud() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db_path = tmp_dir.path().join("test_metrics.db");
+        let db = MetricsDatabase::new(&db_path).unwrap();
+
+        let mut session = SessionMetrics {
+            session_start: Some(Utc::now()),
+            ..Default::default()
+        };
+
+        // Insert creates session (only sets start_time)
+        let session_id = db.insert_session(&session).unwrap();
+        assert!(session_id > 0);
+
+        // Update adds words (realistic usage pattern)
+        session.session_id = Some(session_id);
+        session.words_dictated = 100;
+        db.update_session(session_id, &session).unwrap();
+
+        let retrieved = db.get_session(session_id).unwrap();
+        assert!(retrieved.is_some());
+        assert_eq!(retrieved.unwrap().words_dictated, 100);
+    }
+
+    #[test]
     fn test_get_recent_sessions() {
         let tmp_dir = TempDir::new().unwrap();
         let db_path = tmp_dir.path().join("test_metrics.db");
@@ -859,6 +2189,7 @@ mod tests {
                 timestamp: Some(Utc::now()),
                 text: format!("Test segment {}", i + 1),
                 words: 5 * (i + 1),
+                trimmed_silence_ms: 100.0 * i as f64,
                 ..Default::default()
             };
             db.insert_segment(&segment, true).unwrap();
@@ -868,6 +2199,799 @@ mod tests {
         let segments = db.get_session_segments(session_id).unwrap();
         assert_eq!(segments.len(), 3);
         assert_eq!(segments[0].text, "Test segment 1");
+        assert_eq!(segments[2].trimmed_silence_ms, 200.0);
+        assert_eq!(segments[0].start_sample, None);
+    }
+
+    #[test]
+    fn test_segment_audio_position_roundtrip() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db = MetricsDatabase::new(tmp_dir.path().join("test_metrics.db")).unwrap();
+        let session_id = db.insert_session(&SessionMetrics::default()).unwrap();
+
+        let audio_start = DateTime::from_timestamp_micros(1_700_000_000_123_456).unwrap();
+        let segment = SegmentMetrics {
+            session_id: Some(session_id),
+            start_sample: Some(48_000),
+            audio_start: Some(audio_start),
+            ..Default::default()
+        };
+        db.insert_segment(&segment, false).unwrap();
+
+        let segments = db.get_session_segments(session_id).unwrap();
+        assert_eq!(segments[0].start_sample, Some(48_000));
+        assert_eq!(segments[0].audio_start, Some(audio_start));
+    }
+
+    #[test]
+    fn test_segment_inference_metadata_roundtrip() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db_path = tmp_dir.path().join("test_metrics.db");
+        let db = MetricsDatabase::new(&db_path).unwrap();
+
+        let session_id = db
+            .insert_session(&SessionMetrics {
+                session_start: Some(Utc::now()),
+                ..Default::default()
+            })
+            .unwrap();
+
+        let inference = InferenceMetadata {
+            model: "Parakeet-TDT-0.6B".to_string(),
+            quantization: "int8".to_string(),
+            provider: "CPU".to_string(),
+            device: "CPU".to_string(),
+        };
+        for meta in [Some(inference.clone()), None] {
+            let segment = SegmentMetrics {
+                session_id: Some(session_id),
+                timestamp: Some(Utc::now()),
+                inference: meta,
+                ..Default::default()
+            };
+            db.insert_segment(&segment, false).unwrap();
+        }
+
+        let segments = db.get_session_segments(session_id).unwrap();
+        assert_eq!(segments[0].inference.as_ref(), Some(&inference));
+        assert!(segments[1].inference.is_none());
+    }
+
+    #[test]
+    fn test_segment_alternatives() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db = MetricsDatabase::new(tmp_dir.path().join("test_metrics.db")).unwrap();
+
+        let session_id = db
+            .insert_session(&SessionMetrics {
+                session_start: Some(Utc::now()),
+                ..Default::default()
+            })
+            .unwrap();
+        let segment_id = db
+            .insert_segment(
+                &SegmentMetrics {
+                    session_id: Some(session_id),
+                    ..Default::default()
+                },
+                false,
+            )
+            .unwrap();
+
+        let inference = InferenceMetadata {
+            model: "Parakeet-TDT-1.1B-INT8".to_string(),
+            quantization: "int8".to_string(),
+            provider: "CUDA".to_string(),
+            device: "GPU".to_string(),
+        };
+        db.insert_segment_alternative(&SegmentAlternative {
+            alternative_id: None,
+            segment_id,
+            created_at: None,
+            text: "the quick brown fox".to_string(),
+            inference: Some(inference.clone()),
+        })
+        .unwrap();
+
+        let alternatives = db.get_segment_alternatives(segment_id).unwrap();
+        assert_eq!(alternatives.len(), 1);
+        assert_eq!(alternatives[0].text, "the quick brown fox");
+        assert_eq!(alternatives[0].inference.as_ref(), Some(&inference));
+        assert!(db
+            .get_segment_alternatives(segment_id + 1)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_transcription_jobs() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db = MetricsDatabase::new(tmp_dir.path().join("test_metrics.db")).unwrap();
+
+        let first = db
+            .enqueue_transcription_job("/audio/interview.wav", JobPriority::Background)
+            .unwrap()
+            .unwrap();
+        let second = db
+            .enqueue_transcription_job("/audio/memo.mp3", JobPriority::Background)
+            .unwrap()
+            .unwrap();
+        // A path is only ever queued once
+        assert!(db
+            .enqueue_transcription_job("/audio/interview.wav", JobPriority::Background)
+            .unwrap()
+            .is_none());
+
+        db.update_transcription_job(first, JobStatus::Running, Some(61.5), None, None)
+            .unwrap();
+        db.update_transcription_job(first, JobStatus::Done, None, Some(140), None)
+            .unwrap();
+        db.update_transcription_job(second, JobStatus::Running, None, None, None)
+            .unwrap();
+        assert!(db
+            .update_transcription_job(second + 1, JobStatus::Done, None, None, None)
+            .is_err());
+
+        let jobs = db.get_transcription_jobs(10).unwrap();
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].path, "/audio/memo.mp3");
+        assert_eq!(jobs[1].status, JobStatus::Done);
+        assert_eq!(jobs[1].duration_s, Some(61.5));
+        assert_eq!(jobs[1].words, Some(140));
+
+        // Interrupted jobs are picked up again after a restart
+        let unfinished = db.get_unfinished_transcription_jobs().unwrap();
+        assert_eq!(unfinished.len(), 1);
+        assert_eq!(unfinished[0].job_id, second);
+    }
+
+    #[test]
+    fn test_requeue_transcription_job() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db = MetricsDatabase::new(tmp_dir.path().join("test_metrics.db")).unwrap();
+
+        let watched = db
+            .enqueue_transcription_job("/audio/interview.wav", JobPriority::Background)
+            .unwrap()
+            .unwrap();
+        db.update_transcription_job(watched, JobStatus::Failed, None, None, Some("bad header"))
+            .unwrap();
+        let later = db
+            .enqueue_transcription_job("/audio/memo.mp3", JobPriority::Background)
+            .unwrap()
+            .unwrap();
+
+        // Re-transcribing keeps the job ID and jumps the queue
+        let requeued = db
+            .requeue_transcription_job("/audio/interview.wav", JobPriority::Requested)
+            .unwrap();
+        assert_eq!(requeued, Some(watched));
+        let job = db.get_transcription_job(watched).unwrap().unwrap();
+        assert_eq!(job.status, JobStatus::Queued);
+        assert_eq!(job.priority, JobPriority::Requested);
+        assert_eq!(job.error, None);
+
+        let order: Vec<i64> = db
+            .get_unfinished_transcription_jobs()
+            .unwrap()
+            .iter()
+            .map(|job| job.job_id)
+            .collect();
+        assert_eq!(order, vec![watched, later]);
+        assert!(db.get_transcription_job(later + 1).unwrap().is_none());
+
+        // A file is not queued again while it is being transcribed
+        db.update_transcription_job(later, JobStatus::Running, None, None, None)
+            .unwrap();
+        assert!(db
+            .requeue_transcription_job("/audio/memo.mp3", JobPriority::Requested)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_segment_nbest_compressed_round_trip() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db = MetricsDatabase::new(tmp_dir.path().join("test_metrics.db")).unwrap();
+
+        let session_id = db
+            .insert_session(&SessionMetrics {
+                session_start: Some(Utc::now()),
+                ..Default::default()
+            })
+            .unwrap();
+        let segment = SegmentMetrics {
+            session_id: Some(session_id),
+            text: "call arkon".to_string(),
+            nbest: vec!["call archon".to_string(), "call our con".to_string()],
+            ..Default::default()
+        };
+
+        let stored = db.insert_segment(&segment, true).unwrap();
+        assert_eq!(db.get_segment_nbest(stored).unwrap(), segment.nbest);
+        let segments = db.get_session_segments(session_id).unwrap();
+        assert_eq!(segments[0].nbest, segment.nbest);
+
+        // Alternatives are transcript text: not kept when text storage is off
+        let ephemeral = db.insert_segment(&segment, false).unwrap();
+        assert!(db.get_segment_nbest(ephemeral).unwrap().is_empty());
+        assert!(db.get_segment_nbest(ephemeral + 1).is_err());
+    }
+
+    #[test]
+    fn test_masked_word_counts_round_trip() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db = MetricsDatabase::new(tmp_dir.path().join("test_metrics.db")).unwrap();
+
+        let mut session = SessionMetrics {
+            session_start: Some(Utc::now()),
+            ..Default::default()
+        };
+        let session_id = db.insert_session(&session).unwrap();
+        db.insert_segment(
+            &SegmentMetrics {
+                session_id: Some(session_id),
+                masked_words: 2,
+                ..Default::default()
+            },
+            false,
+        )
+        .unwrap();
+        session.masked_words_count = 2;
+        db.update_session(session_id, &session).unwrap();
+
+        assert_eq!(
+            db.get_session_segments(session_id).unwrap()[0].masked_words,
+            2
+        );
+        assert_eq!(db.get_recent_sessions(1).unwrap()[0].masked_words_count, 2);
+    }
+
+    #[test]
+    fn test_prosody_round_trip() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db = MetricsDatabase::new(tmp_dir.path().join("test_metrics.db")).unwrap();
+
+        let session_id = db.insert_session(&SessionMetrics::default()).unwrap();
+        let prosody = ProsodyMetrics {
+            pitch_std_semitones: None,
+            energy_dbfs: -24.5,
+            speaking_rate_wpm: 172.0,
+        };
+        for prosody in [Some(prosody), None] {
+            db.insert_segment(
+                &SegmentMetrics {
+                    session_id: Some(session_id),
+                    prosody,
+                    ..Default::default()
+                },
+                false,
+            )
+            .unwrap();
+        }
+
+        let segments = db.get_session_segments(session_id).unwrap();
+        assert_eq!(segments[0].prosody, Some(prosody));
+        assert_eq!(segments[1].prosody, None);
+    }
+
+    #[test]
+    fn test_speaker_round_trip() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db = MetricsDatabase::new(tmp_dir.path().join("test_metrics.db")).unwrap();
+
+        let session_id = db.insert_session(&SessionMetrics::default()).unwrap();
+        for speaker in [Some(2), None] {
+            db.insert_segment(
+                &SegmentMetrics {
+                    session_id: Some(session_id),
+                    speaker,
+                    ..Default::default()
+                },
+                false,
+            )
+            .unwrap();
+        }
+
+        let segments = db.get_session_segments(session_id).unwrap();
+        assert_eq!(segments[0].speaker, Some(2));
+        assert_eq!(segments[1].speaker, None);
+    }
+
+    #[test]
+    fn test_same_audio_in_two_sessions_kept_apart() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db = MetricsDatabase::new(tmp_dir.path().join("test_metrics.db")).unwrap();
+
+        // Digital silence captured at the same offset in two sessions
+        let silence = vec![0.0f32; 8_000];
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            let session_id = db
+                .insert_session(&SessionMetrics {
+                    session_start: Some(Utc::now()),
+                    ..Default::default()
+                })
+                .unwrap();
+            let segment = SegmentMetrics {
+                session_id: Some(session_id),
+                start_sample: Some(0),
+                content_hash: Some(crate::audio_content_hash(session_id, 0, &silence)),
+                ..Default::default()
+            };
+            let (id, inserted) = db.insert_segment_checked(&segment, false).unwrap();
+            assert!(inserted);
+            ids.push(id);
+        }
+        assert_ne!(ids[0], ids[1]);
+    }
+
+    #[test]
+    fn test_duplicate_segments_counted_once() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db = MetricsDatabase::new(tmp_dir.path().join("test_metrics.db")).unwrap();
+
+        let session_id = db
+            .insert_session(&SessionMetrics {
+                session_start: Some(Utc::now()),
+                ..Default::default()
+            })
+            .unwrap();
+        let hashed = SegmentMetrics {
+            session_id: Some(session_id),
+            words: 5,
+            content_hash: Some("audio:0123456789abcdef".to_string()),
+            ..Default::default()
+        };
+        let (first, inserted) = db.insert_segment_checked(&hashed, false).unwrap();
+        assert!(inserted);
+        assert_eq!(
+            db.insert_segment_checked(&hashed, false).unwrap(),
+            (first, false)
+        );
+
+        // Rows from before content hashes, double-flushed
+        let legacy = SegmentMetrics {
+            session_id: Some(session_id),
+            words: 10,
+            characters: 50,
+            start_sample: Some(16_000),
+            ..Default::default()
+        };
+        db.insert_segment(&legacy, false).unwrap();
+        db.insert_segment(&legacy, false).unwrap();
+        db.update_session(
+            session_id,
+            &SessionMetrics {
+                session_end: Some(Utc::now()),
+                active_dictation_time_s: 60.0,
+                words_dictated: 25,
+                characters_typed: 100,
+                segments_processed: 3,
+                words_per_minute: 25.0,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        db.recalculate_lifetime_stats().unwrap();
+
+        let preview = db.dedupe_segments(true).unwrap();
+        assert_eq!(preview.duplicate_segments, 1);
+        assert_eq!(db.get_session_segments(session_id).unwrap().len(), 3);
+
+        let report = db.dedupe_segments(false).unwrap();
+        assert_eq!(
+            report,
+            DedupeReport {
+                duplicate_segments: 1,
+                sessions_affected: 1,
+                words_removed: 10,
+                dry_run: false,
+            }
+        );
+        let session = db.get_session(session_id).unwrap().unwrap();
+        assert_eq!(session.words_dictated, 15);
+        assert_eq!(session.segments_processed, 2);
+        assert_eq!(session.words_per_minute, 15.0);
+        assert_eq!(db.get_lifetime_stats().unwrap().total_words, 15);
+        assert_eq!(db.dedupe_segments(false).unwrap().duplicate_segments, 0);
+    }
+
+    #[test]
+    fn test_recompute_stats_repairs_sessions() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db = MetricsDatabase::new(tmp_dir.path().join("test_metrics.db")).unwrap();
+        let long_ago = Utc::now() - chrono::Duration::days(2);
+
+        // Daemon killed mid-session: never ended, totals never written
+        let dangling = db
+            .insert_session(&SessionMetrics {
+                session_start: Some(long_ago),
+                ..Default::default()
+            })
+            .unwrap();
+        for words in [30, 20] {
+            db.insert_segment(
+                &SegmentMetrics {
+                    session_id: Some(dangling),
+                    timestamp: Some(long_ago + chrono::Duration::seconds(60)),
+                    duration_s: 15.0,
+                    words,
+                    ..Default::default()
+                },
+                false,
+            )
+            .unwrap();
+        }
+
+        // Session still in progress: left alone
+        db.insert_session(&SessionMetrics {
+            session_start: Some(Utc::now()),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let broken = db
+            .insert_session(&SessionMetrics {
+                session_start: Some(long_ago),
+                ..Default::default()
+            })
+            .unwrap();
+        db.update_session(
+            broken,
+            &SessionMetrics {
+                session_end: Some(long_ago + chrono::Duration::seconds(90)),
+                total_duration_s: 90.0,
+                active_dictation_time_s: 30.0,
+                words_dictated: 50,
+                words_per_minute: 9000.0,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let check = db.recompute_stats(false).unwrap();
+        assert_eq!(check.dangling_sessions, vec![dangling]);
+        assert_eq!(check.impossible_wpm, vec![broken]);
+        assert!(check.null_durations.is_empty());
+        assert_eq!(check.total_words_after, 50);
+
+        let repaired = db.recompute_stats(true).unwrap();
+        assert!(!repaired.is_clean());
+        assert_eq!(repaired.total_words_after, 100);
+        assert_eq!(repaired.total_sessions_after, 2);
+
+        let session = db.get_session(dangling).unwrap().unwrap();
+        assert_eq!(session.words_dictated, 50);
+        assert_eq!(session.segments_processed, 2);
+        assert_eq!(session.active_dictation_time_s, 30.0);
+        assert_eq!(session.words_per_minute, 100.0);
+        assert_eq!(
+            db.get_session(broken).unwrap().unwrap().words_per_minute,
+            100.0
+        );
+        assert!(db.recompute_stats(false).unwrap().is_clean());
+    }
+
+    #[test]
+    fn test_backfill_latency_percentiles() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db = MetricsDatabase::new(tmp_dir.path().join("test_metrics.db")).unwrap();
+        let start = Utc::now() - chrono::Duration::days(1);
+
+        let mut sessions = Vec::new();
+        for median_latency_ms in [0.0, 999.0] {
+            let id = db
+                .insert_session(&SessionMetrics {
+                    session_start: Some(start),
+                    ..Default::default()
+                })
+                .unwrap();
+            for total_latency_ms in [400.0, 100.0, 300.0, 200.0] {
+                db.insert_segment(
+                    &SegmentMetrics {
+                        session_id: Some(id),
+                        timestamp: Some(start),
+                        total_latency_ms,
+                        ..Default::default()
+                    },
+                    false,
+                )
+                .unwrap();
+            }
+            db.update_session(
+                id,
+                &SessionMetrics {
+                    session_end: Some(start + chrono::Duration::seconds(60)),
+                    median_latency_ms,
+                    p95_latency_ms: median_latency_ms,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            sessions.push(id);
+        }
+        let (missing, stale) = (sessions[0], sessions[1]);
+
+        let preview = db.backfill_latency_percentiles(false, true).unwrap();
+        assert_eq!(preview.sessions_updated, 1);
+        assert_eq!(preview.segments_read, 4);
+        assert_eq!(
+            db.get_session(missing).unwrap().unwrap().median_latency_ms,
+            0.0
+        );
+
+        db.backfill_latency_percentiles(false, false).unwrap();
+        let session = db.get_session(missing).unwrap().unwrap();
+        assert_eq!(session.median_latency_ms, 250.0);
+        assert!((session.p95_latency_ms - 385.0).abs() < 1e-9);
+        assert_eq!(
+            db.get_session(stale).unwrap().unwrap().median_latency_ms,
+            999.0
+        );
+        assert_eq!(
+            db.backfill_latency_percentiles(false, false)
+                .unwrap()
+                .sessions_updated,
+            0
+        );
+
+        let report = db.backfill_latency_percentiles(true, false).unwrap();
+        assert_eq!(report.sessions_updated, 2);
+        assert_eq!(
+            db.get_session(stale).unwrap().unwrap().median_latency_ms,
+            250.0
+        );
+    }
+
+    /// Ended session starting at `start` with one segment per `(offset, words)`
+    fn ended_session(
+        db: &MetricsDatabase,
+        start: DateTime<Utc>,
+        length_s: i64,
+        segments: &[(i64, i32)],
+    ) -> i64 {
+        let id = db
+            .insert_session(&SessionMetrics {
+                session_start: Some(start),
+                ..Default::default()
+            })
+            .unwrap();
+        for &(offset, words) in segments {
+            db.insert_segment(
+                &SegmentMetrics {
+                    session_id: Some(id),
+                    timestamp: Some(start + chrono::Duration::seconds(offset)),
+                    duration_s: 5.0,
+                    words,
+                    total_latency_ms: 100.0 * words as f64,
+                    ..Default::default()
+                },
+                false,
+            )
+            .unwrap();
+        }
+        db.update_session(
+            id,
+            &SessionMetrics {
+                session_end: Some(start + chrono::Duration::seconds(length_s)),
+                total_duration_s: length_s as f64,
+                cpu_usage_mean_percent: 10.0,
+                cpu_usage_peak_percent: 20.0,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        id
+    }
+
+    #[test]
+    fn test_merge_sessions() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db = MetricsDatabase::new(tmp_dir.path().join("test_metrics.db")).unwrap();
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+
+        let first = ended_session(&db, start, 60, &[(10, 10), (20, 20)]);
+        let fragment = ended_session(&db, start + chrono::Duration::seconds(90), 5, &[(3, 5)]);
+        assert!(db.merge_sessions(&[first]).is_err());
+        assert!(db.merge_sessions(&[first, 999]).is_err());
+
+        assert_eq!(db.merge_sessions(&[fragment, first]).unwrap(), first);
+        assert!(db.get_session(fragment).unwrap().is_none());
+        let merged = db.get_session(first).unwrap().unwrap();
+        assert_eq!(merged.segments_processed, 3);
+        assert_eq!(merged.words_dictated, 35);
+        assert_eq!(merged.total_duration_s, 95.0);
+        assert_eq!(merged.active_dictation_time_s, 15.0);
+        assert_eq!(merged.pause_time_s, 80.0);
+        assert_eq!(merged.words_per_minute, 140.0);
+        assert_eq!(merged.median_latency_ms, 1000.0);
+        assert_eq!(merged.cpu_usage_peak_percent, 20.0);
+        assert_eq!(db.get_session_segments(first).unwrap().len(), 3);
+
+        let lifetime = db.get_lifetime_stats().unwrap();
+        assert_eq!(lifetime.total_words, 35);
+        assert_eq!(lifetime.total_sessions, 1);
+    }
+
+    #[test]
+    fn test_split_session() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db = MetricsDatabase::new(tmp_dir.path().join("test_metrics.db")).unwrap();
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let id = ended_session(&db, start, 600, &[(10, 10), (20, 20), (500, 30)]);
+        let at = start.timestamp() as f64 + 300.0;
+
+        assert!(db.split_session(id, start.timestamp() as f64).is_err());
+        assert!(db.split_session(id, at + 1000.0).is_err());
+
+        let tail = db.split_session(id, at).unwrap();
+        let head = db.get_session(id).unwrap().unwrap();
+        assert_eq!(head.words_dictated, 30);
+        assert_eq!(head.total_duration_s, 300.0);
+        assert_eq!(head.words_per_minute, 180.0);
+        assert_eq!(head.median_latency_ms, 1500.0);
+
+        let tail = db.get_session(tail).unwrap().unwrap();
+        assert_eq!(tail.session_start.unwrap().timestamp() as f64, at);
+        assert_eq!(tail.segments_processed, 1);
+        assert_eq!(tail.words_dictated, 30);
+        assert_eq!(tail.total_duration_s, 300.0);
+        assert_eq!(tail.pause_time_s, 295.0);
+        assert_eq!(tail.cpu_usage_mean_percent, 10.0);
+
+        let lifetime = db.get_lifetime_stats().unwrap();
+        assert_eq!(lifetime.total_words, 60);
+        assert_eq!(lifetime.total_sessions, 2);
+    }
+
+    #[test]
+    fn test_archive_and_trash_sessions() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db = MetricsDatabase::new(tmp_dir.path().join("test_metrics.db")).unwrap();
+        let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let kept = ended_session(&db, start, 600, &[(10, 10)]);
+        let private = ended_session(&db, start + chrono::Duration::hours(1), 600, &[(10, 20)]);
+        let open = db.insert_session(&SessionMetrics::default()).unwrap();
+        assert!(db.delete_session(open).is_err());
+        assert!(db.archive_session(999).is_err());
+
+        db.archive_session(private).unwrap();
+        assert_eq!(db.get_lifetime_stats().unwrap().total_sessions, 1);
+        assert!(db.get_session(private).unwrap().is_some());
+        let listed: Vec<Option<i64>> = db
+            .get_recent_sessions(10)
+            .unwrap()
+            .iter()
+            .map(|s| s.session_id)
+            .collect();
+        assert!(!listed.contains(&Some(private)));
+        db.unarchive_session(private).unwrap();
+        assert_eq!(db.get_lifetime_stats().unwrap().total_sessions, 2);
+
+        db.delete_session(private).unwrap();
+        assert_eq!(db.get_lifetime_stats().unwrap().total_segments, 1);
+        assert_eq!(db.purge_trash(TRASH_RETENTION_DAYS).unwrap().sessions, 0);
+        db.restore_session(private).unwrap();
+        assert_eq!(db.get_lifetime_stats().unwrap().total_segments, 2);
+
+        let private_segments: Vec<Option<i64>> = db
+            .get_session_segments(private)
+            .unwrap()
+            .iter()
+            .map(|s| s.segment_id)
+            .collect();
+        db.delete_session(private).unwrap();
+        let purged = db.purge_trash(0).unwrap();
+        assert_eq!(purged.sessions, 1);
+        assert_eq!(
+            purged
+                .segment_ids
+                .iter()
+                .copied()
+                .map(Some)
+                .collect::<Vec<_>>(),
+            private_segments
+        );
+        assert!(db.get_session(private).unwrap().is_none());
+        assert!(db.get_session_segments(private).unwrap().is_empty());
+        assert_eq!(db.get_session_segments(kept).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_sink_stats() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db = MetricsDatabase::new(tmp_dir.path().join("test_metrics.db")).unwrap();
+        let now = Utc::now();
+        let delivery = |sink: &str, latency_ms: f64, error: Option<&str>| SinkDelivery {
+            session_id: Some(1),
+            timestamp: Some(now),
+            sink: sink.to_string(),
+            latency_ms,
+            error: error.map(str::to_string),
+        };
+        db.insert_sink_delivery(&delivery("inject", 20.0, None))
+            .unwrap();
+        db.insert_sink_delivery(&delivery("inject", 40.0, Some("wtype not found")))
+            .unwrap();
+        db.insert_sink_delivery(&delivery("note", 2.0, None))
+            .unwrap();
+
+        let hour = chrono::Duration::hours(1);
+        let stats = db.get_sink_stats_between(now - hour, now + hour).unwrap();
+        assert_eq!(
+            stats[0],
+            SinkStats {
+                sink: "inject".to_string(),
+                deliveries: 2,
+                failures: 1,
+                avg_latency_ms: 30.0,
+                max_latency_ms: 40.0,
+            }
+        );
+        assert_eq!(stats[1].sink, "note");
+        assert!(db
+            .get_sink_stats_between(now + hour, now + hour * 2)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_practice_sessions_newest_first() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db = MetricsDatabase::new(tmp_dir.path().join("test_metrics.db")).unwrap();
+        let now = Utc::now();
+        let practice = |minutes_ago: i64, score: f64| PracticeSession {
+            practice_id: None,
+            started_at: now - chrono::Duration::minutes(minutes_ago + 5),
+            ended_at: now - chrono::Duration::minutes(minutes_ago),
+            script_words: 120,
+            words_read: 100,
+            words_matched: 90,
+            accuracy: 0.9,
+            pronunciation_score: score,
+        };
+        db.insert_practice_session(&practice(60, 71.5)).unwrap();
+        let latest = db.insert_practice_session(&practice(0, 84.0)).unwrap();
+
+        let history = db.get_practice_sessions(10).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].practice_id, Some(latest));
+        assert_eq!(history[0].pronunciation_score, 84.0);
+        assert_eq!(history[1].words_matched, 90);
+        assert_eq!(db.get_practice_sessions(1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_inference_column_added_to_existing_database() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db_path = tmp_dir.path().join("test_metrics.db");
+
+        // Simulate a database created before the column existed
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute(
+                "CREATE TABLE segments (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    session_id INTEGER NOT NULL,
+                    timestamp REAL NOT NULL
+                )",
+                [],
+            )
+            .unwrap();
+        }
+
+        let _db = MetricsDatabase::new(&db_path).unwrap();
+
+        let conn = Connection::open(&db_path).unwrap();
+        let has_column: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('segments') WHERE name = 'inference_metadata'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(has_column);
     }
 
     #[test]
@@ -932,6 +3056,50 @@ mod tests {
         assert_eq!(retrieved.average_wpm, 75.5);
     }
 
+    #[test]
+    fn test_latency_warnings_and_breakdown() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db = MetricsDatabase::new(tmp_dir.path().join("test_metrics.db")).unwrap();
+
+        db.increment_high_latency_warnings().unwrap();
+        db.increment_high_latency_warnings().unwrap();
+        assert_eq!(db.get_lifetime_stats().unwrap().high_latency_warnings, 2);
+
+        let session_id = db.insert_session(&SessionMetrics::default()).unwrap();
+        let now = Utc::now();
+        for (stt, transform_us) in [(400.0, 1000.0), (600.0, 3000.0)] {
+            let segment = SegmentMetrics {
+                session_id: Some(session_id),
+                timestamp: Some(now),
+                vad_latency_ms: 10.0,
+                stt_latency_ms: stt,
+                transform_latency_us: transform_us,
+                total_latency_ms: 10.0 + stt + transform_us / 1000.0,
+                ..Default::default()
+            };
+            db.insert_segment(&segment, false).unwrap();
+        }
+
+        let (breakdown, count) = db
+            .get_latency_breakdown_between(
+                now - chrono::Duration::hours(1),
+                now + chrono::Duration::hours(1),
+            )
+            .unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(breakdown.stt_ms, 500.0);
+        assert_eq!(breakdown.transform_ms, 2.0);
+        assert_eq!(breakdown.total_ms, 512.0);
+
+        let (_, empty) = db
+            .get_latency_breakdown_between(
+                now - chrono::Duration::days(14),
+                now - chrono::Duration::days(7),
+            )
+            .unwrap();
+        assert_eq!(empty, 0);
+    }
+
     #[test]
     fn test_get_sessions_last_n_days() {
         let tmp_dir = TempDir::new().unwrap();
@@ -976,6 +3144,48 @@ mod tests {
         assert_eq!(deleted, 0);
     }
 
+    #[test]
+    fn test_compare_sessions() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db_path = tmp_dir.path().join("test_metrics.db");
+        let db = MetricsDatabase::new(&db_path).unwrap();
+
+        let mut ids = Vec::new();
+        for (wpm, latency, text) in [
+            (100.0, 500.0, "Hello world, this is Rust."),
+            (120.0, 300.0, "hello rust world again"),
+        ] {
+            let mut session = SessionMetrics {
+                session_start: Some(Utc::now()),
+                ..Default::default()
+            };
+            let id = db.insert_session(&session).unwrap();
+            session.words_per_minute = wpm;
+            session.average_latency_ms = latency;
+            db.update_session(id, &session).unwrap();
+
+            let segment = SegmentMetrics {
+                session_id: Some(id),
+                timestamp: Some(Utc::now()),
+                text: text.to_string(),
+                ..Default::default()
+            };
+            db.insert_segment(&segment, true).unwrap();
+            ids.push(id);
+        }
+
+        let cmp = db.compare_sessions(ids[0], ids[1]).unwrap();
+        assert_eq!(cmp.wpm_delta, 20.0);
+        assert_eq!(cmp.average_latency_delta_ms, -200.0);
+        // {hello, world, this, is, rust} vs {hello, rust, world, again}
+        assert_eq!(cmp.vocabulary_a, 5);
+        assert_eq!(cmp.vocabulary_b, 4);
+        assert_eq!(cmp.shared_vocabulary, 3);
+        assert!((cmp.vocabulary_overlap - 0.5).abs() < 1e-9);
+
+        assert!(db.compare_sessions(ids[0], 9999).is_err());
+    }
+
     #[test]
     fn test_database_size() {
         let tmp_dir = TempDir::new().unwrap();
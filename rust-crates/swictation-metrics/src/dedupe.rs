@@ -0,0 +1,106 @@
+//! Content hashes that identify duplicate segments
+//!
+//! Each stored segment carries a `content_hash` with a unique index, so the
+//! same speech can only be counted once even if it reaches the database
+//! twice (as it did with past double-flush bugs). Both hashes cover the
+//! session and the capture position, which a re-processed segment repeats
+//! exactly while two genuine utterances never share; with recordings enabled
+//! the hash also covers the audio itself, otherwise the text. The index
+//! spans every session, so identical audio (digital silence, a replayed
+//! test clip) in two sessions must still hash differently.
+//!
+//! Hashes are FNV-1a, which is stable across builds and platforms.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::SegmentMetrics;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes.into_iter().fold(FNV_OFFSET, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Content hash of a segment's speech audio at `start_sample` in a session
+pub fn audio_content_hash(session_id: i64, start_sample: u64, samples: &[f32]) -> String {
+    let position = session_id
+        .to_le_bytes()
+        .into_iter()
+        .chain(start_sample.to_le_bytes());
+    let audio = samples.iter().flat_map(|s| s.to_bits().to_le_bytes());
+    format!("audio:{:016x}", fnv1a(position.chain(audio)))
+}
+
+/// Fallback content hash when no audio hash was supplied
+///
+/// `None` when the segment has no capture time; the wall-clock `timestamp`
+/// is too coarse to tell two short utterances apart.
+pub fn text_content_hash(segment: &SegmentMetrics) -> Option<String> {
+    let start = segment.audio_start?;
+    let key = format!(
+        "{}|{}|{}",
+        segment.session_id.unwrap_or_default(),
+        start.timestamp_micros(),
+        segment.text
+    );
+    Some(format!("text:{:016x}", fnv1a(key.bytes())))
+}
+
+/// Outcome of [`crate::MetricsDatabase::dedupe_segments`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DedupeReport {
+    /// Duplicate segment rows (removed unless this was a dry run)
+    pub duplicate_segments: usize,
+    pub sessions_affected: usize,
+    /// Words the duplicates had added to session and lifetime totals
+    pub words_removed: i64,
+    pub dry_run: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_text_hash_pins_capture_position() {
+        let segment = SegmentMetrics {
+            session_id: Some(3),
+            text: "hello world".to_string(),
+            audio_start: Some(Utc.timestamp_opt(1_700_000_000, 250_000_000).unwrap()),
+            ..Default::default()
+        };
+        let same = segment.clone();
+        let later = SegmentMetrics {
+            audio_start: Some(Utc.timestamp_opt(1_700_000_009, 0).unwrap()),
+            ..segment.clone()
+        };
+
+        assert_eq!(text_content_hash(&segment), text_content_hash(&same));
+        assert_ne!(text_content_hash(&segment), text_content_hash(&later));
+        assert_eq!(text_content_hash(&SegmentMetrics::default()), None);
+    }
+
+    #[test]
+    fn test_audio_hash_is_stable() {
+        assert_eq!(
+            audio_content_hash(1, 16_000, &[0.5]),
+            audio_content_hash(1, 16_000, &[0.5])
+        );
+        assert_ne!(
+            audio_content_hash(1, 0, &[0.5]),
+            audio_content_hash(1, 0, &[0.25])
+        );
+    }
+
+    #[test]
+    fn test_audio_hash_pins_session_and_position() {
+        let silence = [0.0; 160];
+        let hash = audio_content_hash(1, 0, &silence);
+        assert_ne!(hash, audio_content_hash(2, 0, &silence));
+        assert_ne!(hash, audio_content_hash(1, 160, &silence));
+    }
+}
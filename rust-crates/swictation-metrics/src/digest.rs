@@ -0,0 +1,343 @@
+//! Weekly summary digest
+//!
+//! Aggregates one ISO week of sessions into a Markdown report with totals,
+//! personal bests and week-over-week trends. Topic and correction data come
+//! from other crates (context learning, corrections engine) and are attached
+//! by the caller.
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+use crate::database::MetricsDatabase;
+use crate::latency::LatencyBreakdown;
+use crate::models::{SessionMetrics, SinkStats};
+
+/// Number of sessions listed under "Best sessions"
+const BEST_SESSIONS: usize = 3;
+
+/// A learned correction to mention in the digest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DigestCorrection {
+    pub original: String,
+    pub corrected: String,
+}
+
+/// One week of dictation activity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeeklyDigest {
+    /// ISO week label, e.g. "2025-W03"
+    pub week: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+
+    // Totals
+    pub total_sessions: usize,
+    pub total_words: i64,
+    pub total_minutes: f64,
+
+    // Averages (this week vs previous week)
+    pub average_wpm: f64,
+    pub previous_average_wpm: f64,
+    pub average_latency_ms: f64,
+    pub previous_average_latency_ms: f64,
+
+    /// (session_id, wpm, words) of the fastest sessions this week
+    pub best_sessions: Vec<(i64, f64, i32)>,
+
+    /// Average per-stage latency of this week's segments
+    #[serde(default)]
+    pub latency_breakdown: LatencyBreakdown,
+    /// Number of segments `latency_breakdown` averages over
+    #[serde(default)]
+    pub latency_segments: i64,
+    /// Deliveries per output sink this week
+    #[serde(default)]
+    pub sinks: Vec<SinkStats>,
+
+    pub top_topics: Vec<String>,
+    pub new_corrections: Vec<DigestCorrection>,
+}
+
+impl WeeklyDigest {
+    /// Start (Monday 00:00 UTC) of the ISO week containing `date`
+    pub fn week_start(date: NaiveDate) -> DateTime<Utc> {
+        let monday = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+        Utc.from_utc_datetime(&monday.and_hms_opt(0, 0, 0).unwrap())
+    }
+
+    /// ISO week label for the week starting at `start`
+    pub fn week_label(start: DateTime<Utc>) -> String {
+        let iso = start.iso_week();
+        format!("{}-W{:02}", iso.year(), iso.week())
+    }
+
+    /// Build a digest from this week's and the previous week's sessions
+    pub fn from_sessions(
+        period_start: DateTime<Utc>,
+        current: &[SessionMetrics],
+        previous: &[SessionMetrics],
+    ) -> Self {
+        let mut best: Vec<(i64, f64, i32)> = current
+            .iter()
+            .filter(|s| s.words_dictated > 0)
+            .filter_map(|s| {
+                s.session_id
+                    .map(|id| (id, s.words_per_minute, s.words_dictated))
+            })
+            .collect();
+        best.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        best.truncate(BEST_SESSIONS);
+
+        Self {
+            week: Self::week_label(period_start),
+            period_start,
+            period_end: period_start + Duration::days(7),
+            total_sessions: current.len(),
+            total_words: current.iter().map(|s| s.words_dictated as i64).sum(),
+            total_minutes: current.iter().map(|s| s.total_duration_s).sum::<f64>() / 60.0,
+            average_wpm: mean(current.iter().map(|s| s.words_per_minute)),
+            previous_average_wpm: mean(previous.iter().map(|s| s.words_per_minute)),
+            average_latency_ms: mean(current.iter().map(|s| s.average_latency_ms)),
+            previous_average_latency_ms: mean(previous.iter().map(|s| s.average_latency_ms)),
+            best_sessions: best,
+            latency_breakdown: LatencyBreakdown::default(),
+            latency_segments: 0,
+            sinks: Vec::new(),
+            top_topics: Vec::new(),
+            new_corrections: Vec::new(),
+        }
+    }
+
+    /// Load the week starting at `period_start` (and the week before) from the database
+    pub fn generate(db: &MetricsDatabase, period_start: DateTime<Utc>) -> Result<Self> {
+        let period_end = period_start + Duration::days(7);
+        let current = db.get_sessions_between(period_start, period_end)?;
+        let previous = db.get_sessions_between(period_start - Duration::days(7), period_start)?;
+        let (latency_breakdown, latency_segments) =
+            db.get_latency_breakdown_between(period_start, period_end)?;
+
+        Ok(Self {
+            latency_breakdown,
+            latency_segments,
+            sinks: db.get_sink_stats_between(period_start, period_end)?,
+            ..Self::from_sessions(period_start, &current, &previous)
+        })
+    }
+
+    /// Render as Markdown
+    pub fn to_markdown(&self) -> String {
+        let mut md = String::new();
+
+        let _ = writeln!(md, "# Swictation weekly summary – {}", self.week);
+        let _ = writeln!(
+            md,
+            "\n_{} to {}_\n",
+            self.period_start.format("%Y-%m-%d"),
+            (self.period_end - Duration::days(1)).format("%Y-%m-%d")
+        );
+
+        let _ = writeln!(md, "## Totals\n");
+        let _ = writeln!(md, "- Sessions: {}", self.total_sessions);
+        let _ = writeln!(md, "- Words dictated: {}", self.total_words);
+        let _ = writeln!(md, "- Dictation time: {:.1} min\n", self.total_minutes);
+
+        let _ = writeln!(md, "## Trends\n");
+        let _ = writeln!(
+            md,
+            "- Average WPM: {:.1} ({})",
+            self.average_wpm,
+            trend(self.average_wpm, self.previous_average_wpm)
+        );
+        let _ = writeln!(
+            md,
+            "- Average latency: {:.0} ms ({})\n",
+            self.average_latency_ms,
+            trend(self.average_latency_ms, self.previous_average_latency_ms)
+        );
+
+        if !self.best_sessions.is_empty() {
+            let _ = writeln!(md, "## Best sessions\n");
+            for (id, wpm, words) in &self.best_sessions {
+                let _ = writeln!(md, "- Session #{}: {:.1} WPM, {} words", id, wpm, words);
+            }
+            md.push('\n');
+        }
+
+        if self.latency_segments > 0 {
+            let _ = writeln!(md, "## Where your latency goes\n");
+            let total = self.latency_breakdown.total_ms;
+            for (stage, ms) in self.latency_breakdown.stages() {
+                let share = if total > 0.0 { ms / total * 100.0 } else { 0.0 };
+                let _ = writeln!(md, "- {}: {:.1} ms ({:.0}%)", stage.as_str(), ms, share);
+            }
+            let _ = writeln!(
+                md,
+                "- Total: {:.1} ms average over {} segments\n",
+                total, self.latency_segments
+            );
+        }
+
+        if !self.sinks.is_empty() {
+            let _ = writeln!(md, "## Output sinks\n");
+            for sink in &self.sinks {
+                let _ = write!(
+                    md,
+                    "- {}: {} deliveries, {:.1} ms average ({:.1} ms max)",
+                    sink.sink, sink.deliveries, sink.avg_latency_ms, sink.max_latency_ms
+                );
+                if sink.failures > 0 {
+                    let _ = write!(md, ", {} failed", sink.failures);
+                }
+                md.push('\n');
+            }
+            md.push('\n');
+        }
+
+        if !self.top_topics.is_empty() {
+            let _ = writeln!(md, "## Top topics\n");
+            for topic in &self.top_topics {
+                let _ = writeln!(md, "- {}", topic);
+            }
+            md.push('\n');
+        }
+
+        if !self.new_corrections.is_empty() {
+            let _ = writeln!(md, "## New corrections learned\n");
+            for c in &self.new_corrections {
+                let _ = writeln!(md, "- \"{}\" → \"{}\"", c.original, c.corrected);
+            }
+            md.push('\n');
+        }
+
+        md
+    }
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0usize), |(s, c), v| (s + v, c + 1));
+    if count > 0 {
+        sum / count as f64
+    } else {
+        0.0
+    }
+}
+
+/// Describe change relative to the previous week
+fn trend(current: f64, previous: f64) -> String {
+    if previous <= 0.0 {
+        return "no data for previous week".to_string();
+    }
+    let change = (current - previous) / previous * 100.0;
+    format!("{:+.1}% vs previous week", change)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{SegmentMetrics, SinkDelivery};
+    use tempfile::TempDir;
+
+    fn session(id: i64, wpm: f64, words: i32) -> SessionMetrics {
+        SessionMetrics {
+            session_id: Some(id),
+            words_per_minute: wpm,
+            words_dictated: words,
+            total_duration_s: 120.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_week_start_and_label() {
+        // 2025-01-16 is a Thursday in ISO week 3
+        let start = WeeklyDigest::week_start(NaiveDate::from_ymd_opt(2025, 1, 16).unwrap());
+        assert_eq!(
+            start.date_naive(),
+            NaiveDate::from_ymd_opt(2025, 1, 13).unwrap()
+        );
+        assert_eq!(WeeklyDigest::week_label(start), "2025-W03");
+    }
+
+    #[test]
+    fn test_from_sessions() {
+        let start = WeeklyDigest::week_start(NaiveDate::from_ymd_opt(2025, 1, 16).unwrap());
+        let current = vec![
+            session(1, 100.0, 50),
+            session(2, 140.0, 70),
+            session(3, 120.0, 0),
+            session(4, 130.0, 30),
+            session(5, 90.0, 10),
+        ];
+        let previous = vec![session(0, 100.0, 10)];
+
+        let digest = WeeklyDigest::from_sessions(start, &current, &previous);
+        assert_eq!(digest.total_sessions, 5);
+        assert_eq!(digest.total_words, 160);
+        assert_eq!(digest.total_minutes, 10.0);
+        assert_eq!(digest.average_wpm, 116.0);
+        // Empty session 3 is excluded from best sessions
+        let best_ids: Vec<i64> = digest.best_sessions.iter().map(|b| b.0).collect();
+        assert_eq!(best_ids, vec![2, 4, 1]);
+
+        let md = digest.to_markdown();
+        assert!(md.contains("2025-W03"));
+        assert!(md.contains("+16.0% vs previous week"));
+        assert!(!md.contains("## Top topics"));
+    }
+
+    #[test]
+    fn test_generate_from_database() {
+        let tmp_dir = TempDir::new().unwrap();
+        let db = MetricsDatabase::new(tmp_dir.path().join("test_metrics.db")).unwrap();
+
+        let now = Utc::now();
+        let mut s = SessionMetrics {
+            session_start: Some(now),
+            ..Default::default()
+        };
+        let id = db.insert_session(&s).unwrap();
+        s.words_dictated = 42;
+        db.update_session(id, &s).unwrap();
+
+        let digest =
+            WeeklyDigest::generate(&db, WeeklyDigest::week_start(now.date_naive())).unwrap();
+        assert_eq!(digest.total_sessions, 1);
+        assert_eq!(digest.total_words, 42);
+        assert_eq!(digest.latency_segments, 0);
+        assert!(!digest.to_markdown().contains("Where your latency goes"));
+
+        let segment = SegmentMetrics {
+            session_id: Some(id),
+            timestamp: Some(now),
+            vad_latency_ms: 20.0,
+            stt_latency_ms: 180.0,
+            total_latency_ms: 200.0,
+            ..Default::default()
+        };
+        db.insert_segment(&segment, false).unwrap();
+
+        let digest =
+            WeeklyDigest::generate(&db, WeeklyDigest::week_start(now.date_naive())).unwrap();
+        assert_eq!(digest.latency_segments, 1);
+        let md = digest.to_markdown();
+        assert!(md.contains("## Where your latency goes"));
+        assert!(md.contains("- STT: 180.0 ms (90%)"));
+        assert!(!md.contains("## Output sinks"));
+
+        db.insert_sink_delivery(&SinkDelivery {
+            session_id: Some(id),
+            timestamp: Some(now),
+            sink: "note".to_string(),
+            latency_ms: 1.5,
+            error: Some("disk full".to_string()),
+        })
+        .unwrap();
+        let digest =
+            WeeklyDigest::generate(&db, WeeklyDigest::week_start(now.date_naive())).unwrap();
+        assert!(digest
+            .to_markdown()
+            .contains("- note: 1 deliveries, 1.5 ms average (1.5 ms max), 1 failed"));
+    }
+}
@@ -42,6 +42,7 @@ impl GpuMonitor {
     pub fn new(provider: &str) -> Self {
         let gpu_name = match provider {
             "cuda" => "NVIDIA GPU (CUDA)".to_string(),
+            "rocm" => "AMD GPU (ROCm)".to_string(),
             "directml" => "DirectML GPU".to_string(),
             "coreml" => "Apple Silicon (CoreML)".to_string(),
             "cpu" => "CPU Fallback".to_string(),
@@ -0,0 +1,42 @@
+//! Consistency checks for stored sessions
+//!
+//! Lifetime stats are aggregated from the sessions table, so a session the
+//! daemon never closed, or one written by an older build with broken
+//! accounting, skews every total derived from it. See
+//! [`crate::MetricsDatabase::recompute_stats`].
+
+use serde::{Deserialize, Serialize};
+
+/// Dictation speed no session can genuinely reach
+pub const MAX_PLAUSIBLE_WPM: f64 = 400.0;
+
+/// Seconds without activity before an unfinished session counts as dangling
+/// rather than in progress
+pub const DANGLING_AFTER_S: f64 = 3600.0;
+
+/// Outcome of [`crate::MetricsDatabase::recompute_stats`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    /// Sessions that were never ended (daemon killed mid-session)
+    pub dangling_sessions: Vec<i64>,
+    /// Ended sessions without a total or active duration
+    pub null_durations: Vec<i64>,
+    /// Sessions with a negative WPM, one above [`MAX_PLAUSIBLE_WPM`], or a
+    /// WPM without any active time
+    pub impossible_wpm: Vec<i64>,
+    /// Whether the rows above were repaired
+    pub repaired: bool,
+    pub total_words_before: i64,
+    pub total_words_after: i64,
+    pub total_sessions_before: i32,
+    pub total_sessions_after: i32,
+}
+
+impl IntegrityReport {
+    /// True when no discrepancies were found
+    pub fn is_clean(&self) -> bool {
+        self.dangling_sessions.is_empty()
+            && self.null_durations.is_empty()
+            && self.impossible_wpm.is_empty()
+    }
+}
@@ -0,0 +1,205 @@
+//! Per-stage latency budgets
+//!
+//! A segment's latency is split into VAD, STT, transform and injection
+//! stages. Each stage (and the end-to-end total) has a budget; a segment that
+//! exceeds any of them counts as a high latency warning and is reported with
+//! its full breakdown so the slow stage is obvious.
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::SegmentMetrics;
+
+/// Pipeline stage a latency figure belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LatencyStage {
+    Vad,
+    Stt,
+    Transform,
+    Injection,
+    Total,
+}
+
+impl LatencyStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LatencyStage::Vad => "VAD",
+            LatencyStage::Stt => "STT",
+            LatencyStage::Transform => "Transform",
+            LatencyStage::Injection => "Injection",
+            LatencyStage::Total => "Total",
+        }
+    }
+}
+
+/// Latency budget per stage (milliseconds)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LatencyBudgets {
+    pub vad_ms: f64,
+    pub stt_ms: f64,
+    pub transform_ms: f64,
+    pub injection_ms: f64,
+    pub total_ms: f64,
+}
+
+impl Default for LatencyBudgets {
+    fn default() -> Self {
+        Self {
+            vad_ms: 100.0,
+            stt_ms: 800.0,
+            transform_ms: 10.0,
+            injection_ms: 100.0,
+            total_ms: 1000.0,
+        }
+    }
+}
+
+impl LatencyBudgets {
+    /// Stages of `breakdown` that are over budget (empty if none)
+    pub fn exceeded(&self, breakdown: &LatencyBreakdown) -> Vec<LatencyStage> {
+        [
+            (LatencyStage::Vad, breakdown.vad_ms, self.vad_ms),
+            (LatencyStage::Stt, breakdown.stt_ms, self.stt_ms),
+            (
+                LatencyStage::Transform,
+                breakdown.transform_ms,
+                self.transform_ms,
+            ),
+            (
+                LatencyStage::Injection,
+                breakdown.injection_ms,
+                self.injection_ms,
+            ),
+            (LatencyStage::Total, breakdown.total_ms, self.total_ms),
+        ]
+        .into_iter()
+        .filter(|(_, actual, budget)| actual > budget)
+        .map(|(stage, _, _)| stage)
+        .collect()
+    }
+
+    /// Check a segment against the budgets
+    pub fn check(&self, segment: &SegmentMetrics) -> Option<LatencyWarning> {
+        let breakdown = LatencyBreakdown::from_segment(segment);
+        let exceeded = self.exceeded(&breakdown);
+        if exceeded.is_empty() {
+            return None;
+        }
+        Some(LatencyWarning {
+            exceeded,
+            breakdown,
+            budgets: self.clone(),
+        })
+    }
+}
+
+/// Latency of one segment (or an average over many), per stage in milliseconds
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyBreakdown {
+    pub vad_ms: f64,
+    pub stt_ms: f64,
+    pub transform_ms: f64,
+    pub injection_ms: f64,
+    pub total_ms: f64,
+}
+
+impl LatencyBreakdown {
+    pub fn from_segment(segment: &SegmentMetrics) -> Self {
+        Self {
+            vad_ms: segment.vad_latency_ms,
+            stt_ms: segment.stt_latency_ms,
+            transform_ms: segment.transform_latency_us / 1000.0,
+            injection_ms: segment.injection_latency_ms,
+            total_ms: segment.total_latency_ms,
+        }
+    }
+
+    /// The individual stages (everything except the total)
+    pub fn stages(&self) -> [(LatencyStage, f64); 4] {
+        [
+            (LatencyStage::Vad, self.vad_ms),
+            (LatencyStage::Stt, self.stt_ms),
+            (LatencyStage::Transform, self.transform_ms),
+            (LatencyStage::Injection, self.injection_ms),
+        ]
+    }
+}
+
+/// A segment that went over one or more budgets
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LatencyWarning {
+    pub exceeded: Vec<LatencyStage>,
+    pub breakdown: LatencyBreakdown,
+    pub budgets: LatencyBudgets,
+}
+
+impl LatencyWarning {
+    /// One-line summary, e.g. "STT 950ms > 800ms, Total 1020ms > 1000ms"
+    pub fn summary(&self) -> String {
+        self.exceeded
+            .iter()
+            .map(|stage| {
+                let (actual, budget) = match stage {
+                    LatencyStage::Vad => (self.breakdown.vad_ms, self.budgets.vad_ms),
+                    LatencyStage::Stt => (self.breakdown.stt_ms, self.budgets.stt_ms),
+                    LatencyStage::Transform => {
+                        (self.breakdown.transform_ms, self.budgets.transform_ms)
+                    }
+                    LatencyStage::Injection => {
+                        (self.breakdown.injection_ms, self.budgets.injection_ms)
+                    }
+                    LatencyStage::Total => (self.breakdown.total_ms, self.budgets.total_ms),
+                };
+                format!("{} {:.0}ms > {:.0}ms", stage.as_str(), actual, budget)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(stt_ms: f64, transform_us: f64) -> SegmentMetrics {
+        SegmentMetrics {
+            vad_latency_ms: 20.0,
+            stt_latency_ms: stt_ms,
+            transform_latency_us: transform_us,
+            total_latency_ms: 20.0 + stt_ms + transform_us / 1000.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_within_budget() {
+        let budgets = LatencyBudgets::default();
+        assert!(budgets.check(&segment(400.0, 500.0)).is_none());
+    }
+
+    #[test]
+    fn test_stage_and_total_exceeded() {
+        let budgets = LatencyBudgets::default();
+        let warning = budgets.check(&segment(990.0, 500.0)).unwrap();
+        assert_eq!(
+            warning.exceeded,
+            vec![LatencyStage::Stt, LatencyStage::Total]
+        );
+        assert_eq!(warning.breakdown.transform_ms, 0.5);
+        assert_eq!(
+            warning.summary(),
+            "STT 990ms > 800ms, Total 1010ms > 1000ms"
+        );
+    }
+
+    #[test]
+    fn test_partial_budgets_deserialize_with_defaults() {
+        let budgets: LatencyBudgets = serde_json::from_str(r#"{"stt_ms": 500.0}"#).unwrap();
+        assert_eq!(budgets.stt_ms, 500.0);
+        assert_eq!(budgets.total_ms, 1000.0);
+
+        let json = serde_json::to_string(&LatencyStage::Injection).unwrap();
+        assert_eq!(json, "\"injection\"");
+    }
+}
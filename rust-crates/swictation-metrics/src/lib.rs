@@ -10,22 +10,38 @@
 
 pub mod collector;
 pub mod database;
+pub mod dedupe;
+pub mod digest;
 pub mod gpu;
+pub mod integrity;
+pub mod latency;
 pub mod memory;
 pub mod models;
+pub mod percentile;
+pub mod storage;
 
 // WASM bindings (only when compiled to WebAssembly)
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
 // Re-export main types
-pub use collector::MetricsCollector;
-pub use database::MetricsDatabase;
+pub use collector::{MetricsCollector, PRIVATE_SESSION_ID};
+pub use database::{MetricsDatabase, PurgedTrash, TRASH_RETENTION_DAYS};
+pub use dedupe::{audio_content_hash, DedupeReport};
+pub use digest::{DigestCorrection, WeeklyDigest};
 pub use gpu::{GpuMetrics, GpuMonitor};
+pub use integrity::IntegrityReport;
+pub use latency::{LatencyBreakdown, LatencyBudgets, LatencyStage, LatencyWarning};
 pub use memory::{
     MemoryError, MemoryMonitor, MemoryPressure, MemoryStats, MemoryThresholds, RamStats, VramStats,
 };
-pub use models::{DaemonState, LifetimeMetrics, RealtimeMetrics, SegmentMetrics, SessionMetrics};
+pub use models::{
+    DaemonState, InferenceMetadata, JobPriority, JobStatus, LifetimeMetrics, PracticeSession,
+    ProsodyMetrics, RealtimeMetrics, SegmentAlternative, SegmentMetrics, SessionComparison,
+    SessionMetrics, SinkDelivery, SinkStats, TranscriptionJob,
+};
+pub use percentile::{LatencyPercentiles, PercentileAggregator, PercentileBackfillReport};
+pub use storage::{is_storage_full, StorageStatus};
 
 #[cfg(feature = "wasm")]
 pub use wasm::MetricsDatabaseWasm;
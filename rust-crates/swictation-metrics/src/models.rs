@@ -4,6 +4,7 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 /// Daemon state enum (matches DaemonState in models.py)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -63,6 +64,10 @@ pub struct SessionMetrics {
     pub cpu_usage_mean_percent: f64,
     pub cpu_usage_peak_percent: f64,
 
+    /// Words masked or dropped by the sensitive-word filter
+    #[serde(default)]
+    pub masked_words_count: i32,
+
     // Internal tracking
     #[serde(skip)]
     pub total_samples: u64,
@@ -93,6 +98,7 @@ impl Default for SessionMetrics {
             gpu_memory_mean_mb: 0.0,
             cpu_usage_mean_percent: 0.0,
             cpu_usage_peak_percent: 0.0,
+            masked_words_count: 0,
             total_samples: 0,
         }
     }
@@ -110,6 +116,35 @@ impl SessionMetrics {
     }
 }
 
+/// Which model/execution provider produced a segment
+///
+/// Stored alongside each segment so histories that span model changes
+/// (auto-selection, overrides, hot-swaps) stay interpretable.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InferenceMetadata {
+    /// Model name (e.g. "Parakeet-TDT-1.1B-INT8")
+    pub model: String,
+    /// Weight precision ("fp32", "fp16", "int8")
+    pub quantization: String,
+    /// ONNX Runtime execution provider ("CUDA", "CoreML", "CPU")
+    pub provider: String,
+    /// Device class ("GPU" or "CPU")
+    pub device: String,
+}
+
+/// Delivery of a segment, for pacing feedback (experimental, recorded with
+/// `analytics_prosody` enabled)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ProsodyMetrics {
+    /// Pitch spread in semitones; low values mean a monotone delivery.
+    /// `None` when the segment had too little voiced speech.
+    pub pitch_std_semitones: Option<f64>,
+    /// Mean speech level in dBFS
+    pub energy_dbfs: f64,
+    /// Words per minute of actual speech (pauses excluded)
+    pub speaking_rate_wpm: f64,
+}
+
 /// Metrics for a single VAD-triggered segment (matches SegmentMetrics dataclass)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SegmentMetrics {
@@ -135,6 +170,43 @@ pub struct SegmentMetrics {
     // Quality indicators
     pub transformations_count: i32,
     pub keyboard_actions_count: i32,
+
+    /// Leading/trailing silence removed before STT (milliseconds)
+    #[serde(default)]
+    pub trimmed_silence_ms: f64,
+
+    // Position in the recording (for aligning text with stored audio)
+    /// Index of the segment's first sample since recording started (16 kHz)
+    #[serde(default)]
+    pub start_sample: Option<u64>,
+    /// Wall-clock capture time of the segment's first sample
+    #[serde(default)]
+    pub audio_start: Option<DateTime<Utc>>,
+
+    // Provenance
+    #[serde(default)]
+    pub inference: Option<InferenceMetadata>,
+
+    /// Other likely transcriptions from the decoder, most likely first
+    #[serde(default)]
+    pub nbest: Vec<String>,
+
+    /// Words masked or dropped by the sensitive-word filter
+    #[serde(default)]
+    pub masked_words: i32,
+
+    /// Identifies re-submitted copies of the same segment (see
+    /// [`crate::dedupe`]); derived from text and capture time when unset
+    #[serde(default)]
+    pub content_hash: Option<String>,
+
+    #[serde(default)]
+    pub prosody: Option<ProsodyMetrics>,
+
+    /// Who spoke the segment (1, 2, ...) when speakers are labelled; numbers
+    /// restart with every session
+    #[serde(default)]
+    pub speaker: Option<i32>,
 }
 
 impl Default for SegmentMetrics {
@@ -155,6 +227,15 @@ impl Default for SegmentMetrics {
             total_latency_ms: 0.0,
             transformations_count: 0,
             keyboard_actions_count: 0,
+            trimmed_silence_ms: 0.0,
+            start_sample: None,
+            audio_start: None,
+            inference: None,
+            nbest: Vec::new(),
+            masked_words: 0,
+            content_hash: None,
+            prosody: None,
+            speaker: None,
         }
     }
 }
@@ -170,6 +251,139 @@ impl SegmentMetrics {
     }
 }
 
+/// Alternative transcription of a stored segment (e.g. from a retry with another model)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentAlternative {
+    pub alternative_id: Option<i64>,
+    pub segment_id: i64,
+    pub created_at: Option<DateTime<Utc>>,
+    pub text: String,
+    /// Model and execution provider that produced this text
+    pub inference: Option<InferenceMetadata>,
+}
+
+/// One segment handed to one output sink (typing, note, hook)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SinkDelivery {
+    pub session_id: Option<i64>,
+    pub timestamp: Option<DateTime<Utc>>,
+    /// Sink name as configured (`"inject"`, `"note"`, `"hook"`)
+    pub sink: String,
+    pub latency_ms: f64,
+    /// Why the delivery failed, if it did
+    pub error: Option<String>,
+}
+
+/// Deliveries to one sink over some period
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SinkStats {
+    pub sink: String,
+    pub deliveries: i64,
+    pub failures: i64,
+    pub avg_latency_ms: f64,
+    pub max_latency_ms: f64,
+}
+
+/// One readalong run scored for pronunciation (no script text is kept)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PracticeSession {
+    pub practice_id: Option<i64>,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub script_words: i64,
+    /// Script words the speaker got past, read or not
+    pub words_read: i64,
+    /// Script words read as written
+    pub words_matched: i64,
+    /// `words_matched / words_read` (0-1)
+    pub accuracy: f64,
+    /// Mean per-word score over the words read (0-100): the recognizer's
+    /// confidence in each word read as written, 0 for the others
+    pub pronunciation_score: f64,
+}
+
+/// Progress of a file transcription job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+    /// Held by the user until resumed
+    Paused,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Queued => "queued",
+            JobStatus::Running => "running",
+            JobStatus::Done => "done",
+            JobStatus::Failed => "failed",
+            JobStatus::Paused => "paused",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "queued" => Some(JobStatus::Queued),
+            "running" => Some(JobStatus::Running),
+            "done" => Some(JobStatus::Done),
+            "failed" => Some(JobStatus::Failed),
+            "paused" => Some(JobStatus::Paused),
+            "cancelled" => Some(JobStatus::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+/// Which queued transcription job runs first (live dictation is never
+/// queued and always goes ahead of both)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobPriority {
+    /// Files picked up from watched folders
+    Background,
+    /// Files the user asked for (including re-transcriptions)
+    Requested,
+}
+
+impl JobPriority {
+    pub fn as_i64(&self) -> i64 {
+        match self {
+            JobPriority::Background => 0,
+            JobPriority::Requested => 1,
+        }
+    }
+
+    pub fn from_i64(value: i64) -> Self {
+        if value > 0 {
+            JobPriority::Requested
+        } else {
+            JobPriority::Background
+        }
+    }
+}
+
+/// Audio file transcribed in the background
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionJob {
+    pub job_id: i64,
+    pub path: String,
+    pub status: JobStatus,
+    pub priority: JobPriority,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Audio length, known once the file has been decoded
+    pub duration_s: Option<f64>,
+    pub words: Option<i64>,
+    /// Why the job failed
+    pub error: Option<String>,
+}
+
 /// Aggregate metrics across all sessions (matches LifetimeMetrics dataclass)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LifetimeMetrics {
@@ -291,3 +505,79 @@ impl Default for RealtimeMetrics {
         }
     }
 }
+
+/// Side-by-side comparison of two sessions
+///
+/// Deltas are `b - a`, so comparing an old session (a) against a newer one (b)
+/// yields positive WPM deltas and negative latency deltas when things improved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionComparison {
+    pub session_a: i64,
+    pub session_b: i64,
+
+    // Performance deltas
+    pub wpm_delta: f64,
+    pub average_latency_delta_ms: f64,
+    pub median_latency_delta_ms: f64,
+    pub p95_latency_delta_ms: f64,
+    pub words_delta: i32,
+
+    // Vocabulary (distinct lowercase words from stored segment text)
+    pub vocabulary_a: usize,
+    pub vocabulary_b: usize,
+    pub shared_vocabulary: usize,
+    /// Jaccard similarity of the two vocabularies (0.0 - 1.0)
+    pub vocabulary_overlap: f64,
+
+    // Corrections/transformations applied
+    pub corrections_a: i32,
+    pub corrections_b: i32,
+}
+
+impl SessionComparison {
+    /// Build a comparison from two sessions and their segment texts
+    pub fn new<'a>(
+        a: &SessionMetrics,
+        b: &SessionMetrics,
+        texts_a: impl IntoIterator<Item = &'a str>,
+        texts_b: impl IntoIterator<Item = &'a str>,
+    ) -> Self {
+        let vocab_a = vocabulary(texts_a);
+        let vocab_b = vocabulary(texts_b);
+        let shared = vocab_a.intersection(&vocab_b).count();
+        let union = vocab_a.union(&vocab_b).count();
+
+        Self {
+            session_a: a.session_id.unwrap_or_default(),
+            session_b: b.session_id.unwrap_or_default(),
+            wpm_delta: b.words_per_minute - a.words_per_minute,
+            average_latency_delta_ms: b.average_latency_ms - a.average_latency_ms,
+            median_latency_delta_ms: b.median_latency_ms - a.median_latency_ms,
+            p95_latency_delta_ms: b.p95_latency_ms - a.p95_latency_ms,
+            words_delta: b.words_dictated - a.words_dictated,
+            vocabulary_a: vocab_a.len(),
+            vocabulary_b: vocab_b.len(),
+            shared_vocabulary: shared,
+            vocabulary_overlap: if union > 0 {
+                shared as f64 / union as f64
+            } else {
+                0.0
+            },
+            corrections_a: a.transformations_count,
+            corrections_b: b.transformations_count,
+        }
+    }
+}
+
+/// Distinct lowercase words, ignoring surrounding punctuation
+fn vocabulary<'a>(texts: impl IntoIterator<Item = &'a str>) -> HashSet<String> {
+    texts
+        .into_iter()
+        .flat_map(|t| t.split_whitespace())
+        .map(|w| {
+            w.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'')
+                .to_lowercase()
+        })
+        .filter(|w| !w.is_empty())
+        .collect()
+}
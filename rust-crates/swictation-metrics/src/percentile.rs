@@ -0,0 +1,283 @@
+//! Latency percentiles over a session's segments
+//!
+//! Sessions store the median and p95 of their segments' end-to-end latency.
+//! Up to [`EXACT_LIMIT`] values are kept and the percentiles are exact
+//! (linear interpolation between the closest ranks). Past that the values
+//! are folded into a merging t-digest, which keeps memory bounded for
+//! sessions of any length while staying accurate at the tails, where its
+//! centroids are smallest. See
+//! [`crate::MetricsDatabase::backfill_latency_percentiles`] for sessions
+//! stored before this was computed consistently.
+
+use serde::{Deserialize, Serialize};
+
+/// Values kept for exact percentiles before switching to the t-digest
+pub const EXACT_LIMIT: usize = 10_000;
+
+/// t-digest compression: roughly the number of centroids kept
+const COMPRESSION: f64 = 200.0;
+
+/// Values buffered before they are merged into the centroids
+const BUFFER_SIZE: usize = 1_000;
+
+/// Median and 95th percentile of a set of latencies (milliseconds)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyPercentiles {
+    pub median_ms: f64,
+    pub p95_ms: f64,
+}
+
+/// Outcome of [`crate::MetricsDatabase::backfill_latency_percentiles`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PercentileBackfillReport {
+    /// Sessions whose median and p95 were (or would be) written
+    pub sessions_updated: usize,
+    /// Segment latencies read to compute them
+    pub segments_read: usize,
+    pub dry_run: bool,
+}
+
+/// Streaming percentile estimator: exact for small inputs, t-digest beyond
+/// [`EXACT_LIMIT`] values
+#[derive(Debug, Clone, Default)]
+pub struct PercentileAggregator {
+    exact: Vec<f64>,
+    digest: Option<TDigest>,
+}
+
+impl PercentileAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add one value (NaN is ignored)
+    pub fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        if let Some(digest) = &mut self.digest {
+            digest.add(value);
+            return;
+        }
+        self.exact.push(value);
+        if self.exact.len() > EXACT_LIMIT {
+            let mut digest = TDigest::default();
+            for value in self.exact.drain(..) {
+                digest.add(value);
+            }
+            self.exact.shrink_to_fit();
+            self.digest = Some(digest);
+        }
+    }
+
+    /// Values added so far
+    pub fn count(&self) -> usize {
+        match &self.digest {
+            Some(digest) => digest.count as usize,
+            None => self.exact.len(),
+        }
+    }
+
+    /// Whether percentiles are exact (no t-digest approximation yet)
+    pub fn is_exact(&self) -> bool {
+        self.digest.is_none()
+    }
+
+    /// The `q` quantile (`q` in `[0, 1]`), or `None` without values
+    pub fn quantile(&mut self, q: f64) -> Option<f64> {
+        let q = q.clamp(0.0, 1.0);
+        match &mut self.digest {
+            Some(digest) => digest.quantile(q),
+            None => {
+                self.exact.sort_by(f64::total_cmp);
+                exact_quantile(&self.exact, q)
+            }
+        }
+    }
+
+    /// Median and p95, or `None` without values
+    pub fn latency_percentiles(&mut self) -> Option<LatencyPercentiles> {
+        Some(LatencyPercentiles {
+            median_ms: self.quantile(0.5)?,
+            p95_ms: self.quantile(0.95)?,
+        })
+    }
+}
+
+impl FromIterator<f64> for PercentileAggregator {
+    fn from_iter<I: IntoIterator<Item = f64>>(values: I) -> Self {
+        let mut aggregator = Self::new();
+        for value in values {
+            aggregator.add(value);
+        }
+        aggregator
+    }
+}
+
+/// Quantile of sorted values, interpolating between the closest ranks
+fn exact_quantile(sorted: &[f64], q: f64) -> Option<f64> {
+    let last = sorted.len().checked_sub(1)?;
+    let rank = q * last as f64;
+    let lo = rank.floor() as usize;
+    let hi = rank.ceil() as usize;
+    Some(sorted[lo] + (sorted[hi] - sorted[lo]) * (rank - lo as f64))
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+/// Merging t-digest (Dunning & Ertl) with the arcsine scale function
+#[derive(Debug, Clone, Default)]
+struct TDigest {
+    centroids: Vec<Centroid>,
+    buffer: Vec<f64>,
+    count: u64,
+    min: f64,
+    max: f64,
+}
+
+impl TDigest {
+    fn add(&mut self, value: f64) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.count += 1;
+        self.buffer.push(value);
+        if self.buffer.len() >= BUFFER_SIZE {
+            self.merge();
+        }
+    }
+
+    /// Scale function: centroids near q = 0 and q = 1 stay small
+    fn k(q: f64) -> f64 {
+        COMPRESSION / (2.0 * std::f64::consts::PI) * (2.0 * q - 1.0).asin()
+    }
+
+    fn k_inverse(k: f64) -> f64 {
+        ((k * 2.0 * std::f64::consts::PI / COMPRESSION).sin() + 1.0) / 2.0
+    }
+
+    /// Fold the buffer into the centroids
+    fn merge(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let mut items: Vec<Centroid> = self.centroids.drain(..).collect();
+        items.extend(
+            self.buffer
+                .drain(..)
+                .map(|mean| Centroid { mean, weight: 1.0 }),
+        );
+        items.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+
+        let total: f64 = items.iter().map(|c| c.weight).sum();
+        let mut merged = Vec::with_capacity(COMPRESSION as usize);
+        let mut items = items.into_iter();
+        let Some(mut current) = items.next() else {
+            return;
+        };
+        let mut before = 0.0;
+        let mut limit = total * Self::k_inverse(Self::k(0.0) + 1.0);
+        for item in items {
+            if before + current.weight + item.weight <= limit {
+                let weight = current.weight + item.weight;
+                current.mean += (item.mean - current.mean) * item.weight / weight;
+                current.weight = weight;
+            } else {
+                before += current.weight;
+                merged.push(current);
+                limit = total * Self::k_inverse(Self::k(before / total) + 1.0);
+                current = item;
+            }
+        }
+        merged.push(current);
+        self.centroids = merged;
+    }
+
+    fn quantile(&mut self, q: f64) -> Option<f64> {
+        self.merge();
+        let first = self.centroids.first()?;
+        if self.centroids.len() == 1 {
+            return Some(first.mean);
+        }
+
+        // Each centroid's mean sits at the middle of its weight
+        let target = q * self.count as f64;
+        let mut center = first.weight / 2.0;
+        if target <= center {
+            let t = if center > 0.0 { target / center } else { 0.0 };
+            return Some(self.min + (first.mean - self.min) * t);
+        }
+        for pair in self.centroids.windows(2) {
+            let next_center = center + (pair[0].weight + pair[1].weight) / 2.0;
+            if target <= next_center {
+                let t = (target - center) / (next_center - center);
+                return Some(pair[0].mean + (pair[1].mean - pair[0].mean) * t);
+            }
+            center = next_center;
+        }
+        let last = self.centroids.last()?;
+        let tail = self.count as f64 - center;
+        let t = if tail > 0.0 {
+            (target - center) / tail
+        } else {
+            1.0
+        };
+        Some(last.mean + (self.max - last.mean) * t.min(1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_percentiles_interpolate() {
+        let mut aggregator: PercentileAggregator =
+            [400.0, 100.0, 300.0, 200.0].into_iter().collect();
+        let p = aggregator.latency_percentiles().unwrap();
+        assert_eq!(p.median_ms, 250.0);
+        assert!((p.p95_ms - 385.0).abs() < 1e-9);
+
+        let mut single: PercentileAggregator = [42.0].into_iter().collect();
+        assert_eq!(single.quantile(0.95), Some(42.0));
+        assert_eq!(PercentileAggregator::new().latency_percentiles(), None);
+    }
+
+    #[test]
+    fn test_digest_matches_exact_on_large_input() {
+        // Skewed, latency-like values in a scrambled order
+        let values: Vec<f64> = (0..100_000u64)
+            .map(|i| {
+                let x = (i.wrapping_mul(2_654_435_761) % 100_000) as f64 / 100_000.0;
+                200.0 + 50.0 * x + 2_000.0 * x.powi(8)
+            })
+            .collect();
+        let mut aggregator: PercentileAggregator = values.iter().copied().collect();
+        assert!(!aggregator.is_exact());
+        assert_eq!(aggregator.count(), values.len());
+
+        let mut sorted = values.clone();
+        sorted.sort_by(f64::total_cmp);
+        for q in [0.01, 0.5, 0.95, 0.99] {
+            let exact = exact_quantile(&sorted, q).unwrap();
+            let estimate = aggregator.quantile(q).unwrap();
+            assert!(
+                (estimate - exact).abs() / exact < 0.005,
+                "q={}: {} vs {}",
+                q,
+                estimate,
+                exact
+            );
+        }
+        assert_eq!(aggregator.quantile(0.0), Some(sorted[0]));
+        assert_eq!(aggregator.quantile(1.0), Some(sorted[sorted.len() - 1]));
+    }
+}
@@ -0,0 +1,217 @@
+//! Degraded mode for a metrics database on a full disk
+//!
+//! When a write fails with SQLITE_FULL or ENOSPC the collector stops
+//! touching the database and keeps sessions and segments in a
+//! [`StorageBuffer`] instead, so dictation carries on without an error per
+//! segment. Every [`DEFAULT_RETRY_INTERVAL`] the buffer is written out in
+//! order; once it empties the collector writes directly again.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::database::MetricsDatabase;
+use crate::models::{SegmentMetrics, SessionMetrics};
+
+/// Time between attempts to write the buffer out
+pub const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Segments kept in RAM at most; the oldest are dropped past this
+pub const MAX_BUFFERED_SEGMENTS: usize = 10_000;
+
+/// Whether `error`, or anything in its chain, means the disk is full
+pub fn is_storage_full(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        if let Some(rusqlite::Error::SqliteFailure(e, _)) = cause.downcast_ref::<rusqlite::Error>()
+        {
+            return e.code == rusqlite::ErrorCode::DiskFull;
+        }
+        cause
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|e| e.kind() == std::io::ErrorKind::StorageFull)
+    })
+}
+
+/// Whether metrics are being buffered in RAM, and how many
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageStatus {
+    /// The database's disk is full and writes are buffered
+    pub full: bool,
+    /// Segments waiting to be written
+    pub buffered_segments: usize,
+    /// Segments dropped because the buffer was full
+    pub dropped_segments: usize,
+}
+
+/// Writes held back while the database's disk is full
+///
+/// Sessions started meanwhile get negative provisional IDs, replaced by
+/// their database IDs when the buffer is written out.
+#[derive(Debug, Default)]
+pub struct StorageBuffer {
+    full_since: Option<Instant>,
+    last_attempt: Option<Instant>,
+    last_provisional_id: i64,
+    /// Sessions to insert, by provisional ID
+    sessions: Vec<(i64, SessionMetrics)>,
+    segments: Vec<SegmentMetrics>,
+    /// Final session metrics, by (possibly provisional) session ID
+    session_updates: Vec<(i64, SessionMetrics)>,
+    /// Provisional IDs already replaced by database IDs
+    resolved: HashMap<i64, i64>,
+    lifetime_stale: bool,
+    dropped_segments: usize,
+}
+
+impl StorageBuffer {
+    pub fn is_full(&self) -> bool {
+        self.full_since.is_some()
+    }
+
+    pub fn status(&self) -> StorageStatus {
+        StorageStatus {
+            full: self.is_full(),
+            buffered_segments: self.segments.len(),
+            dropped_segments: self.dropped_segments,
+        }
+    }
+
+    /// Enter degraded mode (a no-op when already in it)
+    pub fn mark_full(&mut self) {
+        let now = Instant::now();
+        self.full_since.get_or_insert(now);
+        self.last_attempt = Some(now);
+    }
+
+    /// Whether `interval` has passed since the last write attempt
+    pub fn retry_due(&self, interval: Duration) -> bool {
+        self.last_attempt
+            .is_none_or(|attempt| attempt.elapsed() >= interval)
+    }
+
+    /// Hold a new session, returning its provisional (negative) ID
+    pub fn push_session(&mut self, session: SessionMetrics) -> i64 {
+        self.last_provisional_id -= 1;
+        let id = self.last_provisional_id;
+        self.sessions.push((id, session));
+        id
+    }
+
+    pub fn push_segment(&mut self, segment: SegmentMetrics) {
+        if self.segments.len() >= MAX_BUFFERED_SEGMENTS {
+            self.segments.remove(0);
+            self.dropped_segments += 1;
+        }
+        self.segments.push(segment);
+    }
+
+    pub fn push_session_update(&mut self, session_id: i64, session: SessionMetrics) {
+        self.session_updates.push((session_id, session));
+        self.lifetime_stale = true;
+    }
+
+    /// Recalculate lifetime stats on the next flush
+    pub fn mark_lifetime_stale(&mut self) {
+        self.lifetime_stale = true;
+    }
+
+    /// Database ID of a session, for IDs handed out while buffering
+    pub fn resolve(&self, session_id: i64) -> i64 {
+        self.resolved
+            .get(&session_id)
+            .copied()
+            .unwrap_or(session_id)
+    }
+
+    /// Write everything out in order, leaving degraded mode when done
+    ///
+    /// Writes that succeed are removed as they go, so a flush that fails
+    /// part way (the disk filled again) resumes where it stopped.
+    pub fn flush(&mut self, db: &MetricsDatabase, store_text: bool) -> Result<()> {
+        self.last_attempt = Some(Instant::now());
+
+        while let Some((provisional_id, session)) = self.sessions.first() {
+            let session_id = db.insert_session(session)?;
+            let provisional_id = *provisional_id;
+            self.sessions.remove(0);
+            self.resolved.insert(provisional_id, session_id);
+            for segment in &mut self.segments {
+                if segment.session_id == Some(provisional_id) {
+                    segment.session_id = Some(session_id);
+                }
+            }
+            for (id, _) in &mut self.session_updates {
+                if *id == provisional_id {
+                    *id = session_id;
+                }
+            }
+        }
+
+        while let Some(segment) = self.segments.first() {
+            db.insert_segment_checked(segment, store_text)?;
+            self.segments.remove(0);
+        }
+
+        while let Some((session_id, session)) = self.session_updates.first() {
+            db.update_session(*session_id, session)?;
+            self.session_updates.remove(0);
+        }
+
+        if self.lifetime_stale {
+            db.recalculate_lifetime_stats()?;
+            self.lifetime_stale = false;
+        }
+
+        self.full_since = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_storage_full() {
+        let full = rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_FULL),
+            None,
+        );
+        assert!(is_storage_full(
+            &anyhow::Error::from(full).context("Failed to insert segment")
+        ));
+
+        let enospc = std::io::Error::from_raw_os_error(28);
+        assert_eq!(is_storage_full(&anyhow::Error::from(enospc)), cfg!(unix));
+
+        let busy = rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
+            None,
+        );
+        assert!(!is_storage_full(&busy.into()));
+    }
+
+    #[test]
+    fn test_buffer_drops_oldest_segments() {
+        let mut buffer = StorageBuffer::default();
+        buffer.mark_full();
+        for words in 0..=MAX_BUFFERED_SEGMENTS as i32 {
+            buffer.push_segment(SegmentMetrics {
+                words,
+                ..Default::default()
+            });
+        }
+        assert_eq!(
+            buffer.status(),
+            StorageStatus {
+                full: true,
+                buffered_segments: MAX_BUFFERED_SEGMENTS,
+                dropped_segments: 1,
+            }
+        );
+        assert_eq!(buffer.segments[0].words, 1);
+        assert_eq!(buffer.push_session(SessionMetrics::default()), -1);
+        assert_eq!(buffer.push_session(SessionMetrics::default()), -2);
+    }
+}
@@ -0,0 +1,120 @@
+//! Models Swictation knows how to download
+
+use std::path::Path;
+
+/// Which files of a repository make up a model
+#[derive(Debug, Clone, Copy)]
+pub enum Files {
+    /// Every file except dotfiles and READMEs, under their own names
+    All,
+    /// `(path in the repository, file name in the model directory)` pairs
+    Only(&'static [(&'static str, &'static str)]),
+}
+
+/// A downloadable model
+#[derive(Debug, Clone, Copy)]
+pub struct ModelSpec {
+    /// Name used on the command line (`swictation-models download 0.6b`)
+    pub id: &'static str,
+    pub description: &'static str,
+    /// Hugging Face repository the files come from
+    pub repo: &'static str,
+    /// Directory under the models directory, as the daemon's defaults expect
+    pub dir: &'static str,
+    pub files: Files,
+}
+
+impl ModelSpec {
+    /// Whether `path` is this model's directory or a file directly inside it
+    pub fn matches(&self, path: &Path) -> bool {
+        let is_dir = |p: &Path| p.file_name().is_some_and(|name| name == self.dir);
+        is_dir(path) || path.parent().is_some_and(is_dir)
+    }
+
+    /// Whether a file listed in the repository belongs to the model, and
+    /// under which local name
+    pub fn local_name<'a>(&'a self, remote: &'a str) -> Option<&'a str> {
+        match self.files {
+            Files::All => {
+                let name = remote.rsplit('/').next().unwrap_or(remote);
+                let skip = name.starts_with('.') || name.to_lowercase().starts_with("readme");
+                (!skip).then_some(remote)
+            }
+            Files::Only(files) => files
+                .iter()
+                .find(|(path, _)| *path == remote)
+                .map(|(_, local)| *local),
+        }
+    }
+}
+
+/// All known models
+pub const CATALOG: &[ModelSpec] = &[
+    ModelSpec {
+        id: "silero-vad",
+        description: "Silero VAD voice activity detector (required)",
+        repo: "onnx-community/silero-vad",
+        dir: "silero-vad",
+        files: Files::Only(&[("onnx/model.onnx", "silero_vad.onnx")]),
+    },
+    ModelSpec {
+        id: "0.6b",
+        description: "Parakeet-TDT 0.6B v3, INT8 (CPU and GPUs with 4GB+ VRAM)",
+        repo: "csukuangfj/sherpa-onnx-nemo-parakeet-tdt-0.6b-v3-int8",
+        dir: "parakeet-tdt-0.6b-v3-onnx",
+        files: Files::Only(&[
+            ("encoder.int8.onnx", "encoder.int8.onnx"),
+            ("decoder.int8.onnx", "decoder.int8.onnx"),
+            ("joiner.int8.onnx", "joiner.int8.onnx"),
+            ("tokens.txt", "tokens.txt"),
+        ]),
+    },
+    ModelSpec {
+        id: "1.1b",
+        description: "Parakeet-TDT 1.1B (GPUs with 6GB+ VRAM)",
+        repo: "jenerallee78/parakeet-tdt-1.1b-onnx",
+        dir: "parakeet-tdt-1.1b-onnx",
+        files: Files::All,
+    },
+];
+
+/// The model named `id`
+pub fn find(id: &str) -> Option<&'static ModelSpec> {
+    CATALOG.iter().find(|spec| spec.id == id)
+}
+
+/// The model whose directory holds `path`
+pub fn for_path(path: &Path) -> Option<&'static ModelSpec> {
+    CATALOG.iter().find(|spec| spec.matches(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_for_configured_paths() {
+        let models = Path::new("/home/u/.local/share/swictation/models");
+        assert_eq!(
+            for_path(&models.join("silero-vad/silero_vad.onnx")).map(|s| s.id),
+            Some("silero-vad")
+        );
+        assert_eq!(
+            for_path(&models.join("parakeet-tdt-0.6b-v3-onnx")).map(|s| s.id),
+            Some("0.6b")
+        );
+        assert!(for_path(&models.join("my-own-model")).is_none());
+    }
+
+    #[test]
+    fn test_local_names() {
+        let vad = find("silero-vad").unwrap();
+        assert_eq!(vad.local_name("onnx/model.onnx"), Some("silero_vad.onnx"));
+        assert_eq!(vad.local_name("onnx/model_fp16.onnx"), None);
+
+        let large = find("1.1b").unwrap();
+        assert_eq!(large.local_name("encoder.onnx"), Some("encoder.onnx"));
+        assert_eq!(large.local_name(".gitattributes"), None);
+        assert_eq!(large.local_name("README.md"), None);
+    }
+}
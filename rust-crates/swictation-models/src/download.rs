@@ -0,0 +1,376 @@
+//! Hugging Face downloads with resume and SHA256 verification
+//!
+//! The repository listing (`/api/models/<repo>?blobs=true`) gives the commit
+//! to download from and, for every LFS file, its size and SHA256. Files are
+//! fetched from that commit into `<name>.part` and only renamed into place
+//! once their checksum matches, so an interrupted download is resumed with
+//! a range request on the next run and a corrupt one never looks installed.
+//! Small non-LFS files (`tokens.txt`) have no published SHA256; their size
+//! is checked instead.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::catalog::ModelSpec;
+use crate::installed::InstalledFile;
+
+/// Hub used unless `HF_ENDPOINT` points at a mirror
+const DEFAULT_ENDPOINT: &str = "https://huggingface.co";
+
+/// Read/write buffer size for downloads and hashing
+const CHUNK_SIZE: usize = 1 << 16;
+
+/// Base URL of the model hub
+pub fn endpoint() -> String {
+    std::env::var("HF_ENDPOINT")
+        .map(|url| url.trim_end_matches('/').to_string())
+        .unwrap_or_else(|_| DEFAULT_ENDPOINT.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+struct Lfs {
+    sha256: String,
+    size: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Sibling {
+    rfilename: String,
+    size: Option<u64>,
+    lfs: Option<Lfs>,
+}
+
+/// Repository listing from the hub API
+#[derive(Debug, Deserialize)]
+pub struct Listing {
+    /// Commit the listing describes
+    pub sha: String,
+    siblings: Vec<Sibling>,
+}
+
+impl Listing {
+    pub fn parse(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("Unexpected repository listing from the model hub")
+    }
+}
+
+/// A file to download and what it should look like
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteFile {
+    /// Path in the repository
+    pub remote: String,
+    /// Path relative to the model directory
+    pub local: String,
+    pub size: Option<u64>,
+    pub sha256: Option<String>,
+}
+
+/// Files of `spec` in `listing`
+pub fn plan(spec: &ModelSpec, listing: &Listing) -> Result<Vec<RemoteFile>> {
+    let files: Vec<RemoteFile> = listing
+        .siblings
+        .iter()
+        .filter_map(|sibling| {
+            let local = spec.local_name(&sibling.rfilename)?;
+            Some(RemoteFile {
+                remote: sibling.rfilename.clone(),
+                local: local.to_string(),
+                size: sibling.lfs.as_ref().map(|lfs| lfs.size).or(sibling.size),
+                sha256: sibling.lfs.as_ref().map(|lfs| lfs.sha256.clone()),
+            })
+        })
+        .collect();
+
+    if let crate::catalog::Files::Only(wanted) = spec.files {
+        for (remote, _) in wanted {
+            if !files.iter().any(|file| file.remote == *remote) {
+                bail!("{} has no file {}", spec.repo, remote);
+            }
+        }
+    }
+    if files.is_empty() {
+        bail!("{} has no model files", spec.repo);
+    }
+    Ok(files)
+}
+
+/// SHA256 of a file as lowercase hex
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; CHUNK_SIZE];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// `file` as already installed at `path`, if it holds the expected content
+fn existing(path: &Path, file: &RemoteFile) -> Result<Option<InstalledFile>> {
+    let Ok(metadata) = fs::metadata(path) else {
+        return Ok(None);
+    };
+    let size = metadata.len();
+    if file.size != Some(size) {
+        return Ok(None);
+    }
+    let sha256 = sha256_file(path)?;
+    if file
+        .sha256
+        .as_ref()
+        .is_some_and(|expected| *expected != sha256)
+    {
+        return Ok(None);
+    }
+    Ok(Some(InstalledFile {
+        path: file.local.clone(),
+        size,
+        sha256,
+    }))
+}
+
+/// Check a finished `.part` file and move it into place
+fn finish(part: &Path, target: &Path, file: &RemoteFile) -> Result<InstalledFile> {
+    let size = fs::metadata(part)?.len();
+    if let Some(expected) = file.size {
+        if size != expected {
+            fs::remove_file(part)?;
+            bail!(
+                "{}: downloaded {} bytes, expected {}",
+                file.remote,
+                size,
+                expected
+            );
+        }
+    }
+    let sha256 = sha256_file(part)?;
+    if let Some(expected) = &file.sha256 {
+        if sha256 != *expected {
+            fs::remove_file(part)?;
+            bail!(
+                "{}: checksum mismatch (expected {}, got {})",
+                file.remote,
+                expected,
+                sha256
+            );
+        }
+    }
+    fs::rename(part, target)
+        .with_context(|| format!("Failed to move {} into place", target.display()))?;
+    Ok(InstalledFile {
+        path: file.local.clone(),
+        size,
+        sha256,
+    })
+}
+
+/// Progress of one file, passed to the download callback
+#[derive(Debug, Clone, Copy)]
+pub struct Progress<'a> {
+    pub file: &'a str,
+    pub done: u64,
+    pub total: Option<u64>,
+}
+
+/// Blocking client for one hub
+pub struct Hub {
+    agent: ureq::Agent,
+    endpoint: String,
+}
+
+impl Hub {
+    pub fn new(endpoint: String) -> Self {
+        let config = ureq::Agent::config_builder()
+            .tls_config(
+                ureq::tls::TlsConfig::builder()
+                    .provider(ureq::tls::TlsProvider::NativeTls)
+                    .build(),
+            )
+            // Range responses (206, 416) are handled here, not as errors
+            .http_status_as_error(false)
+            .user_agent(concat!("swictation-models/", env!("CARGO_PKG_VERSION")))
+            .build();
+        Self {
+            agent: config.into(),
+            endpoint,
+        }
+    }
+
+    /// Current listing of `repo`
+    pub fn listing(&self, repo: &str) -> Result<Listing> {
+        let url = format!("{}/api/models/{}?blobs=true", self.endpoint, repo);
+        let mut response = self
+            .agent
+            .get(&url)
+            .call()
+            .with_context(|| format!("Failed to reach {}", url))?;
+        if !response.status().is_success() {
+            bail!("{} returned {}", url, response.status());
+        }
+        Listing::parse(&response.body_mut().read_to_string()?)
+    }
+
+    /// Download `file` of `repo` at `revision` into `dir`, resuming a
+    /// previous partial download
+    pub fn download(
+        &self,
+        repo: &str,
+        revision: &str,
+        file: &RemoteFile,
+        dir: &Path,
+        progress: &mut dyn FnMut(Progress),
+    ) -> Result<InstalledFile> {
+        let target = dir.join(&file.local);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let part = PathBuf::from(format!("{}.part", target.display()));
+        if let Some(installed) = existing(&target, file)? {
+            return Ok(installed);
+        }
+
+        let url = format!(
+            "{}/{}/resolve/{}/{}",
+            self.endpoint, repo, revision, file.remote
+        );
+        let mut offset = fs::metadata(&part).map(|m| m.len()).unwrap_or(0);
+        if file.size.is_some_and(|size| offset >= size) {
+            return finish(&part, &target, file);
+        }
+
+        let mut request = self.agent.get(&url);
+        if offset > 0 {
+            request = request.header("Range", format!("bytes={}-", offset));
+        }
+        let mut response = request
+            .call()
+            .with_context(|| format!("Failed to download {}", url))?;
+        match response.status().as_u16() {
+            206 => {}
+            200 => offset = 0, // range ignored, start over
+            416 => {
+                // The partial file no longer fits the remote one
+                fs::remove_file(&part)?;
+                return self.download(repo, revision, file, dir, progress);
+            }
+            status => bail!("{} returned {}", url, status),
+        }
+
+        let mut out = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(offset > 0)
+            .truncate(offset == 0)
+            .open(&part)
+            .with_context(|| format!("Failed to open {}", part.display()))?;
+        let mut body = response.body_mut().as_reader();
+        let mut buf = vec![0; CHUNK_SIZE];
+        let mut done = offset;
+        loop {
+            let n = match body.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    return Err(e).with_context(|| {
+                        format!(
+                            "Download of {} interrupted at {} bytes, run again to resume",
+                            file.remote, done
+                        )
+                    })
+                }
+            };
+            out.write_all(&buf[..n])?;
+            done += n as u64;
+            progress(Progress {
+                file: &file.local,
+                done,
+                total: file.size,
+            });
+        }
+        out.flush()?;
+        drop(out);
+        finish(&part, &target, file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::catalog;
+
+    const LISTING: &str = r#"{
+        "sha": "0123abcd",
+        "siblings": [
+            {"rfilename": ".gitattributes", "size": 1519},
+            {"rfilename": "README.md", "size": 90},
+            {"rfilename": "onnx/model.onnx", "size": 2327524,
+             "lfs": {"sha256": "ab12", "size": 2327524, "pointerSize": 132}},
+            {"rfilename": "onnx/model_fp16.onnx", "size": 1280000,
+             "lfs": {"sha256": "cd34", "size": 1280000, "pointerSize": 132}}
+        ]
+    }"#;
+
+    #[test]
+    fn test_plan_from_listing() {
+        let listing = Listing::parse(LISTING).unwrap();
+        assert_eq!(listing.sha, "0123abcd");
+
+        let files = plan(catalog::find("silero-vad").unwrap(), &listing).unwrap();
+        assert_eq!(
+            files,
+            [RemoteFile {
+                remote: "onnx/model.onnx".to_string(),
+                local: "silero_vad.onnx".to_string(),
+                size: Some(2327524),
+                sha256: Some("ab12".to_string()),
+            }]
+        );
+
+        // A listed file the catalog needs is missing
+        let err = plan(catalog::find("0.6b").unwrap(), &listing).unwrap_err();
+        assert!(err.to_string().contains("encoder.int8.onnx"), "{}", err);
+    }
+
+    #[test]
+    fn test_finish_verifies_checksum() {
+        let dir = std::env::temp_dir().join(format!("swictation-models-dl-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let part = dir.join("tokens.txt.part");
+        let target = dir.join("tokens.txt");
+        fs::write(&part, "hello").unwrap();
+
+        let mut file = RemoteFile {
+            remote: "tokens.txt".to_string(),
+            local: "tokens.txt".to_string(),
+            size: Some(5),
+            // SHA256 of "hello"
+            sha256: Some(
+                "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824".to_string(),
+            ),
+        };
+        let installed = finish(&part, &target, &file).unwrap();
+        assert_eq!(installed.size, 5);
+        assert!(target.exists() && !part.exists());
+        assert_eq!(existing(&target, &file).unwrap(), Some(installed));
+
+        // A corrupt download is removed so the next run starts over
+        fs::write(&part, "hellp").unwrap();
+        file.local = "other.txt".to_string();
+        let err = finish(&part, &dir.join("other.txt"), &file).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"), "{}", err);
+        assert!(!part.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
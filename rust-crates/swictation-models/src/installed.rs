@@ -0,0 +1,129 @@
+//! Record of installed models (`installed.json` in the models directory)
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::download::sha256_file;
+
+/// File name of the record inside the models directory
+pub const INSTALLED_FILE: &str = "installed.json";
+
+/// A downloaded file and its checksum at install time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstalledFile {
+    /// Path relative to the model directory
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// One installed model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstalledModel {
+    pub repo: String,
+    /// Repository commit the files were downloaded from
+    pub revision: String,
+    /// Directory under the models directory
+    pub dir: String,
+    pub files: Vec<InstalledFile>,
+    /// Unix time of the download
+    pub installed_at: u64,
+}
+
+/// Installed models by catalog id
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Installed {
+    pub models: BTreeMap<String, InstalledModel>,
+}
+
+impl Installed {
+    fn path(models_dir: &Path) -> PathBuf {
+        models_dir.join(INSTALLED_FILE)
+    }
+
+    /// Read the record, empty if nothing was installed yet
+    pub fn load(models_dir: &Path) -> Result<Self> {
+        let path = Self::path(models_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("Invalid {}", path.display()))
+    }
+
+    pub fn save(&self, models_dir: &Path) -> Result<()> {
+        let path = Self::path(models_dir);
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Files of model `id` that are missing or changed since install
+    pub fn verify(&self, models_dir: &Path, id: &str) -> Result<Vec<String>> {
+        let model = self
+            .models
+            .get(id)
+            .with_context(|| format!("Model '{}' is not installed", id))?;
+        let dir = models_dir.join(&model.dir);
+        let mut problems = Vec::new();
+        for file in &model.files {
+            let path = dir.join(&file.path);
+            if !path.exists() {
+                problems.push(format!("{}: missing", file.path));
+            } else if sha256_file(&path)? != file.sha256 {
+                problems.push(format!("{}: checksum changed", file.path));
+            }
+        }
+        Ok(problems)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_round_trip_and_verify() {
+        let models_dir = std::env::temp_dir().join(format!(
+            "swictation-models-installed-{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(models_dir.join("tiny")).unwrap();
+        fs::write(models_dir.join("tiny/tokens.txt"), "a 0\n").unwrap();
+
+        assert_eq!(Installed::load(&models_dir).unwrap(), Installed::default());
+
+        let mut installed = Installed::default();
+        installed.models.insert(
+            "tiny".to_string(),
+            InstalledModel {
+                repo: "example/tiny".to_string(),
+                revision: "abc123".to_string(),
+                dir: "tiny".to_string(),
+                files: vec![InstalledFile {
+                    path: "tokens.txt".to_string(),
+                    size: 4,
+                    sha256: sha256_file(&models_dir.join("tiny/tokens.txt")).unwrap(),
+                }],
+                installed_at: 0,
+            },
+        );
+        installed.save(&models_dir).unwrap();
+        let loaded = Installed::load(&models_dir).unwrap();
+        assert_eq!(loaded, installed);
+        assert!(loaded.verify(&models_dir, "tiny").unwrap().is_empty());
+
+        fs::write(models_dir.join("tiny/tokens.txt"), "b 0\n").unwrap();
+        assert_eq!(
+            loaded.verify(&models_dir, "tiny").unwrap(),
+            ["tokens.txt: checksum changed"]
+        );
+        assert!(loaded.verify(&models_dir, "other").is_err());
+
+        fs::remove_dir_all(&models_dir).unwrap();
+    }
+}
@@ -0,0 +1,112 @@
+//! Model manager for Swictation
+//!
+//! Downloads the Parakeet-TDT and Silero VAD models into
+//! [`swictation_paths::models_dir()`], verifies their SHA256 against the
+//! model hub, resumes interrupted downloads and records what was installed
+//! from which revision in `installed.json`.
+//!
+//! ```no_run
+//! let spec = swictation_models::find("0.6b").unwrap();
+//! let models_dir = swictation_paths::models_dir();
+//! swictation_models::install(&models_dir, spec, &mut |_| {})?;
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+//!
+//! The `swictation-models` binary wraps this for the command line, and the
+//! daemon uses [`missing`] to name the download for a model it cannot find.
+
+pub mod catalog;
+pub mod download;
+pub mod installed;
+
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+
+pub use catalog::{find, ModelSpec, CATALOG};
+pub use download::{Hub, Progress};
+pub use installed::{Installed, InstalledModel};
+
+/// Download `spec` into `models_dir` and record it as installed
+///
+/// Files already present with the right checksum are kept, so running this
+/// again after an interruption only fetches what is left.
+pub fn install(
+    models_dir: &Path,
+    spec: &ModelSpec,
+    progress: &mut dyn FnMut(Progress),
+) -> Result<InstalledModel> {
+    let hub = Hub::new(download::endpoint());
+    let listing = hub.listing(spec.repo)?;
+    let files = download::plan(spec, &listing)?;
+
+    let dir = models_dir.join(spec.dir);
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let files = files
+        .iter()
+        .map(|file| hub.download(spec.repo, &listing.sha, file, &dir, progress))
+        .collect::<Result<Vec<_>>>()?;
+
+    let model = InstalledModel {
+        repo: spec.repo.to_string(),
+        revision: listing.sha,
+        dir: spec.dir.to_string(),
+        files,
+        installed_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0),
+    };
+    let mut installed = Installed::load(models_dir)?;
+    installed.models.insert(spec.id.to_string(), model.clone());
+    installed.save(models_dir)?;
+    Ok(model)
+}
+
+/// Catalog models for the paths in `paths` that do not exist
+///
+/// Paths outside any catalog model's directory (custom models) are skipped.
+pub fn missing<'a>(paths: impl IntoIterator<Item = &'a Path>) -> Vec<&'static ModelSpec> {
+    let mut specs: Vec<&'static ModelSpec> = Vec::new();
+    for path in paths {
+        if path.exists() {
+            continue;
+        }
+        if let Some(spec) = catalog::for_path(path) {
+            if !specs.iter().any(|s| s.id == spec.id) {
+                specs.push(spec);
+            }
+        }
+    }
+    specs
+}
+
+/// Command that downloads `spec`
+pub fn download_command(spec: &ModelSpec) -> String {
+    format!("swictation-models download {}", spec.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_maps_paths_to_downloads() {
+        let models_dir = Path::new("/nonexistent/swictation/models");
+        let vad = models_dir.join("silero-vad/silero_vad.onnx");
+        let small = models_dir.join("parakeet-tdt-0.6b-v3-onnx");
+        let custom = models_dir.join("my-model");
+        let here = Path::new(".");
+
+        let specs = missing([vad.as_path(), small.as_path(), custom.as_path(), here]);
+        let commands: Vec<String> = specs.iter().map(|s| download_command(s)).collect();
+        assert_eq!(
+            commands,
+            [
+                "swictation-models download silero-vad",
+                "swictation-models download 0.6b"
+            ]
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! `swictation-models`: download, list and verify Swictation models
+
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::{bail, Result};
+use clap::{Parser, Subcommand};
+
+use swictation_models::{catalog, Installed, Progress, CATALOG};
+
+#[derive(Parser)]
+#[command(
+    name = "swictation-models",
+    version,
+    about = "Manage Swictation models"
+)]
+struct Cli {
+    /// Models directory (default: the Swictation data directory's models/)
+    #[arg(long, global = true)]
+    dir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List known models and which are installed
+    List,
+    /// Download models, resuming interrupted downloads
+    Download {
+        /// Model ids (see `list`)
+        ids: Vec<String>,
+        /// Download every known model
+        #[arg(long, conflicts_with = "ids")]
+        all: bool,
+    },
+    /// Check installed files against their recorded checksums
+    Verify {
+        /// Model ids (default: all installed)
+        ids: Vec<String>,
+    },
+}
+
+/// Rewrite one stderr line with the progress of the current file
+fn print_progress(progress: Progress) {
+    const MB: f64 = 1024.0 * 1024.0;
+    let done = progress.done as f64 / MB;
+    match progress.total {
+        Some(total) if total > 0 => eprint!(
+            "\r  {} {:.1}/{:.1} MB ({:.0}%)   ",
+            progress.file,
+            done,
+            total as f64 / MB,
+            100.0 * progress.done as f64 / total as f64
+        ),
+        _ => eprint!("\r  {} {:.1} MB   ", progress.file, done),
+    }
+    let _ = std::io::stderr().flush();
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let models_dir = match cli.dir {
+        Some(dir) => dir,
+        None => swictation_paths::get_models_dir()?,
+    };
+
+    match cli.command {
+        Command::List => {
+            let installed = Installed::load(&models_dir)?;
+            for spec in CATALOG {
+                let state = match installed.models.get(spec.id) {
+                    Some(model) => format!(
+                        "installed ({})",
+                        &model.revision[..model.revision.len().min(8)]
+                    ),
+                    None => "-".to_string(),
+                };
+                println!("{:<12} {:<20} {}", spec.id, state, spec.description);
+            }
+        }
+        Command::Download { ids, all } => {
+            let specs = if all {
+                CATALOG.iter().collect()
+            } else if ids.is_empty() {
+                bail!("Name models to download or pass --all (see `swictation-models list`)");
+            } else {
+                ids.iter()
+                    .map(|id| {
+                        catalog::find(id).ok_or_else(|| anyhow::anyhow!("Unknown model '{}'", id))
+                    })
+                    .collect::<Result<Vec<_>>>()?
+            };
+            for spec in specs {
+                println!("Downloading {} from {}", spec.id, spec.repo);
+                let model = swictation_models::install(&models_dir, spec, &mut print_progress)?;
+                eprintln!();
+                println!(
+                    "✓ {} ({} files) in {}",
+                    spec.id,
+                    model.files.len(),
+                    models_dir.join(&model.dir).display()
+                );
+            }
+        }
+        Command::Verify { ids } => {
+            let installed = Installed::load(&models_dir)?;
+            let ids: Vec<String> = if ids.is_empty() {
+                installed.models.keys().cloned().collect()
+            } else {
+                ids
+            };
+            if ids.is_empty() {
+                println!("No models installed");
+            }
+            let mut failed = false;
+            for id in &ids {
+                let problems = installed.verify(&models_dir, id)?;
+                if problems.is_empty() {
+                    println!("✓ {}", id);
+                } else {
+                    failed = true;
+                    println!("✗ {}", id);
+                    for problem in problems {
+                        println!("    {}", problem);
+                    }
+                }
+            }
+            if failed {
+                bail!("Some models failed verification - run `swictation-models download` to repair them");
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,254 @@
+//! Safe reads and writes of `config.toml`
+//!
+//! The daemon, the `config` CLI and the Tauri settings UI all edit the same
+//! file. Writers hold an exclusive lock on a sibling `config.toml.lock`
+//! while readers hold a shared one, so a reader never sees a half-written
+//! file and two writers never interleave. A write goes to a temporary file
+//! that is read back and checksummed before it is renamed over the config,
+//! so a crash or a full disk leaves the old file intact.
+//!
+//! Writes edit the existing document with `toml_edit`: values change in
+//! place, keeping the user's comments, key order and formatting.
+
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use sha2::{Digest, Sha256};
+use toml_edit::{Item, Table};
+
+pub use toml_edit::DocumentMut;
+
+/// Read the config file under a shared lock (`None` if it does not exist)
+pub fn read(path: &Path) -> Result<Option<String>> {
+    let lock = open_lock(path)?;
+    lock.lock_shared()
+        .with_context(|| format!("Failed to lock {}", path.display()))?;
+
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    }
+}
+
+/// Edit the config document under an exclusive lock and write it back
+/// atomically
+///
+/// A missing file starts as an empty document. `edit` may fail to abort
+/// the write, leaving the file untouched.
+pub fn update(path: &Path, edit: impl FnOnce(&mut DocumentMut) -> Result<()>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create config directory")?;
+    }
+    let lock = open_lock(path)?;
+    lock.lock()
+        .with_context(|| format!("Failed to lock {}", path.display()))?;
+
+    let mut doc = match fs::read_to_string(path) {
+        Ok(contents) => contents
+            .parse::<DocumentMut>()
+            .with_context(|| format!("Failed to parse {}", path.display()))?,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => DocumentMut::new(),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    edit(&mut doc)?;
+    write_atomic(path, &doc.to_string())
+}
+
+/// Copy the values of `new` into `doc`, keeping the comments and layout of
+/// keys that already exist
+///
+/// With `prune`, keys of `doc` missing from `new` are removed (for writers
+/// that serialize the whole config; a partial view of it keeps them).
+pub fn merge(doc: &mut DocumentMut, new: &DocumentMut, prune: bool) {
+    merge_table(doc.as_table_mut(), new.as_table(), prune);
+}
+
+fn merge_table(current: &mut Table, new: &Table, prune: bool) {
+    if prune {
+        let stale: Vec<String> = current
+            .iter()
+            .map(|(key, _)| key.to_string())
+            .filter(|key| !new.contains_key(key))
+            .collect();
+        for key in stale {
+            current.remove(&key);
+        }
+    }
+
+    for (key, item) in new.iter() {
+        match (current.get_mut(key), item) {
+            (Some(Item::Table(existing)), Item::Table(table)) => {
+                merge_table(existing, table, prune)
+            }
+            (Some(Item::Value(existing)), Item::Value(value)) => {
+                if !same_value(existing, value) {
+                    let decor = existing.decor().clone();
+                    *existing = value.clone();
+                    *existing.decor_mut() = decor;
+                }
+            }
+            _ => {
+                current.insert(key, item.clone());
+            }
+        }
+    }
+}
+
+/// Whether writing `new` over `existing` would change nothing
+///
+/// f32 settings serialize widened (0.4 becomes 0.4000000059604645), so a
+/// hand-written 0.4 counts as unchanged.
+fn same_value(existing: &toml_edit::Value, new: &toml_edit::Value) -> bool {
+    match (existing.as_float(), new.as_float()) {
+        (Some(a), Some(b)) => a as f32 == b as f32,
+        _ => existing.to_string().trim() == new.to_string().trim(),
+    }
+}
+
+/// `config.toml.lock` next to `config.toml`
+fn lock_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(".lock");
+    path.with_file_name(name)
+}
+
+fn open_lock(path: &Path) -> Result<File> {
+    let lock_path = lock_path(path);
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .with_context(|| format!("Failed to open {}", lock_path.display()))
+}
+
+/// Write `contents` to a temporary file, verify it, and rename it over `path`
+fn write_atomic(path: &Path, contents: &str) -> Result<()> {
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(".{}.tmp", std::process::id()));
+    let tmp_path = path.with_file_name(name);
+
+    let result = (|| {
+        let mut file = File::create(&tmp_path)
+            .with_context(|| format!("Failed to create {}", tmp_path.display()))?;
+        file.write_all(contents.as_bytes())?;
+        file.sync_all()?;
+        drop(file);
+
+        // Keep the permissions of the file being replaced
+        if let Ok(metadata) = fs::metadata(path) {
+            fs::set_permissions(&tmp_path, metadata.permissions())?;
+        }
+
+        let written = fs::read(&tmp_path)?;
+        if Sha256::digest(&written) != Sha256::digest(contents.as_bytes()) {
+            bail!("Checksum mismatch reading back {}", tmp_path.display());
+        }
+
+        fs::rename(&tmp_path, path)
+            .with_context(|| format!("Failed to replace {}", path.display()))?;
+        sync_parent(path);
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result.with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Persist the rename itself (best effort; not possible on Windows)
+fn sync_parent(path: &Path) {
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        if let Ok(dir) = File::open(parent) {
+            let _ = dir.sync_all();
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_config(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "swictation-config-file-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("config.toml")
+    }
+
+    #[test]
+    fn test_merge_keeps_comments_and_order() {
+        let mut doc: DocumentMut = "# Tuned for my desk mic\nvad_threshold = 0.25 # quiet room\n\
+                                    socket_path = \"/tmp/s.sock\"\n\n[hotkeys]\n# toggle\ntoggle = \"F9\"\n"
+            .parse()
+            .unwrap();
+        let new: DocumentMut = "socket_path = \"/tmp/s.sock\"\nvad_threshold = 0.5\n\
+                                new_key = 1\n\n[hotkeys]\ntoggle = \"F10\"\n"
+            .parse()
+            .unwrap();
+
+        merge(&mut doc, &new, false);
+        assert_eq!(
+            doc.to_string(),
+            "# Tuned for my desk mic\nvad_threshold = 0.5 # quiet room\n\
+             socket_path = \"/tmp/s.sock\"\nnew_key = 1\n\n[hotkeys]\n# toggle\ntoggle = \"F10\"\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_prune_removes_missing_keys() {
+        let mut doc: DocumentMut = "a = 1\nb = 2\n".parse().unwrap();
+        let new: DocumentMut = "a = 1\n".parse().unwrap();
+
+        merge(&mut doc, &new, true);
+        assert_eq!(doc.to_string(), "a = 1\n");
+    }
+
+    #[test]
+    fn test_update_writes_atomically() {
+        let path = temp_config("update");
+        update(&path, |doc| {
+            doc["vad_threshold"] = toml_edit::value(0.25);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(read(&path).unwrap().unwrap(), "vad_threshold = 0.25\n");
+
+        // A failed edit leaves the file alone
+        let failed = update(&path, |doc| {
+            doc["vad_threshold"] = toml_edit::value(0.9);
+            bail!("rejected")
+        });
+        assert!(failed.is_err());
+        assert_eq!(read(&path).unwrap().unwrap(), "vad_threshold = 0.25\n");
+
+        // Only the config and its lock file remain
+        let mut names: Vec<String> = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["config.toml", "config.toml.lock"]);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_read_missing_file() {
+        let path = temp_config("missing");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        assert!(read(&path).unwrap().is_none());
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -11,6 +11,8 @@
 //! | macOS    | `~/Library/Application Support/swictation` | Same as data dir |
 //! | Windows  | `%APPDATA%/swictation` | Named pipes (future) |
 
+pub mod config_file;
+
 use std::fs;
 use std::path::PathBuf;
 
@@ -42,6 +44,9 @@ const IPC_SOCKET_NAME: &str = "swictation.sock";
 /// Socket file name for metrics communication.
 const METRICS_SOCKET_NAME: &str = "swictation_metrics.sock";
 
+/// Socket file name for high-rate metrics (audio levels).
+const FIREHOSE_SOCKET_NAME: &str = "swictation_firehose.sock";
+
 /// Get the application data directory.
 ///
 /// Creates the directory if it doesn't exist with secure permissions (0o700).
@@ -139,6 +144,16 @@ pub fn get_metrics_socket_path() -> Result<PathBuf> {
     Ok(socket_dir.join(METRICS_SOCKET_NAME))
 }
 
+/// Get the path to the firehose socket, which carries the high-rate events
+/// kept off the metrics socket.
+///
+/// # Errors
+/// Returns an error if the socket directory cannot be determined.
+pub fn get_firehose_socket_path() -> Result<PathBuf> {
+    let socket_dir = get_socket_dir()?;
+    Ok(socket_dir.join(FIREHOSE_SOCKET_NAME))
+}
+
 /// Get the models directory.
 ///
 /// # Platform Behavior
@@ -368,6 +383,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_firehose_socket_path() {
+        let path = get_firehose_socket_path().expect("Should get socket path");
+        assert_eq!(path.parent(), get_metrics_socket_path().unwrap().parent());
+        assert!(path.ends_with("swictation_firehose.sock"));
+    }
+
     #[test]
     fn test_models_dir() {
         let dir = get_models_dir().expect("Should get models directory");
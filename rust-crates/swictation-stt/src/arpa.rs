@@ -0,0 +1,329 @@
+//! Back-off n-gram language models in ARPA format for shallow fusion
+//!
+//! Lets a domain language model built with the usual toolkits (KenLM's
+//! `lmplz`, SRILM) score word prefixes during decoding, so rare domain terms
+//! it knows win close calls. Any other model, neural ones included, can be
+//! plugged in by implementing [`PrefixScorer`] directly.
+//!
+//! Words are lowercased on load to match the decoder's word alphabet.
+
+use crate::error::{Result, SttError};
+use crate::fusion::PrefixScorer;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tracing::info;
+
+/// Probability of a prefix no known word starts with, when the model has no
+/// `<unk>`
+const UNKNOWN_PROB: f64 = 1e-7;
+
+/// ARPA line: words, log10 probability, log10 back-off weight
+type Entry = (Vec<String>, f64, Option<f64>);
+
+/// Explicit continuations of one context
+#[derive(Debug)]
+struct Context {
+    /// Back-off weight (linear)
+    backoff: f64,
+    /// (word id, probability), sorted by word id
+    next: Vec<(u32, f64)>,
+}
+
+/// ARPA back-off n-gram model
+#[derive(Debug)]
+pub struct ArpaModel {
+    order: usize,
+    /// Words sorted, so those sharing a prefix have consecutive ids
+    words: Vec<String>,
+    index: HashMap<String, u32>,
+    unigram: Vec<f64>,
+    /// Running sums of `unigram` (one longer than it)
+    unigram_cumulative: Vec<f64>,
+    contexts: HashMap<Vec<u32>, Context>,
+    sentence_start: Option<u32>,
+    unknown: f64,
+}
+
+impl ArpaModel {
+    /// Load an ARPA file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|e| {
+            SttError::model_load(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        let model = Self::parse(&contents)?;
+        info!(
+            "✓ ARPA language model loaded from {} ({}-gram, {} words)",
+            path.display(),
+            model.order,
+            model.words.len()
+        );
+        Ok(model)
+    }
+
+    /// Parse the text of an ARPA file
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut ngrams: Vec<Vec<Entry>> = Vec::new();
+        let mut section = 0;
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line == "\\data\\" || line.starts_with("ngram ") {
+                continue;
+            }
+            if line == "\\end\\" {
+                break;
+            }
+            if let Some(order) = line
+                .strip_prefix('\\')
+                .and_then(|rest| rest.strip_suffix("-grams:"))
+            {
+                section = order.parse().map_err(|_| {
+                    SttError::model_load(format!("Bad ARPA section header: {}", line))
+                })?;
+                if ngrams.len() < section {
+                    ngrams.resize_with(section, Vec::new);
+                }
+                continue;
+            }
+            if section == 0 {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let bad_line =
+                || SttError::model_load(format!("Bad ARPA entry on line {}", number + 1));
+            if fields.len() != section + 1 && fields.len() != section + 2 {
+                return Err(bad_line());
+            }
+            let log_prob: f64 = fields[0].parse().map_err(|_| bad_line())?;
+            let backoff = match fields.get(section + 1) {
+                Some(field) => Some(field.parse::<f64>().map_err(|_| bad_line())?),
+                None => None,
+            };
+            let words = fields[1..=section]
+                .iter()
+                .map(|w| w.to_lowercase())
+                .collect();
+            ngrams[section - 1].push((words, log_prob, backoff));
+        }
+        if ngrams.first().is_none_or(Vec::is_empty) {
+            return Err(SttError::model_load("ARPA model has no 1-grams"));
+        }
+
+        let mut words: Vec<String> = ngrams[0].iter().map(|(w, _, _)| w[0].clone()).collect();
+        words.sort();
+        words.dedup();
+        let index: HashMap<String, u32> = words
+            .iter()
+            .enumerate()
+            .map(|(id, word)| (word.clone(), id as u32))
+            .collect();
+
+        let mut unigram = vec![0.0; words.len()];
+        let mut contexts: HashMap<Vec<u32>, Context> = HashMap::new();
+        for (n, entries) in ngrams.iter().enumerate() {
+            for (entry_words, log_prob, backoff) in entries {
+                // Higher orders may only use words listed as 1-grams
+                let Some(ids) = entry_words
+                    .iter()
+                    .map(|w| index.get(w).copied())
+                    .collect::<Option<Vec<u32>>>()
+                else {
+                    continue;
+                };
+                let prob = 10f64.powf(*log_prob);
+                if n == 0 {
+                    // Words differing only in case share one entry
+                    unigram[ids[0] as usize] += prob;
+                } else {
+                    let (word, context) = ids.split_last().expect("n-grams have words");
+                    contexts
+                        .entry(context.to_vec())
+                        .or_insert_with(|| Context {
+                            backoff: 1.0,
+                            next: Vec::new(),
+                        })
+                        .next
+                        .push((*word, prob));
+                }
+                if let Some(backoff) = backoff {
+                    contexts
+                        .entry(ids)
+                        .or_insert_with(|| Context {
+                            backoff: 1.0,
+                            next: Vec::new(),
+                        })
+                        .backoff = 10f64.powf(*backoff);
+                }
+            }
+        }
+        for context in contexts.values_mut() {
+            context.next.sort_by_key(|&(id, _)| id);
+            context.next.dedup_by_key(|&mut (id, _)| id);
+        }
+
+        let mut unigram_cumulative = Vec::with_capacity(unigram.len() + 1);
+        unigram_cumulative.push(0.0);
+        let mut total = 0.0;
+        for p in &unigram {
+            total += p;
+            unigram_cumulative.push(total);
+        }
+
+        let unknown = index
+            .get("<unk>")
+            .map_or(UNKNOWN_PROB, |&id| unigram[id as usize].max(UNKNOWN_PROB));
+        Ok(Self {
+            order: ngrams.len(),
+            sentence_start: index.get("<s>").copied(),
+            words,
+            index,
+            unigram,
+            unigram_cumulative,
+            contexts,
+            unknown,
+        })
+    }
+
+    /// Highest n-gram order
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    /// The last `order - 1` history words as ids, stopping at a word the
+    /// model doesn't know
+    fn context_ids(&self, history: &[&str]) -> Vec<u32> {
+        let mut ids = Vec::new();
+        let sentence = self.sentence_start.map(Some);
+        let known = history
+            .iter()
+            .rev()
+            .map(|word| self.index.get(&word.to_lowercase()).copied())
+            .chain(sentence);
+        for id in known.take(self.order.saturating_sub(1)) {
+            match id {
+                Some(id) => ids.push(id),
+                None => break,
+            }
+        }
+        ids.reverse();
+        ids
+    }
+
+    /// P(word | context) with back-off
+    fn prob(&self, context: &[u32], word: u32) -> f64 {
+        if context.is_empty() {
+            return self.unigram[word as usize];
+        }
+        match self.contexts.get(context) {
+            Some(c) => match c.next.binary_search_by_key(&word, |&(id, _)| id) {
+                Ok(i) => c.next[i].1,
+                Err(_) => c.backoff * self.prob(&context[1..], word),
+            },
+            None => self.prob(&context[1..], word),
+        }
+    }
+
+    /// Total P(w | context) over word ids `lo..hi`
+    fn mass(&self, context: &[u32], lo: u32, hi: u32) -> f64 {
+        if context.is_empty() {
+            return self.unigram_cumulative[hi as usize] - self.unigram_cumulative[lo as usize];
+        }
+        let lower = self.mass(&context[1..], lo, hi);
+        let Some(c) = self.contexts.get(context) else {
+            return lower;
+        };
+        // Listed continuations replace their backed-off share
+        let start = c.next.partition_point(|&(id, _)| id < lo);
+        let end = c.next.partition_point(|&(id, _)| id < hi);
+        let (explicit, replaced) =
+            c.next[start..end]
+                .iter()
+                .fold((0.0, 0.0), |(explicit, replaced), &(id, p)| {
+                    (explicit + p, replaced + self.prob(&context[1..], id))
+                });
+        explicit + c.backoff * (lower - replaced).max(0.0)
+    }
+}
+
+impl PrefixScorer for ArpaModel {
+    fn prefix_log_prob(&self, history: &[&str], prefix: &str) -> f32 {
+        if prefix.is_empty() {
+            return 0.0;
+        }
+        let prefix = prefix.to_lowercase();
+        let lo = self.words.partition_point(|w| w.as_str() < prefix.as_str());
+        let hi = lo + self.words[lo..].partition_point(|w| w.starts_with(&prefix));
+        let mass = self.mass(&self.context_ids(history), lo as u32, hi as u32);
+        mass.clamp(self.unknown, 1.0).ln() as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARPA: &str = "\\data\\
+ngram 1=6
+ngram 2=3
+
+\\1-grams:
+-1.0\t<s>\t-0.5
+-0.5\tthe\t-0.3
+-1.0\tarchon\t-0.2
+-1.0\tarcade
+-0.8\tcat
+-2.0\t<unk>
+
+\\2-grams:
+-0.2\t<s> the
+-0.1\tthe archon
+-0.7\tthe cat
+
+\\end\\
+";
+
+    fn p(log10: f64) -> f64 {
+        10f64.powf(log10)
+    }
+
+    #[test]
+    fn test_parse() {
+        let model = ArpaModel::parse(ARPA).unwrap();
+        assert_eq!(model.order(), 2);
+        assert_eq!(model.words.len(), 6);
+        assert!(ArpaModel::parse("\\data\\\n\\end\\\n").is_err());
+    }
+
+    #[test]
+    fn test_prefix_sums_explicit_and_backed_off() {
+        let model = ArpaModel::parse(ARPA).unwrap();
+        // After "the": "archon" is listed, "arcade" backs off through "the"
+        let expected = p(-0.1) + p(-0.3) * p(-1.0);
+        let got = model.prefix_log_prob(&["the"], "arc") as f64;
+        assert!(
+            (got - expected.ln()).abs() < 1e-5,
+            "{} vs {}",
+            got,
+            expected.ln()
+        );
+
+        // The listed continuation makes the domain term likelier than the unigram
+        assert!(model.prefix_log_prob(&["the"], "archon") > model.prefix_log_prob(&[], "archon"));
+    }
+
+    #[test]
+    fn test_unknown_words() {
+        let model = ArpaModel::parse(ARPA).unwrap();
+        assert_eq!(model.prefix_log_prob(&["the"], ""), 0.0);
+        // No word starts with "zz": floor at <unk>
+        let unk = (p(-2.0)).ln() as f32;
+        assert!((model.prefix_log_prob(&[], "zz") - unk).abs() < 1e-5);
+        // An unknown history word leaves only the unigram context
+        assert_eq!(
+            model.prefix_log_prob(&["zebra"], "cat"),
+            (p(-0.8)).ln() as f32
+        );
+    }
+}
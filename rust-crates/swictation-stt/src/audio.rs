@@ -6,9 +6,11 @@
 use crate::error::{Result, SttError};
 use hound::WavReader;
 use ndarray::{s, Array2};
-use rustfft::{num_complex::Complex, FftPlanner};
+use realfft::{num_complex::Complex, RealFftPlanner, RealToComplex};
 use std::f32::consts::PI;
+use std::ops::Range;
 use std::path::Path;
+use std::sync::Arc;
 use symphonia::core::audio::SampleBuffer;
 use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
 use symphonia::core::formats::FormatOptions;
@@ -29,9 +31,20 @@ pub const WIN_LENGTH: usize = 400; // 25ms window at 16kHz
 pub const CHUNK_FRAMES: usize = 10000; // Frames per encoder chunk (increased to process full audio)
 
 /// Audio processor for Parakeet-TDT models
+///
+/// The FFT plan, window and per-frame buffers are made once and reused for
+/// every frame of every call, so feature extraction allocates only its
+/// output.
 pub struct AudioProcessor {
     mel_filters: Array2<f32>,
-    fft_planner: FftPlanner<f32>,
+    /// Nonzero frequency bins of each mel filter (a few dozen of 257)
+    mel_bands: Vec<Range<usize>>,
+    fft: Arc<dyn RealToComplex<f32>>,
+    window: Vec<f32>,
+    frame: Vec<f32>,
+    spectrum: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+    power: Vec<f32>,
     n_mel_features: usize,
 }
 
@@ -59,9 +72,32 @@ impl AudioProcessor {
             7600.0, // high_freq = -400 = 16000/2 - 400 = 7600 Hz
         );
 
+        let mel_bands = mel_filters
+            .rows()
+            .into_iter()
+            .map(|filter| {
+                let start = filter.iter().position(|&w| w != 0.0).unwrap_or(0);
+                let end = filter
+                    .iter()
+                    .rposition(|&w| w != 0.0)
+                    .map_or(start, |i| i + 1);
+                start..end
+            })
+            .collect();
+        let fft = RealFftPlanner::<f32>::new().plan_fft_forward(N_FFT);
+
         Ok(Self {
             mel_filters,
-            fft_planner: FftPlanner::new(),
+            mel_bands,
+            frame: fft.make_input_vec(),
+            spectrum: fft.make_output_vec(),
+            scratch: fft.make_scratch_vec(),
+            fft,
+            // CRITICAL FIX: Use Povey window for NeMo models
+            // sherpa-onnx uses "povey" window_type for non-GigaAM NeMo models
+            // (GigaAM explicitly sets "hann", but Parakeet-TDT uses default "povey")
+            window: povey_window(WIN_LENGTH),
+            power: vec![0.0; N_FFT / 2 + 1],
             n_mel_features,
         })
     }
@@ -262,163 +298,60 @@ impl AudioProcessor {
     pub fn extract_mel_features(&mut self, samples: &[f32]) -> Result<Array2<f32>> {
         debug!("Extracting mel-spectrogram from {} samples", samples.len());
 
-        // DEBUG: Check raw audio before normalization
-        let mean: f32 = samples.iter().sum::<f32>() / samples.len() as f32;
-        let rms = (samples.iter().map(|&x| x * x).sum::<f32>() / samples.len() as f32).sqrt();
-        debug!("RAW audio BEFORE normalization:");
-        debug!("  Mean: {:.6}, RMS: {:.6}", mean, rms);
-        debug!(
-            "  Min: {:.6}, Max: {:.6}",
-            samples.iter().fold(f32::INFINITY, |a, &b| a.min(b)),
-            samples.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b))
-        );
-
-        // TEST 4: Remove sample normalization (reference parakeet-rs doesn't have this)
-        // The Queen's analysis identified "Extra sample normalization" as a potential problem
-        // Reference implementation: i16 → f32/32768.0 → NO sample normalization → preemphasis
-        // Our old implementation: i16 → f32/32768.0 → sample normalization → preemphasis (WRONG!)
-        debug!("TEST 4: Skipping sample normalization (raw samples only)");
-
-        // Apply preemphasis filter directly to raw samples (like reference implementation)
+        // Preemphasis goes straight onto the raw samples: like the reference
+        // parakeet-rs, the samples themselves are not normalized first
         let preemphasized = apply_preemphasis(samples, 0.97);
 
-        // Compute STFT
-        let stft = self.compute_stft(&preemphasized)?;
-        debug!("STFT shape: {:?}", stft.shape());
-
-        // Compute power spectrogram
-        // Kaldi fbank default is use_power=True (power spectrum)
-        // This computes |FFT|² = re² + im²
-        let power_spec = stft.mapv(|c| c.re * c.re + c.im * c.im);
-
-        // Apply mel filterbank
-        // power_spec is (frames, freqs) = (num_frames, 257)
-        // mel_filters is (n_mels, freqs) = (128, 257)
-        // Result should be (frames, n_mels) = (num_frames, 128)
-        let mel_spec = power_spec.dot(&self.mel_filters.t());
-        debug!("Mel spectrogram shape: {:?}", mel_spec.shape());
-
-        // Apply log scaling (add small epsilon to avoid log(0))
-        let mut log_mel = mel_spec.mapv(|x| (x + 1e-10).ln());
-
-        // TEST 1: Add per-feature normalization like reference implementation (parakeet-rs)
-        // Reference: /var/tmp/parakeet-rs/src/audio.rs lines 162-176
-        // This normalizes each mel feature (column) independently to mean=0, std=1
-        // across the time dimension (frames). This is different from audio sample
-        // normalization which happens earlier - this operates on the mel features themselves.
-        debug!("Applying per-feature normalization (mean=0, std=1) across time dimension...");
-
-        let num_frames = log_mel.nrows();
-        let num_features = log_mel.ncols();
-
-        for feat_idx in 0..num_features {
-            let mut column = log_mel.column_mut(feat_idx);
-            let mean: f32 = column.iter().sum::<f32>() / num_frames as f32;
-            let variance: f32 =
-                column.iter().map(|&x| (x - mean).powi(2)).sum::<f32>() / num_frames as f32;
-            let std = variance.sqrt().max(1e-10);
-
-            for val in column.iter_mut() {
-                *val = (*val - mean) / std;
-            }
-        }
-
-        debug!(
-            "Per-feature normalization applied: {} features normalized",
-            num_features
-        );
-
-        // DEBUG: Check log_mel values AFTER per-feature normalization
-        if log_mel.nrows() > 0 {
-            debug!(
-                "AFTER per-feature normalization - first frame (first 10): {:?}",
-                &log_mel.row(0).as_slice().unwrap()[..10.min(log_mel.ncols())]
-            );
-            if log_mel.nrows() > 10 {
-                debug!(
-                    "AFTER per-feature normalization - middle frame {} (first 10): {:?}",
-                    log_mel.nrows() / 2,
-                    &log_mel.row(log_mel.nrows() / 2).as_slice().unwrap()
-                        [..10.min(log_mel.ncols())]
-                );
-            }
-            debug!(
-                "AFTER per-feature normalization - stats: min={:.6}, max={:.6}, mean={:.6}",
-                log_mel.iter().fold(f32::INFINITY, |a, &b| a.min(b)),
-                log_mel.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b)),
-                log_mel.mean().unwrap_or(0.0)
-            );
-
-            // DEBUG: Check mel filterbank to ensure it's not all zeros
-            debug!(
-                "Mel filterbank sum: {:.6}, shape: {:?}",
-                self.mel_filters.sum(),
-                self.mel_filters.shape()
-            );
-
-            // Check which mel bins have non-zero sum (should be ALL of them!)
-            for mel_idx in 0..8 {
-                let row_sum: f32 = self.mel_filters.row(mel_idx).sum();
-                debug!("Mel bin {}: sum={:.6}", mel_idx, row_sum);
+        let num_frames = if preemphasized.len() < WIN_LENGTH {
+            0
+        } else {
+            (preemphasized.len() - WIN_LENGTH) / HOP_LENGTH + 1
+        };
+        let mut log_mel = Array2::zeros((num_frames, self.n_mel_features));
+        for (frame_idx, mut row) in log_mel.rows_mut().into_iter().enumerate() {
+            let start = frame_idx * HOP_LENGTH;
+            self.power_spectrum(&preemphasized[start..start + WIN_LENGTH])?;
+
+            // Each triangular filter only covers its own band of bins, so
+            // only those are multiplied (log with a small epsilon for log(0))
+            for ((mel, filter), band) in row
+                .iter_mut()
+                .zip(self.mel_filters.rows())
+                .zip(&self.mel_bands)
+            {
+                let weights = &filter.as_slice().expect("filterbank is contiguous")[band.clone()];
+                *mel = (dot(weights, &self.power[band.clone()]) + 1e-10).ln();
             }
         }
 
-        // TEST 1 IMPLEMENTATION: Per-feature normalization now APPLIED
-        // Testing hypothesis: reference parakeet-rs applies per-feature normalization
-        // This normalizes each mel bin independently across time (mean=0, std=1)
-        // Previous implementation had NO per-feature normalization based on sherpa-onnx investigation
-        // Now testing if adding it matches reference implementation behavior
+        // Per-feature normalization like the reference parakeet-rs: each
+        // mel bin (column) gets mean=0, std=1 across time
+        normalize_features(&mut log_mel);
 
-        debug!(
-            "Extracted features: shape {:?} (with per-feature normalization - TEST 1)",
-            log_mel.shape()
-        );
+        debug!("Extracted features: shape {:?}", log_mel.shape());
         Ok(log_mel)
     }
 
-    /// Compute Short-Time Fourier Transform (STFT)
-    fn compute_stft(&mut self, samples: &[f32]) -> Result<Array2<Complex<f32>>> {
-        let num_frames = (samples.len() - WIN_LENGTH) / HOP_LENGTH + 1;
-        let mut stft = Array2::zeros((num_frames, N_FFT / 2 + 1));
-
-        // CRITICAL FIX: Use Povey window for NeMo models
-        // sherpa-onnx uses "povey" window_type for non-GigaAM NeMo models
-        // (GigaAM explicitly sets "hann", but Parakeet-TDT uses default "povey")
-        let window = povey_window(WIN_LENGTH);
-
-        // Create FFT plan
-        let fft = self.fft_planner.plan_fft_forward(N_FFT);
-
-        for frame_idx in 0..num_frames {
-            let start = frame_idx * HOP_LENGTH;
-            let end = start + WIN_LENGTH;
-
-            if end > samples.len() {
-                break;
-            }
-
-            // NOTE: NeMo models use remove_dc_offset = FALSE
-            // See sherpa-onnx/csrc/offline-recognizer-transducer-nemo-impl.h:167
-            // GigaAM uses remove_dc_offset = false
-            // Standard NeMo models (Parakeet-TDT) also use remove_dc_offset = false
+    /// Power spectrum of one window of samples into `self.power`
+    fn power_spectrum(&mut self, samples: &[f32]) -> Result<()> {
+        // NOTE: NeMo models use remove_dc_offset = FALSE
+        // See sherpa-onnx/csrc/offline-recognizer-transducer-nemo-impl.h:167
+        // so the window is applied as is, then zero-padded to N_FFT (the FFT
+        // uses its input as scratch space, so the padding is rewritten too)
+        for ((x, &sample), &w) in self.frame.iter_mut().zip(samples).zip(&self.window) {
+            *x = sample * w;
+        }
+        self.frame[WIN_LENGTH..].fill(0.0);
 
-            // Apply window and zero-pad to N_FFT
-            let mut buffer: Vec<Complex<f32>> = vec![Complex::new(0.0, 0.0); N_FFT];
-            for i in 0..WIN_LENGTH {
-                // NO DC offset removal for NeMo models!
-                buffer[i] = Complex::new(samples[start + i] * window[i], 0.0);
-            }
+        self.fft
+            .process_with_scratch(&mut self.frame, &mut self.spectrum, &mut self.scratch)
+            .map_err(|e| SttError::audio_processing(format!("FFT failed: {}", e)))?;
 
-            // Compute FFT
-            fft.process(&mut buffer);
-
-            // Store magnitude spectrum (first half + DC and Nyquist)
-            for i in 0..=N_FFT / 2 {
-                stft[[frame_idx, i]] = buffer[i];
-            }
+        // Kaldi fbank default is use_power=True: |FFT|² = re² + im²
+        for (power, c) in self.power.iter_mut().zip(&self.spectrum) {
+            *power = c.re * c.re + c.im * c.im;
         }
-
-        Ok(stft)
+        Ok(())
     }
 
     /// Export mel features to CSV for comparison with Python
@@ -528,7 +461,7 @@ fn apply_preemphasis(audio: &[f32], coef: f32) -> Vec<f32> {
     let mut result = Vec::with_capacity(audio.len());
 
     // First sample remains unchanged
-    result.push(audio[0]);
+    result.extend(audio.first());
 
     // Apply filter to remaining samples
     for i in 1..audio.len() {
@@ -538,6 +471,64 @@ fn apply_preemphasis(audio: &[f32], coef: f32) -> Vec<f32> {
     result
 }
 
+/// Dot product summed in eight independent lanes
+///
+/// A single running sum makes every add wait for the previous one; with
+/// eight accumulators LLVM emits vector multiply-adds (SSE/AVX/NEON)
+/// without needing nightly `std::simd`.
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    let mut lanes = [0.0f32; 8];
+    let (a8, b8) = (a.chunks_exact(8), b.chunks_exact(8));
+    let tail: f32 = a8
+        .remainder()
+        .iter()
+        .zip(b8.remainder())
+        .map(|(x, y)| x * y)
+        .sum();
+    for (x, y) in a8.zip(b8) {
+        for ((lane, x), y) in lanes.iter_mut().zip(x).zip(y) {
+            *lane += x * y;
+        }
+    }
+    lanes.iter().sum::<f32>() + tail
+}
+
+/// Normalize each column of `features` to mean 0 and standard deviation 1
+///
+/// Walks the rows in memory order with one accumulator per column, instead
+/// of striding down each column.
+fn normalize_features(features: &mut Array2<f32>) {
+    let frames = features.nrows() as f32;
+    if features.nrows() == 0 {
+        return;
+    }
+
+    let mut mean = vec![0.0f32; features.ncols()];
+    for row in features.rows() {
+        for (m, &x) in mean.iter_mut().zip(row) {
+            *m += x;
+        }
+    }
+    mean.iter_mut().for_each(|m| *m /= frames);
+
+    let mut variance = vec![0.0f32; features.ncols()];
+    for row in features.rows() {
+        for ((v, &x), &m) in variance.iter_mut().zip(row).zip(&mean) {
+            *v += (x - m) * (x - m);
+        }
+    }
+    let inv_std: Vec<f32> = variance
+        .iter()
+        .map(|v| 1.0 / (v / frames).sqrt().max(1e-10))
+        .collect();
+
+    for mut row in features.rows_mut() {
+        for ((x, &m), &s) in row.iter_mut().zip(&mean).zip(&inv_std) {
+            *x = (*x - m) * s;
+        }
+    }
+}
+
 /// Convert Hz to mel scale
 fn hz_to_mel(hz: f32) -> f32 {
     2595.0 * (1.0 + hz / 700.0).log10()
@@ -620,4 +611,78 @@ mod tests {
             &[N_MEL_FEATURES, N_FFT / 2 + 1]
         );
     }
+
+    /// Straightforward version: complex FFT per frame, dense filterbank
+    /// product, column-wise normalization
+    fn reference_mel_features(processor: &AudioProcessor, samples: &[f32]) -> Array2<f32> {
+        let preemphasized = apply_preemphasis(samples, 0.97);
+        let num_frames = (preemphasized.len() - WIN_LENGTH) / HOP_LENGTH + 1;
+        let window = povey_window(WIN_LENGTH);
+        let fft = rustfft::FftPlanner::new().plan_fft_forward(N_FFT);
+        let mut power = Array2::<f32>::zeros((num_frames, N_FFT / 2 + 1));
+        for frame in 0..num_frames {
+            let mut buffer = vec![Complex::new(0.0, 0.0); N_FFT];
+            for (i, x) in buffer.iter_mut().take(WIN_LENGTH).enumerate() {
+                *x = Complex::new(preemphasized[frame * HOP_LENGTH + i] * window[i], 0.0);
+            }
+            fft.process(&mut buffer);
+            for (bin, c) in buffer.iter().take(N_FFT / 2 + 1).enumerate() {
+                power[[frame, bin]] = c.norm_sqr();
+            }
+        }
+        let mut log_mel = power
+            .dot(&processor.mel_filters.t())
+            .mapv(|x| (x + 1e-10).ln());
+        for mut column in log_mel.columns_mut() {
+            let mean = column.sum() / num_frames as f32;
+            let std = (column.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / num_frames as f32)
+                .sqrt()
+                .max(1e-10);
+            column.mapv_inplace(|x| (x - mean) / std);
+        }
+        log_mel
+    }
+
+    #[test]
+    fn test_mel_features_match_reference() {
+        // A rising tone over a little deterministic noise
+        let mut seed = 1u32;
+        let samples: Vec<f32> = (0..24000)
+            .map(|i| {
+                seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                let t = i as f32 / SAMPLE_RATE as f32;
+                0.3 * (2.0 * PI * (200.0 + 800.0 * t) * t).sin()
+                    + 0.01 * (seed >> 8) as f32 / (1 << 24) as f32
+            })
+            .collect();
+
+        for n_mels in [N_MEL_FEATURES, N_MEL_FEATURES_1_1B] {
+            let mut processor = AudioProcessor::with_mel_features(n_mels).unwrap();
+            let features = processor.extract_mel_features(&samples).unwrap();
+            let expected = reference_mel_features(&processor, &samples);
+            assert_eq!(features.shape(), expected.shape());
+            let max_error = features
+                .iter()
+                .zip(&expected)
+                .map(|(a, b)| (a - b).abs())
+                .fold(0.0f32, f32::max);
+            assert!(max_error < 1e-3, "max error {}", max_error);
+
+            // Buffers are reused: a second call gives the same features
+            assert_eq!(processor.extract_mel_features(&samples).unwrap(), features);
+        }
+    }
+
+    #[test]
+    fn test_mel_features_of_too_little_audio() {
+        let mut processor = AudioProcessor::new().unwrap();
+        assert_eq!(processor.extract_mel_features(&[]).unwrap().nrows(), 0);
+        assert_eq!(
+            processor
+                .extract_mel_features(&[0.1; WIN_LENGTH - 1])
+                .unwrap()
+                .nrows(),
+            0
+        );
+    }
 }
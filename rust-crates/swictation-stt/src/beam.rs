@@ -0,0 +1,272 @@
+//! Beam search over the TDT transducer
+//!
+//! Greedy decoding commits to the joiner's best token at every step, so one
+//! noisy frame can derail the rest of a word. Beam search keeps the `width`
+//! best partial hypotheses instead, each with its own decoder state, and
+//! expands every one with its `width` likeliest tokens (blank included) at
+//! its current frame. Durations stay greedy: each hypothesis advances by its
+//! joiner's best duration, exactly as in greedy decoding.
+//!
+//! Hypotheses scoring more than `prune` below the best are dropped early,
+//! which keeps the cost close to greedy on clean audio where one path
+//! dominates. Every surviving non-blank expansion costs a decoder run and
+//! every hypothesis a joiner run per step, so worst-case latency grows
+//! about linearly with `width`.
+
+use crate::nbest::Emission;
+use ndarray::{Array1, Array3};
+
+/// Beam search settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BeamConfig {
+    /// Hypotheses kept per step (1 behaves like greedy decoding)
+    pub width: usize,
+    /// Log-probability below the best hypothesis at which others are dropped
+    pub prune: f32,
+}
+
+impl Default for BeamConfig {
+    fn default() -> Self {
+        Self {
+            width: 4,
+            prune: 10.0,
+        }
+    }
+}
+
+/// TDT step rules, shared by greedy and beam search
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TdtConfig {
+    /// Tokens emitted on one frame before decoding is forced onward
+    /// (sherpa-onnx uses 5)
+    pub max_tokens_per_frame: usize,
+    /// Most frames one step may skip, however long a duration the joiner
+    /// predicts (0 = as predicted). A small cap catches short words that
+    /// long skips jump over, at the cost of more joiner runs.
+    pub max_duration_skip: usize,
+    /// Subtracted from the blank logit before anything is chosen. Positive
+    /// values recover quiet or clipped words, negative ones suppress
+    /// spurious insertions.
+    pub blank_penalty: f32,
+}
+
+impl Default for TdtConfig {
+    fn default() -> Self {
+        Self {
+            max_tokens_per_frame: 5,
+            max_duration_skip: 0,
+            blank_penalty: 0.0,
+        }
+    }
+}
+
+impl TdtConfig {
+    /// The joiner's predicted duration, capped by `max_duration_skip`
+    pub(crate) fn skip(&self, predicted: usize) -> usize {
+        match self.max_duration_skip {
+            0 => predicted,
+            cap => predicted.min(cap),
+        }
+    }
+
+    /// Apply the blank penalty to joiner token logits
+    pub(crate) fn penalize_blank(&self, token_logits: &mut [f32], blank_id: usize) {
+        if let Some(logit) = token_logits.get_mut(blank_id) {
+            *logit -= self.blank_penalty;
+        }
+    }
+}
+
+/// How the recognizer searches the transducer output
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Decoding {
+    #[default]
+    Greedy,
+    Beam(BeamConfig),
+}
+
+/// A partial transcription and the decoder state behind it
+#[derive(Debug, Clone)]
+pub(crate) struct BeamHypothesis {
+    pub tokens: Vec<i64>,
+    pub emissions: Vec<Emission>,
+    /// Sum of the token log-probabilities (plus any LM bonus)
+    pub score: f32,
+    /// Frame of the current encoder chunk this hypothesis is at
+    pub t: usize,
+    pub tokens_this_frame: usize,
+    pub decoder_out: Array1<f32>,
+    pub decoder_state1: Option<Array3<f32>>,
+    pub decoder_state2: Option<Array3<f32>>,
+}
+
+/// One way to extend a hypothesis by a step
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Candidate {
+    /// Index of the hypothesis being extended
+    pub parent: usize,
+    /// Emitted token (`None` for blank, or for a finished hypothesis)
+    pub token: Option<i64>,
+    pub emission: Option<Emission>,
+    pub score: f32,
+    /// Frames to advance (the joiner's best duration)
+    pub skip: usize,
+    /// The parent had already consumed the chunk and is carried over as is
+    pub finished: bool,
+}
+
+/// Frame and per-frame token count after a step, with the same rules as
+/// greedy decoding: a duration resets the count, too many tokens or a
+/// blank on the same frame force a one-frame advance
+///
+/// `tokens_this_frame` already counts a token emitted by this step.
+pub(crate) fn advance(
+    t: usize,
+    tokens_this_frame: usize,
+    blank: bool,
+    skip: usize,
+    tdt: &TdtConfig,
+) -> (usize, usize) {
+    let mut skip = skip;
+    let mut tokens_this_frame = tokens_this_frame;
+    if skip > 0 {
+        tokens_this_frame = 0;
+    }
+    if tokens_this_frame >= tdt.max_tokens_per_frame || (blank && skip == 0) {
+        tokens_this_frame = 0;
+        skip = 1;
+    }
+    (t + skip, tokens_this_frame)
+}
+
+/// Natural-log softmax of `logits`
+pub(crate) fn log_softmax(logits: &[f32]) -> Vec<f32> {
+    let max = logits.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+    let log_sum = max + logits.iter().map(|&l| (l - max).exp()).sum::<f32>().ln();
+    logits.iter().map(|&l| l - log_sum).collect()
+}
+
+/// Indices of the `k` largest values, largest first
+pub(crate) fn top_k(values: &[f32], k: usize) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..values.len()).collect();
+    indices.sort_by(|&a, &b| values[b].total_cmp(&values[a]));
+    indices.truncate(k);
+    indices
+}
+
+/// Keep the candidates worth expanding: within `prune` of the best, one
+/// per distinct result (`key`), at most `width`, best first
+pub(crate) fn select<K: PartialEq>(
+    mut candidates: Vec<Candidate>,
+    config: &BeamConfig,
+    key: impl Fn(&Candidate) -> K,
+) -> Vec<Candidate> {
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    let Some(best) = candidates.first().map(|c| c.score) else {
+        return candidates;
+    };
+
+    let mut kept: Vec<(K, Candidate)> = Vec::new();
+    for candidate in candidates {
+        if kept.len() >= config.width.max(1) || candidate.score < best - config.prune {
+            break;
+        }
+        let k = key(&candidate);
+        // Sorted, so the first of equal results scores best
+        if !kept.iter().any(|(seen, _)| *seen == k) {
+            kept.push((k, candidate));
+        }
+    }
+    kept.into_iter().map(|(_, candidate)| candidate).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(parent: usize, token: Option<i64>, score: f32) -> Candidate {
+        Candidate {
+            parent,
+            token,
+            emission: None,
+            score,
+            skip: 1,
+            finished: false,
+        }
+    }
+
+    #[test]
+    fn test_advance() {
+        let tdt = TdtConfig::default();
+        // Durations move on; a zero duration stays on the frame
+        assert_eq!(advance(3, 1, false, 2, &tdt), (5, 0));
+        assert_eq!(advance(3, 1, false, 0, &tdt), (3, 1));
+        // Blank never stays, and neither does a full frame
+        assert_eq!(advance(3, 0, true, 0, &tdt), (4, 0));
+        assert_eq!(advance(3, tdt.max_tokens_per_frame, false, 0, &tdt), (4, 0));
+
+        let strict = TdtConfig {
+            max_tokens_per_frame: 2,
+            ..tdt
+        };
+        assert_eq!(advance(3, 2, false, 0, &strict), (4, 0));
+    }
+
+    #[test]
+    fn test_tdt_skip_cap_and_blank_penalty() {
+        let capped = TdtConfig {
+            max_duration_skip: 2,
+            blank_penalty: 1.5,
+            ..TdtConfig::default()
+        };
+        assert_eq!(TdtConfig::default().skip(4), 4);
+        assert_eq!(capped.skip(4), 2);
+        assert_eq!(capped.skip(0), 0);
+
+        let mut logits = [1.0, 3.0, 2.0];
+        capped.penalize_blank(&mut logits, 1);
+        assert_eq!(logits, [1.0, 1.5, 2.0]);
+        capped.penalize_blank(&mut logits, 7);
+    }
+
+    #[test]
+    fn test_log_softmax() {
+        let lp = log_softmax(&[0.0, 0.0, 2.0_f32.ln()]);
+        assert!((lp[0] - 0.25_f32.ln()).abs() < 1e-6);
+        assert!((lp[2] - 0.5_f32.ln()).abs() < 1e-6);
+        assert_eq!(top_k(&lp, 2), vec![2, 0]);
+    }
+
+    #[test]
+    fn test_select_prunes_and_limits() {
+        let config = BeamConfig {
+            width: 2,
+            prune: 5.0,
+        };
+        let candidates = vec![
+            candidate(0, Some(3), -1.0),
+            candidate(0, None, -7.0),
+            candidate(1, Some(4), -0.5),
+            candidate(1, Some(5), -2.0),
+        ];
+        let kept = select(candidates.clone(), &config, |c| (c.parent, c.token));
+        assert_eq!(kept, vec![candidates[2].clone(), candidates[0].clone()]);
+
+        // Outside the prune margin even with room in the beam
+        let wide = BeamConfig {
+            width: 8,
+            prune: 5.0,
+        };
+        assert_eq!(select(candidates, &wide, |c| (c.parent, c.token)).len(), 3);
+    }
+
+    #[test]
+    fn test_select_merges_equal_results() {
+        let config = BeamConfig::default();
+        // Two parents reaching the same text keep only the better one
+        let candidates = vec![candidate(0, Some(3), -2.0), candidate(1, Some(3), -1.0)];
+        let kept = select(candidates, &config, |c| c.token);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].parent, 1);
+    }
+}
@@ -0,0 +1,339 @@
+//! Word error rate benchmarks on your own recordings
+//!
+//! A dataset is a directory of `name.wav` files, each with its reference
+//! transcript in `name.txt` - the layout the daemon's `save_recordings`
+//! already writes, so correcting those `.txt` files gives a benchmark of
+//! your own voice. Every model/provider combination is run over the whole
+//! set and scored by word error rate (WER), character error rate (CER) and
+//! real-time factor (RTF, processing time / audio duration).
+//!
+//! Text is compared after [`normalize`]: lowercase, punctuation dropped, so
+//! models that punctuate are not penalised against plain references.
+
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use serde::Serialize;
+use swictation_textsim::sequence_levenshtein;
+use tracing::{info, warn};
+
+use crate::audio::{AudioProcessor, SAMPLE_RATE};
+use crate::error::{Result, SttError};
+use crate::recognizer_ort::{ExecutionTarget, OrtRecognizer};
+
+/// One recording and what was said
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkItem {
+    pub audio: PathBuf,
+    pub reference: String,
+}
+
+/// `name.wav` + `name.txt` pairs in `dir`, sorted by name
+///
+/// WAV files without a transcript are skipped with a warning.
+pub fn load_dataset(dir: &Path) -> Result<Vec<BenchmarkItem>> {
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        SttError::invalid_input(format!("Cannot read dataset {}: {}", dir.display(), e))
+    })?;
+    let mut wavs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"))
+        })
+        .collect();
+    wavs.sort();
+
+    let mut items = Vec::new();
+    for audio in wavs {
+        let transcript = audio.with_extension("txt");
+        match std::fs::read_to_string(&transcript) {
+            Ok(reference) => items.push(BenchmarkItem {
+                audio,
+                reference: reference.trim().to_string(),
+            }),
+            Err(_) => warn!("Skipping {}: no {}", audio.display(), transcript.display()),
+        }
+    }
+    if items.is_empty() {
+        return Err(SttError::invalid_input(format!(
+            "No .wav files with .txt transcripts in {}",
+            dir.display()
+        )));
+    }
+    Ok(items)
+}
+
+/// Words of `text` for scoring: lowercase, without punctuation (apostrophes
+/// inside words are kept)
+pub fn normalize(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !(c.is_alphanumeric() || c == '\''))
+        .map(|word| word.trim_matches('\''))
+        .filter(|word| !word.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Edit operations against a reference of some length
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ErrorCount {
+    pub errors: usize,
+    pub reference_len: usize,
+}
+
+impl ErrorCount {
+    /// Word and character errors of `hypothesis` against `reference`
+    pub fn score(reference: &str, hypothesis: &str) -> (Self, Self) {
+        let reference = normalize(reference);
+        let hypothesis = normalize(hypothesis);
+        let words = Self {
+            errors: sequence_levenshtein(&reference, &hypothesis),
+            reference_len: reference.len(),
+        };
+
+        let reference: Vec<char> = reference.join(" ").chars().collect();
+        let hypothesis: Vec<char> = hypothesis.join(" ").chars().collect();
+        let chars = Self {
+            errors: sequence_levenshtein(&reference, &hypothesis),
+            reference_len: reference.len(),
+        };
+        (words, chars)
+    }
+
+    /// Errors per reference unit; can exceed 1.0 with many insertions
+    pub fn rate(&self) -> f64 {
+        if self.reference_len == 0 {
+            if self.errors == 0 {
+                0.0
+            } else {
+                1.0
+            }
+        } else {
+            self.errors as f64 / self.reference_len as f64
+        }
+    }
+}
+
+impl std::ops::AddAssign for ErrorCount {
+    fn add_assign(&mut self, other: Self) {
+        self.errors += other.errors;
+        self.reference_len += other.reference_len;
+    }
+}
+
+/// Result for one recording
+#[derive(Debug, Clone, Serialize)]
+pub struct FileResult {
+    pub audio: PathBuf,
+    pub reference: String,
+    pub hypothesis: String,
+    pub words: ErrorCount,
+    pub chars: ErrorCount,
+    pub audio_secs: f64,
+    pub processing_secs: f64,
+}
+
+/// Totals for one model on one provider
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchmarkResult {
+    /// Model directory name
+    pub model: String,
+    /// Execution provider (`"CPU"`, `"CUDA"`, ...)
+    pub provider: String,
+    pub words: ErrorCount,
+    pub chars: ErrorCount,
+    pub audio_secs: f64,
+    pub processing_secs: f64,
+    pub files: Vec<FileResult>,
+}
+
+impl BenchmarkResult {
+    pub fn wer(&self) -> f64 {
+        self.words.rate()
+    }
+
+    pub fn cer(&self) -> f64 {
+        self.chars.rate()
+    }
+
+    /// Processing time per second of audio (below 1.0 is faster than real time)
+    pub fn rtf(&self) -> f64 {
+        if self.audio_secs > 0.0 {
+            self.processing_secs / self.audio_secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Transcribe `items` with the model in `model_dir` on `target` and score it
+///
+/// The first recording is transcribed once before timing starts, so session
+/// warm-up (kernel selection, GPU memory allocation) does not count toward
+/// the RTF.
+pub fn run(
+    model_dir: &Path,
+    target: ExecutionTarget,
+    items: &[BenchmarkItem],
+) -> Result<BenchmarkResult> {
+    let provider = target.provider_name().to_string();
+    let model = model_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| model_dir.display().to_string());
+    info!("Benchmarking {} on {}", model, provider);
+
+    let mut recognizer = OrtRecognizer::new(model_dir, target)?;
+    let loader = AudioProcessor::new()?;
+    let audio: Vec<Vec<f32>> = items
+        .iter()
+        .map(|item| loader.load_audio(&item.audio))
+        .collect::<Result<_>>()?;
+    if let Some(first) = audio.first() {
+        recognizer.recognize_samples(first)?;
+    }
+
+    let mut result = BenchmarkResult {
+        model,
+        provider,
+        words: ErrorCount::default(),
+        chars: ErrorCount::default(),
+        audio_secs: 0.0,
+        processing_secs: 0.0,
+        files: Vec::with_capacity(items.len()),
+    };
+    for (item, samples) in items.iter().zip(&audio) {
+        let started = Instant::now();
+        let hypothesis = recognizer.recognize_samples(samples)?;
+        let processing_secs = started.elapsed().as_secs_f64();
+        let audio_secs = samples.len() as f64 / f64::from(SAMPLE_RATE);
+
+        let (words, chars) = ErrorCount::score(&item.reference, &hypothesis);
+        result.words += words;
+        result.chars += chars;
+        result.audio_secs += audio_secs;
+        result.processing_secs += processing_secs;
+        result.files.push(FileResult {
+            audio: item.audio.clone(),
+            reference: item.reference.clone(),
+            hypothesis,
+            words,
+            chars,
+            audio_secs,
+            processing_secs,
+        });
+    }
+    Ok(result)
+}
+
+/// Summary table, one row per result
+pub fn format_table(results: &[BenchmarkResult]) -> String {
+    let width = results
+        .iter()
+        .map(|r| r.model.len())
+        .max()
+        .unwrap_or(0)
+        .max("model".len());
+    let mut table = format!(
+        "{:<width$}  {:<8}  {:>5}  {:>6}  {:>7}  {:>7}  {:>6}\n",
+        "model",
+        "provider",
+        "files",
+        "words",
+        "WER",
+        "CER",
+        "RTF",
+        width = width
+    );
+    for r in results {
+        table.push_str(&format!(
+            "{:<width$}  {:<8}  {:>5}  {:>6}  {:>6.2}%  {:>6.2}%  {:>6.3}\n",
+            r.model,
+            r.provider,
+            r.files.len(),
+            r.words.reference_len,
+            r.wer() * 100.0,
+            r.cer() * 100.0,
+            r.rtf(),
+            width = width
+        ));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize("Hello, world! It's 'quoted' - OK?"),
+            ["hello", "world", "it's", "quoted", "ok"]
+        );
+    }
+
+    #[test]
+    fn test_scores() {
+        let (words, chars) = ErrorCount::score("Turn on the lights.", "turn the light");
+        assert_eq!(
+            words,
+            ErrorCount {
+                errors: 2,
+                reference_len: 4
+            }
+        );
+        assert_eq!(words.rate(), 0.5);
+        // "turn on the lights" -> "turn the light": drop "on " and "s"
+        assert_eq!(chars.errors, 4);
+        assert_eq!(chars.reference_len, 18);
+
+        let (words, _) = ErrorCount::score("", "");
+        assert_eq!(words.rate(), 0.0);
+        let (words, _) = ErrorCount::score("", "noise");
+        assert_eq!(words.rate(), 1.0);
+    }
+
+    #[test]
+    fn test_dataset_pairs_wav_with_transcript() {
+        let dir = std::env::temp_dir().join(format!("swictation-bench-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("b.wav"), b"").unwrap();
+        std::fs::write(dir.join("b.txt"), "second one\n").unwrap();
+        std::fs::write(dir.join("a.wav"), b"").unwrap();
+        std::fs::write(dir.join("a.txt"), "first").unwrap();
+        std::fs::write(dir.join("untranscribed.wav"), b"").unwrap();
+
+        let items = load_dataset(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].reference, "first");
+        assert_eq!(items[1].reference, "second one");
+    }
+
+    #[test]
+    fn test_table() {
+        let result = BenchmarkResult {
+            model: "parakeet-tdt-0.6b-v3-onnx".to_string(),
+            provider: "CPU".to_string(),
+            words: ErrorCount {
+                errors: 3,
+                reference_len: 40,
+            },
+            chars: ErrorCount {
+                errors: 5,
+                reference_len: 200,
+            },
+            audio_secs: 20.0,
+            processing_secs: 2.0,
+            files: Vec::new(),
+        };
+        let table = format_table(&[result]);
+        let row = table.lines().nth(1).unwrap();
+        assert_eq!(
+            row,
+            "parakeet-tdt-0.6b-v3-onnx  CPU           0      40    7.50%    2.50%   0.100"
+        );
+    }
+}
@@ -0,0 +1,203 @@
+//! Contextual biasing toward user-supplied phrases (hotwords)
+//!
+//! Project names and jargon are rare in the model's training data, so the
+//! joiner tends to spell them as common words ("swick tation"). With a
+//! phrase list registered, every word piece that continues one of the
+//! phrases, given the words decoded just before it, gets `boost` added to
+//! its log-probability.
+//!
+//! Greedy decoding applies this after shallow fusion to the few likeliest
+//! pieces of a non-blank emission, so like fusion it can change spelling
+//! but never turns blank into a word. Beam search adds the boost to each
+//! expansion. Matching ignores case and punctuation, and a match that
+//! breaks off is not penalized afterwards.
+
+use crate::fusion::{clean, split_words, WORD_MARKER};
+
+/// Non-blank pieces considered per emission
+const CANDIDATES: usize = 4;
+
+/// Phrases to favour and how strongly
+#[derive(Debug, Clone)]
+pub struct ContextBias {
+    /// Normalized phrases: lowercase words separated by single spaces
+    phrases: Vec<String>,
+    /// Words in the longest phrase (how much context a match can span)
+    max_words: usize,
+    boost: f32,
+}
+
+impl ContextBias {
+    /// Bias toward `phrases` with `boost` per matching piece (in nats;
+    /// around 1-3 helps without hallucinating the phrases)
+    pub fn new<S: AsRef<str>>(phrases: &[S], boost: f32) -> Self {
+        let mut normalized: Vec<String> = phrases
+            .iter()
+            .map(|p| {
+                p.as_ref()
+                    .split_whitespace()
+                    .map(clean)
+                    .filter(|w| !w.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .filter(|p| !p.is_empty())
+            .collect();
+        normalized.sort();
+        normalized.dedup();
+        let max_words = normalized
+            .iter()
+            .map(|p| p.split(' ').count())
+            .max()
+            .unwrap_or(0);
+
+        Self {
+            phrases: normalized,
+            max_words,
+            boost,
+        }
+    }
+
+    pub fn phrases(&self) -> &[String] {
+        &self.phrases
+    }
+
+    pub fn boost(&self) -> f32 {
+        self.boost
+    }
+
+    /// Token to emit given the joiner's token logits
+    ///
+    /// `current` is the choice so far (the acoustic argmax, or the one shallow
+    /// fusion made) and `emitted` the tokens decoded so far in this segment.
+    pub(crate) fn choose(
+        &self,
+        token_logits: &[f32],
+        current: usize,
+        blank_id: usize,
+        vocab: &[String],
+        emitted: &[i64],
+    ) -> usize {
+        if current == blank_id || self.boost <= 0.0 || self.phrases.is_empty() {
+            return current;
+        }
+
+        let logits = &token_logits[..token_logits.len().min(vocab.len())];
+        choose_boosted(logits, current, blank_id, |id| {
+            self.bonus(id, vocab, emitted)
+        })
+    }
+
+    /// `boost` if emitting `token` after `emitted` continues a phrase
+    pub(crate) fn bonus(&self, token: usize, vocab: &[String], emitted: &[i64]) -> f32 {
+        let Some(piece) = vocab.get(token) else {
+            return 0.0;
+        };
+        if self.phrases.is_empty() {
+            return 0.0;
+        }
+
+        let (mut words, partial) =
+            split_words(emitted.iter().filter_map(|&id| vocab.get(id as usize)));
+        if !partial.is_empty() {
+            words.push(partial);
+        }
+        match piece.strip_prefix(WORD_MARKER) {
+            Some(start) => words.push(clean(start)),
+            None => match words.last_mut() {
+                Some(last) => last.push_str(&clean(piece)),
+                None => words.push(clean(piece)),
+            },
+        }
+        if !piece.chars().any(char::is_alphanumeric) || words.last().is_none_or(String::is_empty) {
+            return 0.0;
+        }
+
+        // Any run of trailing words that spells the start of a phrase
+        let first = words.len().saturating_sub(self.max_words);
+        let continues = (first..words.len()).any(|i| {
+            let tail = words[i..].join(" ");
+            self.phrases.iter().any(|p| p.starts_with(&tail))
+        });
+        if continues {
+            self.boost
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Replace non-blank `current` with the likeliest piece once `bonus` is
+/// added, looking at the `CANDIDATES` likeliest pieces
+pub(crate) fn choose_boosted(
+    token_logits: &[f32],
+    current: usize,
+    blank_id: usize,
+    bonus: impl Fn(usize) -> f32,
+) -> usize {
+    if current == blank_id {
+        return current;
+    }
+
+    let mut candidates: Vec<usize> = (0..token_logits.len())
+        .filter(|&id| id != blank_id)
+        .collect();
+    candidates.sort_by(|&a, &b| token_logits[b].total_cmp(&token_logits[a]));
+    candidates.truncate(CANDIDATES);
+
+    // Logits differ from log-probabilities by the same constant
+    let score = |id: usize| token_logits[id] + bonus(id);
+    let current_score = score(current);
+    candidates
+        .into_iter()
+        .map(|id| (id, score(id)))
+        .filter(|&(_, s)| s > current_score)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map_or(current, |(id, _)| id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vocab() -> Vec<String> {
+        ["<blk>", "▁swi", "ck", "ft", "▁ta", "tion", "▁the", "."]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_phrases_are_normalized() {
+        let bias = ContextBias::new(&["  Swictation ", "swictation", "Agent-Flow!", ""], 2.0);
+        assert_eq!(bias.phrases(), ["agentflow", "swictation"]);
+    }
+
+    #[test]
+    fn test_bonus_follows_phrase() {
+        let bias = ContextBias::new(&["the swiftation"], 1.5);
+        let vocab = vocab();
+        // "the swi" + "ft" continues the phrase, "ck" does not
+        assert_eq!(bias.bonus(3, &vocab, &[6, 1]), 1.5);
+        assert_eq!(bias.bonus(2, &vocab, &[6, 1]), 0.0);
+        // A phrase can start at any word
+        assert_eq!(bias.bonus(6, &vocab, &[4]), 1.5);
+        // Punctuation is not a match
+        assert_eq!(bias.bonus(7, &vocab, &[6, 1]), 0.0);
+    }
+
+    #[test]
+    fn test_choose_prefers_phrase_spelling() {
+        let bias = ContextBias::new(&["swiftation"], 2.0);
+        // Acoustics slightly prefer "ck" after "▁swi"
+        let logits = [0.0, -5.0, 2.0, 1.0, -5.0, -5.0, -5.0, -5.0];
+        assert_eq!(bias.choose(&logits, 2, 0, &vocab(), &[1]), 3);
+
+        // A much likelier piece still wins
+        let logits = [0.0, -5.0, 6.0, 1.0, -5.0, -5.0, -5.0, -5.0];
+        assert_eq!(bias.choose(&logits, 2, 0, &vocab(), &[1]), 2);
+
+        // Blank stays blank
+        assert_eq!(bias.choose(&logits, 0, 0, &vocab(), &[1]), 0);
+    }
+}
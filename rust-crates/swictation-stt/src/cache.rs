@@ -0,0 +1,111 @@
+//! Recognition results for repeated, bit-identical audio
+//!
+//! Integration tests and benchmark runs feed the same buffers over and over
+//! (as do wake-word beeps); decoding them once is enough. Entries are keyed
+//! by a 64-bit fingerprint of the samples plus their length, and the least
+//! recently used entry is evicted when the cache is full.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::VecDeque;
+use std::hash::Hasher;
+
+use crate::nbest::Emission;
+
+/// Fingerprint of an audio buffer (hash of the raw sample bits)
+pub fn fingerprint(samples: &[f32]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write_usize(samples.len());
+    for sample in samples {
+        hasher.write_u32(sample.to_bits());
+    }
+    hasher.finish()
+}
+
+/// Decoded text and the emissions behind it (for n-best alternatives)
+#[derive(Debug, Clone)]
+pub struct CachedRecognition {
+    pub text: String,
+    pub emissions: Vec<Emission>,
+}
+
+/// Least-recently-used cache of recognitions
+#[derive(Debug)]
+pub struct RecognitionCache {
+    capacity: usize,
+    /// Least recently used first
+    entries: VecDeque<(u64, CachedRecognition)>,
+}
+
+impl RecognitionCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn get(&mut self, key: u64) -> Option<CachedRecognition> {
+        let position = self.entries.iter().position(|(k, _)| *k == key)?;
+        let entry = self.entries.remove(position)?;
+        let hit = entry.1.clone();
+        self.entries.push_back(entry);
+        Some(hit)
+    }
+
+    pub fn insert(&mut self, key: u64, recognition: CachedRecognition) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.entries.retain(|(k, _)| *k != key);
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((key, recognition));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recognition(text: &str) -> CachedRecognition {
+        CachedRecognition {
+            text: text.to_string(),
+            emissions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_is_bit_exact() {
+        let a = vec![0.1f32, -0.2, 0.3];
+        assert_eq!(fingerprint(&a), fingerprint(&a.clone()));
+        assert_ne!(fingerprint(&a), fingerprint(&[0.1, -0.2, 0.30001]));
+        assert_ne!(fingerprint(&[0.0]), fingerprint(&[0.0, 0.0]));
+    }
+
+    #[test]
+    fn test_least_recently_used_is_evicted() {
+        let mut cache = RecognitionCache::new(2);
+        cache.insert(1, recognition("one"));
+        cache.insert(2, recognition("two"));
+        assert_eq!(cache.get(1).unwrap().text, "one");
+
+        cache.insert(3, recognition("three"));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(2).is_none());
+        assert_eq!(cache.get(1).unwrap().text, "one");
+        assert_eq!(cache.get(3).unwrap().text, "three");
+    }
+}
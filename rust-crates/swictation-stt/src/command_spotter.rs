@@ -0,0 +1,321 @@
+//! Fixed-grammar command spotting with a small CTC model
+//!
+//! Free dictation can turn a short command like "scratch that" into
+//! "scratch fat" or "Scratch that." with punctuation, so matching commands on
+//! STT text is unreliable. This spotter scores each phrase of a fixed grammar
+//! directly against the acoustic model: the CTC forward algorithm gives the
+//! likelihood of the phrase over all alignments, which is compared with the
+//! unconstrained best path. A segment that really is the phrase scores close
+//! to the best path; anything else falls far below it.
+//!
+//! Expects a NeMo-style CTC export: `model.onnx` (or `model.int8.onnx`) with
+//! `audio_signal` / `length` inputs and per-frame log-probabilities as the
+//! first output, plus the SentencePiece `tokens.txt` used by the STT models.
+//! The model is small and runs on CPU, leaving the GPU to STT.
+
+use crate::audio::AudioProcessor;
+use crate::error::{Result, SttError};
+use ndarray::Array2;
+use ort::{
+    session::{builder::GraphOptimizationLevel, Session},
+    value::Tensor,
+};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tracing::{debug, info};
+
+/// SentencePiece word-boundary marker
+const WORD_MARKER: char = '▁';
+
+/// Mel bins expected by the small NeMo CTC models
+const N_MEL_FEATURES: usize = 80;
+
+/// Best-scoring grammar phrase for a segment
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandMatch {
+    /// Phrase as given to [`CommandSpotter::new`]
+    pub phrase: String,
+    /// Per-frame likelihood relative to the best path, in (0, 1]
+    pub confidence: f32,
+}
+
+/// Scores speech segments against a fixed list of command phrases
+pub struct CommandSpotter {
+    session: Session,
+    blank_id: usize,
+    audio_processor: AudioProcessor,
+    grammar: Vec<(String, Vec<usize>)>,
+}
+
+impl CommandSpotter {
+    /// Load the model in `model_dir` and compile `phrases` to token sequences
+    ///
+    /// Phrases that can't be spelled with the model's vocabulary are an error,
+    /// so a typo in the grammar is caught at startup.
+    pub fn new<P: AsRef<Path>>(model_dir: P, phrases: &[String]) -> Result<Self> {
+        let model_dir = model_dir.as_ref();
+        info!("Loading command spotter from {}", model_dir.display());
+
+        let tokens_path = model_dir.join("tokens.txt");
+        let contents = fs::read_to_string(&tokens_path)
+            .map_err(|e| SttError::model_load(format!("Failed to read tokens.txt: {}", e)))?;
+        let tokens: Vec<&str> = contents
+            .lines()
+            .map(|line| line.split_whitespace().next().unwrap_or(""))
+            .collect();
+        let blank_id = tokens
+            .iter()
+            .position(|t| *t == "<blk>" || *t == "<blank>")
+            .ok_or_else(|| SttError::model_load("Could not find <blk> token"))?;
+        let vocab: HashMap<&str, usize> = tokens.iter().enumerate().map(|(i, t)| (*t, i)).collect();
+
+        let grammar = phrases
+            .iter()
+            .map(|phrase| {
+                tokenize(phrase, &vocab)
+                    .map(|ids| (phrase.clone(), ids))
+                    .ok_or_else(|| {
+                        SttError::config(format!(
+                            "Command phrase '{}' can't be spelled with the model vocabulary",
+                            phrase
+                        ))
+                    })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let model_path = ["model.int8.onnx", "model.onnx"]
+            .iter()
+            .map(|name| model_dir.join(name))
+            .find(|path| path.exists())
+            .ok_or_else(|| {
+                SttError::model_load(format!(
+                    "Could not find model.onnx or model.int8.onnx in {}",
+                    model_dir.display()
+                ))
+            })?;
+
+        let session = Session::builder()
+            .map_err(|e| SttError::model_load(format!("Failed to create session builder: {}", e)))?
+            .with_optimization_level(GraphOptimizationLevel::Level3)
+            .map_err(|e| SttError::model_load(format!("Failed to set optimization level: {}", e)))?
+            .with_intra_threads(1)
+            .map_err(|e| SttError::model_load(format!("Failed to set intra threads: {}", e)))?
+            .commit_from_file(&model_path)
+            .map_err(|e| SttError::model_load(format!("Failed to load command model: {}", e)))?;
+
+        info!(
+            "✓ Command spotter loaded ({} phrases, {} tokens)",
+            grammar.len(),
+            tokens.len()
+        );
+
+        Ok(Self {
+            session,
+            blank_id,
+            audio_processor: AudioProcessor::with_mel_features(N_MEL_FEATURES)?,
+            grammar,
+        })
+    }
+
+    /// Best grammar phrase for a segment (`None` for empty audio)
+    pub fn spot(&mut self, samples: &[f32]) -> Result<Option<CommandMatch>> {
+        if samples.is_empty() || self.grammar.is_empty() {
+            return Ok(None);
+        }
+
+        let features = self.audio_processor.extract_mel_features(samples)?;
+        let log_probs = self.run_model(&features)?;
+        let frames = log_probs.nrows();
+        if frames == 0 {
+            return Ok(None);
+        }
+
+        let best_path: f32 = log_probs
+            .outer_iter()
+            .map(|frame| frame.iter().cloned().fold(f32::NEG_INFINITY, f32::max))
+            .sum();
+
+        let best = self
+            .grammar
+            .iter()
+            .map(|(phrase, ids)| (phrase, ctc_log_likelihood(&log_probs, ids, self.blank_id)))
+            .filter(|(_, ll)| ll.is_finite())
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        Ok(best.map(|(phrase, ll)| {
+            let confidence = ((ll - best_path).min(0.0) / frames as f32).exp();
+            debug!("Command spotter: '{}' confidence {:.3}", phrase, confidence);
+            CommandMatch {
+                phrase: phrase.clone(),
+                confidence,
+            }
+        }))
+    }
+
+    /// Run the CTC model; returns log-probabilities of shape (frames, vocab)
+    fn run_model(&mut self, features: &Array2<f32>) -> Result<Array2<f32>> {
+        let num_frames = features.nrows();
+        let num_features = features.ncols();
+
+        // (batch, features, time), like the STT encoders
+        let data: Vec<f32> = features.t().iter().cloned().collect();
+        let audio_signal =
+            Tensor::from_array((vec![1, num_features, num_frames], data.into_boxed_slice()))
+                .map_err(|e| {
+                    SttError::inference(format!("Failed to create audio tensor: {}", e))
+                })?;
+        let length = Tensor::from_array((vec![1], vec![num_frames as i64].into_boxed_slice()))
+            .map_err(|e| SttError::inference(format!("Failed to create length tensor: {}", e)))?;
+
+        let outputs = self
+            .session
+            .run(ort::inputs!["audio_signal" => audio_signal, "length" => length])
+            .map_err(|e| SttError::inference(format!("Command model inference failed: {}", e)))?;
+
+        let (shape, data) = outputs[0]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| SttError::inference(format!("Failed to extract log-probs: {}", e)))?;
+        if shape.len() != 3 {
+            return Err(SttError::inference(format!(
+                "Expected (batch, frames, vocab) log-probs, got shape {:?}",
+                shape
+            )));
+        }
+        let (frames, vocab) = (shape[1] as usize, shape[2] as usize);
+        let mut log_probs = Array2::from_shape_vec((frames, vocab), data.to_vec())
+            .map_err(|e| SttError::inference(format!("Bad log-prob shape: {}", e)))?;
+
+        // Exports differ in whether they end with log_softmax; normalizing again is harmless
+        for mut frame in log_probs.outer_iter_mut() {
+            let max = frame.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+            let log_sum = max + frame.iter().map(|&v| (v - max).exp()).sum::<f32>().ln();
+            frame.mapv_inplace(|v| v - log_sum);
+        }
+        Ok(log_probs)
+    }
+}
+
+/// Spell a phrase with SentencePiece tokens (greedy longest match per word)
+fn tokenize(phrase: &str, vocab: &HashMap<&str, usize>) -> Option<Vec<usize>> {
+    let mut ids = Vec::new();
+    for word in phrase.split_whitespace() {
+        let word = format!("{}{}", WORD_MARKER, word.to_lowercase());
+        let mut rest = word.as_str();
+        while !rest.is_empty() {
+            let (end, id) = rest
+                .char_indices()
+                .map(|(i, c)| i + c.len_utf8())
+                .rev()
+                .find_map(|end| vocab.get(&rest[..end]).map(|&id| (end, id)))?;
+            ids.push(id);
+            rest = &rest[end..];
+        }
+    }
+    (!ids.is_empty()).then_some(ids)
+}
+
+/// Log-likelihood of `labels` over all CTC alignments (forward algorithm)
+///
+/// Returns negative infinity when the segment has too few frames to emit
+/// the labels.
+fn ctc_log_likelihood(log_probs: &Array2<f32>, labels: &[usize], blank: usize) -> f32 {
+    // Labels interleaved with blanks: b l1 b l2 ... b
+    let mut extended = Vec::with_capacity(labels.len() * 2 + 1);
+    extended.push(blank);
+    for &label in labels {
+        extended.push(label);
+        extended.push(blank);
+    }
+    let states = extended.len();
+
+    let mut alpha = vec![f32::NEG_INFINITY; states];
+    alpha[0] = log_probs[[0, extended[0]]];
+    if states > 1 {
+        alpha[1] = log_probs[[0, extended[1]]];
+    }
+
+    for t in 1..log_probs.nrows() {
+        let mut next = vec![f32::NEG_INFINITY; states];
+        for s in 0..states {
+            let mut acc = alpha[s];
+            if s >= 1 {
+                acc = log_add(acc, alpha[s - 1]);
+            }
+            // Skipping the blank is allowed between different labels only
+            if s >= 2 && extended[s] != blank && extended[s] != extended[s - 2] {
+                acc = log_add(acc, alpha[s - 2]);
+            }
+            next[s] = acc + log_probs[[t, extended[s]]];
+        }
+        alpha = next;
+    }
+
+    if states > 1 {
+        log_add(alpha[states - 1], alpha[states - 2])
+    } else {
+        alpha[0]
+    }
+}
+
+fn log_add(a: f32, b: f32) -> f32 {
+    if a == f32::NEG_INFINITY {
+        return b;
+    }
+    if b == f32::NEG_INFINITY {
+        return a;
+    }
+    let max = a.max(b);
+    max + ((a - max).exp() + (b - max).exp()).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLANK: usize = 0;
+    const A: usize = 1;
+    const B: usize = 2;
+
+    /// Log-probs where each frame puts 0.9 on one token
+    fn frames(peaks: &[usize]) -> Array2<f32> {
+        let mut lp = Array2::from_elem((peaks.len(), 3), (0.05f32).ln());
+        for (t, &peak) in peaks.iter().enumerate() {
+            lp[[t, peak]] = (0.9f32).ln();
+        }
+        lp
+    }
+
+    #[test]
+    fn test_ctc_prefers_matching_phrase() {
+        let lp = frames(&[A, BLANK, B, B]);
+        let ab = ctc_log_likelihood(&lp, &[A, B], BLANK);
+        let ba = ctc_log_likelihood(&lp, &[B, A], BLANK);
+        assert!(ab > ba + 5.0, "ab={} ba={}", ab, ba);
+
+        // The best path is a single alignment of "ab"; summing alignments can only add to it
+        let best_path = 4.0 * (0.9f32).ln();
+        assert!(ab >= best_path - 1e-4);
+    }
+
+    #[test]
+    fn test_ctc_repeated_label_needs_blank() {
+        // "aa" needs at least a b a: impossible in two frames
+        assert_eq!(
+            ctc_log_likelihood(&frames(&[A, A]), &[A, A], BLANK),
+            f32::NEG_INFINITY
+        );
+        assert!(ctc_log_likelihood(&frames(&[A, BLANK, A]), &[A, A], BLANK).is_finite());
+    }
+
+    #[test]
+    fn test_tokenize_longest_match() {
+        let vocab: HashMap<&str, usize> =
+            [("▁scratch", 3), ("▁th", 4), ("at", 5), ("▁", 6), ("t", 7)]
+                .into_iter()
+                .collect();
+        assert_eq!(tokenize("Scratch that", &vocab), Some(vec![3, 4, 5]));
+        assert_eq!(tokenize("scratch tat", &vocab), Some(vec![3, 6, 7, 5]));
+        assert_eq!(tokenize("scratch xyz", &vocab), None);
+    }
+}
@@ -0,0 +1,115 @@
+//! Per-word confidence from the joiner's token probabilities
+//!
+//! Every emitted token carries its softmax probability (see
+//! [`crate::nbest::Emission`]). A word is as uncertain as its least certain
+//! piece: one badly recognized piece is enough to get the word wrong, while
+//! averaging would let confident neighbours hide it.
+//!
+//! Words also carry when they start, where the engine knows it (Parakeet:
+//! the encoder frame of the word's first piece), for subtitle timing.
+
+/// Word boundary marker of the SentencePiece vocabulary
+const WORD_START: char = '▁';
+
+/// A recognized word and how sure the model was of it
+#[derive(Debug, Clone, PartialEq)]
+pub struct WordConfidence {
+    /// Word as it appears in the transcription, punctuation included
+    pub word: String,
+    /// Lowest probability among the word's pieces (0.0 to 1.0)
+    pub confidence: f32,
+    /// Seconds from the start of the audio to the word's first piece
+    pub start_s: Option<f64>,
+}
+
+/// Group token pieces into words, in transcription order
+///
+/// `pieces` are vocabulary strings with their probabilities; a piece
+/// starting with `▁` begins a new word.
+pub fn word_confidences(pieces: &[(&str, f32)]) -> Vec<WordConfidence> {
+    timed_word_confidences(pieces.iter().map(|&(piece, prob)| (piece, prob, None)))
+}
+
+/// Like [`word_confidences`], with each piece's start in seconds
+pub fn timed_word_confidences<'a>(
+    pieces: impl IntoIterator<Item = (&'a str, f32, Option<f64>)>,
+) -> Vec<WordConfidence> {
+    let mut words: Vec<WordConfidence> = Vec::new();
+    for (piece, prob, start_s) in pieces {
+        let text = piece.trim_start_matches(WORD_START);
+        match words.last_mut() {
+            Some(word) if !piece.starts_with(WORD_START) => {
+                word.word.push_str(text);
+                word.confidence = word.confidence.min(prob);
+            }
+            _ => words.push(WordConfidence {
+                word: text.to_string(),
+                confidence: prob,
+                start_s,
+            }),
+        }
+    }
+    words.retain(|w| !w.word.is_empty());
+    words
+}
+
+/// Mean word confidence of a transcription (1.0 when it has no words)
+pub fn mean_confidence(words: &[WordConfidence]) -> f32 {
+    if words.is_empty() {
+        return 1.0;
+    }
+    words.iter().map(|w| w.confidence).sum::<f32>() / words.len() as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words_take_lowest_piece() {
+        let words = word_confidences(&[
+            ("▁meet", 0.9),
+            ("▁at", 0.95),
+            ("▁the", 0.99),
+            ("▁qu", 0.8),
+            ("ay", 0.3),
+            (".", 0.9),
+        ]);
+        let pairs: Vec<(&str, f32)> = words
+            .iter()
+            .map(|w| (w.word.as_str(), w.confidence))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![("meet", 0.9), ("at", 0.95), ("the", 0.99), ("quay.", 0.3)]
+        );
+    }
+
+    #[test]
+    fn test_bare_word_start_joins_next_piece() {
+        let words = word_confidences(&[("▁", 0.6), ("Hello", 0.9)]);
+        assert_eq!(words.len(), 1);
+        assert_eq!(words[0].word, "Hello");
+        assert_eq!(words[0].confidence, 0.6);
+    }
+
+    #[test]
+    fn test_word_starts_at_first_piece() {
+        let words = timed_word_confidences([
+            ("▁", 0.9, Some(0.08)),
+            ("Hel", 0.9, Some(0.16)),
+            ("lo", 0.9, Some(0.24)),
+            ("▁there", 0.9, Some(0.56)),
+        ]);
+        let starts: Vec<(&str, Option<f64>)> =
+            words.iter().map(|w| (w.word.as_str(), w.start_s)).collect();
+        assert_eq!(starts, vec![("Hello", Some(0.08)), ("there", Some(0.56))]);
+    }
+
+    #[test]
+    fn test_mean_confidence() {
+        assert_eq!(mean_confidence(&[]), 1.0);
+        let words = word_confidences(&[("▁a", 0.5), ("▁b", 1.0)]);
+        assert!((mean_confidence(&words) - 0.75).abs() < 1e-6);
+    }
+}
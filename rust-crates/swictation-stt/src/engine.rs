@@ -1,23 +1,64 @@
 //! Unified STT engine interface supporting multiple model implementations
 
-use crate::error::Result;
-use crate::recognizer_ort::OrtRecognizer;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::beam::{Decoding, TdtConfig};
+use crate::biasing::ContextBias;
+use crate::confidence::{mean_confidence, WordConfidence};
+use crate::error::{Result, SttError};
+use crate::fusion::ShallowFusion;
+use crate::nbest::Hypothesis;
+use crate::recognizer_ort::{CancelHandle, OrtRecognizer};
+use crate::stream::RecognitionStream;
+use crate::whisper::{DetectedLanguage, WhisperRecognizer};
+use futures_core::Stream;
+use tracing::warn;
 
 /// Recognition result from STT engine
 #[derive(Debug, Clone)]
 pub struct RecognitionResult {
     /// Transcribed text
     pub text: String,
-    /// Confidence score (0.0 to 1.0) - not currently provided by OrtRecognizer
+    /// Mean of the per-word confidences (0.0 to 1.0)
     pub confidence: f32,
     /// Processing time in milliseconds
     pub processing_time_ms: f64,
+    /// Other likely transcriptions, most likely first (see [`crate::nbest`])
+    pub alternatives: Vec<String>,
+    /// Words of `text` with their confidence (see [`crate::confidence`])
+    pub words: Vec<WordConfidence>,
+}
+
+/// Alternatives kept per recognition
+pub const NBEST_ALTERNATIVES: usize = 4;
+
+/// Audio recognized by [`SttEngine::warm_up`] (1 second at 16kHz)
+const WARM_UP_SAMPLES: usize = 16_000;
+
+fn warm_up_audio() -> Vec<f32> {
+    (0..WARM_UP_SAMPLES)
+        .map(|i| (i as f32 * 0.37).sin() * 0.001)
+        .collect()
+}
+
+/// Describes which model and execution provider produced a transcription
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InferenceInfo {
+    /// Model name (e.g. "Parakeet-TDT-0.6B")
+    pub model: String,
+    /// Weight precision of the loaded encoder ("fp32", "fp16", "int8")
+    pub quantization: String,
+    /// ONNX Runtime execution provider ("CUDA", "ROCm", "CoreML", "DirectML", "OpenVINO", "CPU")
+    pub provider: String,
+    /// Device class ("GPU" or "CPU")
+    pub device: String,
 }
 
 /// Unified STT engine supporting multiple Parakeet-TDT model implementations
 ///
 /// This enum provides a common interface for both the 0.6B and 1.1B models
-/// via direct ONNX Runtime (no sherpa-rs dependency).
+/// via direct ONNX Runtime (no sherpa-rs dependency), and for Whisper.
 ///
 /// # Model Selection
 ///
@@ -26,6 +67,9 @@ pub struct RecognitionResult {
 /// - **≥1.5GB VRAM**: `Parakeet0_6B` with GPU (good quality: 7-8% WER)
 /// - **<1.5GB or no GPU**: `Parakeet0_6B` with CPU (fallback)
 ///
+/// `Whisper` is only chosen explicitly, for multilingual dictation or
+/// hardware the Parakeet models do not run well on.
+///
 /// # Example
 ///
 /// ```no_run
@@ -67,6 +111,13 @@ pub enum SttEngine {
     /// - **Latency**: 150-250ms
     /// - **WER**: 5.77% (best quality)
     Parakeet1_1B(OrtRecognizer),
+
+    /// Whisper encoder/decoder via direct ONNX Runtime (CPU or GPU)
+    ///
+    /// - **Languages**: ~100 with multilingual models (see [`crate::whisper`])
+    /// - **Latency**: decodes whole 30-second windows; slower than Parakeet
+    /// - No phrase biasing, lexicon, shallow fusion or beam search
+    Whisper(WhisperRecognizer),
 }
 
 impl SttEngine {
@@ -91,21 +142,92 @@ impl SttEngine {
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
     pub fn recognize(&mut self, audio: &[f32]) -> Result<RecognitionResult> {
-        // Both variants now use OrtRecognizer
-        let r = match self {
-            SttEngine::Parakeet0_6B(r) => r,
-            SttEngine::Parakeet1_1B(r) => r,
+        let start = std::time::Instant::now();
+        let text = match self {
+            SttEngine::Parakeet0_6B(r) | SttEngine::Parakeet1_1B(r) => {
+                r.recognize_samples(audio)?
+            }
+            SttEngine::Whisper(r) => r.recognize_samples(audio)?,
         };
+        Ok(self.result(text, start))
+    }
 
-        let start = std::time::Instant::now();
-        let text = r.recognize_samples(audio)?;
+    /// Result of the recognition that just produced `text`
+    fn result(&self, text: String, start: std::time::Instant) -> RecognitionResult {
         let processing_time_ms = start.elapsed().as_secs_f64() * 1000.0;
-
-        Ok(RecognitionResult {
+        let (words, alternatives) = match self {
+            SttEngine::Parakeet0_6B(r) | SttEngine::Parakeet1_1B(r) => {
+                (r.word_confidences(), r.alternatives(NBEST_ALTERNATIVES))
+            }
+            SttEngine::Whisper(r) => (r.word_confidences(), Vec::new()),
+        };
+        RecognitionResult {
             text,
-            confidence: 1.0, // OrtRecognizer doesn't provide confidence
+            confidence: mean_confidence(&words),
             processing_time_ms,
-        })
+            alternatives,
+            words,
+        }
+    }
+
+    /// Up to `n` scored transcriptions of the last recognition, best first
+    /// (see [`OrtRecognizer::n_best`])
+    pub fn n_best(&self, n: usize) -> Vec<Hypothesis> {
+        match self {
+            SttEngine::Parakeet0_6B(r) | SttEngine::Parakeet1_1B(r) => r.n_best(n),
+            SttEngine::Whisper(r) => r.n_best(n),
+        }
+    }
+
+    /// Begin a streaming recognition (see [`OrtRecognizer::start_stream`])
+    pub fn start_stream(&mut self) -> Result<()> {
+        match self {
+            SttEngine::Parakeet0_6B(r) | SttEngine::Parakeet1_1B(r) => r.start_stream(),
+            SttEngine::Whisper(r) => r.start_stream(),
+        }
+    }
+
+    /// Add audio to the stream, returning a new partial hypothesis if one
+    /// was decoded
+    pub fn feed(&mut self, audio: &[f32]) -> Result<Option<String>> {
+        match self {
+            SttEngine::Parakeet0_6B(r) | SttEngine::Parakeet1_1B(r) => r.feed(audio),
+            SttEngine::Whisper(r) => r.feed(audio),
+        }
+    }
+
+    /// End the stream with the same result `recognize` gives for all of its audio
+    pub fn finalize(&mut self) -> Result<RecognitionResult> {
+        let start = std::time::Instant::now();
+        let text = match self {
+            SttEngine::Parakeet0_6B(r) | SttEngine::Parakeet1_1B(r) => r.finalize()?,
+            SttEngine::Whisper(r) => r.finalize()?,
+        };
+        Ok(self.result(text, start))
+    }
+
+    /// Recognize `audio` (16 kHz mono chunks of any length) as the source
+    /// produces it: partial hypotheses while it runs, then the final result
+    /// once it ends
+    ///
+    /// Partials come at the pace of [`Self::feed`], and the final result is
+    /// the one `recognize` gives for all of the audio. Inference runs on a
+    /// worker thread holding `engine`'s lock, so the stream can be polled
+    /// straight from async code.
+    ///
+    /// ```no_run
+    /// # use std::sync::{Arc, Mutex};
+    /// # use swictation_stt::{SttEngine, StreamUpdate};
+    /// # use futures_core::Stream;
+    /// # fn demo(engine: Arc<Mutex<SttEngine>>, microphone: impl Stream<Item = Vec<f32>> + Unpin) {
+    /// let results = SttEngine::recognize_stream(engine, microphone);
+    /// # }
+    /// ```
+    pub fn recognize_stream<S>(engine: Arc<Mutex<SttEngine>>, audio: S) -> RecognitionStream<S>
+    where
+        S: Stream<Item = Vec<f32>> + Unpin,
+    {
+        RecognitionStream::new(engine, audio)
     }
 
     /// Get model name for logging/metrics
@@ -114,10 +236,20 @@ impl SttEngine {
     ///
     /// - `"Parakeet-TDT-0.6B"` for 0.6B model
     /// - `"Parakeet-TDT-1.1B-INT8"` for 1.1B model
+    /// - `"Whisper-small"` etc. for Whisper, by size
     pub fn model_name(&self) -> &str {
         match self {
             SttEngine::Parakeet0_6B(_) => "Parakeet-TDT-0.6B",
             SttEngine::Parakeet1_1B(_) => "Parakeet-TDT-1.1B-INT8",
+            SttEngine::Whisper(r) => match r.size() {
+                "tiny" => "Whisper-tiny",
+                "base" => "Whisper-base",
+                "small" => "Whisper-small",
+                "medium" => "Whisper-medium",
+                "large" => "Whisper-large",
+                "turbo" => "Whisper-large-turbo",
+                _ => "Whisper",
+            },
         }
     }
 
@@ -127,13 +259,21 @@ impl SttEngine {
     ///
     /// - `"0.6B"` for 0.6B model
     /// - `"1.1B-INT8"` for 1.1B INT8 quantized model
+    /// - `"tiny"` … `"large"`, `"turbo"` (or `"custom"`) for Whisper
     pub fn model_size(&self) -> &str {
         match self {
             SttEngine::Parakeet0_6B(_) => "0.6B",
             SttEngine::Parakeet1_1B(_) => "1.1B-INT8",
+            SttEngine::Whisper(r) => r.size(),
         }
     }
 
+    /// Whether the model writes its own punctuation and capitalization
+    /// (the 0.6B model and Whisper do; the 1.1B model emits lowercase words)
+    pub fn writes_punctuation(&self) -> bool {
+        !matches!(self, SttEngine::Parakeet1_1B(_))
+    }
+
     /// Get backend type (CPU/GPU)
     ///
     /// # Returns
@@ -141,18 +281,176 @@ impl SttEngine {
     /// - `"GPU"` if using GPU acceleration
     /// - `"CPU"` if using CPU-only inference
     pub fn backend(&self) -> &str {
-        // Both variants now use OrtRecognizer, check is_gpu()
+        let gpu = match self {
+            SttEngine::Parakeet0_6B(r) | SttEngine::Parakeet1_1B(r) => r.is_gpu(),
+            SttEngine::Whisper(r) => r.is_gpu(),
+        };
+        if gpu {
+            "GPU"
+        } else {
+            "CPU"
+        }
+    }
+
+    /// Model/provider metadata for tagging stored segments and events
+    pub fn inference_info(&self) -> InferenceInfo {
+        let (precision, provider) = match self {
+            SttEngine::Parakeet0_6B(r) | SttEngine::Parakeet1_1B(r) => {
+                (r.precision(), r.execution_provider())
+            }
+            SttEngine::Whisper(r) => (r.precision(), r.execution_provider()),
+        };
+        InferenceInfo {
+            model: self.model_name().to_string(),
+            quantization: precision.to_string(),
+            provider: provider.to_string(),
+            device: self.backend().to_string(),
+        }
+    }
+
+    /// Fuse a language model into decoding (`None` turns it off)
+    pub fn set_shallow_fusion(&mut self, fusion: Option<ShallowFusion>) {
+        match self {
+            SttEngine::Parakeet0_6B(r) | SttEngine::Parakeet1_1B(r) => r.set_shallow_fusion(fusion),
+            SttEngine::Whisper(_) => {
+                if fusion.is_some() {
+                    warn!("Shallow fusion is not supported with Whisper; ignoring");
+                }
+            }
+        }
+    }
+
+    /// Bias decoding toward a phrase list (`None` stops biasing)
+    pub fn set_context_bias(&mut self, bias: Option<ContextBias>) {
+        match self {
+            SttEngine::Parakeet0_6B(r) | SttEngine::Parakeet1_1B(r) => r.set_context_bias(bias),
+            SttEngine::Whisper(_) => {
+                if bias.is_some() {
+                    warn!("Context biasing is not supported with Whisper; ignoring phrases");
+                }
+            }
+        }
+    }
+
+    /// Load (or with `None`, unload) a user lexicon; returns its size
+    pub fn load_lexicon(&mut self, path: Option<&Path>) -> Result<usize> {
+        match self {
+            SttEngine::Parakeet0_6B(r) | SttEngine::Parakeet1_1B(r) => r.load_lexicon(path),
+            SttEngine::Whisper(_) => match path {
+                Some(_) => Err(SttError::config(
+                    "Lexicons are spelled in Parakeet word pieces; not supported with Whisper",
+                )),
+                None => Ok(0),
+            },
+        }
+    }
+
+    /// Cache results for up to `capacity` distinct, bit-identical audio
+    /// buffers (0 disables it; meant for tests and benchmarks)
+    pub fn set_result_cache(&mut self, capacity: usize) {
+        match self {
+            SttEngine::Parakeet0_6B(r) | SttEngine::Parakeet1_1B(r) => r.set_result_cache(capacity),
+            SttEngine::Whisper(_) => {}
+        }
+    }
+
+    /// Results held by the result cache (see [`Self::set_result_cache`])
+    pub fn cached_results(&self) -> usize {
+        match self {
+            SttEngine::Parakeet0_6B(r) | SttEngine::Parakeet1_1B(r) => r.cached_results(),
+            SttEngine::Whisper(_) => 0,
+        }
+    }
+
+    /// Spread the encoder windows of long audio over `workers` encoder
+    /// sessions (Parakeet only)
+    pub fn set_encoder_workers(&mut self, workers: usize) -> Result<()> {
         match self {
             SttEngine::Parakeet0_6B(r) | SttEngine::Parakeet1_1B(r) => {
-                if r.is_gpu() {
-                    "GPU"
-                } else {
-                    "CPU"
+                r.set_encoder_workers(workers)
+            }
+            SttEngine::Whisper(_) => Ok(()),
+        }
+    }
+
+    /// Set the TDT step rules of Parakeet decoding
+    pub fn set_tdt(&mut self, tdt: TdtConfig) {
+        match self {
+            SttEngine::Parakeet0_6B(r) | SttEngine::Parakeet1_1B(r) => r.set_tdt(tdt),
+            SttEngine::Whisper(_) => {}
+        }
+    }
+
+    /// Choose greedy or beam search decoding
+    pub fn set_decoding(&mut self, decoding: Decoding) {
+        match self {
+            SttEngine::Parakeet0_6B(r) | SttEngine::Parakeet1_1B(r) => r.set_decoding(decoding),
+            SttEngine::Whisper(_) => {
+                if decoding != Decoding::Greedy {
+                    warn!("Beam search is not supported with Whisper; decoding greedily");
                 }
             }
         }
     }
 
+    /// Language spoken in `audio`, if the model can tell (multilingual
+    /// Whisper; see [`WhisperRecognizer::detect_language`])
+    pub fn detect_language(&mut self, audio: &[f32]) -> Result<Option<DetectedLanguage>> {
+        match self {
+            SttEngine::Parakeet0_6B(_) | SttEngine::Parakeet1_1B(_) => Ok(None),
+            SttEngine::Whisper(r) => r.detect_language(audio),
+        }
+    }
+
+    /// Transcribe in the language `code` (`None` detects it); Parakeet
+    /// models cannot be told a language
+    pub fn set_language(&mut self, code: Option<&str>) -> Result<()> {
+        match self {
+            SttEngine::Parakeet0_6B(_) | SttEngine::Parakeet1_1B(_) => match code {
+                Some(code) => Err(SttError::config(format!(
+                    "Only Whisper models can be set to a language ('{}')",
+                    code
+                ))),
+                None => Ok(()),
+            },
+            SttEngine::Whisper(r) => r.set_language(code),
+        }
+    }
+
+    /// Handle for cancelling a recognition in progress from another thread
+    pub fn cancel_handle(&self) -> CancelHandle {
+        match self {
+            SttEngine::Parakeet0_6B(r) | SttEngine::Parakeet1_1B(r) => r.cancel_handle(),
+            SttEngine::Whisper(r) => r.cancel_handle(),
+        }
+    }
+
+    /// Reload the model's ONNX Runtime sessions (after a cancelled recognition)
+    pub fn reload(&mut self) -> Result<()> {
+        match self {
+            SttEngine::Parakeet0_6B(r) | SttEngine::Parakeet1_1B(r) => r.reload(),
+            SttEngine::Whisper(r) => r.reload(),
+        }
+    }
+
+    /// Run one throwaway recognition so the first real segment is fast
+    ///
+    /// ONNX Runtime allocates its buffers (and GPU providers compile their
+    /// kernels) on a session's first run, which otherwise adds one to two
+    /// seconds to the first segment. The throwaway result is neither cached
+    /// nor kept for [`Self::n_best`], so nothing of it reaches a segment.
+    /// Returns how long the warm-up took.
+    pub fn warm_up(&mut self) -> Result<std::time::Duration> {
+        let start = std::time::Instant::now();
+        // A second of faint noise
+        let audio = warm_up_audio();
+        match self {
+            SttEngine::Parakeet0_6B(r) | SttEngine::Parakeet1_1B(r) => r.warm_up(&audio)?,
+            SttEngine::Whisper(r) => r.warm_up(&audio)?,
+        }
+        Ok(start.elapsed())
+    }
+
     /// Get minimum VRAM/memory required in MB
     ///
     /// Returns the minimum memory threshold for this model configuration.
@@ -167,6 +465,7 @@ impl SttEngine {
     /// - `4096` (4GB) for 1.1B INT8 GPU model (peak 3.5GB + 500MB headroom)
     /// - `1536` (1.5GB) for 0.6B GPU model (peak 1.2GB + 300MB headroom)
     /// - `0` for 0.6B CPU model (no GPU memory required)
+    /// - For Whisper on GPU, by size: from 512 (tiny) to 5120 (large)
     ///
     /// # Example
     ///
@@ -192,6 +491,19 @@ impl SttEngine {
                     0 // CPU doesn't require VRAM
                 }
             }
+            SttEngine::Whisper(r) => {
+                if !r.is_gpu() {
+                    return 0;
+                }
+                match r.size() {
+                    "tiny" => 512,
+                    "base" => 768,
+                    "small" => 1536,
+                    "medium" | "turbo" => 3072,
+                    "large" => 5120,
+                    _ => 2048,
+                }
+            }
         }
     }
 }
@@ -241,4 +553,64 @@ mod tests {
 
         println!("✓ Model metadata strings verified");
     }
+
+    #[test]
+    #[ignore] // Requires model files
+    fn test_warm_up_leaves_no_trace() {
+        let model_dir = "/opt/swictation/models/sherpa-onnx-nemo-parakeet-tdt-0.6b-v3-int8";
+        let mut engine = SttEngine::Parakeet0_6B(OrtRecognizer::new(model_dir, false).unwrap());
+        engine.set_result_cache(8);
+
+        engine.warm_up().unwrap();
+        assert_eq!(engine.cached_results(), 0, "warm-up was cached");
+        assert!(engine
+            .n_best(NBEST_ALTERNATIVES)
+            .iter()
+            .all(|h| h.text.is_empty()));
+
+        // The same audio as a real segment is decoded and cached as usual
+        engine.recognize(&warm_up_audio()).unwrap();
+        assert_eq!(engine.cached_results(), 1);
+    }
+
+    #[test]
+    #[ignore] // Requires model files
+    fn test_recognize_stream_matches_recognize() {
+        use crate::stream::StreamUpdate;
+        use std::pin::Pin;
+        use std::task::{Context, Poll, Waker};
+
+        /// Audio source with every chunk ready at once
+        struct Chunks(std::vec::IntoIter<Vec<f32>>);
+        impl Stream for Chunks {
+            type Item = Vec<f32>;
+            fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Vec<f32>>> {
+                Poll::Ready(self.0.next())
+            }
+        }
+
+        let model_dir = "/opt/swictation/models/sherpa-onnx-nemo-parakeet-tdt-0.6b-v3-int8";
+        let mut engine = SttEngine::Parakeet0_6B(OrtRecognizer::new(model_dir, false).unwrap());
+        let audio: Vec<f32> = (0..16_000 * 3)
+            .map(|i| (i as f32 * 0.02).sin() * 0.1)
+            .collect();
+        let expected = engine.recognize(&audio).unwrap().text;
+
+        let chunks: Vec<Vec<f32>> = audio.chunks(1600).map(<[f32]>::to_vec).collect();
+        let engine = std::sync::Arc::new(std::sync::Mutex::new(engine));
+        let mut results = SttEngine::recognize_stream(engine, Chunks(chunks.into_iter()));
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut updates = Vec::new();
+        loop {
+            match Pin::new(&mut results).poll_next(&mut cx) {
+                Poll::Ready(Some(update)) => updates.push(update.unwrap()),
+                Poll::Ready(None) => break,
+                Poll::Pending => std::thread::yield_now(),
+            }
+        }
+        match updates.last() {
+            Some(StreamUpdate::Final(result)) => assert_eq!(result.text, expected),
+            other => panic!("stream ended without a final result: {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,213 @@
+//! Shallow fusion of an external language model into greedy decoding
+//!
+//! When the joiner emits a non-blank token, the few most likely word pieces
+//! are re-ranked by acoustic log-probability plus `weight` times the
+//! language model's log-probability of the word prefix they spell. The
+//! blank/non-blank decision itself is left to the acoustic model, so fusion
+//! can change spelling ("arkon" → "archon") but never timing or length.
+//!
+//! The language model only has to score word prefixes, which keeps this
+//! crate independent of how it is trained (see [`PrefixScorer`]).
+
+use std::sync::Arc;
+
+/// SentencePiece word-boundary marker
+pub(crate) const WORD_MARKER: char = '▁';
+
+/// Non-blank pieces considered per emission
+const CANDIDATES: usize = 4;
+
+/// Language model queried during decoding
+pub trait PrefixScorer: Send + Sync {
+    /// ln P(next word starts with `prefix` | preceding words)
+    ///
+    /// `history` holds the previous words of the segment, oldest first, and
+    /// an empty prefix must score 0.
+    fn prefix_log_prob(&self, history: &[&str], prefix: &str) -> f32;
+}
+
+/// Language model plus the weight it gets against the acoustic score
+#[derive(Clone)]
+pub struct ShallowFusion {
+    scorer: Arc<dyn PrefixScorer>,
+    weight: f32,
+}
+
+impl ShallowFusion {
+    pub fn new(scorer: Arc<dyn PrefixScorer>, weight: f32) -> Self {
+        Self { scorer, weight }
+    }
+
+    pub fn weight(&self) -> f32 {
+        self.weight
+    }
+
+    /// Token to emit given the joiner's token logits
+    ///
+    /// `greedy` is the acoustic argmax and `emitted` the tokens decoded so far
+    /// in this segment.
+    pub(crate) fn choose(
+        &self,
+        token_logits: &[f32],
+        greedy: usize,
+        blank_id: usize,
+        vocab: &[String],
+        emitted: &[i64],
+    ) -> usize {
+        if greedy == blank_id || self.weight <= 0.0 {
+            return greedy;
+        }
+
+        let max = token_logits
+            .iter()
+            .cloned()
+            .fold(f32::NEG_INFINITY, f32::max);
+        let log_sum = max
+            + token_logits
+                .iter()
+                .map(|&v| (v - max).exp())
+                .sum::<f32>()
+                .ln();
+
+        let mut candidates: Vec<usize> = (0..token_logits.len().min(vocab.len()))
+            .filter(|&id| id != blank_id)
+            .collect();
+        candidates.sort_by(|&a, &b| token_logits[b].total_cmp(&token_logits[a]));
+        candidates.truncate(CANDIDATES);
+
+        let (words, partial) = split_words(emitted.iter().filter_map(|&id| vocab.get(id as usize)));
+        let history: Vec<&str> = words.iter().map(String::as_str).collect();
+
+        let score = |id: usize| {
+            let acoustic = token_logits[id] - log_sum;
+            acoustic + self.weight * self.piece_log_prob(&history, &partial, &vocab[id])
+        };
+
+        candidates
+            .into_iter()
+            .map(|id| (id, score(id)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(greedy, |(id, _)| id)
+    }
+
+    /// Weighted LM log-probability of emitting `token` after `emitted`
+    /// (beam search adds it to the acoustic score of each expansion)
+    pub(crate) fn bonus(&self, token: usize, vocab: &[String], emitted: &[i64]) -> f32 {
+        let Some(piece) = vocab.get(token) else {
+            return 0.0;
+        };
+        if self.weight <= 0.0 {
+            return 0.0;
+        }
+        let (words, partial) = split_words(emitted.iter().filter_map(|&id| vocab.get(id as usize)));
+        let history: Vec<&str> = words.iter().map(String::as_str).collect();
+        self.weight * self.piece_log_prob(&history, &partial, piece)
+    }
+
+    /// Change in LM log-probability from appending `piece` to the segment
+    ///
+    /// A piece that starts a new word is scored as that word's prefix; the
+    /// probability of the previous word ending there is not counted.
+    fn piece_log_prob(&self, history: &[&str], partial: &str, piece: &str) -> f32 {
+        if !piece.chars().any(char::is_alphanumeric) {
+            return 0.0;
+        }
+
+        match piece.strip_prefix(WORD_MARKER) {
+            Some(start) => {
+                let mut history = history.to_vec();
+                if !partial.is_empty() {
+                    history.push(partial);
+                }
+                self.scorer.prefix_log_prob(&history, &clean(start))
+            }
+            None => {
+                let extended = format!("{}{}", partial, clean(piece));
+                self.scorer.prefix_log_prob(history, &extended)
+                    - self.scorer.prefix_log_prob(history, partial)
+            }
+        }
+    }
+}
+
+/// Completed words and the word in progress spelled by `pieces`
+pub(crate) fn split_words<'a>(pieces: impl Iterator<Item = &'a String>) -> (Vec<String>, String) {
+    let mut words = Vec::new();
+    let mut partial = String::new();
+    for piece in pieces {
+        let text = match piece.strip_prefix(WORD_MARKER) {
+            Some(start) => {
+                if !partial.is_empty() {
+                    words.push(std::mem::take(&mut partial));
+                }
+                start
+            }
+            None => piece.as_str(),
+        };
+        partial.push_str(&clean(text));
+    }
+    (words, partial)
+}
+
+/// Lowercase letters, digits and apostrophes (the LM's word alphabet)
+pub(crate) fn clean(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '\'')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Knows the single word "archon"
+    struct FakeLm;
+
+    impl PrefixScorer for FakeLm {
+        fn prefix_log_prob(&self, _history: &[&str], prefix: &str) -> f32 {
+            if prefix.is_empty() {
+                0.0
+            } else if "archon".starts_with(prefix) {
+                (0.5f32).ln()
+            } else {
+                (0.01f32).ln()
+            }
+        }
+    }
+
+    fn vocab() -> Vec<String> {
+        ["<blk>", "▁ar", "kon", "chon", "▁the", "."]
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_fusion_prefers_known_spelling() {
+        let fusion = ShallowFusion::new(Arc::new(FakeLm), 1.0);
+        // Acoustics slightly prefer "kon" after "▁ar"
+        let logits = [0.0, -5.0, 2.0, 1.8, -5.0, -5.0];
+        assert_eq!(fusion.choose(&logits, 2, 0, &vocab(), &[4, 1]), 3);
+
+        // Without the LM, the acoustic choice stands
+        let off = ShallowFusion::new(Arc::new(FakeLm), 0.0);
+        assert_eq!(off.choose(&logits, 2, 0, &vocab(), &[4, 1]), 2);
+    }
+
+    #[test]
+    fn test_fusion_never_overrides_blank() {
+        let fusion = ShallowFusion::new(Arc::new(FakeLm), 5.0);
+        let logits = [3.0, -5.0, 2.0, 1.8, -5.0, -5.0];
+        assert_eq!(fusion.choose(&logits, 0, 0, &vocab(), &[4, 1]), 0);
+    }
+
+    #[test]
+    fn test_split_words() {
+        let vocab = vocab();
+        let pieces = [4, 1, 3, 5].iter().map(|&i| &vocab[i]);
+        let (words, partial) = split_words(pieces);
+        assert_eq!(words, vec!["the"]);
+        assert_eq!(partial, "archon");
+    }
+}
@@ -0,0 +1,264 @@
+//! User lexicon: out-of-vocabulary words spelled in word pieces
+//!
+//! Words like "kubectl" are not in the model's vocabulary, but they can be
+//! spelled with its pieces (`▁ku be ct l`). A lexicon file lists such words
+//! with the pieces that compose them:
+//!
+//! ```text
+//! # word      pieces (optional; derived from the vocabulary when omitted)
+//! kubectl     ▁ku be ct l
+//! Swictation
+//! ```
+//!
+//! During decoding, each piece that continues an entry's spelling gets a
+//! boost, as with phrase biasing (see [`crate::biasing`]), so the model can
+//! assemble the word from pieces it would otherwise rank lower. Words the
+//! decoder spells out are then written as in the lexicon, with its casing.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::biasing::choose_boosted;
+use crate::error::{Result, SttError};
+use crate::fusion::WORD_MARKER;
+
+/// Log-probability boost per piece that continues a lexicon spelling
+const BOOST: f32 = 2.0;
+
+/// A word and the piece IDs that spell it
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexiconEntry {
+    pub word: String,
+    pub pieces: Vec<i64>,
+}
+
+/// Words loaded from a lexicon file
+#[derive(Debug, Clone, Default)]
+pub struct Lexicon {
+    entries: Vec<LexiconEntry>,
+    /// Lowercase spelling of each entry → how the word is written
+    display: HashMap<String, String>,
+}
+
+impl Lexicon {
+    /// Read a lexicon file against the model vocabulary
+    pub fn load(path: &Path, vocab: &[String]) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            SttError::ConfigError(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        Self::parse(&contents, vocab)
+    }
+
+    /// Parse lexicon lines (see the module docs); every bad line is reported
+    pub fn parse(contents: &str, vocab: &[String]) -> Result<Self> {
+        let ids: HashMap<&str, i64> = vocab
+            .iter()
+            .enumerate()
+            .map(|(id, piece)| (piece.as_str(), id as i64))
+            .collect();
+
+        let mut lexicon = Self::default();
+        let mut problems = Vec::new();
+        for (number, line) in contents.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let mut fields = line.split_whitespace();
+            let Some(word) = fields.next() else {
+                continue;
+            };
+            let listed: Vec<&str> = fields.collect();
+
+            let pieces = if listed.is_empty() {
+                spell(word, &ids)
+                    .ok_or_else(|| format!("'{}' cannot be spelled with the vocabulary", word))
+            } else {
+                listed
+                    .iter()
+                    .map(|p| {
+                        ids.get(p)
+                            .copied()
+                            .ok_or_else(|| format!("'{}' is not a vocabulary piece", p))
+                    })
+                    .collect()
+            };
+            match pieces {
+                Ok(pieces) => lexicon.insert(word, pieces, vocab),
+                Err(problem) => problems.push(format!("line {}: {}", number + 1, problem)),
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(lexicon)
+        } else {
+            Err(SttError::ConfigError(format!(
+                "Invalid lexicon:\n{}",
+                problems.join("\n")
+            )))
+        }
+    }
+
+    fn insert(&mut self, word: &str, pieces: Vec<i64>, vocab: &[String]) {
+        let spelled: String = pieces
+            .iter()
+            .filter_map(|&id| vocab.get(id as usize))
+            .map(|piece| piece.trim_start_matches(WORD_MARKER))
+            .collect();
+        self.display
+            .insert(spelled.to_lowercase(), word.to_string());
+        self.entries.push(LexiconEntry {
+            word: word.to_string(),
+            pieces,
+        });
+    }
+
+    pub fn entries(&self) -> &[LexiconEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Token to emit given the joiner's token logits (see
+    /// [`crate::biasing::ContextBias::choose`])
+    pub(crate) fn choose(
+        &self,
+        token_logits: &[f32],
+        current: usize,
+        blank_id: usize,
+        emitted: &[i64],
+    ) -> usize {
+        if self.entries.is_empty() {
+            return current;
+        }
+        choose_boosted(token_logits, current, blank_id, |id| {
+            self.bonus(id, emitted)
+        })
+    }
+
+    /// Boost for `token` if it continues an entry's pieces after `emitted`
+    pub(crate) fn bonus(&self, token: usize, emitted: &[i64]) -> f32 {
+        let token = token as i64;
+        let continues = self.entries.iter().any(|entry| {
+            // Longest already-emitted run this token would extend
+            (0..entry.pieces.len()).any(|done| {
+                entry.pieces[done] == token
+                    && emitted.len() >= done
+                    && emitted[emitted.len() - done..] == entry.pieces[..done]
+            })
+        });
+        if continues {
+            BOOST
+        } else {
+            0.0
+        }
+    }
+
+    /// Write spelled-out lexicon words of `text` as the lexicon does
+    pub fn apply(&self, text: &str) -> String {
+        if self.display.is_empty() {
+            return text.to_string();
+        }
+        text.split(' ')
+            .map(|word| {
+                let start = word.find(char::is_alphanumeric).unwrap_or(word.len());
+                let end = word
+                    .rfind(char::is_alphanumeric)
+                    .map_or(start, |i| i + word[i..].chars().next().unwrap().len_utf8());
+                match self.display.get(&word[start..end].to_lowercase()) {
+                    Some(display) => format!("{}{}{}", &word[..start], display, &word[end..]),
+                    None => word.to_string(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Longest-match spelling of `word` in vocabulary pieces, starting a new word
+fn spell(word: &str, ids: &HashMap<&str, i64>) -> Option<Vec<i64>> {
+    let mut pieces = Vec::new();
+    let mut rest = word;
+    let mut first = true;
+    while !rest.is_empty() || first {
+        let lookup = |len: usize| {
+            let text = &rest[..len];
+            let piece = if first {
+                format!("{}{}", WORD_MARKER, text)
+            } else {
+                text.to_string()
+            };
+            ids.get(piece.as_str()).copied()
+        };
+        let found = rest
+            .char_indices()
+            .map(|(i, c)| i + c.len_utf8())
+            .rev()
+            .find_map(|len| lookup(len).map(|id| (id, len)));
+        match found {
+            Some((id, len)) => {
+                pieces.push(id);
+                rest = &rest[len..];
+            }
+            // A bare word marker, then the word's first piece on its own
+            None if first => pieces.push(lookup(0)?),
+            None => return None,
+        }
+        first = false;
+    }
+    Some(pieces)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vocab() -> Vec<String> {
+        [
+            "<blk>", "▁", "▁ku", "be", "ct", "l", "▁the", "s", "wic", "tation", ".",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+    }
+
+    #[test]
+    fn test_parse_listed_and_derived_pieces() {
+        let lexicon = Lexicon::parse(
+            "# tools\nkubectl ▁ku be ct l\n\nswictation   # derived\n",
+            &vocab(),
+        )
+        .unwrap();
+        assert_eq!(lexicon.len(), 2);
+        assert_eq!(lexicon.entries()[0].pieces, vec![2, 3, 4, 5]);
+        // No "▁s" piece: a bare word marker, then "s"
+        assert_eq!(lexicon.entries()[1].pieces, vec![1, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_parse_reports_every_bad_line() {
+        let err = Lexicon::parse("kubectl ▁ku bee\nzzz\n", &vocab())
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("line 1: 'bee' is not a vocabulary piece"),
+            "{}",
+            err
+        );
+        assert!(err.contains("line 2: 'zzz' cannot be spelled"), "{}", err);
+    }
+
+    #[test]
+    fn test_bonus_and_apply() {
+        let lexicon = Lexicon::parse("kubectl ▁ku be ct l\n", &vocab()).unwrap();
+        assert_eq!(lexicon.bonus(2, &[6]), BOOST);
+        assert_eq!(lexicon.bonus(4, &[6, 2, 3]), BOOST);
+        assert_eq!(lexicon.bonus(4, &[6, 2]), 0.0);
+
+        let lexicon = Lexicon::parse("KubeCTL ▁ku be ct l\n", &vocab()).unwrap();
+        assert_eq!(lexicon.apply("run kubectl, then"), "run KubeCTL, then");
+    }
+}
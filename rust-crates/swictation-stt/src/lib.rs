@@ -6,9 +6,25 @@
 //!
 //! - Direct ONNX Runtime integration (ort 2.0)
 //! - Parakeet-TDT 0.6B/1.1B model support
+//! - Whisper encoder/decoder models for multilingual dictation
+//! - Spoken language detection (`detect_language`, Whisper only)
 //! - RNN-T Transducer architecture
-//! - GPU acceleration via CUDA
+//! - GPU acceleration via CUDA or ROCm (Linux), CoreML (macOS) or DirectML (Windows),
+//!   and OpenVINO for Intel integrated GPUs and NPUs
 //! - CPU fallback support
+//! - Hour-long files in overlapping chunks merged at the seams (`recognize_file`)
+//! - Streaming partial hypotheses (`start_stream` / `feed` / `finalize`, or
+//!   `recognize_stream` over an async audio source)
+//! - Per-word confidence scores
+//! - Scored n-best hypotheses (`n_best`)
+//! - Optional beam search decoding (`set_decoding`)
+//! - Contextual biasing toward user phrases (`set_context_bias`)
+//! - User lexicon of out-of-vocabulary words (`load_lexicon`)
+//! - Shallow fusion with an external language model, e.g. ARPA n-grams (`set_shallow_fusion`)
+//! - Punctuation and capitalization restoration model (`Punctuator`)
+//! - Speaker labels from voice embeddings (`SpeakerEmbedder`, `SpeakerClusters`)
+//! - INT8 quantization of FP32 models, calibrated on recorded speech (`quantize`)
+//! - WER/CER/RTF benchmarks on your own recordings (`swictation-stt benchmark`)
 //! - Pure Rust API
 //!
 //! ## Quick Start
@@ -26,15 +42,47 @@
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 
+pub mod arpa; // ARPA n-gram language models for fusion
 pub mod audio; // Audio processing (mel-spectrogram)
+pub mod beam; // Beam search decoding
+pub mod benchmark; // WER/CER/RTF on recordings with transcripts
+pub mod biasing; // Boosting user phrases (hotwords)
+pub mod cache; // Results for repeated identical audio
+pub mod command_spotter; // Fixed-grammar command spotting (CTC)
+pub mod confidence; // Per-word confidence from token probabilities
 pub mod engine; // Unified STT engine interface
 pub mod error;
+pub mod fusion; // Language model shallow fusion
+pub mod lexicon; // User words spelled in word pieces
+pub mod longform; // Long recordings in overlapping chunks
+pub mod manifest; // Model format from model.json and input shapes
+pub mod nbest; // Alternative hypotheses from greedy decoding
+pub mod onnx_proto; // Minimal protobuf codec for rewriting ONNX graphs
+pub mod pinned; // Pinned-memory IO binding on CUDA and ROCm
+pub mod punctuation; // Punctuation and capitalization restoration
+pub mod quantize; // INT8 quantization of local models
 pub mod recognizer_ort; // Direct ONNX Runtime implementation
+pub mod speaker; // Speaker embeddings and clustering
+pub mod stream; // Partial hypotheses while audio arrives
+pub mod whisper; // Whisper encoder/decoder recognizer
+pub mod window; // Encoder windows with overlapping context
 
+pub use arpa::ArpaModel;
 pub use audio::AudioProcessor;
-pub use engine::{RecognitionResult, SttEngine}; // Unified STT engine enum
+pub use beam::{BeamConfig, Decoding, TdtConfig};
+pub use biasing::ContextBias;
+pub use command_spotter::{CommandMatch, CommandSpotter};
+pub use confidence::WordConfidence;
+pub use engine::{InferenceInfo, RecognitionResult, SttEngine}; // Unified STT engine enum
 pub use error::{Result, SttError};
-pub use recognizer_ort::OrtRecognizer;
+pub use fusion::{PrefixScorer, ShallowFusion};
+pub use lexicon::{Lexicon, LexiconEntry};
+pub use nbest::Hypothesis;
+pub use punctuation::Punctuator;
+pub use recognizer_ort::{CancelHandle, ExecutionTarget, OrtRecognizer, MAX_ENCODER_BATCH};
+pub use speaker::{SpeakerClusters, SpeakerEmbedder};
+pub use stream::{RecognitionStream, StreamEngine, StreamUpdate};
+pub use whisper::{DetectedLanguage, WhisperRecognizer};
 
 /// Default model path
 pub const DEFAULT_MODEL_PATH: &str = "/opt/swictation/models/parakeet-tdt-0.6b-v3-onnx";
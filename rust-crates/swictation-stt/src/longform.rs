@@ -0,0 +1,185 @@
+//! Long recordings in overlapping chunks, joined where they agree
+//!
+//! An hour of audio is too much to feature-extract and decode in one piece,
+//! and cutting it end to end splits words at every cut. Instead the audio is
+//! recognized in [`CHUNK_SECS`] chunks that overlap by [`OVERLAP_SECS`], so
+//! every seam is heard whole by both neighbours. At each seam the tail of the
+//! transcript so far and the head of the next chunk are aligned on their
+//! longest common run of words (ignoring case and punctuation), and the
+//! transcripts are spliced in the middle of that run, where both chunks had
+//! audio on either side.
+//!
+//! When the two sides share no run of [`MIN_RUN_WORDS`] words (silence or
+//! music in the overlap, or a garbled seam), they are cut at the middle of
+//! the overlap by word start time instead.
+
+use std::ops::Range;
+
+use crate::audio::SAMPLE_RATE;
+use crate::confidence::WordConfidence;
+
+/// Length of one recognized chunk
+pub const CHUNK_SECS: usize = 60;
+
+/// Audio shared by consecutive chunks
+pub const OVERLAP_SECS: usize = 5;
+
+/// Words at each side of a seam searched for a common run
+const SEAM_WORDS: usize = 32;
+
+/// Shortest common run trusted as an alignment (one shared "the" is not)
+const MIN_RUN_WORDS: usize = 2;
+
+/// Sample ranges of the chunks covering `total_samples`, in order
+///
+/// Audio up to [`CHUNK_SECS`] is a single chunk. Otherwise each chunk starts
+/// [`OVERLAP_SECS`] before the previous one ends, so the last chunk is
+/// always longer than the overlap.
+pub fn chunks(total_samples: usize) -> Vec<Range<usize>> {
+    let chunk = CHUNK_SECS * SAMPLE_RATE as usize;
+    let step = (CHUNK_SECS - OVERLAP_SECS) * SAMPLE_RATE as usize;
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    loop {
+        let end = (start + chunk).min(total_samples);
+        ranges.push(start..end);
+        if end == total_samples {
+            return ranges;
+        }
+        start += step;
+    }
+}
+
+/// Word as compared across a seam: lowercase, letters and digits only
+fn normalize(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Longest common run of `left` and `right`, as (left start, right start, length)
+fn longest_common_run(left: &[String], right: &[String]) -> (usize, usize, usize) {
+    let mut best = (0, 0, 0);
+    // lengths[j + 1]: run ending at the current left word and right word j
+    let mut lengths = vec![0; right.len() + 1];
+    for (i, l) in left.iter().enumerate() {
+        for j in (0..right.len()).rev() {
+            lengths[j + 1] = if !l.is_empty() && *l == right[j] {
+                lengths[j] + 1
+            } else {
+                0
+            };
+            if lengths[j + 1] > best.2 {
+                best = (
+                    i + 1 - lengths[j + 1],
+                    j + 1 - lengths[j + 1],
+                    lengths[j + 1],
+                );
+            }
+        }
+    }
+    best
+}
+
+/// Append the words of the next chunk to `merged`, dropping what both heard
+///
+/// Start times are in seconds from the start of the recording; `seam_s` is
+/// the middle of the overlap between the two chunks, used only when they
+/// share no run of words.
+pub fn merge(merged: &mut Vec<WordConfidence>, next: Vec<WordConfidence>, seam_s: f64) {
+    if merged.is_empty() {
+        merged.extend(next);
+        return;
+    }
+    let tail_start = merged.len().saturating_sub(SEAM_WORDS);
+    let tail: Vec<String> = merged[tail_start..]
+        .iter()
+        .map(|w| normalize(&w.word))
+        .collect();
+    let head: Vec<String> = next
+        .iter()
+        .take(SEAM_WORDS)
+        .map(|w| normalize(&w.word))
+        .collect();
+
+    let (left, right, run) = longest_common_run(&tail, &head);
+    if run >= MIN_RUN_WORDS {
+        let half = run / 2;
+        merged.truncate(tail_start + left + half);
+        merged.extend(next.into_iter().skip(right + half));
+        return;
+    }
+
+    // Words without a start time stay where they are
+    merged.retain(|w| w.start_s.is_none_or(|s| s < seam_s));
+    merged.extend(
+        next.into_iter()
+            .skip_while(|w| w.start_s.is_some_and(|s| s < seam_s)),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str, start_s: f64) -> Vec<WordConfidence> {
+        text.split_whitespace()
+            .enumerate()
+            .map(|(i, word)| WordConfidence {
+                word: word.to_string(),
+                confidence: 1.0,
+                start_s: Some(start_s + i as f64 * 0.5),
+            })
+            .collect()
+    }
+
+    fn text(words: &[WordConfidence]) -> String {
+        words
+            .iter()
+            .map(|w| w.word.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn test_chunks_overlap_and_cover_the_audio() {
+        let rate = SAMPLE_RATE as usize;
+        assert_eq!(chunks(10 * rate), vec![0..10 * rate]);
+        assert_eq!(chunks(CHUNK_SECS * rate), vec![0..CHUNK_SECS * rate]);
+
+        let total = 3600 * rate + 123;
+        let ranges = chunks(total);
+        assert_eq!(ranges[0].start, 0);
+        assert_eq!(ranges.last().unwrap().end, total);
+        assert!(ranges
+            .windows(2)
+            .all(|w| w[0].end - w[1].start == OVERLAP_SECS * rate));
+        assert!(ranges.last().unwrap().len() > OVERLAP_SECS * rate);
+    }
+
+    #[test]
+    fn test_merge_splices_inside_the_common_run() {
+        let mut merged = words("we should ship the release on Friday if", 0.0);
+        // The first chunk cut "if" short; the next one repeats the overlap
+        let next = words("release, on friday. If tests pass", 2.0);
+        merge(&mut merged, next, 2.5);
+        assert_eq!(
+            text(&merged),
+            "we should ship the release on friday. If tests pass"
+        );
+    }
+
+    #[test]
+    fn test_merge_without_common_run_cuts_at_the_seam() {
+        let mut merged = words("one two three four", 0.0);
+        // Overlap words heard differently on each side
+        let next = words("tree for five six", 1.0);
+        merge(&mut merged, next, 1.75);
+        assert_eq!(text(&merged), "one two three four five six");
+
+        let mut merged = Vec::new();
+        merge(&mut merged, words("first words", 0.0), 2.5);
+        assert_eq!(text(&merged), "first words");
+    }
+}
@@ -0,0 +1,123 @@
+//! `swictation-stt`: speech recognition tools outside the daemon
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+
+use swictation_stt::benchmark;
+use swictation_stt::ExecutionTarget;
+
+/// Model directories benchmarked when none are given
+const DEFAULT_MODELS: [&str; 2] = ["parakeet-tdt-0.6b-v3-onnx", "parakeet-tdt-1.1b-onnx"];
+
+#[derive(Parser)]
+#[command(
+    name = "swictation-stt",
+    version,
+    about = "Swictation speech recognition tools"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum Provider {
+    Cpu,
+    Gpu,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Score models on recordings with reference transcripts (WER, CER, RTF)
+    Benchmark {
+        /// Directory of name.wav files with name.txt transcripts
+        dataset: PathBuf,
+        /// Model directory; repeat to compare (default: installed Parakeet models)
+        #[arg(short, long = "model", value_name = "DIR")]
+        models: Vec<PathBuf>,
+        /// Providers to run each model on; unavailable ones are skipped
+        #[arg(short, long, value_delimiter = ',', default_values = ["cpu", "gpu"])]
+        providers: Vec<Provider>,
+        /// Print every file's reference and hypothesis
+        #[arg(short, long)]
+        verbose: bool,
+        /// Print results as JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::WARN)
+        .with_writer(std::io::stderr)
+        .init();
+
+    match Cli::parse().command {
+        Command::Benchmark {
+            dataset,
+            models,
+            providers,
+            verbose,
+            json,
+        } => {
+            let items = benchmark::load_dataset(&dataset)?;
+            let models = if models.is_empty() {
+                let models_dir = swictation_paths::get_models_dir()?;
+                DEFAULT_MODELS
+                    .iter()
+                    .map(|name| models_dir.join(name))
+                    .filter(|dir| dir.exists())
+                    .collect()
+            } else {
+                models
+            };
+            if models.is_empty() {
+                bail!("No Parakeet models installed - pass --model <DIR>");
+            }
+            eprintln!(
+                "Benchmarking {} recordings on {} models",
+                items.len(),
+                models.len()
+            );
+
+            let mut results = Vec::new();
+            for model in &models {
+                for provider in &providers {
+                    let target = match provider {
+                        Provider::Cpu => ExecutionTarget::Cpu,
+                        Provider::Gpu => ExecutionTarget::Gpu,
+                    };
+                    if !target.is_available() {
+                        eprintln!("- skipping {} (not available)", target.provider_name());
+                        continue;
+                    }
+                    eprintln!("- {} on {}", model.display(), target.provider_name());
+                    let result = benchmark::run(model, target, &items)
+                        .with_context(|| format!("Benchmark of {} failed", model.display()))?;
+                    if verbose {
+                        for file in &result.files {
+                            eprintln!(
+                                "  {} ({:.1}% WER)\n    ref: {}\n    hyp: {}",
+                                file.audio.display(),
+                                file.words.rate() * 100.0,
+                                file.reference,
+                                file.hypothesis
+                            );
+                        }
+                    }
+                    results.push(result);
+                }
+            }
+
+            if json {
+                println!("{}", serde_json::to_string_pretty(&results)?);
+            } else {
+                print!("{}", benchmark::format_table(&results));
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,249 @@
+//! Parakeet model format from `model.json` and the ONNX input shapes
+//!
+//! Exports differ in the number of mel features the encoder takes (128 for
+//! 0.6B, 80 for 1.1B), the decoder's hidden size and whether features or
+//! time come first in the encoder input. Most of that is visible in the
+//! static dimensions of the encoder and decoder inputs. A `model.json` next
+//! to the ONNX files can state it explicitly, and every field in it is
+//! optional:
+//!
+//! ```json
+//! {
+//!   "name": "Parakeet-TDT-1.1B",
+//!   "mel_features": 80,
+//!   "decoder_hidden_size": 640,
+//!   "feature_layout": "features_first"
+//! }
+//! ```
+//!
+//! A manifest value that contradicts a static input dimension is an error
+//! rather than a silent override, since the recognizer would otherwise feed
+//! the encoder features of the wrong size.
+
+use std::path::Path;
+
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::error::{Result, SttError};
+
+/// Manifest file name inside a model directory
+pub const MANIFEST_FILE: &str = "model.json";
+
+/// Mel features assumed when neither the manifest nor the encoder say
+const DEFAULT_MEL_FEATURES: usize = 128;
+
+/// Decoder hidden size assumed when neither the manifest nor the decoder say
+const DEFAULT_DECODER_HIDDEN_SIZE: usize = 640;
+
+/// Order of the two inner dimensions of the encoder input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureLayout {
+    /// `(batch, features, time)`, as NeMo exports it
+    FeaturesFirst,
+    /// `(batch, time, features)`
+    TimeFirst,
+}
+
+/// Contents of `model.json`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct ModelManifest {
+    pub name: Option<String>,
+    pub mel_features: Option<usize>,
+    pub decoder_hidden_size: Option<usize>,
+    pub feature_layout: Option<FeatureLayout>,
+}
+
+impl ModelManifest {
+    /// Read `model.json` from `model_dir`, or `None` if there is none
+    pub fn load(model_dir: &Path) -> Result<Option<Self>> {
+        let path = model_dir.join(MANIFEST_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let json = std::fs::read_to_string(&path)?;
+        Self::parse(&json)
+            .map(Some)
+            .map_err(|e| SttError::config(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Parse and validate a manifest
+    pub fn parse(json: &str) -> Result<Self> {
+        let manifest: Self = serde_json::from_str(json)
+            .map_err(|e| SttError::config(format!("invalid {}: {}", MANIFEST_FILE, e)))?;
+        if manifest.mel_features == Some(0) {
+            return Err(SttError::config("mel_features must be positive"));
+        }
+        if manifest.decoder_hidden_size == Some(0) {
+            return Err(SttError::config("decoder_hidden_size must be positive"));
+        }
+        Ok(manifest)
+    }
+}
+
+/// Resolved format of a loaded Parakeet model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelFormat {
+    /// Variant name for logging
+    pub name: String,
+    pub mel_features: usize,
+    pub decoder_hidden_size: usize,
+    pub feature_layout: FeatureLayout,
+}
+
+/// A dimension of an ONNX input shape, if it is static
+fn static_dim(shape: &[i64], index: usize) -> Option<usize> {
+    shape.get(index).filter(|&&d| d > 0).map(|&d| d as usize)
+}
+
+/// Use `declared` from the manifest unless it contradicts `detected`
+fn reconcile(
+    field: &str,
+    declared: Option<usize>,
+    detected: Option<usize>,
+    input: &str,
+) -> Result<Option<usize>> {
+    match (declared, detected) {
+        (Some(declared), Some(detected)) if declared != detected => Err(SttError::config(format!(
+            "{} declares {} = {} but the {} input has {}",
+            MANIFEST_FILE, field, declared, input, detected
+        ))),
+        (declared, detected) => Ok(declared.or(detected)),
+    }
+}
+
+/// Work out the model format from its manifest and input shapes
+///
+/// * `encoder_input` - shape of the encoder's `audio_signal` input, with
+///   `-1` for dynamic dimensions
+/// * `decoder_state` - shape of the decoder's recurrent state input
+pub fn resolve(
+    manifest: Option<&ModelManifest>,
+    encoder_input: &[i64],
+    decoder_state: &[i64],
+) -> Result<ModelFormat> {
+    let manifest = manifest.cloned().unwrap_or_default();
+
+    // The feature dimension is the static one of the two inner dimensions
+    let detected_layout = match (static_dim(encoder_input, 1), static_dim(encoder_input, 2)) {
+        (Some(_), None) => Some(FeatureLayout::FeaturesFirst),
+        (None, Some(_)) => Some(FeatureLayout::TimeFirst),
+        _ => None,
+    };
+    if let (Some(declared), Some(detected)) = (manifest.feature_layout, detected_layout) {
+        if declared != detected {
+            return Err(SttError::config(format!(
+                "{} declares feature_layout {:?} but the encoder input shape {:?} is {:?}",
+                MANIFEST_FILE, declared, encoder_input, detected
+            )));
+        }
+    }
+    let feature_layout = manifest
+        .feature_layout
+        .or(detected_layout)
+        .unwrap_or(FeatureLayout::FeaturesFirst);
+
+    let feature_dim = match feature_layout {
+        FeatureLayout::FeaturesFirst => 1,
+        FeatureLayout::TimeFirst => 2,
+    };
+    let mel_features = reconcile(
+        "mel_features",
+        manifest.mel_features,
+        static_dim(encoder_input, feature_dim),
+        "encoder audio_signal",
+    )?
+    .unwrap_or_else(|| {
+        warn!(
+            "Mel feature count not in {} or the encoder input, assuming {}",
+            MANIFEST_FILE, DEFAULT_MEL_FEATURES
+        );
+        DEFAULT_MEL_FEATURES
+    });
+
+    let decoder_hidden_size = reconcile(
+        "decoder_hidden_size",
+        manifest.decoder_hidden_size,
+        decoder_state
+            .len()
+            .checked_sub(1)
+            .and_then(|last| static_dim(decoder_state, last)),
+        "decoder state",
+    )?
+    .unwrap_or(DEFAULT_DECODER_HIDDEN_SIZE);
+
+    let name = manifest.name.unwrap_or_else(|| {
+        match mel_features {
+            80 => "Parakeet-TDT-1.1B",
+            128 => "Parakeet-TDT-0.6B",
+            _ => "Parakeet-TDT",
+        }
+        .to_string()
+    });
+
+    Ok(ModelFormat {
+        name,
+        mel_features,
+        decoder_hidden_size,
+        feature_layout,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_input_shapes() {
+        let format = resolve(None, &[-1, 80, -1], &[2, -1, 640]).unwrap();
+        assert_eq!(format.name, "Parakeet-TDT-1.1B");
+        assert_eq!(format.mel_features, 80);
+        assert_eq!(format.decoder_hidden_size, 640);
+        assert_eq!(format.feature_layout, FeatureLayout::FeaturesFirst);
+
+        let format = resolve(None, &[-1, -1, 128], &[2, -1, 512]).unwrap();
+        assert_eq!(format.name, "Parakeet-TDT-0.6B");
+        assert_eq!(format.feature_layout, FeatureLayout::TimeFirst);
+        assert_eq!(format.decoder_hidden_size, 512);
+    }
+
+    #[test]
+    fn test_manifest_fills_dynamic_dimensions() {
+        let manifest = ModelManifest::parse(
+            r#"{"name": "custom", "mel_features": 80, "feature_layout": "time_first"}"#,
+        )
+        .unwrap();
+        let format = resolve(Some(&manifest), &[-1, -1, -1], &[2, -1, -1]).unwrap();
+        assert_eq!(format.name, "custom");
+        assert_eq!(format.mel_features, 80);
+        assert_eq!(format.feature_layout, FeatureLayout::TimeFirst);
+        assert_eq!(format.decoder_hidden_size, DEFAULT_DECODER_HIDDEN_SIZE);
+    }
+
+    #[test]
+    fn test_manifest_contradicting_encoder_is_rejected() {
+        let manifest = ModelManifest::parse(r#"{"mel_features": 80}"#).unwrap();
+        let err = resolve(Some(&manifest), &[-1, 128, -1], &[]).unwrap_err();
+        assert!(err.to_string().contains("mel_features = 80"), "{}", err);
+        assert!(err.to_string().contains("has 128"), "{}", err);
+
+        let manifest = ModelManifest::parse(r#"{"feature_layout": "time_first"}"#).unwrap();
+        assert!(resolve(Some(&manifest), &[-1, 128, -1], &[]).is_err());
+    }
+
+    #[test]
+    fn test_invalid_manifest() {
+        assert!(ModelManifest::parse(r#"{"mel_features": 0}"#).is_err());
+        assert!(ModelManifest::parse(r#"{"feature_layout": "diagonal"}"#).is_err());
+        assert!(ModelManifest::parse("not json").is_err());
+        // Unknown fields are left for other tools
+        assert!(ModelManifest::parse(r#"{"sha256": "abc"}"#).is_ok());
+    }
+
+    #[test]
+    fn test_missing_manifest_is_none() {
+        let dir = std::env::temp_dir().join("swictation-no-manifest");
+        assert!(ModelManifest::load(&dir).unwrap().is_none());
+    }
+}
@@ -0,0 +1,179 @@
+//! N-best hypotheses from a single greedy pass ("lattice-lite")
+//!
+//! Greedy TDT decoding commits to one token per emission, but the joiner
+//! already scored every other token there. Each emission keeps its
+//! runner-up and the log-probability gap to the winner; alternatives are
+//! the best hypothesis with its closest calls flipped, cheapest first. A
+//! blank runner-up turns the emission into a deletion, so alternatives can
+//! also drop a spurious word piece.
+//!
+//! Scores are log-probabilities under the model: the best hypothesis scores
+//! the sum of its tokens' log-probabilities, and each flip costs its gap.
+//! Re-rankers (e.g. a language model) can add their own score on top.
+
+/// A transcription with its score
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hypothesis {
+    pub text: String,
+    /// Log-probability of the token sequence (<= 0, higher is likelier)
+    pub score: f32,
+}
+
+/// One emitted token and the closest competing choice
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Emission {
+    pub token: i64,
+    /// Second-best token at this emission (may be blank)
+    pub runner_up: i64,
+    /// Log-probability of `token` minus that of `runner_up` (>= 0)
+    pub gap: f32,
+    /// Softmax probability of `token` among all tokens (see [`crate::confidence`])
+    pub prob: f32,
+    /// Encoder frame of the emission, counted from the start of the audio
+    pub frame: usize,
+}
+
+impl Emission {
+    /// Build from the joiner's token logits at the emission
+    pub fn from_logits(token: i64, token_logits: &[f32]) -> Self {
+        let (runner_up, runner_logit) = token_logits
+            .iter()
+            .enumerate()
+            .filter(|(id, _)| *id as i64 != token)
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(id, &logit)| (id as i64, logit))
+            .unwrap_or((token, f32::NEG_INFINITY));
+        let gap = token_logits
+            .get(token as usize)
+            .map_or(f32::INFINITY, |&logit| (logit - runner_logit).max(0.0));
+        Self {
+            token,
+            runner_up,
+            gap,
+            prob: softmax_prob(token, token_logits),
+            frame: 0,
+        }
+    }
+
+    /// The same emission at encoder frame `frame`
+    pub fn at(self, frame: usize) -> Self {
+        Self { frame, ..self }
+    }
+}
+
+/// Probability of `token` under a softmax over `logits` (1.0 if out of range)
+fn softmax_prob(token: i64, logits: &[f32]) -> f32 {
+    let Some(&logit) = logits.get(token as usize) else {
+        return 1.0;
+    };
+    let max = logits.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+    let sum: f32 = logits.iter().map(|&l| (l - max).exp()).sum();
+    (logit - max).exp() / sum
+}
+
+/// Up to `k` alternative token sequences, most likely first
+///
+/// Alternative `i` flips the `i`-th closest call. Pairs of close calls are
+/// not combined; with k ≤ 5 single flips cover what users pick in practice.
+pub fn alternatives(emissions: &[Emission], blank_id: i64, k: usize) -> Vec<Vec<i64>> {
+    hypotheses(emissions, blank_id, k + 1)
+        .into_iter()
+        .skip(1)
+        .map(|(tokens, _)| tokens)
+        .collect()
+}
+
+/// Up to `n` token sequences with their scores: the greedy best, then its
+/// alternatives (see [`alternatives`]), highest score first
+pub fn hypotheses(emissions: &[Emission], blank_id: i64, n: usize) -> Vec<(Vec<i64>, f32)> {
+    if n == 0 {
+        return Vec::new();
+    }
+    let best_score: f32 = emissions.iter().map(|e| e.prob.ln()).sum();
+    let mut by_gap: Vec<usize> = (0..emissions.len())
+        .filter(|&i| emissions[i].gap.is_finite() && emissions[i].runner_up != emissions[i].token)
+        .collect();
+    by_gap.sort_by(|&a, &b| emissions[a].gap.total_cmp(&emissions[b].gap));
+
+    let flip_tokens = |flip: Option<usize>| -> Vec<i64> {
+        emissions
+            .iter()
+            .enumerate()
+            .filter_map(|(i, e)| {
+                let token = if Some(i) == flip {
+                    e.runner_up
+                } else {
+                    e.token
+                };
+                (token != blank_id).then_some(token)
+            })
+            .collect()
+    };
+
+    std::iter::once((flip_tokens(None), best_score))
+        .chain(
+            by_gap
+                .into_iter()
+                .take(n - 1)
+                .map(|flip| (flip_tokens(Some(flip)), best_score - emissions[flip].gap)),
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLANK: i64 = 0;
+
+    fn emission(token: i64, runner_up: i64, gap: f32) -> Emission {
+        Emission {
+            token,
+            runner_up,
+            gap,
+            prob: 1.0,
+            frame: 0,
+        }
+    }
+
+    #[test]
+    fn test_runner_up_from_logits() {
+        let e = Emission::from_logits(2, &[1.0, 0.5, 3.0, 2.5]);
+        assert_eq!(e.runner_up, 3);
+        assert!((e.gap - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_prob_is_softmax() {
+        let e = Emission::from_logits(1, &[0.0, 2.0_f32.ln(), 0.0]);
+        assert!((e.prob - 0.5).abs() < 1e-6);
+        assert_eq!(Emission::from_logits(7, &[1.0, 2.0]).prob, 1.0);
+    }
+
+    #[test]
+    fn test_closest_calls_flip_first() {
+        let emissions = [
+            emission(5, 6, 3.0),
+            emission(7, 8, 0.2),
+            emission(9, BLANK, 1.0),
+        ];
+        let alts = alternatives(&emissions, BLANK, 5);
+        assert_eq!(alts, vec![vec![5, 8, 9], vec![5, 7], vec![6, 7, 9]]);
+        assert_eq!(alternatives(&emissions, BLANK, 1).len(), 1);
+    }
+
+    #[test]
+    fn test_hypotheses_are_scored() {
+        let mut emissions = [emission(5, 6, 3.0), emission(7, 8, 0.2)];
+        emissions[0].prob = 0.5;
+        let hyps = hypotheses(&emissions, BLANK, 3);
+        assert_eq!(hyps.len(), 3);
+        assert_eq!(hyps[0].0, vec![5, 7]);
+        assert!((hyps[0].1 - 0.5_f32.ln()).abs() < 1e-6);
+        // Each flip costs its gap
+        assert_eq!(hyps[1].0, vec![5, 8]);
+        assert!((hyps[1].1 - (0.5_f32.ln() - 0.2)).abs() < 1e-6);
+        assert!(hyps[2].1 < hyps[1].1);
+        assert!(hypotheses(&emissions, BLANK, 0).is_empty());
+    }
+}
@@ -0,0 +1,386 @@
+//! Just enough protobuf to rewrite ONNX models
+//!
+//! A [`Message`] keeps every field it was decoded from, in order, as raw
+//! wire values. Code that rewrites a model reads and replaces the handful of
+//! fields it understands (graph nodes, initializers, outputs) and everything
+//! else (metadata, shapes, attributes of other nodes) is written back byte
+//! for byte, so no ONNX schema has to be vendored.
+
+use crate::error::{Result, SttError};
+
+/// Field numbers from `onnx.proto`
+pub mod field {
+    pub mod model {
+        pub const OPSET_IMPORT: u32 = 8;
+        pub const GRAPH: u32 = 7;
+    }
+    pub mod opset {
+        pub const DOMAIN: u32 = 1;
+        pub const VERSION: u32 = 2;
+    }
+    pub mod graph {
+        pub const NODE: u32 = 1;
+        pub const INITIALIZER: u32 = 5;
+        pub const INPUT: u32 = 11;
+        pub const OUTPUT: u32 = 12;
+    }
+    pub mod node {
+        pub const INPUT: u32 = 1;
+        pub const OUTPUT: u32 = 2;
+        pub const NAME: u32 = 3;
+        pub const OP_TYPE: u32 = 4;
+        pub const ATTRIBUTE: u32 = 5;
+        pub const DOMAIN: u32 = 7;
+    }
+    pub mod attribute {
+        pub const NAME: u32 = 1;
+        pub const I: u32 = 3;
+        pub const G: u32 = 6;
+        pub const GRAPHS: u32 = 11;
+        pub const TYPE: u32 = 20;
+    }
+    pub mod tensor {
+        pub const DIMS: u32 = 1;
+        pub const DATA_TYPE: u32 = 2;
+        pub const FLOAT_DATA: u32 = 4;
+        pub const NAME: u32 = 8;
+        pub const RAW_DATA: u32 = 9;
+        pub const EXTERNAL_DATA: u32 = 13;
+        pub const DATA_LOCATION: u32 = 14;
+    }
+    pub mod entry {
+        pub const KEY: u32 = 1;
+        pub const VALUE: u32 = 2;
+    }
+    pub mod value_info {
+        pub const NAME: u32 = 1;
+        pub const TYPE: u32 = 2;
+    }
+    pub mod type_proto {
+        pub const TENSOR_TYPE: u32 = 1;
+        pub const ELEM_TYPE: u32 = 1;
+    }
+}
+
+/// `TensorProto.DataType` values
+pub mod data_type {
+    pub const FLOAT: u64 = 1;
+    pub const UINT8: u64 = 2;
+    pub const INT8: u64 = 3;
+}
+
+/// `AttributeProto.AttributeType.INT`
+pub const ATTRIBUTE_INT: u64 = 2;
+
+/// `TensorProto.DataLocation.EXTERNAL`
+pub const DATA_LOCATION_EXTERNAL: u64 = 1;
+
+/// A raw field value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(Vec<u8>),
+    Fixed32(u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub number: u32,
+    pub value: Value,
+}
+
+/// A decoded protobuf message
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Message {
+    pub fields: Vec<Field>,
+}
+
+fn truncated() -> SttError {
+    SttError::model_load("Truncated protobuf message")
+}
+
+fn read_varint(bytes: &[u8], pos: &mut usize) -> Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*pos).ok_or_else(truncated)?;
+        *pos += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(SttError::model_load("Protobuf varint too long"))
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn take<'a>(bytes: &'a [u8], pos: &mut usize, len: usize) -> Result<&'a [u8]> {
+    let end = pos.checked_add(len).ok_or_else(truncated)?;
+    let slice = bytes.get(*pos..end).ok_or_else(truncated)?;
+    *pos = end;
+    Ok(slice)
+}
+
+impl Message {
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut fields = Vec::new();
+        let mut pos = 0;
+        while pos < bytes.len() {
+            let tag = read_varint(bytes, &mut pos)?;
+            let number = (tag >> 3) as u32;
+            let value = match tag & 7 {
+                0 => Value::Varint(read_varint(bytes, &mut pos)?),
+                1 => Value::Fixed64(u64::from_le_bytes(
+                    take(bytes, &mut pos, 8)?.try_into().unwrap(),
+                )),
+                2 => {
+                    let len = read_varint(bytes, &mut pos)? as usize;
+                    Value::Bytes(take(bytes, &mut pos, len)?.to_vec())
+                }
+                5 => Value::Fixed32(u32::from_le_bytes(
+                    take(bytes, &mut pos, 4)?.try_into().unwrap(),
+                )),
+                wire => {
+                    return Err(SttError::model_load(format!(
+                        "Unsupported protobuf wire type {} in field {}",
+                        wire, number
+                    )))
+                }
+            };
+            fields.push(Field { number, value });
+        }
+        Ok(Self { fields })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        for field in &self.fields {
+            let number = u64::from(field.number) << 3;
+            match &field.value {
+                Value::Varint(v) => {
+                    write_varint(&mut out, number);
+                    write_varint(&mut out, *v);
+                }
+                Value::Fixed64(v) => {
+                    write_varint(&mut out, number | 1);
+                    out.extend_from_slice(&v.to_le_bytes());
+                }
+                Value::Bytes(b) => {
+                    write_varint(&mut out, number | 2);
+                    write_varint(&mut out, b.len() as u64);
+                    out.extend_from_slice(b);
+                }
+                Value::Fixed32(v) => {
+                    write_varint(&mut out, number | 5);
+                    out.extend_from_slice(&v.to_le_bytes());
+                }
+            }
+        }
+        out
+    }
+
+    /// Length-delimited values of field `number`
+    pub fn bytes(&self, number: u32) -> impl Iterator<Item = &[u8]> {
+        self.fields.iter().filter_map(move |f| match &f.value {
+            Value::Bytes(b) if f.number == number => Some(b.as_slice()),
+            _ => None,
+        })
+    }
+
+    /// First string value of field `number`
+    pub fn string(&self, number: u32) -> Option<&str> {
+        self.strings(number).next()
+    }
+
+    pub fn strings(&self, number: u32) -> impl Iterator<Item = &str> {
+        self.bytes(number)
+            .filter_map(|b| std::str::from_utf8(b).ok())
+    }
+
+    /// Varints of field `number`, packed or not
+    pub fn varints(&self, number: u32) -> Result<Vec<u64>> {
+        let mut values = Vec::new();
+        for field in self.fields.iter().filter(|f| f.number == number) {
+            match &field.value {
+                Value::Varint(v) => values.push(*v),
+                Value::Bytes(packed) => {
+                    let mut pos = 0;
+                    while pos < packed.len() {
+                        values.push(read_varint(packed, &mut pos)?);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(values)
+    }
+
+    pub fn varint(&self, number: u32) -> Option<u64> {
+        self.fields.iter().find_map(|f| match f.value {
+            Value::Varint(v) if f.number == number => Some(v),
+            _ => None,
+        })
+    }
+
+    /// Sub-messages in field `number`
+    pub fn messages(&self, number: u32) -> Result<Vec<Message>> {
+        self.bytes(number).map(Message::decode).collect()
+    }
+
+    /// Drop every value of field `number`
+    pub fn remove(&mut self, number: u32) {
+        self.fields.retain(|f| f.number != number);
+    }
+
+    pub fn push_bytes(&mut self, number: u32, bytes: Vec<u8>) {
+        self.fields.push(Field {
+            number,
+            value: Value::Bytes(bytes),
+        });
+    }
+
+    pub fn push_string(&mut self, number: u32, s: &str) {
+        self.push_bytes(number, s.as_bytes().to_vec());
+    }
+
+    pub fn push_varint(&mut self, number: u32, v: u64) {
+        self.fields.push(Field {
+            number,
+            value: Value::Varint(v),
+        });
+    }
+
+    pub fn push_message(&mut self, number: u32, message: &Message) {
+        self.push_bytes(number, message.encode());
+    }
+
+    /// Replace all values of field `number` with `messages`, keeping the
+    /// position of the first one
+    pub fn set_messages(&mut self, number: u32, messages: &[Message]) {
+        let at = self
+            .fields
+            .iter()
+            .position(|f| f.number == number)
+            .unwrap_or(self.fields.len());
+        self.remove(number);
+        let encoded = messages.iter().map(|m| Field {
+            number,
+            value: Value::Bytes(m.encode()),
+        });
+        let tail = self.fields.split_off(at.min(self.fields.len()));
+        self.fields.extend(encoded);
+        self.fields.extend(tail);
+    }
+}
+
+/// A `NodeProto` in the default domain
+pub fn node(op_type: &str, name: &str, inputs: &[&str], outputs: &[&str]) -> Message {
+    let mut node = Message::default();
+    for input in inputs {
+        node.push_string(field::node::INPUT, input);
+    }
+    for output in outputs {
+        node.push_string(field::node::OUTPUT, output);
+    }
+    node.push_string(field::node::NAME, name);
+    node.push_string(field::node::OP_TYPE, op_type);
+    node
+}
+
+/// An integer `AttributeProto`
+pub fn int_attribute(name: &str, value: i64) -> Message {
+    let mut attribute = Message::default();
+    attribute.push_string(field::attribute::NAME, name);
+    attribute.push_varint(field::attribute::I, value as u64);
+    attribute.push_varint(field::attribute::TYPE, ATTRIBUTE_INT);
+    attribute
+}
+
+/// A `TensorProto` with inline raw data
+pub fn tensor(name: &str, data_type: u64, dims: &[usize], raw: Vec<u8>) -> Message {
+    let mut tensor = Message::default();
+    for &dim in dims {
+        tensor.push_varint(field::tensor::DIMS, dim as u64);
+    }
+    tensor.push_varint(field::tensor::DATA_TYPE, data_type);
+    tensor.push_string(field::tensor::NAME, name);
+    tensor.push_bytes(field::tensor::RAW_DATA, raw);
+    tensor
+}
+
+/// A float tensor `ValueInfoProto` without shape, for extra graph outputs
+pub fn float_value_info(name: &str) -> Message {
+    let mut tensor_type = Message::default();
+    tensor_type.push_varint(field::type_proto::ELEM_TYPE, data_type::FLOAT);
+    let mut type_proto = Message::default();
+    type_proto.push_message(field::type_proto::TENSOR_TYPE, &tensor_type);
+    let mut info = Message::default();
+    info.push_string(field::value_info::NAME, name);
+    info.push_message(field::value_info::TYPE, &type_proto);
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_keeps_unknown_fields() {
+        let mut message = Message::default();
+        message.push_varint(1, 300);
+        message.push_string(2, "encoder");
+        message.fields.push(Field {
+            number: 3,
+            value: Value::Fixed32(0x3f80_0000),
+        });
+        message.fields.push(Field {
+            number: 4,
+            value: Value::Fixed64(7),
+        });
+        let decoded = Message::decode(&message.encode()).unwrap();
+        assert_eq!(decoded, message);
+        assert_eq!(decoded.varint(1), Some(300));
+        assert_eq!(decoded.string(2), Some("encoder"));
+    }
+
+    #[test]
+    fn test_packed_and_unpacked_varints() {
+        let mut message = Message::default();
+        message.push_varint(1, 2);
+        message.push_bytes(1, vec![0x80, 0x01, 0x03]); // packed [128, 3]
+        assert_eq!(message.varints(1).unwrap(), [2, 128, 3]);
+    }
+
+    #[test]
+    fn test_set_messages_keeps_position() {
+        let mut graph = Message::default();
+        graph.push_message(field::graph::NODE, &node("Relu", "a", &["x"], &["y"]));
+        graph.push_string(2, "graph-name");
+        graph.set_messages(
+            field::graph::NODE,
+            &[
+                node("Relu", "b", &["x"], &["y"]),
+                node("Relu", "c", &["y"], &["z"]),
+            ],
+        );
+        assert_eq!(graph.fields.len(), 3);
+        assert_eq!(graph.fields[2].number, 2);
+        let nodes = graph.messages(field::graph::NODE).unwrap();
+        assert_eq!(nodes[1].string(field::node::NAME), Some("c"));
+    }
+
+    #[test]
+    fn test_truncated_input_is_an_error() {
+        let mut message = Message::default();
+        message.push_string(1, "abcdef");
+        let bytes = message.encode();
+        assert!(Message::decode(&bytes[..bytes.len() - 2]).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! IO binding through pinned host memory for CUDA and ROCm sessions
+//!
+//! `Session::run` builds a new host tensor for every input and output and
+//! copies each through pageable memory on every call. The joiner runs once
+//! per encoder frame and the decoder once per emitted token, so on a GPU
+//! most of their latency is those copies and allocations rather than
+//! compute. A [`PinnedBinding`] keeps one [`IoBinding`] per session: inputs
+//! are staged in page-locked buffers that are allocated once and reused
+//! while their shape stays the same, and outputs are bound to page-locked
+//! host memory that the device writes by DMA.
+//!
+//! Other providers (CPU, CoreML, DirectML, OpenVINO) keep plain runs, where
+//! a binding would only add bookkeeping.
+
+use std::collections::HashMap;
+
+use ort::io_binding::IoBinding;
+use ort::memory::{AllocationDevice, Allocator, AllocatorType, MemoryInfo, MemoryType};
+use ort::session::{RunOptions, Session, SessionOutputs};
+use ort::tensor::PrimitiveTensorElementType;
+use ort::value::DynTensor;
+
+/// Page-locked memory for `provider`'s device, if it has any
+fn pinned_device(provider: &str) -> Option<AllocationDevice> {
+    match provider {
+        "CUDA" => Some(AllocationDevice::CUDA_PINNED),
+        "ROCm" => Some(AllocationDevice::HIP_PINNED),
+        _ => None,
+    }
+}
+
+/// Whether sessions on `provider` run through a [`PinnedBinding`]
+pub fn supported(provider: &str) -> bool {
+    pinned_device(provider).is_some()
+}
+
+/// Reusable inputs and pinned outputs for one session
+pub struct PinnedBinding {
+    binding: IoBinding,
+    input_allocator: Allocator,
+    /// Staging buffers by input name
+    inputs: HashMap<String, DynTensor>,
+}
+
+impl PinnedBinding {
+    /// Binding for `session` on `provider` (`"CUDA"` or `"ROCm"`), or `None`
+    /// for providers without pinned memory
+    pub fn new(session: &Session, provider: &str) -> ort::Result<Option<Self>> {
+        let Some(device) = pinned_device(provider) else {
+            return Ok(None);
+        };
+        let input_allocator = Allocator::new(
+            session,
+            MemoryInfo::new(device, 0, AllocatorType::Device, MemoryType::CPUInput)?,
+        )?;
+        let output_memory =
+            MemoryInfo::new(device, 0, AllocatorType::Device, MemoryType::CPUOutput)?;
+
+        let mut binding = session.create_binding()?;
+        for output in &session.outputs {
+            binding.bind_output_to_device(&output.name, &output_memory)?;
+        }
+        Ok(Some(Self {
+            binding,
+            input_allocator,
+            inputs: HashMap::new(),
+        }))
+    }
+
+    /// Copy `data` into the staging buffer for input `name` and bind it
+    ///
+    /// The buffer is reallocated only when `shape` differs from the last
+    /// call's. Binding copies the data to the device right away, so the
+    /// buffer is free for the next call.
+    pub fn bind_input<T: PrimitiveTensorElementType + Copy>(
+        &mut self,
+        name: &str,
+        shape: &[usize],
+        data: &[T],
+    ) -> ort::Result<()> {
+        let same_shape = self.inputs.get(name).is_some_and(|tensor| {
+            tensor
+                .shape()
+                .iter()
+                .map(|&d| d as usize)
+                .eq(shape.iter().copied())
+        });
+        if !same_shape {
+            let tensor =
+                DynTensor::new(&self.input_allocator, T::into_tensor_element_type(), shape)?;
+            self.inputs.insert(name.to_string(), tensor);
+        }
+        let tensor = self
+            .inputs
+            .get_mut(name)
+            .expect("staging buffer just inserted");
+        tensor
+            .try_extract_tensor_mut::<T>()?
+            .1
+            .copy_from_slice(data);
+        self.binding.bind_input(name, tensor)
+    }
+
+    /// Run `session` on the bound inputs
+    pub fn run<'b>(
+        &'b mut self,
+        session: &'b mut Session,
+        run_options: &RunOptions,
+    ) -> ort::Result<SessionOutputs<'b>> {
+        session.run_binding_with_options(&self.binding, run_options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pinned_memory_only_for_cuda_and_rocm() {
+        assert!(supported("CUDA"));
+        assert!(supported("ROCm"));
+        for provider in ["CPU", "CoreML", "DirectML", "OpenVINO"] {
+            assert!(!supported(provider), "{}", provider);
+        }
+    }
+}
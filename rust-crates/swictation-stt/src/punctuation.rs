@@ -0,0 +1,292 @@
+//! Punctuation and capitalization restored by a token-classification model
+//!
+//! The 1.1B model writes no punctuation, and the 0.6B model and Whisper add
+//! it inconsistently (sometimes "comma" becomes ",", sometimes "period"
+//! becomes "period."). This stage ignores whatever punctuation and casing
+//! the recognizer wrote and predicts them afresh for every word, so all
+//! models produce the same natural sentences.
+//!
+//! Expects a NeMo `punctuation_capitalization` ONNX export: `model.onnx`
+//! with `input_ids` / `attention_mask` (and optionally `token_type_ids`)
+//! inputs and `punct_logits` / `capit_logits` outputs, the BERT WordPiece
+//! `vocab.txt`, and `punct_label_ids.csv` / `capit_label_ids.csv` listing
+//! the labels in id order (`O` for none, `U` to capitalize). The model is
+//! small and runs on CPU.
+
+use crate::error::{Result, SttError};
+use ort::{
+    session::{builder::GraphOptimizationLevel, Session},
+    value::Tensor,
+};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tracing::info;
+
+/// Longest input the BERT encoders accept, including [CLS] and [SEP]
+const MAX_TOKENS: usize = 512;
+
+/// "No punctuation" / "keep lowercase" label
+const NO_LABEL: &str = "O";
+
+/// Label asking for the word's first letter in upper case
+const CAPITALIZE: &str = "U";
+
+/// Adds punctuation and capitals to unpunctuated lowercase text
+pub struct Punctuator {
+    session: Session,
+    vocab: HashMap<String, i64>,
+    cls_id: i64,
+    sep_id: i64,
+    unk_id: i64,
+    punct_labels: Vec<String>,
+    capit_labels: Vec<String>,
+    token_type_ids: bool,
+}
+
+impl Punctuator {
+    /// Load the model and its vocabulary and label files from `model_dir`
+    pub fn new<P: AsRef<Path>>(model_dir: P) -> Result<Self> {
+        let model_dir = model_dir.as_ref();
+        info!("Loading punctuation model from {}", model_dir.display());
+
+        let read_lines = |name: &str| -> Result<Vec<String>> {
+            let contents = fs::read_to_string(model_dir.join(name))
+                .map_err(|e| SttError::model_load(format!("Failed to read {}: {}", name, e)))?;
+            Ok(contents
+                .lines()
+                .map(|line| line.trim().to_string())
+                .collect())
+        };
+        let vocab: HashMap<String, i64> = read_lines("vocab.txt")?
+            .into_iter()
+            .enumerate()
+            .map(|(id, piece)| (piece, id as i64))
+            .collect();
+        let special = |token: &str| {
+            vocab
+                .get(token)
+                .copied()
+                .ok_or_else(|| SttError::model_load(format!("vocab.txt has no {} token", token)))
+        };
+        let (cls_id, sep_id, unk_id) = (special("[CLS]")?, special("[SEP]")?, special("[UNK]")?);
+        let punct_labels = read_lines("punct_label_ids.csv")?;
+        let capit_labels = read_lines("capit_label_ids.csv")?;
+        if punct_labels.is_empty() || capit_labels.is_empty() {
+            return Err(SttError::model_load("Punctuation label files are empty"));
+        }
+
+        let model_path = model_dir.join("model.onnx");
+        let session = Session::builder()
+            .map_err(|e| SttError::model_load(format!("Failed to create session builder: {}", e)))?
+            .with_optimization_level(GraphOptimizationLevel::Level3)
+            .map_err(|e| SttError::model_load(format!("Failed to set optimization level: {}", e)))?
+            .with_intra_threads(1)
+            .map_err(|e| SttError::model_load(format!("Failed to set intra threads: {}", e)))?
+            .commit_from_file(&model_path)
+            .map_err(|e| {
+                SttError::model_load(format!("Failed to load punctuation model: {}", e))
+            })?;
+        let token_type_ids = session
+            .inputs
+            .iter()
+            .any(|input| input.name == "token_type_ids");
+
+        info!(
+            "✓ Punctuation model loaded ({} pieces, punctuation {})",
+            vocab.len(),
+            punct_labels
+                .iter()
+                .filter(|l| *l != NO_LABEL)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+
+        Ok(Self {
+            session,
+            vocab,
+            cls_id,
+            sep_id,
+            unk_id,
+            punct_labels,
+            capit_labels,
+            token_type_ids,
+        })
+    }
+
+    /// `text` with its punctuation and capitals replaced by the model's
+    pub fn restore(&mut self, text: &str) -> Result<String> {
+        let words: Vec<String> = text
+            .split_whitespace()
+            .map(bare_word)
+            .filter(|word| !word.is_empty())
+            .collect();
+        let pieces: Vec<Vec<i64>> = words
+            .iter()
+            .map(|word| wordpiece(word, &self.vocab, self.unk_id))
+            .collect();
+
+        let mut restored = Vec::with_capacity(words.len());
+        let mut start = 0;
+        while start < words.len() {
+            // As many whole words as fit, but always at least one
+            let mut end = start + 1;
+            let mut len = pieces[start].len();
+            while end < words.len() && len + pieces[end].len() <= MAX_TOKENS - 2 {
+                len += pieces[end].len();
+                end += 1;
+            }
+            let labels = self.classify(&pieces[start..end])?;
+            for (word, (punct, capit)) in words[start..end].iter().zip(labels) {
+                restored.push(label_word(
+                    word,
+                    &self.punct_labels[punct],
+                    &self.capit_labels[capit],
+                ));
+            }
+            start = end;
+        }
+        Ok(restored.join(" "))
+    }
+
+    /// Punctuation and capitalization label ids for each word, read at the
+    /// word's first piece
+    fn classify(&mut self, words: &[Vec<i64>]) -> Result<Vec<(usize, usize)>> {
+        let mut ids = vec![self.cls_id];
+        let mut first_piece = Vec::with_capacity(words.len());
+        for pieces in words {
+            first_piece.push(ids.len());
+            // A word longer than the window is cut; its label comes from the start
+            ids.extend(pieces.iter().take(MAX_TOKENS - 2));
+        }
+        ids.push(self.sep_id);
+        let len = ids.len();
+
+        let tensor = |data: Vec<i64>| {
+            Tensor::from_array((vec![1, len], data.into_boxed_slice())).map_err(|e| {
+                SttError::inference(format!("Failed to create punctuation input: {}", e))
+            })
+        };
+        let mut inputs = ort::inputs![
+            "input_ids" => tensor(ids)?,
+            "attention_mask" => tensor(vec![1; len])?
+        ];
+        if self.token_type_ids {
+            inputs.push(("token_type_ids".into(), tensor(vec![0; len])?.into()));
+        }
+        let outputs = self.session.run(inputs).map_err(|e| {
+            SttError::inference(format!("Punctuation model inference failed: {}", e))
+        })?;
+
+        let argmax = |name: &str, labels: usize| -> Result<Vec<usize>> {
+            let (shape, data) = outputs[name]
+                .try_extract_tensor::<f32>()
+                .map_err(|e| SttError::inference(format!("Failed to extract {}: {}", name, e)))?;
+            if shape.len() != 3 || shape[1] as usize != len || shape[2] as usize != labels {
+                return Err(SttError::inference(format!(
+                    "Expected {} of shape (1, {}, {}), got {:?}",
+                    name, len, labels, shape
+                )));
+            }
+            Ok(first_piece
+                .iter()
+                .map(|&t| {
+                    let row = &data[t * labels..(t + 1) * labels];
+                    (0..labels)
+                        .max_by(|&a, &b| row[a].total_cmp(&row[b]))
+                        .unwrap_or(0)
+                })
+                .collect())
+        };
+        let punct = argmax("punct_logits", self.punct_labels.len())?;
+        let capit = argmax("capit_logits", self.capit_labels.len())?;
+        Ok(punct.into_iter().zip(capit).collect())
+    }
+}
+
+/// Word without surrounding punctuation, lowercased
+fn bare_word(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+/// Spell a word with WordPiece (greedy longest match, `##` continuations);
+/// a word that can't be spelled is `[UNK]`
+fn wordpiece(word: &str, vocab: &HashMap<String, i64>, unk_id: i64) -> Vec<i64> {
+    let mut ids = Vec::new();
+    let mut rest = word;
+    let mut continuation = false;
+    while !rest.is_empty() {
+        let found = rest
+            .char_indices()
+            .map(|(i, c)| i + c.len_utf8())
+            .rev()
+            .find_map(|end| {
+                let piece = if continuation {
+                    format!("##{}", &rest[..end])
+                } else {
+                    rest[..end].to_string()
+                };
+                vocab.get(&piece).map(|&id| (end, id))
+            });
+        match found {
+            Some((end, id)) => {
+                ids.push(id);
+                rest = &rest[end..];
+                continuation = true;
+            }
+            None => return vec![unk_id],
+        }
+    }
+    ids
+}
+
+/// Word with its predicted capitalization and trailing punctuation
+fn label_word(word: &str, punct: &str, capit: &str) -> String {
+    let mut out = if capit == CAPITALIZE {
+        let mut chars = word.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    } else {
+        word.to_string()
+    };
+    if punct != NO_LABEL {
+        out.push_str(punct);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wordpiece_longest_match() {
+        let vocab: HashMap<String, i64> = [("un", 1), ("##aff", 2), ("##able", 3), ("able", 4)]
+            .into_iter()
+            .map(|(piece, id)| (piece.to_string(), id))
+            .collect();
+        assert_eq!(wordpiece("unaffable", &vocab, 0), vec![1, 2, 3]);
+        assert_eq!(wordpiece("able", &vocab, 0), vec![4]);
+        // No piece for the rest: the whole word is unknown
+        assert_eq!(wordpiece("unx", &vocab, 0), vec![0]);
+    }
+
+    #[test]
+    fn test_recognizer_punctuation_is_dropped() {
+        assert_eq!(bare_word("Hello,"), "hello");
+        assert_eq!(bare_word("period."), "period");
+        assert_eq!(bare_word("don't"), "don't");
+        assert_eq!(bare_word("--"), "");
+    }
+
+    #[test]
+    fn test_label_word() {
+        assert_eq!(label_word("hello", ",", "U"), "Hello,");
+        assert_eq!(label_word("world", "?", "O"), "world?");
+        assert_eq!(label_word("élan", "O", "U"), "Élan");
+    }
+}
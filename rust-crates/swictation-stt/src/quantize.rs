@@ -0,0 +1,788 @@
+//! INT8 quantization of local Parakeet models
+//!
+//! Rewrites every `MatMul` whose weight is a 2-D FP32 initializer into
+//! ONNX Runtime's dynamic quantization pattern:
+//!
+//! ```text
+//! A -> DynamicQuantizeLinear -> MatMulInteger(W int8) -> Cast -> Mul(A scale * W scale) -> Y
+//! ```
+//!
+//! Weights are quantized symmetrically per output column; activations are
+//! quantized per tensor at run time. Conformer encoders have a few layers
+//! whose activations have large outliers and lose too much precision that
+//! way, so the encoder can first be calibrated on recorded speech: each
+//! `MatMul` input's signal-to-quantization-noise ratio is measured and the
+//! layers below [`QuantizeOptions::min_sqnr_db`] stay in FP32.
+//!
+//! The result is written next to the source as `{name}.int8.onnx`, which
+//! [`crate::OrtRecognizer`] already picks up for CPU inference. Weights the
+//! rewrite leaves alone keep pointing at the original external data file.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use ort::session::{builder::GraphOptimizationLevel, Session};
+use ort::value::Tensor;
+use tracing::{info, warn};
+
+use crate::audio::AudioProcessor;
+use crate::error::{Result, SttError};
+use crate::manifest::{self, FeatureLayout, ModelManifest};
+use crate::onnx_proto::{self, data_type, field, Message, Value};
+use crate::recognizer_ort::input_shape;
+
+/// Default SQNR below which a calibrated encoder layer stays in FP32
+pub const DEFAULT_MIN_SQNR_DB: f32 = 30.0;
+
+/// Longest stretch of each recording fed to the encoder during calibration
+/// (10 ms frames)
+const CALIBRATION_FRAMES: usize = 1500;
+
+/// Lowest default-domain opset with `DynamicQuantizeLinear`
+const MIN_OPSET: u64 = 11;
+
+/// Parakeet model files, in loading order
+const MODEL_FILES: [&str; 3] = ["encoder", "decoder", "joiner"];
+
+/// How to quantize a model directory
+#[derive(Debug, Clone)]
+pub struct QuantizeOptions {
+    /// 16 kHz recordings to calibrate the encoder on; empty quantizes every layer
+    pub calibration: Vec<PathBuf>,
+    /// Layers whose activations measure below this SQNR (dB) stay in FP32
+    pub min_sqnr_db: f32,
+}
+
+impl Default for QuantizeOptions {
+    fn default() -> Self {
+        Self {
+            calibration: Vec::new(),
+            min_sqnr_db: DEFAULT_MIN_SQNR_DB,
+        }
+    }
+}
+
+/// Measured quantization quality of one `MatMul` input
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerSensitivity {
+    /// Node name, or its output when the node is unnamed
+    pub layer: String,
+    /// Mean SQNR over the calibration runs
+    pub sqnr_db: f32,
+}
+
+/// Outcome for one model file
+#[derive(Debug, Clone)]
+pub struct QuantizedFile {
+    pub source: PathBuf,
+    /// `None` when the file had no quantizable `MatMul`
+    pub output: Option<PathBuf>,
+    pub quantized: usize,
+    /// Layers left in FP32 by calibration
+    pub kept: Vec<LayerSensitivity>,
+    /// Size of the rewritten weights before and after
+    pub fp32_bytes: u64,
+    pub int8_bytes: u64,
+}
+
+/// Quantize the FP32 encoder, decoder and joiner in `model_dir`
+pub fn quantize_model(model_dir: &Path, options: &QuantizeOptions) -> Result<Vec<QuantizedFile>> {
+    let mut results = Vec::new();
+    for name in MODEL_FILES {
+        let source = model_dir.join(format!("{}.onnx", name));
+        if !source.exists() {
+            return Err(SttError::model_load(format!(
+                "No FP32 {}.onnx in {} - quantization needs the full-precision model",
+                name,
+                model_dir.display()
+            )));
+        }
+
+        let sensitivities = if name == "encoder" && !options.calibration.is_empty() {
+            calibrate_encoder(model_dir, &source, &options.calibration)?
+        } else {
+            Vec::new()
+        };
+        let kept: Vec<LayerSensitivity> = sensitivities
+            .into_iter()
+            .filter(|s| s.sqnr_db < options.min_sqnr_db)
+            .collect();
+        let keep: HashSet<&str> = kept.iter().map(|s| s.layer.as_str()).collect();
+
+        info!("Quantizing {}", source.display());
+        let bytes = fs::read(&source)
+            .map_err(|e| SttError::model_load(format!("Failed to read {}: {}", name, e)))?;
+        let (rewritten, stats) = quantize_bytes(&bytes, model_dir, &keep)?;
+
+        let output = if stats.quantized == 0 {
+            info!("{} has no quantizable MatMul, keeping FP32", name);
+            None
+        } else {
+            let output = model_dir.join(format!("{}.int8.onnx", name));
+            let partial = output.with_extension("onnx.part");
+            fs::write(&partial, &rewritten)
+                .and_then(|()| fs::rename(&partial, &output))
+                .map_err(|e| {
+                    SttError::model_load(format!("Failed to write {}: {}", output.display(), e))
+                })?;
+            Some(output)
+        };
+        results.push(QuantizedFile {
+            source,
+            output,
+            quantized: stats.quantized,
+            kept,
+            fp32_bytes: stats.fp32_bytes,
+            int8_bytes: stats.int8_bytes,
+        });
+    }
+    Ok(results)
+}
+
+#[derive(Debug, Default)]
+struct RewriteStats {
+    quantized: usize,
+    fp32_bytes: u64,
+    int8_bytes: u64,
+}
+
+/// A `MatMul` with a constant 2-D FP32 weight
+struct Candidate {
+    node: usize,
+    layer: String,
+    activation: String,
+    weight: String,
+    output: String,
+}
+
+/// Name used for a node in reports and in the calibration keep-list
+fn layer_name(node: &Message) -> String {
+    match node.string(field::node::NAME) {
+        Some(name) if !name.is_empty() => name.to_string(),
+        _ => node
+            .string(field::node::OUTPUT)
+            .unwrap_or_default()
+            .to_string(),
+    }
+}
+
+fn dims(tensor: &Message) -> Result<Vec<usize>> {
+    Ok(tensor
+        .varints(field::tensor::DIMS)?
+        .into_iter()
+        .map(|d| d as usize)
+        .collect())
+}
+
+fn candidates(
+    nodes: &[Message],
+    initializers: &HashMap<String, Message>,
+) -> Result<Vec<Candidate>> {
+    let mut found = Vec::new();
+    for (index, node) in nodes.iter().enumerate() {
+        let domain = node.string(field::node::DOMAIN).unwrap_or_default();
+        if node.string(field::node::OP_TYPE) != Some("MatMul") || !domain.is_empty() {
+            continue;
+        }
+        let inputs: Vec<&str> = node.strings(field::node::INPUT).collect();
+        let (Some(output), [activation, weight]) = (node.string(field::node::OUTPUT), &inputs[..])
+        else {
+            continue;
+        };
+        let Some(tensor) = initializers.get(*weight) else {
+            continue;
+        };
+        if initializers.contains_key(*activation)
+            || tensor.varint(field::tensor::DATA_TYPE) != Some(data_type::FLOAT)
+            || dims(tensor)?.len() != 2
+        {
+            continue;
+        }
+        found.push(Candidate {
+            node: index,
+            layer: layer_name(node),
+            activation: activation.to_string(),
+            weight: weight.to_string(),
+            output: output.to_string(),
+        });
+    }
+    Ok(found)
+}
+
+/// Values of an FP32 initializer, wherever they are stored
+fn load_floats(tensor: &Message, model_dir: &Path) -> Result<Vec<f32>> {
+    let name = tensor.string(field::tensor::NAME).unwrap_or_default();
+    let count: usize = dims(tensor)?.iter().product();
+    let external = tensor.messages(field::tensor::EXTERNAL_DATA)?;
+
+    let bytes = if tensor.varint(field::tensor::DATA_LOCATION)
+        == Some(onnx_proto::DATA_LOCATION_EXTERNAL)
+        || !external.is_empty()
+    {
+        let entry = |key: &str| {
+            external
+                .iter()
+                .find(|e| e.string(field::entry::KEY) == Some(key))
+                .and_then(|e| e.string(field::entry::VALUE))
+        };
+        let location = entry("location").ok_or_else(|| {
+            SttError::model_load(format!("External tensor {} has no location", name))
+        })?;
+        let offset = entry("offset").and_then(|v| v.parse().ok()).unwrap_or(0);
+        let length = entry("length")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(count * 4);
+        let path = model_dir.join(location);
+        let mut bytes = vec![0u8; length];
+        fs::File::open(&path)
+            .and_then(|mut file| {
+                file.seek(SeekFrom::Start(offset))?;
+                file.read_exact(&mut bytes)
+            })
+            .map_err(|e| {
+                SttError::model_load(format!(
+                    "Failed to read {} from {}: {}",
+                    name,
+                    path.display(),
+                    e
+                ))
+            })?;
+        bytes
+    } else if let Some(raw) = tensor.bytes(field::tensor::RAW_DATA).next() {
+        raw.to_vec()
+    } else {
+        let mut bytes = Vec::with_capacity(count * 4);
+        for f in tensor
+            .fields
+            .iter()
+            .filter(|f| f.number == field::tensor::FLOAT_DATA)
+        {
+            match &f.value {
+                Value::Bytes(packed) => bytes.extend_from_slice(packed),
+                Value::Fixed32(v) => bytes.extend_from_slice(&v.to_le_bytes()),
+                _ => {}
+            }
+        }
+        bytes
+    };
+
+    if bytes.len() != count * 4 {
+        return Err(SttError::model_load(format!(
+            "Tensor {} has {} bytes of data for {} floats",
+            name,
+            bytes.len(),
+            count
+        )));
+    }
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect())
+}
+
+/// Symmetric per-column INT8 quantization of a row-major `(rows, cols)` matrix
+///
+/// Returns the quantized values and one scale per column.
+fn quantize_columns(weights: &[f32], rows: usize, cols: usize) -> (Vec<i8>, Vec<f32>) {
+    let mut scales = vec![0.0f32; cols];
+    for row in weights.chunks_exact(cols).take(rows) {
+        for (scale, &w) in scales.iter_mut().zip(row) {
+            *scale = scale.max(w.abs());
+        }
+    }
+    for scale in &mut scales {
+        *scale = if *scale > 0.0 { *scale / 127.0 } else { 1.0 };
+    }
+    let values = weights
+        .iter()
+        .enumerate()
+        .map(|(i, &w)| (w / scales[i % cols]).round().clamp(-127.0, 127.0) as i8)
+        .collect();
+    (values, scales)
+}
+
+fn default_opset(model: &Message) -> Result<u64> {
+    Ok(model
+        .messages(field::model::OPSET_IMPORT)?
+        .iter()
+        .filter(|o| {
+            o.string(field::opset::DOMAIN)
+                .unwrap_or_default()
+                .is_empty()
+        })
+        .filter_map(|o| o.varint(field::opset::VERSION))
+        .max()
+        .unwrap_or(0))
+}
+
+fn graph_of(model: &Message) -> Result<Message> {
+    model
+        .messages(field::model::GRAPH)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| SttError::model_load("ONNX model has no graph"))
+}
+
+fn initializer_map(graph: &Message) -> Result<HashMap<String, Message>> {
+    Ok(graph
+        .messages(field::graph::INITIALIZER)?
+        .into_iter()
+        .map(|t| {
+            let name = t
+                .string(field::tensor::NAME)
+                .unwrap_or_default()
+                .to_string();
+            (name, t)
+        })
+        .collect())
+}
+
+/// Rewrite the `MatMul`s of a serialized model, except the layers in `keep`
+fn quantize_bytes(
+    bytes: &[u8],
+    model_dir: &Path,
+    keep: &HashSet<&str>,
+) -> Result<(Vec<u8>, RewriteStats)> {
+    let mut model = Message::decode(bytes)?;
+    let opset = default_opset(&model)?;
+    if opset < MIN_OPSET {
+        return Err(SttError::model_load(format!(
+            "Model uses opset {}, INT8 quantization needs {} or newer",
+            opset, MIN_OPSET
+        )));
+    }
+    let mut graph = graph_of(&model)?;
+    let nodes = graph.messages(field::graph::NODE)?;
+    let initializers = initializer_map(&graph)?;
+
+    let mut stats = RewriteStats::default();
+    let mut replaced: HashMap<usize, Vec<Message>> = HashMap::new();
+    let mut new_initializers = Vec::new();
+    let mut quantized_weights = HashSet::new();
+    let mut quantized_activations = HashSet::new();
+    for candidate in candidates(&nodes, &initializers)? {
+        if keep.contains(candidate.layer.as_str()) {
+            continue;
+        }
+        let (a, w, y) = (&candidate.activation, &candidate.weight, &candidate.output);
+        let tensor = &initializers[w];
+        let shape = dims(tensor)?;
+        let (rows, cols) = (shape[0], shape[1]);
+
+        if quantized_weights.insert(w.clone()) {
+            let weights = load_floats(tensor, model_dir)?;
+            let (values, scales) = quantize_columns(&weights, rows, cols);
+            stats.fp32_bytes += (rows * cols * 4) as u64;
+            stats.int8_bytes += (rows * cols + cols * 5) as u64;
+            new_initializers.push(onnx_proto::tensor(
+                &format!("{}_quantized", w),
+                data_type::INT8,
+                &[rows, cols],
+                values.iter().map(|&v| v as u8).collect(),
+            ));
+            new_initializers.push(onnx_proto::tensor(
+                &format!("{}_scale", w),
+                data_type::FLOAT,
+                &[cols],
+                scales.iter().flat_map(|s| s.to_le_bytes()).collect(),
+            ));
+            new_initializers.push(onnx_proto::tensor(
+                &format!("{}_zero_point", w),
+                data_type::INT8,
+                &[cols],
+                vec![0; cols],
+            ));
+        }
+
+        let mut replacement = Vec::new();
+        if quantized_activations.insert(a.clone()) {
+            replacement.push(onnx_proto::node(
+                "DynamicQuantizeLinear",
+                &format!("{}_DynamicQuantizeLinear", a),
+                &[a],
+                &[
+                    &format!("{}_quantized", a),
+                    &format!("{}_scale", a),
+                    &format!("{}_zero_point", a),
+                ],
+            ));
+        }
+        let layer = &candidate.layer;
+        replacement.push(onnx_proto::node(
+            "MatMulInteger",
+            &format!("{}_MatMulInteger", layer),
+            &[
+                &format!("{}_quantized", a),
+                &format!("{}_quantized", w),
+                &format!("{}_zero_point", a),
+                &format!("{}_zero_point", w),
+            ],
+            &[&format!("{}_int32", y)],
+        ));
+        let mut cast = onnx_proto::node(
+            "Cast",
+            &format!("{}_Cast", layer),
+            &[&format!("{}_int32", y)],
+            &[&format!("{}_float", y)],
+        );
+        cast.push_message(
+            field::node::ATTRIBUTE,
+            &onnx_proto::int_attribute("to", data_type::FLOAT as i64),
+        );
+        replacement.push(cast);
+        replacement.push(onnx_proto::node(
+            "Mul",
+            &format!("{}_scale_Mul", layer),
+            &[&format!("{}_scale", a), &format!("{}_scale", w)],
+            &[&format!("{}_scale", y)],
+        ));
+        replacement.push(onnx_proto::node(
+            "Mul",
+            &format!("{}_output_Mul", layer),
+            &[&format!("{}_float", y), &format!("{}_scale", y)],
+            &[y],
+        ));
+        replaced.insert(candidate.node, replacement);
+        stats.quantized += 1;
+    }
+
+    let nodes: Vec<Message> = nodes
+        .into_iter()
+        .enumerate()
+        .flat_map(|(i, node)| replaced.remove(&i).unwrap_or_else(|| vec![node]))
+        .collect();
+
+    // FP32 weights nothing reads any more can go. Control-flow subgraphs may
+    // read outer initializers by name, so with those everything stays.
+    let has_subgraphs = nodes.iter().any(|node| {
+        node.messages(field::node::ATTRIBUTE)
+            .unwrap_or_default()
+            .iter()
+            .any(|a| {
+                a.bytes(field::attribute::G).next().is_some()
+                    || a.bytes(field::attribute::GRAPHS).next().is_some()
+            })
+    });
+    let mut used: HashSet<&str> = nodes
+        .iter()
+        .flat_map(|n| n.strings(field::node::INPUT))
+        .collect();
+    let outputs = graph.messages(field::graph::OUTPUT)?;
+    used.extend(
+        outputs
+            .iter()
+            .filter_map(|o| o.string(field::value_info::NAME)),
+    );
+    let unused: HashSet<&str> = quantized_weights
+        .iter()
+        .map(String::as_str)
+        .filter(|w| !has_subgraphs && !used.contains(w))
+        .collect();
+
+    let mut kept_initializers: Vec<Message> = graph
+        .messages(field::graph::INITIALIZER)?
+        .into_iter()
+        .filter(|t| !unused.contains(t.string(field::tensor::NAME).unwrap_or_default()))
+        .collect();
+    kept_initializers.extend(new_initializers);
+    let inputs: Vec<Message> = graph
+        .messages(field::graph::INPUT)?
+        .into_iter()
+        .filter(|i| !unused.contains(i.string(field::value_info::NAME).unwrap_or_default()))
+        .collect();
+
+    graph.set_messages(field::graph::NODE, &nodes);
+    graph.set_messages(field::graph::INITIALIZER, &kept_initializers);
+    graph.set_messages(field::graph::INPUT, &inputs);
+    model.set_messages(field::model::GRAPH, &[graph]);
+
+    let encoded = model.encode();
+    if encoded.len() > i32::MAX as usize {
+        return Err(SttError::model_load(
+            "Quantized model exceeds the 2 GB protobuf limit",
+        ));
+    }
+    Ok((encoded, stats))
+}
+
+/// Signal-to-quantization-noise ratio of dynamic per-tensor UINT8 quantization
+///
+/// `DynamicQuantizeLinear` maps `[min(0, min), max(0, max)]` onto 256 steps,
+/// so the noise is that of a uniform quantizer with that step. Returns
+/// `None` for an all-zero tensor.
+fn activation_sqnr_db(values: &[f32]) -> Option<f32> {
+    if values.is_empty() {
+        return None;
+    }
+    let (min, max) = values
+        .iter()
+        .fold((0.0f32, 0.0f32), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+    let rms = (values.iter().map(|&v| (v as f64).powi(2)).sum::<f64>() / values.len() as f64).sqrt()
+        as f32;
+    if rms == 0.0 || max == min {
+        return None;
+    }
+    let noise = (max - min) / 255.0 / 12f32.sqrt();
+    Some(20.0 * (rms / noise).log10())
+}
+
+/// Removes the temporary calibration model and restores the working
+/// directory, however calibration ends
+struct CalibrationGuard {
+    model: PathBuf,
+    original_dir: PathBuf,
+}
+
+impl Drop for CalibrationGuard {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.model);
+        let _ = std::env::set_current_dir(&self.original_dir);
+    }
+}
+
+/// Measure each encoder `MatMul` input's SQNR on `recordings`, worst first
+///
+/// The activations are exposed as extra graph outputs of a temporary copy of
+/// the encoder, which runs on the CPU.
+pub fn calibrate_encoder(
+    model_dir: &Path,
+    encoder: &Path,
+    recordings: &[PathBuf],
+) -> Result<Vec<LayerSensitivity>> {
+    let bytes = fs::read(encoder)
+        .map_err(|e| SttError::model_load(format!("Failed to read encoder: {}", e)))?;
+    let mut model = Message::decode(&bytes)?;
+    let mut graph = graph_of(&model)?;
+    let nodes = graph.messages(field::graph::NODE)?;
+    let layers = candidates(&nodes, &initializer_map(&graph)?)?;
+
+    let outputs = graph.messages(field::graph::OUTPUT)?;
+    let existing: HashSet<&str> = outputs
+        .iter()
+        .filter_map(|o| o.string(field::value_info::NAME))
+        .collect();
+    let mut probed = HashSet::new();
+    for layer in &layers {
+        if !existing.contains(layer.activation.as_str()) && probed.insert(&layer.activation) {
+            graph.push_message(
+                field::graph::OUTPUT,
+                &onnx_proto::float_value_info(&layer.activation),
+            );
+        }
+    }
+    model.set_messages(field::model::GRAPH, &[graph]);
+
+    // Next to the encoder so relative external data paths still resolve
+    let original_dir = std::env::current_dir()
+        .map_err(|e| SttError::model_load(format!("Failed to get current directory: {}", e)))?;
+    let guard = CalibrationGuard {
+        model: model_dir.join(".encoder.calibration.onnx"),
+        original_dir,
+    };
+    fs::write(&guard.model, model.encode())
+        .map_err(|e| SttError::model_load(format!("Failed to write calibration model: {}", e)))?;
+    std::env::set_current_dir(model_dir).map_err(|e| {
+        SttError::model_load(format!(
+            "Failed to change to model directory {}: {}",
+            model_dir.display(),
+            e
+        ))
+    })?;
+    let mut session = Session::builder()
+        .map_err(|e| SttError::model_load(format!("Failed to create session builder: {}", e)))?
+        .with_optimization_level(GraphOptimizationLevel::Level1)
+        .map_err(|e| SttError::model_load(format!("Failed to set optimization level: {}", e)))?
+        .commit_from_file(&guard.model)
+        .map_err(|e| SttError::model_load(format!("Failed to load calibration model: {}", e)))?;
+    drop(guard);
+
+    let manifest = ModelManifest::load(model_dir)?;
+    let format = manifest::resolve(
+        manifest.as_ref(),
+        &input_shape(&session, "audio_signal"),
+        &[],
+    )?;
+    let mut processor = AudioProcessor::with_mel_features(format.mel_features)?;
+
+    let mut totals: HashMap<&str, (f64, usize)> = HashMap::new();
+    let mut runs = 0;
+    for path in recordings {
+        let features = match processor
+            .load_audio(path)
+            .and_then(|samples| processor.extract_mel_features(&samples))
+        {
+            Ok(features) => features,
+            Err(e) => {
+                warn!("Skipping {} for calibration: {}", path.display(), e);
+                continue;
+            }
+        };
+        let frames = features.nrows().min(CALIBRATION_FRAMES);
+        if frames == 0 {
+            continue;
+        }
+        let features = features.slice(ndarray::s![..frames, ..]);
+        let mels = features.ncols();
+        let (shape, data): (Vec<usize>, Vec<f32>) = match format.feature_layout {
+            FeatureLayout::FeaturesFirst => (
+                vec![1, mels, frames],
+                features.t().iter().copied().collect(),
+            ),
+            FeatureLayout::TimeFirst => (vec![1, frames, mels], features.iter().copied().collect()),
+        };
+        let audio_signal = Tensor::from_array((shape, data.into_boxed_slice()))
+            .map_err(|e| SttError::inference(format!("Failed to create audio tensor: {}", e)))?;
+        let length = Tensor::from_array((vec![1], vec![frames as i64].into_boxed_slice()))
+            .map_err(|e| SttError::inference(format!("Failed to create length tensor: {}", e)))?;
+        let outputs = session
+            .run(ort::inputs!["audio_signal" => audio_signal, "length" => length])
+            .map_err(|e| SttError::inference(format!("Calibration run failed: {}", e)))?;
+
+        for layer in &layers {
+            let Some(value) = outputs.get(layer.activation.as_str()) else {
+                continue;
+            };
+            let Ok((_, values)) = value.try_extract_tensor::<f32>() else {
+                continue;
+            };
+            if let Some(sqnr) = activation_sqnr_db(values) {
+                let total = totals.entry(layer.layer.as_str()).or_default();
+                total.0 += f64::from(sqnr);
+                total.1 += 1;
+            }
+        }
+        runs += 1;
+    }
+    if runs == 0 {
+        return Err(SttError::invalid_input(
+            "None of the calibration recordings could be used",
+        ));
+    }
+    info!(
+        "Calibrated {} encoder layers on {} recordings",
+        totals.len(),
+        runs
+    );
+
+    let mut sensitivities: Vec<LayerSensitivity> = totals
+        .into_iter()
+        .map(|(layer, (sum, count))| LayerSensitivity {
+            layer: layer.to_string(),
+            sqnr_db: (sum / count as f64) as f32,
+        })
+        .collect();
+    sensitivities.sort_by(|a, b| a.sqnr_db.total_cmp(&b.sqnr_db));
+    Ok(sensitivities)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// y = x @ w with a 2x3 weight, in opset 13
+    fn tiny_model(weights: &[f32]) -> Vec<u8> {
+        let mut graph = Message::default();
+        graph.push_message(
+            field::graph::NODE,
+            &onnx_proto::node("MatMul", "proj", &["x", "w"], &["y"]),
+        );
+        graph.push_message(
+            field::graph::INITIALIZER,
+            &onnx_proto::tensor(
+                "w",
+                data_type::FLOAT,
+                &[2, 3],
+                weights.iter().flat_map(|w| w.to_le_bytes()).collect(),
+            ),
+        );
+        graph.push_message(field::graph::INPUT, &onnx_proto::float_value_info("x"));
+        graph.push_message(field::graph::INPUT, &onnx_proto::float_value_info("w"));
+        graph.push_message(field::graph::OUTPUT, &onnx_proto::float_value_info("y"));
+
+        let mut opset = Message::default();
+        opset.push_varint(field::opset::VERSION, 13);
+        let mut model = Message::default();
+        model.push_message(field::model::GRAPH, &graph);
+        model.push_message(field::model::OPSET_IMPORT, &opset);
+        model.encode()
+    }
+
+    #[test]
+    fn test_per_column_quantization() {
+        let (values, scales) = quantize_columns(&[1.0, -0.5, 0.0, -2.0, 0.25, 0.0], 2, 3);
+        assert_eq!(scales, [2.0 / 127.0, 0.5 / 127.0, 1.0]);
+        assert_eq!(values, [64, -127, 0, -127, 64, 0]);
+    }
+
+    #[test]
+    fn test_matmul_rewrite() {
+        let weights = [1.0, -0.5, 0.0, -2.0, 0.25, 0.0];
+        let (bytes, stats) =
+            quantize_bytes(&tiny_model(&weights), Path::new("."), &HashSet::new()).unwrap();
+        assert_eq!(stats.quantized, 1);
+        assert_eq!(stats.fp32_bytes, 24);
+
+        let model = Message::decode(&bytes).unwrap();
+        let graph = graph_of(&model).unwrap();
+        let ops: Vec<String> = graph
+            .messages(field::graph::NODE)
+            .unwrap()
+            .iter()
+            .map(|n| n.string(field::node::OP_TYPE).unwrap().to_string())
+            .collect();
+        assert_eq!(
+            ops,
+            [
+                "DynamicQuantizeLinear",
+                "MatMulInteger",
+                "Cast",
+                "Mul",
+                "Mul"
+            ]
+        );
+        let initializers = initializer_map(&graph).unwrap();
+        assert!(!initializers.contains_key("w"));
+        assert_eq!(
+            initializers["w_quantized"].varint(field::tensor::DATA_TYPE),
+            Some(data_type::INT8)
+        );
+        // The FP32 weight was also a graph input; that goes with it
+        let inputs = graph.messages(field::graph::INPUT).unwrap();
+        assert_eq!(inputs.len(), 1);
+    }
+
+    #[test]
+    fn test_calibrated_layers_stay_fp32() {
+        let keep = HashSet::from(["proj"]);
+        let (bytes, stats) = quantize_bytes(&tiny_model(&[1.0; 6]), Path::new("."), &keep).unwrap();
+        assert_eq!(stats.quantized, 0);
+        let graph = graph_of(&Message::decode(&bytes).unwrap()).unwrap();
+        assert!(initializer_map(&graph).unwrap().contains_key("w"));
+    }
+
+    #[test]
+    fn test_old_opset_is_rejected() {
+        let mut model = Message::decode(&tiny_model(&[1.0; 6])).unwrap();
+        let mut opset = Message::default();
+        opset.push_varint(field::opset::VERSION, 9);
+        model.set_messages(field::model::OPSET_IMPORT, &[opset]);
+        assert!(quantize_bytes(&model.encode(), Path::new("."), &HashSet::new()).is_err());
+    }
+
+    #[test]
+    fn test_activation_sqnr() {
+        // A uniform signal over the full range quantizes at ~6 dB per bit
+        let uniform: Vec<f32> = (-1000..=1000).map(|i| i as f32 / 1000.0).collect();
+        let sqnr = activation_sqnr_db(&uniform).unwrap();
+        assert!((sqnr - 48.1).abs() < 0.5, "sqnr {}", sqnr);
+
+        // One large outlier stretches the range and costs precision
+        let mut outlier = uniform.clone();
+        outlier[0] = 100.0;
+        assert!(activation_sqnr_db(&outlier).unwrap() < sqnr - 15.0);
+
+        assert_eq!(activation_sqnr_db(&[0.0; 8]), None);
+    }
+}
@@ -17,85 +17,355 @@
 //! export ORT_DYLIB_PATH=$(python3 -c "import onnxruntime; import os; print(os.path.join(os.path.dirname(onnxruntime.__file__), 'capi/libonnxruntime.so.1.23.2'))")
 //! ```
 
-use crate::audio::AudioProcessor;
+use crate::audio::{AudioProcessor, SAMPLE_RATE, WIN_LENGTH};
+use crate::beam::{self, BeamConfig, BeamHypothesis, Candidate, Decoding, TdtConfig};
+use crate::biasing::ContextBias;
+use crate::cache::{self, CachedRecognition, RecognitionCache};
+use crate::confidence::{self, WordConfidence};
 use crate::error::{Result, SttError};
-use ndarray::{s, Array1, Array2, Array3};
+use crate::fusion::ShallowFusion;
+use crate::lexicon::Lexicon;
+use crate::longform;
+use crate::manifest::{self, FeatureLayout, ModelFormat, ModelManifest};
+use crate::nbest::{self, Emission, Hypothesis};
+use crate::pinned::PinnedBinding;
+use crate::stream::{StreamState, DEFAULT_PARTIAL_INTERVAL};
+use crate::window::{self, Window};
+use ndarray::{s, Array1, Array2, Array3, Axis};
 #[cfg(target_os = "macos")]
 use ort::execution_providers::coreml::{CoreMLComputeUnits, CoreMLModelFormat};
 use ort::{
     execution_providers as ep,
-    session::{builder::GraphOptimizationLevel, Session},
+    session::{builder::GraphOptimizationLevel, RunOptions, Session},
     value::Tensor,
 };
+use rayon::prelude::*;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tracing::{debug, info, warn};
 
+/// Encoder windows run together in one encoder session run
+pub const MAX_ENCODER_BATCH: usize = 16;
+
 /// Decoder state returned by decode_frames_with_state
 /// Format: (tokens, final_decoder_token, final_decoder_out, (blank_count, nonblank_count))
 type DecoderState = (Vec<i64>, i64, Array1<f32>, (usize, usize));
 
-/// Model configuration for different Parakeet-TDT variants
-#[derive(Debug, Clone, Copy)]
-struct ModelConfig {
-    /// Decoder hidden state size (512 for 0.6B, 640 for 1.1B)
-    decoder_hidden_size: usize,
-    /// Number of mel features (128 for 0.6B, 80 for 1.1B)
-    n_mel_features: usize,
-    /// Whether encoder expects transposed input
-    transpose_input: bool,
-    /// Model variant name for logging
-    model_name: &'static str,
-}
-
-impl ModelConfig {
-    fn for_0_6b() -> Self {
-        Self {
-            decoder_hidden_size: 640, // Both 0.6B and 1.1B use 640 hidden size
-            n_mel_features: 128,
-            transpose_input: true,
-            model_name: "Parakeet-TDT-0.6B",
-        }
-    }
-
-    fn for_1_1b() -> Self {
-        Self {
-            decoder_hidden_size: 640,
-            n_mel_features: 80,
-            transpose_input: false,
-            model_name: "Parakeet-TDT-1.1B",
-        }
-    }
-
-    /// Auto-detect from model path
-    fn detect_from_path(path: &Path) -> Self {
-        let path_str = path.to_string_lossy();
-        if path_str.contains("1.1b") || path_str.contains("1-1b") {
-            Self::for_1_1b()
-        } else {
-            // Default to 0.6B (more common, lower VRAM)
-            Self::for_0_6b()
-        }
-    }
-}
-
 /// Direct ONNX Runtime recognizer for Parakeet-TDT models (0.6B and 1.1B)
 pub struct OrtRecognizer {
     encoder: Session,
+    // Further encoder sessions for parallel window encoding (none by default)
+    extra_encoders: Vec<Session>,
     decoder: Session,
     joiner: Session,
     tokens: Vec<String>,
     blank_id: i64,
     unk_id: i64,
     model_path: PathBuf,
+    encoder_path: PathBuf,
     audio_processor: AudioProcessor,
     // Decoder RNN states - size depends on model variant (512 for 0.6B, 640 for 1.1B)
     decoder_state1: Option<Array3<f32>>,
     decoder_state2: Option<Array3<f32>>,
-    // Model configuration (determines hidden sizes, mel features, etc.)
-    config: ModelConfig,
-    // GPU mode flag
-    use_gpu: bool,
+    // Model format (determines hidden sizes, mel features, input layout)
+    format: ModelFormat,
+    // Where the sessions run (CPU, platform GPU or OpenVINO)
+    target: ExecutionTarget,
+    // Weight precision of the loaded encoder ("fp32", "fp16" or "int8")
+    precision: &'static str,
+    // Optional language model fused into decoding
+    fusion: Option<ShallowFusion>,
+    // Greedy or beam search
+    decoding: Decoding,
+    // Step rules of both searches
+    tdt: TdtConfig,
+    // Optional boost for user phrases (hotwords)
+    bias: Option<ContextBias>,
+    // Optional out-of-vocabulary words spelled in pieces
+    lexicon: Option<Lexicon>,
+    // Emissions of the last decode with their runner-up tokens (for n-best)
+    emissions: Vec<Emission>,
+    // Shared by every session run so a watchdog can terminate inference
+    run_options: Arc<RunOptions>,
+    // Results for repeated identical audio (off unless enabled)
+    cache: Option<RecognitionCache>,
+    // Audio of the streaming recognition in progress, if any
+    stream: Option<StreamState>,
+    // Pinned-memory IO bindings on CUDA and ROCm (see [`crate::pinned`])
+    bindings: Option<SessionBindings>,
+}
+
+/// One [`PinnedBinding`] per session of an [`OrtRecognizer`]
+struct SessionBindings {
+    encoder: PinnedBinding,
+    decoder: PinnedBinding,
+    joiner: PinnedBinding,
+}
+
+impl SessionBindings {
+    /// Bindings for GPU sessions whose provider has pinned memory
+    fn new(
+        encoder: &Session,
+        decoder: &Session,
+        joiner: &Session,
+        provider: &str,
+    ) -> ort::Result<Option<Self>> {
+        let (Some(encoder), Some(decoder), Some(joiner)) = (
+            PinnedBinding::new(encoder, provider)?,
+            PinnedBinding::new(decoder, provider)?,
+            PinnedBinding::new(joiner, provider)?,
+        ) else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            encoder,
+            decoder,
+            joiner,
+        }))
+    }
+}
+
+/// Session options for the encoder on `target`, with `intra_threads`
+/// threads for CPU work
+fn encoder_session_builder(
+    target: &ExecutionTarget,
+    intra_threads: usize,
+) -> Result<ort::session::builder::SessionBuilder> {
+    let use_gpu = *target == ExecutionTarget::Gpu;
+    let mut session_builder = Session::builder()
+        .map_err(|e| SttError::ModelLoadError(format!("Failed to create session builder: {}", e)))?
+        .with_optimization_level(GraphOptimizationLevel::Level3)
+        .map_err(|e| SttError::ModelLoadError(format!("Failed to set optimization level: {}", e)))?
+        .with_intra_threads(intra_threads)
+        .map_err(|e| SttError::ModelLoadError(format!("Failed to set intra threads: {}", e)))?;
+
+    if let ExecutionTarget::OpenVino(device) = target {
+        info!("Enabling OpenVINO execution provider ({})", device);
+        session_builder = with_openvino(session_builder, device).map_err(|e| {
+            SttError::ModelLoadError(format!("Failed to set OpenVINO execution providers: {}", e))
+        })?;
+    } else if use_gpu {
+        // macOS: Use CoreML execution provider (internally uses Metal/GPU)
+        #[cfg(target_os = "macos")]
+        {
+            info!("Enabling CoreML execution provider (Apple Silicon GPU acceleration)");
+            session_builder = session_builder
+                .with_execution_providers([
+                    ep::CoreMLExecutionProvider::default()
+                        // NeuralNetwork format avoids .mlpackage directory creation that conflicts
+                        // with ONNX external weights files (e.g., encoder.onnx + encoder.weights)
+                        .with_model_format(CoreMLModelFormat::NeuralNetwork)
+                        .with_compute_units(CoreMLComputeUnits::All) // CPU + GPU + ANE
+                        .build(),
+                    ep::CPUExecutionProvider::default().build(),
+                ])
+                .map_err(|e| {
+                    SttError::ModelLoadError(format!(
+                        "Failed to set CoreML execution providers: {}",
+                        e
+                    ))
+                })?;
+        }
+
+        // Linux: Use CUDA (NVIDIA) or ROCm (AMD) execution provider
+        #[cfg(target_os = "linux")]
+        {
+            info!("Enabling {} execution provider", linux_gpu_provider());
+            session_builder = with_linux_gpu(session_builder).map_err(|e| {
+                SttError::ModelLoadError(format!(
+                    "Failed to set {} execution providers: {}",
+                    linux_gpu_provider(),
+                    e
+                ))
+            })?;
+        }
+
+        // Windows: Use DirectML execution provider (any DirectX 12 GPU)
+        #[cfg(target_os = "windows")]
+        {
+            info!("Enabling DirectML execution provider");
+            session_builder = with_directml(session_builder).map_err(|e| {
+                SttError::ModelLoadError(format!(
+                    "Failed to set DirectML execution providers: {}",
+                    e
+                ))
+            })?;
+        }
+    } else {
+        info!("Using CPU execution provider");
+    }
+    Ok(session_builder)
+}
+
+/// Aborts the recognition running on an [`OrtRecognizer`] from another thread
+///
+/// The session run in progress fails with an inference error, and so does
+/// every run after it until the next `recognize_samples` call.
+#[derive(Clone)]
+pub struct CancelHandle(pub(crate) Arc<RunOptions>);
+
+impl CancelHandle {
+    pub fn cancel(&self) {
+        if let Err(e) = self.0.terminate() {
+            warn!("Failed to terminate inference: {}", e);
+        }
+    }
+}
+
+/// Register DirectML (any DirectX 12 GPU) ahead of CPU on Windows
+///
+/// DirectML doesn't support ONNX Runtime's memory pattern optimization or
+/// parallel execution, so both are turned off.
+#[cfg(target_os = "windows")]
+pub(crate) fn with_directml(
+    builder: ort::session::builder::SessionBuilder,
+) -> ort::Result<ort::session::builder::SessionBuilder> {
+    builder
+        .with_memory_pattern(false)?
+        .with_parallel_execution(false)?
+        .with_execution_providers([
+            ep::DirectMLExecutionProvider::default().build(),
+            ep::CPUExecutionProvider::default().build(),
+        ])
+}
+
+/// GPU execution provider on Linux: CUDA (NVIDIA), or ROCm (AMD) when the
+/// loaded ONNX Runtime was built with ROCm rather than CUDA
+#[cfg(target_os = "linux")]
+pub(crate) fn linux_gpu_provider() -> &'static str {
+    use ep::ExecutionProvider;
+
+    let cuda = ep::CUDAExecutionProvider::default()
+        .is_available()
+        .unwrap_or(false);
+    let rocm = ep::ROCmExecutionProvider::default()
+        .is_available()
+        .unwrap_or(false);
+    if rocm && !cuda {
+        "ROCm"
+    } else {
+        "CUDA"
+    }
+}
+
+/// Register OpenVINO on an Intel `device` ahead of CPU
+pub(crate) fn with_openvino(
+    builder: ort::session::builder::SessionBuilder,
+    device: &str,
+) -> ort::Result<ort::session::builder::SessionBuilder> {
+    builder.with_execution_providers([
+        ep::OpenVINOExecutionProvider::default()
+            .with_device_type(device)
+            .build(),
+        ep::CPUExecutionProvider::default().build(),
+    ])
+}
+
+/// Where ONNX Runtime runs a recognizer's sessions
+///
+/// Converts from `bool` (`true` is [`ExecutionTarget::Gpu`]), so
+/// `OrtRecognizer::new(dir, true)` still picks the platform GPU.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ExecutionTarget {
+    /// CPU execution provider only
+    #[default]
+    Cpu,
+    /// The platform GPU provider: CUDA or ROCm on Linux, CoreML on macOS,
+    /// DirectML on Windows
+    Gpu,
+    /// OpenVINO on an Intel device: `"GPU"` (integrated GPU), `"NPU"`,
+    /// `"CPU"`, or an indexed device such as `"GPU.1"`
+    OpenVino(String),
+}
+
+impl From<bool> for ExecutionTarget {
+    fn from(use_gpu: bool) -> Self {
+        if use_gpu {
+            Self::Gpu
+        } else {
+            Self::Cpu
+        }
+    }
+}
+
+impl ExecutionTarget {
+    /// Target for a provider name as the daemon detects it: `None` is the
+    /// CPU, `"openvino"` the OpenVINO GPU device (`"openvino:NPU"` picks
+    /// another device), anything else the platform GPU
+    pub fn from_provider(provider: Option<&str>) -> Self {
+        match provider {
+            None => Self::Cpu,
+            Some(p) => match p.split_once(':') {
+                Some((name, device)) if name.eq_ignore_ascii_case("openvino") => {
+                    Self::OpenVino(device.to_uppercase())
+                }
+                _ if p.eq_ignore_ascii_case("openvino") => Self::OpenVino("GPU".to_string()),
+                _ => Self::Gpu,
+            },
+        }
+    }
+
+    /// Whether the loaded ONNX Runtime was built with this target's
+    /// execution provider (the device itself may still be missing)
+    pub fn is_available(&self) -> bool {
+        use ep::ExecutionProvider;
+
+        match self {
+            Self::Cpu => true,
+            Self::OpenVino(_) => ep::OpenVINOExecutionProvider::default()
+                .is_available()
+                .unwrap_or(false),
+            Self::Gpu => {
+                #[cfg(target_os = "macos")]
+                let available = ep::CoreMLExecutionProvider::default().is_available();
+                #[cfg(target_os = "linux")]
+                let available = match linux_gpu_provider() {
+                    "ROCm" => ep::ROCmExecutionProvider::default().is_available(),
+                    _ => ep::CUDAExecutionProvider::default().is_available(),
+                };
+                #[cfg(target_os = "windows")]
+                let available = ep::DirectMLExecutionProvider::default().is_available();
+                #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+                let available = Ok(false);
+                available.unwrap_or(false)
+            }
+        }
+    }
+
+    /// Execution provider name (`"CPU"`, `"CUDA"`, `"OpenVINO"`, ...)
+    pub fn provider_name(&self) -> &'static str {
+        match self {
+            Self::Cpu => "CPU",
+            Self::Gpu => gpu_provider_name(),
+            Self::OpenVino(_) => "OpenVINO",
+        }
+    }
+}
+
+/// Name of the execution provider GPU sessions are built with on this platform
+pub(crate) fn gpu_provider_name() -> &'static str {
+    #[cfg(target_os = "macos")]
+    let name = "CoreML";
+    #[cfg(target_os = "linux")]
+    let name = linux_gpu_provider();
+    #[cfg(target_os = "windows")]
+    let name = "DirectML";
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+    let name = "CPU";
+    name
+}
+
+/// Register the Linux GPU provider (see [`linux_gpu_provider`]) ahead of CPU
+#[cfg(target_os = "linux")]
+pub(crate) fn with_linux_gpu(
+    builder: ort::session::builder::SessionBuilder,
+) -> ort::Result<ort::session::builder::SessionBuilder> {
+    let gpu = match linux_gpu_provider() {
+        "ROCm" => ep::ROCmExecutionProvider::default().build(),
+        _ => ep::CUDAExecutionProvider::default().build(),
+    };
+    builder.with_execution_providers([gpu, ep::CPUExecutionProvider::default().build()])
 }
 
 impl OrtRecognizer {
@@ -103,7 +373,9 @@ impl OrtRecognizer {
     ///
     /// # Arguments
     /// * `model_dir` - Path to directory containing encoder.onnx, decoder.onnx, joiner.onnx, tokens.txt
-    /// * `use_gpu` - Enable CUDA execution provider
+    /// * `target` - Where to run: `false`/`true` for CPU or the platform GPU
+    ///   (CUDA or ROCm on Linux, CoreML on macOS, DirectML on Windows), or an
+    ///   [`ExecutionTarget`] such as OpenVINO on an Intel NPU
     ///
     /// # Example
     /// ```no_run
@@ -115,10 +387,12 @@ impl OrtRecognizer {
     /// )?;
     /// # Ok::<(), Box<dyn std::error::Error>>(())
     /// ```
-    pub fn new<P: AsRef<Path>>(model_dir: P, use_gpu: bool) -> Result<Self> {
+    pub fn new<P: AsRef<Path>, T: Into<ExecutionTarget>>(model_dir: P, target: T) -> Result<Self> {
         let model_path = model_dir.as_ref().to_path_buf();
+        let target = target.into();
+        let use_gpu = target == ExecutionTarget::Gpu;
 
-        info!("Loading 1.1B Parakeet-TDT model with direct ONNX Runtime");
+        info!("Loading Parakeet-TDT model with direct ONNX Runtime");
         info!("Model directory: {}", model_path.display());
 
         // Load tokens and find special token IDs
@@ -141,68 +415,25 @@ impl OrtRecognizer {
             unk_id
         );
 
-        // Configure ONNX Runtime session options
-        let mut session_builder = Session::builder()
-            .map_err(|e| {
-                SttError::ModelLoadError(format!("Failed to create session builder: {}", e))
-            })?
-            .with_optimization_level(GraphOptimizationLevel::Level3)
-            .map_err(|e| {
-                SttError::ModelLoadError(format!("Failed to set optimization level: {}", e))
-            })?
-            .with_intra_threads(4)
-            .map_err(|e| SttError::ModelLoadError(format!("Failed to set intra threads: {}", e)))?;
-
-        if use_gpu {
-            // macOS: Use CoreML execution provider (internally uses Metal/GPU)
-            #[cfg(target_os = "macos")]
-            {
-                info!("Enabling CoreML execution provider (Apple Silicon GPU acceleration)");
-                session_builder = session_builder
-                    .with_execution_providers([
-                        ep::CoreMLExecutionProvider::default()
-                            // NeuralNetwork format avoids .mlpackage directory creation that conflicts
-                            // with ONNX external weights files (e.g., encoder.onnx + encoder.weights)
-                            .with_model_format(CoreMLModelFormat::NeuralNetwork)
-                            .with_compute_units(CoreMLComputeUnits::All) // CPU + GPU + ANE
-                            .build(),
-                        ep::CPUExecutionProvider::default().build(),
-                    ])
-                    .map_err(|e| {
-                        SttError::ModelLoadError(format!(
-                            "Failed to set CoreML execution providers: {}",
-                            e
-                        ))
-                    })?;
-            }
-
-            // Linux: Use CUDA execution provider
-            #[cfg(target_os = "linux")]
-            {
-                info!("Enabling CUDA execution provider");
-                session_builder = session_builder
-                    .with_execution_providers([
-                        ep::CUDAExecutionProvider::default().build(),
-                        ep::CPUExecutionProvider::default().build(),
-                    ])
-                    .map_err(|e| {
-                        SttError::ModelLoadError(format!(
-                            "Failed to set CUDA execution providers: {}",
-                            e
-                        ))
-                    })?;
-            }
-        } else {
-            info!("Using CPU execution provider");
-        }
+        let session_builder = encoder_session_builder(&target, 4)?;
 
         // Helper function to find model file
         // Platform-specific model format selection:
         // - macOS CoreML: Prefer FP16 (INT8 quantization poorly supported on CoreML)
-        // - Linux CUDA: Prefer FP32 (INT8 ops have no CUDA kernels)
+        // - Linux CUDA/ROCm: Prefer FP32 (INT8 ops have no GPU kernels)
+        // - Windows DirectML: Prefer FP32, then FP16 (few quantized ops in DirectML)
+        // - OpenVINO: Prefer FP32, then FP16 (it picks its own precision per device)
         // - CPU: Prefer INT8 (smaller and faster on CPU)
         let find_model_file = |name: &str| -> std::result::Result<PathBuf, SttError> {
-            if use_gpu {
+            if let ExecutionTarget::OpenVino(device) = &target {
+                for suffix in ["onnx", "fp16.onnx", "int8.onnx"] {
+                    let path = model_path.join(format!("{}.{}", name, suffix));
+                    if path.exists() {
+                        info!("Using {}.{} for OpenVINO ({})", name, suffix, device);
+                        return Ok(path);
+                    }
+                }
+            } else if use_gpu {
                 // macOS CoreML: Prefer FP16, fallback to FP32, avoid INT8
                 #[cfg(target_os = "macos")]
                 {
@@ -229,18 +460,36 @@ impl OrtRecognizer {
                     }
                 }
 
-                // Linux CUDA: Prefer FP32 (INT8 ops have no CUDA kernels)
+                // Linux CUDA/ROCm: Prefer FP32 (INT8 ops have no GPU kernels)
                 #[cfg(target_os = "linux")]
                 {
+                    let provider = linux_gpu_provider();
                     let onnx_path = model_path.join(format!("{}.onnx", name));
                     if onnx_path.exists() {
-                        info!("Using FP32 model for CUDA: {}.onnx", name);
+                        info!("Using FP32 model for {}: {}.onnx", provider, name);
                         return Ok(onnx_path);
                     }
                     // Fallback to INT8 if FP32 not available (will be slow)
                     let int8_path = model_path.join(format!("{}.int8.onnx", name));
                     if int8_path.exists() {
-                        warn!("⚠️  Using INT8 model on CUDA - will be slow (no CUDA kernels for quantized ops)");
+                        warn!("⚠️  Using INT8 model on {} - will be slow (no GPU kernels for quantized ops)", provider);
+                        return Ok(int8_path);
+                    }
+                }
+
+                // Windows DirectML: Prefer FP32, fallback to FP16, INT8 last
+                #[cfg(target_os = "windows")]
+                {
+                    for (suffix, label) in [("onnx", "FP32"), ("fp16.onnx", "FP16")] {
+                        let path = model_path.join(format!("{}.{}", name, suffix));
+                        if path.exists() {
+                            info!("Using {} model for DirectML: {}.{}", label, name, suffix);
+                            return Ok(path);
+                        }
+                    }
+                    let int8_path = model_path.join(format!("{}.int8.onnx", name));
+                    if int8_path.exists() {
+                        warn!("⚠️  Using INT8 model on DirectML - quantized ops mostly fall back to CPU");
                         return Ok(int8_path);
                     }
                 }
@@ -308,7 +557,16 @@ impl OrtRecognizer {
                 SttError::ModelLoadError(format!("Failed to set decoder optimization: {}", e))
             })?;
 
-        if use_gpu {
+        if let ExecutionTarget::OpenVino(device) = &target {
+            info!("Enabling OpenVINO for decoder ({})", device);
+            decoder_builder = with_openvino(decoder_builder, device).map_err(|e| {
+                let _ = std::env::set_current_dir(&original_dir);
+                SttError::ModelLoadError(format!(
+                    "Failed to set decoder OpenVINO execution providers: {}",
+                    e
+                ))
+            })?;
+        } else if use_gpu {
             // macOS: Use CoreML execution provider
             #[cfg(target_os = "macos")]
             {
@@ -331,22 +589,31 @@ impl OrtRecognizer {
                     })?;
             }
 
-            // Linux: Use CUDA execution provider
+            // Linux: Use CUDA (NVIDIA) or ROCm (AMD) execution provider
             #[cfg(target_os = "linux")]
             {
-                info!("Enabling CUDA for decoder");
-                decoder_builder = decoder_builder
-                    .with_execution_providers([
-                        ep::CUDAExecutionProvider::default().build(),
-                        ep::CPUExecutionProvider::default().build(),
-                    ])
-                    .map_err(|e| {
-                        let _ = std::env::set_current_dir(&original_dir);
-                        SttError::ModelLoadError(format!(
-                            "Failed to set decoder CUDA execution providers: {}",
-                            e
-                        ))
-                    })?;
+                info!("Enabling {} for decoder", linux_gpu_provider());
+                decoder_builder = with_linux_gpu(decoder_builder).map_err(|e| {
+                    let _ = std::env::set_current_dir(&original_dir);
+                    SttError::ModelLoadError(format!(
+                        "Failed to set decoder {} execution providers: {}",
+                        linux_gpu_provider(),
+                        e
+                    ))
+                })?;
+            }
+
+            // Windows: Use DirectML execution provider
+            #[cfg(target_os = "windows")]
+            {
+                info!("Enabling DirectML for decoder");
+                decoder_builder = with_directml(decoder_builder).map_err(|e| {
+                    let _ = std::env::set_current_dir(&original_dir);
+                    SttError::ModelLoadError(format!(
+                        "Failed to set decoder DirectML execution providers: {}",
+                        e
+                    ))
+                })?;
             }
         }
 
@@ -371,7 +638,16 @@ impl OrtRecognizer {
                 SttError::ModelLoadError(format!("Failed to set joiner optimization: {}", e))
             })?;
 
-        if use_gpu {
+        if let ExecutionTarget::OpenVino(device) = &target {
+            info!("Enabling OpenVINO for joiner ({})", device);
+            joiner_builder = with_openvino(joiner_builder, device).map_err(|e| {
+                let _ = std::env::set_current_dir(&original_dir);
+                SttError::ModelLoadError(format!(
+                    "Failed to set joiner OpenVINO execution providers: {}",
+                    e
+                ))
+            })?;
+        } else if use_gpu {
             // macOS: Use CoreML execution provider
             #[cfg(target_os = "macos")]
             {
@@ -394,22 +670,31 @@ impl OrtRecognizer {
                     })?;
             }
 
-            // Linux: Use CUDA execution provider
+            // Linux: Use CUDA (NVIDIA) or ROCm (AMD) execution provider
             #[cfg(target_os = "linux")]
             {
-                info!("Enabling CUDA for joiner");
-                joiner_builder = joiner_builder
-                    .with_execution_providers([
-                        ep::CUDAExecutionProvider::default().build(),
-                        ep::CPUExecutionProvider::default().build(),
-                    ])
-                    .map_err(|e| {
-                        let _ = std::env::set_current_dir(&original_dir);
-                        SttError::ModelLoadError(format!(
-                            "Failed to set joiner CUDA execution providers: {}",
-                            e
-                        ))
-                    })?;
+                info!("Enabling {} for joiner", linux_gpu_provider());
+                joiner_builder = with_linux_gpu(joiner_builder).map_err(|e| {
+                    let _ = std::env::set_current_dir(&original_dir);
+                    SttError::ModelLoadError(format!(
+                        "Failed to set joiner {} execution providers: {}",
+                        linux_gpu_provider(),
+                        e
+                    ))
+                })?;
+            }
+
+            // Windows: Use DirectML execution provider
+            #[cfg(target_os = "windows")]
+            {
+                info!("Enabling DirectML for joiner");
+                joiner_builder = with_directml(joiner_builder).map_err(|e| {
+                    let _ = std::env::set_current_dir(&original_dir);
+                    SttError::ModelLoadError(format!(
+                        "Failed to set joiner DirectML execution providers: {}",
+                        e
+                    ))
+                })?;
             }
         }
 
@@ -425,39 +710,316 @@ impl OrtRecognizer {
         })?;
         info!("Restored working directory to {}", original_dir.display());
 
-        // Auto-detect model configuration from path
-        let config = ModelConfig::detect_from_path(&model_path);
-
-        info!("Detected {} model", config.model_name);
-        info!("  Decoder hidden size: {}", config.decoder_hidden_size);
-        info!("  Mel features: {}", config.n_mel_features);
-        info!("  Transpose input: {}", config.transpose_input);
-
-        let audio_processor = AudioProcessor::with_mel_features(config.n_mel_features)?;
+        // Model format from model.json and the encoder/decoder input shapes
+        let manifest = ModelManifest::load(&model_path)?;
+        let format = manifest::resolve(
+            manifest.as_ref(),
+            &input_shape(&encoder, "audio_signal"),
+            &input_shape(&decoder, "states.1"),
+        )?;
+
+        info!("Detected {} model", format.name);
+        info!("  Decoder hidden size: {}", format.decoder_hidden_size);
+        info!("  Mel features: {}", format.mel_features);
+        info!("  Feature layout: {:?}", format.feature_layout);
+
+        let audio_processor = AudioProcessor::with_mel_features(format.mel_features)?;
+        let precision = precision_from_path(&encoder_path);
+
+        let run_options = Arc::new(RunOptions::new().map_err(|e| {
+            SttError::ModelLoadError(format!("Failed to create run options: {}", e))
+        })?);
+
+        // Without bindings every run goes through plain (pageable) tensors
+        let bindings = if use_gpu {
+            let provider = target.provider_name();
+            match SessionBindings::new(&encoder, &decoder, &joiner, provider) {
+                Ok(Some(bindings)) => {
+                    info!("✓ Pinned-memory IO binding enabled ({})", provider);
+                    Some(bindings)
+                }
+                Ok(None) => None,
+                Err(e) => {
+                    warn!("IO binding unavailable, using plain session runs: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
 
         Ok(Self {
             encoder,
+            extra_encoders: Vec::new(),
             decoder,
             joiner,
             tokens,
             blank_id,
             unk_id,
             model_path,
+            encoder_path,
             audio_processor,
             decoder_state1: None,
             decoder_state2: None,
-            config,
-            use_gpu,
+            format,
+            target,
+            precision,
+            fusion: None,
+            decoding: Decoding::Greedy,
+            tdt: TdtConfig::default(),
+            bias: None,
+            lexicon: None,
+            emissions: Vec::new(),
+            run_options,
+            cache: None,
+            stream: None,
+            bindings,
         })
     }
 
+    /// Handle for cancelling recognition from another thread
+    pub fn cancel_handle(&self) -> CancelHandle {
+        CancelHandle(self.run_options.clone())
+    }
+
+    /// Replace all ONNX Runtime sessions with freshly loaded ones
+    ///
+    /// Used after a cancelled or stuck recognition, when the old sessions may
+    /// be left in a bad state. Keeps the decoding settings.
+    pub fn reload(&mut self) -> Result<()> {
+        let mut fresh = Self::new(&self.model_path, self.target.clone())?;
+        fresh.fusion = self.fusion.take();
+        fresh.decoding = self.decoding;
+        fresh.tdt = self.tdt;
+        fresh.bias = self.bias.take();
+        fresh.lexicon = self.lexicon.take();
+        fresh.cache = self.cache.take();
+        fresh.set_encoder_workers(self.encoder_workers())?;
+        *self = fresh;
+        Ok(())
+    }
+
+    /// Enable (or with `None`, disable) language model shallow fusion
+    pub fn set_shallow_fusion(&mut self, fusion: Option<ShallowFusion>) {
+        if let Some(fusion) = &fusion {
+            info!("Shallow fusion enabled (LM weight {:.2})", fusion.weight());
+        }
+        self.fusion = fusion;
+        // Cached results were decoded with the old language model
+        if let Some(cache) = &mut self.cache {
+            cache.clear();
+        }
+    }
+
+    /// Bias decoding toward a phrase list (or with `None`, stop biasing;
+    /// see [`crate::biasing`])
+    pub fn set_context_bias(&mut self, bias: Option<ContextBias>) {
+        match &bias {
+            Some(bias) => info!(
+                "Context biasing toward {} phrases (boost {:.1})",
+                bias.phrases().len(),
+                bias.boost()
+            ),
+            None => debug!("Context biasing disabled"),
+        }
+        self.bias = bias;
+        // Cached results were decoded with the old phrase list
+        if let Some(cache) = &mut self.cache {
+            cache.clear();
+        }
+    }
+
+    /// Load a user lexicon (see [`crate::lexicon`]), replacing any loaded
+    /// before; `None` unloads it. Returns the number of entries.
+    ///
+    /// A lexicon that fails to load leaves the current one in place.
+    pub fn load_lexicon(&mut self, path: Option<&Path>) -> Result<usize> {
+        let lexicon = path
+            .map(|path| Lexicon::load(path, &self.tokens))
+            .transpose()?;
+        let count = lexicon.as_ref().map_or(0, Lexicon::len);
+        if let Some(path) = path {
+            info!("Lexicon loaded from {} ({} words)", path.display(), count);
+        }
+        self.lexicon = lexicon;
+        // Cached results were decoded with the old lexicon
+        if let Some(cache) = &mut self.cache {
+            cache.clear();
+        }
+        Ok(count)
+    }
+
+    /// Choose greedy or beam search decoding (see [`crate::beam`])
+    pub fn set_decoding(&mut self, decoding: Decoding) {
+        if let Decoding::Beam(config) = decoding {
+            info!(
+                "Beam search decoding enabled (width {}, prune {:.1})",
+                config.width, config.prune
+            );
+        }
+        self.decoding = decoding;
+        // Cached results were decoded the other way
+        if let Some(cache) = &mut self.cache {
+            cache.clear();
+        }
+    }
+
+    /// Encode the windows of long audio on `workers` encoder sessions at once
+    /// (1, the default, runs them all through one)
+    ///
+    /// Every worker past the first loads another copy of the encoder. On CPU
+    /// the sessions run on a rayon pool and share the cores between them; on
+    /// CUDA each session has a stream of its own, so their runs overlap on
+    /// the GPU. Decoding stays sequential either way.
+    pub fn set_encoder_workers(&mut self, workers: usize) -> Result<()> {
+        let workers = workers.max(1);
+        if workers == self.encoder_workers() {
+            return Ok(());
+        }
+        self.extra_encoders.clear();
+        if workers == 1 {
+            return Ok(());
+        }
+
+        let threads = std::thread::available_parallelism().map_or(4, |n| n.get());
+        let threads = (threads / workers).max(1);
+        // External weights are looked up relative to the working directory
+        let original_dir = std::env::current_dir().map_err(|e| {
+            SttError::ModelLoadError(format!("Failed to get current directory: {}", e))
+        })?;
+        std::env::set_current_dir(&self.model_path).map_err(|e| {
+            SttError::ModelLoadError(format!(
+                "Failed to change to model directory {}: {}",
+                self.model_path.display(),
+                e
+            ))
+        })?;
+        let loaded: Result<Vec<Session>> = (1..workers)
+            .map(|_| {
+                encoder_session_builder(&self.target, threads)?
+                    .commit_from_file(&self.encoder_path)
+                    .map_err(|e| SttError::ModelLoadError(format!("Failed to load encoder: {}", e)))
+            })
+            .collect();
+        std::env::set_current_dir(&original_dir).map_err(|e| {
+            SttError::ModelLoadError(format!("Failed to restore original directory: {}", e))
+        })?;
+
+        self.extra_encoders = loaded?;
+        info!(
+            "✓ {} encoder workers ({} threads each on CPU)",
+            workers, threads
+        );
+        Ok(())
+    }
+
+    /// Number of encoder sessions windows are spread over
+    pub fn encoder_workers(&self) -> usize {
+        1 + self.extra_encoders.len()
+    }
+
+    /// Set the TDT step rules (tokens per frame, duration cap, blank penalty)
+    pub fn set_tdt(&mut self, tdt: TdtConfig) {
+        if tdt != TdtConfig::default() {
+            info!(
+                "TDT decoding: {} tokens per frame, duration cap {}, blank penalty {:.2}",
+                tdt.max_tokens_per_frame, tdt.max_duration_skip, tdt.blank_penalty
+            );
+        }
+        self.tdt = tdt;
+        if let Some(cache) = &mut self.cache {
+            cache.clear();
+        }
+    }
+
+    /// Remember the results of up to `capacity` distinct audio buffers so
+    /// bit-identical audio is not decoded again (0 disables the cache)
+    pub fn set_result_cache(&mut self, capacity: usize) {
+        self.cache = (capacity > 0).then(|| RecognitionCache::new(capacity));
+    }
+
+    /// Results held by the result cache
+    pub fn cached_results(&self) -> usize {
+        self.cache.as_ref().map_or(0, RecognitionCache::len)
+    }
+
+    /// Decode `samples` once to warm the sessions up, bypassing the result
+    /// cache, then forget the emissions so `n_best` does not report them
+    pub fn warm_up(&mut self, samples: &[f32]) -> Result<()> {
+        self.run_options
+            .unterminate()
+            .map_err(|e| SttError::InferenceError(format!("Failed to reset run options: {}", e)))?;
+        self.decode_samples(samples)?;
+        self.emissions.clear();
+        Ok(())
+    }
+
+    /// Up to `k` alternative transcriptions of the last recognized audio
+    ///
+    /// Built from the runner-up tokens of the last greedy pass (see
+    /// [`crate::nbest`]), most likely first, without duplicates of the best
+    /// text or each other.
+    pub fn alternatives(&self, k: usize) -> Vec<String> {
+        self.n_best(k + 1)
+            .into_iter()
+            .skip(1)
+            .map(|hypothesis| hypothesis.text)
+            .collect()
+    }
+
+    /// Up to `n` scored transcriptions of the last recognized audio: the
+    /// greedy result first, then its [`alternatives`](Self::alternatives)
+    pub fn n_best(&self, n: usize) -> Vec<Hypothesis> {
+        let mut hypotheses: Vec<Hypothesis> = Vec::new();
+        // Flips inside one word piece can collapse to the same text; ask for spares
+        for (tokens, score) in nbest::hypotheses(&self.emissions, self.blank_id, n * 2) {
+            let text = self.join_tokens(&tokens);
+            let is_best = hypotheses.is_empty();
+            if is_best || (!text.is_empty() && !hypotheses.iter().any(|h| h.text == text)) {
+                hypotheses.push(Hypothesis { text, score });
+            }
+        }
+        hypotheses.truncate(n);
+        hypotheses
+    }
+
+    /// Per-word confidence and start time of the last recognized audio (see
+    /// [`crate::confidence`])
+    pub fn word_confidences(&self) -> Vec<WordConfidence> {
+        confidence::timed_word_confidences(
+            self.emissions
+                .iter()
+                .filter(|e| e.token != self.blank_id && e.token != self.unk_id)
+                .filter_map(|e| {
+                    let piece = self.tokens.get(e.token as usize)?.as_str();
+                    Some((piece, e.prob, Some(e.frame as f64 * window::FRAME_SECS)))
+                }),
+        )
+    }
+
+    /// Weight precision of the loaded encoder (`"fp32"`, `"fp16"` or `"int8"`)
+    pub fn precision(&self) -> &'static str {
+        self.precision
+    }
+
+    /// ONNX Runtime execution provider the sessions were built with
+    pub fn execution_provider(&self) -> &'static str {
+        self.target.provider_name()
+    }
+
+    /// Where the sessions run
+    pub fn execution_target(&self) -> &ExecutionTarget {
+        &self.target
+    }
+
     /// Check if GPU mode is enabled
     ///
     /// # Returns
     ///
-    /// `true` if CUDA execution provider is enabled, `false` for CPU-only
+    /// `true` if an accelerator execution provider (platform GPU or
+    /// OpenVINO) is enabled, `false` for CPU-only
     pub fn is_gpu(&self) -> bool {
-        self.use_gpu
+        self.target != ExecutionTarget::Cpu
     }
 
     /// Load tokens from tokens.txt
@@ -531,7 +1093,10 @@ impl OrtRecognizer {
 
     /// Recognize speech from audio file
     ///
-    /// Full implementation with mel-spectrogram extraction and greedy search decoding
+    /// Full implementation with mel-spectrogram extraction and greedy search decoding.
+    /// Recordings longer than [`longform::CHUNK_SECS`] are recognized in
+    /// overlapping chunks joined at their seams (see [`crate::longform`]);
+    /// [`alternatives`](Self::alternatives) are not kept for them.
     ///
     /// # Arguments
     /// * `audio_path` - Path to audio file (WAV, MP3, FLAC)
@@ -554,6 +1119,10 @@ impl OrtRecognizer {
             audio_min, audio_max, audio_mean
         );
 
+        if samples.len() > longform::CHUNK_SECS * SAMPLE_RATE as usize {
+            return self.recognize_long(&samples);
+        }
+
         // Extract mel-spectrogram features
         let features = self.audio_processor.extract_mel_features(&samples)?;
         info!("Extracted features: {:?}", features.shape());
@@ -587,66 +1156,373 @@ impl OrtRecognizer {
                 );
             }
 
-            // Check if features need normalization (typical for NeMo models)
-            debug!("Note: NVIDIA NeMo models typically expect normalized features (mean=0, std=1)");
-            debug!("Current features are log-mel without normalization");
+            // Check if features need normalization (typical for NeMo models)
+            debug!("Note: NVIDIA NeMo models typically expect normalized features (mean=0, std=1)");
+            debug!("Current features are log-mel without normalization");
+        }
+
+        let windows = window::plan(features.nrows());
+        info!(
+            "Encoding {} frames in {} windows",
+            features.nrows(),
+            windows.len()
+        );
+        let encoded = self.encode_windows(&features, &windows)?;
+        let text = self.greedy_search_decode(&encoded)?;
+
+        Ok(text)
+    }
+
+    /// Recognize a long recording chunk by chunk, merging the overlaps
+    fn recognize_long(&mut self, samples: &[f32]) -> Result<String> {
+        let chunks = longform::chunks(samples.len());
+        info!(
+            "Recognizing {:.0} s of audio in {} overlapping chunks",
+            samples.len() as f64 / SAMPLE_RATE as f64,
+            chunks.len()
+        );
+
+        let mut words: Vec<WordConfidence> = Vec::new();
+        for chunk in chunks {
+            let offset_s = chunk.start as f64 / SAMPLE_RATE as f64;
+            self.decode_samples(&samples[chunk])?;
+            let next = self
+                .word_confidences()
+                .into_iter()
+                .map(|mut word| {
+                    word.start_s = word.start_s.map(|s| s + offset_s);
+                    word
+                })
+                .collect();
+            let seam_s = offset_s + longform::OVERLAP_SECS as f64 / 2.0;
+            longform::merge(&mut words, next, seam_s);
+        }
+        // Emissions of the last chunk alone would describe the wrong audio
+        self.emissions.clear();
+
+        let text = words
+            .iter()
+            .map(|word| word.word.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        Ok(match &self.lexicon {
+            Some(lexicon) => lexicon.apply(&text),
+            None => text,
+        })
+    }
+
+    /// Recognize speech from audio samples (16kHz mono f32)
+    ///
+    /// This method processes raw audio samples directly, which is useful for
+    /// streaming/pipeline applications where audio is already loaded in memory.
+    ///
+    /// # Arguments
+    /// * `samples` - Audio samples at 16kHz, mono, f32 format
+    ///
+    /// # Returns
+    /// Transcribed text
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use swictation_stt::OrtRecognizer;
+    /// # let mut recognizer = OrtRecognizer::new("model_path", true)?;
+    /// let samples: Vec<f32> = vec![0.0; 16000]; // 1 second of audio
+    /// let text = recognizer.recognize_samples(&samples)?;
+    /// println!("Transcription: {}", text);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn recognize_samples(&mut self, samples: &[f32]) -> Result<String> {
+        info!("Processing {} audio samples", samples.len());
+
+        // Clear a cancellation left over from an earlier recognition
+        self.run_options
+            .unterminate()
+            .map_err(|e| SttError::InferenceError(format!("Failed to reset run options: {}", e)))?;
+
+        let key = self.cache.as_ref().map(|_| cache::fingerprint(samples));
+        if let Some(hit) = key.and_then(|key| self.cache.as_mut()?.get(key)) {
+            debug!("Recognition cache hit");
+            self.emissions = hit.emissions;
+            return Ok(hit.text);
+        }
+
+        let text = self.decode_samples(samples)?;
+
+        if let (Some(cache), Some(key)) = (self.cache.as_mut(), key) {
+            cache.insert(
+                key,
+                CachedRecognition {
+                    text: text.clone(),
+                    emissions: self.emissions.clone(),
+                },
+            );
+        }
+
+        Ok(text)
+    }
+
+    /// Recognize several segments, batching their encoder runs
+    ///
+    /// Returns the same texts as [`recognize_samples`](Self::recognize_samples)
+    /// on each segment in turn. The encoder windows of every segment are
+    /// stacked and encoded up to [`MAX_ENCODER_BATCH`] at a time, which
+    /// saves the fixed cost of a session run per short segment during
+    /// dictation bursts; decoding stays per segment. Cached segments are not
+    /// encoded, and [`alternatives`](Self::alternatives) afterwards describe
+    /// the last segment.
+    pub fn recognize_batch(&mut self, segments: &[&[f32]]) -> Result<Vec<String>> {
+        info!("Batch recognizing {} segments", segments.len());
+
+        self.run_options
+            .unterminate()
+            .map_err(|e| SttError::InferenceError(format!("Failed to reset run options: {}", e)))?;
+
+        let keys: Vec<Option<u64>> = segments
+            .iter()
+            .map(|samples| self.cache.as_ref().map(|_| cache::fingerprint(samples)))
+            .collect();
+        let mut results: Vec<Option<CachedRecognition>> = vec![None; segments.len()];
+
+        // Segments still to decode, with how many windows each contributed
+        let mut pending = Vec::new();
+        let mut inputs = Vec::new();
+        let mut windows = Vec::new();
+        for (i, samples) in segments.iter().enumerate() {
+            if let Some(hit) = keys[i].and_then(|key| self.cache.as_mut()?.get(key)) {
+                debug!("Recognition cache hit for segment {}", i);
+                results[i] = Some(hit);
+                continue;
+            }
+            let features = self.extract_features(samples)?;
+            let segment_windows = window::plan(features.nrows());
+            pending.push((i, segment_windows.len()));
+            inputs.extend(segment_windows.iter().map(|w| w.input(&features)));
+            windows.extend(segment_windows);
+        }
+
+        let encoded = self.encode_inputs(&inputs, &windows)?;
+        info!(
+            "Encoded {} windows of {} segments in {} encoder runs",
+            windows.len(),
+            pending.len(),
+            windows.len().div_ceil(MAX_ENCODER_BATCH)
+        );
+
+        let mut encoded = encoded.into_iter();
+        for (i, count) in pending {
+            let segment: Vec<Array3<f32>> = encoded.by_ref().take(count).collect();
+            let recognition = CachedRecognition {
+                text: self.search(&segment)?,
+                emissions: self.emissions.clone(),
+            };
+            if let (Some(cache), Some(key)) = (self.cache.as_mut(), keys[i]) {
+                cache.insert(key, recognition.clone());
+            }
+            results[i] = Some(recognition);
+        }
+
+        if let Some(Some(last)) = results.last() {
+            self.emissions = last.emissions.clone();
+        }
+        Ok(results
+            .into_iter()
+            .map(|result| result.map(|r| r.text).unwrap_or_default())
+            .collect())
+    }
+
+    /// Begin a streaming recognition (see [`crate::stream`])
+    ///
+    /// Feed audio with [`feed`](Self::feed) as it is captured, then call
+    /// [`finalize`](Self::finalize) for the final text. A partial hypothesis
+    /// is decoded every half second of new audio. Starting a stream discards
+    /// one that was never finalized.
+    pub fn start_stream(&mut self) -> Result<()> {
+        self.start_stream_with_interval(DEFAULT_PARTIAL_INTERVAL)
+    }
+
+    /// Like [`start_stream`](Self::start_stream), decoding a partial every
+    /// `interval` new samples
+    pub fn start_stream_with_interval(&mut self, interval: usize) -> Result<()> {
+        self.run_options
+            .unterminate()
+            .map_err(|e| SttError::InferenceError(format!("Failed to reset run options: {}", e)))?;
+        self.stream = Some(StreamState::new(interval));
+        Ok(())
+    }
+
+    /// Add 16kHz mono samples to the stream
+    ///
+    /// Returns the partial transcription of everything fed so far when one
+    /// was decoded and it changed since the last partial. Partials are not
+    /// cached and may be revised by later audio.
+    pub fn feed(&mut self, samples: &[f32]) -> Result<Option<String>> {
+        let mut stream = self
+            .stream
+            .take()
+            .ok_or_else(|| SttError::invalid_input("feed() called before start_stream()"))?;
+
+        let result = if stream.push(samples) && stream.samples().len() >= WIN_LENGTH {
+            self.decode_partial(&mut stream)
+                .map(|text| stream.partial(text))
+        } else {
+            Ok(None)
+        };
+        self.stream = Some(stream);
+        result
+    }
+
+    /// End the stream and return the final transcription
+    ///
+    /// Identical to [`recognize_samples`](Self::recognize_samples) over all
+    /// fed audio, including [`alternatives`](Self::alternatives) afterwards.
+    pub fn finalize(&mut self) -> Result<String> {
+        let samples = self
+            .stream
+            .take()
+            .ok_or_else(|| SttError::invalid_input("finalize() called before start_stream()"))?
+            .into_samples();
+        if samples.len() < WIN_LENGTH {
+            self.emissions.clear();
+            return Ok(String::new());
         }
+        self.recognize_samples(&samples)
+    }
 
-        // Try processing without chunking first (match C++ reference)
-        let text = if features.nrows() <= 80 {
-            // Small file - process in one chunk
-            let chunks = self.audio_processor.chunk_features(&features);
-            info!("Small file: {} chunks of 80 frames", chunks.len());
-            self.greedy_search_decode(&chunks)?
-        } else {
-            // Large file - try processing ALL frames at once (no chunking)
-            info!(
-                "Large file: {} frames total - processing all at once (no chunking)",
-                features.nrows()
-            );
+    /// Extract features from `samples`, encode them and decode the result
+    fn decode_samples(&mut self, samples: &[f32]) -> Result<String> {
+        let features = self.extract_features(samples)?;
+        let windows = window::plan(features.nrows());
+        let encoded = self.encode_windows(&features, &windows)?;
+        self.search(&encoded)
+    }
 
-            // Pad to multiple of 80 for encoder
-            let padded_rows = features.nrows().div_ceil(80) * 80;
-            let mut padded = Array2::zeros((padded_rows, features.ncols()));
-            padded
-                .slice_mut(s![..features.nrows(), ..])
-                .assign(&features);
+    /// Decode everything fed to `stream`, encoding only unsettled windows
+    ///
+    /// Settled windows keep the encoder output computed when they settled,
+    /// under the feature normalization of the audio at that time; the final
+    /// result re-encodes everything.
+    fn decode_partial(&mut self, stream: &mut StreamState) -> Result<String> {
+        let features = self.extract_features(stream.samples())?;
+        let windows = window::plan(features.nrows());
+        let fresh_windows = &windows[stream.settled().len().min(windows.len())..];
+        let fresh = self.encode_windows(&features, fresh_windows)?;
+        debug!(
+            "Partial: {} settled windows reused, {} encoded",
+            windows.len() - fresh_windows.len(),
+            fresh_windows.len()
+        );
 
-            // Process all 80-frame chunks in sequence but decode all at once
-            let chunks = self.audio_processor.chunk_features(&padded);
-            info!(
-                "Processing {} encoder chunks without decoder reset between chunks",
-                chunks.len()
-            );
-            self.greedy_search_decode(&chunks)?
-        };
+        let mut encoded = stream.settled().to_vec();
+        for (window, output) in fresh_windows.iter().zip(fresh) {
+            if window.is_settled() && encoded.len() == stream.settled().len() {
+                stream.settle(output.clone());
+            }
+            encoded.push(output);
+        }
+        self.search(&encoded)
+    }
 
-        Ok(text)
+    /// Run the encoder over `windows` of `features`, keeping the output for
+    /// the frames each window owns
+    fn encode_windows(
+        &mut self,
+        features: &Array2<f32>,
+        windows: &[Window],
+    ) -> Result<Vec<Array3<f32>>> {
+        let inputs: Vec<Array2<f32>> = windows.iter().map(|w| w.input(features)).collect();
+        self.encode_inputs(&inputs, windows)
     }
 
-    /// Recognize speech from audio samples (16kHz mono f32)
-    ///
-    /// This method processes raw audio samples directly, which is useful for
-    /// streaming/pipeline applications where audio is already loaded in memory.
-    ///
-    /// # Arguments
-    /// * `samples` - Audio samples at 16kHz, mono, f32 format
-    ///
-    /// # Returns
-    /// Transcribed text
-    ///
-    /// # Example
-    /// ```no_run
-    /// # use swictation_stt::OrtRecognizer;
-    /// # let mut recognizer = OrtRecognizer::new("model_path", true)?;
-    /// let samples: Vec<f32> = vec![0.0; 16000]; // 1 second of audio
-    /// let text = recognizer.recognize_samples(&samples)?;
-    /// println!("Transcription: {}", text);
-    /// # Ok::<(), Box<dyn std::error::Error>>(())
-    /// ```
-    pub fn recognize_samples(&mut self, samples: &[f32]) -> Result<String> {
-        info!("Processing {} audio samples", samples.len());
+    /// Encode window inputs up to [`MAX_ENCODER_BATCH`] at a time, dropping
+    /// the output for their context
+    fn encode_inputs(
+        &mut self,
+        inputs: &[Array2<f32>],
+        windows: &[Window],
+    ) -> Result<Vec<Array3<f32>>> {
+        // Spread evenly over the workers, so each gets its share of the audio
+        let workers = self.encoder_workers();
+        let batch_size = inputs.len().div_ceil(workers).clamp(1, MAX_ENCODER_BATCH);
+        let batches: Vec<&[Array2<f32>]> = inputs.chunks(batch_size).collect();
+        let encoded = if workers > 1 && batches.len() > 1 {
+            self.encode_parallel(&batches)?
+        } else {
+            let mut encoded = Vec::with_capacity(inputs.len());
+            for batch in batches {
+                encoded.extend(run_encoder_batch(
+                    &mut self.encoder,
+                    self.bindings.as_mut().map(|b| &mut b.encoder),
+                    self.format.feature_layout,
+                    &self.run_options,
+                    batch,
+                )?);
+            }
+            encoded
+        };
+        Ok(encoded
+            .into_iter()
+            .zip(windows)
+            .map(|(output, window)| {
+                let (left, right) = window.context_frames();
+                let frames = output.shape()[2];
+                let start = left.min(frames);
+                let end = frames.saturating_sub(right).max(start);
+                output.slice(s![.., .., start..end]).to_owned()
+            })
+            .collect())
+    }
 
+    /// Encode `batches` on all encoder sessions at once, worker `w` taking
+    /// batches `w`, `w + workers`, ...; outputs come back in batch order
+    fn encode_parallel(&mut self, batches: &[&[Array2<f32>]]) -> Result<Vec<Array3<f32>>> {
+        let feature_layout = self.format.feature_layout;
+        let run_options: &RunOptions = &self.run_options;
+        let mut sessions: Vec<(&mut Session, Option<&mut PinnedBinding>)> = std::iter::once((
+            &mut self.encoder,
+            self.bindings.as_mut().map(|b| &mut b.encoder),
+        ))
+        .chain(
+            self.extra_encoders
+                .iter_mut()
+                .map(|session| (session, None)),
+        )
+        .collect();
+        let workers = sessions.len();
+        debug!("Encoding {} batches on {} workers", batches.len(), workers);
+
+        let per_worker = sessions
+            .par_iter_mut()
+            .enumerate()
+            .map(|(worker, (session, binding))| {
+                batches
+                    .iter()
+                    .enumerate()
+                    .skip(worker)
+                    .step_by(workers)
+                    .map(|(index, batch)| {
+                        let encoded = run_encoder_batch(
+                            session,
+                            binding.as_deref_mut(),
+                            feature_layout,
+                            run_options,
+                            batch,
+                        )?;
+                        Ok((index, encoded))
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut outputs: Vec<_> = per_worker.into_iter().flatten().collect();
+        outputs.sort_by_key(|(index, _)| *index);
+        Ok(outputs
+            .into_iter()
+            .flat_map(|(_, encoded)| encoded)
+            .collect())
+    }
+
+    /// Mel features of `samples`, one row per 10 ms frame
+    fn extract_features(&mut self, samples: &[f32]) -> Result<Array2<f32>> {
         // Debug: Audio statistics
         let audio_min = samples.iter().fold(f32::INFINITY, |a, &b| a.min(b));
         let audio_max = samples.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b));
@@ -672,30 +1548,224 @@ impl OrtRecognizer {
             mel_min, mel_max, mel_mean
         );
 
-        // Process frames (chunking handled internally)
-        let text = if features.nrows() <= 80 {
-            // Small audio - process in one chunk
-            let chunks = self.audio_processor.chunk_features(&features);
-            info!("Small audio: {} chunks of 80 frames", chunks.len());
-            self.greedy_search_decode(&chunks)?
-        } else {
-            // Large audio - chunk and process
-            info!("Large audio: {} frames total - chunking", features.nrows());
-
-            // Pad to multiple of 80 for encoder
-            let padded_rows = features.nrows().div_ceil(80) * 80;
-            let mut padded = Array2::zeros((padded_rows, features.ncols()));
-            padded
-                .slice_mut(s![..features.nrows(), ..])
-                .assign(&features);
-
-            // Process all 80-frame chunks
-            let chunks = self.audio_processor.chunk_features(&padded);
-            info!("Processing {} encoder chunks", chunks.len());
-            self.greedy_search_decode(&chunks)?
-        };
+        Ok(features)
+    }
 
-        Ok(text)
+    /// Decode encoder outputs, one per window, with the configured search
+    fn search(&mut self, encoded: &[Array3<f32>]) -> Result<String> {
+        match self.decoding {
+            Decoding::Greedy => self.greedy_search_decode(encoded),
+            Decoding::Beam(config) => self.beam_search_decode(encoded, &config),
+        }
+    }
+
+    /// Beam search decoder (see [`crate::beam`])
+    ///
+    /// Hypotheses carry their decoder state across encoder chunks, like the
+    /// decoder output carried by greedy search.
+    fn beam_search_decode(
+        &mut self,
+        encoded: &[Array3<f32>],
+        config: &BeamConfig,
+    ) -> Result<String> {
+        self.emissions.clear();
+        self.decoder_state1 = None;
+        self.decoder_state2 = None;
+        let decoder_out = self.run_decoder(&[self.blank_id])?;
+        let mut hypotheses = vec![BeamHypothesis {
+            tokens: Vec::new(),
+            emissions: Vec::new(),
+            score: 0.0,
+            t: 0,
+            tokens_this_frame: 0,
+            decoder_out,
+            decoder_state1: self.decoder_state1.take(),
+            decoder_state2: self.decoder_state2.take(),
+        }];
+
+        let mut frame_offset = 0;
+        for encoder_out in encoded {
+            for hypothesis in &mut hypotheses {
+                hypothesis.t = 0;
+                hypothesis.tokens_this_frame = 0;
+            }
+            hypotheses = self.beam_search_chunk(encoder_out, hypotheses, config, frame_offset)?;
+            frame_offset += encoder_out.shape()[2];
+        }
+
+        let count = hypotheses.len();
+        let best = hypotheses
+            .into_iter()
+            .max_by(|a, b| a.score.total_cmp(&b.score))
+            .expect("beam always keeps a hypothesis");
+        debug!(
+            "Beam search kept {} hypotheses, best score {:.2}",
+            count, best.score
+        );
+        self.emissions = best.emissions;
+        Ok(self.tokens_to_text(&best.tokens))
+    }
+
+    /// Run the beam over the frames of one encoder chunk, which starts
+    /// `frame_offset` frames into the audio
+    fn beam_search_chunk(
+        &mut self,
+        encoder_out: &Array3<f32>,
+        mut hypotheses: Vec<BeamHypothesis>,
+        config: &BeamConfig,
+        frame_offset: usize,
+    ) -> Result<Vec<BeamHypothesis>> {
+        let num_frames = encoder_out.shape()[2];
+        let vocab_size = self.tokens.len();
+        let blank_id = self.blank_id;
+        let tdt = self.tdt;
+
+        let mut steps = 0;
+        while hypotheses.iter().any(|h| h.t < num_frames) {
+            steps += 1;
+            if steps > 100_000 {
+                warn!("Beam search did not finish a chunk after 100k steps");
+                break;
+            }
+
+            let mut candidates = Vec::new();
+            for (parent, hypothesis) in hypotheses.iter().enumerate() {
+                if hypothesis.t >= num_frames {
+                    candidates.push(Candidate {
+                        parent,
+                        token: None,
+                        emission: None,
+                        score: hypothesis.score,
+                        skip: 0,
+                        finished: true,
+                    });
+                    continue;
+                }
+
+                let frame = encoder_out.slice(s![0, .., hypothesis.t]).to_owned();
+                let mut logits = self.run_joiner(&frame, &hypothesis.decoder_out)?;
+                let (token_logits, duration_logits) =
+                    logits.as_slice_mut().unwrap().split_at_mut(vocab_size);
+                tdt.penalize_blank(token_logits, blank_id as usize);
+                let (token_logits, duration_logits) = (&*token_logits, &*duration_logits);
+                let skip = tdt.skip(
+                    beam::top_k(duration_logits, 1)
+                        .first()
+                        .copied()
+                        .unwrap_or(0),
+                );
+                let log_probs = beam::log_softmax(token_logits);
+
+                let expansions = beam::top_k(&log_probs, config.width.max(1));
+                // LM bonuses relative to the best non-blank piece, so fusion
+                // re-ranks spellings without favouring blank (deletions)
+                let bonuses: Vec<f32> = expansions
+                    .iter()
+                    .map(|&id| match &self.fusion {
+                        Some(fusion) if id as i64 != blank_id => {
+                            fusion.bonus(id, &self.tokens, &hypothesis.tokens)
+                        }
+                        _ => 0.0,
+                    })
+                    .collect();
+                // Phrase and lexicon boosts count in full: they should beat
+                // blank too
+                let boosts: Vec<f32> = expansions
+                    .iter()
+                    .map(|&id| {
+                        if id as i64 == blank_id {
+                            return 0.0;
+                        }
+                        let phrase = self
+                            .bias
+                            .as_ref()
+                            .map_or(0.0, |bias| bias.bonus(id, &self.tokens, &hypothesis.tokens));
+                        let word = self
+                            .lexicon
+                            .as_ref()
+                            .map_or(0.0, |lexicon| lexicon.bonus(id, &hypothesis.tokens));
+                        phrase + word
+                    })
+                    .collect();
+                let best_bonus = expansions
+                    .iter()
+                    .zip(&bonuses)
+                    .filter(|(&id, _)| id as i64 != blank_id)
+                    .map(|(_, &bonus)| bonus)
+                    .fold(f32::NEG_INFINITY, f32::max);
+
+                for ((&id, &bonus), &boost) in expansions.iter().zip(&bonuses).zip(&boosts) {
+                    let token = (id as i64 != blank_id).then_some(id as i64);
+                    let bonus = if token.is_some() {
+                        bonus - best_bonus + boost
+                    } else {
+                        0.0
+                    };
+                    candidates.push(Candidate {
+                        parent,
+                        token,
+                        emission: token.map(|y| {
+                            Emission::from_logits(y, token_logits).at(frame_offset + hypothesis.t)
+                        }),
+                        score: hypothesis.score + log_probs[id] + bonus,
+                        skip,
+                        finished: false,
+                    });
+                }
+            }
+
+            // Identical tokens at the same position are one hypothesis
+            let kept = beam::select(candidates, config, |c| {
+                let parent = &hypotheses[c.parent];
+                let mut tokens = parent.tokens.clone();
+                tokens.extend(c.token);
+                let position = if c.finished {
+                    (parent.t, parent.tokens_this_frame)
+                } else {
+                    beam::advance(
+                        parent.t,
+                        parent.tokens_this_frame + usize::from(c.token.is_some()),
+                        c.token.is_none(),
+                        c.skip,
+                        &tdt,
+                    )
+                };
+                (tokens, position)
+            });
+
+            let mut next = Vec::with_capacity(kept.len());
+            for candidate in kept {
+                let parent = &hypotheses[candidate.parent];
+                let mut hypothesis = parent.clone();
+                hypothesis.score = candidate.score;
+                if candidate.finished {
+                    next.push(hypothesis);
+                    continue;
+                }
+
+                if let Some(y) = candidate.token {
+                    hypothesis.tokens.push(y);
+                    hypothesis.emissions.extend(candidate.emission);
+                    self.decoder_state1 = parent.decoder_state1.clone();
+                    self.decoder_state2 = parent.decoder_state2.clone();
+                    hypothesis.decoder_out = self.run_decoder(&[y])?;
+                    hypothesis.decoder_state1 = self.decoder_state1.take();
+                    hypothesis.decoder_state2 = self.decoder_state2.take();
+                    hypothesis.tokens_this_frame += 1;
+                }
+                (hypothesis.t, hypothesis.tokens_this_frame) = beam::advance(
+                    hypothesis.t,
+                    hypothesis.tokens_this_frame,
+                    candidate.token.is_none(),
+                    candidate.skip,
+                    &tdt,
+                );
+                next.push(hypothesis);
+            }
+            hypotheses = next;
+        }
+
+        Ok(hypotheses)
     }
 
     /// Greedy search decoder implementation
@@ -706,10 +1776,10 @@ impl OrtRecognizer {
     /// 3. Joiner combines encoder/decoder outputs
     /// 4. Greedy selection picks highest probability token
     /// 5. Loop until blank or end-of-sequence
-    fn greedy_search_decode(&mut self, chunks: &[Array2<f32>]) -> Result<String> {
+    fn greedy_search_decode(&mut self, encoded: &[Array3<f32>]) -> Result<String> {
         eprintln!(
             "🎯 greedy_search_decode() called with {} chunks",
-            chunks.len()
+            encoded.len()
         );
         let mut all_tokens = Vec::new();
 
@@ -717,6 +1787,7 @@ impl OrtRecognizer {
         eprintln!("   Resetting decoder states...");
         self.decoder_state1 = None;
         self.decoder_state2 = None;
+        self.emissions.clear();
 
         // Track decoder output across chunks
         // For first chunk, we'll compute it with blank_id
@@ -730,26 +1801,22 @@ impl OrtRecognizer {
 
         eprintln!("   Starting chunk loop...");
 
-        for (chunk_idx, chunk) in chunks.iter().enumerate() {
-            eprintln!("\n📦 Processing chunk {}/{}", chunk_idx + 1, chunks.len());
-            eprintln!("   Chunk shape: {:?}", chunk.shape());
-
-            // Run encoder
-            eprintln!("   Calling run_encoder()...");
-            let encoder_out = self.run_encoder(chunk)?;
-            eprintln!(
-                "   ✅ Encoder finished! Output shape: {:?}",
-                encoder_out.shape()
-            );
+        let mut frame_offset = 0;
+        for (chunk_idx, encoder_out) in encoded.iter().enumerate() {
+            eprintln!("\n📦 Processing chunk {}/{}", chunk_idx + 1, encoded.len());
+            eprintln!("   Encoder output shape: {:?}", encoder_out.shape());
 
             // Decode each frame with greedy search
             // Pass both the decoder_out and token from previous chunk
             let (chunk_tokens, final_token, final_decoder_out, stats) = self
                 .decode_frames_with_state(
-                    &encoder_out,
+                    encoder_out,
                     decoder_out_opt.take(),
                     last_decoder_token,
+                    &all_tokens,
+                    frame_offset,
                 )?;
+            frame_offset += encoder_out.shape()[2];
             eprintln!(
                 "   Chunk produced {} tokens (final_token={})",
                 chunk_tokens.len(),
@@ -771,7 +1838,7 @@ impl OrtRecognizer {
         eprintln!(
             "\n📊 TOTAL: {} tokens from {} chunks",
             all_tokens.len(),
-            chunks.len()
+            encoded.len()
         );
         eprintln!(
             "📊 PREDICTIONS: {} blank, {} non-blank ({:.1}% blank)",
@@ -787,93 +1854,6 @@ impl OrtRecognizer {
         Ok(text)
     }
 
-    /// Run encoder on feature chunk
-    ///
-    /// Automatically detects and applies correct input format based on model:
-    /// - 0.6B models: (batch, 128 features, 80 time) - TRANSPOSED
-    /// - 1.1B models: (batch, 80 time, features) - NOT TRANSPOSED
-    ///
-    /// Detection happens in constructor based on encoder input shape.
-    fn run_encoder(&mut self, features: &Array2<f32>) -> Result<Array3<f32>> {
-        // Prepare input tensors
-        let batch_size = 1;
-        let num_frames = features.nrows();
-        let num_features = features.ncols();
-
-        // NOTE: Encoder can handle variable frame counts (tested with 615 frames successfully in Python)
-        // No need for fixed 80-frame chunks - the model uses dynamic shape inference
-        debug!(
-            "Encoder processing {} frames x {} features",
-            num_frames, num_features
-        );
-
-        // CRITICAL FIX: Encoder ALWAYS expects (batch, features, time) format!
-        // The ONNX input signature shows: audio_signal: [batch, 80, time]
-        // This means ALL models expect features dimension FIRST, then time dimension.
-        // The previous logic was INVERTED - causing wrong input shape for 1.1B model!
-        let (shape, audio_data) = {
-            // TRANSPOSE FOR ALL MODELS: (batch, num_features, num_frames)
-            // Layout: Row-major transposed - all frames for feature 0, then feature 1, etc.
-            debug!(
-                "Using TRANSPOSED input format: (batch, features={}, time={})",
-                num_features, num_frames
-            );
-            let mut data = Vec::with_capacity(batch_size * num_frames * num_features);
-            for col_idx in 0..num_features {
-                for row in features.outer_iter() {
-                    data.push(row[col_idx]);
-                }
-            }
-            (vec![batch_size, num_features, num_frames], data)
-        };
-
-        let audio_signal =
-            Tensor::from_array((shape, audio_data.into_boxed_slice())).map_err(|e| {
-                SttError::InferenceError(format!("Failed to create audio tensor: {}", e))
-            })?;
-
-        // length: (batch=1,)
-        let length_data = vec![num_frames as i64];
-        let length_tensor = Tensor::from_array((vec![batch_size], length_data.into_boxed_slice()))
-            .map_err(|e| {
-                SttError::InferenceError(format!("Failed to create length tensor: {}", e))
-            })?;
-
-        // Run encoder
-        let outputs = self
-            .encoder
-            .run(ort::inputs!["audio_signal" => audio_signal, "length" => length_tensor])
-            .map_err(|e| SttError::InferenceError(format!("Encoder inference failed: {}", e)))?;
-
-        // Extract encoder output (first output is the encoded features)
-        let encoder_out_tensor = &outputs[0];
-        let (shape, data) = encoder_out_tensor
-            .try_extract_tensor::<f32>()
-            .map_err(|e| {
-                SttError::InferenceError(format!("Failed to extract encoder output: {}", e))
-            })?;
-
-        // Convert to ndarray - shape should be (batch, encoder_dim, num_frames)
-        let encoder_out = Array3::from_shape_vec(
-            (shape[0] as usize, shape[1] as usize, shape[2] as usize),
-            data.to_vec(),
-        )
-        .map_err(|e| {
-            SttError::InferenceError(format!("Failed to reshape encoder output: {}", e))
-        })?;
-
-        // Debug: Encoder output statistics
-        let enc_min = data.iter().fold(f32::INFINITY, |a, &b| a.min(b));
-        let enc_max = data.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b));
-        let enc_mean = data.iter().sum::<f32>() / data.len() as f32;
-        debug!(
-            "Encoder output stats: min={:.6}, max={:.6}, mean={:.6}",
-            enc_min, enc_max, enc_mean
-        );
-
-        Ok(encoder_out)
-    }
-
     /// Decode frames using TDT greedy search with cross-chunk state persistence
     ///
     /// **REWRITTEN TO MATCH sherpa-onnx C++ EXACTLY + cross-chunk support**
@@ -891,6 +1871,8 @@ impl OrtRecognizer {
     /// - encoder_out: Encoder output for this chunk
     /// - prev_decoder_out: Decoder output from end of previous chunk (None for first chunk)
     /// - initial_token: Last token from previous chunk (blank_id for first chunk)
+    /// - prev_tokens: Tokens emitted by previous chunks (language model history)
+    /// - frame_offset: Encoder frames before this chunk (emission timing)
     ///
     /// Returns: (tokens, final_decoder_token, final_decoder_out, (blank_count, nonblank_count)) for next chunk
     fn decode_frames_with_state(
@@ -898,6 +1880,8 @@ impl OrtRecognizer {
         encoder_out: &Array3<f32>,
         prev_decoder_out: Option<Array1<f32>>,
         initial_token: i64,
+        prev_tokens: &[i64],
+        frame_offset: usize,
     ) -> Result<DecoderState> {
         // Encoder output shape: (batch, encoder_dim, num_frames)
         let _encoder_dim = encoder_out.shape()[1];
@@ -913,7 +1897,7 @@ impl OrtRecognizer {
         let _blank_count = 0_usize;
         let _nonblank_count = 0_usize;
 
-        let max_tokens_per_frame = 5; // sherpa-onnx uses 5 for TDT
+        let tdt = self.tdt;
 
         // C++ line 108-113: Initialize decoder output
         // If we have decoder_out from previous chunk, reuse it (don't call run_decoder!)
@@ -972,15 +1956,19 @@ impl OrtRecognizer {
                     iteration_count, t
                 );
             }
-            let logits = self.run_joiner(&encoder_frame, &decoder_out)?;
+            let mut logits = self.run_joiner(&encoder_frame, &decoder_out)?;
             if iteration_count <= 5 {
                 eprintln!("   Iteration {}: run_joiner() returned", iteration_count);
             }
 
             // C++ line 136-141: Split logits into token and duration
-            let logits_slice = logits.as_slice().unwrap();
-            let output_size = logits_slice.len();
+            let output_size = logits.len();
             let num_durations = output_size - vocab_size;
+            tdt.penalize_blank(
+                &mut logits.as_slice_mut().unwrap()[..vocab_size],
+                blank_id as usize,
+            );
+            let logits_slice = logits.as_slice().unwrap();
 
             let token_logits = &logits_slice[0..vocab_size];
             let duration_logits = &logits_slice[vocab_size..];
@@ -991,16 +1979,41 @@ impl OrtRecognizer {
                 .enumerate()
                 .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
                 .unwrap();
+
+            // Let the language model re-rank the word piece (never blank vs non-blank)
+            let y = match &self.fusion {
+                Some(fusion) => {
+                    let emitted: Vec<i64> = prev_tokens.iter().chain(&tokens).copied().collect();
+                    fusion.choose(token_logits, y, blank_id as usize, &self.tokens, &emitted)
+                }
+                None => y,
+            };
+            let y = match &self.bias {
+                Some(bias) => {
+                    let emitted: Vec<i64> = prev_tokens.iter().chain(&tokens).copied().collect();
+                    bias.choose(token_logits, y, blank_id as usize, &self.tokens, &emitted)
+                }
+                None => y,
+            };
+            let y = match &self.lexicon {
+                Some(lexicon) => {
+                    let emitted: Vec<i64> = prev_tokens.iter().chain(&tokens).copied().collect();
+                    lexicon.choose(token_logits, y, blank_id as usize, &emitted)
+                }
+                None => y,
+            };
             let y = y as i64;
 
             // C++ line 148-150: Greedy selection for duration (note: can be 0!)
             let mut skip = if num_durations > 0 {
-                duration_logits
-                    .iter()
-                    .enumerate()
-                    .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
-                    .map(|(idx, _)| idx)
-                    .unwrap_or(0)
+                tdt.skip(
+                    duration_logits
+                        .iter()
+                        .enumerate()
+                        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+                        .map(|(idx, _)| idx)
+                        .unwrap_or(0),
+                )
             } else {
                 0
             };
@@ -1015,6 +2028,8 @@ impl OrtRecognizer {
             // C++ line 152-165: If non-blank, emit token and update decoder
             if y != blank_id {
                 tokens.push(y);
+                self.emissions
+                    .push(Emission::from_logits(y, token_logits).at(frame_offset + t));
                 timestamps.push(t);
                 durations_vec.push(skip);
 
@@ -1068,7 +2083,7 @@ impl OrtRecognizer {
             }
 
             // C++ line 171-174: If max tokens reached, force skip=1
-            if tokens_this_frame >= max_tokens_per_frame {
+            if tokens_this_frame >= tdt.max_tokens_per_frame {
                 tokens_this_frame = 0;
                 skip = 1;
             }
@@ -1152,67 +2167,73 @@ impl OrtRecognizer {
         let batch_size = 1;
         let seq_len = tokens.len();
 
-        // Prepare targets tensor: (batch, seq_len) - convert i64 to i32
+        // Prepare targets: (batch, seq_len) - convert i64 to i32
         let targets_i32: Vec<i32> = tokens.iter().map(|&t| t as i32).collect();
-        let targets =
-            Tensor::from_array((vec![batch_size, seq_len], targets_i32.into_boxed_slice()))
-                .map_err(|e| {
-                    SttError::InferenceError(format!("Failed to create targets tensor: {}", e))
-                })?;
-
-        // Prepare target_length tensor: (batch,)
-        let target_length =
-            Tensor::from_array((vec![batch_size], vec![seq_len as i32].into_boxed_slice()))
-                .map_err(|e| {
-                    SttError::InferenceError(format!(
-                        "Failed to create target_length tensor: {}",
-                        e
-                    ))
-                })?;
 
         // Initialize or reuse decoder states
-        let hidden_size = self.config.decoder_hidden_size;
+        let hidden_size = self.format.decoder_hidden_size;
         if self.decoder_state1.is_none() {
             // Initialize states to zeros: (2, batch, hidden_size)
             self.decoder_state1 = Some(Array3::zeros((2, batch_size, hidden_size)));
             self.decoder_state2 = Some(Array3::zeros((2, 1, hidden_size)));
         }
-
-        let state1_data = self
-            .decoder_state1
-            .as_ref()
-            .unwrap()
-            .as_slice()
-            .unwrap()
-            .to_vec();
-        let state1 = Tensor::from_array((
-            vec![2, batch_size, hidden_size],
-            state1_data.into_boxed_slice(),
-        ))
-        .map_err(|e| SttError::InferenceError(format!("Failed to create state1 tensor: {}", e)))?;
-
-        let state2_data = self
-            .decoder_state2
-            .as_ref()
-            .unwrap()
-            .as_slice()
-            .unwrap()
-            .to_vec();
-        let state2 = Tensor::from_array((vec![2, 1, hidden_size], state2_data.into_boxed_slice()))
-            .map_err(|e| {
-                SttError::InferenceError(format!("Failed to create state2 tensor: {}", e))
-            })?;
+        let state_shape = [2, batch_size, hidden_size];
+        let state1_data = self.decoder_state1.as_ref().unwrap().as_slice().unwrap();
+        let state2_data = self.decoder_state2.as_ref().unwrap().as_slice().unwrap();
 
         // Run decoder with all 4 inputs
-        let outputs = self
-            .decoder
-            .run(ort::inputs![
-                "targets" => targets,
-                "target_length" => target_length,
-                "states.1" => state1,
-                "onnx::Slice_3" => state2
-            ])
-            .map_err(|e| SttError::InferenceError(format!("Decoder inference failed: {}", e)))?;
+        let outputs = match self.bindings.as_mut() {
+            Some(bindings) => {
+                let binding = &mut bindings.decoder;
+                binding
+                    .bind_input("targets", &[batch_size, seq_len], &targets_i32)
+                    .and_then(|()| {
+                        binding.bind_input("target_length", &[batch_size], &[seq_len as i32])
+                    })
+                    .and_then(|()| binding.bind_input("states.1", &state_shape, state1_data))
+                    .and_then(|()| binding.bind_input("onnx::Slice_3", &state_shape, state2_data))
+                    .map_err(|e| {
+                        SttError::InferenceError(format!("Failed to bind decoder inputs: {}", e))
+                    })?;
+                binding.run(&mut self.decoder, &self.run_options)
+            }
+            None => {
+                let targets =
+                    Tensor::from_array((vec![batch_size, seq_len], targets_i32.into_boxed_slice()))
+                        .map_err(|e| {
+                            SttError::InferenceError(format!(
+                                "Failed to create targets tensor: {}",
+                                e
+                            ))
+                        })?;
+                let target_length =
+                    Tensor::from_array((vec![batch_size], vec![seq_len as i32].into_boxed_slice()))
+                        .map_err(|e| {
+                            SttError::InferenceError(format!(
+                                "Failed to create target_length tensor: {}",
+                                e
+                            ))
+                        })?;
+                let state1 = Tensor::from_array((state_shape.to_vec(), state1_data.to_vec()))
+                    .map_err(|e| {
+                        SttError::InferenceError(format!("Failed to create state1 tensor: {}", e))
+                    })?;
+                let state2 = Tensor::from_array((state_shape.to_vec(), state2_data.to_vec()))
+                    .map_err(|e| {
+                        SttError::InferenceError(format!("Failed to create state2 tensor: {}", e))
+                    })?;
+                self.decoder.run_with_options(
+                    ort::inputs![
+                        "targets" => targets,
+                        "target_length" => target_length,
+                        "states.1" => state1,
+                        "onnx::Slice_3" => state2
+                    ],
+                    &*self.run_options,
+                )
+            }
+        }
+        .map_err(|e| SttError::InferenceError(format!("Decoder inference failed: {}", e)))?;
 
         // Extract decoder output: outputs[0] is the decoder output (batch, 640, seq_len)
         let decoder_out_tensor = &outputs[0];
@@ -1320,28 +2341,54 @@ impl OrtRecognizer {
         debug!("Joiner inputs: encoder({:.3} to {:.3}, mean={:.3}), decoder({:.3} to {:.3}, mean={:.3})",
                enc_min, enc_max, enc_mean, dec_min, dec_max, dec_mean);
 
-        // Prepare joiner inputs
-        let encoder_input = Tensor::from_array((
-            vec![1, encoder_out.len(), 1], // (batch, 1024, 1)
-            encoder_out.to_vec().into_boxed_slice(),
-        ))
-        .map_err(|e| {
-            SttError::InferenceError(format!("Failed to create encoder input for joiner: {}", e))
+        // Joiner inputs: (batch, encoder_dim, 1) and (batch, hidden_size, 1)
+        let encoder_shape = [1, encoder_out.len(), 1];
+        let decoder_shape = [1, decoder_out.len(), 1];
+        let encoder_data = encoder_out.as_slice().ok_or_else(|| {
+            SttError::InferenceError("Encoder frame is not contiguous".to_string())
         })?;
-
-        let decoder_input = Tensor::from_array((
-            vec![1, decoder_out.len(), 1], // (batch, hidden_size, 1)
-            decoder_out.to_vec().into_boxed_slice(),
-        ))
-        .map_err(|e| {
-            SttError::InferenceError(format!("Failed to create decoder input for joiner: {}", e))
+        let decoder_data = decoder_out.as_slice().ok_or_else(|| {
+            SttError::InferenceError("Decoder output is not contiguous".to_string())
         })?;
 
         // Run joiner with correct input names
-        let outputs = self
-            .joiner
-            .run(ort::inputs!["encoder_outputs" => encoder_input, "decoder_outputs" => decoder_input])
-            .map_err(|e| SttError::InferenceError(format!("Joiner inference failed: {}", e)))?;
+        let outputs = match self.bindings.as_mut() {
+            Some(bindings) => {
+                let binding = &mut bindings.joiner;
+                binding
+                    .bind_input("encoder_outputs", &encoder_shape, encoder_data)
+                    .and_then(|()| binding.bind_input("decoder_outputs", &decoder_shape, decoder_data))
+                    .map_err(|e| {
+                        SttError::InferenceError(format!("Failed to bind joiner inputs: {}", e))
+                    })?;
+                binding.run(&mut self.joiner, &self.run_options)
+            }
+            None => {
+                let encoder_input =
+                    Tensor::from_array((encoder_shape.to_vec(), encoder_data.to_vec())).map_err(
+                        |e| {
+                            SttError::InferenceError(format!(
+                                "Failed to create encoder input for joiner: {}",
+                                e
+                            ))
+                        },
+                    )?;
+                let decoder_input =
+                    Tensor::from_array((decoder_shape.to_vec(), decoder_data.to_vec())).map_err(
+                        |e| {
+                            SttError::InferenceError(format!(
+                                "Failed to create decoder input for joiner: {}",
+                                e
+                            ))
+                        },
+                    )?;
+                self.joiner.run_with_options(
+                    ort::inputs!["encoder_outputs" => encoder_input, "decoder_outputs" => decoder_input],
+                    &*self.run_options,
+                )
+            }
+        }
+        .map_err(|e| SttError::InferenceError(format!("Joiner inference failed: {}", e)))?;
 
         // Extract logits from 4D tensor (batch, frames, frames, vocab_size)
         // With inputs (1, 1024, 1) and (1, hidden_size, 1), output is (1, 1, 1, vocab_size)
@@ -1427,7 +2474,15 @@ impl OrtRecognizer {
         eprintln!("   Input: {} tokens", tokens.len());
         eprintln!("   Token IDs: {:?}", tokens);
 
-        let result = tokens
+        let result = self.join_tokens(tokens);
+
+        eprintln!("   Output: '{}'", result);
+        result
+    }
+
+    /// Token IDs to text, skipping blank and unknown tokens
+    fn join_tokens(&self, tokens: &[i64]) -> String {
+        let text = tokens
             .iter()
             .filter_map(|&token_id| {
                 let idx = token_id as usize;
@@ -1442,9 +2497,10 @@ impl OrtRecognizer {
             .replace("▁", " ") // Replace BPE underscores with spaces
             .trim()
             .to_string();
-
-        eprintln!("   Output: '{}'", result);
-        result
+        match &self.lexicon {
+            Some(lexicon) => lexicon.apply(&text),
+            None => text,
+        }
     }
 
     /// Get model information
@@ -1459,10 +2515,209 @@ impl OrtRecognizer {
     }
 }
 
+/// Run `encoder` once over several feature chunks
+///
+/// Chunks may differ in length: shorter ones are zero-padded and the
+/// encoder is given each chunk's real length. Returns one
+/// (1, encoder_dim, frames) output per chunk, in order, cut to the frames
+/// the chunk's own features produced.
+fn run_encoder_batch(
+    encoder: &mut Session,
+    binding: Option<&mut PinnedBinding>,
+    feature_layout: FeatureLayout,
+    run_options: &RunOptions,
+    chunks: &[Array2<f32>],
+) -> Result<Vec<Array3<f32>>> {
+    let Some(first) = chunks.first() else {
+        return Ok(Vec::new());
+    };
+    // Prepare input tensors
+    let batch_size = chunks.len();
+    let num_frames = chunks.iter().map(|c| c.nrows()).max().unwrap_or(0);
+    let num_features = first.ncols();
+    if chunks.iter().any(|c| c.ncols() != num_features) {
+        return Err(SttError::invalid_input(
+            "Batched encoder chunks must all have the same feature count",
+        ));
+    }
+
+    // The encoder uses dynamic shape inference, so chunks are fed at
+    // their real length rather than padded to a fixed block
+    debug!(
+        "Encoder processing {} chunks of up to {} frames x {} features",
+        batch_size, num_frames, num_features
+    );
+
+    // NeMo exports take (batch, features, time); other exports may take
+    // (batch, time, features). Padding frames are zero either way.
+    let mut audio_data = Vec::with_capacity(batch_size * num_frames * num_features);
+    let shape = match feature_layout {
+        FeatureLayout::FeaturesFirst => {
+            for features in chunks {
+                for col_idx in 0..num_features {
+                    audio_data.extend(features.column(col_idx).iter());
+                    audio_data.extend(std::iter::repeat_n(0.0, num_frames - features.nrows()));
+                }
+            }
+            vec![batch_size, num_features, num_frames]
+        }
+        FeatureLayout::TimeFirst => {
+            for features in chunks {
+                audio_data.extend(features.iter());
+                audio_data.extend(std::iter::repeat_n(
+                    0.0,
+                    (num_frames - features.nrows()) * num_features,
+                ));
+            }
+            vec![batch_size, num_frames, num_features]
+        }
+    };
+    debug!("Encoder input {:?} ({:?})", shape, feature_layout);
+
+    // length: (batch,)
+    let length_data: Vec<i64> = chunks.iter().map(|c| c.nrows() as i64).collect();
+
+    // Run encoder
+    let outputs = match binding {
+        Some(binding) => {
+            binding
+                .bind_input("audio_signal", &shape, &audio_data)
+                .and_then(|()| binding.bind_input("length", &[batch_size], &length_data))
+                .map_err(|e| {
+                    SttError::InferenceError(format!("Failed to bind encoder inputs: {}", e))
+                })?;
+            binding.run(encoder, run_options)
+        }
+        None => {
+            let audio_signal =
+                Tensor::from_array((shape, audio_data.into_boxed_slice())).map_err(|e| {
+                    SttError::InferenceError(format!("Failed to create audio tensor: {}", e))
+                })?;
+            let length_tensor =
+                Tensor::from_array((vec![batch_size], length_data.into_boxed_slice())).map_err(
+                    |e| SttError::InferenceError(format!("Failed to create length tensor: {}", e)),
+                )?;
+            encoder.run_with_options(
+                ort::inputs!["audio_signal" => audio_signal, "length" => length_tensor],
+                run_options,
+            )
+        }
+    }
+    .map_err(|e| SttError::InferenceError(format!("Encoder inference failed: {}", e)))?;
+
+    // Extract encoder output (first output is the encoded features)
+    let encoder_out_tensor = &outputs[0];
+    let (shape, data) = encoder_out_tensor
+        .try_extract_tensor::<f32>()
+        .map_err(|e| {
+            SttError::InferenceError(format!("Failed to extract encoder output: {}", e))
+        })?;
+
+    // Convert to ndarray - shape should be (batch, encoder_dim, num_frames)
+    let encoder_out = Array3::from_shape_vec(
+        (shape[0] as usize, shape[1] as usize, shape[2] as usize),
+        data.to_vec(),
+    )
+    .map_err(|e| SttError::InferenceError(format!("Failed to reshape encoder output: {}", e)))?;
+
+    // Debug: Encoder output statistics
+    let enc_min = data.iter().fold(f32::INFINITY, |a, &b| a.min(b));
+    let enc_max = data.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+    let enc_mean = data.iter().sum::<f32>() / data.len() as f32;
+    debug!(
+        "Encoder output stats: min={:.6}, max={:.6}, mean={:.6}",
+        enc_min, enc_max, enc_mean
+    );
+
+    // Output frames of each chunk: the encoded lengths output when the
+    // export has one, otherwise the subsampled input length
+    let encoded_lengths: Vec<usize> = match outputs
+        .get("encoded_lengths")
+        .map(|lengths| lengths.try_extract_tensor::<i64>())
+    {
+        Some(Ok((_, lengths))) => lengths.iter().map(|&len| len.max(0) as usize).collect(),
+        _ => chunks
+            .iter()
+            .map(|c| c.nrows().div_ceil(window::SUBSAMPLING))
+            .collect(),
+    };
+
+    Ok(encoder_out
+        .axis_iter(Axis(0))
+        .zip(encoded_lengths)
+        .map(|(out, len)| {
+            let len = len.min(out.shape()[1]);
+            out.slice(s![.., ..len]).insert_axis(Axis(0)).to_owned()
+        })
+        .collect())
+}
+
+/// Shape of a session input, with -1 for dynamic dimensions, or empty if
+/// the session has no such tensor input
+pub(crate) fn input_shape(session: &Session, name: &str) -> Vec<i64> {
+    session
+        .inputs
+        .iter()
+        .find(|input| input.name == name)
+        .and_then(|input| input.input_type.tensor_shape())
+        .map(|shape| shape.to_vec())
+        .unwrap_or_default()
+}
+
+/// Infer weight precision from an ONNX file name (`encoder.int8.onnx` etc.)
+pub(crate) fn precision_from_path(path: &Path) -> &'static str {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if name.contains(".int8.") {
+        "int8"
+    } else if name.contains(".fp16.") {
+        "fp16"
+    } else {
+        "fp32"
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_precision_from_path() {
+        assert_eq!(
+            precision_from_path(Path::new("/m/encoder.int8.onnx")),
+            "int8"
+        );
+        assert_eq!(
+            precision_from_path(Path::new("/m/encoder.fp16.onnx")),
+            "fp16"
+        );
+        assert_eq!(precision_from_path(Path::new("/m/encoder.onnx")), "fp32");
+    }
+
+    #[test]
+    fn test_execution_target_from_provider() {
+        assert_eq!(ExecutionTarget::from_provider(None), ExecutionTarget::Cpu);
+        assert_eq!(
+            ExecutionTarget::from_provider(Some("cuda")),
+            ExecutionTarget::Gpu
+        );
+        assert_eq!(
+            ExecutionTarget::from_provider(Some("openvino")),
+            ExecutionTarget::OpenVino("GPU".to_string())
+        );
+        assert_eq!(
+            ExecutionTarget::from_provider(Some("openvino:npu")),
+            ExecutionTarget::OpenVino("NPU".to_string())
+        );
+        assert_eq!(ExecutionTarget::from(true), ExecutionTarget::Gpu);
+        assert_eq!(
+            ExecutionTarget::OpenVino("NPU".to_string()).provider_name(),
+            "OpenVINO"
+        );
+    }
+
     #[test]
     #[ignore] // Requires model files
     fn test_ort_recognizer_init() {
@@ -1473,4 +2728,41 @@ mod tests {
         }
         assert!(recognizer.is_ok());
     }
+
+    #[test]
+    #[ignore] // Requires model files
+    fn test_recognize_batch_matches_sequential() {
+        let model_dir = "/opt/swictation/models/sherpa-onnx-nemo-parakeet-tdt-0.6b-v3-int8";
+        let mut recognizer = OrtRecognizer::new(model_dir, false).unwrap();
+        // Short, long (several chunks) and repeated segments
+        let tone = |len: usize, freq: f32| -> Vec<f32> {
+            (0..len).map(|i| (i as f32 * freq).sin() * 0.1).collect()
+        };
+        let short = tone(16_000, 0.05);
+        let long = tone(16_000 * 3, 0.02);
+        let segments: Vec<&[f32]> = vec![&short, &long, &short];
+
+        let sequential: Vec<String> = segments
+            .iter()
+            .map(|s| recognizer.recognize_samples(s).unwrap())
+            .collect();
+        assert_eq!(recognizer.recognize_batch(&segments).unwrap(), sequential);
+        assert!(recognizer.recognize_batch(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    #[ignore] // Requires model files
+    fn test_encoder_workers_match_single_encoder() {
+        let model_dir = "/opt/swictation/models/sherpa-onnx-nemo-parakeet-tdt-0.6b-v3-int8";
+        let mut recognizer = OrtRecognizer::new(model_dir, false).unwrap();
+        // Long enough for several encoder windows
+        let samples: Vec<f32> = (0..16_000 * 60)
+            .map(|i| (i as f32 * 0.03).sin() * 0.1)
+            .collect();
+
+        let single = recognizer.recognize_samples(&samples).unwrap();
+        recognizer.set_encoder_workers(3).unwrap();
+        assert_eq!(recognizer.encoder_workers(), 3);
+        assert_eq!(recognizer.recognize_samples(&samples).unwrap(), single);
+    }
 }
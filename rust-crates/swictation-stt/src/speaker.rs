@@ -0,0 +1,188 @@
+//! Speaker embeddings and "Speaker 1 / Speaker 2" labels
+//!
+//! [`SpeakerEmbedder`] turns a stretch of speech into a fixed-length voice
+//! embedding with a speaker-verification model, and [`SpeakerClusters`]
+//! groups those embeddings online: each segment joins the closest speaker
+//! seen so far, or starts a new one when nobody is similar enough. Labels are
+//! numbered in order of first appearance, so the first voice in a recording
+//! is always Speaker 1.
+//!
+//! Expects a WeSpeaker-style ONNX export (ResNet or ECAPA): one input of
+//! 80-bin log-mel features shaped `(1, frames, 80)` and one output of shape
+//! `(1, dim)`. The model is small and runs on CPU.
+
+use crate::audio::{AudioProcessor, SAMPLE_RATE};
+use crate::error::{Result, SttError};
+use ort::{
+    session::{builder::GraphOptimizationLevel, Session},
+    value::Tensor,
+};
+use std::path::Path;
+use tracing::info;
+
+/// Mel bins the embedding models are trained on
+const N_MEL_FEATURES: usize = 80;
+
+/// Shorter speech says too little about the voice to be labelled
+const MIN_SPEECH_SECS: f32 = 0.5;
+
+/// Voice embeddings from a speaker-verification model
+pub struct SpeakerEmbedder {
+    session: Session,
+    audio: AudioProcessor,
+}
+
+impl SpeakerEmbedder {
+    /// Load the embedding model from `model_path` (an `.onnx` file)
+    pub fn new<P: AsRef<Path>>(model_path: P) -> Result<Self> {
+        let model_path = model_path.as_ref();
+        info!("Loading speaker model from {}", model_path.display());
+
+        let session = Session::builder()
+            .map_err(|e| SttError::model_load(format!("Failed to create session builder: {}", e)))?
+            .with_optimization_level(GraphOptimizationLevel::Level3)
+            .map_err(|e| SttError::model_load(format!("Failed to set optimization level: {}", e)))?
+            .with_intra_threads(1)
+            .map_err(|e| SttError::model_load(format!("Failed to set intra threads: {}", e)))?
+            .commit_from_file(model_path)
+            .map_err(|e| SttError::model_load(format!("Failed to load speaker model: {}", e)))?;
+        if session.inputs.len() != 1 || session.outputs.is_empty() {
+            return Err(SttError::model_load(format!(
+                "Speaker model should have one input and an embedding output, has {} inputs and {} outputs",
+                session.inputs.len(),
+                session.outputs.len()
+            )));
+        }
+
+        info!("✓ Speaker model loaded");
+        Ok(Self {
+            session,
+            audio: AudioProcessor::with_mel_features(N_MEL_FEATURES)?,
+        })
+    }
+
+    /// Unit-length embedding of the voice in `samples` (16 kHz mono), or
+    /// `None` when there is too little audio to tell
+    pub fn embed(&mut self, samples: &[f32]) -> Result<Option<Vec<f32>>> {
+        if (samples.len() as f32) < MIN_SPEECH_SECS * SAMPLE_RATE as f32 {
+            return Ok(None);
+        }
+        let features = self.audio.extract_mel_features(samples)?;
+        let frames = features.nrows();
+        let data: Vec<f32> = features.iter().copied().collect();
+        let input = Tensor::from_array((vec![1, frames, N_MEL_FEATURES], data.into_boxed_slice()))
+            .map_err(|e| SttError::inference(format!("Failed to create speaker input: {}", e)))?;
+        let outputs = self
+            .session
+            .run(ort::inputs![input])
+            .map_err(|e| SttError::inference(format!("Speaker model inference failed: {}", e)))?;
+        let (_, embedding) = outputs[0]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| SttError::inference(format!("Failed to extract embedding: {}", e)))?;
+
+        let norm = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+        if norm == 0.0 || !norm.is_finite() {
+            return Ok(None);
+        }
+        Ok(Some(embedding.iter().map(|x| x / norm).collect()))
+    }
+}
+
+/// Cosine similarity of two unit-length vectors
+fn similarity(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Speakers told apart by online clustering of their embeddings
+pub struct SpeakerClusters {
+    threshold: f32,
+    max_speakers: usize,
+    /// Sum of each speaker's embeddings; its direction is the centroid
+    centroids: Vec<Vec<f32>>,
+}
+
+impl SpeakerClusters {
+    /// `threshold` is the cosine similarity above which an embedding is the
+    /// same speaker; once `max_speakers` are known every embedding joins the
+    /// closest of them
+    pub fn new(threshold: f32, max_speakers: usize) -> Self {
+        Self {
+            threshold,
+            max_speakers: max_speakers.max(1),
+            centroids: Vec::new(),
+        }
+    }
+
+    /// Speaker number (from 1) of a unit-length embedding, learning from it
+    pub fn assign(&mut self, embedding: &[f32]) -> usize {
+        let closest = self
+            .centroids
+            .iter()
+            .map(|centroid| {
+                let norm = centroid.iter().map(|x| x * x).sum::<f32>().sqrt();
+                similarity(centroid, embedding) / norm.max(f32::EPSILON)
+            })
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        match closest {
+            Some((index, score))
+                if score >= self.threshold || self.centroids.len() >= self.max_speakers =>
+            {
+                for (sum, x) in self.centroids[index].iter_mut().zip(embedding) {
+                    *sum += x;
+                }
+                index + 1
+            }
+            _ => {
+                self.centroids.push(embedding.to_vec());
+                self.centroids.len()
+            }
+        }
+    }
+
+    /// Number of speakers told apart so far
+    pub fn speakers(&self) -> usize {
+        self.centroids.len()
+    }
+
+    /// Forget every speaker, e.g. before the next recording
+    pub fn clear(&mut self) {
+        self.centroids.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(v: &[f32]) -> Vec<f32> {
+        let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
+        v.iter().map(|x| x / norm).collect()
+    }
+
+    #[test]
+    fn test_clusters_number_speakers_in_order_of_appearance() {
+        let mut clusters = SpeakerClusters::new(0.7, 8);
+        let alice = unit(&[1.0, 0.1, 0.0]);
+        let bob = unit(&[0.0, 1.0, 0.2]);
+        assert_eq!(clusters.assign(&alice), 1);
+        assert_eq!(clusters.assign(&bob), 2);
+        assert_eq!(clusters.assign(&unit(&[0.9, 0.2, 0.1])), 1);
+        assert_eq!(clusters.assign(&unit(&[0.1, 0.9, 0.3])), 2);
+        assert_eq!(clusters.speakers(), 2);
+
+        clusters.clear();
+        assert_eq!(clusters.assign(&bob), 1);
+    }
+
+    #[test]
+    fn test_clusters_stop_at_max_speakers() {
+        let mut clusters = SpeakerClusters::new(0.9, 2);
+        assert_eq!(clusters.assign(&unit(&[1.0, 0.0, 0.0])), 1);
+        assert_eq!(clusters.assign(&unit(&[0.0, 1.0, 0.0])), 2);
+        // A third voice goes to whichever known speaker is closest
+        assert_eq!(clusters.assign(&unit(&[0.2, 1.0, 1.0])), 2);
+        assert_eq!(clusters.speakers(), 2);
+    }
+}
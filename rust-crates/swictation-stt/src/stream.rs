@@ -0,0 +1,402 @@
+//! Partial hypotheses while audio is still arriving
+//!
+//! Parakeet-TDT's encoder attends over a whole window (see
+//! [`crate::window`]) and its features are normalized per utterance, so
+//! audio cannot be encoded piecewise as it arrives. Streaming therefore
+//! re-decodes everything fed so far once enough new audio has arrived, but
+//! keeps the encoder output of windows that have settled, so a long
+//! utterance only re-encodes its last window or two. Partials may still
+//! change as context accumulates, and the final result equals a one-shot
+//! `recognize_samples` over the same audio.
+//!
+//! [`RecognitionStream`] wraps the same calls for callers holding an async
+//! audio source, so they need not buffer and feed it themselves. Inference
+//! runs on a worker thread, so polling never blocks the async runtime.
+
+use crate::engine::{RecognitionResult, SttEngine};
+use crate::error::{Result, SttError};
+use futures_core::Stream;
+use ndarray::Array3;
+use std::pin::Pin;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// New audio (in samples at 16 kHz) between partial hypotheses
+pub const DEFAULT_PARTIAL_INTERVAL: usize = 8000;
+
+/// Audio of one streaming recognition
+#[derive(Debug)]
+pub struct StreamState {
+    samples: Vec<f32>,
+    /// Buffer length at the last partial decode
+    decoded_len: usize,
+    interval: usize,
+    /// Last partial hypothesis, so unchanged ones are not reported again
+    last_partial: String,
+    /// Encoder output of the leading windows that have settled
+    settled: Vec<Array3<f32>>,
+}
+
+impl StreamState {
+    pub fn new(interval: usize) -> Self {
+        Self {
+            samples: Vec::new(),
+            decoded_len: 0,
+            interval: interval.max(1),
+            last_partial: String::new(),
+            settled: Vec::new(),
+        }
+    }
+
+    /// Append audio, returning whether a partial decode is due
+    pub fn push(&mut self, samples: &[f32]) -> bool {
+        self.samples.extend_from_slice(samples);
+        self.samples.len() - self.decoded_len >= self.interval
+    }
+
+    /// Everything fed so far
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    /// Record a partial decode of the whole buffer, returning the text if it
+    /// differs from the previous partial
+    pub fn partial(&mut self, text: String) -> Option<String> {
+        self.decoded_len = self.samples.len();
+        if text == self.last_partial {
+            return None;
+        }
+        self.last_partial = text.clone();
+        Some(text)
+    }
+
+    /// Encoder output kept for the first windows, in order
+    pub fn settled(&self) -> &[Array3<f32>] {
+        &self.settled
+    }
+
+    /// Keep the encoder output of the next window, which has settled
+    pub fn settle(&mut self, encoded: Array3<f32>) {
+        self.settled.push(encoded);
+    }
+
+    pub fn into_samples(self) -> Vec<f32> {
+        self.samples
+    }
+}
+
+/// One result of [`SttEngine::recognize_stream`]
+#[derive(Debug, Clone)]
+pub enum StreamUpdate {
+    /// Hypothesis for the audio so far; later ones may differ
+    Partial(String),
+    /// Result for all of the audio, once the source has ended
+    Final(RecognitionResult),
+}
+
+/// The streaming calls [`RecognitionStream`] drives
+pub trait StreamEngine: Send + 'static {
+    fn start_stream(&mut self) -> Result<()>;
+    fn feed(&mut self, audio: &[f32]) -> Result<Option<String>>;
+    fn finalize(&mut self) -> Result<RecognitionResult>;
+}
+
+impl StreamEngine for SttEngine {
+    fn start_stream(&mut self) -> Result<()> {
+        SttEngine::start_stream(self)
+    }
+
+    fn feed(&mut self, audio: &[f32]) -> Result<Option<String>> {
+        SttEngine::feed(self, audio)
+    }
+
+    fn finalize(&mut self) -> Result<RecognitionResult> {
+        SttEngine::finalize(self)
+    }
+}
+
+/// Work for the inference thread
+enum Request {
+    Feed(Vec<f32>),
+    Finish,
+}
+
+/// Inference thread of a [`RecognitionStream`]
+struct Worker {
+    requests: Sender<Request>,
+    results: Receiver<Result<StreamUpdate>>,
+    /// Woken when a result is ready
+    waker: Arc<Mutex<Option<Waker>>>,
+}
+
+impl Worker {
+    fn spawn<E: StreamEngine>(engine: Arc<Mutex<E>>) -> Self {
+        let (requests, request_rx) = mpsc::channel();
+        let (result_tx, results) = mpsc::channel();
+        let waker = Arc::new(Mutex::new(None::<Waker>));
+        let wake = Arc::clone(&waker);
+        std::thread::Builder::new()
+            .name("swictation-stt-stream".into())
+            .spawn(move || {
+                let send = |update: Result<StreamUpdate>| {
+                    let sent = result_tx.send(update).is_ok();
+                    if let Some(waker) = wake.lock().unwrap().take() {
+                        waker.wake();
+                    }
+                    sent
+                };
+                let Ok(mut engine) = engine.lock() else {
+                    send(Err(SttError::inference("STT engine lock poisoned")));
+                    return;
+                };
+                run_stream(&mut *engine, &request_rx, send);
+            })
+            .expect("failed to spawn the streaming inference thread");
+        Self {
+            requests,
+            results,
+            waker,
+        }
+    }
+}
+
+/// Start a stream on `engine`, feed it each request and report partials,
+/// the final result or the first error through `send` (which returns
+/// `false` once nobody is listening)
+fn run_stream<E: StreamEngine + ?Sized>(
+    engine: &mut E,
+    requests: &Receiver<Request>,
+    send: impl Fn(Result<StreamUpdate>) -> bool,
+) {
+    if let Err(e) = engine.start_stream() {
+        send(Err(e));
+        return;
+    }
+    while let Ok(request) = requests.recv() {
+        match request {
+            Request::Feed(samples) => match engine.feed(&samples) {
+                Ok(Some(text)) => {
+                    if !send(Ok(StreamUpdate::Partial(text))) {
+                        return;
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    send(Err(e));
+                    return;
+                }
+            },
+            Request::Finish => {
+                send(engine.finalize().map(StreamUpdate::Final));
+                return;
+            }
+        }
+    }
+}
+
+/// Partial and final results for an audio source, recognized as it
+/// produces audio (see [`SttEngine::recognize_stream`])
+///
+/// Audio is handed to a worker thread that holds the engine's lock until
+/// the stream ends or is dropped. Ends after the [`StreamUpdate::Final`]
+/// result or the first error.
+pub struct RecognitionStream<S, E: StreamEngine = SttEngine> {
+    engine: Arc<Mutex<E>>,
+    audio: S,
+    worker: Option<Worker>,
+    audio_done: bool,
+    done: bool,
+}
+
+impl<S, E: StreamEngine> RecognitionStream<S, E> {
+    pub fn new(engine: Arc<Mutex<E>>, audio: S) -> Self {
+        Self {
+            engine,
+            audio,
+            worker: None,
+            audio_done: false,
+            done: false,
+        }
+    }
+}
+
+impl<S, E> Stream for RecognitionStream<S, E>
+where
+    S: Stream<Item = Vec<f32>> + Unpin,
+    E: StreamEngine,
+{
+    type Item = Result<StreamUpdate>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.done {
+            return Poll::Ready(None);
+        }
+        let engine = &this.engine;
+        let worker = this
+            .worker
+            .get_or_insert_with(|| Worker::spawn(Arc::clone(engine)));
+        // Registered before checking for results so none is missed
+        *worker.waker.lock().unwrap() = Some(cx.waker().clone());
+
+        // Hand whatever the source has ready to the worker
+        while !this.audio_done {
+            match Pin::new(&mut this.audio).poll_next(cx) {
+                Poll::Ready(Some(samples)) => {
+                    let _ = worker.requests.send(Request::Feed(samples));
+                }
+                Poll::Ready(None) => {
+                    this.audio_done = true;
+                    let _ = worker.requests.send(Request::Finish);
+                }
+                Poll::Pending => break,
+            }
+        }
+
+        match worker.results.try_recv() {
+            Ok(update) => {
+                this.done = !matches!(update, Ok(StreamUpdate::Partial(_)));
+                Poll::Ready(Some(update))
+            }
+            Err(TryRecvError::Empty) => Poll::Pending,
+            Err(TryRecvError::Disconnected) => {
+                this.done = true;
+                Poll::Ready(Some(Err(SttError::inference(
+                    "streaming inference thread stopped",
+                ))))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Engine that reports the number of samples fed as its partials
+    #[derive(Default)]
+    struct CountingEngine {
+        samples: usize,
+        started: bool,
+        fail_start: bool,
+        /// Fail on feeding this many samples in total
+        fail_at: Option<usize>,
+    }
+
+    impl StreamEngine for CountingEngine {
+        fn start_stream(&mut self) -> Result<()> {
+            if self.fail_start {
+                return Err(SttError::inference("no model"));
+            }
+            self.started = true;
+            self.samples = 0;
+            Ok(())
+        }
+
+        fn feed(&mut self, audio: &[f32]) -> Result<Option<String>> {
+            assert!(self.started, "fed before start_stream");
+            self.samples += audio.len();
+            if Some(self.samples) == self.fail_at {
+                return Err(SttError::inference("decode failed"));
+            }
+            Ok(self.samples.is_multiple_of(2).then(|| self.samples.to_string()))
+        }
+
+        fn finalize(&mut self) -> Result<RecognitionResult> {
+            Ok(RecognitionResult {
+                text: format!("{} samples", self.samples),
+                confidence: 1.0,
+                processing_time_ms: 0.0,
+                alternatives: Vec::new(),
+                words: Vec::new(),
+            })
+        }
+    }
+
+    /// Audio source with every chunk ready at once
+    struct Chunks(VecDeque<Vec<f32>>);
+
+    impl Stream for Chunks {
+        type Item = Vec<f32>;
+
+        fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Vec<f32>>> {
+            Poll::Ready(self.0.pop_front())
+        }
+    }
+
+    /// Poll to the end, waiting on the worker by spinning
+    fn collect(engine: CountingEngine, chunk_sizes: &[usize]) -> Vec<Result<StreamUpdate>> {
+        let chunks = chunk_sizes.iter().map(|&n| vec![0.0; n]).collect();
+        let mut stream = RecognitionStream::new(Arc::new(Mutex::new(engine)), Chunks(chunks));
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut updates = Vec::new();
+        loop {
+            match Pin::new(&mut stream).poll_next(&mut cx) {
+                Poll::Ready(Some(update)) => updates.push(update),
+                Poll::Ready(None) => return updates,
+                Poll::Pending => std::thread::yield_now(),
+            }
+        }
+    }
+
+    fn describe(updates: &[Result<StreamUpdate>]) -> Vec<String> {
+        updates
+            .iter()
+            .map(|update| match update {
+                Ok(StreamUpdate::Partial(text)) => format!("partial {}", text),
+                Ok(StreamUpdate::Final(result)) => format!("final {}", result.text),
+                Err(e) => format!("error {}", e),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_stream_reports_partials_then_final() {
+        let updates = collect(CountingEngine::default(), &[2, 1, 1, 3]);
+        assert_eq!(
+            describe(&updates),
+            ["partial 2", "partial 4", "final 7 samples"]
+        );
+    }
+
+    #[test]
+    fn test_stream_ends_on_start_error() {
+        let engine = CountingEngine {
+            fail_start: true,
+            ..Default::default()
+        };
+        let updates = collect(engine, &[2, 2]);
+        assert_eq!(describe(&updates), ["error Inference error: no model"]);
+    }
+
+    #[test]
+    fn test_stream_ends_on_feed_error() {
+        let engine = CountingEngine {
+            fail_at: Some(4),
+            ..Default::default()
+        };
+        let updates = collect(engine, &[2, 2, 2]);
+        assert_eq!(
+            describe(&updates),
+            ["partial 2", "error Inference error: decode failed"]
+        );
+    }
+
+    #[test]
+    fn test_partial_due_after_interval() {
+        let mut stream = StreamState::new(8000);
+        assert!(!stream.push(&[0.0; 4000]));
+        assert!(stream.push(&[0.0; 4000]));
+
+        assert_eq!(stream.partial("hello".into()).as_deref(), Some("hello"));
+        // The interval counts from the last decode
+        assert!(!stream.push(&[0.0; 7999]));
+        assert!(stream.push(&[0.0; 1]));
+
+        // Unchanged text is not reported again
+        assert_eq!(stream.partial("hello".into()), None);
+        assert_eq!(stream.into_samples().len(), 16000);
+    }
+}
@@ -0,0 +1,924 @@
+//! Whisper encoder/decoder recognizer via direct ONNX Runtime
+//!
+//! For machines without Parakeet-compatible hardware or for dictation in
+//! languages Parakeet does not cover. Expects a model directory in the
+//! sherpa-onnx Whisper export layout:
+//!
+//! ```text
+//! whisper-small-onnx/
+//! ├── encoder.onnx        (or small-encoder.onnx / *.int8.onnx)
+//! ├── decoder.onnx        (or small-decoder.onnx / *.int8.onnx)
+//! └── tokens.txt          (base64 token bytes and id per line)
+//! ```
+//!
+//! Dimensions and special tokens come from the encoder's ONNX metadata.
+//! Audio is decoded in 30-second windows, each greedily from the
+//! `<|startoftranscript|>` prompt without timestamps. Multilingual models
+//! detect the language of each window unless one is set, optionally among
+//! a few expected languages only ([`WhisperRecognizer::set_languages`]).
+//! Whisper writes
+//! punctuation and casing itself; phrase biasing, lexicons, shallow fusion
+//! and beam search only apply to Parakeet.
+
+use crate::audio::{SAMPLE_RATE, WIN_LENGTH};
+use crate::beam::log_softmax;
+use crate::confidence::{self, WordConfidence};
+use crate::error::{Result, SttError};
+use crate::nbest::Hypothesis;
+use crate::recognizer_ort::{precision_from_path, CancelHandle};
+use crate::stream::{StreamState, DEFAULT_PARTIAL_INTERVAL};
+use base64::Engine as _;
+use ndarray::Array2;
+#[cfg(target_os = "macos")]
+use ort::execution_providers::{
+    self as ep,
+    coreml::{CoreMLComputeUnits, CoreMLModelFormat},
+};
+use ort::{
+    session::{builder::GraphOptimizationLevel, RunOptions, Session},
+    value::{DynValue, Tensor},
+};
+use rustfft::{num_complex::Complex, Fft, FftPlanner};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{debug, info};
+
+/// Whisper STFT parameters (fixed by the model)
+const N_FFT: usize = 400;
+const HOP_LENGTH: usize = 160;
+/// Audio per encoder window: 30 seconds
+const WINDOW_SAMPLES: usize = 30 * SAMPLE_RATE as usize;
+/// Mel frames per encoder window
+const WINDOW_FRAMES: usize = WINDOW_SAMPLES / HOP_LENGTH;
+
+/// Model dimensions and special tokens from the ONNX metadata
+#[derive(Debug, Clone, PartialEq)]
+struct WhisperConfig {
+    n_mels: usize,
+    n_text_layer: usize,
+    n_text_ctx: usize,
+    n_text_state: usize,
+    /// `<|startoftranscript|>` and, for multilingual models, language and task
+    sot_sequence: Vec<i64>,
+    eot: i64,
+    no_timestamps: i64,
+    multilingual: bool,
+    language_tokens: Vec<i64>,
+    language_codes: Vec<String>,
+}
+
+impl WhisperConfig {
+    fn from_metadata(meta: &HashMap<String, String>) -> Result<Self> {
+        let get = |key: &str| {
+            meta.get(key)
+                .map(|v| v.trim())
+                .ok_or_else(|| SttError::model_load(format!("Whisper metadata lacks '{}'", key)))
+        };
+        let int = |key: &str| {
+            get(key)?.parse::<i64>().map_err(|e| {
+                SttError::model_load(format!("Invalid Whisper metadata '{}': {}", key, e))
+            })
+        };
+        let ints = |key: &str| -> Result<Vec<i64>> {
+            get(key)?
+                .split(',')
+                .filter(|v| !v.trim().is_empty())
+                .map(|v| {
+                    v.trim().parse::<i64>().map_err(|e| {
+                        SttError::model_load(format!("Invalid Whisper metadata '{}': {}", key, e))
+                    })
+                })
+                .collect()
+        };
+
+        let multilingual = int("is_multilingual")? != 0;
+        let (language_tokens, language_codes) = if multilingual {
+            let codes: Vec<String> = get("all_language_codes")?
+                .split(',')
+                .map(|c| c.trim().to_string())
+                .collect();
+            (ints("all_language_tokens")?, codes)
+        } else {
+            (Vec::new(), Vec::new())
+        };
+        if language_tokens.len() != language_codes.len() {
+            return Err(SttError::model_load(
+                "Whisper metadata has mismatched language tokens and codes",
+            ));
+        }
+
+        Ok(Self {
+            n_mels: int("n_mels")? as usize,
+            n_text_layer: int("n_text_layer")? as usize,
+            n_text_ctx: int("n_text_ctx")? as usize,
+            n_text_state: int("n_text_state")? as usize,
+            sot_sequence: ints("sot_sequence")?,
+            eot: int("eot")?,
+            no_timestamps: int("no_timestamps")?,
+            multilingual,
+            language_tokens,
+            language_codes,
+        })
+    }
+
+    /// Language token for `code` (`None` for English on English-only models)
+    fn language_token(&self, code: &str) -> Result<Option<i64>> {
+        if !self.multilingual {
+            return if code == "en" {
+                Ok(None)
+            } else {
+                Err(SttError::config(format!(
+                    "This Whisper model is English-only; cannot transcribe '{}'",
+                    code
+                )))
+            };
+        }
+        self.language_codes
+            .iter()
+            .position(|c| c == code)
+            .map(|index| Some(self.language_tokens[index]))
+            .ok_or_else(|| SttError::config(format!("Unknown Whisper language '{}'", code)))
+    }
+
+    fn language_code(&self, token: i64) -> Option<&str> {
+        let index = self.language_tokens.iter().position(|&t| t == token)?;
+        Some(self.language_codes[index].as_str())
+    }
+
+    /// Likeliest language token among `candidates` (all languages when
+    /// empty) after `<|startoftranscript|>`, with its probability among them
+    fn pick_language(&self, logits: &[f32], candidates: &[i64]) -> Option<(i64, f32)> {
+        let candidates: Vec<i64> = if candidates.is_empty() {
+            &self.language_tokens
+        } else {
+            candidates
+        }
+        .iter()
+        .copied()
+        .filter(|&id| (id as usize) < logits.len())
+        .collect();
+        let scores: Vec<f32> = candidates.iter().map(|&id| logits[id as usize]).collect();
+        let probs = log_softmax(&scores);
+        let (best, log_prob) = probs.iter().enumerate().max_by(|a, b| a.1.total_cmp(b.1))?;
+        Some((candidates[best], log_prob.exp()))
+    }
+
+    /// Decoder prompt: the start-of-transcript sequence with `language`
+    /// (for multilingual models), then `<|notimestamps|>`
+    fn prompt(&self, language: Option<i64>) -> Vec<i64> {
+        let mut prompt = self.sot_sequence.clone();
+        if let (true, Some(language)) = (self.multilingual, language) {
+            if prompt.len() > 1 {
+                prompt[1] = language;
+            }
+        }
+        prompt.push(self.no_timestamps);
+        prompt
+    }
+}
+
+/// Language found by [`WhisperRecognizer::detect_language`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectedLanguage {
+    /// Whisper language code ("en", "de", ...)
+    pub code: String,
+    /// Probability among the candidate languages (0.0 to 1.0)
+    pub probability: f32,
+}
+
+/// Whisper recognizer (see the module docs)
+pub struct WhisperRecognizer {
+    encoder: Session,
+    decoder: Session,
+    /// Bytes of each text token, by id
+    tokens: Vec<Vec<u8>>,
+    config: WhisperConfig,
+    features: LogMel,
+    model_path: PathBuf,
+    use_gpu: bool,
+    precision: &'static str,
+    size: &'static str,
+    /// Forced language token (`None` detects the language per window)
+    language: Option<i64>,
+    /// Language tokens detection chooses from (empty = all)
+    candidates: Vec<i64>,
+    /// Tokens of the last recognition with their probabilities
+    emitted: Vec<(i64, f32)>,
+    last_text: String,
+    // Shared by every session run so a watchdog can terminate inference
+    run_options: Arc<RunOptions>,
+    stream: Option<StreamState>,
+}
+
+impl WhisperRecognizer {
+    /// Load a Whisper model directory (see the module docs)
+    ///
+    /// On CPU the INT8 models are preferred, on GPU the FP32 ones.
+    pub fn new<P: AsRef<Path>>(model_dir: P, use_gpu: bool) -> Result<Self> {
+        let model_path = model_dir.as_ref().to_path_buf();
+        info!("Loading Whisper model from {}", model_path.display());
+
+        let tokens_path = find_file(&model_path, "tokens", "txt", false)?;
+        let contents = std::fs::read_to_string(&tokens_path).map_err(|e| {
+            SttError::model_load(format!("Failed to read {}: {}", tokens_path.display(), e))
+        })?;
+        let tokens = parse_tokens(&contents)?;
+
+        let encoder_path = find_file(&model_path, "encoder", "onnx", !use_gpu)?;
+        let decoder_path = find_file(&model_path, "decoder", "onnx", !use_gpu)?;
+
+        // External weights of the large models resolve against the working
+        // directory (see OrtRecognizer::new)
+        let original_dir = std::env::current_dir()?;
+        std::env::set_current_dir(&model_path)?;
+        let sessions = load_session(&encoder_path, use_gpu)
+            .and_then(|encoder| Ok((encoder, load_session(&decoder_path, use_gpu)?)));
+        std::env::set_current_dir(&original_dir)?;
+        let (encoder, decoder) = sessions?;
+
+        let metadata = encoder
+            .metadata()
+            .map_err(|e| SttError::model_load(format!("Failed to read model metadata: {}", e)))?;
+        let mut meta = HashMap::new();
+        for key in metadata.custom_keys().unwrap_or_default() {
+            if let Ok(Some(value)) = metadata.custom(&key) {
+                meta.insert(key, value);
+            }
+        }
+        drop(metadata);
+        let config = WhisperConfig::from_metadata(&meta)?;
+
+        let size = size_from_path(&model_path);
+        info!(
+            "✓ Whisper {} loaded ({} mels, {} text layers, {})",
+            size,
+            config.n_mels,
+            config.n_text_layer,
+            if config.multilingual {
+                "multilingual"
+            } else {
+                "English-only"
+            }
+        );
+
+        let run_options =
+            Arc::new(RunOptions::new().map_err(|e| {
+                SttError::model_load(format!("Failed to create run options: {}", e))
+            })?);
+
+        Ok(Self {
+            encoder,
+            decoder,
+            tokens,
+            features: LogMel::new(config.n_mels),
+            config,
+            model_path,
+            use_gpu,
+            precision: precision_from_path(&encoder_path),
+            size,
+            language: None,
+            candidates: Vec::new(),
+            emitted: Vec::new(),
+            last_text: String::new(),
+            run_options,
+            stream: None,
+        })
+    }
+
+    /// Transcribe in `code` (e.g. `"de"`), or with `None`, detect the
+    /// language of each window
+    pub fn set_language(&mut self, code: Option<&str>) -> Result<()> {
+        self.language = match code {
+            Some(code) => self.config.language_token(code)?,
+            None => None,
+        };
+        Ok(())
+    }
+
+    /// Only detect among `codes` (e.g. `["en", "de"]`; empty allows every
+    /// language the model knows)
+    pub fn set_languages(&mut self, codes: &[String]) -> Result<()> {
+        let mut candidates = Vec::new();
+        for code in codes {
+            candidates.extend(self.config.language_token(code)?);
+        }
+        self.candidates = candidates;
+        Ok(())
+    }
+
+    /// Language spoken in (the first 30 seconds of) `samples`
+    ///
+    /// `None` for English-only models, which have nothing to detect.
+    pub fn detect_language(&mut self, samples: &[f32]) -> Result<Option<DetectedLanguage>> {
+        if !self.config.multilingual {
+            return Ok(None);
+        }
+        self.run_options
+            .unterminate()
+            .map_err(|e| SttError::inference(format!("Failed to reset run options: {}", e)))?;
+        let window = &samples[..samples.len().min(WINDOW_SAMPLES)];
+        let cross = self.encode(self.features.compute(window))?;
+        let (token, probability) = self.language_of(&cross)?;
+        Ok(self
+            .config
+            .language_code(token)
+            .map(|code| DetectedLanguage {
+                code: code.to_string(),
+                probability,
+            }))
+    }
+
+    /// Handle for cancelling recognition from another thread
+    pub fn cancel_handle(&self) -> CancelHandle {
+        CancelHandle(self.run_options.clone())
+    }
+
+    /// Replace both ONNX Runtime sessions with freshly loaded ones, keeping
+    /// the language setting
+    pub fn reload(&mut self) -> Result<()> {
+        let mut fresh = Self::new(&self.model_path, self.use_gpu)?;
+        fresh.language = self.language;
+        *self = fresh;
+        Ok(())
+    }
+
+    /// Size named in the model directory (`"tiny"` … `"large"`, `"turbo"`)
+    pub fn size(&self) -> &'static str {
+        self.size
+    }
+
+    /// Weight precision of the loaded encoder (`"fp32"`, `"fp16"` or `"int8"`)
+    pub fn precision(&self) -> &'static str {
+        self.precision
+    }
+
+    /// ONNX Runtime execution provider the sessions were built with
+    pub fn execution_provider(&self) -> &'static str {
+        if !self.use_gpu {
+            "CPU"
+        } else {
+            crate::recognizer_ort::gpu_provider_name()
+        }
+    }
+
+    pub fn is_gpu(&self) -> bool {
+        self.use_gpu
+    }
+
+    /// Transcribe 16kHz mono samples
+    pub fn recognize_samples(&mut self, samples: &[f32]) -> Result<String> {
+        info!("Processing {} audio samples with Whisper", samples.len());
+        self.run_options
+            .unterminate()
+            .map_err(|e| SttError::inference(format!("Failed to reset run options: {}", e)))?;
+        self.decode_samples(samples)
+    }
+
+    /// Decode `samples` once to warm the sessions up, then forget the result
+    /// so `n_best` does not report it
+    pub fn warm_up(&mut self, samples: &[f32]) -> Result<()> {
+        self.recognize_samples(samples)?;
+        self.emitted.clear();
+        self.last_text.clear();
+        Ok(())
+    }
+
+    /// Begin a streaming recognition (see [`crate::stream`])
+    pub fn start_stream(&mut self) -> Result<()> {
+        self.run_options
+            .unterminate()
+            .map_err(|e| SttError::inference(format!("Failed to reset run options: {}", e)))?;
+        self.stream = Some(StreamState::new(DEFAULT_PARTIAL_INTERVAL));
+        Ok(())
+    }
+
+    /// Add samples to the stream, returning a changed partial transcription
+    pub fn feed(&mut self, samples: &[f32]) -> Result<Option<String>> {
+        let mut stream = self
+            .stream
+            .take()
+            .ok_or_else(|| SttError::invalid_input("feed() called before start_stream()"))?;
+
+        let result = if stream.push(samples) && stream.samples().len() >= WIN_LENGTH {
+            self.decode_samples(stream.samples())
+                .map(|text| stream.partial(text))
+        } else {
+            Ok(None)
+        };
+        self.stream = Some(stream);
+        result
+    }
+
+    /// End the stream with the transcription of all fed audio
+    pub fn finalize(&mut self) -> Result<String> {
+        let samples = self
+            .stream
+            .take()
+            .ok_or_else(|| SttError::invalid_input("finalize() called before start_stream()"))?
+            .into_samples();
+        if samples.len() < WIN_LENGTH {
+            self.emitted.clear();
+            self.last_text.clear();
+            return Ok(String::new());
+        }
+        self.recognize_samples(&samples)
+    }
+
+    /// The last transcription as the only hypothesis (greedy Whisper
+    /// decoding keeps no runner-ups)
+    pub fn n_best(&self, n: usize) -> Vec<Hypothesis> {
+        let score = self.emitted.iter().map(|&(_, p)| p.max(1e-10).ln()).sum();
+        let mut hypotheses = vec![Hypothesis {
+            text: self.last_text.clone(),
+            score,
+        }];
+        hypotheses.truncate(n);
+        hypotheses
+    }
+
+    /// Per-word confidence of the last recognition (see [`crate::confidence`])
+    pub fn word_confidences(&self) -> Vec<WordConfidence> {
+        let pieces: Vec<(String, f32)> = self
+            .emitted
+            .iter()
+            .filter_map(|&(id, prob)| {
+                let text = String::from_utf8_lossy(self.tokens.get(id as usize)?);
+                // Whisper marks word starts with a leading space
+                let piece = match text.strip_prefix(' ') {
+                    Some(rest) => format!("▁{}", rest),
+                    None => text.into_owned(),
+                };
+                Some((piece, prob))
+            })
+            .collect();
+        let pieces: Vec<(&str, f32)> = pieces.iter().map(|(p, c)| (p.as_str(), *c)).collect();
+        confidence::word_confidences(&pieces)
+    }
+
+    /// Decode `samples` window by window
+    fn decode_samples(&mut self, samples: &[f32]) -> Result<String> {
+        self.emitted.clear();
+        let mut bytes = Vec::new();
+        for window in samples.chunks(WINDOW_SAMPLES) {
+            let features = self.features.compute(window);
+            let tokens = self.decode_window(features)?;
+            for &(id, _) in &tokens {
+                if let Some(token) = self.tokens.get(id as usize) {
+                    bytes.extend_from_slice(token);
+                }
+            }
+            self.emitted.extend(tokens);
+        }
+        self.last_text = String::from_utf8_lossy(&bytes).trim().to_string();
+        Ok(self.last_text.clone())
+    }
+
+    /// Encode one window and greedily decode its text tokens
+    fn decode_window(&mut self, features: Vec<f32>) -> Result<Vec<(i64, f32)>> {
+        let cross = self.encode(features)?;
+        let language = match self.language {
+            Some(language) => Some(language),
+            None if self.config.multilingual => Some(self.language_of(&cross)?.0),
+            None => None,
+        };
+        let prompt = self.config.prompt(language);
+
+        let mut cache = self.empty_cache()?;
+        let mut logits = self.decode_step(&prompt, 0, &mut cache, &cross)?;
+        let mut offset = prompt.len();
+        let max_tokens = self.config.n_text_ctx / 2;
+        let mut emitted = Vec::new();
+        while emitted.len() < max_tokens && offset < self.config.n_text_ctx {
+            // Text tokens and end-of-text only; timestamps and other
+            // special tokens come after it
+            let allowed = &logits[..logits.len().min(self.config.eot as usize + 1)];
+            let token = allowed
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(b.1))
+                .map_or(self.config.eot, |(id, _)| id as i64);
+            if token == self.config.eot {
+                break;
+            }
+            let prob = log_softmax(&logits)[token as usize].exp();
+            emitted.push((token, prob));
+            logits = self.decode_step(&[token], offset, &mut cache, &cross)?;
+            offset += 1;
+        }
+        debug!("Whisper window decoded to {} tokens", emitted.len());
+        Ok(emitted)
+    }
+
+    /// Run the encoder on one window's features; returns the cross-attention
+    /// keys and values the decoder attends to
+    fn encode(&mut self, features: Vec<f32>) -> Result<(DynValue, DynValue)> {
+        let mel = Tensor::from_array((
+            vec![1usize, self.config.n_mels, WINDOW_FRAMES],
+            features.into_boxed_slice(),
+        ))
+        .map_err(|e| SttError::inference(format!("Failed to create mel tensor: {}", e)))?;
+        let mut outputs = self
+            .encoder
+            .run_with_options(ort::inputs!["mel" => mel], &*self.run_options)
+            .map_err(|e| SttError::inference(format!("Encoder inference failed: {}", e)))?;
+        Ok((
+            take_output(&mut outputs, "n_layer_cross_k")?,
+            take_output(&mut outputs, "n_layer_cross_v")?,
+        ))
+    }
+
+    /// Most likely language token for the window (among the candidates)
+    /// and its probability
+    fn language_of(&mut self, cross: &(DynValue, DynValue)) -> Result<(i64, f32)> {
+        let sot = self.config.sot_sequence[..1].to_vec();
+        let mut cache = self.empty_cache()?;
+        let logits = self.decode_step(&sot, 0, &mut cache, cross)?;
+        let (language, probability) = self
+            .config
+            .pick_language(&logits, &self.candidates)
+            .ok_or_else(|| SttError::inference("Whisper model has no language tokens"))?;
+        debug!(
+            "Detected language: {} ({:.0}%)",
+            self.config.language_code(language).unwrap_or("?"),
+            probability * 100.0
+        );
+        Ok((language, probability))
+    }
+
+    /// Zeroed self-attention key/value caches
+    fn empty_cache(&self) -> Result<(DynValue, DynValue)> {
+        let c = &self.config;
+        let shape = vec![c.n_text_layer, 1, c.n_text_ctx, c.n_text_state];
+        let len = shape.iter().product::<usize>();
+        let zeros = || {
+            Tensor::from_array((shape.clone(), vec![0.0f32; len].into_boxed_slice()))
+                .map(|t| t.into_dyn())
+                .map_err(|e| SttError::inference(format!("Failed to create cache tensor: {}", e)))
+        };
+        Ok((zeros()?, zeros()?))
+    }
+
+    /// Run the decoder on `tokens` at position `offset`, updating `cache`;
+    /// returns the logits after the last token
+    fn decode_step(
+        &mut self,
+        tokens: &[i64],
+        offset: usize,
+        cache: &mut (DynValue, DynValue),
+        cross: &(DynValue, DynValue),
+    ) -> Result<Vec<f32>> {
+        let tokens = Tensor::from_array((
+            vec![1usize, tokens.len()],
+            tokens.to_vec().into_boxed_slice(),
+        ))
+        .map_err(|e| SttError::inference(format!("Failed to create tokens tensor: {}", e)))?;
+        let offset = Tensor::from_array((vec![1usize], vec![offset as i64].into_boxed_slice()))
+            .map_err(|e| SttError::inference(format!("Failed to create offset tensor: {}", e)))?;
+
+        let mut outputs = self
+            .decoder
+            .run_with_options(
+                ort::inputs![
+                    "tokens" => tokens,
+                    "in_n_layer_self_k_cache" => &cache.0,
+                    "in_n_layer_self_v_cache" => &cache.1,
+                    "n_layer_cross_k" => &cross.0,
+                    "n_layer_cross_v" => &cross.1,
+                    "offset" => offset,
+                ],
+                &*self.run_options,
+            )
+            .map_err(|e| SttError::inference(format!("Decoder inference failed: {}", e)))?;
+
+        let (shape, data) = outputs["logits"]
+            .try_extract_tensor::<f32>()
+            .map_err(|e| SttError::inference(format!("Failed to extract logits: {}", e)))?;
+        let vocab = shape[shape.len() - 1] as usize;
+        let logits = data[data.len() - vocab..].to_vec();
+
+        cache.0 = take_output(&mut outputs, "out_n_layer_self_k_cache")?;
+        cache.1 = take_output(&mut outputs, "out_n_layer_self_v_cache")?;
+        Ok(logits)
+    }
+}
+
+fn take_output(outputs: &mut ort::session::SessionOutputs<'_>, name: &str) -> Result<DynValue> {
+    outputs
+        .remove(name)
+        .ok_or_else(|| SttError::inference(format!("Whisper model has no '{}' output", name)))
+}
+
+/// Build a session with the same execution providers as the Parakeet models
+fn load_session(path: &Path, use_gpu: bool) -> Result<Session> {
+    let load_err =
+        |e: ort::Error| SttError::model_load(format!("Failed to load {}: {}", path.display(), e));
+    let mut builder = Session::builder()
+        .map_err(load_err)?
+        .with_optimization_level(GraphOptimizationLevel::Level3)
+        .map_err(load_err)?
+        .with_intra_threads(4)
+        .map_err(load_err)?;
+
+    if use_gpu {
+        #[cfg(target_os = "macos")]
+        {
+            builder = builder
+                .with_execution_providers([
+                    ep::CoreMLExecutionProvider::default()
+                        .with_model_format(CoreMLModelFormat::NeuralNetwork)
+                        .with_compute_units(CoreMLComputeUnits::All)
+                        .build(),
+                    ep::CPUExecutionProvider::default().build(),
+                ])
+                .map_err(load_err)?;
+        }
+        #[cfg(target_os = "linux")]
+        {
+            builder = crate::recognizer_ort::with_linux_gpu(builder).map_err(load_err)?;
+        }
+        #[cfg(target_os = "windows")]
+        {
+            builder = crate::recognizer_ort::with_directml(builder).map_err(load_err)?;
+        }
+    }
+
+    info!("Loading {}...", path.display());
+    builder.commit_from_file(path).map_err(load_err)
+}
+
+/// `{name}.{ext}` in `dir`, also with a size prefix (`small-{name}.{ext}`);
+/// for ONNX files the `.int8` variant is preferred when `prefer_int8`
+fn find_file(dir: &Path, name: &str, ext: &str, prefer_int8: bool) -> Result<PathBuf> {
+    let entries: Vec<String> = std::fs::read_dir(dir)
+        .map_err(|e| SttError::model_load(format!("Failed to read {}: {}", dir.display(), e)))?
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .collect();
+    let find = |suffix: &str| {
+        entries
+            .iter()
+            .filter(|file| *file == suffix || file.ends_with(&format!("-{}", suffix)))
+            .min()
+            .map(|file| dir.join(file))
+    };
+
+    let plain = format!("{}.{}", name, ext);
+    let int8 = format!("{}.int8.{}", name, ext);
+    let found = if prefer_int8 {
+        find(&int8).or_else(|| find(&plain))
+    } else {
+        find(&plain).or_else(|| find(&int8))
+    };
+    found.ok_or_else(|| {
+        SttError::model_load(format!("Could not find {} in {}", plain, dir.display()))
+    })
+}
+
+/// Parse `tokens.txt`: base64-encoded token bytes and the token id per line
+fn parse_tokens(contents: &str) -> Result<Vec<Vec<u8>>> {
+    let mut tokens = Vec::new();
+    for (number, line) in contents.lines().enumerate() {
+        let mut fields = line.split_whitespace();
+        let (Some(encoded), Some(id)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let bad =
+            |what: &str| SttError::model_load(format!("tokens.txt line {}: {}", number + 1, what));
+        let id: usize = id.parse().map_err(|_| bad("invalid token id"))?;
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|_| bad("invalid base64"))?;
+        if tokens.len() <= id {
+            tokens.resize(id + 1, Vec::new());
+        }
+        tokens[id] = bytes;
+    }
+    Ok(tokens)
+}
+
+/// Model size named in the directory (`whisper-small-onnx` → `"small"`)
+fn size_from_path(path: &Path) -> &'static str {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    ["turbo", "large", "medium", "small", "base", "tiny"]
+        .into_iter()
+        .find(|size| name.contains(size))
+        .unwrap_or("custom")
+}
+
+/// Whisper's log-mel spectrogram of one 30-second window
+struct LogMel {
+    filters: Array2<f32>,
+    window: Vec<f32>,
+    fft: Arc<dyn Fft<f32>>,
+}
+
+impl LogMel {
+    fn new(n_mels: usize) -> Self {
+        // Periodic Hann window, as torch.hann_window
+        let window = (0..N_FFT)
+            .map(|n| 0.5 - 0.5 * (2.0 * PI * n as f32 / N_FFT as f32).cos())
+            .collect();
+        Self {
+            filters: slaney_filterbank(n_mels),
+            window,
+            fft: FftPlanner::new().plan_fft_forward(N_FFT),
+        }
+    }
+
+    /// Features of `samples` (padded or cut to 30 s), `n_mels` rows of
+    /// `WINDOW_FRAMES` frames, row-major
+    fn compute(&self, samples: &[f32]) -> Vec<f32> {
+        let mut audio = vec![0.0f32; WINDOW_SAMPLES];
+        let len = samples.len().min(WINDOW_SAMPLES);
+        audio[..len].copy_from_slice(&samples[..len]);
+
+        // Centered frames with reflect padding
+        let pad = N_FFT / 2;
+        let mut padded = Vec::with_capacity(WINDOW_SAMPLES + 2 * pad);
+        padded.extend((1..=pad).rev().map(|i| audio[i]));
+        padded.extend_from_slice(&audio);
+        padded.extend((1..=pad).map(|i| audio[WINDOW_SAMPLES - 1 - i]));
+
+        let n_mels = self.filters.nrows();
+        let bins = self.filters.ncols();
+        let mut mel = vec![0.0f32; n_mels * WINDOW_FRAMES];
+        let mut buffer = vec![Complex::new(0.0, 0.0); N_FFT];
+        let mut power = vec![0.0f32; bins];
+        for frame in 0..WINDOW_FRAMES {
+            let start = frame * HOP_LENGTH;
+            for (i, value) in buffer.iter_mut().enumerate() {
+                *value = Complex::new(padded[start + i] * self.window[i], 0.0);
+            }
+            self.fft.process(&mut buffer);
+            for (p, value) in power.iter_mut().zip(&buffer) {
+                *p = value.norm_sqr();
+            }
+            for (m, filter) in self.filters.outer_iter().enumerate() {
+                mel[m * WINDOW_FRAMES + frame] =
+                    filter.iter().zip(&power).map(|(f, p)| f * p).sum();
+            }
+        }
+
+        for value in mel.iter_mut() {
+            *value = value.max(1e-10).log10();
+        }
+        let max = mel.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        for value in mel.iter_mut() {
+            *value = (value.max(max - 8.0) + 4.0) / 4.0;
+        }
+        mel
+    }
+}
+
+/// Slaney-style mel filterbank over 0-8000 Hz with area normalization
+/// (librosa's default, which Whisper was trained with)
+fn slaney_filterbank(n_mels: usize) -> Array2<f32> {
+    const F_SP: f32 = 200.0 / 3.0;
+    const MIN_LOG_HZ: f32 = 1000.0;
+    let min_log_mel = MIN_LOG_HZ / F_SP;
+    let log_step = 6.4f32.ln() / 27.0;
+    let hz_to_mel = |hz: f32| {
+        if hz < MIN_LOG_HZ {
+            hz / F_SP
+        } else {
+            min_log_mel + (hz / MIN_LOG_HZ).ln() / log_step
+        }
+    };
+    let mel_to_hz = |mel: f32| {
+        if mel < min_log_mel {
+            mel * F_SP
+        } else {
+            MIN_LOG_HZ * ((mel - min_log_mel) * log_step).exp()
+        }
+    };
+
+    let bins = N_FFT / 2 + 1;
+    let max_mel = hz_to_mel(SAMPLE_RATE as f32 / 2.0);
+    let edges: Vec<f32> = (0..n_mels + 2)
+        .map(|i| mel_to_hz(max_mel * i as f32 / (n_mels + 1) as f32))
+        .collect();
+
+    let mut filters = Array2::zeros((n_mels, bins));
+    for m in 0..n_mels {
+        let (low, center, high) = (edges[m], edges[m + 1], edges[m + 2]);
+        let norm = 2.0 / (high - low);
+        for k in 0..bins {
+            let hz = k as f32 * SAMPLE_RATE as f32 / N_FFT as f32;
+            let rising = (hz - low) / (center - low);
+            let falling = (high - hz) / (high - center);
+            filters[[m, k]] = rising.min(falling).max(0.0) * norm;
+        }
+    }
+    filters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(multilingual: bool) -> HashMap<String, String> {
+        let mut meta: HashMap<String, String> = [
+            ("n_mels", "80"),
+            ("n_text_layer", "4"),
+            ("n_text_ctx", "448"),
+            ("n_text_state", "384"),
+            ("eot", "50257"),
+            ("no_timestamps", "50363"),
+            ("all_language_tokens", "50259,50260,50261"),
+            ("all_language_codes", "en,zh,de"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let (flag, sot) = if multilingual {
+            ("1", "50258,50259,50359")
+        } else {
+            ("0", "50257")
+        };
+        meta.insert("is_multilingual".into(), flag.into());
+        meta.insert("sot_sequence".into(), sot.into());
+        meta
+    }
+
+    #[test]
+    fn test_config_and_prompt() {
+        let config = WhisperConfig::from_metadata(&metadata(true)).unwrap();
+        assert_eq!(config.language_codes, ["en", "zh", "de"]);
+        // The language slot takes the chosen language
+        assert_eq!(config.prompt(Some(50261)), vec![50258, 50261, 50359, 50363]);
+
+        let english = WhisperConfig::from_metadata(&metadata(false)).unwrap();
+        assert!(english.language_tokens.is_empty());
+        assert_eq!(english.prompt(Some(50261)), vec![50257, 50363]);
+
+        assert_eq!(config.language_token("de").unwrap(), Some(50261));
+        assert!(config.language_token("xx").is_err());
+        assert_eq!(english.language_token("en").unwrap(), None);
+        assert!(english.language_token("de").is_err());
+
+        let mut missing = metadata(true);
+        missing.remove("eot");
+        let err = WhisperConfig::from_metadata(&missing)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("'eot'"), "{}", err);
+    }
+
+    #[test]
+    fn test_pick_language_among_candidates() {
+        let config = WhisperConfig::from_metadata(&metadata(true)).unwrap();
+        let mut logits = vec![0.0f32; 50262];
+        logits[50259] = 1.0; // en
+        logits[50260] = 3.0; // zh
+        logits[50261] = 2.0; // de
+
+        assert_eq!(config.pick_language(&logits, &[]).unwrap().0, 50260);
+        let (token, probability) = config.pick_language(&logits, &[50259, 50261]).unwrap();
+        assert_eq!(config.language_code(token), Some("de"));
+        // e^2 / (e^1 + e^2)
+        assert!((probability - 0.7311).abs() < 1e-3, "{}", probability);
+    }
+
+    #[test]
+    fn test_parse_tokens_and_size() {
+        let tokens = parse_tokens("IQ== 0\nIGhlbGxv 2\n").unwrap();
+        assert_eq!(tokens, vec![b"!".to_vec(), Vec::new(), b" hello".to_vec()]);
+        assert!(parse_tokens("!!! 0\n").is_err());
+
+        assert_eq!(size_from_path(Path::new("/m/whisper-small-onnx")), "small");
+        assert_eq!(
+            size_from_path(Path::new("/m/sherpa-onnx-whisper-large-v3-turbo")),
+            "turbo"
+        );
+        assert_eq!(size_from_path(Path::new("/m/my-model")), "custom");
+    }
+
+    #[test]
+    fn test_filterbank_shape() {
+        let filters = slaney_filterbank(80);
+        assert_eq!(filters.dim(), (80, N_FFT / 2 + 1));
+        // Every filter covers some bins, and they move up in frequency
+        let peaks: Vec<usize> = filters
+            .outer_iter()
+            .map(|row| {
+                assert!(row.sum() > 0.0);
+                (0..row.len())
+                    .max_by(|&a, &b| row[a].total_cmp(&row[b]))
+                    .unwrap()
+            })
+            .collect();
+        assert!(peaks.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn test_silence_features() {
+        let features = LogMel::new(80).compute(&[0.0; 16000]);
+        assert_eq!(features.len(), 80 * WINDOW_FRAMES);
+        // log10(1e-10) everywhere, scaled: (-10 + 4) / 4
+        assert!(features.iter().all(|&v| (v + 1.5).abs() < 1e-6));
+    }
+}
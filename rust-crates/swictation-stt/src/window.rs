@@ -0,0 +1,143 @@
+//! Encoder windows with overlapping context
+//!
+//! Utterances up to [`WINDOW_FRAMES`] feature frames are encoded in one run
+//! over their real length, without padding. Longer ones are split into
+//! windows that each own [`WINDOW_FRAMES`] frames but feed the encoder
+//! [`LEFT_CONTEXT_FRAMES`] before and [`RIGHT_CONTEXT_FRAMES`] after them.
+//! The encoder output for the context is dropped, so every kept frame was
+//! encoded with audio on both sides, and the decoder carries its state from
+//! one window into the next.
+//!
+//! Windows start at multiples of [`WINDOW_FRAMES`] whatever the utterance
+//! length, so appending audio only ever changes the last windows. Once a
+//! window's right context is complete it is [settled](Window::is_settled)
+//! and a stream can keep its encoder output instead of encoding it again.
+
+use ndarray::{s, Array2};
+
+/// Feature frames per encoder output frame (FastConformer subsampling)
+pub const SUBSAMPLING: usize = 8;
+
+/// Seconds per encoder output frame (10 ms hop × [`SUBSAMPLING`])
+pub const FRAME_SECS: f64 = 0.01 * SUBSAMPLING as f64;
+
+/// Feature frames owned by one window (20 s at a 10 ms hop)
+pub const WINDOW_FRAMES: usize = 2000;
+
+/// Frames before a window fed to the encoder as context
+pub const LEFT_CONTEXT_FRAMES: usize = 400;
+
+/// Frames after a window fed to the encoder as lookahead
+pub const RIGHT_CONTEXT_FRAMES: usize = 160;
+
+/// One encoder run over part of an utterance, in feature frames
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Window {
+    /// First frame fed to the encoder
+    pub input_start: usize,
+    /// End (exclusive) of the frames fed to the encoder
+    pub input_end: usize,
+    /// First frame whose encoder output is kept
+    pub start: usize,
+    /// End (exclusive) of the frames whose encoder output is kept
+    pub end: usize,
+}
+
+impl Window {
+    /// The rows of `features` fed to the encoder
+    pub fn input(&self, features: &Array2<f32>) -> Array2<f32> {
+        features
+            .slice(s![self.input_start..self.input_end, ..])
+            .to_owned()
+    }
+
+    pub fn input_len(&self) -> usize {
+        self.input_end - self.input_start
+    }
+
+    /// Encoder output frames produced by the left and right context
+    pub fn context_frames(&self) -> (usize, usize) {
+        (
+            (self.start - self.input_start) / SUBSAMPLING,
+            (self.input_end - self.end) / SUBSAMPLING,
+        )
+    }
+
+    /// Whether more audio can no longer change the frames fed to the encoder
+    pub fn is_settled(&self) -> bool {
+        self.input_end == self.end + RIGHT_CONTEXT_FRAMES
+    }
+}
+
+/// Windows covering `total_frames` feature frames, in order
+pub fn plan(total_frames: usize) -> Vec<Window> {
+    if total_frames <= WINDOW_FRAMES {
+        return vec![Window {
+            input_start: 0,
+            input_end: total_frames,
+            start: 0,
+            end: total_frames,
+        }];
+    }
+    (0..total_frames)
+        .step_by(WINDOW_FRAMES)
+        .map(|start| {
+            let end = (start + WINDOW_FRAMES).min(total_frames);
+            Window {
+                input_start: start.saturating_sub(LEFT_CONTEXT_FRAMES),
+                input_end: (end + RIGHT_CONTEXT_FRAMES).min(total_frames),
+                start,
+                end,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_utterance_is_one_window() {
+        let windows = plan(615);
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].input_len(), 615);
+        assert_eq!(windows[0].context_frames(), (0, 0));
+        assert!(!windows[0].is_settled());
+        assert_eq!(plan(0)[0].input_len(), 0);
+    }
+
+    #[test]
+    fn test_long_utterance_windows_overlap() {
+        let total = 2 * WINDOW_FRAMES + 500;
+        let windows = plan(total);
+        assert_eq!(windows.len(), 3);
+
+        // Owned frames tile the utterance exactly
+        assert_eq!(windows[0].start, 0);
+        assert!(windows.windows(2).all(|w| w[0].end == w[1].start));
+        assert_eq!(windows[2].end, total);
+
+        assert_eq!(windows[0].context_frames(), (0, RIGHT_CONTEXT_FRAMES / 8));
+        assert_eq!(
+            windows[1].context_frames(),
+            (LEFT_CONTEXT_FRAMES / 8, RIGHT_CONTEXT_FRAMES / 8)
+        );
+        assert_eq!(windows[2].context_frames(), (LEFT_CONTEXT_FRAMES / 8, 0));
+        assert!(windows[0].is_settled() && windows[1].is_settled());
+        assert!(!windows[2].is_settled());
+    }
+
+    #[test]
+    fn test_settled_windows_survive_more_audio() {
+        let before = plan(WINDOW_FRAMES + RIGHT_CONTEXT_FRAMES + 10);
+        let after = plan(3 * WINDOW_FRAMES);
+        assert!(before[0].is_settled());
+        assert_eq!(before[0], after[0]);
+
+        // Without its full lookahead the first window still changes
+        let early = plan(WINDOW_FRAMES + 10);
+        assert!(!early[0].is_settled());
+        assert_ne!(early[0], after[0]);
+    }
+}
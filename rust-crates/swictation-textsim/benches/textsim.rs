@@ -0,0 +1,75 @@
+//! Benchmarks for the similarity and diff algorithms
+//!
+//! Run with `cargo bench -p swictation-textsim`. Inputs mirror real use:
+//! short words and phrases for fuzzy corrections and key suggestions, and
+//! ~100-word transcripts for diffs.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use swictation_textsim::{damerau_levenshtein, jaro_winkler, levenshtein, metaphone, myers_diff};
+
+const WORD_PAIRS: &[(&str, &str)] = &[
+    ("arkon", "archon"),
+    ("kubernetes", "cooper netties"),
+    ("phonetic_threshold", "phonetic_treshold"),
+    ("swictation", "switch nation"),
+];
+
+/// A transcript and the corrected version of it
+fn transcripts() -> (String, String) {
+    let sentence = "the quick brown fox jumps over the lazy dog near the river bank ";
+    let original = sentence.repeat(8);
+    let corrected = original
+        .replace("brown fox", "brown socks")
+        .replacen("lazy", "sleepy", 3)
+        .replacen("river", "", 2);
+    (original, corrected)
+}
+
+fn bench_distances(c: &mut Criterion) {
+    let mut group = c.benchmark_group("distance");
+    group.bench_function("levenshtein", |b| {
+        b.iter(|| {
+            for (x, y) in WORD_PAIRS {
+                black_box(levenshtein(black_box(x), black_box(y)));
+            }
+        })
+    });
+    group.bench_function("damerau_levenshtein", |b| {
+        b.iter(|| {
+            for (x, y) in WORD_PAIRS {
+                black_box(damerau_levenshtein(black_box(x), black_box(y)));
+            }
+        })
+    });
+    group.bench_function("jaro_winkler", |b| {
+        b.iter(|| {
+            for (x, y) in WORD_PAIRS {
+                black_box(jaro_winkler(black_box(x), black_box(y)));
+            }
+        })
+    });
+    group.bench_function("metaphone", |b| {
+        b.iter(|| {
+            for (x, y) in WORD_PAIRS {
+                black_box(metaphone(black_box(x)));
+                black_box(metaphone(black_box(y)));
+            }
+        })
+    });
+    group.finish();
+}
+
+fn bench_diff(c: &mut Criterion) {
+    let (original, corrected) = transcripts();
+    let a: Vec<&str> = original.split_whitespace().collect();
+    let b: Vec<&str> = corrected.split_whitespace().collect();
+    c.bench_function("myers_diff_100_words", |bench| {
+        bench.iter(|| black_box(myers_diff(black_box(&a), black_box(&b))))
+    });
+    c.bench_function("myers_diff_identical", |bench| {
+        bench.iter(|| black_box(myers_diff(black_box(&a), black_box(&a))))
+    });
+}
+
+criterion_group!(benches, bench_distances, bench_diff);
+criterion_main!(benches);
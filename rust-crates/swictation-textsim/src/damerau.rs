@@ -0,0 +1,102 @@
+//! Damerau-Levenshtein distance (adjacent transpositions count as one edit)
+
+use alloc::collections::BTreeMap;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Fewest insertions, deletions, substitutions and transpositions of two
+/// adjacent characters turning `a` into `b`
+///
+/// This is the unrestricted distance: a transposed pair may be edited again
+/// afterwards, so `damerau_levenshtein("ca", "abc")` is 2.
+///
+/// ```
+/// assert_eq!(swictation_textsim::damerau_levenshtein("recieve", "receive"), 1);
+/// ```
+pub fn damerau_levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    distance(&a, &b)
+}
+
+/// Damerau-Levenshtein similarity in `[0, 1]`: 1.0 when equal
+pub fn normalized_damerau_levenshtein(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    crate::normalize(distance(&a, &b), a.len(), b.len())
+}
+
+/// Lowrance-Wagner algorithm over a table with a sentinel row and column
+fn distance(a: &[char], b: &[char]) -> usize {
+    if a.is_empty() {
+        return b.len();
+    }
+    if b.is_empty() {
+        return a.len();
+    }
+
+    let (n, m) = (a.len(), b.len());
+    let width = m + 2;
+    let infinity = n + m;
+    let mut d = vec![0usize; (n + 2) * width];
+    d[0] = infinity;
+    for i in 0..=n {
+        d[(i + 1) * width] = infinity;
+        d[(i + 1) * width + 1] = i;
+    }
+    for j in 0..=m {
+        d[j + 1] = infinity;
+        d[width + j + 1] = j;
+    }
+
+    // Last row each character was seen in `a`
+    let mut last_row: BTreeMap<char, usize> = BTreeMap::new();
+    for i in 1..=n {
+        // Last column in this row where a[i - 1] matched
+        let mut last_match_col = 0;
+        for j in 1..=m {
+            let k = last_row.get(&b[j - 1]).copied().unwrap_or(0);
+            let l = last_match_col;
+            let cost = if a[i - 1] == b[j - 1] {
+                last_match_col = j;
+                0
+            } else {
+                1
+            };
+            d[(i + 1) * width + j + 1] =
+                (d[i * width + j] + cost) // Substitution
+                    .min(d[(i + 1) * width + j] + 1) // Insertion
+                    .min(d[i * width + j + 1] + 1) // Deletion
+                    .min(d[k * width + l] + (i - k - 1) + 1 + (j - l - 1)); // Transposition
+        }
+        last_row.insert(a[i - 1], i);
+    }
+    d[(n + 1) * width + m + 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_damerau_levenshtein() {
+        assert_eq!(damerau_levenshtein("", ""), 0);
+        assert_eq!(damerau_levenshtein("abc", ""), 3);
+        assert_eq!(damerau_levenshtein("kitten", "sitting"), 3);
+        assert_eq!(damerau_levenshtein("ab", "ba"), 1);
+        // Plain Levenshtein needs two edits for a swap
+        assert_eq!(crate::levenshtein("ab", "ba"), 2);
+        // Unrestricted: the swapped pair can be edited again
+        assert_eq!(damerau_levenshtein("ca", "abc"), 2);
+    }
+
+    #[test]
+    fn test_normalized_damerau_levenshtein() {
+        assert_eq!(normalized_damerau_levenshtein("", ""), 1.0);
+        assert_eq!(normalized_damerau_levenshtein("abc", "xyz"), 0.0);
+        assert_eq!(
+            normalized_damerau_levenshtein("hotkey", "hotkye"),
+            1.0 - 1.0 / 6.0
+        );
+    }
+}
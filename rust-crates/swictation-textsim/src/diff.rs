@@ -0,0 +1,205 @@
+//! Myers' O(ND) difference algorithm
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// One step of an edit script, as indexes into the old (`a`) and new (`b`)
+/// sequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edit {
+    /// `a[i]` and `b[j]` are the same element
+    Equal(usize, usize),
+    /// `a[i]` was removed
+    Delete(usize),
+    /// `b[j]` was added
+    Insert(usize),
+}
+
+/// Shortest edit script from `a` to `b`, in order
+///
+/// Time is O((N + M) D) for D differences, so near-identical texts diff
+/// quickly however long they are. Where a run is replaced, its deletions
+/// come before its insertions.
+///
+/// ```
+/// use swictation_textsim::{myers_diff, Edit};
+/// let edits = myers_diff(&["a", "b", "c"], &["a", "x", "c"]);
+/// assert_eq!(
+///     edits,
+///     vec![Edit::Equal(0, 0), Edit::Delete(1), Edit::Insert(1), Edit::Equal(2, 2)]
+/// );
+/// ```
+pub fn myers_diff<T: PartialEq>(a: &[T], b: &[T]) -> Vec<Edit> {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let max = n + m;
+    // Furthest x reached on each diagonal k = x - y, for k in -max-1..=max+1
+    let offset = max + 1;
+    let index = |k: isize| (k + offset) as usize;
+    let mut v = vec![0isize; 2 * max as usize + 3];
+    // Diagonal k took a step down from k + 1 rather than right from k - 1
+    let down =
+        |v: &[isize], k: isize, d: isize| k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]);
+
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    'search: for d in 0..=max {
+        trace.push(v.clone());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if down(&v, k, d) {
+                v[index(k + 1)]
+            } else {
+                v[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index(k)] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    // Walk back from the end through each round's furthest points
+    let mut edits = Vec::with_capacity(a.len().max(b.len()));
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let k = x - y;
+        let prev_k = if down(v, k, d) { k + 1 } else { k - 1 };
+        let prev_x = v[index(prev_k)];
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Equal(x as usize, y as usize));
+        }
+        if d > 0 {
+            if x == prev_x {
+                edits.push(Edit::Insert(prev_y as usize));
+            } else {
+                edits.push(Edit::Delete(prev_x as usize));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    edits.reverse();
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rebuild `b` from `a` and the script
+    fn apply<'a>(a: &[&'a str], b: &[&'a str], edits: &[Edit]) -> Vec<&'a str> {
+        let mut out = Vec::new();
+        let mut next_a = 0;
+        for edit in edits {
+            match *edit {
+                Edit::Equal(i, j) => {
+                    assert_eq!(i, next_a);
+                    assert_eq!(a[i], b[j]);
+                    next_a += 1;
+                    out.push(a[i]);
+                }
+                Edit::Delete(i) => {
+                    assert_eq!(i, next_a);
+                    next_a += 1;
+                }
+                Edit::Insert(j) => out.push(b[j]),
+            }
+        }
+        assert_eq!(next_a, a.len());
+        out
+    }
+
+    #[test]
+    fn test_empty_and_identical() {
+        let empty: [&str; 0] = [];
+        assert!(myers_diff(&empty, &empty).is_empty());
+        assert_eq!(myers_diff(&empty, &["a"]), vec![Edit::Insert(0)]);
+        assert_eq!(myers_diff(&["a"], &empty), vec![Edit::Delete(0)]);
+        assert_eq!(
+            myers_diff(&["a", "b"], &["a", "b"]),
+            vec![Edit::Equal(0, 0), Edit::Equal(1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_shortest_script() {
+        let a: Vec<char> = "abcabba".chars().collect();
+        let b: Vec<char> = "cbabac".chars().collect();
+        let edits = myers_diff(&a, &b);
+        let changes = edits
+            .iter()
+            .filter(|e| !matches!(e, Edit::Equal(..)))
+            .count();
+        // The example from Myers' paper: D = 5
+        assert_eq!(changes, 5);
+    }
+
+    /// Longest common subsequence length, by dynamic programming
+    fn lcs_len(a: &[u8], b: &[u8]) -> usize {
+        let mut row = vec![0usize; b.len() + 1];
+        for x in a {
+            let mut diagonal = 0;
+            for (j, y) in b.iter().enumerate() {
+                let above = row[j + 1];
+                row[j + 1] = if x == y {
+                    diagonal + 1
+                } else {
+                    above.max(row[j])
+                };
+                diagonal = above;
+            }
+        }
+        row[b.len()]
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_script_is_valid_and_shortest(
+            a in proptest::collection::vec(0u8..4, 0..60),
+            b in proptest::collection::vec(0u8..4, 0..60),
+        ) {
+            let edits = myers_diff(&a, &b);
+            let equal = edits.iter().filter(|e| matches!(e, Edit::Equal(..))).count();
+            // A shortest script keeps a longest common subsequence
+            proptest::prop_assert_eq!(equal, lcs_len(&a, &b));
+            proptest::prop_assert_eq!(edits.len(), a.len() + b.len() - equal);
+
+            let (mut i, mut j) = (0, 0);
+            for edit in edits {
+                match edit {
+                    Edit::Equal(x, y) => {
+                        proptest::prop_assert_eq!((x, y), (i, j));
+                        proptest::prop_assert_eq!(a[x], b[y]);
+                        i += 1;
+                        j += 1;
+                    }
+                    Edit::Delete(x) => {
+                        proptest::prop_assert_eq!(x, i);
+                        i += 1;
+                    }
+                    Edit::Insert(y) => {
+                        proptest::prop_assert_eq!(y, j);
+                        j += 1;
+                    }
+                }
+            }
+            proptest::prop_assert_eq!((i, j), (a.len(), b.len()));
+        }
+    }
+
+    #[test]
+    fn test_script_rebuilds_new_text() {
+        let a: Vec<&str> = "the quick brown socks jumped over".split(' ').collect();
+        let b: Vec<&str> = "a quick brown fox jumped over the dog".split(' ').collect();
+        assert_eq!(apply(&a, &b, &myers_diff(&a, &b)), b);
+    }
+}
@@ -0,0 +1,109 @@
+//! Jaro and Jaro-Winkler similarity
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// Longest common prefix Jaro-Winkler rewards
+const MAX_PREFIX: usize = 4;
+
+/// Jaro-Winkler prefix scaling factor
+const PREFIX_WEIGHT: f64 = 0.1;
+
+/// Jaro similarity in `[0, 1]`, from characters the strings share within a
+/// window of half the longer length, and how many of those are out of order
+///
+/// ```
+/// let s = swictation_textsim::jaro("martha", "marhta");
+/// assert!((s - 0.944).abs() < 1e-3);
+/// ```
+pub fn jaro(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    similarity(&a, &b)
+}
+
+/// Jaro similarity raised by a common prefix of up to 4 characters, for
+/// strings that differ mostly at the end ("swictation" vs "swictaion")
+///
+/// ```
+/// let s = swictation_textsim::jaro_winkler("martha", "marhta");
+/// assert!((s - 0.961).abs() < 1e-3);
+/// ```
+pub fn jaro_winkler(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let jaro = similarity(&a, &b);
+    let prefix = a
+        .iter()
+        .zip(&b)
+        .take(MAX_PREFIX)
+        .take_while(|(x, y)| x == y)
+        .count();
+    jaro + prefix as f64 * PREFIX_WEIGHT * (1.0 - jaro)
+}
+
+fn similarity(a: &[char], b: &[char]) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+    let mut b_matched = vec![false; b.len()];
+    let mut a_matches = Vec::new();
+    for (i, ca) in a.iter().enumerate() {
+        let lo = i.saturating_sub(window);
+        let hi = (i + window + 1).min(b.len());
+        if let Some(j) = (lo..hi).find(|&j| !b_matched[j] && b[j] == *ca) {
+            b_matched[j] = true;
+            a_matches.push(*ca);
+        }
+    }
+    let matches = a_matches.len();
+    if matches == 0 {
+        return 0.0;
+    }
+
+    // Matched characters in a different order in each string, counted in pairs
+    let b_matches = b.iter().zip(&b_matched).filter(|(_, &m)| m).map(|(c, _)| c);
+    let transpositions = a_matches
+        .iter()
+        .zip(b_matches)
+        .filter(|(x, y)| x != y)
+        .count()
+        / 2;
+
+    let m = matches as f64;
+    (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn test_jaro() {
+        assert_eq!(jaro("", ""), 1.0);
+        assert_eq!(jaro("abc", ""), 0.0);
+        assert_eq!(jaro("abc", "xyz"), 0.0);
+        assert_eq!(jaro("same", "same"), 1.0);
+        assert!(close(jaro("dwayne", "duane"), 0.822));
+        assert!(close(jaro("dixon", "dicksonx"), 0.767));
+    }
+
+    #[test]
+    fn test_jaro_winkler() {
+        assert!(close(jaro_winkler("dwayne", "duane"), 0.84));
+        assert!(close(jaro_winkler("dixon", "dicksonx"), 0.813));
+        // The prefix bonus stops at four characters
+        let j = jaro("swictation", "swictaion");
+        assert_eq!(jaro_winkler("swictation", "swictaion"), j + 0.4 * (1.0 - j));
+        assert_eq!(jaro_winkler("same", "same"), 1.0);
+    }
+}
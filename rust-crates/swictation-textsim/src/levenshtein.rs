@@ -0,0 +1,81 @@
+//! Levenshtein edit distance
+
+use alloc::vec::Vec;
+
+/// Fewest single-character insertions, deletions and substitutions turning
+/// `a` into `b`
+///
+/// ```
+/// assert_eq!(swictation_textsim::levenshtein("kitten", "sitting"), 3);
+/// ```
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    distance(&a, &b)
+}
+
+/// Levenshtein similarity in `[0, 1]`: 1.0 when equal, 0.0 when nothing is
+/// shared
+pub fn normalized_levenshtein(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    crate::normalize(distance(&a, &b), a.len(), b.len())
+}
+
+/// Levenshtein distance between two sequences of any comparable items,
+/// e.g. words for a word error rate
+///
+/// ```
+/// let reference = ["turn", "on", "the", "lights"];
+/// let heard = ["turn", "the", "light"];
+/// assert_eq!(swictation_textsim::sequence_levenshtein(&reference, &heard), 2);
+/// ```
+pub fn sequence_levenshtein<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    distance(a, b)
+}
+
+/// Two-row dynamic programme, O(len(a) * len(b)) time, O(len(b)) memory
+fn distance<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    if a.is_empty() {
+        return b.len();
+    }
+    if b.is_empty() {
+        return a.len();
+    }
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = alloc::vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            curr[j + 1] = (prev[j + 1] + 1) // Deletion
+                .min(curr[j] + 1) // Insertion
+                .min(prev[j] + cost); // Substitution
+        }
+        core::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("hello", "hello"), 0);
+        assert_eq!(levenshtein("", "test"), 4);
+        assert_eq!(levenshtein("test", ""), 4);
+        // Characters, not bytes
+        assert_eq!(levenshtein("café", "cafe"), 1);
+    }
+
+    #[test]
+    fn test_normalized_levenshtein() {
+        assert_eq!(normalized_levenshtein("", ""), 1.0);
+        assert_eq!(normalized_levenshtein("abc", ""), 0.0);
+        assert!((normalized_levenshtein("kitten", "sitting") - (1.0 - 3.0 / 7.0)).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,47 @@
+//! String similarity and diff algorithms for Swictation.
+//!
+//! One implementation of each, shared by the daemon (fuzzy corrections,
+//! retry diffs, config key suggestions) and the WASM UI utilities (pattern
+//! clustering, correction diffs). The crate is `no_std` and only needs
+//! `alloc`, so it builds for `wasm32-unknown-unknown` unchanged.
+//!
+//! - [`levenshtein`] / [`normalized_levenshtein`] - insertions, deletions,
+//!   substitutions; [`sequence_levenshtein`] counts them over words or any
+//!   other items
+//! - [`damerau_levenshtein`] / [`normalized_damerau_levenshtein`] - also
+//!   transpositions of adjacent characters
+//! - [`jaro`] / [`jaro_winkler`] - similarity weighted towards a shared
+//!   prefix, good for short names
+//! - [`metaphone`] - English phonetic key, so "nite" and "night" compare
+//!   equal
+//! - [`myers_diff`] - shortest edit script between two sequences (words,
+//!   characters, lines)
+//!
+//! Distances count characters (Unicode scalar values), not bytes.
+//! Normalized variants are similarities in `[0, 1]`, 1 meaning identical.
+
+#![no_std]
+
+extern crate alloc;
+
+pub mod damerau;
+pub mod diff;
+pub mod jaro;
+pub mod levenshtein;
+pub mod metaphone;
+
+pub use damerau::{damerau_levenshtein, normalized_damerau_levenshtein};
+pub use diff::{myers_diff, Edit};
+pub use jaro::{jaro, jaro_winkler};
+pub use levenshtein::{levenshtein, normalized_levenshtein, sequence_levenshtein};
+pub use metaphone::metaphone;
+
+/// `1 - distance / longer length`, 1.0 for two empty strings
+fn normalize(distance: usize, a_len: usize, b_len: usize) -> f64 {
+    let longest = a_len.max(b_len);
+    if longest == 0 {
+        1.0
+    } else {
+        1.0 - distance as f64 / longest as f64
+    }
+}
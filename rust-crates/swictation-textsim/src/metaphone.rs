@@ -0,0 +1,182 @@
+//! Metaphone phonetic keys (Lawrence Philips, 1990)
+//!
+//! Reduces an English word to the consonant sounds it is spoken with, so
+//! words a recognizer might spell differently ("nite" / "night", "fone" /
+//! "phone") share a key. `0` stands for "th", `X` for "sh" and "ch".
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+fn is_vowel(c: Option<u8>) -> bool {
+    matches!(c, Some(b'A' | b'E' | b'I' | b'O' | b'U'))
+}
+
+fn is_front_vowel(c: Option<u8>) -> bool {
+    matches!(c, Some(b'E' | b'I' | b'Y'))
+}
+
+/// Metaphone key of `word`, in upper case
+///
+/// Characters other than ASCII letters are ignored.
+///
+/// ```
+/// use swictation_textsim::metaphone;
+/// assert_eq!(metaphone("knight"), metaphone("nite"));
+/// assert_eq!(metaphone("phone"), "FN");
+/// ```
+pub fn metaphone(word: &str) -> String {
+    let w: Vec<u8> = word
+        .bytes()
+        .filter(u8::is_ascii_alphabetic)
+        .map(|b| b.to_ascii_uppercase())
+        .collect();
+    let mut key = String::new();
+    let at = |i: usize| w.get(i).copied();
+
+    // Initial letter exceptions
+    let start = match (at(0), at(1)) {
+        (Some(b'A'), Some(b'E'))
+        | (Some(b'G' | b'K' | b'P'), Some(b'N'))
+        | (Some(b'W'), Some(b'R')) => 1,
+        (Some(b'X'), _) => {
+            key.push('S');
+            1
+        }
+        (Some(b'W'), Some(b'H')) => {
+            key.push('W');
+            2
+        }
+        _ => 0,
+    };
+
+    for i in start..w.len() {
+        let c = w[i];
+        let prev = i.checked_sub(1).and_then(at);
+        let next = at(i + 1);
+        let after = at(i + 2);
+        let last = i + 1 == w.len();
+
+        // Doubled letters sound once, except "cc" ("accent")
+        if prev == Some(c) && c != b'C' {
+            continue;
+        }
+
+        match c {
+            b'A' | b'E' | b'I' | b'O' | b'U' => {
+                if i == start && key.is_empty() {
+                    key.push(c as char);
+                }
+            }
+            // Silent in a final "mb" ("thumb")
+            b'B' => {
+                if !(last && prev == Some(b'M')) {
+                    key.push('B');
+                }
+            }
+            b'C' => {
+                if next == Some(b'I') && after == Some(b'A') {
+                    key.push('X');
+                } else if next == Some(b'H') {
+                    key.push(if prev == Some(b'S') { 'K' } else { 'X' });
+                } else if is_front_vowel(next) {
+                    if prev != Some(b'S') {
+                        key.push('S');
+                    }
+                } else {
+                    key.push('K');
+                }
+            }
+            b'D' => {
+                if next == Some(b'G') && is_front_vowel(after) {
+                    key.push('J');
+                } else {
+                    key.push('T');
+                }
+            }
+            b'G' => {
+                let silent_gh = next == Some(b'H') && !(i + 2 == w.len() || is_vowel(after));
+                let silent_gn = next == Some(b'N')
+                    && (i + 2 == w.len() || (w[i + 2..] == *b"ED" && i + 4 == w.len()));
+                let in_dge = prev == Some(b'D') && is_front_vowel(next);
+                if silent_gh || silent_gn || in_dge {
+                    continue;
+                }
+                key.push(if is_front_vowel(next) { 'J' } else { 'K' });
+            }
+            b'H' => {
+                let after_consonant = matches!(prev, Some(b'C' | b'S' | b'P' | b'T' | b'G'));
+                // Silent after a vowel unless another vowel follows
+                let trailing = is_vowel(prev) && !is_vowel(next);
+                if !after_consonant && !trailing {
+                    key.push('H');
+                }
+            }
+            b'K' => {
+                if prev != Some(b'C') {
+                    key.push('K');
+                }
+            }
+            b'P' => key.push(if next == Some(b'H') { 'F' } else { 'P' }),
+            b'Q' => key.push('K'),
+            b'S' => {
+                if next == Some(b'H') || (next == Some(b'I') && matches!(after, Some(b'O' | b'A')))
+                {
+                    key.push('X');
+                } else {
+                    key.push('S');
+                }
+            }
+            b'T' => {
+                if next == Some(b'I') && matches!(after, Some(b'O' | b'A')) {
+                    key.push('X');
+                } else if next == Some(b'H') {
+                    key.push('0');
+                } else if !(next == Some(b'C') && after == Some(b'H')) {
+                    key.push('T');
+                }
+            }
+            b'V' => key.push('F'),
+            b'W' | b'Y' => {
+                if is_vowel(next) {
+                    key.push(c as char);
+                }
+            }
+            b'X' => key.push_str("KS"),
+            b'Z' => key.push('S'),
+            _ => key.push(c as char),
+        }
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metaphone_keys() {
+        assert_eq!(metaphone(""), "");
+        assert_eq!(metaphone("hello"), "HL");
+        assert_eq!(metaphone("school"), "SKL");
+        assert_eq!(metaphone("Xavier"), "SFR");
+        assert_eq!(metaphone("wright"), "RT");
+        assert_eq!(metaphone("edge"), "EJ");
+        assert_eq!(metaphone("thumb"), "0M");
+        assert_eq!(metaphone("nation"), "NXN");
+        assert_eq!(metaphone("box"), "BKS");
+    }
+
+    #[test]
+    fn test_sound_alikes_share_a_key() {
+        for (a, b) in [
+            ("night", "nite"),
+            ("knight", "night"),
+            ("phone", "fone"),
+            ("write", "right"),
+            ("Smith", "smyth"),
+        ] {
+            assert_eq!(metaphone(a), metaphone(b), "{} / {}", a, b);
+        }
+        assert_ne!(metaphone("cat"), metaphone("dog"));
+    }
+}
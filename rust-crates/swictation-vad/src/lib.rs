@@ -47,23 +47,55 @@
 //! ```
 
 mod error;
+mod segmenter;
 mod silero_ort;
 
 pub use error::{Result, VadError};
 use silero_ort::SileroVadOrt;
 
+/// Speech starts and ends kept for `take_events` (32 utterances), so a
+/// caller that never collects them does not grow memory
+const MAX_PENDING_EVENTS: usize = 64;
+
 /// VAD detection result
 #[derive(Debug, Clone, PartialEq)]
 pub enum VadResult {
     /// Speech detected with start sample index and audio samples
+    ///
+    /// `start_sample` is the index of the segment's first sample in the
+    /// stream fed to the detector since the last `clear()`, including any
+    /// samples reported missing via `skip_samples`.
     Speech {
-        start_sample: i32,
+        start_sample: u64,
         samples: Vec<f32>,
     },
     /// No speech detected (silence)
     Silence,
 }
 
+/// Change of speaking state, reported as soon as the VAD sees it
+///
+/// A segment is only returned after `min_silence` of quiet; these events let
+/// a UI show that the user is being heard while they are still talking.
+/// Sample offsets count like `VadResult::Speech::start_sample`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VadEvent {
+    /// First window above threshold
+    SpeechStart { sample: u64 },
+    /// End of the last window of speech, once enough silence followed it
+    /// (or the stream was flushed)
+    SpeechEnd { sample: u64 },
+}
+
+/// Speech probability of one VAD window, kept for diagnostics
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VadTracePoint {
+    /// Unix time (seconds) when the window was processed
+    pub timestamp: f64,
+    /// Model speech probability for the window
+    pub probability: f32,
+}
+
 /// VAD configuration
 #[derive(Debug, Clone)]
 pub struct VadConfig {
@@ -82,6 +114,12 @@ pub struct VadConfig {
     /// Segments longer than this are split
     pub max_speech_duration: f32,
 
+    /// Windows below threshold tolerated inside speech (default: 3 ≈ 96ms)
+    /// Bridges plosive/breath dips so words are not split. The silence of a
+    /// real pause counts from the last speech window, so this does not add to
+    /// min_silence_duration (it only sets a floor when longer than it)
+    pub hangover_frames: usize,
+
     /// Speech probability threshold (0.0 to 1.0, default: 0.5)
     /// Higher = more aggressive filtering (fewer false positives)
     pub threshold: f32,
@@ -97,12 +135,18 @@ pub struct VadConfig {
     /// How much audio to buffer before forcing a segment
     pub buffer_size_seconds: f32,
 
-    /// ONNX Runtime provider (default: "cpu")
+    /// ONNX Runtime provider (default: "cpu"); "cuda", "openvino" (optionally
+    /// "openvino:NPU" etc.), or "coreml" on macOS, falls back to CPU when
+    /// unavailable
     pub provider: Option<String>,
 
     /// Number of threads for inference (default: 1)
     pub num_threads: Option<i32>,
 
+    /// Number of recent window probabilities kept for `VadDetector::trace`
+    /// (default: 0 = tracing disabled)
+    pub trace_capacity: usize,
+
     /// Enable debug logging
     pub debug: bool,
 }
@@ -114,6 +158,7 @@ impl Default for VadConfig {
             min_silence_duration: 0.5,
             min_speech_duration: 0.25,
             max_speech_duration: 30.0,
+            hangover_frames: 3,
             // NOTE: Silero VAD ONNX model has ~100-200x lower probabilities than PyTorch JIT
             // Optimal threshold for ONNX: 0.001-0.005 (NOT 0.5 as in PyTorch examples)
             threshold: 0.003,
@@ -122,6 +167,7 @@ impl Default for VadConfig {
             buffer_size_seconds: 60.0,
             provider: None,
             num_threads: Some(1),
+            trace_capacity: 0,
             debug: false,
         }
     }
@@ -154,6 +200,12 @@ impl VadConfig {
         self
     }
 
+    /// Set number of below-threshold windows bridged inside speech
+    pub fn hangover_frames(mut self, frames: usize) -> Self {
+        self.hangover_frames = frames;
+        self
+    }
+
     /// Set detection threshold
     ///
     /// **IMPORTANT**: Silero VAD ONNX model outputs probabilities in range ~0.0005-0.002
@@ -188,6 +240,12 @@ impl VadConfig {
         self
     }
 
+    /// Keep the last `capacity` window probabilities for diagnostics
+    pub fn trace(mut self, capacity: usize) -> Self {
+        self.trace_capacity = capacity;
+        self
+    }
+
     /// Enable debug logging
     pub fn debug(mut self) -> Self {
         self.debug = true;
@@ -242,6 +300,8 @@ pub struct VadDetector {
     is_speaking: bool,
     // Buffer for incomplete chunks
     chunk_buffer: Vec<f32>,
+    // Speaking state changes not yet collected by `take_events`
+    events: Vec<VadEvent>,
 }
 
 impl VadDetector {
@@ -256,6 +316,8 @@ impl VadDetector {
             config.window_size as usize,
             (config.min_speech_duration * 1000.0) as i32,
             (config.min_silence_duration * 1000.0) as i32,
+            config.hangover_frames,
+            config.trace_capacity,
             config.provider.clone(),
             config.debug,
         )
@@ -267,6 +329,7 @@ impl VadDetector {
             total_samples_processed: 0,
             is_speaking: false,
             chunk_buffer: Vec::new(),
+            events: Vec::new(),
         })
     }
 
@@ -324,25 +387,20 @@ impl VadDetector {
                 .process(chunk)
                 .map_err(|e| VadError::processing(format!("VAD processing error: {}", e)))?
             {
-                Some(speech_samples) => {
+                Some(segment) => {
                     // Speech segment complete from VAD
-                    self.is_speaking = true;
-
                     if self.config.debug {
                         eprintln!(
-                            "VAD: Speech segment detected, {} samples",
-                            speech_samples.len()
+                            "VAD: Speech segment detected, {} samples at {}",
+                            segment.samples.len(),
+                            segment.start
                         );
                     }
 
                     // Return this speech segment
-                    let start_sample = (self
-                        .total_samples_processed
-                        .saturating_sub(speech_samples.len()))
-                        as i32;
                     result = VadResult::Speech {
-                        start_sample,
-                        samples: speech_samples,
+                        start_sample: segment.start as u64,
+                        samples: segment.samples,
                     };
                     // Don't break - continue processing remaining chunks
                 }
@@ -354,6 +412,7 @@ impl VadDetector {
 
             self.total_samples_processed += window_size;
         }
+        self.collect_events();
 
         // Save any remaining incomplete chunk for next call
         self.chunk_buffer.clear();
@@ -373,33 +432,51 @@ impl VadDetector {
 
     /// Check if speech is currently being detected (real-time)
     ///
-    /// This is faster than `process_audio` and useful for real-time indicators.
+    /// True from the window that starts speech until the one that ends it,
+    /// as of the last `process_audio` call.
     pub fn is_speech_detected(&mut self) -> bool {
         self.is_speaking
     }
 
+    /// Speech starts and ends since the last call, oldest first
+    ///
+    /// Poll after `process_audio` to follow the speaking state without
+    /// waiting for segments; every start is eventually followed by an end.
+    /// Only the most recent 32 utterances are kept between calls.
+    pub fn take_events(&mut self) -> Vec<VadEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn collect_events(&mut self) {
+        let events = self.vad.take_events();
+        if let Some(last) = events.last() {
+            self.is_speaking = matches!(last, VadEvent::SpeechStart { .. });
+        }
+        self.events.extend(events);
+        keep_recent_events(&mut self.events);
+    }
+
     /// Flush any remaining audio in the buffer
     ///
     /// Call this at the end of a stream to process any remaining audio.
     /// Returns any remaining speech segment if available.
     pub fn flush(&mut self) -> Option<VadResult> {
         // Get any remaining buffered speech from VAD
-        if let Some(speech_samples) = self.vad.flush() {
+        let segment = self.vad.flush();
+        self.collect_events();
+        if let Some(segment) = segment {
             if self.config.debug {
                 eprintln!(
                     "VAD: Flushed remaining speech, {} samples",
-                    speech_samples.len()
+                    segment.samples.len()
                 );
             }
 
             self.is_speaking = false;
-            let start_sample = (self
-                .total_samples_processed
-                .saturating_sub(speech_samples.len())) as i32;
 
             Some(VadResult::Speech {
-                start_sample,
-                samples: speech_samples,
+                start_sample: segment.start as u64,
+                samples: segment.samples,
             })
         } else {
             self.is_speaking = false;
@@ -415,6 +492,16 @@ impl VadDetector {
         self.is_speaking = false;
         self.total_samples_processed = 0;
         self.chunk_buffer.clear();
+        self.events.clear();
+    }
+
+    /// Account for `count` samples missing from the stream (e.g. dropped chunks)
+    ///
+    /// Keeps `start_sample` of later segments aligned with the capture
+    /// position. Samples buffered but not yet processed are treated as
+    /// preceding the gap.
+    pub fn skip_samples(&mut self, count: u64) {
+        self.vad.skip_samples(count as usize);
     }
 
     /// Get total samples processed
@@ -431,12 +518,101 @@ impl VadDetector {
     pub fn config(&self) -> &VadConfig {
         &self.config
     }
+
+    /// Recent per-window speech probabilities, oldest first
+    ///
+    /// Empty unless `trace_capacity` was set. Useful for diagnosing
+    /// "it never detects my speech" reports against the configured threshold.
+    pub fn trace(&self) -> Vec<VadTracePoint> {
+        self.vad.trace()
+    }
+
+    /// Speech probability of the most recent VAD window (0 before the first)
+    ///
+    /// Compare with `config().threshold`, not 0.5: ONNX probabilities are
+    /// far lower than PyTorch ones.
+    pub fn speech_probability(&self) -> f32 {
+        self.vad.last_probability()
+    }
+
+    /// Speech probability of every VAD window since the last call, oldest
+    /// first, for confidence meters drawn alongside the waveform
+    ///
+    /// Each value covers `window_size` samples (32 ms by default). Only the
+    /// most recent ~8 s are kept between calls.
+    pub fn take_probabilities(&mut self) -> Vec<f32> {
+        self.vad.take_probabilities()
+    }
+
+    /// Highest window speech probability since the last call, for level
+    /// meters that sample slower than the VAD windows
+    pub fn take_peak_probability(&mut self) -> f32 {
+        self.vad.take_peak_probability()
+    }
+
+    /// Retune threshold and silence/speech durations on the live detector
+    ///
+    /// The ONNX session is reused, so this is cheap enough to call while the
+    /// user drags a settings slider. Invalid values are rejected and leave the
+    /// current configuration untouched.
+    pub fn update_tuning(
+        &mut self,
+        threshold: f32,
+        min_silence_duration: f32,
+        min_speech_duration: f32,
+    ) -> Result<()> {
+        let config = VadConfig {
+            threshold,
+            min_silence_duration,
+            min_speech_duration,
+            ..self.config.clone()
+        };
+        config.validate()?;
+
+        self.vad.set_params(
+            threshold,
+            (min_speech_duration * 1000.0) as i32,
+            (min_silence_duration * 1000.0) as i32,
+            config.hangover_frames,
+        );
+        self.config = config;
+        Ok(())
+    }
+}
+
+/// Drop the oldest events beyond `MAX_PENDING_EVENTS`, a start and its
+/// end together, so the events kept still begin with a start
+fn keep_recent_events(events: &mut Vec<VadEvent>) {
+    let excess = events.len().saturating_sub(MAX_PENDING_EVENTS);
+    events.drain(..excess.next_multiple_of(2).min(events.len()));
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_pending_events_are_capped_in_pairs() {
+        let utterance = |n: u64| {
+            [
+                VadEvent::SpeechStart { sample: n * 1000 },
+                VadEvent::SpeechEnd {
+                    sample: n * 1000 + 500,
+                },
+            ]
+        };
+        let mut events: Vec<VadEvent> = (0..40).flat_map(utterance).collect();
+        events.push(VadEvent::SpeechStart { sample: 40_000 });
+
+        keep_recent_events(&mut events);
+        assert!(events.len() <= MAX_PENDING_EVENTS);
+        assert!(matches!(events[0], VadEvent::SpeechStart { .. }));
+        assert_eq!(
+            events.last(),
+            Some(&VadEvent::SpeechStart { sample: 40_000 })
+        );
+    }
+
     #[test]
     fn test_config_validation() {
         // Valid config
@@ -476,12 +652,14 @@ mod tests {
             .min_silence(0.3)
             .min_speech(0.2)
             .threshold(0.6)
+            .hangover_frames(5)
             .debug();
 
         assert_eq!(config.model_path, "/path/to/model.onnx");
         assert_eq!(config.min_silence_duration, 0.3);
         assert_eq!(config.min_speech_duration, 0.2);
         assert_eq!(config.threshold, 0.6);
+        assert_eq!(config.hangover_frames, 5);
         assert!(config.debug);
     }
 
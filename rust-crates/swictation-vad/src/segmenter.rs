@@ -0,0 +1,342 @@
+//! Speech segmentation from per-window speech probabilities
+//!
+//! Kept separate from the ONNX model so the segment boundary rules can be
+//! exercised with synthetic probability traces.
+
+use crate::VadEvent;
+
+/// A completed speech segment
+pub(crate) struct Segment {
+    /// Index of the segment's first sample in the stream fed since `reset`
+    pub start: usize,
+    pub samples: Vec<f32>,
+}
+
+/// Turns a stream of (window, probability) pairs into speech segments
+pub(crate) struct Segmenter {
+    threshold: f32,
+    min_speech_samples: usize,
+    min_silence_samples: usize,
+
+    /// Windows below threshold that are still treated as speech
+    ///
+    /// Plosives and breaths produce dips of one or two windows in the middle
+    /// of words. While a dip is this short the silence countdown does not
+    /// start, so segments are not split even with a very short `min_silence`.
+    hangover_windows: usize,
+
+    triggered: bool,
+    temp_end: usize,
+    current_sample: usize,
+    windows_below: usize,
+
+    /// Stream index where the buffered speech begins
+    speech_start: usize,
+    speech_buffer: Vec<f32>,
+
+    /// Speech starts and ends not yet collected by `take_events`
+    events: Vec<VadEvent>,
+}
+
+impl Segmenter {
+    pub(crate) fn new(
+        threshold: f32,
+        min_speech_samples: usize,
+        min_silence_samples: usize,
+        hangover_windows: usize,
+    ) -> Self {
+        Self {
+            threshold,
+            min_speech_samples,
+            min_silence_samples,
+            hangover_windows,
+            triggered: false,
+            temp_end: 0,
+            current_sample: 0,
+            windows_below: 0,
+            speech_start: 0,
+            speech_buffer: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub(crate) fn set_params(
+        &mut self,
+        threshold: f32,
+        min_speech_samples: usize,
+        min_silence_samples: usize,
+        hangover_windows: usize,
+    ) {
+        self.threshold = threshold;
+        self.min_speech_samples = min_speech_samples;
+        self.min_silence_samples = min_silence_samples;
+        self.hangover_windows = hangover_windows;
+    }
+
+    /// Feed one window and its speech probability
+    ///
+    /// Returns the buffered segment once enough silence follows it.
+    pub(crate) fn push(&mut self, window: &[f32], speech_prob: f32) -> Option<Segment> {
+        self.current_sample += window.len();
+
+        if speech_prob >= self.threshold {
+            if self.speech_buffer.is_empty() {
+                self.speech_start = self.current_sample - window.len();
+            }
+            if !self.triggered {
+                self.events.push(VadEvent::SpeechStart {
+                    sample: (self.current_sample - window.len()) as u64,
+                });
+            }
+            self.triggered = true;
+            self.windows_below = 0;
+            // Track the END of speech (last sample where speech was detected)
+            self.temp_end = self.current_sample;
+            self.speech_buffer.extend_from_slice(window);
+            return None;
+        }
+
+        if !self.triggered {
+            return None;
+        }
+
+        self.windows_below += 1;
+        if self.windows_below <= self.hangover_windows {
+            // Brief dip - hold the segment open, but keep timing the silence
+            // from the last speech window so a real pause ends on time
+            self.speech_buffer.extend_from_slice(window);
+            return None;
+        }
+
+        if self.current_sample - self.temp_end > self.min_silence_samples {
+            // Silence duration exceeded threshold - speech segment complete
+            self.end_speech();
+            // Too short to be speech (clicks, noise) is discarded
+            self.take_segment()
+        } else {
+            // Still within silence tolerance, keep buffering
+            self.speech_buffer.extend_from_slice(window);
+            None
+        }
+    }
+
+    /// Speech starts and ends since the last call, oldest first
+    ///
+    /// Every start is followed by an end, even when the speech turns out too
+    /// short to become a segment.
+    pub(crate) fn take_events(&mut self) -> Vec<VadEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    pub(crate) fn threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// Samples fed so far
+    pub(crate) fn current_sample(&self) -> usize {
+        self.current_sample
+    }
+
+    /// Advance the stream position over a gap in the input
+    ///
+    /// A gap counts as silence: an open segment closes once the gap plus
+    /// following quiet exceeds `min_silence`.
+    pub(crate) fn skip(&mut self, samples: usize) {
+        self.current_sample += samples;
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.triggered = false;
+        self.temp_end = 0;
+        self.current_sample = 0;
+        self.windows_below = 0;
+        self.speech_start = 0;
+        self.speech_buffer.clear();
+        self.events.clear();
+    }
+
+    /// Return buffered speech at end of stream (if long enough)
+    pub(crate) fn flush(&mut self) -> Option<Segment> {
+        if self.triggered {
+            self.end_speech();
+        }
+        self.take_segment()
+    }
+
+    /// Leave the speaking state; speech ended after the last window above
+    /// threshold (or bridged by the hangover)
+    fn end_speech(&mut self) {
+        self.triggered = false;
+        self.windows_below = 0;
+        self.events.push(VadEvent::SpeechEnd {
+            sample: self.temp_end as u64,
+        });
+    }
+
+    fn take_segment(&mut self) -> Option<Segment> {
+        let samples = std::mem::take(&mut self.speech_buffer);
+        (!samples.is_empty() && samples.len() >= self.min_speech_samples).then_some(Segment {
+            start: self.speech_start,
+            samples,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: usize = 512;
+
+    /// Run a probability trace and return the lengths (in windows) of emitted segments
+    fn run(segmenter: &mut Segmenter, trace: &[f32]) -> Vec<usize> {
+        let window = vec![0.1; WINDOW];
+        trace
+            .iter()
+            .filter_map(|&p| segmenter.push(&window, p))
+            .map(|s| s.samples.len() / WINDOW)
+            .collect()
+    }
+
+    /// 5 windows speech, `dip` windows below threshold, 5 windows speech, then long silence
+    fn trace_with_dip(dip: usize) -> Vec<f32> {
+        let mut trace = vec![0.9; 5];
+        trace.extend(vec![0.0; dip]);
+        trace.extend(vec![0.9; 5]);
+        trace.extend(vec![0.0; 20]);
+        trace
+    }
+
+    #[test]
+    fn test_short_dip_split_without_hangover() {
+        // min_silence of one window: any 2-window dip ends the segment
+        let mut segmenter = Segmenter::new(0.5, WINDOW, WINDOW, 0);
+        assert_eq!(run(&mut segmenter, &trace_with_dip(2)), vec![6, 6]);
+    }
+
+    #[test]
+    fn test_hangover_bridges_short_dip() {
+        let mut segmenter = Segmenter::new(0.5, WINDOW, WINDOW, 3);
+        // Dip of 2 windows (~64ms) stays within the hangover: one segment,
+        // ending once the hangover has passed
+        assert_eq!(run(&mut segmenter, &trace_with_dip(2)), vec![15]);
+    }
+
+    #[test]
+    fn test_hangover_does_not_bridge_long_pause() {
+        let mut segmenter = Segmenter::new(0.5, WINDOW, WINDOW, 3);
+        // 10-window pause (~320ms) exceeds the hangover: two segments
+        assert_eq!(run(&mut segmenter, &trace_with_dip(10)), vec![8, 8]);
+    }
+
+    #[test]
+    fn test_hangover_does_not_delay_speech_end() {
+        // min_silence of 4 windows, longer than the 3-window hangover
+        let mut segmenter = Segmenter::new(0.5, WINDOW, 4 * WINDOW, 3);
+        let window = vec![0.1; WINDOW];
+        let mut trace = vec![0.9; 5];
+        trace.extend(vec![0.0; 10]);
+
+        // Ends on the window that makes the silence exceed min_silence,
+        // counted from the end of the last speech window
+        let ended_at = trace
+            .iter()
+            .position(|&p| segmenter.push(&window, p).is_some());
+        assert_eq!(ended_at, Some(5 + 4));
+        assert_eq!(
+            segmenter.take_events(),
+            vec![
+                VadEvent::SpeechStart { sample: 0 },
+                VadEvent::SpeechEnd {
+                    sample: 5 * WINDOW as u64
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_min_speech_discards_blips() {
+        let mut segmenter = Segmenter::new(0.5, 4 * WINDOW, WINDOW, 0);
+        let mut trace = vec![0.9; 2];
+        trace.extend(vec![0.0; 10]);
+        assert!(run(&mut segmenter, &trace).is_empty());
+    }
+
+    #[test]
+    fn test_flush_returns_open_segment() {
+        let mut segmenter = Segmenter::new(0.5, WINDOW, WINDOW * 10, 0);
+        assert!(run(&mut segmenter, &[0.9, 0.9, 0.0]).is_empty());
+        assert_eq!(segmenter.flush().map(|s| s.samples.len()), Some(3 * WINDOW));
+        assert!(segmenter.flush().is_none());
+    }
+
+    #[test]
+    fn test_events_mark_speech_boundaries() {
+        let mut segmenter = Segmenter::new(0.5, 4 * WINDOW, WINDOW, 0);
+
+        // Blip too short for a segment still starts and ends speech
+        let mut trace = vec![0.0, 0.9];
+        trace.extend(vec![0.0; 3]);
+        // Then real speech from window 5, still open at the end
+        trace.extend(vec![0.9; 6]);
+        assert!(run(&mut segmenter, &trace).is_empty());
+        assert_eq!(
+            segmenter.take_events(),
+            vec![
+                VadEvent::SpeechStart {
+                    sample: WINDOW as u64
+                },
+                VadEvent::SpeechEnd {
+                    sample: 2 * WINDOW as u64
+                },
+                VadEvent::SpeechStart {
+                    sample: 5 * WINDOW as u64
+                },
+            ]
+        );
+        assert!(segmenter.take_events().is_empty());
+
+        assert!(segmenter.flush().is_some());
+        assert_eq!(
+            segmenter.take_events(),
+            vec![VadEvent::SpeechEnd {
+                sample: 11 * WINDOW as u64
+            }]
+        );
+        segmenter.flush();
+        assert!(segmenter.take_events().is_empty());
+    }
+
+    #[test]
+    fn test_segment_start_is_stream_index() {
+        let mut segmenter = Segmenter::new(0.5, WINDOW, WINDOW, 0);
+        let window = vec![0.1; WINDOW];
+
+        // 3 windows of silence, then speech starting at window 3
+        let mut trace = vec![0.0; 3];
+        trace.extend(vec![0.9; 4]);
+        trace.extend(vec![0.0; 5]);
+        // Second utterance starting at window 15
+        trace.extend(vec![0.0; 3]);
+        trace.extend(vec![0.9; 4]);
+        trace.extend(vec![0.0; 5]);
+
+        let starts: Vec<usize> = trace
+            .iter()
+            .filter_map(|&p| segmenter.push(&window, p))
+            .map(|s| s.start)
+            .collect();
+        assert_eq!(starts, vec![3 * WINDOW, 15 * WINDOW]);
+
+        // A gap of 100 windows shifts the next segment by the same amount
+        segmenter.skip(100 * WINDOW);
+        let mut trace = vec![0.9; 4];
+        trace.extend(vec![0.0; 5]);
+        let starts: Vec<usize> = trace
+            .iter()
+            .filter_map(|&p| segmenter.push(&window, p))
+            .map(|s| s.start)
+            .collect();
+        assert_eq!(starts, vec![(24 + 100) * WINDOW]);
+    }
+}
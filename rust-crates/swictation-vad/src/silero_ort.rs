@@ -1,35 +1,139 @@
 //! Direct ONNX Runtime implementation of Silero VAD
 //! Replaces sherpa-rs dependency with modern ort crate
 
-use crate::{Result, VadError};
+use crate::segmenter::{Segment, Segmenter};
+use crate::{Result, VadError, VadEvent, VadTracePoint};
 use ndarray::{Array2, Array3, ArrayView3};
+#[cfg(target_os = "macos")]
+use ort::execution_providers::coreml::{CoreMLComputeUnits, CoreMLModelFormat};
+#[cfg(target_os = "macos")]
+use ort::execution_providers::CoreMLExecutionProvider;
 use ort::{
-    execution_providers::{CPUExecutionProvider, CUDAExecutionProvider},
+    execution_providers::{
+        CPUExecutionProvider, CUDAExecutionProvider, ExecutionProviderDispatch,
+        OpenVINOExecutionProvider,
+    },
     inputs,
     session::Session,
     value::Tensor,
 };
+use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Window probabilities kept for `take_probabilities` (~8 s of 32 ms
+/// windows), so a caller that never collects them does not grow memory
+const MAX_PENDING_PROBABILITIES: usize = 256;
+
+/// Window probabilities not yet collected, dropping the oldest past
+/// `MAX_PENDING_PROBABILITIES`
+#[derive(Debug, Default)]
+struct PendingProbabilities(VecDeque<f32>);
+
+impl PendingProbabilities {
+    fn push(&mut self, probability: f32) {
+        if self.0.len() == MAX_PENDING_PROBABILITIES {
+            self.0.pop_front();
+        }
+        self.0.push_back(probability);
+    }
+
+    /// Oldest first
+    fn take(&mut self) -> Vec<f32> {
+        self.0.drain(..).collect()
+    }
+
+    fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+/// Execution provider for a requested name ("cuda", "openvino" or
+/// "openvino:<device>" for Intel GPUs and NPUs, or "coreml" on Apple
+/// Silicon); `None` means CPU
+fn accelerated_provider(
+    provider: Option<&str>,
+) -> Option<(&'static str, ExecutionProviderDispatch)> {
+    let provider = provider.map(str::to_lowercase);
+    match provider.as_deref() {
+        Some(p) if p.contains("cuda") => Some(("CUDA", CUDAExecutionProvider::default().build())),
+        Some(p) if p.starts_with("openvino") => {
+            let device = p.split_once(':').map_or("GPU", |(_, device)| device);
+            Some((
+                "OpenVINO",
+                OpenVINOExecutionProvider::default()
+                    .with_device_type(device.to_uppercase())
+                    .build(),
+            ))
+        }
+        #[cfg(target_os = "macos")]
+        Some(p) if p.contains("coreml") => Some((
+            "CoreML",
+            CoreMLExecutionProvider::default()
+                .with_model_format(CoreMLModelFormat::NeuralNetwork)
+                .with_compute_units(CoreMLComputeUnits::All) // CPU + GPU + ANE
+                .build(),
+        )),
+        _ => None,
+    }
+}
+
+/// Session on the requested provider, falling back to CPU when it is
+/// unknown or can't be used
+fn build_session(model_path: &str, provider: Option<&str>) -> Result<Session> {
+    if let Some((name, ep)) = accelerated_provider(provider) {
+        let session = Session::builder()
+            .map_err(|e| {
+                VadError::initialization(format!("Failed to create session builder: {}", e))
+            })?
+            .with_execution_providers([ep.error_on_failure()])
+            .and_then(|builder| builder.commit_from_file(model_path));
+        match session {
+            Ok(s) => {
+                eprintln!("Silero VAD: Using {} provider", name);
+                return Ok(s);
+            }
+            Err(e) => {
+                eprintln!(
+                    "Silero VAD: {} not available ({}), falling back to CPU",
+                    name, e
+                );
+            }
+        }
+    }
+
+    Session::builder()
+        .map_err(|e| VadError::initialization(format!("Failed to create session builder: {}", e)))?
+        .with_execution_providers([CPUExecutionProvider::default().build()])
+        .map_err(|e| VadError::initialization(format!("Failed to set CPU provider: {}", e)))?
+        .commit_from_file(model_path)
+        .map_err(|e| VadError::initialization(format!("Failed to load model with CPU: {}", e)))
+}
 
 /// Silero VAD model using direct ONNX Runtime
 pub struct SileroVadOrt {
     session: Arc<Mutex<Session>>,
     sample_rate: i32,
     window_size: usize,
-    threshold: f32,
-    min_speech_samples: usize,
-    min_silence_samples: usize,
 
     // State for streaming - Silero VAD v6 uses LSTM with separate h and c states
     // Each state is [2 layers, 1 batch, 64 hidden units]
     h_state: Array3<f32>, // LSTM hidden state [2, 1, 64]
     c_state: Array3<f32>, // LSTM cell state [2, 1, 64]
-    triggered: bool,
-    temp_end: usize,
-    current_sample: usize,
 
-    // Speech segment buffering
-    speech_buffer: Vec<f32>,
+    // Speech segment boundaries and buffering
+    segmenter: Segmenter,
+
+    // Recent speech probabilities for diagnostics (capacity 0 = disabled)
+    trace: VecDeque<VadTracePoint>,
+    trace_capacity: usize,
+
+    // Highest speech probability since `take_peak_probability`
+    peak_probability: f32,
+
+    // Per-window probabilities since `take_probabilities`, newest last
+    probabilities: PendingProbabilities,
+    last_probability: f32,
 
     // Debug mode
     debug: bool,
@@ -45,94 +149,27 @@ impl SileroVadOrt {
         window_size: usize,
         min_speech_duration_ms: i32,
         min_silence_duration_ms: i32,
+        hangover_windows: usize,
+        trace_capacity: usize,
         provider: Option<String>,
         debug: bool,
     ) -> Result<Self> {
-        // Build session with appropriate provider
-        let session = if let Some(ref prov) = provider {
-            if prov.contains("cuda") || prov.contains("CUDA") {
-                // Try CUDA provider
-                match Session::builder()
-                    .map_err(|e| {
-                        VadError::initialization(format!("Failed to create session builder: {}", e))
-                    })?
-                    .with_execution_providers([CUDAExecutionProvider::default().build()])
-                    .map_err(|e| {
-                        VadError::initialization(format!("Failed to set CUDA provider: {}", e))
-                    })?
-                    .commit_from_file(model_path)
-                {
-                    Ok(s) => {
-                        println!("Silero VAD: Using CUDA provider");
-                        s
-                    }
-                    Err(e) => {
-                        println!(
-                            "Silero VAD: CUDA not available ({}), falling back to CPU",
-                            e
-                        );
-                        Session::builder()
-                            .map_err(|e| {
-                                VadError::initialization(format!(
-                                    "Failed to create session builder: {}",
-                                    e
-                                ))
-                            })?
-                            .with_execution_providers([CPUExecutionProvider::default().build()])
-                            .map_err(|e| {
-                                VadError::initialization(format!(
-                                    "Failed to set CPU provider: {}",
-                                    e
-                                ))
-                            })?
-                            .commit_from_file(model_path)
-                            .map_err(|e| {
-                                VadError::initialization(format!(
-                                    "Failed to load model with CPU: {}",
-                                    e
-                                ))
-                            })?
-                    }
-                }
-            } else {
-                Session::builder()
-                    .map_err(|e| {
-                        VadError::initialization(format!("Failed to create session builder: {}", e))
-                    })?
-                    .with_execution_providers([CPUExecutionProvider::default().build()])
-                    .map_err(|e| {
-                        VadError::initialization(format!("Failed to set CPU provider: {}", e))
-                    })?
-                    .commit_from_file(model_path)
-                    .map_err(|e| VadError::initialization(format!("Failed to load model: {}", e)))?
-            }
-        } else {
-            Session::builder()
-                .map_err(|e| {
-                    VadError::initialization(format!("Failed to create session builder: {}", e))
-                })?
-                .with_execution_providers([CPUExecutionProvider::default().build()])
-                .map_err(|e| {
-                    VadError::initialization(format!("Failed to set CPU provider: {}", e))
-                })?
-                .commit_from_file(model_path)
-                .map_err(|e| VadError::initialization(format!("Failed to load model: {}", e)))?
-        };
+        let session = build_session(model_path, provider.as_deref())?;
 
         // Print model input/output names for debugging
-        println!("=== ONNX Model Metadata ===");
-        println!("Model inputs:");
+        eprintln!("=== ONNX Model Metadata ===");
+        eprintln!("Model inputs:");
         for input in session.inputs.iter() {
-            println!("  - name: '{}' (type: {:?})", input.name, input.input_type);
+            eprintln!("  - name: '{}' (type: {:?})", input.name, input.input_type);
         }
-        println!("Model outputs:");
+        eprintln!("Model outputs:");
         for output in session.outputs.iter() {
-            println!(
+            eprintln!(
                 "  - name: '{}' (type: {:?})",
                 output.name, output.output_type
             );
         }
-        println!("===========================");
+        eprintln!("===========================");
 
         // Calculate sample counts from durations
         let min_speech_samples =
@@ -148,21 +185,25 @@ impl SileroVadOrt {
             session: Arc::new(Mutex::new(session)),
             sample_rate,
             window_size,
-            threshold,
-            min_speech_samples,
-            min_silence_samples,
             h_state,
             c_state,
-            triggered: false,
-            temp_end: 0,
-            current_sample: 0,
-            speech_buffer: Vec::new(),
+            segmenter: Segmenter::new(
+                threshold,
+                min_speech_samples,
+                min_silence_samples,
+                hangover_windows,
+            ),
+            trace: VecDeque::with_capacity(trace_capacity),
+            trace_capacity,
+            peak_probability: 0.0,
+            probabilities: PendingProbabilities::default(),
+            last_probability: 0.0,
             debug,
         })
     }
 
     /// Process audio chunk and detect speech
-    pub fn process(&mut self, audio_chunk: &[f32]) -> Result<Option<Vec<f32>>> {
+    pub fn process(&mut self, audio_chunk: &[f32]) -> Result<Option<Segment>> {
         if audio_chunk.len() != self.window_size {
             return Err(VadError::processing(format!(
                 "Expected {} samples, got {}",
@@ -176,7 +217,7 @@ impl SileroVadOrt {
         let input_array = Array2::from_shape_vec((1, audio_chunk.len()), audio_chunk.to_vec())
             .map_err(|e| VadError::processing(format!("Failed to reshape input: {}", e)))?;
 
-        if self.debug && self.current_sample == 0 {
+        if self.debug && self.segmenter.current_sample() == 0 {
             eprintln!("VAD Debug:");
             eprintln!("  input shape: {:?}", input_array.shape());
             eprintln!(
@@ -218,7 +259,7 @@ impl SileroVadOrt {
             ])
             .map_err(|e| VadError::processing(format!("Failed to run inference: {}", e)))?;
 
-        if self.debug && self.current_sample == 0 {
+        if self.debug && self.segmenter.current_sample() == 0 {
             eprintln!("  Model returned {} outputs", outputs.len());
         }
 
@@ -231,7 +272,7 @@ impl SileroVadOrt {
             .map_err(|e| VadError::processing(format!("Failed to reshape prob: {}", e)))?;
         let speech_prob = output_array[[0, 0]];
 
-        if self.debug && self.current_sample == 0 {
+        if self.debug && self.segmenter.current_sample() == 0 {
             eprintln!("  Output array shape: {:?}", output_array.shape());
             eprintln!("  Speech probability: {}", speech_prob);
         }
@@ -250,7 +291,7 @@ impl SileroVadOrt {
             .map_err(|e| VadError::processing(format!("Failed to reshape new_c: {}", e)))?;
 
         // Copy state data
-        if self.debug && self.current_sample == 0 {
+        if self.debug && self.segmenter.current_sample() == 0 {
             eprintln!("  h_state before: sum={}", self.h_state.sum());
             eprintln!("  c_state before: sum={}", self.c_state.sum());
             eprintln!("  new_h sum: {}", new_h.sum());
@@ -259,79 +300,155 @@ impl SileroVadOrt {
         self.h_state.assign(&new_h);
         self.c_state.assign(&new_c);
 
-        self.current_sample += audio_chunk.len();
-
         if self.debug {
             // Print every chunk between 1-2 seconds where we expect speech (RMS=0.087746 in second 1)
-            let time_s = self.current_sample as f32 / self.sample_rate as f32;
+            let current_sample = self.segmenter.current_sample() + audio_chunk.len();
+            let time_s = current_sample as f32 / self.sample_rate as f32;
             if (1.0..=2.0).contains(&time_s)
                 || (3.0..=4.5).contains(&time_s)
-                || self
-                    .current_sample
-                    .is_multiple_of(self.sample_rate as usize)
+                || current_sample.is_multiple_of(self.sample_rate as usize)
             {
                 eprintln!(
                     "VAD: t={:.2}s, prob={:.6}, threshold={:.3}",
-                    time_s, speech_prob, self.threshold
+                    time_s,
+                    speech_prob,
+                    self.segmenter.threshold()
                 );
             }
         }
 
-        // Improved speech detection with buffering
-        if speech_prob >= self.threshold {
-            // Speech detected
-            if !self.triggered {
-                self.triggered = true;
-            }
-            // Update temp_end to track the END of speech (last sample where speech was detected)
-            self.temp_end = self.current_sample;
-            // Add samples to buffer
-            self.speech_buffer.extend_from_slice(audio_chunk);
-        } else if self.triggered {
-            // Was speaking, now silence
-            if self.current_sample - self.temp_end > self.min_silence_samples {
-                // Silence duration exceeded threshold - speech segment complete
-                if self.speech_buffer.len() >= self.min_speech_samples {
-                    // Have enough speech samples to return
-                    let speech = self.speech_buffer.clone();
-                    self.speech_buffer.clear();
-                    self.triggered = false;
-                    return Ok(Some(speech));
-                } else {
-                    // Not enough speech, discard (was noise)
-                    self.speech_buffer.clear();
-                    self.triggered = false;
-                }
-            } else {
-                // Still within silence tolerance, keep buffering
-                self.speech_buffer.extend_from_slice(audio_chunk);
+        self.peak_probability = self.peak_probability.max(speech_prob);
+        self.probabilities.push(speech_prob);
+        self.last_probability = speech_prob;
+        if self.trace_capacity > 0 {
+            if self.trace.len() == self.trace_capacity {
+                self.trace.pop_front();
             }
+            self.trace.push_back(VadTracePoint {
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs_f64())
+                    .unwrap_or_default(),
+                probability: speech_prob,
+            });
         }
 
-        Ok(None)
+        Ok(self.segmenter.push(audio_chunk, speech_prob))
+    }
+
+    /// Recent (timestamp, probability) pairs, oldest first
+    pub fn trace(&self) -> Vec<VadTracePoint> {
+        self.trace.iter().copied().collect()
+    }
+
+    /// Probability of the most recent window (0 before the first)
+    pub fn last_probability(&self) -> f32 {
+        self.last_probability
+    }
+
+    /// Per-window probabilities since the last call, oldest first
+    pub fn take_probabilities(&mut self) -> Vec<f32> {
+        self.probabilities.take()
+    }
+
+    /// Speech starts and ends since the last call
+    pub fn take_events(&mut self) -> Vec<VadEvent> {
+        self.segmenter.take_events()
+    }
+
+    /// Highest speech probability since the last call (0 if none)
+    pub fn take_peak_probability(&mut self) -> f32 {
+        std::mem::take(&mut self.peak_probability)
+    }
+
+    /// Update detection parameters without reloading the model
+    ///
+    /// Takes effect from the next processed window; LSTM state and any
+    /// buffered speech are kept.
+    pub fn set_params(
+        &mut self,
+        threshold: f32,
+        min_speech_duration_ms: i32,
+        min_silence_duration_ms: i32,
+        hangover_windows: usize,
+    ) {
+        self.segmenter.set_params(
+            threshold,
+            (min_speech_duration_ms as f32 * self.sample_rate as f32 / 1000.0) as usize,
+            (min_silence_duration_ms as f32 * self.sample_rate as f32 / 1000.0) as usize,
+            hangover_windows,
+        );
     }
 
     /// Reset the VAD state
     pub fn reset(&mut self) {
         self.h_state.fill(0.0);
         self.c_state.fill(0.0);
-        self.triggered = false;
-        self.temp_end = 0;
-        self.current_sample = 0;
-        self.speech_buffer.clear();
+        self.segmenter.reset();
+        self.probabilities.clear();
+        self.last_probability = 0.0;
+    }
+
+    /// Advance the stream position past samples that were never fed
+    pub fn skip_samples(&mut self, count: usize) {
+        self.segmenter.skip(count);
     }
 
     /// Flush any remaining buffered speech (call at end of stream)
-    pub fn flush(&mut self) -> Option<Vec<f32>> {
-        if !self.speech_buffer.is_empty() && self.speech_buffer.len() >= self.min_speech_samples {
-            let speech = self.speech_buffer.clone();
-            self.speech_buffer.clear();
-            self.triggered = false;
-            Some(speech)
-        } else {
-            self.speech_buffer.clear();
-            self.triggered = false;
-            None
+    pub fn flush(&mut self) -> Option<Segment> {
+        self.segmenter.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider_name(provider: &str) -> Option<&'static str> {
+        accelerated_provider(Some(provider)).map(|(name, _)| name)
+    }
+
+    #[test]
+    fn test_provider_selection() {
+        assert_eq!(provider_name("CUDA"), Some("CUDA"));
+        assert_eq!(provider_name("openvino:npu"), Some("OpenVINO"));
+        assert!(accelerated_provider(None).is_none());
+    }
+
+    #[test]
+    fn test_unknown_provider_uses_cpu() {
+        assert_eq!(provider_name("rocm"), None);
+        assert_eq!(provider_name("cpu"), None);
+        #[cfg(not(target_os = "macos"))]
+        assert_eq!(provider_name("coreml"), None);
+    }
+
+    #[test]
+    #[ignore] // Requires model files
+    fn test_unavailable_provider_falls_back_to_cpu() {
+        let model = "/opt/swictation/models/silero-vad/silero_vad.onnx";
+        for provider in ["rocm", "coreml"] {
+            assert!(build_session(model, Some(provider)).is_ok(), "{}", provider);
+        }
+    }
+
+    #[test]
+    fn test_pending_probabilities_drop_oldest() {
+        let mut pending = PendingProbabilities::default();
+        for i in 0..MAX_PENDING_PROBABILITIES + 10 {
+            pending.push(i as f32);
         }
+
+        let probabilities = pending.take();
+        assert_eq!(probabilities.len(), MAX_PENDING_PROBABILITIES);
+        assert_eq!(probabilities[0], 10.0);
+        assert_eq!(
+            probabilities.last(),
+            Some(&((MAX_PENDING_PROBABILITIES + 9) as f32))
+        );
+
+        assert!(pending.take().is_empty());
+        pending.push(0.5);
+        assert_eq!(pending.take(), [0.5]);
     }
 }
@@ -3,12 +3,15 @@
 //! Pure computation module for client-side processing:
 //! - Metrics aggregations (WPM trends, latency stats)
 //! - Text diff algorithms (Myers diff for correction preview)
-//! - Pattern clustering (k-means for learned corrections)
+//! - Pattern clustering (seeded k-means with silhouette scores for learned corrections)
+//! - Locale-aware display formatting (numbers, durations, local-day buckets)
 //!
 //! No database dependencies - designed to process data fetched via Tauri commands.
 
+use chrono::{Datelike, Timelike};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use swictation_textsim::{levenshtein, Edit};
 use wasm_bindgen::prelude::*;
 
 // Initialize panic hook for better error messages
@@ -159,7 +162,7 @@ pub fn calculate_wpm_trend(sessions_json: &str, bucket_size_hours: f64) -> Resul
 // ============================================================================
 
 /// Myers diff edit operations
-#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 pub enum DiffOp {
     Equal,
     Insert,
@@ -167,7 +170,7 @@ pub enum DiffOp {
 }
 
 /// Single diff hunk
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct DiffHunk {
     pub op: DiffOp,
     pub text: String,
@@ -195,97 +198,84 @@ pub fn compute_text_diff(original: &str, corrected: &str) -> Result<String, JsVa
         .map_err(|e| JsValue::from_str(&format!("JSON serialize error: {}", e)))
 }
 
-/// Myers diff algorithm (simplified word-level implementation)
+/// Word-level diff hunks, one per word
 fn myers_diff<T>(a: &[T], b: &[T]) -> Vec<DiffHunk>
 where
     T: PartialEq + std::fmt::Display,
 {
-    let n = a.len();
-    let m = b.len();
-    let max = n + m;
-
-    let mut v: HashMap<i32, i32> = HashMap::new();
-    v.insert(1, 0);
-
-    let mut trace: Vec<HashMap<i32, i32>> = Vec::new();
-
-    'outer: for d in 0..=max as i32 {
-        trace.push(v.clone());
-
-        for k in (-d..=d).step_by(2) {
-            let mut x = if k == -d
-                || (k != d && v.get(&(k - 1)).unwrap_or(&-1) < v.get(&(k + 1)).unwrap_or(&-1))
-            {
-                *v.get(&(k + 1)).unwrap_or(&0)
-            } else {
-                v.get(&(k - 1)).unwrap_or(&0) + 1
-            };
-
-            let mut y = x - k;
+    swictation_textsim::myers_diff(a, b)
+        .into_iter()
+        .map(|edit| match edit {
+            Edit::Equal(i, _) => DiffHunk {
+                op: DiffOp::Equal,
+                text: a[i].to_string(),
+            },
+            Edit::Delete(i) => DiffHunk {
+                op: DiffOp::Delete,
+                text: a[i].to_string(),
+            },
+            Edit::Insert(j) => DiffHunk {
+                op: DiffOp::Insert,
+                text: b[j].to_string(),
+            },
+        })
+        .collect()
+}
 
-            while x < n as i32 && y < m as i32 && a[x as usize] == b[y as usize] {
-                x += 1;
-                y += 1;
-            }
+/// Apply hunks from `compute_text_diff` to the original text (word-level)
+///
+/// Lets the UI preview an edited diff (hunks dropped or changed) without
+/// diffing again. `Equal` and `Delete` hunks must match the original's
+/// words in order; words come out joined by single spaces, as the diff
+/// ignores whitespace.
+///
+/// # Arguments
+/// * `original` - Text the diff was computed from
+/// * `hunks_json` - JSON array of DiffHunk
+///
+/// # Returns
+/// The patched text
+#[wasm_bindgen]
+pub fn apply_diff(original: &str, hunks_json: &str) -> Result<String, JsValue> {
+    let hunks: Vec<DiffHunk> = serde_json::from_str(hunks_json)
+        .map_err(|e| JsValue::from_str(&format!("JSON parse error: {}", e)))?;
 
-            v.insert(k, x);
+    patch(original, &hunks).map_err(|e| JsValue::from_str(&e))
+}
 
-            if x >= n as i32 && y >= m as i32 {
-                break 'outer;
-            }
+fn patch(original: &str, hunks: &[DiffHunk]) -> Result<String, String> {
+    let mut words = original.split_whitespace().enumerate();
+    let mut patched: Vec<&str> = Vec::with_capacity(hunks.len());
+
+    for hunk in hunks {
+        match hunk.op {
+            DiffOp::Insert => patched.push(&hunk.text),
+            DiffOp::Equal | DiffOp::Delete => match words.next() {
+                Some((_, word)) if word == hunk.text => {
+                    if hunk.op == DiffOp::Equal {
+                        patched.push(word);
+                    }
+                }
+                Some((i, word)) => return Err(format!(
+                    "Diff does not match the original at word {}: expected \"{}\", found \"{}\"",
+                    i + 1,
+                    hunk.text,
+                    word
+                )),
+                None => {
+                    return Err(format!(
+                        "Diff runs past the end of the original at \"{}\"",
+                        hunk.text
+                    ))
+                }
+            },
         }
     }
 
-    // Backtrack to build diff hunks
-    let mut hunks = Vec::new();
-    let mut x = n as i32;
-    let mut y = m as i32;
-
-    for (d, v) in trace.iter().enumerate().rev() {
-        let d = d as i32;
-        let k = x - y;
-
-        let prev_k = if k == -d
-            || (k != d && v.get(&(k - 1)).unwrap_or(&-1) < v.get(&(k + 1)).unwrap_or(&-1))
-        {
-            k + 1
-        } else {
-            k - 1
-        };
-
-        let prev_x = *v.get(&prev_k).unwrap_or(&0);
-        let prev_y = prev_x - prev_k;
-
-        while x > prev_x && y > prev_y {
-            hunks.push(DiffHunk {
-                op: DiffOp::Equal,
-                text: format!("{}", a[(x - 1) as usize]),
-            });
-            x -= 1;
-            y -= 1;
-        }
-
-        if d > 0 {
-            if x == prev_x {
-                // Insert
-                hunks.push(DiffHunk {
-                    op: DiffOp::Insert,
-                    text: format!("{}", b[(y - 1) as usize]),
-                });
-                y -= 1;
-            } else {
-                // Delete
-                hunks.push(DiffHunk {
-                    op: DiffOp::Delete,
-                    text: format!("{}", a[(x - 1) as usize]),
-                });
-                x -= 1;
-            }
-        }
+    if let Some((i, _)) = words.next() {
+        return Err(format!("Diff stops at word {} of the original", i + 1));
     }
-
-    hunks.reverse();
-    hunks
+    Ok(patched.join(" "))
 }
 
 // ============================================================================
@@ -302,174 +292,613 @@ pub struct CorrectionPattern {
 }
 
 /// Cluster of similar patterns
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct PatternCluster {
     pub cluster_id: usize,
     pub centroid_original: String,
     pub centroid_corrected: String,
     pub members: Vec<i64>, // Pattern IDs
     pub size: usize,
+    /// Mean silhouette of the members (-1 to 1, higher = tighter cluster)
+    pub silhouette: f64,
+}
+
+/// Clusters plus how well they separate the patterns
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PatternClustering {
+    pub clusters: Vec<PatternCluster>,
+    /// Mean silhouette over all patterns (0 for a single cluster)
+    pub silhouette: f64,
+    pub iterations: usize,
+    /// False if the iteration limit stopped it first
+    pub converged: bool,
+}
+
+/// Iteration limit for pattern clustering (it usually settles in a few)
+const MAX_CLUSTER_ITERATIONS: usize = 100;
+
+/// Small deterministic PRNG (SplitMix64) for seeding clusters
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
 }
 
-/// Simple k-means clustering for correction patterns (Levenshtein distance)
+/// k-means (k-medoids) clustering of correction patterns by Levenshtein
+/// distance, seeded with k-means++
+///
+/// Patterns are ordered by id first, so the same patterns and `seed` give
+/// the same clusters however they were fetched.
 ///
 /// # Arguments
 /// * `patterns_json` - JSON array of CorrectionPattern
-/// * `k` - Number of clusters (default: sqrt(n))
+/// * `k` - Number of clusters (0 = sqrt(n))
+/// * `seed` - Seed for choosing the initial centroids
 ///
 /// # Returns
-/// JSON array of PatternCluster
+/// JSON PatternClustering
 #[wasm_bindgen]
-pub fn cluster_correction_patterns(patterns_json: &str, k: usize) -> Result<String, JsValue> {
-    let patterns: Vec<CorrectionPattern> = serde_json::from_str(patterns_json)
+pub fn cluster_correction_patterns(
+    patterns_json: &str,
+    k: usize,
+    seed: u32,
+) -> Result<String, JsValue> {
+    let mut patterns: Vec<CorrectionPattern> = serde_json::from_str(patterns_json)
         .map_err(|e| JsValue::from_str(&format!("JSON parse error: {}", e)))?;
+    patterns.sort_by_key(|p| p.id);
 
-    if patterns.is_empty() {
-        return Ok("[]".to_string());
+    let clustering = cluster_patterns(&patterns, k, seed as u64);
+    serde_json::to_string(&clustering)
+        .map_err(|e| JsValue::from_str(&format!("JSON serialize error: {}", e)))
+}
+
+fn cluster_patterns(patterns: &[CorrectionPattern], k: usize, seed: u64) -> PatternClustering {
+    let n = patterns.len();
+    if n == 0 {
+        return PatternClustering {
+            clusters: Vec::new(),
+            silhouette: 0.0,
+            iterations: 0,
+            converged: true,
+        };
     }
 
     let k = if k == 0 {
         // Auto k = sqrt(n)
-        (patterns.len() as f64).sqrt().ceil() as usize
+        (n as f64).sqrt().ceil() as usize
     } else {
-        k.min(patterns.len())
+        k.min(n)
     };
 
-    // Initialize centroids (pick first k patterns)
-    let mut centroids: Vec<usize> = (0..k).collect();
-    let mut assignments: Vec<usize> = vec![0; patterns.len()];
-
-    // Run k-means for 10 iterations (sufficient for UI clustering)
-    for _ in 0..10 {
-        // Assign each pattern to nearest centroid
-        for (i, pattern) in patterns.iter().enumerate() {
-            let mut min_dist = usize::MAX;
-            let mut best_cluster = 0;
-
-            for (cluster_id, &centroid_idx) in centroids.iter().enumerate() {
-                let dist =
-                    levenshtein_distance(&pattern.original, &patterns[centroid_idx].original);
-                if dist < min_dist {
-                    min_dist = dist;
-                    best_cluster = cluster_id;
-                }
-            }
-
-            assignments[i] = best_cluster;
+    let mut dist = vec![0.0f64; n * n];
+    for i in 0..n {
+        for j in i + 1..n {
+            let d = levenshtein(&patterns[i].original, &patterns[j].original) as f64;
+            dist[i * n + j] = d;
+            dist[j * n + i] = d;
         }
+    }
+    let d = |i: usize, j: usize| dist[i * n + j];
+
+    // k-means++: each further centroid drawn with probability ∝ squared
+    // distance to the nearest one so far
+    let mut rng = SplitMix64(seed);
+    let mut centroids = vec![(rng.next_u64() % n as u64) as usize];
+    let mut nearest: Vec<f64> = (0..n).map(|i| d(i, centroids[0]).powi(2)).collect();
+    while centroids.len() < k {
+        let total: f64 = nearest.iter().sum();
+        let next = if total > 0.0 {
+            let mut target = rng.next_f64() * total;
+            (0..n)
+                .find(|&i| {
+                    target -= nearest[i];
+                    target < 0.0 && nearest[i] > 0.0
+                })
+                .unwrap_or_else(|| (0..n).rfind(|&i| nearest[i] > 0.0).unwrap_or(0))
+        } else {
+            // All remaining patterns coincide with a centroid
+            (0..n).find(|i| !centroids.contains(i)).unwrap_or(0)
+        };
+        centroids.push(next);
+        for (i, near) in nearest.iter_mut().enumerate() {
+            *near = near.min(d(i, next).powi(2));
+        }
+    }
 
-        // Update centroids (most central pattern in each cluster)
-        for (cluster_id, centroid) in centroids.iter_mut().enumerate().take(k) {
-            let cluster_members: Vec<usize> = assignments
-                .iter()
-                .enumerate()
-                .filter(|(_, &c)| c == cluster_id)
-                .map(|(i, _)| i)
-                .collect();
-
-            if cluster_members.is_empty() {
-                continue;
+    let mut assignments = vec![usize::MAX; n];
+    let mut iterations = 0;
+    let mut converged = false;
+    while iterations < MAX_CLUSTER_ITERATIONS {
+        iterations += 1;
+
+        // Assign each pattern to its nearest centroid (lowest id on ties)
+        let mut changed = false;
+        for (i, assigned) in assignments.iter_mut().enumerate() {
+            let best = (0..k)
+                .min_by(|&a, &b| d(i, centroids[a]).total_cmp(&d(i, centroids[b])))
+                .unwrap_or(0);
+            if *assigned != best {
+                *assigned = best;
+                changed = true;
             }
+        }
 
-            // Find most central pattern (min total distance to others)
-            let mut best_centroid = cluster_members[0];
-            let mut min_total_dist = usize::MAX;
-
-            for &candidate in &cluster_members {
-                let total_dist: usize = cluster_members
-                    .iter()
-                    .map(|&other| {
-                        levenshtein_distance(
-                            &patterns[candidate].original,
-                            &patterns[other].original,
-                        )
-                    })
-                    .sum();
-
-                if total_dist < min_total_dist {
-                    min_total_dist = total_dist;
-                    best_centroid = candidate;
+        // Move each centroid to its most central member
+        for (cluster_id, centroid) in centroids.iter_mut().enumerate() {
+            let members: Vec<usize> = (0..n).filter(|&i| assignments[i] == cluster_id).collect();
+            let total = |c: usize| members.iter().map(|&m| d(c, m)).sum::<f64>();
+            if let Some(best) = members
+                .iter()
+                .copied()
+                .min_by(|&a, &b| total(a).total_cmp(&total(b)))
+            {
+                if best != *centroid {
+                    *centroid = best;
+                    changed = true;
                 }
             }
+        }
 
-            *centroid = best_centroid;
+        if !changed {
+            converged = true;
+            break;
         }
     }
 
-    // Build cluster objects
+    // Silhouette per pattern; a pattern alone in its cluster scores 0
+    let silhouettes: Vec<f64> = (0..n)
+        .map(|i| {
+            let mean_to = |cluster_id: usize| {
+                let others: Vec<f64> = (0..n)
+                    .filter(|&j| j != i && assignments[j] == cluster_id)
+                    .map(|j| d(i, j))
+                    .collect();
+                (!others.is_empty()).then(|| others.iter().sum::<f64>() / others.len() as f64)
+            };
+            let Some(cohesion) = mean_to(assignments[i]) else {
+                return 0.0;
+            };
+            let separation = (0..k)
+                .filter(|&c| c != assignments[i])
+                .filter_map(mean_to)
+                .fold(f64::INFINITY, f64::min);
+            if separation.is_finite() && cohesion.max(separation) > 0.0 {
+                (separation - cohesion) / cohesion.max(separation)
+            } else {
+                0.0
+            }
+        })
+        .collect();
+
     let clusters: Vec<PatternCluster> = (0..k)
         .map(|cluster_id| {
-            let members: Vec<i64> = assignments
-                .iter()
-                .enumerate()
-                .filter(|(_, &c)| c == cluster_id)
-                .map(|(i, _)| patterns[i].id)
-                .collect();
-
+            let indices: Vec<usize> = (0..n).filter(|&i| assignments[i] == cluster_id).collect();
             let centroid_idx = centroids[cluster_id];
             PatternCluster {
                 cluster_id,
                 centroid_original: patterns[centroid_idx].original.clone(),
                 centroid_corrected: patterns[centroid_idx].corrected.clone(),
-                members: members.clone(),
-                size: members.len(),
+                members: indices.iter().map(|&i| patterns[i].id).collect(),
+                size: indices.len(),
+                silhouette: if indices.is_empty() {
+                    0.0
+                } else {
+                    indices.iter().map(|&i| silhouettes[i]).sum::<f64>() / indices.len() as f64
+                },
             }
         })
         .filter(|c| c.size > 0) // Remove empty clusters
         .collect();
 
-    serde_json::to_string(&clusters)
-        .map_err(|e| JsValue::from_str(&format!("JSON serialize error: {}", e)))
+    PatternClustering {
+        clusters,
+        silhouette: silhouettes.iter().sum::<f64>() / n as f64,
+        iterations,
+        converged,
+    }
 }
 
-/// Levenshtein distance (edit distance) between two strings
-fn levenshtein_distance(a: &str, b: &str) -> usize {
-    let a_chars: Vec<char> = a.chars().collect();
-    let b_chars: Vec<char> = b.chars().collect();
-    let n = a_chars.len();
-    let m = b_chars.len();
+// ============================================================================
+// SECTION 4: Locale-Aware Display Formatting
+// ============================================================================
 
-    if n == 0 {
-        return m;
+/// Separators a locale writes numbers with
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct NumberFormat {
+    group: &'static str,
+    decimal: &'static str,
+    /// Lakh/crore grouping (12,34,567) instead of groups of three
+    indian: bool,
+    /// Integer digits below which no group separator is written
+    /// (Spanish writes 1234 but 12 345)
+    min_grouping: usize,
+}
+
+impl NumberFormat {
+    const fn new(group: &'static str, decimal: &'static str) -> Self {
+        Self {
+            group,
+            decimal,
+            indian: false,
+            min_grouping: 4,
+        }
     }
-    if m == 0 {
-        return n;
+}
+
+const NO_BREAK_SPACE: &str = "\u{00A0}";
+const NARROW_NO_BREAK_SPACE: &str = "\u{202F}";
+
+/// Separators for a BCP 47 tag such as `de-DE` or `fr_CA`, from the CLDR
+/// defaults; unknown locales get the en-US style
+fn number_format(locale: &str) -> NumberFormat {
+    let tag = locale.trim().to_ascii_lowercase().replace('_', "-");
+    let language = tag.split('-').next().unwrap_or("");
+
+    match tag.as_str() {
+        "en-in" => {
+            return NumberFormat {
+                indian: true,
+                ..NumberFormat::new(",", ".")
+            }
+        }
+        "de-ch" | "it-ch" => return NumberFormat::new("\u{2019}", "."),
+        "de-at" => return NumberFormat::new(NO_BREAK_SPACE, ","),
+        "pt-pt" => {
+            return NumberFormat {
+                min_grouping: 5,
+                ..NumberFormat::new(NO_BREAK_SPACE, ",")
+            }
+        }
+        _ => {}
     }
 
-    let mut dp = vec![vec![0; m + 1]; n + 1];
+    match language {
+        "hi" => NumberFormat {
+            indian: true,
+            ..NumberFormat::new(",", ".")
+        },
+        "de" | "nl" | "it" | "pt" | "id" | "tr" | "da" | "el" | "ro" => NumberFormat::new(".", ","),
+        "es" => NumberFormat {
+            min_grouping: 5,
+            ..NumberFormat::new(".", ",")
+        },
+        "pl" => NumberFormat {
+            min_grouping: 5,
+            ..NumberFormat::new(NO_BREAK_SPACE, ",")
+        },
+        "fr" => NumberFormat::new(NARROW_NO_BREAK_SPACE, ","),
+        "ru" | "uk" | "cs" | "sk" | "sv" | "nb" | "no" | "fi" | "hu" | "bg" => {
+            NumberFormat::new(NO_BREAK_SPACE, ",")
+        }
+        _ => NumberFormat::new(",", "."),
+    }
+}
 
-    for (i, row) in dp.iter_mut().enumerate().take(n + 1) {
-        row[0] = i;
+/// Write `value` rounded to `decimals` places in the locale's style
+fn format_with(value: f64, decimals: usize, format: NumberFormat) -> String {
+    if !value.is_finite() {
+        return value.to_string();
     }
-    for (j, cell) in dp[0].iter_mut().enumerate().take(m + 1) {
-        *cell = j;
+
+    let rounded = format!("{:.*}", decimals, value.abs());
+    let (integer, fraction) = rounded.split_once('.').unwrap_or((&rounded, ""));
+
+    // Group from the right: three digits, then twos for Indian grouping
+    let mut groups: Vec<&str> = Vec::new();
+    let mut end = integer.len();
+    if integer.len() >= format.min_grouping {
+        let mut size = 3;
+        while end > size {
+            groups.push(&integer[end - size..end]);
+            end -= size;
+            if format.indian {
+                size = 2;
+            }
+        }
     }
+    groups.push(&integer[..end]);
+    groups.reverse();
 
-    for i in 1..=n {
-        for j in 1..=m {
-            let cost = if a_chars[i - 1] == b_chars[j - 1] {
-                0
-            } else {
-                1
-            };
-            dp[i][j] = (dp[i - 1][j] + 1) // Deletion
-                .min(dp[i][j - 1] + 1) // Insertion
-                .min(dp[i - 1][j - 1] + cost); // Substitution
+    let mut out = String::new();
+    // "-0" after rounding is just 0
+    if value < 0.0 && rounded.bytes().any(|b| b.is_ascii_digit() && b != b'0') {
+        out.push('-');
+    }
+    out.push_str(&groups.join(format.group));
+    if !fraction.is_empty() {
+        out.push_str(format.decimal);
+        out.push_str(fraction);
+    }
+    out
+}
+
+/// Format a number with the locale's thousands and decimal separators
+///
+/// # Arguments
+/// * `value` - Number to format
+/// * `decimals` - Digits after the decimal separator
+/// * `locale` - BCP 47 tag, e.g. `navigator.language`
+///
+/// # Returns
+/// e.g. `12,345.6` (en-US), `12.345,6` (de-DE), `12 345,6` (fr-FR)
+#[wasm_bindgen]
+pub fn format_number(value: f64, decimals: usize, locale: &str) -> String {
+    format_with(value, decimals, number_format(locale))
+}
+
+/// Humanize a duration with its two largest units
+///
+/// Units are the international symbols (d, h, min, s, ms), which read the
+/// same in every locale; only the numbers follow `locale`.
+///
+/// # Arguments
+/// * `seconds` - Duration in seconds
+/// * `locale` - BCP 47 tag, e.g. `navigator.language`
+///
+/// # Returns
+/// e.g. `850 ms`, `4.5 s`, `12 min 5 s`, `1 h 30 min`, `3 d 4 h`
+#[wasm_bindgen]
+pub fn format_duration(seconds: f64, locale: &str) -> String {
+    let format = number_format(locale);
+    if !seconds.is_finite() {
+        return seconds.to_string();
+    }
+    let sign = if seconds < 0.0 { "-" } else { "" };
+    let seconds = seconds.abs();
+
+    if seconds < 1.0 {
+        let ms = (seconds * 1000.0).round();
+        if ms < 1000.0 {
+            return format!("{}{} ms", sign, format_with(ms, 0, format));
+        }
+    }
+    if seconds < 10.0 && (seconds * 10.0).round() < 100.0 {
+        return format!("{}{} s", sign, format_with(seconds, 1, format));
+    }
+
+    const UNITS: [(&str, u64); 4] = [("d", 86_400), ("h", 3_600), ("min", 60), ("s", 1)];
+    let mut rest = seconds.round() as u64;
+    let mut parts = Vec::new();
+    for (symbol, size) in UNITS {
+        let count = rest / size;
+        rest %= size;
+        if count > 0 || !parts.is_empty() {
+            parts.push((count, symbol));
+        }
+        if parts.len() == 2 {
+            break;
+        }
+    }
+
+    let text = parts
+        .iter()
+        // A zero second unit ("2 h 0 min") says nothing
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, symbol)| format!("{} {}", format_with(*count as f64, 0, format), symbol))
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!("{}{}", sign, text)
+}
+
+/// Sessions grouped into a local day or hour
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LocalBucket {
+    /// Local date (`2025-01-31`) or date and hour (`2025-01-31 14:00`)
+    pub label: String,
+    /// Unix timestamp of the bucket's local start
+    pub start_unix: i64,
+    pub session_count: usize,
+    pub total_words: i64,
+    pub total_duration_s: f64,
+    pub average_wpm: f64,
+}
+
+/// Group sessions by the local calendar day or hour they started in
+///
+/// Bucketing by Unix time puts an evening session in Berlin on the next
+/// day's bar; this shifts each start time by the UTC offset first. The
+/// offset is fixed, so across a DST change sessions within an hour of
+/// midnight may land on the neighbouring day.
+///
+/// # Arguments
+/// * `sessions_json` - JSON array of SessionMetrics
+/// * `utc_offset_minutes` - Minutes east of UTC, i.e.
+///   `-new Date().getTimezoneOffset()` in JS
+/// * `period` - `"day"` or `"hour"`
+///
+/// # Returns
+/// JSON array of LocalBucket, oldest first
+#[wasm_bindgen]
+pub fn bucket_sessions_local(
+    sessions_json: &str,
+    utc_offset_minutes: i32,
+    period: &str,
+) -> Result<String, JsValue> {
+    let sessions: Vec<SessionMetrics> = serde_json::from_str(sessions_json)
+        .map_err(|e| JsValue::from_str(&format!("JSON parse error: {}", e)))?;
+
+    if utc_offset_minutes.abs() > 18 * 60 {
+        return Err(JsValue::from_str(&format!(
+            "UTC offset must be within ±18 hours, got {} minutes",
+            utc_offset_minutes
+        )));
+    }
+    let bucket_seconds: i64 = match period {
+        "day" => 86_400,
+        "hour" => 3_600,
+        other => {
+            return Err(JsValue::from_str(&format!(
+                "Unknown period '{}' (expected 'day' or 'hour')",
+                other
+            )))
         }
+    };
+    let offset_seconds = utc_offset_minutes as i64 * 60;
+
+    let mut buckets: BTreeMap<i64, Vec<&SessionMetrics>> = BTreeMap::new();
+    for session in &sessions {
+        let local = session.start_time + offset_seconds;
+        buckets
+            .entry(local.div_euclid(bucket_seconds))
+            .or_default()
+            .push(session);
     }
 
-    dp[n][m]
+    let buckets: Vec<LocalBucket> = buckets
+        .into_iter()
+        .map(|(key, members)| {
+            let local_start = key * bucket_seconds;
+            let label = local_label(local_start, bucket_seconds < 86_400);
+            LocalBucket {
+                label,
+                start_unix: local_start - offset_seconds,
+                session_count: members.len(),
+                total_words: members.iter().map(|s| s.words_dictated as i64).sum(),
+                total_duration_s: members.iter().map(|s| s.duration_s).sum(),
+                average_wpm: members.iter().map(|s| s.wpm).sum::<f64>() / members.len() as f64,
+            }
+        })
+        .collect();
+
+    serde_json::to_string(&buckets)
+        .map_err(|e| JsValue::from_str(&format!("JSON serialize error: {}", e)))
+}
+
+/// ISO date (and hour) of a local time given as seconds since the epoch
+fn local_label(local_seconds: i64, with_hour: bool) -> String {
+    // Formatting by hand: chrono's formatter needs its `alloc` feature
+    let Some(time) = chrono::DateTime::from_timestamp(local_seconds, 0) else {
+        return local_seconds.to_string();
+    };
+    let date = format!("{:04}-{:02}-{:02}", time.year(), time.month(), time.day());
+    if with_hour {
+        format!("{} {:02}:00", date, time.hour())
+    } else {
+        date
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn diff(original: &str, corrected: &str) -> Vec<DiffHunk> {
+        let a: Vec<&str> = original.split_whitespace().collect();
+        let b: Vec<&str> = corrected.split_whitespace().collect();
+        myers_diff(&a, &b)
+    }
+
+    #[test]
+    fn test_apply_diff() {
+        let original = "the quick brown socks jumped";
+        let mut hunks = diff(original, "the quick brown fox jumped over");
+        assert_eq!(
+            patch(original, &hunks).unwrap(),
+            "the quick brown fox jumped over"
+        );
+
+        // Preview with the trailing insertion rejected
+        hunks.pop();
+        assert_eq!(
+            patch(original, &hunks).unwrap(),
+            "the quick brown fox jumped"
+        );
+
+        // Hunks from another text are refused
+        assert!(patch("a different text entirely", &hunks).is_err());
+        assert!(patch(original, &hunks[..2]).is_err());
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn prop_apply_diff_reconstructs_corrected(
+            original in proptest::collection::vec("[a-e]{1,3}", 0..40),
+            corrected in proptest::collection::vec("[a-e]{1,3}", 0..40),
+        ) {
+            let (original, corrected) = (original.join(" "), corrected.join(" "));
+            let hunks = diff(&original, &corrected);
+            proptest::prop_assert_eq!(patch(&original, &hunks).unwrap(), corrected);
+        }
+
+        #[test]
+        fn prop_diff_keeps_every_word_once(
+            original in proptest::collection::vec("[a-c]{1,2}", 0..30),
+            corrected in proptest::collection::vec("[a-c]{1,2}", 0..30),
+        ) {
+            let hunks = diff(&original.join(" "), &corrected.join(" "));
+            let kept = |op| {
+                hunks
+                    .iter()
+                    .filter(|h| h.op == DiffOp::Equal || h.op == op)
+                    .map(|h| h.text.clone())
+                    .collect::<Vec<_>>()
+            };
+            // Equal + Delete spell the original, Equal + Insert the correction
+            proptest::prop_assert_eq!(kept(DiffOp::Delete), original);
+            proptest::prop_assert_eq!(kept(DiffOp::Insert), corrected);
+        }
+    }
+
     #[test]
     fn test_levenshtein() {
-        assert_eq!(levenshtein_distance("kitten", "sitting"), 3);
-        assert_eq!(levenshtein_distance("hello", "hello"), 0);
-        assert_eq!(levenshtein_distance("", "test"), 4);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("hello", "hello"), 0);
+        assert_eq!(levenshtein("", "test"), 4);
+    }
+
+    fn pattern(id: i64, original: &str) -> CorrectionPattern {
+        CorrectionPattern {
+            id,
+            original: original.to_string(),
+            corrected: original.to_uppercase(),
+            usage_count: 1,
+        }
+    }
+
+    #[test]
+    fn test_cluster_patterns_finds_groups() {
+        let patterns = vec![
+            pattern(1, "arkon"),
+            pattern(2, "archen"),
+            pattern(3, "arkhon"),
+            pattern(4, "kubectle"),
+            pattern(5, "kube cuttle"),
+            pattern(6, "kubecuttle"),
+        ];
+        let json = serde_json::to_string(&patterns).unwrap();
+        let result: PatternClustering =
+            serde_json::from_str(&cluster_correction_patterns(&json, 2, 7).unwrap()).unwrap();
+
+        assert!(result.converged);
+        assert!(result.silhouette > 0.5, "silhouette {}", result.silhouette);
+        let mut groups: Vec<Vec<i64>> = result.clusters.into_iter().map(|c| c.members).collect();
+        groups.sort();
+        assert_eq!(groups, vec![vec![1, 2, 3], vec![4, 5, 6]]);
+    }
+
+    #[test]
+    fn test_cluster_patterns_stable_across_reloads() {
+        let mut patterns: Vec<CorrectionPattern> = ["teh", "recieve", "thier", "wierd", "hte"]
+            .iter()
+            .enumerate()
+            .map(|(i, w)| pattern(i as i64, w))
+            .collect();
+        let first = cluster_correction_patterns(&serde_json::to_string(&patterns).unwrap(), 0, 3);
+        // Same patterns fetched in another order
+        patterns.reverse();
+        let again = cluster_correction_patterns(&serde_json::to_string(&patterns).unwrap(), 0, 3);
+        assert_eq!(first.unwrap(), again.unwrap());
     }
 
     #[test]
@@ -491,4 +920,64 @@ mod tests {
         assert_eq!(stats.total_sessions, 1);
         assert_eq!(stats.total_words, 120);
     }
+
+    #[test]
+    fn test_format_number_locales() {
+        assert_eq!(format_number(1234567.891, 2, "en-US"), "1,234,567.89");
+        assert_eq!(format_number(1234567.891, 2, "de-DE"), "1.234.567,89");
+        assert_eq!(
+            format_number(1234567.0, 0, "fr_FR"),
+            "1\u{202F}234\u{202F}567"
+        );
+        assert_eq!(
+            format_number(1234567.0, 0, "de-CH"),
+            "1\u{2019}234\u{2019}567"
+        );
+        assert_eq!(format_number(1234567.0, 0, "en-IN"), "12,34,567");
+        // Spanish leaves four-digit numbers ungrouped
+        assert_eq!(format_number(1234.0, 0, "es-ES"), "1234");
+        assert_eq!(format_number(12345.0, 0, "es-ES"), "12.345");
+        assert_eq!(format_number(-1234.5, 1, "xx"), "-1,234.5");
+        assert_eq!(format_number(-0.001, 1, "en"), "0.0");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(0.85, "en-US"), "850 ms");
+        assert_eq!(format_duration(4.5, "de-DE"), "4,5 s");
+        assert_eq!(format_duration(725.0, "en-US"), "12 min 5 s");
+        assert_eq!(format_duration(7200.0, "en-US"), "2 h");
+        assert_eq!(format_duration(5400.0, "en-US"), "1 h 30 min");
+        assert_eq!(format_duration(1_234.0 * 86_400.0, "de-DE"), "1.234 d");
+    }
+
+    #[test]
+    fn test_bucket_sessions_local() {
+        let session = |id, start_time| SessionMetrics {
+            id,
+            start_time,
+            end_time: None,
+            duration_s: 60.0,
+            words_dictated: 100,
+            wpm: 100.0,
+            avg_latency_ms: 200.0,
+        };
+        // 2025-01-01 23:30 UTC and 2025-01-02 00:30 UTC
+        let sessions = vec![session(1, 1735774200), session(2, 1735777800)];
+        let json = serde_json::to_string(&sessions).unwrap();
+
+        // Both are on January 1st in New York (UTC-5)...
+        let result = bucket_sessions_local(&json, -300, "day").unwrap();
+        let buckets: Vec<LocalBucket> = serde_json::from_str(&result).unwrap();
+        assert_eq!(buckets.len(), 1);
+        assert_eq!(buckets[0].label, "2025-01-01");
+        assert_eq!(buckets[0].start_unix, 1735707600);
+        assert_eq!(buckets[0].total_words, 200);
+
+        // ...and both on January 2nd in Berlin (UTC+1)
+        let result = bucket_sessions_local(&json, 60, "hour").unwrap();
+        let buckets: Vec<LocalBucket> = serde_json::from_str(&result).unwrap();
+        let labels: Vec<&str> = buckets.iter().map(|b| b.label.as_str()).collect();
+        assert_eq!(labels, vec!["2025-01-02 00:00", "2025-01-02 01:00"]);
+    }
 }
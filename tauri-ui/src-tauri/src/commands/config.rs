@@ -1,6 +1,7 @@
 //! Configuration management commands
 
 use std::sync::Mutex;
+use swictation_paths::config_file::{self, DocumentMut};
 use tauri::State;
 
 /// State for daemon config path
@@ -39,12 +40,9 @@ pub async fn get_daemon_config(
 ) -> Result<DaemonConfig, String> {
     let config_path = state.config_path.lock().unwrap();
 
-    if !config_path.exists() {
-        return Err("Config file not found".to_string());
-    }
-
-    let contents = std::fs::read_to_string(config_path.as_path())
-        .map_err(|e| format!("Failed to read config file: {}", e))?;
+    let contents = config_file::read(config_path.as_path())
+        .map_err(|e| format!("Failed to read config file: {:#}", e))?
+        .ok_or_else(|| "Config file not found".to_string())?;
 
     let config: DaemonConfig = toml::from_str(&contents)
         .map_err(|e| format!("Failed to parse config file: {}", e))?;
@@ -53,6 +51,9 @@ pub async fn get_daemon_config(
 }
 
 /// Update daemon configuration
+///
+/// Only the settings this UI knows about are replaced; the rest of the
+/// file, comments included, is left as the daemon and the user wrote it.
 #[tauri::command]
 pub async fn update_daemon_config(
     state: State<'_, ConfigState>,
@@ -60,19 +61,24 @@ pub async fn update_daemon_config(
 ) -> Result<(), String> {
     let config_path = state.config_path.lock().unwrap();
 
-    // Ensure parent directory exists
-    if let Some(parent) = config_path.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
-    }
-
     let contents = toml::to_string_pretty(&config)
         .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let new: DocumentMut = contents
+        .parse()
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
 
-    std::fs::write(config_path.as_path(), contents)
-        .map_err(|e| format!("Failed to write config file: {}", e))?;
-
-    Ok(())
+    config_file::update(config_path.as_path(), |doc| {
+        config_file::merge(doc, &new, false);
+        // Unset options are missing from `new` rather than removed
+        if config.num_threads.is_none() {
+            doc.remove("num_threads");
+        }
+        if config.audio_device_index.is_none() {
+            doc.remove("audio_device_index");
+        }
+        Ok(())
+    })
+    .map_err(|e| format!("Failed to write config file: {:#}", e))
 }
 
 /// Update only phonetic threshold (convenience method)
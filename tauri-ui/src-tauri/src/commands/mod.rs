@@ -2,9 +2,15 @@ pub mod corrections;
 pub mod config;
 
 use crate::database::Database;
-use crate::models::{ConnectionStatus, LifetimeStats, SessionSummary, TranscriptionRecord};
+use crate::models::{
+    ConnectionStatus, ErrorEvent, HistoryEntry, LifetimeStats, PracticeRecord, ProsodyPoint,
+    SessionComparison, SessionSummary, TranscriptionRecord, TrashedSession,
+};
 use std::sync::Mutex;
-use tauri::State;
+use tauri::{AppHandle, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
 
 // Re-export corrections types
 pub use corrections::CorrectionsState;
@@ -57,6 +63,34 @@ pub async fn get_session_details(
         .map_err(|e| format!("Failed to get session transcriptions: {}", e))
 }
 
+/// Get prosody analytics (pitch spread, loudness, speaking rate) per segment
+#[tauri::command]
+pub async fn get_session_prosody(
+    state: State<'_, AppState>,
+    session_id: i64,
+) -> Result<Vec<ProsodyPoint>, String> {
+    state
+        .db
+        .lock()
+        .unwrap()
+        .get_session_prosody(session_id)
+        .map_err(|e| format!("Failed to get session prosody: {}", e))
+}
+
+/// Get pronunciation practice history (scored readalong runs), newest first
+#[tauri::command]
+pub async fn get_practice_history(
+    state: State<'_, AppState>,
+    limit: usize,
+) -> Result<Vec<PracticeRecord>, String> {
+    state
+        .db
+        .lock()
+        .unwrap()
+        .get_practice_history(limit)
+        .map_err(|e| format!("Failed to get practice history: {}", e))
+}
+
 /// Search transcriptions by text
 #[tauri::command]
 pub async fn search_transcriptions(
@@ -85,6 +119,21 @@ pub async fn get_lifetime_stats(
         .map_err(|e| format!("Failed to get lifetime stats: {}", e))
 }
 
+/// Compare two sessions (WPM/latency deltas, vocabulary overlap, corrections)
+#[tauri::command]
+pub async fn compare_sessions(
+    state: State<'_, AppState>,
+    session_a: i64,
+    session_b: i64,
+) -> Result<SessionComparison, String> {
+    state
+        .db
+        .lock()
+        .unwrap()
+        .compare_sessions(session_a, session_b)
+        .map_err(|e| format!("Failed to compare sessions: {}", e))
+}
+
 /// Toggle recording (triggers via hotkey or tray menu)
 /// This command is deprecated - use the tray menu or hotkey instead.
 /// The daemon handles toggle recording internally via global hotkey.
@@ -95,6 +144,192 @@ pub async fn toggle_recording() -> Result<String, String> {
     Ok("Toggle recording via hotkey (Ctrl+Shift+D) or tray menu".to_string())
 }
 
+/// Replace a recently dictated segment with one of its n-best alternatives
+///
+/// The daemon undoes the typed text and retypes the chosen alternative.
+/// Returns the text that was typed.
+#[tauri::command]
+pub async fn use_alternative(segment_id: i64, index: usize) -> Result<String, String> {
+    let response = daemon_request(serde_json::json!({
+        "action": "use_alternative",
+        "segment_id": segment_id,
+        "index": index,
+    }))
+    .await?;
+    Ok(response["text"].as_str().unwrap_or_default().to_string())
+}
+
+/// Have the daemon speak the last dictated segment aloud
+///
+/// Requires `tts.enabled` in the daemon config. Returns the text read back.
+#[tauri::command]
+pub async fn read_back() -> Result<String, String> {
+    let response = daemon_request(serde_json::json!({ "action": "read_back" })).await?;
+    Ok(response["text"].as_str().unwrap_or_default().to_string())
+}
+
+/// Recent transcriptions kept in daemon memory (available even when text
+/// storage is disabled), most recent first
+#[tauri::command]
+pub async fn get_transcription_history() -> Result<Vec<HistoryEntry>, String> {
+    let response = daemon_request(serde_json::json!({ "action": "history_list" })).await?;
+    serde_json::from_value(response["history"].clone())
+        .map_err(|e| format!("Invalid history response: {}", e))
+}
+
+/// Recent warnings and errors logged by the daemon, most recent first
+#[tauri::command]
+pub async fn get_recent_errors(limit: Option<usize>) -> Result<Vec<ErrorEvent>, String> {
+    let mut request = serde_json::json!({ "action": "get_recent_errors" });
+    if let Some(limit) = limit {
+        request["limit"] = limit.into();
+    }
+    let response = daemon_request(request).await?;
+    serde_json::from_value(response["errors"].clone())
+        .map_err(|e| format!("Invalid errors response: {}", e))
+}
+
+/// Merge sessions fragmented by accidental toggles into the earliest of
+/// them, returning its ID (words, latency and WPM are re-aggregated)
+#[tauri::command]
+pub async fn merge_sessions(session_ids: Vec<i64>) -> Result<i64, String> {
+    let response = daemon_request(serde_json::json!({
+        "action": "merge_sessions",
+        "session_ids": session_ids,
+    }))
+    .await?;
+    response["session_id"]
+        .as_i64()
+        .ok_or_else(|| "Invalid merge response".to_string())
+}
+
+/// Split a session at `at` (Unix seconds), returning the new session's ID
+#[tauri::command]
+pub async fn split_session(session_id: i64, at: f64) -> Result<i64, String> {
+    let response = daemon_request(serde_json::json!({
+        "action": "split_session",
+        "session_id": session_id,
+        "at": at,
+    }))
+    .await?;
+    response["session_id"]
+        .as_i64()
+        .ok_or_else(|| "Invalid split response".to_string())
+}
+
+/// Get archived sessions (hidden from stats and the session list)
+#[tauri::command]
+pub async fn get_archived_sessions(
+    state: State<'_, AppState>,
+    limit: usize,
+    offset: Option<usize>,
+) -> Result<Vec<SessionSummary>, String> {
+    state
+        .db
+        .lock()
+        .unwrap()
+        .get_archived_sessions(limit, offset.unwrap_or(0))
+        .map_err(|e| format!("Failed to get archived sessions: {}", e))
+}
+
+/// Get the sessions in the trash and when each will be purged
+#[tauri::command]
+pub async fn get_trashed_sessions(
+    state: State<'_, AppState>,
+) -> Result<Vec<TrashedSession>, String> {
+    state
+        .db
+        .lock()
+        .unwrap()
+        .get_trashed_sessions()
+        .map_err(|e| format!("Failed to get trash: {}", e))
+}
+
+/// Archive or unarchive a session (archived sessions are left out of stats)
+#[tauri::command]
+pub async fn archive_session(session_id: i64, archived: bool) -> Result<(), String> {
+    daemon_request(serde_json::json!({
+        "action": if archived { "archive_session" } else { "unarchive_session" },
+        "session_id": session_id,
+    }))
+    .await
+    .map(drop)
+}
+
+/// Move a single session to the trash (purged after 30 days)
+#[tauri::command]
+pub async fn delete_session(session_id: i64) -> Result<(), String> {
+    daemon_request(serde_json::json!({
+        "action": "delete_session",
+        "session_id": session_id,
+    }))
+    .await
+    .map(drop)
+}
+
+/// Take a session back out of the trash
+#[tauri::command]
+pub async fn restore_session(session_id: i64) -> Result<(), String> {
+    daemon_request(serde_json::json!({
+        "action": "restore_session",
+        "session_id": session_id,
+    }))
+    .await
+    .map(drop)
+}
+
+/// Permanently delete every session in the trash, returning how many
+#[tauri::command]
+pub async fn empty_trash() -> Result<u64, String> {
+    let response = daemon_request(serde_json::json!({ "action": "empty_trash" })).await?;
+    response["purged"]
+        .as_u64()
+        .ok_or_else(|| "Invalid empty_trash response".to_string())
+}
+
+/// Copy history entry `n` (1 = most recent) to the clipboard
+#[tauri::command]
+pub async fn copy_history_entry(app: AppHandle, n: usize) -> Result<String, String> {
+    let response = daemon_request(serde_json::json!({
+        "action": "history_copy",
+        "index": n,
+    }))
+    .await?;
+    let text = response["text"].as_str().unwrap_or_default().to_string();
+    app.clipboard()
+        .write_text(text.clone())
+        .map_err(|e| format!("Failed to copy to clipboard: {}", e))?;
+    Ok(text)
+}
+
+/// Send one JSON request to the daemon's IPC socket and return its
+/// successful response
+async fn daemon_request(request: serde_json::Value) -> Result<serde_json::Value, String> {
+    let mut stream = UnixStream::connect(swictation_paths::ipc_socket_path())
+        .await
+        .map_err(|e| format!("Failed to connect to daemon: {}", e))?;
+    stream
+        .write_all(request.to_string().as_bytes())
+        .await
+        .map_err(|e| format!("Failed to send request: {}", e))?;
+
+    let mut response = String::new();
+    stream
+        .read_to_string(&mut response)
+        .await
+        .map_err(|e| format!("Failed to read daemon response: {}", e))?;
+    let response: serde_json::Value = serde_json::from_str(&response)
+        .map_err(|e| format!("Invalid daemon response: {}", e))?;
+
+    match response["status"].as_str() {
+        Some("success") => Ok(response),
+        _ => Err(response["error"]
+            .as_str()
+            .unwrap_or("Unknown daemon error")
+            .to_string()),
+    }
+}
+
 /// Get socket connection status
 /// This command is deprecated - connection status is sent via "metrics-connected" events.
 #[tauri::command]
@@ -1,9 +1,27 @@
 use anyhow::{Context, Result};
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
-use crate::models::{LifetimeStats, SessionSummary, TranscriptionRecord};
+use crate::models::{
+    LifetimeStats, PracticeRecord, ProsodyPoint, SessionComparison, SessionSummary,
+    TranscriptionRecord, TrashedSession,
+};
+
+/// Days the daemon keeps deleted sessions before purging them
+/// (`swictation_metrics::TRASH_RETENTION_DAYS`)
+const TRASH_RETENTION_DAYS: i64 = 30;
+
+/// Per-session fields needed for comparison
+struct SessionStats {
+    wpm: f64,
+    avg_latency_ms: f64,
+    median_latency_ms: f64,
+    p95_latency_ms: f64,
+    words_dictated: i32,
+    transformations_count: i32,
+}
 
 /// Thread-safe database wrapper for UI queries
 pub struct Database {
@@ -46,12 +64,64 @@ impl Database {
         Ok(expanded)
     }
 
-    /// Get recent sessions with pagination support (only completed sessions)
+    /// Get recent sessions with pagination support (only completed sessions,
+    /// excluding archived and trashed ones)
     pub fn get_recent_sessions(&self, limit: usize, offset: usize) -> Result<Vec<SessionSummary>> {
         log::info!("🔍 get_recent_sessions called: limit={}, offset={}", limit, offset);
+        let sessions = self.query_sessions(
+            "s.archived_at IS NULL AND s.deleted_at IS NULL",
+            "s.start_time DESC",
+            Some(limit),
+            offset,
+        )?;
+        log::info!("✓ Returning {} sessions", sessions.len());
+        Ok(sessions.into_iter().map(|(session, _)| session).collect())
+    }
+
+    /// Get archived sessions (left out of stats) with pagination support
+    pub fn get_archived_sessions(&self, limit: usize, offset: usize) -> Result<Vec<SessionSummary>> {
+        let sessions = self.query_sessions(
+            "s.archived_at IS NOT NULL AND s.deleted_at IS NULL",
+            "s.start_time DESC",
+            Some(limit),
+            offset,
+        )?;
+        Ok(sessions.into_iter().map(|(session, _)| session).collect())
+    }
+
+    /// Get sessions in the trash, most recently deleted first
+    pub fn get_trashed_sessions(&self) -> Result<Vec<TrashedSession>> {
+        let sessions = self.query_sessions(
+            "s.deleted_at IS NOT NULL",
+            "s.deleted_at DESC",
+            None,
+            0,
+        )?;
+        Ok(sessions
+            .into_iter()
+            .map(|(session, deleted_at)| {
+                let deleted_at = deleted_at.unwrap_or_default() as i64;
+                TrashedSession {
+                    session,
+                    deleted_at,
+                    purge_at: deleted_at + TRASH_RETENTION_DAYS * 24 * 60 * 60,
+                }
+            })
+            .collect())
+    }
+
+    /// Completed sessions matching `filter`, with their deletion time
+    /// (`limit` of `None` returns them all)
+    fn query_sessions(
+        &self,
+        filter: &str,
+        order: &str,
+        limit: Option<usize>,
+        offset: usize,
+    ) -> Result<Vec<(SessionSummary, Option<f64>)>> {
         let conn = self.conn.lock().unwrap();
 
-        let mut stmt = conn.prepare(
+        let mut stmt = conn.prepare(&format!(
             "SELECT
                 s.id,
                 s.start_time,
@@ -59,17 +129,19 @@ impl Database {
                 s.duration_s,
                 s.words_dictated,
                 s.wpm,
-                s.avg_latency_ms
+                s.avg_latency_ms,
+                s.deleted_at
              FROM sessions s
-             WHERE s.duration_s IS NOT NULL
-             ORDER BY s.start_time DESC
-             LIMIT ?1 OFFSET ?2"
-        ).map_err(|e| {
+             WHERE s.duration_s IS NOT NULL AND {}
+             ORDER BY {}
+             LIMIT ?1 OFFSET ?2",
+            filter, order
+        )).map_err(|e| {
             log::error!("❌ SQL prepare error: {}", e);
             e
         })?;
 
-        let sessions = stmt.query_map(params![limit, offset], |row| {
+        let sessions = stmt.query_map(params![limit.map_or(-1, |l| l as i64), offset as i64], |row| {
             let start_time: f64 = row.get(1)?;
             let end_time: Option<f64> = row.get(2)?;
             let duration_s: f64 = row.get(3)?;
@@ -77,15 +149,18 @@ impl Database {
             let wpm: f64 = row.get(5)?;
             let avg_latency_ms: f64 = row.get(6)?;
 
-            Ok(SessionSummary {
-                id: row.get(0)?,
-                start_time: start_time as i64,
-                end_time: end_time.map(|t| t as i64),
-                duration_s,
-                words_dictated,
-                wpm,
-                avg_latency_ms,
-            })
+            Ok((
+                SessionSummary {
+                    id: row.get(0)?,
+                    start_time: start_time as i64,
+                    end_time: end_time.map(|t| t as i64),
+                    duration_s,
+                    words_dictated,
+                    wpm,
+                    avg_latency_ms,
+                },
+                row.get(7)?,
+            ))
         })?
         .collect::<std::result::Result<Vec<_>, _>>()
         .map_err(|e| {
@@ -93,7 +168,6 @@ impl Database {
             e
         })?;
 
-        log::info!("✓ Returning {} sessions", sessions.len());
         Ok(sessions)
     }
 
@@ -102,7 +176,8 @@ impl Database {
         log::info!("🔍 get_session_count called");
         let conn = self.conn.lock().unwrap();
         let count: i64 = conn.query_row(
-            "SELECT COUNT(*) FROM sessions WHERE duration_s IS NOT NULL",
+            "SELECT COUNT(*) FROM sessions
+             WHERE duration_s IS NOT NULL AND archived_at IS NULL AND deleted_at IS NULL",
             [],
             |row| row.get(0)
         ).map_err(|e| {
@@ -124,7 +199,8 @@ impl Database {
                 text,
                 timestamp,
                 total_latency_ms,
-                words
+                words,
+                speaker
              FROM segments
              WHERE session_id = ?1 AND text IS NOT NULL
              ORDER BY timestamp ASC"
@@ -140,6 +216,7 @@ impl Database {
                 timestamp: timestamp as i64,
                 latency_ms: row.get(4)?,
                 words: row.get(5)?,
+                speaker: row.get(6)?,
             })
         })?
         .collect::<std::result::Result<Vec<_>, _>>()?;
@@ -147,6 +224,78 @@ impl Database {
         Ok(transcriptions)
     }
 
+    /// Get per-segment prosody analytics for a session (segments recorded
+    /// with `analytics_prosody` enabled)
+    pub fn get_session_prosody(&self, session_id: i64) -> Result<Vec<ProsodyPoint>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT
+                id,
+                timestamp,
+                prosody_pitch_std,
+                prosody_energy_dbfs,
+                COALESCE(prosody_rate_wpm, 0)
+             FROM segments
+             WHERE session_id = ?1 AND prosody_energy_dbfs IS NOT NULL
+             ORDER BY timestamp ASC"
+        )?;
+
+        let points = stmt.query_map([session_id], |row| {
+            let timestamp: f64 = row.get(1)?;
+
+            Ok(ProsodyPoint {
+                segment_id: row.get(0)?,
+                timestamp: timestamp as i64,
+                pitch_std_semitones: row.get(2)?,
+                energy_dbfs: row.get(3)?,
+                speaking_rate_wpm: row.get(4)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(points)
+    }
+
+    /// Get scored practice runs, most recent first
+    pub fn get_practice_history(&self, limit: usize) -> Result<Vec<PracticeRecord>> {
+        let conn = self.conn.lock().unwrap();
+
+        let mut stmt = conn.prepare(
+            "SELECT
+                id,
+                started_at,
+                ended_at,
+                script_words,
+                words_read,
+                words_matched,
+                accuracy,
+                pronunciation_score
+             FROM practice_sessions
+             ORDER BY started_at DESC
+             LIMIT ?1"
+        )?;
+
+        let practices = stmt.query_map([limit as i64], |row| {
+            let started_at: f64 = row.get(1)?;
+            let ended_at: f64 = row.get(2)?;
+
+            Ok(PracticeRecord {
+                id: row.get(0)?,
+                started_at: started_at as i64,
+                ended_at: ended_at as i64,
+                script_words: row.get(3)?,
+                words_read: row.get(4)?,
+                words_matched: row.get(5)?,
+                accuracy: row.get(6)?,
+                pronunciation_score: row.get(7)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        Ok(practices)
+    }
+
     /// Search transcriptions by text content
     pub fn search_transcriptions(&self, query: &str, limit: usize) -> Result<Vec<TranscriptionRecord>> {
         let conn = self.conn.lock().unwrap();
@@ -160,9 +309,11 @@ impl Database {
                 text,
                 timestamp,
                 total_latency_ms,
-                words
+                words,
+                speaker
              FROM segments
              WHERE text IS NOT NULL AND text LIKE ?1
+               AND session_id NOT IN (SELECT id FROM sessions WHERE deleted_at IS NOT NULL)
              ORDER BY timestamp DESC
              LIMIT ?2"
         )?;
@@ -177,6 +328,7 @@ impl Database {
                 timestamp: timestamp as i64,
                 latency_ms: row.get(4)?,
                 words: row.get(5)?,
+                speaker: row.get(6)?,
             })
         })?
         .collect::<std::result::Result<Vec<_>, _>>()?;
@@ -268,4 +420,75 @@ impl Database {
 
         Ok(())
     }
+
+    /// Compare two sessions' stats and vocabulary (deltas are b - a)
+    pub fn compare_sessions(&self, session_a: i64, session_b: i64) -> Result<SessionComparison> {
+        let conn = self.conn.lock().unwrap();
+
+        let (a, vocab_a) = Self::load_session_stats(&conn, session_a)?;
+        let (b, vocab_b) = Self::load_session_stats(&conn, session_b)?;
+
+        let shared = vocab_a.intersection(&vocab_b).count();
+        let union = vocab_a.union(&vocab_b).count();
+
+        Ok(SessionComparison {
+            session_a,
+            session_b,
+            wpm_delta: b.wpm - a.wpm,
+            average_latency_delta_ms: b.avg_latency_ms - a.avg_latency_ms,
+            median_latency_delta_ms: b.median_latency_ms - a.median_latency_ms,
+            p95_latency_delta_ms: b.p95_latency_ms - a.p95_latency_ms,
+            words_delta: b.words_dictated - a.words_dictated,
+            vocabulary_a: vocab_a.len(),
+            vocabulary_b: vocab_b.len(),
+            shared_vocabulary: shared,
+            vocabulary_overlap: if union > 0 { shared as f64 / union as f64 } else { 0.0 },
+            corrections_a: a.transformations_count,
+            corrections_b: b.transformations_count,
+        })
+    }
+
+    /// Load a session's stats and the distinct words of its stored transcriptions
+    fn load_session_stats(conn: &Connection, session_id: i64) -> Result<(SessionStats, HashSet<String>)> {
+        let stats = conn.query_row(
+            "SELECT
+                COALESCE(wpm, 0),
+                COALESCE(avg_latency_ms, 0),
+                COALESCE(median_latency_ms, 0),
+                COALESCE(p95_latency_ms, 0),
+                COALESCE(words_dictated, 0),
+                COALESCE(transformations_count, 0)
+             FROM sessions
+             WHERE id = ?1",
+            [session_id],
+            |row| {
+                Ok(SessionStats {
+                    wpm: row.get(0)?,
+                    avg_latency_ms: row.get(1)?,
+                    median_latency_ms: row.get(2)?,
+                    p95_latency_ms: row.get(3)?,
+                    words_dictated: row.get(4)?,
+                    transformations_count: row.get(5)?,
+                })
+            },
+        )
+        .optional()?
+        .with_context(|| format!("Session {} not found", session_id))?;
+
+        let mut stmt = conn.prepare(
+            "SELECT text FROM segments WHERE session_id = ?1 AND text IS NOT NULL"
+        )?;
+        let texts = stmt
+            .query_map([session_id], |row| row.get::<_, String>(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+
+        let vocabulary = texts
+            .iter()
+            .flat_map(|t| t.split_whitespace())
+            .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'').to_lowercase())
+            .filter(|w| !w.is_empty())
+            .collect();
+
+        Ok((stats, vocabulary))
+    }
 }
@@ -174,10 +174,26 @@ fn main() {
             commands::get_recent_sessions,
             commands::get_session_count,
             commands::get_session_details,
+            commands::get_session_prosody,
+            commands::get_practice_history,
             commands::search_transcriptions,
             commands::get_lifetime_stats,
+            commands::compare_sessions,
             commands::toggle_recording,
             commands::get_connection_status,
+            commands::use_alternative,
+            commands::read_back,
+            commands::get_transcription_history,
+            commands::copy_history_entry,
+            commands::get_recent_errors,
+            commands::merge_sessions,
+            commands::split_session,
+            commands::get_archived_sessions,
+            commands::get_trashed_sessions,
+            commands::archive_session,
+            commands::delete_session,
+            commands::restore_session,
+            commands::empty_trash,
             commands::reset_database,
             // Corrections commands
             commands::corrections::learn_correction,
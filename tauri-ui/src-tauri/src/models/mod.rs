@@ -12,6 +12,15 @@ pub struct SessionSummary {
     pub avg_latency_ms: f64,
 }
 
+/// Session in the trash, purged by the daemon at `purge_at` (Unix seconds)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashedSession {
+    #[serde(flatten)]
+    pub session: SessionSummary,
+    pub deleted_at: i64,
+    pub purge_at: i64,
+}
+
 /// Transcription record from database
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranscriptionRecord {
@@ -21,6 +30,34 @@ pub struct TranscriptionRecord {
     pub timestamp: i64,
     pub latency_ms: Option<f64>,
     pub words: i32,
+    /// Speaker number (1, 2, ...) when speakers were labelled
+    pub speaker: Option<i32>,
+}
+
+/// Prosody of one segment, for the pacing chart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProsodyPoint {
+    pub segment_id: i64,
+    pub timestamp: i64,
+    /// Pitch spread in semitones (None with too little voiced speech)
+    pub pitch_std_semitones: Option<f64>,
+    pub energy_dbfs: f64,
+    pub speaking_rate_wpm: f64,
+}
+
+/// One scored readalong run (pronunciation practice)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PracticeRecord {
+    pub id: i64,
+    pub started_at: i64,
+    pub ended_at: i64,
+    pub script_words: i64,
+    pub words_read: i64,
+    pub words_matched: i64,
+    /// Fraction of the words read said as written (0-1)
+    pub accuracy: f64,
+    /// Mean per-word score (0-100)
+    pub pronunciation_score: f64,
 }
 
 /// Lifetime statistics
@@ -39,6 +76,43 @@ pub struct LifetimeStats {
     pub lowest_latency_session: Option<i64>,
 }
 
+/// Comparison of two sessions (deltas are b - a)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionComparison {
+    pub session_a: i64,
+    pub session_b: i64,
+    pub wpm_delta: f64,
+    pub average_latency_delta_ms: f64,
+    pub median_latency_delta_ms: f64,
+    pub p95_latency_delta_ms: f64,
+    pub words_delta: i32,
+    pub vocabulary_a: usize,
+    pub vocabulary_b: usize,
+    pub shared_vocabulary: usize,
+    pub vocabulary_overlap: f64,
+    pub corrections_a: i32,
+    pub corrections_b: i32,
+}
+
+/// Recently typed transcription held in daemon memory (1 = most recent)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub n: usize,
+    pub text: String,
+    pub age_secs: u64,
+}
+
+/// Warning or error logged by the daemon, from `get_recent_errors`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorEvent {
+    /// Unix time in seconds
+    pub timestamp: f64,
+    /// "error" or "warn"
+    pub level: String,
+    pub target: String,
+    pub message: String,
+}
+
 /// Connection status response
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectionStatus {
@@ -93,6 +93,12 @@ pub enum MetricsEvent {
         #[serde(deserialize_with = "deserialize_flexible_number")]
         latency_ms: u64,
         words: i64,
+        /// Metrics database session and segment row (absent if the daemon
+        /// couldn't store the segment)
+        #[serde(default)]
+        session_id: Option<i64>,
+        #[serde(default)]
+        segment_id: Option<i64>,
     },
 
     /// Periodic metrics update
@@ -109,6 +115,33 @@ pub enum MetricsEvent {
         cpu_percent: f64,
         session_id: Option<i64>,
     },
+
+    /// Weekly summary digest written to the data directory
+    WeeklySummaryReady {
+        week: String,
+        path: String,
+        #[serde(deserialize_with = "deserialize_flexible_timestamp")]
+        timestamp: u64,
+    },
+
+    /// Live VAD tuning applied by the daemon (threshold / min silence / min speech)
+    VadConfigChanged {
+        threshold: f32,
+        min_silence: f32,
+        min_speech: f32,
+        persisted: bool,
+        #[serde(deserialize_with = "deserialize_flexible_timestamp")]
+        timestamp: u64,
+    },
+
+    /// Segment over its latency budget, with per-stage breakdown and budgets (ms)
+    LatencyWarning {
+        exceeded: Vec<String>,
+        breakdown: serde_json::Value,
+        budgets: serde_json::Value,
+        #[serde(deserialize_with = "deserialize_flexible_timestamp")]
+        timestamp: u64,
+    },
 }
 
 /// Unix socket connection manager for real-time metrics
@@ -255,6 +288,25 @@ impl MetricsSocket {
                     .emit("metrics-update", event)
                     .context("Failed to emit metrics-update")?;
             }
+
+            MetricsEvent::WeeklySummaryReady { week, path, .. } => {
+                info!("Weekly summary {} ready: {}", week, path);
+                app_handle
+                    .emit("weekly-summary-ready", event)
+                    .context("Failed to emit weekly-summary-ready")?;
+            }
+
+            MetricsEvent::VadConfigChanged { .. } => {
+                app_handle
+                    .emit("vad-config-changed", event)
+                    .context("Failed to emit vad-config-changed")?;
+            }
+
+            MetricsEvent::LatencyWarning { .. } => {
+                app_handle
+                    .emit("latency-warning", event)
+                    .context("Failed to emit latency-warning")?;
+            }
         }
 
         Ok(())
@@ -311,17 +363,20 @@ mod tests {
         }
 
         // Test transcription
-        let json = r#"{"type":"transcription","session_id":"test-123","text":"Hello world","timestamp":1234567890,"wpm":120.0,"latency_ms":100}"#;
+        let json = r#"{"type":"transcription","session_id":123,"segment_id":4567,"text":"Hello world","timestamp":1234567890,"wpm":120.0,"latency_ms":100,"words":2}"#;
         let event: MetricsEvent = serde_json::from_str(json).unwrap();
         match event {
             MetricsEvent::Transcription {
                 session_id,
+                segment_id,
                 text,
                 timestamp,
                 wpm,
                 latency_ms,
+                ..
             } => {
-                assert_eq!(session_id, "test-123");
+                assert_eq!(session_id, Some(123));
+                assert_eq!(segment_id, Some(4567));
                 assert_eq!(text, "Hello world");
                 assert_eq!(timestamp, 1234567890);
                 assert_eq!(wpm, 120.0);